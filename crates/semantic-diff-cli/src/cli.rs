@@ -2,8 +2,13 @@
 //!
 //! 提供命令行参数解析和用户交互功能
 
-use clap::{Parser, ValueEnum};
-use semantic_diff_core::{HighlightStyle, OutputFormat, Result, SemanticDiffError};
+use crate::config_file::FileConfig;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use semantic_diff_core::{
+    BuildConstraints, ExcludePatterns, HighlightStyle, OutputFormat, Result, SemanticDiffError,
+};
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 /// semantic-diff - 语义代码差异分析工具
@@ -19,12 +24,20 @@ use std::path::PathBuf;
     long_about = "semantic-diff analyzes Git commits and provides semantic context for code changes, including related type definitions, function dependencies, and complete code slices that are self-contained and compilable."
 )]
 pub struct Cli {
-    /// Git 提交哈希 (支持完整或短格式)
+    /// Git 提交哈希 (支持完整或短格式，`HEAD`、分支名等引用名，以及 `<base>..<head>` 范围)
     #[arg(
-        help = "Git commit hash to analyze (supports both full and short format)",
-        value_name = "COMMIT_HASH"
+        help = "Git commit to analyze: a full or abbreviated hash, a ref name such as HEAD, or a <base>..<head> range",
+        value_name = "COMMIT_HASH",
+        required_unless_present = "doctor"
     )]
-    pub commit_hash: String,
+    pub commit_hash: Option<String>,
+
+    /// 分析提交范围时折叠 fixup 提交
+    #[arg(
+        long = "autosquash",
+        help = "When COMMIT_HASH is a <base>..<head> range, fold fixup!/squash!/amend! commits into the commits they target"
+    )]
+    pub autosquash: bool,
 
     /// 输出格式
     #[arg(
@@ -90,6 +103,14 @@ pub struct Cli {
     )]
     pub output_file: Option<PathBuf>,
 
+    /// 统计报告文件
+    #[arg(
+        long = "stats-json",
+        value_name = "FILE",
+        help = "Write aggregate statistics (targets by kind, lines, files, per-target context stats) as JSON to FILE"
+    )]
+    pub stats_json: Option<PathBuf>,
+
     /// 只显示变更的函数
     #[arg(
         long = "functions-only",
@@ -112,6 +133,172 @@ pub struct Cli {
         help = "Include dependency graph in the output"
     )]
     pub show_dependencies: bool,
+
+    /// 标注来源文件
+    #[arg(
+        long = "annotate-files",
+        help = "Annotate the output with the source file wherever it changes"
+    )]
+    pub annotate_files: bool,
+
+    /// 按依赖图以树形渲染
+    #[arg(
+        long = "tree",
+        help = "Render the context as a tree nested along the dependency graph"
+    )]
+    pub tree: bool,
+
+    /// 左右两栏对照渲染
+    #[arg(
+        long = "side-by-side",
+        help = "Render each changed definition as a two-column before/after view (text format only)"
+    )]
+    pub side_by_side: bool,
+
+    /// 折叠未变更的上下文
+    #[arg(
+        long = "collapse-context",
+        value_name = "LINES",
+        help = "Keep LINES unchanged lines around each change and collapse longer unchanged runs",
+        value_parser = clap::value_parser!(usize)
+    )]
+    pub collapse_context: Option<usize>,
+
+    /// 无上下文模式
+    #[arg(
+        long = "no-context",
+        help = "Only output the changed definitions, skipping dependency resolution entirely"
+    )]
+    pub no_context: bool,
+
+    /// 标记缺少测试的导出函数
+    #[arg(
+        long = "flag-untested",
+        conflicts_with = "no_context",
+        help = "Flag changed exported functions that have no corresponding Test<Name> function"
+    )]
+    pub flag_untested: bool,
+
+    /// 标记显著增长的函数
+    #[arg(
+        long = "flag-growth",
+        value_name = "PERCENT",
+        help = "Flag changed functions whose line count grew by more than PERCENT percent"
+    )]
+    pub flag_growth: Option<f64>,
+
+    /// 标记新增的 panic/unsafe/reflect 用法
+    #[arg(
+        long = "flag-risks",
+        help = "Flag panic, unsafe and reflect usage introduced by added lines in changed targets"
+    )]
+    pub flag_risks: bool,
+
+    /// 标记结构体字段序列化标签的变化
+    #[arg(
+        long = "flag-tag-changes",
+        help = "Flag json/xml/yaml struct tag changes on types used by changed targets"
+    )]
+    pub flag_tag_changes: bool,
+
+    /// 建议版本升级级别
+    #[arg(
+        long = "suggest-version",
+        help = "Classify exported API changes and print the suggested semantic version bump on stderr"
+    )]
+    pub suggest_version: bool,
+
+    /// 共享依赖前言
+    #[arg(
+        long = "shared-preamble",
+        value_name = "SLICES",
+        help = "Emit dependencies shared by at least SLICES slices once in a shared preamble",
+        value_parser = clap::value_parser!(u32).range(2..)
+    )]
+    pub shared_preamble: Option<u32>,
+
+    /// 审查清单配置文件
+    #[arg(
+        long = "review-checklist",
+        value_name = "FILE",
+        help = "Inject review checklist items from a JSON file into each context header by change kind and signals"
+    )]
+    pub review_checklist: Option<PathBuf>,
+
+    /// 排除的文件模式
+    #[arg(
+        long = "exclude",
+        value_name = "GLOB",
+        help = "Skip changed files whose repository-relative path matches this glob (gitignore syntax, e.g. '*.pb.go' or 'vendor/**'); can be repeated"
+    )]
+    pub exclude: Vec<String>,
+
+    /// 包含的路径前缀
+    #[arg(
+        long = "path",
+        value_name = "PREFIX",
+        help = "Only analyze changed files whose repository-relative path starts with this prefix (e.g. 'internal/user'); can be repeated"
+    )]
+    pub paths: Vec<PathBuf>,
+
+    /// 并发线程数
+    #[arg(
+        long = "threads",
+        value_name = "N",
+        help = "Number of threads used to extract contexts (1 runs sequentially; defaults to one per CPU)",
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub threads: Option<u32>,
+
+    /// 解析缓存目录
+    #[arg(
+        long = "cache-dir",
+        value_name = "DIR",
+        help = "Directory where parse results are cached between runs; unchanged files are not re-extracted"
+    )]
+    pub cache_dir: Option<PathBuf>,
+
+    /// 进度显示
+    #[arg(
+        long = "progress",
+        help = "Show progress bars for file parsing and context extraction on stderr"
+    )]
+    pub progress: bool,
+
+    /// 目标操作系统
+    #[arg(
+        long = "goos",
+        value_name = "GOOS",
+        help = "Only analyze files whose build constraints match this GOOS (defaults to the host when --goarch is set)"
+    )]
+    pub goos: Option<String>,
+
+    /// 目标体系结构
+    #[arg(
+        long = "goarch",
+        value_name = "GOARCH",
+        help = "Only analyze files whose build constraints match this GOARCH (defaults to the host when --goos is set)"
+    )]
+    pub goarch: Option<String>,
+
+    /// 环境自检
+    #[arg(
+        long = "doctor",
+        help = "Check that the Go parser loads and the repository is valid, then exit"
+    )]
+    pub doctor: bool,
+
+    /// 配置文件
+    #[arg(
+        long = "config",
+        value_name = "FILE",
+        help = "Read options from this TOML file instead of the auto-discovered .semantic-diff.toml; command-line flags take precedence"
+    )]
+    pub config: Option<PathBuf>,
+
+    /// 在命令行上显式传入的参数 id，用于判断配置文件中的值是否被覆盖
+    #[arg(skip)]
+    pub explicit_args: HashSet<String>,
 }
 
 /// 输出格式命令行参数
@@ -126,6 +313,22 @@ pub enum OutputFormatArg {
     /// HTML 格式输出
     #[value(name = "html")]
     Html,
+    /// 统一差异格式输出（只包含变更目标范围内的差异）
+    #[value(name = "diff")]
+    UnifiedDiff,
+    /// JSON 格式输出（序列化完整的语义上下文）
+    #[value(name = "json")]
+    Json,
+    /// MessagePack 二进制格式输出（需要启用 msgpack 特性）
+    #[cfg(feature = "msgpack")]
+    #[value(name = "msgpack")]
+    MessagePack,
+    /// 变更概览表格（不生成代码切片）
+    #[value(name = "summary")]
+    Summary,
+    /// SARIF 2.1.0 文档，供 CI 在 PR 上标注变更的声明
+    #[value(name = "sarif")]
+    Sarif,
 }
 
 /// 高亮样式命令行参数
@@ -163,12 +366,54 @@ pub struct Config {
     pub verbose: bool,
     /// 输出文件路径
     pub output_file: Option<PathBuf>,
+    /// 统计报告 JSON 文件路径
+    pub stats_json: Option<PathBuf>,
     /// 是否只显示函数
     pub functions_only: bool,
     /// 最大输出行数
     pub max_lines: Option<u32>,
     /// 是否显示依赖图
     pub show_dependencies: bool,
+    /// 是否在输出中标注来源文件
+    pub annotate_files: bool,
+    /// 是否按依赖图以树形渲染上下文
+    pub tree: bool,
+    /// 是否以左右两栏对照渲染变更目标的前后版本
+    pub side_by_side: bool,
+    /// 每个变更前后保留的未变更行数，更长的未变更行段会被折叠
+    pub collapse_context: Option<usize>,
+    /// 是否跳过依赖解析，只输出变更目标
+    pub no_context: bool,
+    /// 是否标记缺少测试的导出函数
+    pub flag_untested: bool,
+    /// 函数行数增长的标记阈值（百分比）
+    pub flag_growth: Option<f64>,
+    /// 是否标记新增的 panic/unsafe/reflect 用法
+    pub flag_risks: bool,
+    /// 是否标记结构体字段序列化标签的变化
+    pub flag_tag_changes: bool,
+    /// 是否输出建议的语义化版本升级级别
+    pub suggest_version: bool,
+    /// 共享依赖前言的最少共享切片数
+    pub shared_preamble: Option<u32>,
+    /// 审查清单配置文件路径
+    pub review_checklist: Option<PathBuf>,
+    /// 构建约束的目标平台，未指定时分析所有文件
+    pub build_constraints: Option<BuildConstraints>,
+    /// 分析前排除的文件模式（匹配仓库相对路径）
+    pub exclude_patterns: ExcludePatterns,
+    /// 只分析仓库相对路径以这些前缀开头的文件，为空时不限制
+    pub include_paths: Vec<PathBuf>,
+    /// 并发提取上下文使用的线程数，未指定时使用全局线程池
+    pub concurrency: Option<usize>,
+    /// 解析缓存目录，未指定时不在运行之间保存解析结果
+    pub cache_dir: Option<PathBuf>,
+    /// 是否在标准错误输出上显示进度
+    pub progress: bool,
+    /// 分析 `<base>..<head>` 范围时是否折叠 fixup 提交
+    pub autosquash: bool,
+    /// 是否追加写入输出文件而非覆盖（逐个分析范围内的提交时使用）
+    pub append_output: bool,
 }
 
 impl From<OutputFormatArg> for OutputFormat {
@@ -177,6 +422,12 @@ impl From<OutputFormatArg> for OutputFormat {
             OutputFormatArg::PlainText => OutputFormat::PlainText,
             OutputFormatArg::Markdown => OutputFormat::Markdown,
             OutputFormatArg::Html => OutputFormat::Html,
+            OutputFormatArg::UnifiedDiff => OutputFormat::UnifiedDiff,
+            OutputFormatArg::Json => OutputFormat::Json,
+            #[cfg(feature = "msgpack")]
+            OutputFormatArg::MessagePack => OutputFormat::MessagePack,
+            OutputFormatArg::Summary => OutputFormat::Summary,
+            OutputFormatArg::Sarif => OutputFormat::Sarif,
         }
     }
 }
@@ -191,10 +442,12 @@ impl From<HighlightStyleArg> for HighlightStyle {
     }
 }
 
-impl From<Cli> for Config {
-    fn from(cli: Cli) -> Self {
-        Config {
-            commit_hash: cli.commit_hash,
+impl TryFrom<Cli> for Config {
+    type Error = SemanticDiffError;
+
+    fn try_from(cli: Cli) -> Result<Self> {
+        Ok(Config {
+            commit_hash: cli.commit_hash.unwrap_or_default(),
             output_format: cli.format.into(),
             include_comments: cli.include_comments,
             max_dependency_depth: cli.max_depth,
@@ -203,41 +456,109 @@ impl From<Cli> for Config {
             repo_path: cli.repo_path,
             verbose: cli.verbose,
             output_file: cli.output_file,
+            stats_json: cli.stats_json,
             functions_only: cli.functions_only,
             max_lines: cli.max_lines,
             show_dependencies: cli.show_dependencies,
-        }
+            annotate_files: cli.annotate_files,
+            tree: cli.tree,
+            side_by_side: cli.side_by_side,
+            collapse_context: cli.collapse_context,
+            no_context: cli.no_context,
+            flag_untested: cli.flag_untested,
+            flag_growth: cli.flag_growth,
+            flag_risks: cli.flag_risks,
+            flag_tag_changes: cli.flag_tag_changes,
+            suggest_version: cli.suggest_version,
+            shared_preamble: cli.shared_preamble,
+            review_checklist: cli.review_checklist,
+            build_constraints: if cli.goos.is_some() || cli.goarch.is_some() {
+                let host = BuildConstraints::host();
+                Some(BuildConstraints::new(
+                    cli.goos.unwrap_or(host.goos),
+                    cli.goarch.unwrap_or(host.goarch),
+                ))
+            } else {
+                None
+            },
+            exclude_patterns: ExcludePatterns::new(&cli.exclude)?,
+            include_paths: normalize_path_prefixes(cli.paths),
+            concurrency: cli.threads.map(|threads| threads as usize),
+            cache_dir: cli.cache_dir,
+            progress: cli.progress,
+            autosquash: cli.autosquash,
+            append_output: false,
+        })
     }
 }
 
+/// 去掉路径前缀开头的 `./`，使其能与仓库相对路径按组件比较
+pub fn normalize_path_prefixes(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    paths
+        .into_iter()
+        .map(|path| match path.strip_prefix(".") {
+            Ok(stripped) => stripped.to_path_buf(),
+            Err(_) => path,
+        })
+        .collect()
+}
+
 impl Cli {
     /// 解析命令行参数
     pub fn parse_args() -> Self {
-        Self::parse()
+        let matches = Self::command().get_matches();
+        let mut cli = Self::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        cli.explicit_args = matches
+            .ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+            .map(|id| id.to_string())
+            .collect();
+        cli
+    }
+
+    /// 合并配置文件并生成应用程序配置
+    ///
+    /// 优先使用 `--config` 指定的文件，否则从仓库路径逐级向上查找 `.semantic-diff.toml`；
+    /// 命令行显式传入的参数覆盖配置文件中的值
+    pub fn into_config(self) -> Result<Config> {
+        let config_path = match &self.config {
+            Some(path) => Some(path.clone()),
+            None => FileConfig::discover(&self.repo_path),
+        };
+        let file_config = config_path.as_deref().map(FileConfig::load).transpose()?;
+
+        let explicit_args = self.explicit_args.clone();
+        let mut config: Config = self.try_into()?;
+        if let Some(file_config) = file_config {
+            file_config.apply(&mut config, |id| explicit_args.contains(id))?;
+        }
+        Ok(config)
     }
 
     /// 验证参数的有效性
     pub fn validate(&self) -> Result<()> {
-        // 验证提交哈希
-        if self.commit_hash.is_empty() {
+        // 验证提交哈希；缩写哈希和引用名由 Git 解析，无法解析时由分析过程报告 RevisionNotFound
+        let commit_hash = self.commit_hash.as_deref().unwrap_or_default();
+        if commit_hash.is_empty() {
             return Err(SemanticDiffError::InvalidCommitHash(
                 "Commit hash cannot be empty".to_string(),
             ));
         }
 
-        // 验证提交哈希格式 (Git 哈希应该是 7-40 个十六进制字符)
-        if !self.commit_hash.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(SemanticDiffError::InvalidCommitHash(format!(
-                "Invalid commit hash format: {}",
-                self.commit_hash
-            )));
+        // autosquash 只对提交范围有意义
+        if self.autosquash && !commit_hash.contains("..") {
+            return Err(SemanticDiffError::ConfigError(
+                "--autosquash requires a <base>..<head> commit range".to_string(),
+            ));
         }
 
-        let hash_len = self.commit_hash.len();
-        if !(7..=40).contains(&hash_len) {
-            return Err(SemanticDiffError::InvalidCommitHash(format!(
-                "Commit hash length must be between 7 and 40 characters, got {hash_len}"
-            )));
+        // 验证增长阈值
+        if let Some(percent) = self.flag_growth {
+            if !percent.is_finite() || percent < 0.0 {
+                return Err(SemanticDiffError::ConfigError(format!(
+                    "Growth threshold must be a non-negative percentage, got {percent}"
+                )));
+            }
         }
 
         // 验证仓库路径
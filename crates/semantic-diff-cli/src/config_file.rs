@@ -0,0 +1,210 @@
+//! 配置文件模块
+//!
+//! 从 `.semantic-diff.toml` 读取常用选项，避免每次运行都重复传入大量参数。
+//! 配置文件中的键与 [`Config`] 字段同名，命令行显式传入的参数优先于文件中的值
+
+use crate::cli::{Config, HighlightStyleArg, OutputFormatArg, normalize_path_prefixes};
+use clap::ValueEnum;
+use semantic_diff_core::{ExcludePatterns, Result, SemanticDiffError};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// 自动发现的配置文件名
+pub const CONFIG_FILE_NAME: &str = ".semantic-diff.toml";
+
+/// 配置文件内容
+///
+/// 所有字段都是可选的，未出现的键保留命令行的默认值；未知的键会被拒绝，
+/// 以免拼写错误的选项被悄悄忽略
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    /// 输出格式（与 `--format` 的取值相同）
+    pub output_format: Option<String>,
+    /// 是否包含注释
+    pub include_comments: Option<bool>,
+    /// 最大依赖深度
+    pub max_dependency_depth: Option<u32>,
+    /// 是否排除测试文件
+    pub exclude_test_files: Option<bool>,
+    /// 高亮样式（与 `--highlight` 的取值相同）
+    pub highlight_style: Option<String>,
+    /// 是否只显示函数
+    pub functions_only: Option<bool>,
+    /// 最大输出行数
+    pub max_lines: Option<u32>,
+    /// 是否显示依赖图
+    pub show_dependencies: Option<bool>,
+    /// 是否在输出中标注来源文件
+    pub annotate_files: Option<bool>,
+    /// 是否按依赖图以树形渲染上下文
+    pub tree: Option<bool>,
+    /// 是否以左右两栏对照渲染
+    pub side_by_side: Option<bool>,
+    /// 每个变更前后保留的未变更行数
+    pub collapse_context: Option<usize>,
+    /// 是否跳过依赖解析
+    pub no_context: Option<bool>,
+    /// 是否标记缺少测试的导出函数
+    pub flag_untested: Option<bool>,
+    /// 函数行数增长的标记阈值（百分比）
+    pub flag_growth: Option<f64>,
+    /// 是否标记新增的 panic/unsafe/reflect 用法
+    pub flag_risks: Option<bool>,
+    /// 是否标记结构体字段序列化标签的变化
+    pub flag_tag_changes: Option<bool>,
+    /// 是否输出建议的语义化版本升级级别
+    pub suggest_version: Option<bool>,
+    /// 共享依赖前言的最少共享切片数
+    pub shared_preamble: Option<u32>,
+    /// 审查清单配置文件路径（相对路径以配置文件所在目录为基准）
+    pub review_checklist: Option<PathBuf>,
+    /// 分析前排除的文件模式
+    pub exclude_patterns: Option<Vec<String>>,
+    /// 只分析以这些前缀开头的仓库相对路径
+    pub include_paths: Option<Vec<PathBuf>>,
+    /// 并发提取上下文使用的线程数
+    pub concurrency: Option<usize>,
+    /// 是否在标准错误输出上显示进度
+    pub progress: Option<bool>,
+}
+
+impl FileConfig {
+    /// 从指定路径加载配置文件
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            SemanticDiffError::ConfigError(format!(
+                "Failed to read config file {}: {e}",
+                path.display()
+            ))
+        })?;
+        let mut file_config: FileConfig = toml::from_str(&content).map_err(|e| {
+            SemanticDiffError::ConfigError(format!("Invalid config file {}: {e}", path.display()))
+        })?;
+
+        if let (Some(checklist), Some(base)) = (&file_config.review_checklist, path.parent()) {
+            file_config.review_checklist = Some(base.join(checklist));
+        }
+
+        Ok(file_config)
+    }
+
+    /// 从 `start` 开始逐级向上查找配置文件
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        let start = start.canonicalize().unwrap_or_else(|_| start.to_path_buf());
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// 将配置文件中的值写入 `config`
+    ///
+    /// `explicit` 判断某个命令行参数（按参数 id）是否由用户显式传入，
+    /// 显式传入的参数保留命令行的值
+    pub fn apply(self, config: &mut Config, explicit: impl Fn(&str) -> bool) -> Result<()> {
+        let pick = |id: &str| !explicit(id);
+
+        if let Some(format) = self.output_format.filter(|_| pick("format")) {
+            config.output_format = OutputFormatArg::from_str(&format, true)
+                .map_err(|e| SemanticDiffError::ConfigError(format!("Invalid output_format: {e}")))?
+                .into();
+        }
+        if let Some(style) = self.highlight_style.filter(|_| pick("highlight")) {
+            config.highlight_style = HighlightStyleArg::from_str(&style, true)
+                .map_err(|e| {
+                    SemanticDiffError::ConfigError(format!("Invalid highlight_style: {e}"))
+                })?
+                .into();
+        }
+        if let Some(depth) = self.max_dependency_depth.filter(|_| pick("max_depth")) {
+            if !(1..=10).contains(&depth) {
+                return Err(SemanticDiffError::ConfigError(format!(
+                    "max_dependency_depth must be between 1 and 10, got {depth}"
+                )));
+            }
+            config.max_dependency_depth = depth;
+        }
+        if let Some(percent) = self.flag_growth.filter(|_| pick("flag_growth")) {
+            if !percent.is_finite() || percent < 0.0 {
+                return Err(SemanticDiffError::ConfigError(format!(
+                    "flag_growth must be a non-negative percentage, got {percent}"
+                )));
+            }
+            config.flag_growth = Some(percent);
+        }
+        if let Some(slices) = self.shared_preamble.filter(|_| pick("shared_preamble")) {
+            if slices < 2 {
+                return Err(SemanticDiffError::ConfigError(format!(
+                    "shared_preamble must be at least 2, got {slices}"
+                )));
+            }
+            config.shared_preamble = Some(slices);
+        }
+        if let Some(threads) = self.concurrency.filter(|_| pick("threads")) {
+            if threads == 0 {
+                return Err(SemanticDiffError::ConfigError(
+                    "concurrency must be at least 1".to_string(),
+                ));
+            }
+            config.concurrency = Some(threads);
+        }
+        if let Some(patterns) = self.exclude_patterns.filter(|_| pick("exclude")) {
+            config.exclude_patterns = ExcludePatterns::new(&patterns)?;
+        }
+        if let Some(paths) = self.include_paths.filter(|_| pick("paths")) {
+            config.include_paths = normalize_path_prefixes(paths);
+        }
+
+        if let Some(value) = self.include_comments.filter(|_| pick("include_comments")) {
+            config.include_comments = value;
+        }
+        if let Some(value) = self.exclude_test_files.filter(|_| pick("exclude_tests")) {
+            config.exclude_test_files = value;
+        }
+        if let Some(value) = self.functions_only.filter(|_| pick("functions_only")) {
+            config.functions_only = value;
+        }
+        if let Some(value) = self.max_lines.filter(|_| pick("max_lines")) {
+            config.max_lines = Some(value);
+        }
+        if let Some(value) = self.show_dependencies.filter(|_| pick("show_dependencies")) {
+            config.show_dependencies = value;
+        }
+        if let Some(value) = self.annotate_files.filter(|_| pick("annotate_files")) {
+            config.annotate_files = value;
+        }
+        if let Some(value) = self.tree.filter(|_| pick("tree")) {
+            config.tree = value;
+        }
+        if let Some(value) = self.side_by_side.filter(|_| pick("side_by_side")) {
+            config.side_by_side = value;
+        }
+        if let Some(value) = self.collapse_context.filter(|_| pick("collapse_context")) {
+            config.collapse_context = Some(value);
+        }
+        if let Some(value) = self.no_context.filter(|_| pick("no_context")) {
+            config.no_context = value;
+        }
+        if let Some(value) = self.flag_untested.filter(|_| pick("flag_untested")) {
+            config.flag_untested = value;
+        }
+        if let Some(value) = self.flag_risks.filter(|_| pick("flag_risks")) {
+            config.flag_risks = value;
+        }
+        if let Some(value) = self.flag_tag_changes.filter(|_| pick("flag_tag_changes")) {
+            config.flag_tag_changes = value;
+        }
+        if let Some(value) = self.suggest_version.filter(|_| pick("suggest_version")) {
+            config.suggest_version = value;
+        }
+        if let Some(value) = self.review_checklist.filter(|_| pick("review_checklist")) {
+            config.review_checklist = Some(value);
+        }
+        if let Some(value) = self.progress.filter(|_| pick("progress")) {
+            config.progress = value;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+//! 环境自检模块
+//!
+//! 在正式分析之前检查解析器和 Git 仓库是否可用，便于排查 CI 环境问题
+
+use semantic_diff_core::{GitDiffParser, ParserFactory, SupportedLanguage};
+use std::fmt;
+use std::path::Path;
+
+/// 单项检查结果
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    /// 检查项名称
+    pub name: &'static str,
+    /// 是否通过
+    pub passed: bool,
+    /// 详细信息（成功说明或失败原因）
+    pub detail: String,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Default)]
+pub struct SelfCheckReport {
+    /// 所有检查项的结果
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelfCheckReport {
+    /// 是否所有检查项都通过
+    pub fn all_passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+
+    fn record(&mut self, name: &'static str, outcome: Result<String, String>) {
+        let (passed, detail) = match outcome {
+            Ok(detail) => (true, detail),
+            Err(detail) => (false, detail),
+        };
+        self.checks.push(CheckResult {
+            name,
+            passed,
+            detail,
+        });
+    }
+}
+
+impl fmt::Display for SelfCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "semantic-diff self-check")?;
+        for check in &self.checks {
+            let status = if check.passed { "ok" } else { "FAIL" };
+            writeln!(f, "  [{status:>4}] {}: {}", check.name, check.detail)?;
+        }
+        let summary = if self.all_passed() {
+            "all checks passed"
+        } else {
+            "some checks failed"
+        };
+        write!(f, "Result: {summary}")
+    }
+}
+
+/// 运行环境自检
+///
+/// 依次检查 Go 语法是否可以加载、简单源码能否解析、目标路径是否为有效的 Git 仓库
+pub fn run_self_check(repo_path: &Path) -> SelfCheckReport {
+    let mut report = SelfCheckReport::default();
+
+    let parser = ParserFactory::create_parser(SupportedLanguage::Go);
+    report.record(
+        "tree-sitter Go grammar",
+        parser
+            .as_ref()
+            .map(|parser| format!("loaded {} parser", parser.language_name()))
+            .map_err(|e| e.to_string()),
+    );
+
+    let parse_outcome = match parser {
+        Ok(mut parser) => match parser.parse_source("package main\n\nfunc main() {}\n") {
+            Ok(tree) if !tree.root_node().has_error() => Ok("trivial source parsed".to_string()),
+            Ok(_) => Err("trivial source parsed with syntax errors".to_string()),
+            Err(e) => Err(e.to_string()),
+        },
+        Err(_) => Err("skipped: parser unavailable".to_string()),
+    };
+    report.record("trivial parse", parse_outcome);
+
+    let repo_outcome = if !repo_path.exists() {
+        Err(format!("path does not exist: {}", repo_path.display()))
+    } else {
+        GitDiffParser::new(repo_path.to_path_buf())
+            .map(|_| format!("opened {}", repo_path.display()))
+            .map_err(|e| e.to_string())
+    };
+    report.record("git repository", repo_outcome);
+
+    report
+}
@@ -4,11 +4,16 @@
 //! 能够提供比传统 git diff 更丰富的语义上下文信息。
 
 mod cli;
+mod config_file;
+mod doctor;
 
 use cli::{Cli, Config};
-use semantic_diff_core::Result;
+use semantic_diff_core::{Diagnostics, ErrorRecoveryStrategy, ParserCache, ProgressEvent, Result};
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use tracing::{debug, info, warn};
 use tracing_subscriber::{EnvFilter, fmt};
 
@@ -19,6 +24,13 @@ fn main() {
     // 初始化日志记录 (根据 verbose 标志调整日志级别)
     init_logging(cli.verbose);
 
+    // 环境自检模式
+    if cli.doctor {
+        let report = doctor::run_self_check(&cli.repo_path);
+        println!("{report}");
+        std::process::exit(if report.all_passed() { 0 } else { 1 });
+    }
+
     // 验证参数
     if let Err(e) = cli.validate() {
         eprintln!("Error: {e}");
@@ -26,7 +38,13 @@ fn main() {
         std::process::exit(1);
     }
 
-    let config: Config = cli.into();
+    let config: Config = match cli.into_config() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    };
 
     if config.verbose {
         info!("Starting semantic-diff analysis");
@@ -69,8 +87,59 @@ fn init_logging(verbose: bool) {
 
 /// 主要应用逻辑
 fn run(config: Config) -> Result<()> {
+    let mut diagnostics = Diagnostics::new();
+    let parser_cache = config
+        .cache_dir
+        .as_ref()
+        .map(ParserCache::load_from_disk)
+        .transpose()?;
+    let result = run_analysis(
+        &config,
+        parser_cache.as_ref(),
+        &ErrorRecoveryStrategy::new(),
+        &mut diagnostics,
+    );
+    report_diagnostics(&diagnostics);
+
+    // 保存本次运行的解析结果，写入失败不影响分析结果
+    if let Some(parser_cache) = &parser_cache {
+        let stats = parser_cache.get_stats();
+        debug!(
+            "Parse cache: {} disk hits, {} memory hits, {} misses",
+            stats.disk_hits, stats.memory_hits, stats.parse_misses
+        );
+        if let Err(e) = parser_cache.persist_to_disk() {
+            warn!("Failed to persist parse cache: {}", e);
+        }
+    }
+
+    result
+}
+
+/// 在标准错误输出上列出分析中跳过的文件和变更目标
+fn report_diagnostics(diagnostics: &Diagnostics) {
+    if diagnostics.is_empty() {
+        return;
+    }
+
+    eprintln!(
+        "Warning: {} item(s) were skipped during analysis:",
+        diagnostics.len()
+    );
+    for diagnostic in diagnostics.entries() {
+        eprintln!("  - {diagnostic}");
+    }
+}
+
+/// 执行一次完整的分析，可恢复的错误记录到 `diagnostics`
+fn run_analysis(
+    config: &Config,
+    parser_cache: Option<&ParserCache>,
+    recovery: &ErrorRecoveryStrategy,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
     // 验证和加载配置
-    validate_and_load_config(&config)?;
+    validate_and_load_config(config)?;
 
     info!(
         "Analyzing commit {} in repository: {}",
@@ -82,14 +151,79 @@ fn run(config: Config) -> Result<()> {
     debug!("Initializing Git diff parser");
     let git_parser = semantic_diff_core::GitDiffParser::new(config.repo_path.clone())?;
 
+    // 提交范围中的每个提交分别分析
+    if config.commit_hash.contains("..") {
+        return run_range_analysis(config, &git_parser, parser_cache, recovery, diagnostics);
+    }
+
     // 2. 解析提交差异
     debug!("Parsing commit diff for: {}", config.commit_hash);
     let file_changes = git_parser.parse_commit(&config.commit_hash)?;
 
+    analyze_commit(
+        config,
+        &git_parser,
+        file_changes,
+        parser_cache,
+        recovery,
+        diagnostics,
+    )
+}
+
+/// 逐个分析 `<base>..<head>` 范围内的提交，各提交的输出依次追加
+fn run_range_analysis(
+    config: &Config,
+    git_parser: &semantic_diff_core::GitDiffParser,
+    parser_cache: Option<&ParserCache>,
+    recovery: &ErrorRecoveryStrategy,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
+    debug!("Parsing commit range: {}", config.commit_hash);
+    let commits = git_parser.parse_range(&config.commit_hash, config.autosquash)?;
+    info!("Found {} commits in range", commits.len());
+
+    // 输出文件只在开始时清空一次，之后每个提交追加写入
+    if let Some(output_file) = &config.output_file {
+        std::fs::write(output_file, b"")?;
+    }
+
+    for commit in commits {
+        eprintln!("==> {} {}", commit.commit_hash, commit.summary);
+        for folded in &commit.folded_commits {
+            eprintln!("    folded {folded}");
+        }
+
+        let commit_config = Config {
+            commit_hash: commit.commit_hash,
+            append_output: true,
+            ..config.clone()
+        };
+        analyze_commit(
+            &commit_config,
+            git_parser,
+            commit.changes,
+            parser_cache,
+            recovery,
+            diagnostics,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// 分析单个提交的文件变更并输出结果
+fn analyze_commit(
+    config: &Config,
+    git_parser: &semantic_diff_core::GitDiffParser,
+    file_changes: Vec<semantic_diff_core::FileChange>,
+    parser_cache: Option<&ParserCache>,
+    recovery: &ErrorRecoveryStrategy,
+    diagnostics: &mut Diagnostics,
+) -> Result<()> {
     if file_changes.is_empty() {
         info!("No file changes found in commit {}", config.commit_hash);
         let output = "No changes found in the specified commit.\n";
-        write_output(output, &config)?;
+        write_output(output, config)?;
         return Ok(());
     }
 
@@ -97,12 +231,17 @@ fn run(config: Config) -> Result<()> {
 
     // 3. 分析变更的文件
     debug!("Analyzing changed files");
-    let analyzed_files = analyze_changed_files(&file_changes, &config)?;
+    let mut analyzed_files =
+        analyze_changed_files(&file_changes, config, parser_cache, recovery, diagnostics)?;
 
-    if analyzed_files.is_empty() {
+    // 删除的文件只存在于父提交中，其中的声明全部作为删除的声明报告
+    debug!("Loading deleted files");
+    let deleted_files = load_deleted_files(git_parser, &file_changes, config);
+
+    if analyzed_files.is_empty() && deleted_files.is_empty() {
         info!("No supported files found in changes");
         let output = "No supported source files found in the changes.\n";
-        write_output(output, &config)?;
+        write_output(output, config)?;
         return Ok(());
     }
 
@@ -111,89 +250,285 @@ fn run(config: Config) -> Result<()> {
         analyzed_files.len()
     );
 
+    // 读取并解析变更前的文件版本，供重命名和增长检测共用
+    debug!("Loading previous versions of changed files");
+    let previous_versions =
+        load_previous_versions(git_parser, &file_changes, &analyzed_files, config);
+
     // 4. 查找变更的函数和其他目标
     debug!("Finding changed targets");
-    let change_targets = find_change_targets(&file_changes, &analyzed_files, &config)?;
+    let change_targets = find_change_targets(
+        &file_changes,
+        &analyzed_files,
+        &previous_versions,
+        &deleted_files,
+        config,
+    )?;
+    let grown_functions = match config.flag_growth {
+        Some(threshold) => find_grown_functions(&analyzed_files, &previous_versions, threshold),
+        None => Vec::new(),
+    };
+    let tag_changes = if config.flag_tag_changes {
+        find_tag_changes(&analyzed_files, &previous_versions)
+    } else {
+        Vec::new()
+    };
+    if config.suggest_version {
+        let bump = suggest_version_bump(&analyzed_files, &previous_versions);
+        eprintln!("Suggested version bump: {bump}");
+    }
 
     if change_targets.is_empty() {
         info!("No change targets found");
         let output = "No functions or types were changed in the specified commit.\n";
-        write_output(output, &config)?;
+        write_output(output, config)?;
         return Ok(());
     }
 
     info!("Found {} change targets", change_targets.len());
 
-    // 5. 初始化语义上下文提取器
-    debug!("Initializing semantic context extractor");
-    let context_extractor = create_context_extractor(&config)?;
+    // JSON、MessagePack 和概览输出直接由语义上下文生成，不生成代码切片
+    if config.output_format.serializes_contexts() {
+        let mut semantic_contexts = if config.no_context {
+            change_targets
+                .iter()
+                .cloned()
+                .map(semantic_diff_core::SemanticContext::new)
+                .collect()
+        } else {
+            if config.flag_untested {
+                let test_files = load_sibling_test_files(&analyzed_files);
+                analyzed_files.extend(test_files);
+            }
+            let context_extractor = create_context_extractor(config)?;
+            extract_semantic_contexts(
+                &change_targets,
+                &analyzed_files,
+                &context_extractor,
+                recovery,
+                diagnostics,
+            )?
+        };
+        apply_function_growth(&mut semantic_contexts, &grown_functions);
+        apply_tag_changes(&mut semantic_contexts, &tag_changes);
+        if config.flag_risks {
+            apply_risk_signals(
+                &mut semantic_contexts,
+                &analyzed_files,
+                &file_changes,
+                &config.repo_path,
+            );
+        }
+        return output_serialized_contexts(&semantic_contexts, config);
+    }
 
-    // 6. 提取语义上下文
-    debug!("Extracting semantic context");
-    let semantic_contexts = extract_semantic_contexts(
-        &change_targets,
-        &analyzed_files,
-        &context_extractor,
-        &config,
-    )?;
+    // 5. 初始化代码生成器
+    debug!("Initializing code slice generator");
+    let code_generator = create_code_generator(config)?;
+
+    let mut semantic_contexts = Vec::new();
+    let mut code_slices = if config.no_context {
+        // 无上下文模式：跳过依赖解析，只输出变更目标本身
+        debug!("Generating target-only code slices");
+        generate_target_only_slices(
+            &change_targets,
+            &file_changes,
+            &code_generator,
+            &config.repo_path,
+            diagnostics,
+        )?
+    } else {
+        // 检查测试覆盖时需要把同目录的测试文件加入源文件池
+        if config.flag_untested {
+            debug!("Loading test files for untested-function detection");
+            let test_files = load_sibling_test_files(&analyzed_files);
+            info!("Loaded {} test files for lookup", test_files.len());
+            analyzed_files.extend(test_files);
+        }
 
-    info!(
-        "Extracted semantic context for {} targets",
-        semantic_contexts.len()
-    );
+        // 6. 初始化语义上下文提取器
+        debug!("Initializing semantic context extractor");
+        let context_extractor = create_context_extractor(config)?;
+
+        // 7. 提取语义上下文
+        debug!("Extracting semantic context");
+        semantic_contexts = extract_semantic_contexts(
+            &change_targets,
+            &analyzed_files,
+            &context_extractor,
+            recovery,
+            diagnostics,
+        )?;
+        apply_function_growth(&mut semantic_contexts, &grown_functions);
+        apply_tag_changes(&mut semantic_contexts, &tag_changes);
+        if config.flag_risks {
+            apply_risk_signals(
+                &mut semantic_contexts,
+                &analyzed_files,
+                &file_changes,
+                &config.repo_path,
+            );
+        }
 
-    // 7. 初始化代码生成器
-    debug!("Initializing code slice generator");
-    let code_generator = create_code_generator(&config);
+        info!(
+            "Extracted semantic context for {} targets",
+            semantic_contexts.len()
+        );
 
-    // 8. 生成代码切片
-    debug!("Generating code slices");
-    let code_slices = generate_code_slices(&semantic_contexts, &file_changes, &code_generator)?;
+        // 多个切片共享的依赖移入前言，只输出一次
+        let preamble = match config.shared_preamble {
+            Some(min_shared) => code_generator
+                .hoist_shared_dependencies(&mut semantic_contexts, min_shared as usize)?,
+            None => None,
+        };
+
+        // 8. 生成代码切片
+        debug!("Generating code slices");
+        let code_slices = generate_code_slices(
+            &semantic_contexts,
+            &file_changes,
+            &code_generator,
+            &config.repo_path,
+            diagnostics,
+        )?;
+        preamble.into_iter().chain(code_slices).collect()
+    };
 
     info!("Generated {} code slices", code_slices.len());
 
+    if config.side_by_side {
+        debug!("Attaching previous versions for side-by-side rendering");
+        attach_target_versions(&mut code_slices, &analyzed_files, &previous_versions);
+    }
+
+    if let Some(stats_path) = &config.stats_json {
+        write_stats_report(stats_path, &code_slices, &semantic_contexts)?;
+    }
+
     // 9. 格式化并输出结果
     debug!("Formatting and outputting results");
-    format_and_output(&code_slices, &config)?;
+    format_and_output(&code_slices, config)?;
 
     info!("Analysis completed successfully");
     Ok(())
 }
 
+/// 将统计报告写入单独的 JSON 文件，与主输出互不影响
+fn write_stats_report(
+    path: &Path,
+    code_slices: &[semantic_diff_core::CodeSlice],
+    semantic_contexts: &[semantic_diff_core::SemanticContext],
+) -> Result<()> {
+    use semantic_diff_core::generator::stats::StatsReport;
+
+    let report = StatsReport::new(code_slices, semantic_contexts);
+    debug!("Writing statistics report to: {}", path.display());
+    std::fs::write(path, report.to_json()? + "\n")?;
+    Ok(())
+}
+
 /// 将输出写入文件或标准输出
 fn write_output(content: &str, config: &Config) -> Result<()> {
+    write_output_bytes(content.as_bytes(), config)
+}
+
+/// 将原始字节写入文件或标准输出
+fn write_output_bytes(content: &[u8], config: &Config) -> Result<()> {
     match &config.output_file {
         Some(file_path) => {
             debug!("Writing output to file: {}", file_path.display());
-            std::fs::write(file_path, content)?;
+            if config.append_output {
+                std::fs::OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(file_path)?
+                    .write_all(content)?;
+            } else {
+                std::fs::write(file_path, content)?;
+            }
             if !config.verbose {
                 eprintln!("Output written to: {}", file_path.display());
             }
         }
         None => {
-            print!("{content}");
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(content)?;
+            stdout.flush()?;
         }
     }
     Ok(())
 }
 
+/// 将语义上下文按输出格式序列化为 JSON、MessagePack 或概览表格
+fn output_serialized_contexts(
+    semantic_contexts: &[semantic_diff_core::SemanticContext],
+    config: &Config,
+) -> Result<()> {
+    use semantic_diff_core::formatter::{FormatterConfig, OutputRenderer};
+
+    if config.stats_json.is_some() {
+        warn!(
+            "--stats-json is ignored for {:?} output, which does not render code slices",
+            config.output_format
+        );
+    }
+
+    info!(
+        "Serializing {} semantic contexts as {:?}",
+        semantic_contexts.len(),
+        config.output_format
+    );
+    let renderer = OutputRenderer::new(FormatterConfig {
+        output_format: config.output_format.clone(),
+        include_dependency_graph: config.show_dependencies,
+        ..Default::default()
+    });
+    let bytes = match config.output_format {
+        semantic_diff_core::OutputFormat::MessagePack => {
+            renderer.render_contexts_msgpack(semantic_contexts)?
+        }
+        semantic_diff_core::OutputFormat::Summary => renderer
+            .render_contexts_summary(semantic_contexts)
+            .into_bytes(),
+        _ => {
+            let mut json = renderer.render_contexts_json(semantic_contexts)?;
+            json.push('\n');
+            json.into_bytes()
+        }
+    };
+    write_output_bytes(&bytes, config)
+}
+
 /// 分析变更的文件
+///
+/// 无法分析的文件交给 `recovery` 处理，可恢复的错误记录到 `diagnostics` 后跳过该文件；
+/// 提供 `parser_cache` 时内容未变化的文件复用缓存中的声明
 fn analyze_changed_files(
     file_changes: &[semantic_diff_core::FileChange],
     config: &Config,
+    parser_cache: Option<&ParserCache>,
+    recovery: &ErrorRecoveryStrategy,
+    diagnostics: &mut Diagnostics,
 ) -> Result<Vec<semantic_diff_core::SourceFile>> {
     use semantic_diff_core::analyzer::SourceAnalyzer;
 
     let mut analyzed_files = Vec::new();
 
-    for file_change in file_changes {
+    for (index, file_change) in file_changes.iter().enumerate() {
+        if config.progress {
+            render_progress(ProgressEvent::FileParsed {
+                completed: index,
+                total: file_changes.len(),
+            });
+        }
+
         // 跳过二进制文件
         if file_change.is_binary {
             debug!("Skipping binary file: {:?}", file_change.file_path);
             continue;
         }
 
-        // 跳过删除的文件
+        // 删除的文件由 load_deleted_files 从父提交中加载
         if matches!(
             file_change.change_type,
             semantic_diff_core::ChangeType::Deleted
@@ -202,9 +537,7 @@ fn analyze_changed_files(
             continue;
         }
 
-        // 检查是否应该排除测试文件
-        if config.exclude_test_files && is_test_file(&file_change.file_path) {
-            debug!("Skipping test file: {:?}", file_change.file_path);
+        if is_excluded_file(file_change, config) {
             continue;
         }
 
@@ -220,15 +553,31 @@ fn analyze_changed_files(
         // 尝试分析文件
         match SourceAnalyzer::new_for_file(&full_path) {
             Ok(mut analyzer) => {
-                match analyzer.analyze_file(&full_path) {
+                let analyzed = match parser_cache {
+                    Some(cache) => analyzer.analyze_file_with_cache(&full_path, cache),
+                    None => analyzer.analyze_file(&full_path),
+                };
+                match analyzed {
                     Ok(source_file) => {
+                        // 不满足目标平台构建约束的文件不参与分析，避免引入其他平台的依赖
+                        if let Some(constraints) = &config.build_constraints {
+                            if !constraints.matches(&source_file) {
+                                debug!(
+                                    "Skipping file excluded by build constraints: {:?}",
+                                    file_change.file_path
+                                );
+                                continue;
+                            }
+                        }
                         debug!("Successfully analyzed file: {:?}", file_change.file_path);
                         analyzed_files.push(source_file);
                     }
-                    Err(e) => {
-                        warn!("Failed to analyze file {:?}: {}", file_change.file_path, e);
-                        // 继续处理其他文件
-                    }
+                    Err(e) => recovery.recover(
+                        e,
+                        Some(file_change.file_path.as_path()),
+                        None,
+                        diagnostics,
+                    )?,
                 }
             }
             Err(e) => {
@@ -238,9 +587,198 @@ fn analyze_changed_files(
         }
     }
 
+    if config.progress && !file_changes.is_empty() {
+        render_progress(ProgressEvent::FileParsed {
+            completed: file_changes.len(),
+            total: file_changes.len(),
+        });
+    }
+
     Ok(analyzed_files)
 }
 
+/// 检查文件是否被测试文件、排除模式或包含路径的配置排除
+fn is_excluded_file(file_change: &semantic_diff_core::FileChange, config: &Config) -> bool {
+    // 检查是否应该排除测试文件
+    if config.exclude_test_files && is_test_file(&file_change.file_path) {
+        debug!("Skipping test file: {:?}", file_change.file_path);
+        return true;
+    }
+
+    // 检查是否匹配排除模式
+    if let Some(pattern) = config
+        .exclude_patterns
+        .matching_pattern(&file_change.file_path)
+    {
+        debug!(
+            "Skipping file excluded by pattern '{}': {:?}",
+            pattern, file_change.file_path
+        );
+        return true;
+    }
+
+    // 检查是否位于包含的路径前缀下
+    if !is_included_path(&file_change.file_path, &config.include_paths) {
+        debug!(
+            "Skipping file outside included paths: {:?}",
+            file_change.file_path
+        );
+        return true;
+    }
+
+    false
+}
+
+/// 读取并解析被删除文件在父提交中的版本
+///
+/// 删除的文件不在工作区中，只能从 Git 历史中获取；无法读取或解析的文件记录到调试日志后跳过
+fn load_deleted_files(
+    git_parser: &semantic_diff_core::GitDiffParser,
+    file_changes: &[semantic_diff_core::FileChange],
+    config: &Config,
+) -> Vec<semantic_diff_core::SourceFile> {
+    use semantic_diff_core::analyzer::SourceAnalyzer;
+
+    let mut deleted_files = Vec::new();
+
+    for file_change in file_changes {
+        if file_change.is_binary
+            || !matches!(
+                file_change.change_type,
+                semantic_diff_core::ChangeType::Deleted
+            )
+            || is_excluded_file(file_change, config)
+        {
+            continue;
+        }
+
+        let full_path = config.repo_path.join(&file_change.file_path);
+        let Ok(mut analyzer) = SourceAnalyzer::new_for_file(&full_path) else {
+            debug!("Unsupported deleted file: {:?}", file_change.file_path);
+            continue;
+        };
+
+        let old_content = match git_parser.get_old_file_content(&config.commit_hash, file_change) {
+            Ok(Some(content)) => content,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!(
+                    "Failed to read deleted file {:?}: {}",
+                    file_change.file_path, e
+                );
+                continue;
+            }
+        };
+
+        match analyzer.analyze_source(&full_path, old_content) {
+            Ok(old_file) => {
+                // 与变更的文件一样，不满足目标平台构建约束的删除文件不参与分析
+                let excluded = config
+                    .build_constraints
+                    .as_ref()
+                    .is_some_and(|constraints| !constraints.matches(&old_file));
+                if !excluded {
+                    deleted_files.push(old_file);
+                }
+            }
+            Err(e) => debug!(
+                "Failed to analyze deleted file {:?}: {}",
+                file_change.file_path, e
+            ),
+        }
+    }
+
+    deleted_files
+}
+
+/// 最近一次显示的进度（阶段名称和已完成数量）
+static LAST_PROGRESS: Mutex<(&str, usize)> = Mutex::new(("", 0));
+
+/// 在标准错误输出上渲染进度条
+///
+/// 并发提取时事件可能乱序到达，同一阶段中只显示更大的进度
+fn render_progress(event: ProgressEvent) {
+    const WIDTH: usize = 30;
+
+    let (label, completed, total) = match event {
+        ProgressEvent::FileParsed { completed, total } => ("Parsing files", completed, total),
+        ProgressEvent::ContextExtracted { completed, total } => {
+            ("Extracting contexts", completed, total)
+        }
+    };
+
+    let mut last = LAST_PROGRESS.lock().unwrap();
+    if last.0 == label && completed <= last.1 && completed != 0 {
+        return;
+    }
+    *last = (label, completed);
+
+    let filled = completed * WIDTH / total.max(1);
+    let mut stderr = io::stderr().lock();
+    let _ = write!(
+        stderr,
+        "\r{label:<20} [{}{}] {completed}/{total}",
+        "#".repeat(filled),
+        " ".repeat(WIDTH - filled)
+    );
+    if completed == total {
+        let _ = writeln!(stderr);
+    }
+    let _ = stderr.flush();
+}
+
+/// 加载已分析文件所在目录中的 `_test.go` 文件
+///
+/// 这些文件只用于查找 `Test<Name>` 函数，已在源文件池中的文件不会重复加载
+fn load_sibling_test_files(
+    analyzed_files: &[semantic_diff_core::SourceFile],
+) -> Vec<semantic_diff_core::SourceFile> {
+    use semantic_diff_core::analyzer::SourceAnalyzer;
+
+    let loaded: HashSet<&Path> = analyzed_files.iter().map(|f| f.path.as_path()).collect();
+    let directories: HashSet<&Path> = analyzed_files
+        .iter()
+        .filter_map(|f| f.path.parent())
+        .collect();
+
+    let mut test_files = Vec::new();
+    for directory in directories {
+        let Ok(entries) = std::fs::read_dir(directory) else {
+            continue;
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.ends_with("_test.go"))
+                    && !loaded.contains(path.as_path())
+            })
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            match SourceAnalyzer::new_for_file(&path).and_then(|mut a| a.analyze_file(&path)) {
+                Ok(source_file) => test_files.push(source_file),
+                Err(e) => warn!("Failed to analyze test file {:?}: {}", path, e),
+            }
+        }
+    }
+
+    test_files
+}
+
+/// 判断仓库相对路径是否位于任一包含的路径前缀下，没有指定前缀时包含所有文件
+///
+/// 前缀按路径组件比较，`internal/user` 不会匹配 `internal/username`
+fn is_included_path(file_path: &Path, include_paths: &[PathBuf]) -> bool {
+    include_paths.is_empty()
+        || include_paths
+            .iter()
+            .any(|prefix| file_path.starts_with(prefix))
+}
+
 /// 检查是否为测试文件
 fn is_test_file(file_path: &std::path::Path) -> bool {
     let file_name = file_path
@@ -261,39 +799,156 @@ fn is_test_file(file_path: &std::path::Path) -> bool {
 fn find_change_targets(
     file_changes: &[semantic_diff_core::FileChange],
     analyzed_files: &[semantic_diff_core::SourceFile],
+    previous_versions: &HashMap<PathBuf, semantic_diff_core::SourceFile>,
+    deleted_files: &[semantic_diff_core::SourceFile],
     config: &Config,
 ) -> Result<Vec<semantic_diff_core::extractor::ChangeTarget>> {
-    use semantic_diff_core::{analyzer::SourceAnalyzer, extractor::ChangeTarget};
+    use semantic_diff_core::{
+        SemanticDiff,
+        analyzer::SourceAnalyzer,
+        extractor::{ChangeTarget, ChangeType},
+    };
 
-    let mut change_targets = Vec::new();
+    // 根据差异块查找变更的函数
+    let mut change_targets =
+        SemanticDiff::detect_targets(analyzed_files, file_changes, &config.repo_path)?;
 
-    // 为每个分析的文件查找变更的函数
+    // 重命名和复制的文件改为与来源版本比较，未修改的函数不再作为变更目标
     for source_file in analyzed_files {
-        // 找到对应的文件变更
-        let file_change = file_changes
+        let Some(file_change) = file_changes
             .iter()
-            .find(|fc| config.repo_path.join(&fc.file_path) == source_file.path);
+            .find(|fc| fc.matches_path(&source_file.path, &config.repo_path))
+        else {
+            continue;
+        };
+        if !matches!(
+            file_change.change_type,
+            semantic_diff_core::ChangeType::Renamed { .. }
+                | semantic_diff_core::ChangeType::Copied { .. }
+        ) {
+            continue;
+        }
+        let Some(old_file) = previous_versions.get(&source_file.path) else {
+            continue;
+        };
 
-        if let Some(file_change) = file_change {
-            // 创建源分析器
-            let analyzer = SourceAnalyzer::new_for_language(source_file.language)?;
+        let analyzer = SourceAnalyzer::new_for_language(source_file.language)?;
+        change_targets.retain(|target| {
+            !matches!(target, ChangeTarget::Function(f) if f.file_path == source_file.path)
+        });
+        for function in analyzer.find_modified_functions(old_file, source_file) {
+            debug!(
+                "Found changed function in rewritten file: {} in {:?}",
+                function.name, source_file.path
+            );
+            change_targets.push(ChangeTarget::Function(function));
+        }
+    }
 
-            // 查找变更的函数
-            let changed_functions =
-                analyzer.find_changed_functions(source_file, &file_change.hunks)?;
+    // 检测同一文件内的函数重命名，将其作为单个变更目标报告
+    for source_file in analyzed_files {
+        let Some(old_file) = previous_versions.get(&source_file.path) else {
+            continue;
+        };
 
-            for function in changed_functions {
+        let renamed = match SourceAnalyzer::new_for_language(source_file.language) {
+            Ok(analyzer) => analyzer.find_renamed_functions(old_file, source_file),
+            Err(e) => {
                 debug!(
-                    "Found changed function: {} in {:?}",
-                    function.name, source_file.path
+                    "Failed to create analyzer for {:?}: {}",
+                    source_file.path, e
                 );
-                change_targets.push(ChangeTarget::Function(function));
+                continue;
+            }
+        };
+
+        for renamed in renamed {
+            if let Some(function) = renamed.as_function() {
+                change_targets.retain(|target| {
+                    !matches!(target, ChangeTarget::Function(f)
+                        if f.name == function.name
+                            && f.start_line == function.start_line
+                            && f.file_path == function.file_path)
+                });
             }
+            debug!(
+                "Found renamed function: {} in {:?}",
+                renamed.name(),
+                source_file.path
+            );
+            change_targets.push(renamed);
+        }
+    }
+
+    // 检测被删除的函数和类型，它们只存在于变更前的版本中
+    for source_file in analyzed_files {
+        let Some(old_file) = previous_versions.get(&source_file.path) else {
+            continue;
+        };
+        // 复制的来源文件依然存在，副本中缺少的声明并没有被删除
+        let is_copy = file_changes.iter().any(|fc| {
+            fc.matches_path(&source_file.path, &config.repo_path)
+                && matches!(
+                    fc.change_type,
+                    semantic_diff_core::ChangeType::Copied { .. }
+                )
+        });
+        if is_copy {
+            continue;
+        }
 
-            // 如果不是只显示函数，还要查找其他类型的变更
-            if !config.functions_only {
-                // TODO: 在未来的版本中添加对类型、变量、常量变更的检测
-                // 目前主要关注函数变更
+        let analyzer = SourceAnalyzer::new_for_language(source_file.language)?;
+        for removed in analyzer.find_removed_declarations(old_file, source_file) {
+            if config.functions_only && removed.change_type() != ChangeType::Function {
+                continue;
+            }
+            debug!(
+                "Found removed declaration: {} in {:?}",
+                removed.name(),
+                source_file.path
+            );
+            change_targets.push(removed);
+        }
+    }
+
+    // 被删除的文件中的所有声明都已删除
+    for old_file in deleted_files {
+        let analyzer = SourceAnalyzer::new_for_language(old_file.language)?;
+        for removed in analyzer.find_deleted_file_declarations(old_file) {
+            if config.functions_only && removed.change_type() != ChangeType::Function {
+                continue;
+            }
+            debug!(
+                "Found declaration of deleted file: {} in {:?}",
+                removed.name(),
+                old_file.path
+            );
+            change_targets.push(removed);
+        }
+    }
+
+    // 如果不是只显示函数，还要查找类型、变量和常量的变更
+    if !config.functions_only {
+        for source_file in analyzed_files {
+            let Some(file_change) = file_changes
+                .iter()
+                .find(|fc| fc.matches_path(&source_file.path, &config.repo_path))
+            else {
+                continue;
+            };
+
+            let analyzer = SourceAnalyzer::new_for_language(source_file.language)?;
+            for target in analyzer.find_changed_declarations(source_file, &file_change.hunks)? {
+                // 函数变更已经由 detect_targets 报告
+                if matches!(target, ChangeTarget::Function(_)) {
+                    continue;
+                }
+                debug!(
+                    "Found changed declaration: {} in {:?}",
+                    target.name(),
+                    source_file.path
+                );
+                change_targets.push(target);
             }
         }
     }
@@ -301,33 +956,273 @@ fn find_change_targets(
     Ok(change_targets)
 }
 
-/// 创建语义上下文提取器
-fn create_context_extractor(
+/// 读取并解析变更文件在父提交中的版本，按新版本的源文件路径索引
+///
+/// 新增文件、初始提交等无法获取旧版本的文件不会出现在结果中
+fn load_previous_versions(
+    git_parser: &semantic_diff_core::GitDiffParser,
+    file_changes: &[semantic_diff_core::FileChange],
+    analyzed_files: &[semantic_diff_core::SourceFile],
     config: &Config,
-) -> Result<semantic_diff_core::SemanticContextExtractor> {
-    // 尝试从项目根目录创建提取器（以获取 go.mod 信息）
-    match semantic_diff_core::SemanticContextExtractor::from_project_root(&config.repo_path) {
-        Ok(extractor) => {
-            debug!("Created context extractor with project module information");
-            Ok(extractor.with_max_recursion_depth(config.max_dependency_depth as usize))
+) -> HashMap<PathBuf, semantic_diff_core::SourceFile> {
+    use semantic_diff_core::analyzer::SourceAnalyzer;
+
+    let mut previous_versions = HashMap::new();
+
+    for source_file in analyzed_files {
+        // 找到对应的文件变更
+        let Some(file_change) = file_changes
+            .iter()
+            .find(|fc| fc.matches_path(&source_file.path, &config.repo_path))
+        else {
+            continue;
+        };
+
+        let old_content = match git_parser.get_old_file_content(&config.commit_hash, file_change) {
+            Ok(Some(content)) => content,
+            Ok(None) => continue,
+            Err(e) => {
+                debug!(
+                    "Failed to read previous version of {:?}: {}",
+                    file_change.file_path, e
+                );
+                continue;
+            }
+        };
+
+        let old_file = SourceAnalyzer::new_for_language(source_file.language)
+            .and_then(|mut analyzer| analyzer.analyze_source(&source_file.path, old_content));
+
+        match old_file {
+            Ok(old_file) => {
+                previous_versions.insert(source_file.path.clone(), old_file);
+            }
+            Err(e) => debug!(
+                "Failed to analyze previous version of {:?}: {}",
+                file_change.file_path, e
+            ),
         }
-        Err(e) => {
+    }
+
+    previous_versions
+}
+
+/// 查找行数增长超过阈值的函数
+fn find_grown_functions(
+    analyzed_files: &[semantic_diff_core::SourceFile],
+    previous_versions: &HashMap<PathBuf, semantic_diff_core::SourceFile>,
+    threshold_percent: f64,
+) -> Vec<(PathBuf, semantic_diff_core::FunctionGrowth)> {
+    use semantic_diff_core::analyzer::SourceAnalyzer;
+
+    let mut grown_functions = Vec::new();
+
+    for source_file in analyzed_files {
+        let Some(old_file) = previous_versions.get(&source_file.path) else {
+            continue;
+        };
+        let Ok(analyzer) = SourceAnalyzer::new_for_language(source_file.language) else {
+            continue;
+        };
+
+        for growth in analyzer.find_grown_functions(old_file, source_file, threshold_percent) {
+            debug!(
+                "Function {} grew {:.2}x in {:?}",
+                growth.name, growth.growth_ratio, source_file.path
+            );
+            grown_functions.push((source_file.path.clone(), growth));
+        }
+    }
+
+    grown_functions
+}
+
+/// 查找结构体字段序列化标签的变化
+fn find_tag_changes(
+    analyzed_files: &[semantic_diff_core::SourceFile],
+    previous_versions: &HashMap<PathBuf, semantic_diff_core::SourceFile>,
+) -> Vec<(PathBuf, semantic_diff_core::TagChange)> {
+    use semantic_diff_core::analyzer::SourceAnalyzer;
+
+    let mut tag_changes = Vec::new();
+
+    for source_file in analyzed_files {
+        let Some(old_file) = previous_versions.get(&source_file.path) else {
+            continue;
+        };
+        let Ok(analyzer) = SourceAnalyzer::new_for_language(source_file.language) else {
+            continue;
+        };
+
+        for change in analyzer.find_tag_changes(old_file, source_file) {
             debug!(
-                "Failed to read project module info: {}, using default extractor",
-                e
+                "Tag {} of {}.{} changed in {:?}",
+                change.tag_key, change.type_name, change.field_name, source_file.path
             );
-            Ok(semantic_diff_core::SemanticContextExtractor::new()
-                .with_max_recursion_depth(config.max_dependency_depth as usize))
+            tag_changes.push((source_file.path.clone(), change));
         }
     }
+
+    tag_changes
+}
+
+/// 按兼容性分类变更文件中的声明，汇总为建议的版本升级级别
+///
+/// 没有旧版本的文件视为新增文件；删除的文件不参与分析
+fn suggest_version_bump(
+    analyzed_files: &[semantic_diff_core::SourceFile],
+    previous_versions: &HashMap<PathBuf, semantic_diff_core::SourceFile>,
+) -> semantic_diff_core::SemverBump {
+    use semantic_diff_core::analyzer::{SourceAnalyzer, semver_impact};
+
+    let mut classified = Vec::new();
+
+    for source_file in analyzed_files {
+        let Ok(analyzer) = SourceAnalyzer::new_for_language(source_file.language) else {
+            continue;
+        };
+
+        for change in
+            analyzer.classify_changes(previous_versions.get(&source_file.path), source_file)
+        {
+            if change.change.is_breaking() {
+                debug!(
+                    "Breaking change to {} in {:?}: {:?}",
+                    change.name, source_file.path, change.change
+                );
+            }
+            classified.push(change);
+        }
+    }
+
+    semver_impact(&classified)
+}
+
+/// 为变更目标或其相关类型记录字段序列化标签的变化
+fn apply_tag_changes(
+    semantic_contexts: &mut [semantic_diff_core::SemanticContext],
+    tag_changes: &[(PathBuf, semantic_diff_core::TagChange)],
+) {
+    use semantic_diff_core::extractor::ChangeTarget;
+
+    for context in semantic_contexts {
+        let target_type = match &context.change_target {
+            ChangeTarget::Type(type_def) => Some(type_def),
+            _ => None,
+        };
+
+        context.tag_changes = tag_changes
+            .iter()
+            .filter(|(path, change)| {
+                target_type
+                    .into_iter()
+                    .chain(&context.related_types)
+                    .any(|t| t.file_path == *path && t.name == change.type_name)
+            })
+            .map(|(_, change)| change.clone())
+            .collect();
+    }
+}
+
+/// 为变更目标记录新增行中引入的 panic/unsafe/reflect 用法
+fn apply_risk_signals(
+    semantic_contexts: &mut [semantic_diff_core::SemanticContext],
+    analyzed_files: &[semantic_diff_core::SourceFile],
+    file_changes: &[semantic_diff_core::FileChange],
+    repo_path: &Path,
+) {
+    use semantic_diff_core::SemanticDiff;
+
+    for context in semantic_contexts {
+        let Some(source_file) = analyzed_files
+            .iter()
+            .find(|file| &file.path == context.change_target.file_path())
+        else {
+            continue;
+        };
+        let hunks = relevant_hunks(context, file_changes, repo_path);
+        context.risk_signals =
+            SemanticDiff::detect_risks(&context.change_target, source_file, &hunks);
+    }
+}
+
+/// 为显著增长的变更函数设置 `growth_ratio`
+fn apply_function_growth(
+    semantic_contexts: &mut [semantic_diff_core::SemanticContext],
+    grown_functions: &[(PathBuf, semantic_diff_core::FunctionGrowth)],
+) {
+    use semantic_diff_core::extractor::ChangeTarget;
+
+    for context in semantic_contexts {
+        let ChangeTarget::Function(function) = &context.change_target else {
+            continue;
+        };
+        let receiver = function.receiver.as_ref().map(|r| &r.type_name);
+
+        context.growth_ratio = grown_functions
+            .iter()
+            .find(|(path, growth)| {
+                *path == function.file_path
+                    && growth.name == function.name
+                    && growth.receiver.as_ref() == receiver
+            })
+            .map(|(_, growth)| growth.growth_ratio);
+    }
+}
+
+/// 创建语义上下文提取器
+fn create_context_extractor(
+    config: &Config,
+) -> Result<semantic_diff_core::SemanticContextExtractor> {
+    // 尝试从项目根目录创建提取器（以获取 go.mod 信息）
+    let extractor =
+        match semantic_diff_core::SemanticContextExtractor::from_project_root(&config.repo_path) {
+            Ok(extractor) => {
+                debug!("Created context extractor with project module information");
+                extractor
+                    .with_max_recursion_depth(config.max_dependency_depth as usize)
+                    .with_test_coverage_check(config.flag_untested)
+            }
+            Err(e) => {
+                debug!(
+                    "Failed to read project module info: {}, using default extractor",
+                    e
+                );
+                semantic_diff_core::SemanticContextExtractor::new()
+                    .with_max_recursion_depth(config.max_dependency_depth as usize)
+                    .with_test_coverage_check(config.flag_untested)
+            }
+        };
+
+    let extractor = match config.concurrency {
+        Some(threads) => extractor.with_threads(threads),
+        None => extractor,
+    };
+    let extractor = if config.progress {
+        extractor.on_progress(render_progress)
+    } else {
+        extractor
+    };
+
+    // 排除的文件同样不作为依赖定义的来源
+    let extractor =
+        extractor.with_exclude_patterns(config.exclude_patterns.clone(), &config.repo_path);
+
+    Ok(match &config.build_constraints {
+        Some(constraints) => extractor.with_build_constraints(constraints.clone()),
+        None => extractor,
+    })
 }
 
 /// 提取语义上下文
+///
+/// 提取失败的目标交给 `recovery` 处理，可恢复的错误记录到 `diagnostics` 后跳过该目标
 fn extract_semantic_contexts(
     change_targets: &[semantic_diff_core::extractor::ChangeTarget],
     analyzed_files: &[semantic_diff_core::SourceFile],
     context_extractor: &semantic_diff_core::SemanticContextExtractor,
-    _config: &Config,
+    recovery: &ErrorRecoveryStrategy,
+    diagnostics: &mut Diagnostics,
 ) -> Result<Vec<semantic_diff_core::SemanticContext>> {
     if change_targets.len() > 10 {
         // 对于大量目标，使用批量处理
@@ -336,28 +1231,22 @@ fn extract_semantic_contexts(
             change_targets.len()
         );
         context_extractor.extract_contexts_in_batches(change_targets, analyzed_files, 5)
-    } else if change_targets.len() > 1 {
-        // 对于中等数量的目标，使用并发处理
+    } else {
         debug!(
             "Using concurrent processing for {} targets",
             change_targets.len()
         );
-        context_extractor.extract_contexts_concurrent(change_targets, analyzed_files)
-    } else {
-        // 对于单个目标，直接处理
-        debug!("Processing single target");
-        let mut contexts = Vec::new();
-        for target in change_targets {
-            let context =
-                context_extractor.extract_context_for_target(target.clone(), analyzed_files)?;
-            contexts.push(context);
-        }
-        Ok(contexts)
+        context_extractor.extract_contexts_with_diagnostics(
+            change_targets,
+            analyzed_files,
+            recovery,
+            diagnostics,
+        )
     }
 }
 
 /// 创建代码生成器
-fn create_code_generator(config: &Config) -> semantic_diff_core::CodeSliceGenerator {
+fn create_code_generator(config: &Config) -> Result<semantic_diff_core::CodeSliceGenerator> {
     use semantic_diff_core::{CodeSliceGenerator, generator::GeneratorConfig};
 
     let generator_config = GeneratorConfig {
@@ -365,71 +1254,166 @@ fn create_code_generator(config: &Config) -> semantic_diff_core::CodeSliceGenera
         include_imports: true,
         include_types: true,
         include_dependent_functions: !config.functions_only,
-        include_dependency_graph: config.show_dependencies,
+        // 树形渲染需要依赖图
+        include_dependency_graph: config.show_dependencies || config.tree,
         max_lines: config.max_lines.map(|n| n as usize),
         output_format: config.output_format.clone(),
         highlight_style: config.highlight_style.clone(),
+        review_checklist: load_review_checklist(config.review_checklist.as_deref())?,
+        repo_root: Some(config.repo_path.clone()),
+    };
+
+    Ok(CodeSliceGenerator::with_config(generator_config))
+}
+
+/// 读取审查清单配置，未指定文件时返回空清单
+fn load_review_checklist(path: Option<&Path>) -> Result<semantic_diff_core::ReviewChecklist> {
+    let Some(path) = path else {
+        return Ok(semantic_diff_core::ReviewChecklist::default());
     };
 
-    CodeSliceGenerator::with_config(generator_config)
+    debug!("Loading review checklist from {}", path.display());
+    let content = std::fs::read_to_string(path)?;
+    semantic_diff_core::ReviewChecklist::from_json(&content)
+}
+
+/// 查找与变更目标所在文件对应的差异块
+///
+/// 变更目标的路径由仓库路径和 `FileChange` 的仓库相对路径拼接而成，按完整路径比较，
+/// 不同包中的同名文件（如两个 `service.go`）不会互相混入差异
+fn relevant_hunks(
+    context: &semantic_diff_core::SemanticContext,
+    file_changes: &[semantic_diff_core::FileChange],
+    repo_path: &Path,
+) -> Vec<semantic_diff_core::DiffHunk> {
+    let target_file_path = context.change_target.file_path();
+
+    file_changes
+        .iter()
+        .filter(|fc| fc.matches_path(target_file_path, repo_path))
+        .flat_map(|fc| &fc.hunks)
+        .cloned()
+        .collect()
 }
 
 /// 生成代码切片
+///
+/// 各上下文之间相互独立，并行生成后按输入顺序返回
 fn generate_code_slices(
     semantic_contexts: &[semantic_diff_core::SemanticContext],
     file_changes: &[semantic_diff_core::FileChange],
     code_generator: &semantic_diff_core::CodeSliceGenerator,
+    repo_path: &Path,
+    diagnostics: &mut Diagnostics,
 ) -> Result<Vec<semantic_diff_core::CodeSlice>> {
-    let mut code_slices = Vec::new();
+    let results = code_generator.generate_slices_parallel(semantic_contexts, |context| {
+        relevant_hunks(context, file_changes, repo_path)
+    });
 
-    for context in semantic_contexts {
-        // 找到对应的文件变更以获取差异信息
-        let target_file_path = context.change_target.file_path();
-
-        let relevant_hunks: Vec<_> = file_changes
+    Ok(collect_code_slices(
+        semantic_contexts
             .iter()
-            .filter(|fc| {
-                // 比较完整路径或者文件名
-                let target_path_str = target_file_path.to_string_lossy();
-                let fc_path_str = fc.file_path.to_string_lossy();
-
-                
+            .map(|context| &context.change_target),
+        results,
+        diagnostics,
+    ))
+}
 
-                fc_path_str == target_path_str
-                    || fc_path_str.ends_with(&*target_path_str)
-                    || target_path_str.ends_with(&*fc_path_str)
-            })
-            .flat_map(|fc| &fc.hunks)
-            .cloned()
-            .collect();
+/// 只为变更目标生成代码切片（`--no-context` 模式）
+fn generate_target_only_slices(
+    change_targets: &[semantic_diff_core::extractor::ChangeTarget],
+    file_changes: &[semantic_diff_core::FileChange],
+    code_generator: &semantic_diff_core::CodeSliceGenerator,
+    repo_path: &Path,
+    diagnostics: &mut Diagnostics,
+) -> Result<Vec<semantic_diff_core::CodeSlice>> {
+    let results = code_generator.generate_targets_only(change_targets, |context| {
+        relevant_hunks(context, file_changes, repo_path)
+    });
+
+    Ok(collect_code_slices(
+        change_targets.iter(),
+        results,
+        diagnostics,
+    ))
+}
 
-        // 生成代码切片
-        match code_generator.generate_slice(context, &relevant_hunks) {
+/// 收集成功生成的代码切片，失败的目标记录到 `diagnostics` 后跳过
+fn collect_code_slices<'a>(
+    targets: impl Iterator<Item = &'a semantic_diff_core::extractor::ChangeTarget>,
+    results: Vec<Result<semantic_diff_core::CodeSlice>>,
+    diagnostics: &mut Diagnostics,
+) -> Vec<semantic_diff_core::CodeSlice> {
+    let mut code_slices = Vec::new();
+    for (target, result) in targets.zip(results) {
+        match result {
             Ok(slice) => {
-                debug!(
-                    "Generated code slice for target: {}",
-                    context.change_target.name()
-                );
+                debug!("Generated code slice for target: {}", target.name());
                 code_slices.push(slice);
             }
             Err(e) => {
-                warn!(
-                    "Failed to generate code slice for {}: {}",
-                    context.change_target.name(),
-                    e
+                warn!("Failed to generate code slice for {}: {}", target.name(), e);
+                diagnostics.record_error(
+                    &e,
+                    Some(target.file_path().as_path()),
+                    Some(target.name()),
                 );
-                // 继续处理其他上下文
             }
         }
     }
 
-    Ok(code_slices)
+    code_slices
+}
+
+/// 为代码切片附加变更目标在变更前后的源码，供左右对照渲染
+///
+/// 旧版本取自变更前的 blob（见 [`load_previous_versions`]），找不到变更目标所在源文件的切片保持不变
+fn attach_target_versions(
+    code_slices: &mut [semantic_diff_core::CodeSlice],
+    analyzed_files: &[semantic_diff_core::SourceFile],
+    previous_versions: &HashMap<PathBuf, semantic_diff_core::SourceFile>,
+) {
+    use semantic_diff_core::analyzer::SourceAnalyzer;
+
+    for slice in code_slices {
+        let Some(target) = &slice.change_target else {
+            continue;
+        };
+        let Some(source_file) = analyzed_files
+            .iter()
+            .find(|file| &file.path == target.file_path())
+        else {
+            continue;
+        };
+
+        match SourceAnalyzer::new_for_language(source_file.language) {
+            Ok(analyzer) => {
+                slice.target_versions = Some(analyzer.target_versions(
+                    target,
+                    previous_versions.get(&source_file.path),
+                    source_file,
+                ));
+            }
+            Err(e) => debug!(
+                "Failed to create analyzer for {:?}: {}",
+                source_file.path, e
+            ),
+        }
+    }
 }
 
 /// 格式化并输出结果
 fn format_and_output(code_slices: &[semantic_diff_core::CodeSlice], config: &Config) -> Result<()> {
     use semantic_diff_core::formatter::OutputRenderer;
 
+    // SARIF 把所有切片汇总为一个文档，没有变更时也输出空结果供 CI 上传
+    if config.output_format == semantic_diff_core::OutputFormat::Sarif {
+        let mut sarif =
+            semantic_diff_core::formatter::sarif::to_sarif(code_slices, &config.repo_path);
+        sarif.push('\n');
+        return write_output(&sarif, config);
+    }
+
     if code_slices.is_empty() {
         let output = "No code slices generated.\n";
         write_output(output, config)?;
@@ -450,6 +1434,12 @@ fn format_and_output(code_slices: &[semantic_diff_core::CodeSlice], config: &Con
         custom_css: None,
         max_line_width: config.max_lines.map(|n| n as usize),
         indent_size: 4,
+        annotate_source_files: config.annotate_files,
+        render_dependency_tree: config.tree,
+        include_dependency_graph: config.show_dependencies,
+        side_by_side: config.side_by_side,
+        collapse_context: config.collapse_context,
+        color_depth: semantic_diff_core::formatter::ColorDepth::detect(),
     };
 
     let renderer = OutputRenderer::new(renderer_config);
@@ -472,7 +1462,8 @@ fn format_and_output(code_slices: &[semantic_diff_core::CodeSlice], config: &Con
 
     // 处理每个代码切片
     for (index, slice) in code_slices.iter().enumerate() {
-        if index > 0 {
+        // 统一差异的各个切片直接拼接为一个补丁
+        if index > 0 && config.output_format != semantic_diff_core::OutputFormat::UnifiedDiff {
             final_output.push_str("\n\n");
             final_output.push_str("=".repeat(80).as_str());
             final_output.push_str("\n\n");
@@ -593,6 +1584,13 @@ fn validate_and_load_config(config: &Config) -> Result<()> {
         }
     }
 
+    // 测试覆盖检查依赖上下文提取，配置文件中的组合不经过命令行参数校验
+    if config.no_context && config.flag_untested {
+        return Err(semantic_diff_core::SemanticDiffError::ConfigError(
+            "flag_untested cannot be combined with no_context".to_string(),
+        ));
+    }
+
     debug!("Configuration validation completed successfully");
     Ok(())
 }
@@ -2,6 +2,7 @@
 //!
 //! 测试命令行接口的各种功能和参数组合
 
+use std::path::Path;
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -16,29 +17,38 @@ fn get_binary_path() -> String {
     path.to_string_lossy().to_string()
 }
 
+/// 在测试仓库中运行 git 命令，返回标准输出
+fn run_git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .expect("Failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// 暂存仓库中的所有改动并提交，返回新提交的哈希
+fn commit_all(repo: &Path, message: &str) -> String {
+    run_git(repo, &["add", "-A"]);
+    run_git(repo, &["commit", "-m", message]);
+    run_git(repo, &["rev-parse", "HEAD"]).trim().to_string()
+}
+
 /// 创建测试用的临时 Git 仓库
 fn create_test_repo() -> TempDir {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let repo_path = temp_dir.path();
 
-    // 初始化 Git 仓库
-    Command::new("git")
-        .args(["init"])
-        .current_dir(&temp_dir)
-        .output()
-        .expect("Failed to initialize git repo");
-
-    // 配置 Git 用户
-    Command::new("git")
-        .args(["config", "user.name", "Test User"])
-        .current_dir(&temp_dir)
-        .output()
-        .expect("Failed to configure git user");
-
-    Command::new("git")
-        .args(["config", "user.email", "test@example.com"])
-        .current_dir(&temp_dir)
-        .output()
-        .expect("Failed to configure git email");
+    // 初始化 Git 仓库并配置用户
+    run_git(repo_path, &["init"]);
+    run_git(repo_path, &["config", "user.name", "Test User"]);
+    run_git(repo_path, &["config", "user.email", "test@example.com"]);
 
     // 创建一个简单的 Go 文件
     let go_content = r#"package main
@@ -50,20 +60,10 @@ func main() {
 }
 "#;
 
-    std::fs::write(temp_dir.path().join("main.go"), go_content).expect("Failed to write Go file");
+    std::fs::write(repo_path.join("main.go"), go_content).expect("Failed to write Go file");
 
     // 添加并提交文件
-    Command::new("git")
-        .args(["add", "."])
-        .current_dir(&temp_dir)
-        .output()
-        .expect("Failed to add files");
-
-    Command::new("git")
-        .args(["commit", "-m", "Initial commit"])
-        .current_dir(&temp_dir)
-        .output()
-        .expect("Failed to commit files");
+    commit_all(repo_path, "Initial commit");
 
     temp_dir
 }
@@ -108,26 +108,56 @@ fn test_missing_commit_hash() {
 
 #[test]
 fn test_invalid_commit_hash() {
+    let temp_repo = create_test_repo();
+
     let output = Command::new(get_binary_path())
-        .args(["invalid-hash-with-special-chars!@#"])
+        .args([
+            "invalid-hash-with-special-chars!@#",
+            "--repo",
+            temp_repo.path().to_str().unwrap(),
+        ])
         .output()
         .expect("Failed to execute command");
 
+    // 无法解析的修订由 Git 解析阶段报告
     assert!(!output.status.success());
     let stderr = String::from_utf8(output.stderr).unwrap();
-    assert!(stderr.contains("Invalid commit hash format"));
+    assert!(stderr.contains("Revision not found"));
 }
 
 #[test]
 fn test_short_commit_hash() {
+    let temp_repo = create_test_repo();
+
     let output = Command::new(get_binary_path())
-        .args(["abc123"])
+        .args(["abc123", "--repo", temp_repo.path().to_str().unwrap()])
         .output()
         .expect("Failed to execute command");
 
     assert!(!output.status.success());
     let stderr = String::from_utf8(output.stderr).unwrap();
-    assert!(stderr.contains("Commit hash length must be between 7 and 40 characters"));
+    assert!(stderr.contains("Revision not found"));
+}
+
+#[test]
+fn test_ref_name_commit() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+    std::fs::write(
+        repo_path.join("main.go"),
+        "package main\n\nfunc main() {\n    println(\"changed\")\n}\n",
+    )
+    .unwrap();
+    commit_all(repo_path, "Change main");
+
+    let output = Command::new(get_binary_path())
+        .args(["HEAD", "--repo", repo_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("println(\"changed\")"));
 }
 
 #[test]
@@ -148,7 +178,7 @@ fn test_valid_commit_hash_format() {
 
 #[test]
 fn test_output_format_options() {
-    let formats = ["text", "markdown", "html"];
+    let formats = ["text", "markdown", "html", "diff", "json"];
 
     for format in &formats {
         let output = Command::new(get_binary_path())
@@ -251,7 +281,16 @@ fn test_output_file_option() {
 
 #[test]
 fn test_boolean_flags() {
-    let flags = ["--exclude-tests", "--functions-only", "--include-comments"];
+    let flags = [
+        "--exclude-tests",
+        "--flag-risks",
+        "--flag-tag-changes",
+        "--functions-only",
+        "--include-comments",
+        "--no-context",
+        "--side-by-side",
+        "--tree",
+    ];
 
     for flag in &flags {
         let output = Command::new(get_binary_path())
@@ -266,6 +305,19 @@ fn test_boolean_flags() {
     }
 }
 
+#[test]
+fn test_flag_untested_conflicts_with_no_context() {
+    let output = Command::new(get_binary_path())
+        .args(["abcdef1234", "--no-context", "--flag-untested"])
+        .output()
+        .expect("Failed to execute command");
+
+    // 无上下文模式不检查测试覆盖，两个标志不能同时使用
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("cannot be used with"));
+}
+
 #[test]
 fn test_complex_argument_combination() {
     let temp_repo = create_test_repo();
@@ -288,6 +340,17 @@ fn test_complex_argument_combination() {
             "--exclude-tests",
             "--functions-only",
             "--verbose",
+            "--goos",
+            "linux",
+            "--goarch",
+            "amd64",
+            "--exclude",
+            "*.pb.go",
+            "--exclude",
+            "vendor/**",
+            "--threads",
+            "1",
+            "--progress",
         ])
         .output()
         .expect("Failed to execute command");
@@ -298,3 +361,599 @@ fn test_complex_argument_combination() {
     assert!(!stderr.contains("invalid value"));
     assert!(!stderr.contains("Invalid commit hash format"));
 }
+
+#[test]
+fn test_invalid_exclude_pattern_is_rejected() {
+    let temp_repo = create_test_repo();
+
+    let output = Command::new(get_binary_path())
+        .args([
+            "HEAD",
+            "--repo",
+            temp_repo.path().to_str().unwrap(),
+            "--exclude",
+            "gen/[a-z",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    // 无法匹配任何路径的模式直接报错，而不是被静默忽略
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Invalid exclude pattern 'gen/[a-z'"));
+}
+
+#[test]
+fn test_doctor_passes_on_valid_repo() {
+    let temp_repo = create_test_repo();
+
+    let output = Command::new(get_binary_path())
+        .args(["--doctor", "--repo", temp_repo.path().to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("tree-sitter Go grammar"));
+    assert!(stdout.contains("trivial parse"));
+    assert!(stdout.contains("all checks passed"));
+    assert!(!stdout.contains("FAIL"));
+}
+
+#[test]
+fn test_doctor_reports_invalid_repo() {
+    let output = Command::new(get_binary_path())
+        .args(["--doctor", "--repo", "/nonexistent/path"])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(!output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // 解析器检查应该通过，只有仓库检查失败
+    assert!(stdout.contains("[  ok] tree-sitter Go grammar"));
+    assert!(stdout.contains("[FAIL] git repository: path does not exist: /nonexistent/path"));
+    assert!(stdout.contains("some checks failed"));
+}
+
+#[test]
+fn test_removed_method_appears_in_output() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+    let with_method = r#"package main
+
+type Greeter struct {
+    Name string
+}
+
+func (g *Greeter) Greet() string {
+    return "Hello, " + g.Name
+}
+
+func main() {
+    g := &Greeter{Name: "World"}
+    println(g.Name)
+}
+"#;
+    std::fs::write(repo_path.join("main.go"), with_method).unwrap();
+    commit_all(repo_path, "Add Greet method");
+
+    let without_method = r#"package main
+
+type Greeter struct {
+    Name string
+}
+
+func main() {
+    g := &Greeter{Name: "World"}
+    println(g.Name)
+}
+"#;
+    std::fs::write(repo_path.join("main.go"), without_method).unwrap();
+    let head = commit_all(repo_path, "Remove Greet method");
+
+    let output = Command::new(get_binary_path())
+        .args([head.as_str(), "--repo", repo_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Removed method: Greet"));
+    assert!(stdout.contains("return \"Hello, \" + g.Name"));
+}
+
+#[test]
+fn test_unified_diff_output_applies_with_git() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+    std::fs::create_dir(repo_path.join("calc")).unwrap();
+    std::fs::write(
+        repo_path.join("calc/math.go"),
+        "package calc\n\nfunc Add(a, b int) int {\n    return a - b\n}\n\nfunc Double(a int) int {\n    return a\n}\n",
+    )
+    .unwrap();
+    commit_all(repo_path, "Add calc");
+    std::fs::write(
+        repo_path.join("calc/math.go"),
+        "package calc\n\nfunc Add(a, b int) int {\n    return a + b\n}\n\nfunc Double(a int) int {\n    return a * 2\n}\n",
+    )
+    .unwrap();
+    let head = commit_all(repo_path, "Fix calc");
+
+    let patch_path = repo_path.join("targets.patch");
+    let output = Command::new(get_binary_path())
+        .args([
+            head.as_str(),
+            "--repo",
+            repo_path.to_str().unwrap(),
+            "--format",
+            "diff",
+            "--output",
+            patch_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    let patch = std::fs::read_to_string(&patch_path).unwrap();
+
+    // 头部路径相对于仓库根目录
+    assert!(
+        patch.starts_with("--- a/calc/math.go\n+++ b/calc/math.go\n"),
+        "{patch}"
+    );
+    assert!(!patch.contains(repo_path.to_str().unwrap()));
+
+    // 补丁可以在父提交上直接应用，反向应用于 HEAD
+    run_git(
+        repo_path,
+        &["apply", "--check", "--reverse", "targets.patch"],
+    );
+    run_git(
+        repo_path,
+        &["checkout", "-q", "HEAD~1", "--", "calc/math.go"],
+    );
+    run_git(repo_path, &["apply", "targets.patch"]);
+    assert!(run_git(repo_path, &["diff", "HEAD", "--stat", "--", "calc"]).is_empty());
+}
+
+#[test]
+fn test_sarif_output_uses_repo_relative_uris() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+    std::fs::create_dir(repo_path.join("calc")).unwrap();
+    std::fs::write(
+        repo_path.join("calc/math.go"),
+        "package calc\n\nfunc Add(a, b int) int {\n    return a - b\n}\n",
+    )
+    .unwrap();
+    commit_all(repo_path, "Add calc");
+    std::fs::write(
+        repo_path.join("calc/math.go"),
+        "package calc\n\nfunc Add(a, b int) int {\n    return a + b\n}\n",
+    )
+    .unwrap();
+    let head = commit_all(repo_path, "Fix calc");
+
+    let sarif_path = repo_path.join("results.sarif");
+    let output = Command::new(get_binary_path())
+        .args([
+            head.as_str(),
+            "--repo",
+            repo_path.to_str().unwrap(),
+            "--format",
+            "sarif",
+            "--output",
+            sarif_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    let sarif: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&sarif_path).unwrap()).unwrap();
+    assert_eq!(sarif["version"], "2.1.0");
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["message"]["text"], "Changed function: Add");
+
+    let location = &results[0]["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "calc/math.go");
+    assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+    assert_eq!(location["region"]["startLine"], 3);
+    assert_eq!(location["region"]["endLine"], 5);
+}
+
+#[test]
+fn test_deleted_file_declarations_appear_in_output() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+
+    let legacy = r#"package main
+
+const LegacyVersion = "1.0"
+
+type LegacyClient struct {
+    Endpoint string
+}
+
+func (c *LegacyClient) Fetch() string {
+    return "fetch " + c.Endpoint
+}
+
+func newLegacyClient() *LegacyClient {
+    return &LegacyClient{Endpoint: LegacyVersion}
+}
+"#;
+    std::fs::write(repo_path.join("legacy.go"), legacy).unwrap();
+    commit_all(repo_path, "Add legacy client");
+
+    std::fs::remove_file(repo_path.join("legacy.go")).unwrap();
+    let head = commit_all(repo_path, "Remove legacy client");
+
+    let output = Command::new(get_binary_path())
+        .args([head.as_str(), "--repo", repo_path.to_str().unwrap()])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Removed constant: LegacyVersion"));
+    assert!(stdout.contains("Removed type: LegacyClient"));
+    assert!(stdout.contains("Removed method: Fetch"));
+    assert!(stdout.contains("Removed function: newLegacyClient"));
+    assert!(stdout.contains("return \"fetch \" + c.Endpoint"));
+}
+
+/// 以详细模式运行并返回合并后的输出（配置信息以 debug 日志形式打印）
+fn run_verbose(repo_path: &std::path::Path, extra_args: &[&str]) -> (bool, String) {
+    let output = Command::new(get_binary_path())
+        .args([
+            "abcdef1234",
+            "--repo",
+            repo_path.to_str().unwrap(),
+            "--verbose",
+        ])
+        .args(extra_args)
+        .output()
+        .expect("Failed to execute command");
+    let mut combined = String::from_utf8(output.stdout).unwrap();
+    combined.push_str(&String::from_utf8(output.stderr).unwrap());
+    (output.status.success(), combined)
+}
+
+#[test]
+fn test_config_file_is_discovered() {
+    let temp_repo = create_test_repo();
+    std::fs::write(
+        temp_repo.path().join(".semantic-diff.toml"),
+        "max_dependency_depth = 5\nfunctions_only = true\noutput_format = \"markdown\"\n",
+    )
+    .unwrap();
+
+    let (_, output) = run_verbose(temp_repo.path(), &[]);
+    assert!(output.contains("max_dependency_depth: 5"));
+    assert!(output.contains("functions_only: true"));
+    assert!(output.contains("output_format: Markdown"));
+}
+
+#[test]
+fn test_command_line_flags_override_config_file() {
+    let temp_repo = create_test_repo();
+    std::fs::write(
+        temp_repo.path().join(".semantic-diff.toml"),
+        "max_dependency_depth = 5\nexclude_test_files = true\n",
+    )
+    .unwrap();
+
+    let (_, output) = run_verbose(temp_repo.path(), &["--max-depth", "2"]);
+    // 显式传入的参数优先，未传入的参数仍取配置文件中的值
+    assert!(output.contains("max_dependency_depth: 2"));
+    assert!(output.contains("exclude_test_files: true"));
+}
+
+#[test]
+fn test_config_file_rejects_unknown_keys() {
+    let temp_repo = create_test_repo();
+    let config_dir = TempDir::new().expect("Failed to create temp directory");
+    let config_path = config_dir.path().join("custom.toml");
+    std::fs::write(&config_path, "max_dependency_depth = 5\nmax_depht = 3\n").unwrap();
+
+    let (success, output) = run_verbose(
+        temp_repo.path(),
+        &["--config", config_path.to_str().unwrap()],
+    );
+    assert!(!success);
+    assert!(output.contains("unknown field"));
+    assert!(output.contains("max_depht"));
+}
+
+#[test]
+fn test_path_filter_limits_targets_to_prefix() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+
+    let write_go = |dir: &str, file: &str, content: &str| {
+        std::fs::create_dir_all(repo_path.join(dir)).unwrap();
+        std::fs::write(repo_path.join(dir).join(file), content).unwrap();
+    };
+    write_go(
+        "internal/user",
+        "user.go",
+        "package user\n\nfunc UserName() string {\n    return \"alice\"\n}\n",
+    );
+    write_go(
+        "internal/order",
+        "order.go",
+        "package order\n\nfunc OrderTotal() int {\n    return 1\n}\n",
+    );
+    commit_all(repo_path, "Add packages");
+
+    write_go(
+        "internal/user",
+        "user.go",
+        "package user\n\nfunc UserName() string {\n    return \"bob\"\n}\n",
+    );
+    write_go(
+        "internal/order",
+        "order.go",
+        "package order\n\nfunc OrderTotal() int {\n    return 2\n}\n",
+    );
+    let head = commit_all(repo_path, "Change both packages");
+
+    let output = Command::new(get_binary_path())
+        .args([
+            head.as_str(),
+            "--repo",
+            repo_path.to_str().unwrap(),
+            "--path",
+            "internal/user",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("UserName"));
+    assert!(!stdout.contains("OrderTotal"));
+}
+
+#[test]
+fn test_same_named_files_do_not_share_hunks() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+
+    // `data/service.go` 以 `a/service.go` 结尾，按字符串后缀匹配会把两者的差异混在一起
+    let service = |package: &str, value: &str| {
+        format!("package {package}\n\nfunc Name() string {{\n    return \"{value}\"\n}}\n")
+    };
+    for dir in ["a", "data"] {
+        std::fs::create_dir_all(repo_path.join(dir)).unwrap();
+        std::fs::write(
+            repo_path.join(dir).join("service.go"),
+            service(dir, &format!("{dir}-old")),
+        )
+        .unwrap();
+    }
+    commit_all(repo_path, "Add services");
+
+    for dir in ["a", "data"] {
+        std::fs::write(
+            repo_path.join(dir).join("service.go"),
+            service(dir, &format!("{dir}-new")),
+        )
+        .unwrap();
+    }
+    let head = commit_all(repo_path, "Change services");
+
+    let output = Command::new(get_binary_path())
+        .args([
+            head.as_str(),
+            "--repo",
+            repo_path.to_str().unwrap(),
+            "--format",
+            "diff",
+        ])
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // 每个切片只包含自己文件的差异行
+    assert_eq!(stdout.matches("+    return \"a-new\"").count(), 1);
+    assert_eq!(stdout.matches("+    return \"data-new\"").count(), 1);
+}
+
+#[test]
+fn test_stats_json_report() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+
+    let content = r#"package main
+
+import "fmt"
+
+type Greeter struct {
+    Name string
+}
+
+func greet(g Greeter) {
+    fmt.Println("Hello, " + g.Name)
+}
+
+func main() {
+    greet(Greeter{Name: "World"})
+}
+"#;
+    std::fs::write(repo_path.join("main.go"), content).unwrap();
+    let head = commit_all(repo_path, "Add greet");
+
+    let stats_dir = TempDir::new().expect("Failed to create temp directory");
+    let stats_path = stats_dir.path().join("stats.json");
+    let output = Command::new(get_binary_path())
+        .args([
+            head.as_str(),
+            "--repo",
+            repo_path.to_str().unwrap(),
+            "--stats-json",
+            stats_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+
+    // 主输出保持不变，不包含统计注释
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Slice Statistics"));
+
+    let report: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&stats_path).unwrap()).unwrap();
+    let targets = report["targets"].as_array().unwrap();
+    let names: Vec<&str> = targets
+        .iter()
+        .map(|target| target["name"].as_str().unwrap())
+        .collect();
+    assert!(names.contains(&"greet"));
+    assert!(names.contains(&"main"));
+    assert_eq!(report["total_targets"], targets.len());
+
+    // 汇总值与各切片的统计一致
+    let function_count = targets
+        .iter()
+        .filter(|target| target["kind"] == "function")
+        .count();
+    assert_eq!(report["targets_by_kind"]["function"], function_count);
+    let line_sum: u64 = targets
+        .iter()
+        .map(|target| target["slice"]["total_lines"].as_u64().unwrap())
+        .sum();
+    assert_eq!(report["total_lines"].as_u64().unwrap(), line_sum);
+    let highlighted_sum: u64 = targets
+        .iter()
+        .map(|target| target["slice"]["highlighted_lines"].as_u64().unwrap())
+        .sum();
+    assert_eq!(
+        report["highlighted_lines"].as_u64().unwrap(),
+        highlighted_sum
+    );
+    assert!(highlighted_sum > 0);
+
+    let greet = targets
+        .iter()
+        .find(|target| target["name"] == "greet")
+        .unwrap();
+    assert_eq!(greet["context"]["types_count"], 1);
+}
+
+#[test]
+fn test_shared_preamble_emits_shared_type_once() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+
+    let content = r#"package main
+
+type Config struct {
+    Name string
+}
+
+func Load(cfg Config) string {
+    return cfg.Name
+}
+
+func Save(cfg Config) string {
+    return "saved " + cfg.Name
+}
+
+func main() {
+}
+"#;
+    std::fs::write(repo_path.join("main.go"), content).unwrap();
+    let head = commit_all(repo_path, "Add Load and Save");
+    let run = |extra_args: &[&str]| {
+        let output = Command::new(get_binary_path())
+            .args([head.as_str(), "--repo", repo_path.to_str().unwrap()])
+            .args(extra_args)
+            // 未变更的代码按语法着色，比较原始文本时关闭颜色
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("Failed to execute command");
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    // 默认每个切片各自输出 Config
+    assert!(run(&[]).matches("type Config struct").count() > 1);
+
+    // 共享前言中只输出一次，切片头部引用前言
+    let stdout = run(&["--shared-preamble", "2", "--functions-only"]);
+    assert_eq!(stdout.matches("type Config struct").count(), 1);
+    assert!(stdout.contains("Shared definitions (see preamble): Config"));
+}
+
+#[test]
+fn test_commit_range_autosquash() {
+    let temp_repo = create_test_repo();
+    let repo_path = temp_repo.path();
+    let base = run_git(repo_path, &["rev-parse", "HEAD"])
+        .trim()
+        .to_string();
+
+    let with_greet = |greeting: &str| {
+        format!(
+            r#"package main
+
+import "fmt"
+
+func Greet(name string) string {{
+    return "{greeting}, " + name
+}}
+
+func main() {{
+    fmt.Println("Hello, World!")
+}}
+"#
+        )
+    };
+    std::fs::write(repo_path.join("main.go"), with_greet("Hello")).unwrap();
+    commit_all(repo_path, "Add Greet");
+    std::fs::write(repo_path.join("main.go"), with_greet("Hi")).unwrap();
+    let fixup = commit_all(repo_path, "fixup! Add Greet");
+
+    let range = format!("{base}..HEAD");
+    let run = |extra_args: &[&str]| {
+        Command::new(get_binary_path())
+            .args([range.as_str(), "--repo", repo_path.to_str().unwrap()])
+            .args(extra_args)
+            .env("NO_COLOR", "1")
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    // 不折叠时范围内的两个提交分别输出
+    let output = run(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("// Function: Greet").count(), 2);
+
+    // 折叠后 fixup 提交并入目标提交，只输出一次
+    let output = run(&["--autosquash"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("// Function: Greet").count(), 1);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Add Greet"));
+    assert!(stderr.contains(&format!("folded {fixup}")));
+
+    // 单个提交不能使用 --autosquash
+    let output = Command::new(get_binary_path())
+        .args([
+            "HEAD",
+            "--autosquash",
+            "--repo",
+            repo_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute command");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--autosquash requires a <base>..<head> commit range"));
+}
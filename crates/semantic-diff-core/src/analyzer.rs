@@ -2,19 +2,36 @@
 //!
 //! 提供依赖关系解析和语义分析功能
 
+use crate::constraints::BuildConstraints;
+use crate::diagnostics::Diagnostics;
 use crate::error::{Result, SemanticDiffError};
+use crate::extractor::ChangeTarget;
+use crate::generator::TargetVersions;
 use crate::git::DiffHunk;
 use crate::parser::{
-    GoFunctionInfo, GoTypeDefinition, LanguageParser, ParserFactory, SourceFile, SupportedLanguage,
+    BodyReferences, GoDeclaration, GoFunctionInfo, GoLanguageInfo, GoType, GoTypeDefinition,
+    LanguageParser, ParserFactory, SourceFile, SupportedLanguage,
     common::{CstNavigator, LanguageSpecificInfo},
+    go::IOTA,
+};
+use crate::performance::{
+    CachedParse, ConcurrentFileProcessor, ErrorRecoveryStrategy, ParserCache,
 };
-use crate::performance::{ConcurrentFileProcessor, ErrorRecoveryStrategy};
 use rayon::prelude::*;
-use std::collections::HashSet;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tracing::{debug, info, warn};
 
+pub mod compatibility;
+
+pub use compatibility::{
+    ClassifiedChange, CompatChange, FieldRetype, SemverBump, StructDelta, classify_function_change,
+    diff_struct, semver_impact,
+};
+
 /// 通用源文件分析器
 ///
 /// 提供多语言源文件分析功能，整合解析器和 CST 导航
@@ -28,11 +45,38 @@ pub struct SourceAnalyzer {
 pub struct DependencyResolver {
     /// 项目的模块路径（从go.mod中获取）
     project_module_path: Option<String>,
+    /// 构建约束，存在同名声明时优先选择满足约束的文件中的定义
+    build_constraints: Option<BuildConstraints>,
+}
+
+/// 包级符号索引
+///
+/// 从源文件列表一次性构建，按声明名称索引类型、函数和方法。解析时只需检查同名的候选声明，
+/// 包限定和接收者在匹配候选时再检查，而不必遍历每个文件的所有声明。
+/// 索引同时持有被索引的源文件，一次分析中的所有查找共享同一个索引
+pub struct SymbolIndex<'a> {
+    source_files: &'a [SourceFile],
+    types: HashMap<&'a str, Vec<(&'a GoLanguageInfo, &'a GoTypeDefinition)>>,
+    functions: HashMap<&'a str, Vec<(&'a GoLanguageInfo, &'a GoDeclaration)>>,
+}
+
+/// 包限定引用的解析结果
+#[derive(Debug, Clone)]
+pub struct QualifiedSymbol {
+    /// 限定符对应的导入路径，引用文件中没有匹配的导入时为 None
+    pub import_path: Option<String>,
+    /// 定义所在包的包名
+    pub package: String,
+    /// 被引用的声明
+    pub declaration: GoDeclaration,
 }
 
 /// 类型分析器
 pub struct TypeAnalyzer;
 
+/// 方法签名中的参数类型列表和返回类型列表，参数类型附带是否为可变参数
+type MethodSignature = (Vec<(GoType, bool)>, Vec<GoType>);
+
 /// 依赖关系信息
 #[derive(Debug, Clone)]
 pub struct Dependency {
@@ -65,6 +109,141 @@ pub struct FunctionCall {
     pub package: Option<String>,
 }
 
+/// 函数体增长信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionGrowth {
+    pub name: String,
+    pub receiver: Option<String>,
+    /// 变更前的函数行数
+    pub old_lines: u32,
+    /// 变更后的函数行数
+    pub new_lines: u32,
+    /// 新旧行数之比，例如 2.0 表示函数体翻倍
+    pub growth_ratio: f64,
+}
+
+/// 结构体字段序列化标签的变化
+///
+/// 例如 `json:"name"` 变为 `json:"full_name"`，即使 Go 类型本身没有变化，
+/// 序列化后的数据格式也会随之改变
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TagChange {
+    /// 字段所属的结构体类型名
+    pub type_name: String,
+    pub field_name: String,
+    /// 标签键（`json`、`xml` 或 `yaml`）
+    pub tag_key: String,
+    /// 变更前的标签值，未设置该标签时为 None
+    pub old_value: Option<String>,
+    /// 变更后的标签值，未设置该标签时为 None
+    pub new_value: Option<String>,
+}
+
+impl TagChange {
+    /// 会影响序列化格式的标签键
+    pub const SERIALIZATION_KEYS: [&'static str; 3] = ["json", "xml", "yaml"];
+
+    /// 序列化名称是否发生变化（仅修改 `omitempty` 等选项不算）
+    pub fn is_wire_breaking(&self) -> bool {
+        self.effective_name(self.old_value.as_deref())
+            != self.effective_name(self.new_value.as_deref())
+    }
+
+    /// 字段实际使用的序列化名称：标签值逗号前的部分，缺省时使用字段名
+    fn effective_name(&self, value: Option<&str>) -> String {
+        let name = value
+            .and_then(|value| value.split(',').next())
+            .unwrap_or_default();
+        match name {
+            "" if self.tag_key == "yaml" => self.field_name.to_lowercase(),
+            "" => self.field_name.clone(),
+            name => name.to_string(),
+        }
+    }
+}
+
+impl<'a> SymbolIndex<'a> {
+    /// 按 `lookup_order` 给出的文件顺序索引 `source_files` 中的声明
+    ///
+    /// 同名候选保持文件和声明的先后顺序，解析时返回第一个匹配的候选
+    pub fn new<I>(source_files: &'a [SourceFile], lookup_order: I) -> Self
+    where
+        I: IntoIterator<Item = &'a SourceFile>,
+    {
+        let mut types: HashMap<_, Vec<_>> = HashMap::new();
+        let mut functions: HashMap<_, Vec<_>> = HashMap::new();
+
+        for source_file in lookup_order {
+            let Some(go_info) = source_file
+                .language_specific
+                .as_any()
+                .downcast_ref::<GoLanguageInfo>()
+            else {
+                continue;
+            };
+
+            for declaration in go_info.declarations() {
+                let Some(declaration) = declaration.as_any().downcast_ref::<GoDeclaration>() else {
+                    continue;
+                };
+                match declaration {
+                    GoDeclaration::Type(type_def) => types
+                        .entry(type_def.name.as_str())
+                        .or_default()
+                        .push((go_info, type_def)),
+                    GoDeclaration::Function(func) | GoDeclaration::Method(func) => functions
+                        .entry(func.name.as_str())
+                        .or_default()
+                        .push((go_info, declaration)),
+                    _ => {}
+                }
+            }
+        }
+
+        Self {
+            source_files,
+            types,
+            functions,
+        }
+    }
+
+    /// 被索引的源文件
+    pub fn source_files(&self) -> &'a [SourceFile] {
+        self.source_files
+    }
+
+    /// 获取指定名称的类型定义候选
+    pub fn types_named(&self, name: &str) -> &[(&'a GoLanguageInfo, &'a GoTypeDefinition)] {
+        self.types.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// 获取指定名称的函数和方法候选
+    pub fn functions_named(&self, name: &str) -> &[(&'a GoLanguageInfo, &'a GoDeclaration)] {
+        self.functions
+            .get(name)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// 按名称查找类型定义，结果与 [`DependencyResolver::find_type_definition`] 相同
+    pub fn find_type(&self, name: &str) -> Option<&'a GoTypeDefinition> {
+        self.types_named(name)
+            .first()
+            .map(|(_, type_def)| *type_def)
+    }
+
+    /// 按名称查找函数或方法，结果与 [`DependencyResolver::find_function_definition`] 相同
+    pub fn find_function(&self, name: &str) -> Option<&'a GoFunctionInfo> {
+        self.functions_named(name)
+            .first()
+            .and_then(|(_, declaration)| match declaration {
+                GoDeclaration::Function(func) | GoDeclaration::Method(func) => Some(func),
+                _ => None,
+            })
+    }
+}
+
 impl Default for DependencyResolver {
     fn default() -> Self {
         Self::new()
@@ -76,6 +255,7 @@ impl DependencyResolver {
     pub fn new() -> Self {
         Self {
             project_module_path: None,
+            build_constraints: None,
         }
     }
 
@@ -83,16 +263,47 @@ impl DependencyResolver {
     pub fn new_with_project_path(project_module_path: String) -> Self {
         Self {
             project_module_path: Some(project_module_path),
+            build_constraints: None,
         }
     }
 
+    /// 设置构建约束
+    ///
+    /// 同一声明分布在多个受构建标签约束的文件中（如 `foo_linux.go` 与 `foo_windows.go`）时，
+    /// 解析会选择满足约束的实现，而不是第一个找到的实现
+    pub fn with_build_constraints(mut self, constraints: BuildConstraints) -> Self {
+        self.build_constraints = Some(constraints);
+        self
+    }
+
+    /// 获取构建约束
+    pub fn build_constraints(&self) -> Option<&BuildConstraints> {
+        self.build_constraints.as_ref()
+    }
+
+    /// 按构建约束排列待查找的源文件：满足约束的文件在前，其余文件作为后备
+    fn files_by_constraints<'a>(&self, source_files: &'a [SourceFile]) -> Vec<&'a SourceFile> {
+        let Some(constraints) = &self.build_constraints else {
+            return source_files.iter().collect();
+        };
+        let (mut preferred, fallback): (Vec<_>, Vec<_>) = source_files
+            .iter()
+            .partition(|file| constraints.matches(file));
+        preferred.extend(fallback);
+        preferred
+    }
+
     /// 从项目根目录的go.mod文件中读取模块路径
     pub fn from_project_root<P: AsRef<std::path::Path>>(project_root: P) -> Result<Self> {
         let go_mod_path = project_root.as_ref().join("go.mod");
 
         if go_mod_path.exists() {
-            let content =
-                std::fs::read_to_string(&go_mod_path).map_err(SemanticDiffError::IoError)?;
+            let content = std::fs::read_to_string(&go_mod_path).map_err(|e| {
+                SemanticDiffError::ModuleResolution {
+                    detail: format!("failed to read {}", go_mod_path.display()),
+                    source: Some(e),
+                }
+            })?;
 
             if let Some(module_path) = Self::extract_module_path_from_go_mod(&content) {
                 Ok(Self::new_with_project_path(module_path))
@@ -116,38 +327,99 @@ impl DependencyResolver {
         None
     }
 
+    /// 为源文件列表构建符号索引
+    ///
+    /// 索引按构建约束排列文件，满足约束的文件中的声明优先被解析
+    pub fn build_index<'a>(&self, source_files: &'a [SourceFile]) -> SymbolIndex<'a> {
+        SymbolIndex::new(source_files, self.files_by_constraints(source_files))
+    }
+
     /// 解析类型引用，查找项目内部类型定义
     ///
-    /// 在提供的源文件列表中查找指定类型的定义
+    /// 在符号索引中查找指定类型的定义
     pub fn resolve_type(
         &self,
         type_ref: &TypeReference,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
     ) -> Option<GoTypeDefinition> {
-        // 遍历所有源文件查找类型定义
-        for source_file in source_files {
-            if let Some(type_def) = self.find_type_in_file(type_ref, source_file) {
-                return Some(type_def);
-            }
-        }
-        None
+        index
+            .types_named(&type_ref.name)
+            .iter()
+            .find(|(go_info, type_def)| self.type_matches(type_ref, type_def, go_info))
+            .map(|(_, type_def)| (*type_def).clone())
     }
 
     /// 解析函数调用，查找项目内部函数定义
     ///
-    /// 在提供的源文件列表中查找指定函数的定义
+    /// 在符号索引中查找指定函数或方法的定义。接收者类型上找不到的方法按嵌入关系
+    /// 在被嵌入类型上查找，即 Go 的方法提升（`admin.Save()` 中 `Save` 定义在嵌入的 `User` 上）
     pub fn resolve_function(
         &self,
         func_call: &FunctionCall,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
+    ) -> Option<GoFunctionInfo> {
+        self.resolve_function_direct(func_call, index).or_else(|| {
+            let receiver = func_call.receiver.as_deref()?;
+            Self::promoted_receivers(receiver, index)
+                .into_iter()
+                .find_map(|embedded| {
+                    let promoted = FunctionCall {
+                        receiver: Some(embedded),
+                        ..func_call.clone()
+                    };
+                    self.resolve_function_direct(&promoted, index)
+                })
+        })
+    }
+
+    /// 按函数名称和接收者在符号索引中直接查找，不考虑方法提升
+    fn resolve_function_direct(
+        &self,
+        func_call: &FunctionCall,
+        index: &SymbolIndex,
     ) -> Option<GoFunctionInfo> {
-        // 遍历所有源文件查找函数定义
-        for source_file in source_files {
-            if let Some(func_info) = self.find_function_in_file(func_call, source_file) {
-                return Some(func_info);
+        index
+            .functions_named(&func_call.name)
+            .iter()
+            .find_map(|(go_info, declaration)| match declaration {
+                GoDeclaration::Function(func_info)
+                    if self.function_matches(func_call, func_info, go_info) =>
+                {
+                    Some(func_info.clone())
+                }
+                GoDeclaration::Method(method_info)
+                    if self.method_matches(func_call, method_info, go_info) =>
+                {
+                    Some(method_info.clone())
+                }
+                _ => None,
+            })
+    }
+
+    /// 收集 `receiver` 类型直接和间接嵌入的类型名称
+    ///
+    /// 按嵌入深度由浅到深排列，与 Go 选择提升方法时浅层优先的规则一致；
+    /// 嵌入字段名已去掉指针和包限定符，循环嵌入只访问一次
+    fn promoted_receivers(receiver: &str, index: &SymbolIndex) -> Vec<String> {
+        let mut visited = HashSet::from([receiver.to_string()]);
+        let mut queue = VecDeque::from([receiver.to_string()]);
+        let mut promoted = Vec::new();
+
+        while let Some(type_name) = queue.pop_front() {
+            let embedded = index
+                .types_named(&type_name)
+                .iter()
+                .flat_map(|(_, type_def)| &type_def.fields)
+                .filter(|field| field.embedded);
+            for field in embedded {
+                if visited.insert(field.name.clone()) {
+                    promoted.push(field.name.clone());
+                    queue.push_back(field.name.clone());
+                }
             }
         }
-        None
+
+        promoted
     }
 
     /// 检查导入是否为外部依赖
@@ -160,7 +432,7 @@ impl DependencyResolver {
         }
 
         // 然后检查是否为标准库
-        if self.is_standard_library(&import.path) {
+        if Self::is_standard_library(&import.path) {
             return true;
         }
 
@@ -192,7 +464,139 @@ impl DependencyResolver {
             name: type_name.to_string(),
             package: None,
         };
-        self.resolve_type(&type_ref, source_files)
+        // 单次查找直接遍历源文件，满足构建约束的文件优先；批量查找应使用符号索引
+        self.files_by_constraints(source_files)
+            .into_iter()
+            .find_map(|source_file| self.find_type_in_file(&type_ref, source_file))
+    }
+
+    /// 解析 `from_file` 第 `line` 行（从 1 开始）引用的类型
+    ///
+    /// 包限定的引用按 [`Self::find_qualified_type_definition`] 查找，其余按名称查找；
+    /// 找不到定义时返回 [`SemanticDiffError::UnresolvedType`]
+    pub fn resolve_type_reference(
+        &self,
+        type_ref: &TypeReference,
+        from_file: &Path,
+        line: usize,
+        source_files: &[SourceFile],
+    ) -> Result<GoTypeDefinition> {
+        let resolved = match &type_ref.package {
+            Some(package) => self.find_qualified_type_definition(
+                package,
+                &type_ref.name,
+                from_file,
+                source_files,
+            ),
+            None => self.find_type_definition(&type_ref.name, source_files),
+        };
+
+        resolved.ok_or_else(|| SemanticDiffError::UnresolvedType {
+            name: match &type_ref.package {
+                Some(package) => format!("{package}.{}", type_ref.name),
+                None => type_ref.name.clone(),
+            },
+            file: from_file.to_path_buf(),
+            line,
+        })
+    }
+
+    /// 查找包限定的类型定义（如 `models.Base`）
+    ///
+    /// 通过 `from_file` 的导入声明把限定符解析为导入路径，只在对应包的文件中查找；
+    /// `from_file` 中没有匹配的导入时按包名查找
+    pub fn find_qualified_type_definition(
+        &self,
+        package: &str,
+        type_name: &str,
+        from_file: &Path,
+        source_files: &[SourceFile],
+    ) -> Option<GoTypeDefinition> {
+        let imports = source_files
+            .iter()
+            .find(|file| file.path == from_file)
+            .map(|file| file.language_specific.imports())
+            .unwrap_or_default();
+
+        match self
+            .resolve_qualified(package, type_name, imports, source_files)?
+            .declaration
+        {
+            GoDeclaration::Type(type_def) => Some(type_def),
+            _ => None,
+        }
+    }
+
+    /// 解析包限定引用（如 `models.User`，或别名导入后的 `m.User`）
+    ///
+    /// 通过引用文件的导入表把限定符映射到导入路径，只在对应包的文件中查找名为 `name` 的
+    /// 类型、函数、常量或变量，返回定义所在的包和定义本身；导入中没有该限定符时按包名查找
+    pub fn resolve_qualified(
+        &self,
+        qualifier: &str,
+        name: &str,
+        imports: &[crate::parser::Import],
+        source_files: &[SourceFile],
+    ) -> Option<QualifiedSymbol> {
+        let import = imports
+            .iter()
+            .find(|import| import.local_name() == qualifier);
+        let import_path = import.map(|import| import.path.as_str());
+
+        // 别名导入时限定符与包名不同，包名按导入路径推断
+        let package_name = import
+            .map(crate::parser::Import::package_name)
+            .unwrap_or(qualifier);
+        // 导入路径属于当前项目时，还要求文件所在目录与导入路径对应，以区分同名的不同包
+        let package_dir = import_path
+            .zip(self.project_module_path.as_deref())
+            .and_then(|(path, module_path)| {
+                path.strip_prefix(module_path)
+                    .map(|relative| relative.trim_start_matches('/'))
+            });
+
+        for source_file in self.files_by_constraints(source_files) {
+            let Some(go_info) = source_file
+                .language_specific
+                .as_any()
+                .downcast_ref::<GoLanguageInfo>()
+            else {
+                continue;
+            };
+            if go_info.package_name() != package_name {
+                continue;
+            }
+            if let Some(package_dir) = package_dir {
+                let in_package_dir = source_file
+                    .path
+                    .parent()
+                    .is_some_and(|dir| dir.ends_with(package_dir));
+                if !in_package_dir {
+                    continue;
+                }
+            }
+
+            let declaration = go_info.declarations().iter().find_map(|declaration| {
+                let declaration = declaration.as_any().downcast_ref::<GoDeclaration>()?;
+                let declared_name = match declaration {
+                    GoDeclaration::Type(type_def) => &type_def.name,
+                    GoDeclaration::Function(func) => &func.name,
+                    GoDeclaration::Constant(constant) => &constant.name,
+                    GoDeclaration::Variable(variable) => &variable.name,
+                    // 方法只能通过接收者引用，不能被包名限定
+                    _ => return None,
+                };
+                (declared_name == name).then(|| declaration.clone())
+            });
+            if let Some(declaration) = declaration {
+                return Some(QualifiedSymbol {
+                    import_path: import_path.map(str::to_string),
+                    package: package_name.to_string(),
+                    declaration,
+                });
+            }
+        }
+        None
     }
 
     /// 查找函数定义
@@ -208,7 +612,10 @@ impl DependencyResolver {
             receiver: None,
             package: None,
         };
-        self.resolve_function(&func_call, source_files)
+        // 单次查找直接遍历源文件，满足构建约束的文件优先；批量查找应使用符号索引
+        self.files_by_constraints(source_files)
+            .into_iter()
+            .find_map(|source_file| self.find_function_in_file(&func_call, source_file))
     }
 
     /// 提取函数中的所有依赖
@@ -217,7 +624,7 @@ impl DependencyResolver {
     pub fn extract_function_dependencies(
         &self,
         function: &GoFunctionInfo,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
     ) -> Vec<Dependency> {
         let mut dependencies = Vec::new();
 
@@ -225,7 +632,7 @@ impl DependencyResolver {
         for param in &function.parameters {
             let type_refs = self.parse_type_string(&param.param_type.name);
             for type_ref in type_refs {
-                if let Some(type_def) = self.resolve_type(&type_ref, source_files) {
+                if let Some(type_def) = self.resolve_type(&type_ref, index) {
                     dependencies.push(Dependency {
                         name: type_def.name.clone(),
                         dependency_type: DependencyType::Type,
@@ -239,7 +646,7 @@ impl DependencyResolver {
         for return_type in &function.return_types {
             let type_refs = self.parse_type_string(&return_type.name);
             for type_ref in type_refs {
-                if let Some(type_def) = self.resolve_type(&type_ref, source_files) {
+                if let Some(type_def) = self.resolve_type(&type_ref, index) {
                     dependencies.push(Dependency {
                         name: type_def.name.clone(),
                         dependency_type: DependencyType::Type,
@@ -250,9 +657,9 @@ impl DependencyResolver {
         }
 
         // 从函数体中提取类型引用
-        let type_refs = self.extract_type_references_from_code(&function.body);
+        let type_refs = self.extract_type_references(&function.body_references);
         for type_ref in type_refs {
-            if let Some(type_def) = self.resolve_type(&type_ref, source_files) {
+            if let Some(type_def) = self.resolve_type(&type_ref, index) {
                 dependencies.push(Dependency {
                     name: type_def.name.clone(),
                     dependency_type: DependencyType::Type,
@@ -261,10 +668,44 @@ impl DependencyResolver {
             }
         }
 
-        // 从函数体中提取函数调用
-        let func_calls = self.extract_function_calls_from_code(&function.body);
-        for func_call in func_calls {
-            if let Some(func_info) = self.resolve_function(&func_call, source_files) {
+        // 从函数体中提取函数调用，函数所在文件的导入决定 `x.F()` 中的 `x` 是否为包名
+        let package_names: Vec<String> = index
+            .source_files()
+            .iter()
+            .find(|file| file.path == function.file_path)
+            .map(|file| {
+                file.language_specific
+                    .imports()
+                    .iter()
+                    .map(|import| import.local_name().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let func_calls = self.extract_function_calls(&function.body_references, &package_names);
+        for mut func_call in func_calls {
+            // 接收者或参数上的方法调用按其类型解析；局部变量等无法确定类型的接收者
+            // 只按方法名匹配，`T.Method` 形式的方法表达式保留类型名
+            if let Some(receiver) = func_call.receiver.take() {
+                let receiver_type = function
+                    .receiver
+                    .as_ref()
+                    .filter(|recv| recv.name == receiver)
+                    .map(|recv| recv.type_name.clone())
+                    .or_else(|| {
+                        function
+                            .parameters
+                            .iter()
+                            .find(|param| param.name == receiver)
+                            .map(|param| param.param_type.name.trim_start_matches('*').to_string())
+                    });
+                func_call.receiver = match receiver_type {
+                    Some(receiver_type) => Some(receiver_type),
+                    None if index.types_named(&receiver).is_empty() => None,
+                    None => Some(receiver),
+                };
+            }
+
+            if let Some(func_info) = self.resolve_function(&func_call, index) {
                 dependencies.push(Dependency {
                     name: func_info.name.clone(),
                     dependency_type: DependencyType::Function,
@@ -373,19 +814,14 @@ impl DependencyResolver {
 
         // 处理包限定的类型名称
         if let Some(package) = &type_ref.package {
-            // 检查是否有对应的导入别名
-            for import in go_info.imports() {
-                if let Some(alias) = &import.alias {
-                    if alias == package && type_ref.name == type_def.name {
-                        return true;
-                    }
-                } else {
-                    // 使用包路径的最后一部分作为包名
-                    let package_name = import.path.split('/').next_back().unwrap_or(&import.path);
-                    if package_name == package && type_ref.name == type_def.name {
-                        return true;
-                    }
-                }
+            // 限定符与某个导入在文件中使用的名称（别名或推断的包名）一致
+            if type_ref.name == type_def.name
+                && go_info
+                    .imports()
+                    .iter()
+                    .any(|import| import.local_name() == package)
+            {
+                return true;
             }
         }
 
@@ -411,17 +847,13 @@ impl DependencyResolver {
 
         // 处理包限定的函数名称
         if let Some(package) = &func_call.package {
-            for import in go_info.imports() {
-                if let Some(alias) = &import.alias {
-                    if alias == package && func_call.name == func_info.name {
-                        return true;
-                    }
-                } else {
-                    let package_name = import.path.split('/').next_back().unwrap_or(&import.path);
-                    if package_name == package && func_call.name == func_info.name {
-                        return true;
-                    }
-                }
+            if func_call.name == func_info.name
+                && go_info
+                    .imports()
+                    .iter()
+                    .any(|import| import.local_name() == package)
+            {
+                return true;
             }
         }
 
@@ -451,8 +883,21 @@ impl DependencyResolver {
         false
     }
 
+    /// 按 gofmt 的分组习惯排序导入：标准库在前、其他包在后，组内按路径字母序
+    pub fn sort_imports(imports: &mut [crate::parser::Import]) {
+        imports.sort_by(|a, b| {
+            (!Self::is_standard_library(&a.path), &a.path, &a.alias).cmp(&(
+                !Self::is_standard_library(&b.path),
+                &b.path,
+                &b.alias,
+            ))
+        });
+    }
+
     /// 检查是否为标准库
-    fn is_standard_library(&self, import_path: &str) -> bool {
+    ///
+    /// 除常见标准库包外，与 goimports 一致，把第一段路径不含 `.` 的包视为标准库
+    pub fn is_standard_library(import_path: &str) -> bool {
         // Go 标准库的常见包
         const STANDARD_PACKAGES: &[&str] = &[
             "fmt",
@@ -488,7 +933,12 @@ impl DependencyResolver {
         // 检查是否为标准库包
         STANDARD_PACKAGES.contains(&import_path) ||
         // 或者是标准库的子包
-        STANDARD_PACKAGES.iter().any(|&std_pkg| import_path.starts_with(&format!("{std_pkg}/")))
+        STANDARD_PACKAGES.iter().any(|&std_pkg| import_path.starts_with(&format!("{std_pkg}/"))) ||
+        // 或者第一段路径不像域名
+        import_path
+            .split('/')
+            .next()
+            .is_some_and(|first| !first.is_empty() && !first.contains('.'))
     }
 
     /// 检查是否为项目内部包
@@ -527,91 +977,55 @@ impl DependencyResolver {
             && !path_str.contains(".cache/")
     }
 
-    /// 从代码中提取类型引用
-    pub fn extract_type_references_from_code(&self, code: &str) -> Vec<TypeReference> {
-        let mut type_refs = Vec::new();
-
-        // 简单的正则表达式匹配（实际实现中应该使用语法树分析）
-        // 这里提供一个基础实现，实际应该通过 tree-sitter 分析
-
-        // 匹配类型声明模式，如 var x Type, func() Type, *Type 等
-        let patterns = [
-            r"\b([A-Z][a-zA-Z0-9_]*)\s*\{",        // 结构体字面量
-            r"\*([A-Z][a-zA-Z0-9_]*)\b",           // 指针类型
-            r"\[\]([A-Z][a-zA-Z0-9_]*)\b",         // 切片类型
-            r"var\s+\w+\s+([A-Z][a-zA-Z0-9_]*)\b", // 变量声明
-            r":\s*([A-Z][a-zA-Z0-9_]*)\b",         // 短变量声明
-        ];
-
-        for pattern in &patterns {
-            if let Ok(re) = regex::Regex::new(pattern) {
-                for cap in re.captures_iter(code) {
-                    if let Some(type_name) = cap.get(1) {
-                        let type_ref = TypeReference {
-                            name: type_name.as_str().to_string(),
-                            package: None,
-                        };
-                        if !type_refs
-                            .iter()
-                            .any(|t: &TypeReference| t.name == type_ref.name)
-                        {
-                            type_refs.push(type_ref);
-                        }
-                    }
-                }
-            }
-        }
-
-        type_refs
+    /// 从函数体的类型引用中提取非内置类型
+    ///
+    /// 类型引用在提取函数信息时从语法树中收集，见 [`BodyReferences::type_references`]
+    pub fn extract_type_references(&self, references: &BodyReferences) -> Vec<TypeReference> {
+        let type_analyzer = TypeAnalyzer::new();
+        references
+            .type_references()
+            .filter(|(package, name)| package.is_some() || !type_analyzer.is_builtin_type(name))
+            .map(|(package, name)| TypeReference {
+                name: name.to_string(),
+                package: package.map(str::to_string),
+            })
+            .collect()
     }
 
-    /// 从代码中提取函数调用
-    fn extract_function_calls_from_code(&self, code: &str) -> Vec<FunctionCall> {
+    /// 从函数体的调用中提取函数调用
+    ///
+    /// 调用在提取函数信息时从语法树中收集，因此 `if (`、字符串字面量中的 `name(`
+    /// 不会被误认为调用，内置类型的转换 `int(x)` 会被忽略。`pkg.Func()` 形式中 `pkg` 属于
+    /// `package_names` 时记录为包限定调用，其他 `x.Method()` 形式记录为方法调用
+    fn extract_function_calls(
+        &self,
+        references: &BodyReferences,
+        package_names: &[String],
+    ) -> Vec<FunctionCall> {
+        let type_analyzer = TypeAnalyzer::new();
         let mut func_calls = Vec::new();
-
-        // 简单的正则表达式匹配函数调用
-        let patterns = [
-            r"([a-zA-Z_][a-zA-Z0-9_]*)\s*\(", // 简单函数调用
-            r"([a-zA-Z_][a-zA-Z0-9_]*)\.([a-zA-Z_][a-zA-Z0-9_]*)\s*\(", // 方法调用
-        ];
-
-        // 匹配简单函数调用
-        if let Ok(re) = regex::Regex::new(patterns[0]) {
-            for cap in re.captures_iter(code) {
-                if let Some(func_name) = cap.get(1) {
-                    let func_call = FunctionCall {
-                        name: func_name.as_str().to_string(),
-                        receiver: None,
-                        package: None,
-                    };
-                    if !func_calls
-                        .iter()
-                        .any(|f: &FunctionCall| f.name == func_call.name)
-                    {
-                        func_calls.push(func_call);
-                    }
-                }
-            }
-        }
-
-        // 匹配方法调用
-        if let Ok(re) = regex::Regex::new(patterns[1]) {
-            for cap in re.captures_iter(code) {
-                if let (Some(receiver), Some(method)) = (cap.get(1), cap.get(2)) {
-                    let func_call = FunctionCall {
-                        name: method.as_str().to_string(),
-                        receiver: Some(receiver.as_str().to_string()),
-                        package: None,
-                    };
-                    if !func_calls.iter().any(|f: &FunctionCall| {
-                        f.name == func_call.name && f.receiver == func_call.receiver
-                    }) {
-                        func_calls.push(func_call);
+        for call in references.call_sites() {
+            let func_call = match &call.operand {
+                // 内置类型的转换在语法上也是调用
+                None if type_analyzer.is_builtin_type(&call.name) => continue,
+                None => FunctionCall {
+                    name: call.name.clone(),
+                    receiver: None,
+                    package: None,
+                },
+                Some(operand) => {
+                    let is_package = package_names.contains(operand);
+                    FunctionCall {
+                        name: call.name.clone(),
+                        receiver: (!is_package).then(|| operand.clone()),
+                        package: is_package.then(|| operand.clone()),
                     }
                 }
+            };
+            if !func_calls.contains(&func_call) {
+                func_calls.push(func_call);
             }
         }
-
         func_calls
     }
 
@@ -719,6 +1133,49 @@ impl SourceAnalyzer {
         Ok(successful)
     }
 
+    /// 并发分析多个文件，失败的文件记录为诊断信息
+    ///
+    /// 每个文件的错误交给 `recovery_strategy` 处理：可恢复的错误记录到 `diagnostics`
+    /// 后跳过该文件，不可恢复的错误中止分析并返回。成功的文件按输入顺序返回
+    pub fn analyze_files_with_diagnostics(
+        file_paths: &[PathBuf],
+        recovery_strategy: &ErrorRecoveryStrategy,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Vec<SourceFile>> {
+        info!("开始并发分析 {} 个文件（收集诊断信息）", file_paths.len());
+
+        let results: Vec<_> = file_paths
+            .par_iter()
+            .map(|file_path| {
+                recovery_strategy.execute_with_retry(|| {
+                    let mut analyzer = Self::new_for_file(file_path)?;
+                    analyzer.analyze_file(file_path)
+                })
+            })
+            .collect();
+
+        let mut successful = Vec::new();
+        for (file_path, result) in file_paths.iter().zip(results) {
+            match result {
+                Ok(source_file) => successful.push(source_file),
+                Err(error) => recovery_strategy.recover(
+                    error,
+                    Some(file_path.as_path()),
+                    None,
+                    diagnostics,
+                )?,
+            }
+        }
+
+        info!(
+            "文件分析完成: 成功 {}, 诊断 {}",
+            successful.len(),
+            diagnostics.len()
+        );
+
+        Ok(successful)
+    }
+
     /// 批量分析文件
     ///
     /// 将文件分批处理，避免内存使用过多
@@ -782,6 +1239,14 @@ impl SourceAnalyzer {
         })
     }
 
+    /// 设置单个文件的解析超时时间，`None` 表示不限制
+    ///
+    /// 超时的文件返回 [`SemanticDiffError::ParseTimeout`]，避免异常的输入拖住整个分析过程
+    pub fn with_parse_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.parser.set_timeout(timeout);
+        self
+    }
+
     /// 分析文件的核心功能
     ///
     /// 读取文件内容，解析为语法树，并提取语言特定信息
@@ -794,18 +1259,82 @@ impl SourceAnalyzer {
             ))
         })?;
 
+        self.analyze_source(file_path, source_code)
+    }
+
+    /// 分析内存中的源码
+    ///
+    /// 与 `analyze_file` 相同，但源码由调用方提供（例如来自 Git 历史中的旧版本）
+    pub fn analyze_source(&mut self, file_path: &Path, source_code: String) -> Result<SourceFile> {
+        self.analyze_source_cached(file_path, source_code, None)
+    }
+
+    /// 分析文件，内容未变化时复用解析缓存中的语法树、包名、导入和声明
+    pub fn analyze_file_with_cache(
+        &mut self,
+        file_path: &Path,
+        cache: &ParserCache,
+    ) -> Result<SourceFile> {
+        let source_code = fs::read_to_string(file_path).map_err(|e| {
+            SemanticDiffError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("Failed to read file {}: {}", file_path.display(), e),
+            ))
+        })?;
+
+        self.analyze_source_cached(file_path, source_code, Some(cache))
+    }
+
+    /// 分析源码，提供 `cache` 时通过缓存获取语言特定信息
+    fn analyze_source_cached(
+        &mut self,
+        file_path: &Path,
+        source_code: String,
+        cache: Option<&ParserCache>,
+    ) -> Result<SourceFile> {
+        // 缓存命中时直接复用语法树，未命中时解析源码
+        if let (SupportedLanguage::Go, Some(cache)) = (self.language, cache) {
+            // 两个回调分别需要可变的解析器和整个分析器，通过 RefCell 在调用时再借用
+            let analyzer = RefCell::new(&mut *self);
+            let (parse, syntax_tree) = cache.get_or_parse_with(
+                file_path,
+                &source_code,
+                || analyzer.borrow_mut().parser.parse_source(&source_code),
+                |syntax_tree, content_hash| {
+                    let info = analyzer.borrow().extract_go_specific_info(
+                        syntax_tree,
+                        &source_code,
+                        file_path,
+                    )?;
+                    Ok(CachedParse::from_language_info(content_hash, &info))
+                },
+            )?;
+            return Ok(SourceFile {
+                path: file_path.to_path_buf(),
+                build_constraint: Self::parse_build_constraints(&source_code),
+                source_code,
+                syntax_tree,
+                language: self.language,
+                language_specific: Box::new(parse.to_language_info()),
+            });
+        }
+
         // 解析源码为语法树
         let syntax_tree = self.parser.parse_source(&source_code)?;
 
         // 根据语言类型提取特定信息
-        let language_specific = match self.language {
+        let language_specific: Box<dyn LanguageSpecificInfo> = match self.language {
             SupportedLanguage::Go => {
-                self.extract_go_specific_info(&syntax_tree, &source_code, file_path)?
+                Box::new(self.extract_go_specific_info(&syntax_tree, &source_code, file_path)?)
             } // 未来可以在这里添加其他语言的支持
+            SupportedLanguage::Custom(_) => Box::new(crate::parser::GenericLanguageInfo {
+                language: self.language,
+            }),
         };
 
         Ok(SourceFile {
             path: file_path.to_path_buf(),
+            build_constraint: Self::parse_build_constraints(&source_code),
             source_code,
             syntax_tree,
             language: self.language,
@@ -813,6 +1342,14 @@ impl SourceAnalyzer {
         })
     }
 
+    /// 读取文件头部的构建约束
+    ///
+    /// 返回 package 子句之前的 `//go:build` 表达式；只有旧式 `// +build` 行时返回转换后的等价表达式，
+    /// 没有约束时返回 `None`
+    pub fn parse_build_constraints(source_code: &str) -> Option<String> {
+        crate::constraints::parse_build_expression(source_code)
+    }
+
     /// 查找变更函数的功能
     ///
     /// 根据差异块信息查找受影响的函数，支持多语言
@@ -845,15 +1382,775 @@ impl SourceAnalyzer {
         Ok(changed_functions)
     }
 
-    /// 查找包含指定行号的函数
-    fn find_function_containing_line(
+    /// 查找变更声明的功能
+    ///
+    /// 将差异块的行范围映射到包含它的顶层声明：函数、方法、`type`、`const` 或 `var`。
+    /// 落在分组声明括号上、不属于任何规格的行会把整个分组视为变更
+    pub fn find_changed_declarations(
         &self,
         source_file: &SourceFile,
-        line: u32,
-    ) -> Result<Option<GoFunctionInfo>> {
+        hunks: &[DiffHunk],
+    ) -> Result<Vec<ChangeTarget>> {
+        use crate::parser::GoDeclaration;
+
         let root = source_file.syntax_tree.root_node();
+        let source_code = &source_file.source_code;
+        let declarations: Vec<&GoDeclaration> = source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| decl.as_any().downcast_ref::<GoDeclaration>())
+            .collect();
 
-        // 查找包含该行的节点
+        let mut changed = Vec::new();
+        let mut seen = HashSet::new();
+
+        for hunk in hunks {
+            // 差异块行号从 1 开始，CST 行号从 0 开始；纯删除的差异块只检查删除位置所在的行
+            let start_row = hunk.new_start.saturating_sub(1);
+            let end_row = start_row + hunk.new_lines.saturating_sub(1);
+
+            for row in start_row..=end_row {
+                let Some(node) = self.navigator.find_node_containing_line(root, row) else {
+                    continue;
+                };
+
+                for decl_node in Self::enclosing_declarations(node) {
+                    if decl_node.kind() == "function_declaration"
+                        || decl_node.kind() == "method_declaration"
+                    {
+                        if seen.insert((decl_node.start_byte(), String::new())) {
+                            changed.push(ChangeTarget::Function(self.extract_function_info(
+                                decl_node,
+                                source_code,
+                                &source_file.path,
+                            )?));
+                        }
+                        continue;
+                    }
+
+                    let mut cursor = decl_node.walk();
+                    for name_node in decl_node.children_by_field_name("name", &mut cursor) {
+                        let name = &source_code[name_node.byte_range()];
+                        if !seen.insert((decl_node.start_byte(), name.to_string())) {
+                            continue;
+                        }
+
+                        let target =
+                            declarations
+                                .iter()
+                                .find_map(|decl| match (decl_node.kind(), decl) {
+                                    ("type_spec" | "type_alias", GoDeclaration::Type(t))
+                                        if t.name == name =>
+                                    {
+                                        Some(ChangeTarget::Type(t.clone()))
+                                    }
+                                    ("const_spec", GoDeclaration::Constant(c))
+                                        if c.name == name =>
+                                    {
+                                        Some(ChangeTarget::Constant(c.clone()))
+                                    }
+                                    ("var_spec", GoDeclaration::Variable(v)) if v.name == name => {
+                                        Some(ChangeTarget::Variable(v.clone()))
+                                    }
+                                    _ => None,
+                                });
+                        if let Some(target) = target {
+                            debug!(
+                                "Found changed declaration: {} in {:?}",
+                                name, source_file.path
+                            );
+                            changed.push(target);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// 向上查找包含节点的声明
+    ///
+    /// 函数和声明规格只返回自身；分组声明本身（例如 `const (` 所在的行）返回其中的所有规格
+    fn enclosing_declarations(node: tree_sitter::Node) -> Vec<tree_sitter::Node> {
+        let mut current = Some(node);
+        while let Some(node) = current {
+            match node.kind() {
+                "function_declaration"
+                | "method_declaration"
+                | "type_spec"
+                | "type_alias"
+                | "const_spec"
+                | "var_spec" => return vec![node],
+                "type_declaration" | "const_declaration" | "var_declaration" => {
+                    // 分组 `var (...)` 的规格位于 `var_spec_list` 节点内
+                    let mut cursor = node.walk();
+                    let mut groups: Vec<_> = node.named_children(&mut cursor).collect();
+                    if let Some(index) = groups.iter().position(|c| c.kind() == "var_spec_list") {
+                        let list = groups.remove(index);
+                        let mut list_cursor = list.walk();
+                        groups.extend(list.named_children(&mut list_cursor));
+                    }
+                    return groups
+                        .into_iter()
+                        .filter(|child| {
+                            matches!(
+                                child.kind(),
+                                "type_spec" | "type_alias" | "const_spec" | "var_spec"
+                            )
+                        })
+                        .collect();
+                }
+                _ => current = node.parent(),
+            }
+        }
+        Vec::new()
+    }
+
+    /// 检测同一文件内的函数原地重命名
+    ///
+    /// 比较变更前后两个版本中的函数：旧版本中消失的函数与新版本中新出现的函数，
+    /// 如果接收者、参数类型和函数体指纹一致，则视为一次重命名，而不是删除加新增
+    pub fn find_renamed_functions(
+        &self,
+        old_source: &SourceFile,
+        new_source: &SourceFile,
+    ) -> Vec<ChangeTarget> {
+        let old_functions = Self::collect_functions(old_source);
+        let new_functions = Self::collect_functions(new_source);
+
+        let function_key = |f: &GoFunctionInfo| {
+            let receiver = f.receiver.as_ref().map(|r| r.type_name.clone());
+            (receiver, f.name.clone())
+        };
+        let old_keys: HashSet<_> = old_functions.iter().map(|f| function_key(f)).collect();
+        let new_keys: HashSet<_> = new_functions.iter().map(|f| function_key(f)).collect();
+
+        // 只有在旧版本中消失的函数才可能是重命名的来源
+        let mut removed: Vec<(u64, &GoFunctionInfo)> = old_functions
+            .iter()
+            .filter(|f| !new_keys.contains(&function_key(f)))
+            .filter_map(|f| Self::function_fingerprint(f).map(|fp| (fp, *f)))
+            .collect();
+
+        let mut renamed = Vec::new();
+        for added in new_functions
+            .iter()
+            .filter(|f| !old_keys.contains(&function_key(f)))
+        {
+            let Some(fingerprint) = Self::function_fingerprint(added) else {
+                continue;
+            };
+
+            if let Some(index) = removed.iter().position(|(fp, _)| *fp == fingerprint) {
+                let (_, old_function) = removed.swap_remove(index);
+                debug!(
+                    "检测到函数重命名: {} -> {} ({:?})",
+                    old_function.name, added.name, new_source.path
+                );
+                renamed.push(ChangeTarget::Renamed {
+                    old_name: old_function.name.clone(),
+                    new_name: added.name.clone(),
+                    function: (*added).clone(),
+                });
+            }
+        }
+
+        renamed
+    }
+
+    /// 检测变更中被删除的函数和类型
+    ///
+    /// 比较变更前后两个版本的声明集合：只存在于旧版本中的函数（按接收者和函数名匹配）
+    /// 和类型（按类型名匹配）会作为 [`ChangeTarget::Removed`] 返回。
+    /// 已被识别为原地重命名的函数不会重复报告为删除
+    pub fn find_removed_declarations(
+        &self,
+        old_source: &SourceFile,
+        new_source: &SourceFile,
+    ) -> Vec<ChangeTarget> {
+        let function_key = |f: &GoFunctionInfo| {
+            let receiver = f.receiver.as_ref().map(|r| r.type_name.clone());
+            (receiver, f.name.clone())
+        };
+        let new_functions: HashSet<_> = Self::collect_functions(new_source)
+            .into_iter()
+            .map(function_key)
+            .collect();
+        let renamed: HashSet<String> = self
+            .find_renamed_functions(old_source, new_source)
+            .iter()
+            .filter_map(|target| match target {
+                ChangeTarget::Renamed { old_name, .. } => Some(old_name.clone()),
+                _ => None,
+            })
+            .collect();
+        let new_types: HashSet<&str> = Self::collect_types(new_source)
+            .into_iter()
+            .map(|t| t.name.as_str())
+            .collect();
+
+        let removed_functions = Self::collect_functions(old_source)
+            .into_iter()
+            .filter(|f| !new_functions.contains(&function_key(f)) && !renamed.contains(&f.name))
+            .map(|f| ChangeTarget::Function(f.clone()));
+        let removed_types = Self::collect_types(old_source)
+            .into_iter()
+            .filter(|t| !new_types.contains(t.name.as_str()))
+            .map(|t| ChangeTarget::Type(t.clone()));
+
+        removed_functions
+            .chain(removed_types)
+            .map(|declaration| {
+                debug!(
+                    "检测到被删除的声明: {} ({:?})",
+                    declaration.name(),
+                    new_source.path
+                );
+                ChangeTarget::Removed {
+                    declaration: Box::new(declaration),
+                }
+            })
+            .collect()
+    }
+
+    /// 查找被整体删除的文件中的声明
+    ///
+    /// 删除的文件没有新版本，其中所有顶层的函数、方法、类型、常量和变量都按源码顺序作为删除的声明返回
+    pub fn find_deleted_file_declarations(&self, old_source: &SourceFile) -> Vec<ChangeTarget> {
+        let mut removed: Vec<ChangeTarget> = old_source
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| decl.as_any().downcast_ref::<GoDeclaration>())
+            .map(|decl| {
+                let declaration = match decl {
+                    GoDeclaration::Function(f) | GoDeclaration::Method(f) => {
+                        ChangeTarget::Function(f.clone())
+                    }
+                    GoDeclaration::Type(t) => ChangeTarget::Type(t.clone()),
+                    GoDeclaration::Constant(c) => ChangeTarget::Constant(c.clone()),
+                    GoDeclaration::Variable(v) => ChangeTarget::Variable(v.clone()),
+                };
+                debug!(
+                    "检测到被删除文件中的声明: {} ({:?})",
+                    declaration.name(),
+                    old_source.path
+                );
+                ChangeTarget::Removed {
+                    declaration: Box::new(declaration),
+                }
+            })
+            .collect();
+
+        // 解析结果按声明种类分组，这里恢复源码中的顺序
+        removed.sort_by_key(|target| target.line_range().0);
+        removed
+    }
+
+    /// 获取变更目标在变更前后两个版本中的源码，用于左右对照渲染
+    ///
+    /// 旧版本在 `old_source` 中按种类和名称查找同一声明（函数还需匹配接收者，重命名的函数按旧名称查找），
+    /// 再按其行范围从变更前的文件内容中截取。删除的声明只有旧版本；
+    /// 新增的声明或没有变更前的文件时旧版本为空
+    pub fn target_versions(
+        &self,
+        target: &ChangeTarget,
+        old_source: Option<&SourceFile>,
+        new_source: &SourceFile,
+    ) -> TargetVersions {
+        if let ChangeTarget::Removed { declaration } = target {
+            return TargetVersions {
+                old: old_source
+                    .map(|old| Self::source_lines(&old.source_code, declaration.line_range()))
+                    .unwrap_or_default(),
+                new: String::new(),
+            };
+        }
+
+        let old = old_source
+            .and_then(|old| {
+                Self::find_previous_range(target, old)
+                    .map(|range| Self::source_lines(&old.source_code, range))
+            })
+            .unwrap_or_default();
+
+        TargetVersions {
+            old,
+            new: Self::source_lines(&new_source.source_code, target.line_range()),
+        }
+    }
+
+    /// 在变更前的源文件中查找与变更目标对应的声明，返回其起止行号
+    fn find_previous_range(target: &ChangeTarget, old_source: &SourceFile) -> Option<(u32, u32)> {
+        let receiver_of = |f: &GoFunctionInfo| f.receiver.as_ref().map(|r| r.type_name.clone());
+
+        old_source
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| decl.as_any().downcast_ref::<GoDeclaration>())
+            .find_map(|decl| match (target, decl) {
+                (
+                    ChangeTarget::Function(f),
+                    GoDeclaration::Function(old) | GoDeclaration::Method(old),
+                ) if old.name == f.name && receiver_of(old) == receiver_of(f) => {
+                    Some((old.start_line, old.end_line))
+                }
+                (
+                    ChangeTarget::Renamed {
+                        old_name, function, ..
+                    },
+                    GoDeclaration::Function(old) | GoDeclaration::Method(old),
+                ) if &old.name == old_name && receiver_of(old) == receiver_of(function) => {
+                    Some((old.start_line, old.end_line))
+                }
+                (ChangeTarget::Type(t), GoDeclaration::Type(old)) if old.name == t.name => {
+                    Some((old.start_line, old.end_line))
+                }
+                (ChangeTarget::Variable(v), GoDeclaration::Variable(old)) if old.name == v.name => {
+                    Some((old.start_line, old.end_line))
+                }
+                (ChangeTarget::Constant(c), GoDeclaration::Constant(old)) if old.name == c.name => {
+                    Some((old.start_line, old.end_line))
+                }
+                _ => None,
+            })
+    }
+
+    /// 截取源码中从 0 开始计数的起止行（包含两端）
+    fn source_lines(source_code: &str, (start_line, end_line): (u32, u32)) -> String {
+        source_code
+            .lines()
+            .skip(start_line as usize)
+            .take(end_line.saturating_sub(start_line) as usize + 1)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// 检测变更后显著增长的函数
+    ///
+    /// 按接收者和函数名匹配变更前后的同一函数，比较其行数；
+    /// 行数增长超过 `threshold_percent` 百分比的函数会连同 `growth_ratio` 一起返回
+    pub fn find_grown_functions(
+        &self,
+        old_source: &SourceFile,
+        new_source: &SourceFile,
+        threshold_percent: f64,
+    ) -> Vec<FunctionGrowth> {
+        let receiver_of = |f: &GoFunctionInfo| f.receiver.as_ref().map(|r| r.type_name.clone());
+        let line_count = |f: &GoFunctionInfo| f.end_line.saturating_sub(f.start_line) + 1;
+
+        let old_functions = Self::collect_functions(old_source);
+        let mut grown = Vec::new();
+
+        for new_function in Self::collect_functions(new_source) {
+            let receiver = receiver_of(new_function);
+            let Some(old_function) = old_functions
+                .iter()
+                .find(|f| f.name == new_function.name && receiver_of(f) == receiver)
+            else {
+                continue;
+            };
+
+            let old_lines = line_count(old_function);
+            let new_lines = line_count(new_function);
+            let growth_ratio = f64::from(new_lines) / f64::from(old_lines);
+
+            if (growth_ratio - 1.0) * 100.0 > threshold_percent {
+                debug!(
+                    "检测到函数显著增长: {} ({} -> {} 行, {:.2}x)",
+                    new_function.name, old_lines, new_lines, growth_ratio
+                );
+                grown.push(FunctionGrowth {
+                    name: new_function.name.clone(),
+                    receiver,
+                    old_lines,
+                    new_lines,
+                    growth_ratio,
+                });
+            }
+        }
+
+        grown
+    }
+
+    /// 比较同一文件变更前后的两个版本，找出新增或内容发生变化的函数
+    ///
+    /// 用于重命名和复制的文件：行级差异会把新路径下的每一行都标记为新增，
+    /// 这里改为按接收者和函数名匹配旧版本中的同一函数，签名和函数体（忽略空白差异）一致的函数不会返回
+    pub fn find_modified_functions(
+        &self,
+        old_source: &SourceFile,
+        new_source: &SourceFile,
+    ) -> Vec<GoFunctionInfo> {
+        let receiver_of = |f: &GoFunctionInfo| f.receiver.as_ref().map(|r| r.type_name.clone());
+        let normalized = |f: &GoFunctionInfo| {
+            let parameters: Vec<GoType> =
+                f.parameters.iter().map(|p| p.param_type.clone()).collect();
+            let return_types: Vec<GoType> = f
+                .return_types
+                .iter()
+                .map(GoType::without_result_name)
+                .collect();
+            let body: String = f.body.split_whitespace().collect();
+            (parameters, return_types, body)
+        };
+
+        let old_functions = Self::collect_functions(old_source);
+        Self::collect_functions(new_source)
+            .into_iter()
+            .filter(|new_function| {
+                let receiver = receiver_of(new_function);
+                !old_functions.iter().any(|old_function| {
+                    old_function.name == new_function.name
+                        && receiver_of(old_function) == receiver
+                        && normalized(old_function) == normalized(new_function)
+                })
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// 收集源文件中的所有函数和方法
+    fn collect_functions(source_file: &SourceFile) -> Vec<&GoFunctionInfo> {
+        use crate::parser::GoDeclaration;
+
+        source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Function(f)) | Some(GoDeclaration::Method(f)) => Some(f),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 检测结构体字段序列化标签的变化
+    ///
+    /// 按类型名和字段名匹配变更前后的同一字段，比较其 `json`/`xml`/`yaml` 标签值；
+    /// 新增或删除的字段不在此报告
+    pub fn find_tag_changes(
+        &self,
+        old_source: &SourceFile,
+        new_source: &SourceFile,
+    ) -> Vec<TagChange> {
+        let old_types = Self::collect_types(old_source);
+        let mut changes = Vec::new();
+
+        for new_type in Self::collect_types(new_source) {
+            let Some(old_type) = old_types.iter().find(|t| t.name == new_type.name) else {
+                continue;
+            };
+
+            for new_field in &new_type.fields {
+                let Some(old_field) = old_type.fields.iter().find(|f| f.name == new_field.name)
+                else {
+                    continue;
+                };
+
+                for tag_key in TagChange::SERIALIZATION_KEYS {
+                    let old_value = old_field.tag_value(tag_key);
+                    let new_value = new_field.tag_value(tag_key);
+                    if old_value == new_value {
+                        continue;
+                    }
+
+                    debug!(
+                        "检测到字段标签变化: {}.{} {}: {:?} -> {:?}",
+                        new_type.name, new_field.name, tag_key, old_value, new_value
+                    );
+                    changes.push(TagChange {
+                        type_name: new_type.name.clone(),
+                        field_name: new_field.name.clone(),
+                        tag_key: tag_key.to_string(),
+                        old_value,
+                        new_value,
+                    });
+                }
+            }
+        }
+
+        changes
+    }
+
+    /// 按兼容性分类同一文件变更前后的函数、方法、类型、常量和变量声明
+    ///
+    /// `old_source` 为 None 表示新增的文件，其中所有声明都视为新增。
+    /// 函数按接收者和名称匹配，其他声明按名称匹配；名称只翻转首字母大小写的声明
+    /// 报告为可见性变化。结构体比较字段，其他类型比较忽略空白后的定义文本，
+    /// 常量和变量比较显式声明的类型；删除导出声明报告为 [`CompatChange::Removed`]。
+    /// 前后完全一致（忽略空白差异）的声明不会返回
+    pub fn classify_changes(
+        &self,
+        old_source: Option<&SourceFile>,
+        new_source: &SourceFile,
+    ) -> Vec<ClassifiedChange> {
+        use crate::parser::GoTypeKind;
+        use compatibility::{describe, is_exported, pair_declarations};
+
+        let receiver_of = |f: &GoFunctionInfo| f.receiver.as_ref().map(|r| r.type_name.clone());
+        let function_exported = |f: &GoFunctionInfo| {
+            is_exported(&f.name) && receiver_of(f).is_none_or(|receiver| is_exported(&receiver))
+        };
+        let added = |exported: bool| {
+            if exported {
+                CompatChange::Additive
+            } else {
+                CompatChange::Internal
+            }
+        };
+        let removed = |exported: bool| {
+            if exported {
+                CompatChange::Removed
+            } else {
+                CompatChange::Internal
+            }
+        };
+        let same_text = |old: &str, new: &str| old.split_whitespace().eq(new.split_whitespace());
+        // 变更前后名称相同的常量或变量，只有显式声明的类型属于 API
+        let retyped = |name: &str, what: &str, old: &Option<GoType>, new: &Option<GoType>| {
+            if !is_exported(name) {
+                CompatChange::Internal
+            } else if format!("{old:?}") != format!("{new:?}") {
+                CompatChange::Breaking(vec![format!("type of exported {what} {name} changed")])
+            } else {
+                CompatChange::Compatible
+            }
+        };
+
+        let mut changes = Vec::new();
+        let mut push = |name: String, change: CompatChange| {
+            changes.push(ClassifiedChange {
+                name,
+                file_path: new_source.path.clone(),
+                change,
+            });
+        };
+
+        let old_functions = old_source.map(Self::collect_functions).unwrap_or_default();
+        let functions =
+            pair_declarations(&old_functions, &Self::collect_functions(new_source), |f| {
+                (receiver_of(f), f.name.clone())
+            });
+        let signature = |f: &GoFunctionInfo| {
+            format!(
+                "{:?}",
+                (
+                    &f.receiver,
+                    &f.parameters,
+                    &f.return_types,
+                    &f.type_parameters
+                )
+            )
+        };
+        for (old, new) in functions.matched {
+            if signature(old) == signature(new) && same_text(&old.body, &new.body) {
+                continue;
+            }
+            push(describe(new), classify_function_change(old, new));
+        }
+        for (old, new) in functions.flipped {
+            push(describe(new), classify_function_change(old, new));
+        }
+        for new in functions.added {
+            push(describe(new), added(function_exported(new)));
+        }
+        for old in functions.removed {
+            push(describe(old), removed(function_exported(old)));
+        }
+
+        let old_types = old_source.map(Self::collect_types).unwrap_or_default();
+        let types = pair_declarations(&old_types, &Self::collect_types(new_source), |t| {
+            (None, t.name.clone())
+        });
+        for (old, new) in types.matched {
+            if same_text(&old.definition, &new.definition) {
+                continue;
+            }
+            let change = if matches!(old.kind, GoTypeKind::Struct)
+                && matches!(new.kind, GoTypeKind::Struct)
+            {
+                diff_struct(old, new).compat()
+            } else if !is_exported(&new.name) {
+                CompatChange::Internal
+            } else {
+                CompatChange::Breaking(vec![format!(
+                    "definition of exported type {} changed",
+                    new.name
+                )])
+            };
+            push(format!("type {}", new.name), change);
+        }
+        for (_, new) in types.flipped {
+            let now_exported = is_exported(&new.name);
+            push(
+                format!("type {}", new.name),
+                CompatChange::VisibilityChanged { now_exported },
+            );
+        }
+        for new in types.added {
+            push(format!("type {}", new.name), added(is_exported(&new.name)));
+        }
+        for old in types.removed {
+            push(
+                format!("type {}", old.name),
+                removed(is_exported(&old.name)),
+            );
+        }
+
+        let old_constants = old_source.map(Self::collect_constants).unwrap_or_default();
+        let constants =
+            pair_declarations(&old_constants, &Self::collect_constants(new_source), |c| {
+                (None, c.name.clone())
+            });
+        for (old, new) in constants.matched {
+            if same_text(&old.value, &new.value)
+                && format!("{:?}", old.const_type) == format!("{:?}", new.const_type)
+            {
+                continue;
+            }
+            let change = retyped(&new.name, "constant", &old.const_type, &new.const_type);
+            push(format!("constant {}", new.name), change);
+        }
+        for (_, new) in constants.flipped {
+            let now_exported = is_exported(&new.name);
+            push(
+                format!("constant {}", new.name),
+                CompatChange::VisibilityChanged { now_exported },
+            );
+        }
+        for new in constants.added {
+            push(
+                format!("constant {}", new.name),
+                added(is_exported(&new.name)),
+            );
+        }
+        for old in constants.removed {
+            push(
+                format!("constant {}", old.name),
+                removed(is_exported(&old.name)),
+            );
+        }
+
+        let old_variables = old_source.map(Self::collect_variables).unwrap_or_default();
+        let variables =
+            pair_declarations(&old_variables, &Self::collect_variables(new_source), |v| {
+                (None, v.name.clone())
+            });
+        for (old, new) in variables.matched {
+            if old.initial_value == new.initial_value
+                && format!("{:?}", old.var_type) == format!("{:?}", new.var_type)
+            {
+                continue;
+            }
+            let change = retyped(&new.name, "variable", &old.var_type, &new.var_type);
+            push(format!("variable {}", new.name), change);
+        }
+        for (_, new) in variables.flipped {
+            let now_exported = is_exported(&new.name);
+            push(
+                format!("variable {}", new.name),
+                CompatChange::VisibilityChanged { now_exported },
+            );
+        }
+        for new in variables.added {
+            push(
+                format!("variable {}", new.name),
+                added(is_exported(&new.name)),
+            );
+        }
+        for old in variables.removed {
+            push(
+                format!("variable {}", old.name),
+                removed(is_exported(&old.name)),
+            );
+        }
+
+        changes
+    }
+
+    /// 收集源文件中的所有类型定义
+    fn collect_types(source_file: &SourceFile) -> Vec<&GoTypeDefinition> {
+        use crate::parser::GoDeclaration;
+
+        source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Type(t)) => Some(t),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 收集源文件中的所有常量定义
+    fn collect_constants(source_file: &SourceFile) -> Vec<&crate::parser::GoConstantDefinition> {
+        source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Constant(c)) => Some(c),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 收集源文件中的所有变量定义
+    fn collect_variables(source_file: &SourceFile) -> Vec<&crate::parser::GoVariableDefinition> {
+        source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Variable(v)) => Some(v),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 计算函数指纹（忽略空白差异和函数名）
+    ///
+    /// 空函数体没有区分度，返回 None 以避免误判
+    fn function_fingerprint(function: &GoFunctionInfo) -> Option<u64> {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let normalized_body: String = function.body.split_whitespace().collect();
+        if normalized_body.is_empty() || normalized_body == "{}" {
+            return None;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        function
+            .receiver
+            .as_ref()
+            .map(|r| (&r.type_name, r.is_pointer))
+            .hash(&mut hasher);
+        for param in &function.parameters {
+            param.param_type.hash(&mut hasher);
+        }
+        for ret in &function.return_types {
+            ret.without_result_name().hash(&mut hasher);
+        }
+        normalized_body.hash(&mut hasher);
+
+        Some(hasher.finish())
+    }
+
+    /// 查找包含指定行号的函数
+    fn find_function_containing_line(
+        &self,
+        source_file: &SourceFile,
+        line: u32,
+    ) -> Result<Option<GoFunctionInfo>> {
+        let root = source_file.syntax_tree.root_node();
+
+        // 查找包含该行的节点
         if let Some(containing_node) = self.navigator.find_node_containing_line(root, line) {
             // 向上遍历找到函数声明节点
             let mut current_node = containing_node;
@@ -888,14 +2185,16 @@ impl SourceAnalyzer {
         source_code: &str,
         file_path: &Path,
     ) -> Result<GoFunctionInfo> {
-        use crate::parser::{GoParameter, GoReceiverInfo, GoType};
+        use crate::parser::{GoParameter, GoReceiverInfo};
+        use std::sync::Arc;
 
         // 获取函数签名
         let signature = self
             .navigator
             .get_function_signature(func_node, source_code)
-            .ok_or_else(|| {
-                SemanticDiffError::ParseError("Failed to extract function signature".to_string())
+            .ok_or_else(|| SemanticDiffError::MalformedSyntax {
+                file: file_path.to_path_buf(),
+                byte_offset: func_node.start_byte(),
             })?;
 
         // 获取函数体
@@ -908,43 +2207,33 @@ impl SourceAnalyzer {
         // 获取行号范围
         let (start_line, end_line) = self.navigator.get_node_line_range(func_node);
 
-        // 转换参数
-        let parameters: Vec<GoParameter> = signature
-            .parameters
-            .iter()
-            .enumerate()
-            .map(|(i, param_str)| GoParameter {
-                name: format!("param{i}"), // 简化的参数名
-                param_type: GoType {
-                    name: param_str.clone(),
-                    is_pointer: param_str.contains('*'),
-                    is_slice: param_str.contains("[]"),
-                },
+        // 转换参数（按 CST 展开分组参数，保留真实参数名）
+        let parameters: Vec<GoParameter> = self
+            .navigator
+            .get_function_parameters(func_node, source_code)
+            .into_iter()
+            .map(|param| GoParameter {
+                param_type: param.go_type,
+                name: param.name,
+                is_variadic: param.is_variadic,
             })
             .collect();
 
-        // 转换返回类型
-        let return_types: Vec<GoType> = signature
-            .return_types
-            .iter()
-            .map(|ret_str| GoType {
-                name: ret_str.clone(),
-                is_pointer: ret_str.contains('*'),
-                is_slice: ret_str.contains("[]"),
+        // 转换返回类型（保留命名返回值的名称）
+        let return_types: Vec<GoType> = self
+            .navigator
+            .get_function_results(func_node, source_code)
+            .into_iter()
+            .map(|result| GoType {
+                result_name: Some(result.name).filter(|name| !name.is_empty()),
+                ..result.go_type
             })
             .collect();
 
         // 转换接收者信息
-        let receiver = signature.receiver.map(|recv_str| {
-            let is_pointer = recv_str.contains('*');
-            let type_name = recv_str.replace(['*', '(', ')'], "").trim().to_string();
-
-            GoReceiverInfo {
-                name: "self".to_string(), // 简化的接收者名
-                type_name,
-                is_pointer,
-            }
-        });
+        let receiver = signature
+            .receiver
+            .map(|recv_str| GoReceiverInfo::from_signature(&recv_str));
 
         Ok(GoFunctionInfo {
             name: signature.name.clone(),
@@ -954,23 +2243,30 @@ impl SourceAnalyzer {
             body,
             start_line,
             end_line,
+            byte_start: func_node.start_byte(),
+            byte_end: func_node.end_byte(),
+            doc_comment: self.navigator.get_doc_comment(func_node, source_code),
             file_path: file_path.to_path_buf(),
+            body_references: Arc::new(BodyReferences::from_function_node(func_node, source_code)),
+            type_parameters: Self::extract_type_parameters(func_node, source_code),
         })
     }
 
     /// 提取 Go 语言特定信息
-    fn extract_go_specific_info(
+    pub(crate) fn extract_go_specific_info(
         &self,
         syntax_tree: &tree_sitter::Tree,
         source_code: &str,
         file_path: &Path,
-    ) -> Result<Box<dyn LanguageSpecificInfo>> {
-        use crate::parser::GoLanguageInfo;
+    ) -> Result<GoLanguageInfo> {
+        use crate::parser::UNKNOWN_PACKAGE_NAME;
 
         let root = syntax_tree.root_node();
 
-        // 提取包名
-        let package_name = self.extract_package_name(root, source_code);
+        // 提取包名，缺少 package 子句时不猜测包名
+        let package_name = self
+            .extract_package_name(root, source_code)
+            .unwrap_or_else(|| UNKNOWN_PACKAGE_NAME.to_string());
 
         // 提取导入
         let imports = self.extract_imports(root, source_code);
@@ -978,15 +2274,15 @@ impl SourceAnalyzer {
         // 提取声明
         let declarations = self.extract_go_declarations(root, source_code, file_path)?;
 
-        Ok(Box::new(GoLanguageInfo {
+        Ok(GoLanguageInfo {
             package_name,
             imports,
             declarations,
-        }))
+        })
     }
 
-    /// 提取 Go 包名
-    fn extract_package_name(&self, root: tree_sitter::Node, source_code: &str) -> String {
+    /// 提取 Go 包名，没有 package 子句时返回 None
+    fn extract_package_name(&self, root: tree_sitter::Node, source_code: &str) -> Option<String> {
         let mut cursor = root.walk();
         for child in root.children(&mut cursor) {
             if child.kind() == "package_clause" {
@@ -994,12 +2290,12 @@ impl SourceAnalyzer {
                 let mut pkg_cursor = child.walk();
                 for pkg_child in child.children(&mut pkg_cursor) {
                     if pkg_child.kind() == "package_identifier" {
-                        return source_code[pkg_child.byte_range()].to_string();
+                        return Some(source_code[pkg_child.byte_range()].to_string());
                     }
                 }
             }
         }
-        "main".to_string() // 默认包名
+        None
     }
 
     /// 提取导入声明
@@ -1050,6 +2346,7 @@ impl SourceAnalyzer {
     ) -> Option<crate::parser::Import> {
         let mut path = String::new();
         let mut alias = None;
+        let mut kind = crate::parser::ImportKind::Normal;
 
         let mut cursor = import_spec.walk();
         for child in import_spec.children(&mut cursor) {
@@ -1064,12 +2361,14 @@ impl SourceAnalyzer {
                     // 导入别名
                     alias = Some(source_code[child.byte_range()].to_string());
                 }
+                "dot" => kind = crate::parser::ImportKind::Dot,
+                "blank_identifier" => kind = crate::parser::ImportKind::Blank,
                 _ => {}
             }
         }
 
         if !path.is_empty() {
-            Some(crate::parser::Import { path, alias })
+            Some(crate::parser::Import { path, alias, kind })
         } else {
             None
         }
@@ -1106,8 +2405,7 @@ impl SourceAnalyzer {
         // 提取类型声明
         let types = self.navigator.find_type_declarations(root);
         for type_node in types {
-            if let Some(type_def) = self.extract_type_definition(type_node, source_code, file_path)
-            {
+            for type_def in Self::extract_type_definitions(type_node, source_code, file_path) {
                 declarations.push(Box::new(GoDeclaration::Type(type_def)));
             }
         }
@@ -1133,53 +2431,197 @@ impl SourceAnalyzer {
         Ok(declarations)
     }
 
-    /// 提取类型定义
-    fn extract_type_definition(
-        &self,
-        type_node: tree_sitter::Node,
+    /// 提取函数声明或类型规范上的泛型类型参数
+    ///
+    /// `[K comparable, V any]` 和 `[A, B Number]` 都会按参数名展开，共享约束的参数各自记录一份约束
+    pub(crate) fn extract_type_parameters(
+        node: tree_sitter::Node,
         source_code: &str,
-        file_path: &Path,
-    ) -> Option<GoTypeDefinition> {
-        use crate::parser::GoTypeKind;
+    ) -> Vec<crate::parser::GoTypeParam> {
+        use crate::parser::GoTypeParam;
 
-        let mut type_name = String::new();
-        let mut type_kind = GoTypeKind::Alias;
+        let Some(param_list) = node.child_by_field_name("type_parameters") else {
+            return Vec::new();
+        };
 
-        let mut cursor = type_node.walk();
-        for child in type_node.children(&mut cursor) {
-            if child.kind() == "type_spec" {
-                // 在 type_spec 中查找类型名称和种类
-                let mut spec_cursor = child.walk();
-                for spec_child in child.children(&mut spec_cursor) {
-                    match spec_child.kind() {
-                        "type_identifier" => {
-                            if type_name.is_empty() {
-                                type_name = source_code[spec_child.byte_range()].to_string();
-                            }
-                        }
-                        "struct_type" => {
-                            type_kind = GoTypeKind::Struct;
-                        }
-                        "interface_type" => {
-                            type_kind = GoTypeKind::Interface;
-                        }
-                        _ => {}
-                    }
-                }
+        let mut type_params = Vec::new();
+        let mut cursor = param_list.walk();
+        for declaration in param_list.named_children(&mut cursor) {
+            if declaration.kind() != "type_parameter_declaration" {
+                continue;
+            }
+            let constraint = declaration
+                .child_by_field_name("type")
+                .map(|constraint| source_code[constraint.byte_range()].to_string())
+                .unwrap_or_default();
+
+            let mut name_cursor = declaration.walk();
+            for name_node in declaration.children_by_field_name("name", &mut name_cursor) {
+                type_params.push(GoTypeParam {
+                    name: source_code[name_node.byte_range()].to_string(),
+                    constraint: constraint.clone(),
+                });
             }
         }
 
-        if !type_name.is_empty() {
-            Some(GoTypeDefinition {
-                name: type_name,
-                kind: type_kind,
-                definition: source_code[type_node.byte_range()].to_string(),
-                file_path: file_path.to_path_buf(),
-                dependencies: Vec::new(), // TODO: 在后续任务中实现依赖提取
-            })
-        } else {
-            None
+        type_params
+    }
+
+    /// 提取类型规范中的结构体字段，非结构体类型返回空列表
+    ///
+    /// `A, B int` 这样的分组字段会按字段名展开
+    pub(crate) fn extract_struct_fields(
+        type_spec: tree_sitter::Node,
+        source_code: &str,
+    ) -> Vec<crate::parser::GoStructField> {
+        use crate::parser::GoStructField;
+
+        let Some(struct_node) = type_spec
+            .child_by_field_name("type")
+            .filter(|node| node.kind() == "struct_type")
+        else {
+            return Vec::new();
+        };
+        let mut cursor = struct_node.walk();
+        let Some(field_list) = struct_node
+            .named_children(&mut cursor)
+            .find(|node| node.kind() == "field_declaration_list")
+        else {
+            return Vec::new();
+        };
+
+        let mut fields = Vec::new();
+        let mut field_cursor = field_list.walk();
+        for field in field_list.named_children(&mut field_cursor) {
+            if field.kind() != "field_declaration" {
+                continue;
+            }
+            let Some(type_node) = field.child_by_field_name("type") else {
+                continue;
+            };
+            // 只去掉字面量两端的一对分隔符，标签值自身结尾的引号需要保留
+            let tag = field.child_by_field_name("tag").map(|tag| {
+                let literal = &source_code[tag.byte_range()];
+                ['`', '"']
+                    .into_iter()
+                    .find_map(|delimiter| {
+                        literal
+                            .strip_prefix(delimiter)
+                            .and_then(|inner| inner.strip_suffix(delimiter))
+                    })
+                    .unwrap_or(literal)
+                    .to_string()
+            });
+
+            // 名称之间的逗号也会作为字段子节点返回，只保留标识符
+            let mut name_cursor = field.walk();
+            let names: Vec<String> = field
+                .children_by_field_name("name", &mut name_cursor)
+                .filter(|name| name.is_named())
+                .map(|name| source_code[name.byte_range()].to_string())
+                .collect();
+
+            let mut field_type = GoType::from_type_node(type_node, source_code);
+            // 嵌入指针字段的 `*` 不属于类型节点
+            if names.is_empty() && field.start_byte() < type_node.start_byte() {
+                field_type.is_pointer = true;
+            }
+
+            if names.is_empty() {
+                // 嵌入字段以类型名作为字段名：`*pkg.Base` -> `Base`
+                let base = &source_code[type_node.byte_range()];
+                fields.push(GoStructField {
+                    name: base.rsplit('.').next().unwrap_or(base).to_string(),
+                    field_type,
+                    tag,
+                    embedded: true,
+                });
+            } else {
+                fields.extend(names.into_iter().map(|name| GoStructField {
+                    name,
+                    field_type: field_type.clone(),
+                    tag: tag.clone(),
+                    embedded: false,
+                }));
+            }
+        }
+
+        fields
+    }
+
+    /// 提取类型定义
+    ///
+    /// 分组声明 `type ( A ...; B ... )` 中的每个类型都会生成独立的定义，
+    /// 其定义文本为 `type <spec>`，行号范围为该类型自身所在的行
+    pub(crate) fn extract_type_definitions(
+        type_node: tree_sitter::Node,
+        source_code: &str,
+        file_path: &Path,
+    ) -> Vec<GoTypeDefinition> {
+        use crate::parser::GoTypeKind;
+
+        let mut cursor = type_node.walk();
+        let children: Vec<_> = type_node.children(&mut cursor).collect();
+        let is_grouped = children.iter().any(|child| child.kind() == "(");
+
+        let mut type_defs = Vec::new();
+        for spec in children
+            .iter()
+            .filter(|child| matches!(child.kind(), "type_spec" | "type_alias"))
+        {
+            let Some(name_node) = spec.child_by_field_name("name") else {
+                continue;
+            };
+            let type_name = source_code[name_node.byte_range()].to_string();
+
+            let type_kind = match spec.child_by_field_name("type").map(|n| n.kind()) {
+                Some("struct_type") => GoTypeKind::Struct,
+                Some("interface_type") => GoTypeKind::Interface,
+                _ => GoTypeKind::Alias,
+            };
+
+            // 单独声明保留原文；分组成员补上 `type` 关键字，使其可以独立阅读
+            let (definition, range_node) = if is_grouped {
+                (format!("type {}", &source_code[spec.byte_range()]), *spec)
+            } else {
+                (source_code[type_node.byte_range()].to_string(), type_node)
+            };
+            let start_line = range_node.start_position().row as u32;
+            let end_line = range_node.end_position().row as u32;
+
+            // 嵌入字段的类型（如 `User`、`*User`、`models.Base`）是结构体的直接依赖，
+            // 其字段和方法会被提升到外层结构体
+            let fields = Self::extract_struct_fields(*spec, source_code);
+
+            // 分组成员的注释位于括号内的类型规范上方，单独声明的注释位于 `type` 关键字上方
+            let doc_comment = if is_grouped {
+                CstNavigator::new().get_doc_comment(*spec, source_code)
+            } else {
+                CstNavigator::new().get_doc_comment(type_node, source_code)
+            };
+            let dependencies = fields
+                .iter()
+                .filter(|field| field.embedded)
+                .map(|field| field.field_type.name.trim_start_matches('*').to_string())
+                .collect();
+
+            type_defs.push(GoTypeDefinition {
+                name: type_name,
+                kind: type_kind,
+                definition,
+                start_line,
+                end_line,
+                byte_start: range_node.start_byte(),
+                byte_end: range_node.end_byte(),
+                doc_comment,
+                file_path: file_path.to_path_buf(),
+                dependencies,
+                type_parameters: Self::extract_type_parameters(*spec, source_code),
+                fields,
+            });
         }
+
+        type_defs
     }
 
     /// 提取常量定义
@@ -1189,53 +2631,80 @@ impl SourceAnalyzer {
         source_code: &str,
         file_path: &Path,
     ) -> Vec<crate::parser::GoConstantDefinition> {
-        use crate::parser::{GoConstantDefinition, GoType};
+        use crate::parser::GoConstantDefinition;
 
         let mut constants = Vec::new();
         let (start_line, end_line) = self.navigator.get_node_line_range(const_node);
 
+        // 常量块中每个规范对应一个 iota 值；省略表达式的规范隐式重复上一个表达式列表和类型
+        let mut iota = 0usize;
+        let mut inherited: (Vec<String>, Option<GoType>) = (Vec::new(), None);
+
         let mut cursor = const_node.walk();
+        let is_grouped = const_node
+            .children(&mut cursor)
+            .any(|child| child.kind() == "(");
         for child in const_node.children(&mut cursor) {
-            if child.kind() == "const_spec" {
-                // 提取常量规范
-                let mut name = String::new();
-                let mut value = String::new();
-                let mut const_type = None;
-
-                let mut spec_cursor = child.walk();
-                for spec_child in child.children(&mut spec_cursor) {
-                    match spec_child.kind() {
-                        "identifier" => {
-                            if name.is_empty() {
-                                name = source_code[spec_child.byte_range()].to_string();
-                            }
-                        }
-                        "type_identifier" => {
-                            const_type = Some(GoType {
-                                name: source_code[spec_child.byte_range()].to_string(),
-                                is_pointer: false,
-                                is_slice: false,
-                            });
-                        }
-                        _ => {
-                            // 其他节点可能是值表达式
-                            if !name.is_empty() && value.is_empty() {
-                                value = source_code[spec_child.byte_range()].to_string();
-                            }
-                        }
-                    }
+            if child.kind() != "const_spec" {
+                continue;
+            }
+            let spec_iota = iota;
+            iota += 1;
+
+            let explicit_values = child.child_by_field_name("value").map(|list| {
+                let mut list_cursor = list.walk();
+                list.named_children(&mut list_cursor)
+                    .filter(|expr| expr.kind() != "comment")
+                    .map(|expr| source_code[expr.byte_range()].to_string())
+                    .collect::<Vec<_>>()
+            });
+            let (values, const_type) = match explicit_values {
+                Some(values) => {
+                    let const_type = child
+                        .child_by_field_name("type")
+                        .map(|t| GoType::from_type_node(t, source_code));
+                    inherited = (values.clone(), const_type.clone());
+                    (values, const_type)
                 }
+                None => inherited.clone(),
+            };
 
-                if !name.is_empty() {
-                    constants.push(GoConstantDefinition {
-                        name,
-                        value,
-                        const_type,
-                        start_line,
-                        end_line,
-                        file_path: file_path.to_path_buf(),
-                    });
-                }
+            // 分组成员的注释位于括号内的规范上方，单独声明的注释位于 `const` 关键字上方
+            let doc_comment = if is_grouped {
+                self.navigator.get_doc_comment(child, source_code)
+            } else {
+                self.navigator.get_doc_comment(const_node, source_code)
+            };
+
+            // 分组成员只覆盖自己的规范，修改其中一个成员不会波及同组的其他成员
+            let byte_range = if is_grouped {
+                child.byte_range()
+            } else {
+                const_node.byte_range()
+            };
+
+            // 名称字段之间的逗号也会被当作字段子节点返回，只保留标识符
+            let mut name_cursor = child.walk();
+            for (i, name_node) in child
+                .children_by_field_name("name", &mut name_cursor)
+                .filter(|name_node| name_node.is_named())
+                .enumerate()
+            {
+                let value = values.get(i).cloned().unwrap_or_default();
+                let uses_iota = IOTA.is_match(&value);
+
+                constants.push(GoConstantDefinition {
+                    name: source_code[name_node.byte_range()].to_string(),
+                    value,
+                    const_type: const_type.clone(),
+                    iota_index: uses_iota.then_some(spec_iota),
+                    start_line,
+                    end_line,
+                    byte_start: byte_range.start,
+                    byte_end: byte_range.end,
+                    doc_comment: doc_comment.clone(),
+                    file_path: file_path.to_path_buf(),
+                });
             }
         }
 
@@ -1243,60 +2712,77 @@ impl SourceAnalyzer {
     }
 
     /// 提取变量定义
+    ///
+    /// 分组声明的规范位于 `var_spec_list` 中；一个规范声明多个名称时，按位置与初始值配对
     fn extract_variable_definitions(
         &self,
         var_node: tree_sitter::Node,
         source_code: &str,
         file_path: &Path,
     ) -> Vec<crate::parser::GoVariableDefinition> {
-        use crate::parser::{GoType, GoVariableDefinition};
+        use crate::parser::GoVariableDefinition;
 
         let mut variables = Vec::new();
         let (start_line, end_line) = self.navigator.get_node_line_range(var_node);
 
         let mut cursor = var_node.walk();
-        for child in var_node.children(&mut cursor) {
-            if child.kind() == "var_spec" {
-                // 提取变量规范
-                let mut name = String::new();
-                let mut var_type = None;
-                let mut initial_value = None;
-
-                let mut spec_cursor = child.walk();
-                for spec_child in child.children(&mut spec_cursor) {
-                    match spec_child.kind() {
-                        "identifier" => {
-                            if name.is_empty() {
-                                name = source_code[spec_child.byte_range()].to_string();
-                            }
-                        }
-                        "type_identifier" => {
-                            var_type = Some(GoType {
-                                name: source_code[spec_child.byte_range()].to_string(),
-                                is_pointer: false,
-                                is_slice: false,
-                            });
-                        }
-                        _ => {
-                            // 其他节点可能是初始值表达式
-                            if !name.is_empty() && initial_value.is_none() {
-                                initial_value =
-                                    Some(source_code[spec_child.byte_range()].to_string());
-                            }
-                        }
-                    }
-                }
+        let spec_list = var_node
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "var_spec_list");
+        let is_grouped = spec_list.is_some();
+        let container = spec_list.unwrap_or(var_node);
+
+        let mut spec_cursor = container.walk();
+        for spec in container.named_children(&mut spec_cursor) {
+            if spec.kind() != "var_spec" {
+                continue;
+            }
 
-                if !name.is_empty() {
-                    variables.push(GoVariableDefinition {
-                        name,
-                        var_type,
-                        initial_value,
-                        start_line,
-                        end_line,
-                        file_path: file_path.to_path_buf(),
-                    });
-                }
+            let var_type = spec
+                .child_by_field_name("type")
+                .map(|t| GoType::from_type_node(t, source_code));
+            let values: Vec<String> = spec
+                .child_by_field_name("value")
+                .map(|list| {
+                    let mut list_cursor = list.walk();
+                    list.named_children(&mut list_cursor)
+                        .filter(|expr| expr.kind() != "comment")
+                        .map(|expr| source_code[expr.byte_range()].to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            // 分组成员的注释位于括号内的规范上方，单独声明的注释位于 `var` 关键字上方
+            let doc_comment = if is_grouped {
+                self.navigator.get_doc_comment(spec, source_code)
+            } else {
+                self.navigator.get_doc_comment(var_node, source_code)
+            };
+
+            let byte_range = if is_grouped {
+                spec.byte_range()
+            } else {
+                var_node.byte_range()
+            };
+
+            // 名称之间的逗号也会作为字段子节点返回，只保留标识符
+            let mut name_cursor = spec.walk();
+            for (i, name_node) in spec
+                .children_by_field_name("name", &mut name_cursor)
+                .filter(|name_node| name_node.is_named())
+                .enumerate()
+            {
+                variables.push(GoVariableDefinition {
+                    name: source_code[name_node.byte_range()].to_string(),
+                    var_type: var_type.clone(),
+                    initial_value: values.get(i).cloned(),
+                    start_line,
+                    end_line,
+                    byte_start: byte_range.start,
+                    byte_end: byte_range.end,
+                    doc_comment: doc_comment.clone(),
+                    file_path: file_path.to_path_buf(),
+                });
             }
         }
 
@@ -1334,7 +2820,9 @@ impl TypeAnalyzer {
         type_def: &GoTypeDefinition,
         source_files: &[SourceFile],
     ) -> Vec<Dependency> {
-        self.analyze_type_dependencies_recursive(type_def, source_files, &mut HashSet::new())
+        let resolver = DependencyResolver::new();
+        let index = resolver.build_index(source_files);
+        self.analyze_type_dependencies_recursive(type_def, &resolver, &index, &mut HashSet::new())
     }
 
     /// 递归分析类型的依赖关系，包括间接依赖
@@ -1343,7 +2831,8 @@ impl TypeAnalyzer {
     fn analyze_type_dependencies_recursive(
         &self,
         type_def: &GoTypeDefinition,
-        source_files: &[SourceFile],
+        resolver: &DependencyResolver,
+        index: &SymbolIndex,
         visited: &mut HashSet<String>,
     ) -> Vec<Dependency> {
         let mut dependencies = Vec::new();
@@ -1354,28 +2843,44 @@ impl TypeAnalyzer {
         }
         visited.insert(type_def.name.clone());
 
-        // 从类型定义中提取类型引用
-        let type_refs = self.extract_type_references_from_definition(&type_def.definition);
+        // 嵌入字段按解析器识别的字段提取，其余类型引用从类型定义中提取
+        let mut type_refs: Vec<TypeReference> = type_def
+            .fields
+            .iter()
+            .filter(|field| field.embedded)
+            .map(|field| self.parse_type_reference(&field.field_type.name))
+            .collect();
+        type_refs.extend(self.extract_type_references_from_definition(&type_def.definition));
+
+        // 同一类型可能沿多条嵌入路径到达，按名称和所在文件去重
+        let mut push_unique = |dependency: Dependency| {
+            let exists = dependencies
+                .iter()
+                .any(|d| d.name == dependency.name && d.file_path == dependency.file_path);
+            if !exists {
+                dependencies.push(dependency);
+            }
+        };
 
         for type_ref in type_refs {
             // 查找类型定义
-            let resolver = DependencyResolver::new();
-            if let Some(dep_type_def) = resolver.resolve_type(&type_ref, source_files) {
+            if let Some(dep_type_def) = resolver.resolve_type(&type_ref, index) {
                 // 添加直接依赖
-                dependencies.push(Dependency {
+                push_unique(Dependency {
                     name: dep_type_def.name.clone(),
                     dependency_type: DependencyType::Type,
                     file_path: dep_type_def.file_path.clone(),
                 });
 
                 // 递归分析间接依赖
-                let indirect_deps =
-                    self.analyze_type_dependencies_recursive(&dep_type_def, source_files, visited);
+                let indirect_deps = self.analyze_type_dependencies_recursive(
+                    &dep_type_def,
+                    resolver,
+                    index,
+                    visited,
+                );
                 for indirect_dep in indirect_deps {
-                    // 避免重复添加
-                    if !dependencies.iter().any(|d| d.name == indirect_dep.name) {
-                        dependencies.push(indirect_dep);
-                    }
+                    push_unique(indirect_dep);
                 }
             }
         }
@@ -1468,6 +2973,166 @@ impl TypeAnalyzer {
         method_types
     }
 
+    /// 查找实现了指定接口的结构体
+    ///
+    /// 比较每个结构体的方法集（值接收者和指针接收者的方法都计入）与接口的方法签名，
+    /// 接口中嵌入的其他接口的方法会一并提升到接口的方法集中。无法在源文件中找到定义的
+    /// 嵌入接口（如 `io.Reader`）会被忽略；空接口被所有类型实现，不返回任何结构体
+    pub fn find_implementors(
+        &self,
+        interface: &GoTypeDefinition,
+        source_files: &[SourceFile],
+    ) -> Vec<GoTypeDefinition> {
+        use crate::parser::GoTypeKind;
+
+        if !matches!(interface.kind, GoTypeKind::Interface) {
+            return Vec::new();
+        }
+
+        let index = DependencyResolver::new().build_index(source_files);
+        let mut required = HashMap::new();
+        Self::collect_interface_methods(interface, &index, &mut HashSet::new(), &mut required);
+        if required.is_empty() {
+            return Vec::new();
+        }
+
+        let go_infos: Vec<&GoLanguageInfo> = source_files
+            .iter()
+            .filter_map(|file| {
+                file.language_specific
+                    .as_any()
+                    .downcast_ref::<GoLanguageInfo>()
+            })
+            .collect();
+
+        let mut implementors = Vec::new();
+        for go_info in &go_infos {
+            for declaration in go_info.declarations() {
+                let Some(GoDeclaration::Type(type_def)) =
+                    declaration.as_any().downcast_ref::<GoDeclaration>()
+                else {
+                    continue;
+                };
+                if !matches!(type_def.kind, GoTypeKind::Struct) {
+                    continue;
+                }
+
+                // 方法必须与结构体声明在同一个包中，但可以分布在包内的多个文件里
+                let method_set: HashMap<&str, MethodSignature> = go_infos
+                    .iter()
+                    .filter(|other| other.package_name == go_info.package_name)
+                    .flat_map(|other| other.methods_of(&type_def.name))
+                    .map(|method| (method.name.as_str(), Self::function_signature(method)))
+                    .collect();
+
+                let implements = required
+                    .iter()
+                    .all(|(name, signature)| method_set.get(name.as_str()) == Some(signature));
+                if implements {
+                    implementors.push(type_def.clone());
+                }
+            }
+        }
+
+        implementors
+    }
+
+    /// 收集接口声明的方法签名，递归展开嵌入的接口
+    ///
+    /// 使用 visited 集合避免接口相互嵌入时无限递归
+    fn collect_interface_methods(
+        interface: &GoTypeDefinition,
+        index: &SymbolIndex,
+        visited: &mut HashSet<String>,
+        methods: &mut HashMap<String, MethodSignature>,
+    ) {
+        use crate::parser::GoTypeKind;
+
+        if !visited.insert(interface.name.clone()) {
+            return;
+        }
+
+        // 在接口所在源文件的语法树中按声明的字节范围定位接口节点
+        let Some(source_file) = index
+            .source_files()
+            .iter()
+            .find(|file| file.path == interface.file_path)
+        else {
+            return;
+        };
+        let source = source_file.source_code.as_str();
+        let Some(interface_node) = source_file
+            .syntax_tree
+            .root_node()
+            .descendant_for_byte_range(interface.byte_start, interface.byte_end)
+            .and_then(Self::find_interface_node)
+        else {
+            return;
+        };
+
+        let navigator = CstNavigator::new();
+        let mut cursor = interface_node.walk();
+        for child in interface_node.named_children(&mut cursor) {
+            match child.kind() {
+                "method_elem" | "method_spec" => {
+                    let Some(name_node) = child.child_by_field_name("name") else {
+                        continue;
+                    };
+                    let parameters = navigator
+                        .get_function_parameters(child, source)
+                        .into_iter()
+                        .map(|param| (param.go_type.without_result_name(), param.is_variadic))
+                        .collect();
+                    let results = navigator
+                        .get_function_results(child, source)
+                        .into_iter()
+                        .map(|result| result.go_type.without_result_name())
+                        .collect();
+                    methods.insert(
+                        source[name_node.byte_range()].to_string(),
+                        (parameters, results),
+                    );
+                }
+                "type_elem" => {
+                    // 只有单个类型名的类型元素是嵌入接口，`~int | ~string` 等约束元素不引入方法
+                    let embedded = source[child.byte_range()].trim();
+                    for (_, embedded_def) in index.types_named(embedded) {
+                        if matches!(embedded_def.kind, GoTypeKind::Interface) {
+                            Self::collect_interface_methods(embedded_def, index, visited, methods);
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// 查找语法树中的第一个接口类型节点
+    fn find_interface_node(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
+        if node.kind() == "interface_type" {
+            return Some(node);
+        }
+        let mut cursor = node.walk();
+        node.children(&mut cursor)
+            .find_map(Self::find_interface_node)
+    }
+
+    /// 函数的签名：参数类型列表和返回类型列表，不含参数名
+    fn function_signature(function: &GoFunctionInfo) -> MethodSignature {
+        let parameters = function
+            .parameters
+            .iter()
+            .map(|param| (param.param_type.without_result_name(), param.is_variadic))
+            .collect();
+        let results = function
+            .return_types
+            .iter()
+            .map(GoType::without_result_name)
+            .collect();
+        (parameters, results)
+    }
+
     /// 从类型定义中提取类型引用
     fn extract_type_references_from_definition(&self, definition: &str) -> Vec<TypeReference> {
         let mut type_refs = Vec::new();
@@ -1822,114 +3487,568 @@ func multiply(x, y int) int {
     }
 
     #[test]
-    fn test_dependency_resolver_creation() {
-        let resolver = DependencyResolver::new();
-        let _default_resolver = DependencyResolver::default();
+    fn test_find_changed_declarations() {
+        use crate::extractor::ChangeType;
 
-        // 测试基本功能可用性
-        let type_ref = TypeReference {
-            name: "TestType".to_string(),
-            package: None,
-        };
+        let go_source = r#"package main
 
-        let func_call = FunctionCall {
-            name: "testFunc".to_string(),
-            receiver: None,
-            package: None,
-        };
+const (
+    MaxUsers = 100
+    MinUsers = 1
+)
 
-        // 空的源文件列表应该返回 None
-        assert!(resolver.resolve_type(&type_ref, &[]).is_none());
-        assert!(resolver.resolve_function(&func_call, &[]).is_none());
-    }
+var registry = map[string]User{}
 
-    #[test]
-    fn test_is_external_dependency() {
-        let resolver = DependencyResolver::new();
+type User struct {
+    Name  string
+    Email string
+}
 
-        // 测试标准库
-        let std_import = crate::parser::Import {
-            path: "fmt".to_string(),
-            alias: None,
-        };
-        assert!(resolver.is_external_dependency(&std_import));
+func save(u User) {
+    registry[u.Name] = u
+}
 
-        let http_import = crate::parser::Import {
-            path: "net/http".to_string(),
-            alias: None,
+var (
+    cache   = map[string]User{}
+    enabled = true
+)
+"#;
+        let file_path = PathBuf::from("user.go");
+        let mut analyzer = SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_file = analyzer
+            .analyze_source(&file_path, go_source.to_string())
+            .unwrap();
+
+        let hunk = |new_start: u32, new_lines: u32| DiffHunk {
+            old_start: new_start,
+            old_lines: new_lines,
+            new_start,
+            new_lines,
+            lines: Vec::new(),
+            context_lines: 0,
         };
-        assert!(resolver.is_external_dependency(&http_import));
-
-        // 测试第三方库
-        let github_import = crate::parser::Import {
-            path: "github.com/user/repo".to_string(),
-            alias: None,
+        let targets_of = |hunks: &[DiffHunk]| {
+            analyzer
+                .find_changed_declarations(&source_file, hunks)
+                .unwrap()
+                .iter()
+                .map(|t| (t.change_type(), t.name().to_string()))
+                .collect::<Vec<_>>()
         };
-        assert!(resolver.is_external_dependency(&github_import));
 
-        // 测试项目内部包（相对路径）
-        let internal_import = crate::parser::Import {
-            path: "./internal/utils".to_string(),
-            alias: None,
-        };
-        assert!(!resolver.is_external_dependency(&internal_import));
+        // 结构体字段的变更应当报告为类型变更
+        assert_eq!(
+            targets_of(&[hunk(12, 1)]),
+            vec![(ChangeType::Type, "User".to_string())]
+        );
 
-        // 注意：没有项目模块路径时，非相对路径的包会被当作外部依赖处理
-        let relative_import = crate::parser::Import {
-            path: "myproject/internal".to_string(),
-            alias: None,
-        };
-        assert!(resolver.is_external_dependency(&relative_import));
+        // 分组常量中的一行只影响对应的常量
+        assert_eq!(
+            targets_of(&[hunk(5, 1)]),
+            vec![(ChangeType::Constant, "MinUsers".to_string())]
+        );
+
+        // 跨越变量和函数的差异块
+        assert_eq!(
+            targets_of(&[hunk(8, 1), hunk(16, 2)]),
+            vec![
+                (ChangeType::Variable, "registry".to_string()),
+                (ChangeType::Function, "save".to_string()),
+            ]
+        );
+
+        // 分组变量的括号行把整个分组视为变更
+        assert_eq!(
+            targets_of(&[hunk(22, 1)]),
+            vec![
+                (ChangeType::Variable, "cache".to_string()),
+                (ChangeType::Variable, "enabled".to_string()),
+            ]
+        );
     }
 
     #[test]
-    fn test_is_external_dependency_with_project_path() {
-        // 测试带有项目模块路径的解析器
-        let resolver =
-            DependencyResolver::new_with_project_path("github.com/M4n5ter/examplePkg".to_string());
+    fn test_find_removed_declarations() {
+        let file_path = PathBuf::from("greeter.go");
+        let mut analyzer = SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let old_source = analyzer
+            .analyze_source(
+                &file_path,
+                r#"package main
 
-        // 测试项目内部包
-        let project_internal = crate::parser::Import {
-            path: "github.com/M4n5ter/examplePkg/internal/utils".to_string(),
-            alias: None,
-        };
-        assert!(!resolver.is_external_dependency(&project_internal));
+type Greeter struct{}
 
-        let project_subpackage = crate::parser::Import {
-            path: "github.com/M4n5ter/examplePkg/api/v1".to_string(),
-            alias: None,
-        };
-        assert!(!resolver.is_external_dependency(&project_subpackage));
+type Legacy struct{}
 
-        // 测试第三方库（即使有相似的前缀）
-        let third_party = crate::parser::Import {
-            path: "github.com/M4n5ter/otherPkg".to_string(),
-            alias: None,
-        };
-        assert!(resolver.is_external_dependency(&third_party));
+func (g *Greeter) Greet() string {
+    return "hello"
+}
 
-        let another_third_party = crate::parser::Import {
-            path: "github.com/other/repo".to_string(),
-            alias: None,
-        };
-        assert!(resolver.is_external_dependency(&another_third_party));
+func sum(a, b int) int {
+    return a + b
+}
+"#
+                .to_string(),
+            )
+            .unwrap();
+        let new_source = analyzer
+            .analyze_source(
+                &file_path,
+                r#"package main
 
-        // 测试标准库
-        let std_import = crate::parser::Import {
-            path: "fmt".to_string(),
-            alias: None,
-        };
-        assert!(resolver.is_external_dependency(&std_import));
+type Greeter struct{}
+
+func add(a, b int) int {
+    return a + b
+}
+"#
+                .to_string(),
+            )
+            .unwrap();
+
+        let removed = analyzer.find_removed_declarations(&old_source, &new_source);
+        let names: Vec<(&str, &str)> = removed
+            .iter()
+            .map(|target| match target {
+                ChangeTarget::Removed { declaration } => (declaration.kind(), declaration.name()),
+                other => panic!("unexpected target: {other:?}"),
+            })
+            .collect();
+
+        // sum 被重命名为 add，不应报告为删除
+        assert_eq!(names, vec![("method", "Greet"), ("type", "Legacy")]);
     }
 
     #[test]
-    fn test_extract_module_path_from_go_mod() {
-        let go_mod_content = r#"module github.com/M4n5ter/examplePkg
+    fn test_find_deleted_file_declarations() {
+        let file_path = PathBuf::from("legacy.go");
+        let mut analyzer = SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let old_source = analyzer
+            .analyze_source(
+                &file_path,
+                r#"package main
 
-go 1.21
+const Version = "1.0"
 
-require (
-    github.com/stretchr/testify v1.8.4
+var registry = map[string]int{}
+
+type Legacy struct{}
+
+func (l *Legacy) Run() {}
+
+func helper() {}
+"#
+                .to_string(),
+            )
+            .unwrap();
+
+        let removed = analyzer.find_deleted_file_declarations(&old_source);
+        let names: Vec<(&str, &str)> = removed
+            .iter()
+            .map(|target| match target {
+                ChangeTarget::Removed { declaration } => (declaration.kind(), declaration.name()),
+                other => panic!("unexpected target: {other:?}"),
+            })
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                ("constant", "Version"),
+                ("variable", "registry"),
+                ("type", "Legacy"),
+                ("method", "Run"),
+                ("function", "helper"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_target_versions_from_previous_source() {
+        let file_path = PathBuf::from("greeter.go");
+        let mut analyzer = SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let old_source = analyzer
+            .analyze_source(
+                &file_path,
+                "package main\n\nfunc Greet() string {\n    return \"hello\"\n}\n\nfunc Legacy() {}\n"
+                    .to_string(),
+            )
+            .unwrap();
+        let new_source = analyzer
+            .analyze_source(
+                &file_path,
+                "package main\n\nimport \"fmt\"\n\nfunc Greet() string {\n    return fmt.Sprint(\"hi\")\n}\n"
+                    .to_string(),
+            )
+            .unwrap();
+
+        // 旧版本按名称在变更前的文件中定位，不受行号偏移影响
+        let greet = SourceAnalyzer::collect_functions(&new_source)[0].clone();
+        let versions = analyzer.target_versions(
+            &ChangeTarget::Function(greet.clone()),
+            Some(&old_source),
+            &new_source,
+        );
+        assert_eq!(
+            versions.old,
+            "func Greet() string {\n    return \"hello\"\n}"
+        );
+        assert_eq!(
+            versions.new,
+            "func Greet() string {\n    return fmt.Sprint(\"hi\")\n}"
+        );
+
+        // 没有变更前的文件时旧版本为空
+        let versions = analyzer.target_versions(&ChangeTarget::Function(greet), None, &new_source);
+        assert!(versions.old.is_empty());
+
+        // 删除的声明只有旧版本
+        let removed = analyzer.find_removed_declarations(&old_source, &new_source);
+        let versions = analyzer.target_versions(&removed[0], Some(&old_source), &new_source);
+        assert_eq!(versions.old, "func Legacy() {}");
+        assert!(versions.new.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_type_reference_reports_unresolved_type() {
+        let resolver = DependencyResolver::new();
+        let file_path = PathBuf::from("app/service.go");
+        let source = "package app\n\ntype Config struct {\n    Name string\n}\n".to_string();
+        let mut analyzer = SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_files = vec![analyzer.analyze_source(&file_path, source).unwrap()];
+
+        let found = TypeReference {
+            name: "Config".to_string(),
+            package: None,
+        };
+        let config = resolver
+            .resolve_type_reference(&found, &file_path, 7, &source_files)
+            .unwrap();
+        assert_eq!(config.name, "Config");
+
+        let missing = TypeReference {
+            name: "User".to_string(),
+            package: Some("models".to_string()),
+        };
+        let error = resolver
+            .resolve_type_reference(&missing, &file_path, 12, &source_files)
+            .unwrap_err();
+        match &error {
+            SemanticDiffError::UnresolvedType { name, file, line } => {
+                assert_eq!(name, "models.User");
+                assert_eq!(file, &file_path);
+                assert_eq!(*line, 12);
+            }
+            other => panic!("expected UnresolvedType, got {other:?}"),
+        }
+        assert_eq!(
+            error.to_string(),
+            "Unresolved type models.User referenced at app/service.go:12"
+        );
+    }
+
+    #[test]
+    fn test_symbol_index_matches_linear_search() {
+        let resolver = DependencyResolver::new();
+        let source_files: Vec<SourceFile> = (0..300)
+            .map(|i| {
+                let file_path = PathBuf::from(format!("pkg/file{i}.go"));
+                let source = format!(
+                    "package pkg\n\ntype Model{i} struct {{\n    ID int\n}}\n\nfunc NewModel{i}() *Model{i} {{\n    return &Model{i}{{}}\n}}\n\nfunc (m *Model{i}) Save() error {{\n    return nil\n}}\n"
+                );
+                let mut analyzer = SourceAnalyzer::new_for_file(&file_path).unwrap();
+                analyzer.analyze_source(&file_path, source).unwrap()
+            })
+            .collect();
+
+        let index = resolver.build_index(&source_files);
+
+        for i in 0..300 {
+            let type_name = format!("Model{i}");
+            let func_name = format!("NewModel{i}");
+
+            // 每次查找只检查同名候选
+            assert_eq!(index.types_named(&type_name).len(), 1);
+            assert_eq!(index.functions_named(&func_name).len(), 1);
+
+            let type_ref = TypeReference {
+                name: type_name.clone(),
+                package: None,
+            };
+            let indexed = resolver.resolve_type(&type_ref, &index).unwrap();
+            let linear = resolver
+                .find_type_definition(&type_name, &source_files)
+                .unwrap();
+            assert_eq!(indexed.file_path, linear.file_path);
+
+            let func_call = FunctionCall {
+                name: func_name.clone(),
+                receiver: None,
+                package: None,
+            };
+            let indexed = resolver.resolve_function(&func_call, &index).unwrap();
+            let linear = resolver
+                .find_function_definition(&func_name, &source_files)
+                .unwrap();
+            assert_eq!(indexed.file_path, linear.file_path);
+
+            // 按名称查找与线性查找得到同一个定义
+            assert_eq!(
+                index.find_type(&type_name).unwrap().file_path,
+                linear.file_path
+            );
+            assert_eq!(
+                index.find_function(&func_name).unwrap().file_path,
+                linear.file_path
+            );
+
+            // 所有函数共享同一个索引解析依赖
+            let dependencies = resolver.extract_function_dependencies(&linear, &index);
+            assert!(dependencies.iter().any(|dependency| {
+                dependency.name == type_name
+                    && dependency.dependency_type == DependencyType::Type
+                    && dependency.file_path == linear.file_path
+            }));
+        }
+
+        // 同名方法按接收者区分，顺序与线性查找一致
+        assert_eq!(index.functions_named("Save").len(), 300);
+        let save = FunctionCall {
+            name: "Save".to_string(),
+            receiver: Some("Model42".to_string()),
+            package: None,
+        };
+        let method = resolver.resolve_function(&save, &index).unwrap();
+        assert_eq!(method.file_path, PathBuf::from("pkg/file42.go"));
+        assert_eq!(
+            resolver
+                .find_function_definition("Save", &source_files)
+                .unwrap()
+                .file_path,
+            resolver
+                .resolve_function(
+                    &FunctionCall {
+                        name: "Save".to_string(),
+                        receiver: None,
+                        package: None,
+                    },
+                    &index
+                )
+                .unwrap()
+                .file_path
+        );
+        assert!(index.types_named("Missing").is_empty());
+    }
+
+    #[test]
+    fn test_resolve_qualified_with_import_aliases() {
+        let files = [
+            (
+                "app/compat/user.go",
+                "package models\n\ntype User struct {\n    Legacy bool\n}\n",
+            ),
+            (
+                "app/models/user.go",
+                "package models\n\nconst DefaultRole = \"member\"\n\ntype User struct {\n    Name string\n}\n\nfunc (u *User) Rename(name string) {\n    u.Name = name\n}\n",
+            ),
+            (
+                "app/services/user_service.go",
+                "package services\n\nimport m \"example.com/app/models\"\n\nfunc Load() *m.User {\n    return &m.User{}\n}\n",
+            ),
+            (
+                "app/handlers/user_handler.go",
+                "package handlers\n\nimport (\n    \"example.com/app/models\"\n    _ \"example.com/app/compat\"\n)\n\nfunc Role() string {\n    return models.DefaultRole\n}\n",
+            ),
+        ];
+        let source_files: Vec<SourceFile> = files
+            .iter()
+            .map(|(path, source)| {
+                let file_path = PathBuf::from(path);
+                let mut analyzer = SourceAnalyzer::new_for_file(&file_path).unwrap();
+                analyzer
+                    .analyze_source(&file_path, source.to_string())
+                    .unwrap()
+            })
+            .collect();
+        let imports_of = |index: usize| source_files[index].language_specific.imports();
+        let resolver = DependencyResolver::new_with_project_path("example.com/app".to_string());
+
+        // 别名导入：`m` 映射到 models 包，且只在导入路径对应的目录中查找
+        let user = resolver
+            .resolve_qualified("m", "User", imports_of(2), &source_files)
+            .unwrap();
+        assert_eq!(user.import_path.as_deref(), Some("example.com/app/models"));
+        assert_eq!(user.package, "models");
+        match user.declaration {
+            GoDeclaration::Type(type_def) => {
+                assert_eq!(type_def.file_path, PathBuf::from("app/models/user.go"))
+            }
+            other => panic!("unexpected declaration: {other:?}"),
+        }
+
+        // 默认名称导入，解析到常量
+        let role = resolver
+            .resolve_qualified("models", "DefaultRole", imports_of(3), &source_files)
+            .unwrap();
+        assert!(
+            matches!(role.declaration, GoDeclaration::Constant(ref c) if c.name == "DefaultRole")
+        );
+
+        // 引用文件没有导入该限定符时按包名查找
+        let fallback = resolver
+            .resolve_qualified("models", "DefaultRole", imports_of(2), &source_files)
+            .unwrap();
+        assert_eq!(fallback.import_path, None);
+
+        // 方法不能被包名限定
+        assert!(
+            resolver
+                .resolve_qualified("m", "Rename", imports_of(2), &source_files)
+                .is_none()
+        );
+
+        // 空白导入不进入别名表
+        let handler_info = source_files[3]
+            .language_specific
+            .as_any()
+            .downcast_ref::<GoLanguageInfo>()
+            .unwrap();
+        let aliases = handler_info.import_aliases();
+        assert_eq!(aliases.get("models"), Some(&"example.com/app/models"));
+        assert_eq!(aliases.len(), 1);
+
+        // find_qualified_type_definition 使用同样的解析规则
+        let via_alias = resolver
+            .find_qualified_type_definition(
+                "m",
+                "User",
+                Path::new("app/services/user_service.go"),
+                &source_files,
+            )
+            .unwrap();
+        assert_eq!(via_alias.file_path, PathBuf::from("app/models/user.go"));
+    }
+
+    #[test]
+    fn test_dependency_resolver_creation() {
+        let resolver = DependencyResolver::new();
+        let _default_resolver = DependencyResolver::default();
+
+        // 测试基本功能可用性
+        let type_ref = TypeReference {
+            name: "TestType".to_string(),
+            package: None,
+        };
+
+        let func_call = FunctionCall {
+            name: "testFunc".to_string(),
+            receiver: None,
+            package: None,
+        };
+
+        // 空的源文件列表应该返回 None
+        let index = resolver.build_index(&[]);
+        assert!(resolver.resolve_type(&type_ref, &index).is_none());
+        assert!(resolver.resolve_function(&func_call, &index).is_none());
+    }
+
+    #[test]
+    fn test_is_external_dependency() {
+        let resolver = DependencyResolver::new();
+
+        // 测试标准库
+        let std_import = crate::parser::Import {
+            path: "fmt".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(resolver.is_external_dependency(&std_import));
+
+        let http_import = crate::parser::Import {
+            path: "net/http".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(resolver.is_external_dependency(&http_import));
+
+        // 测试第三方库
+        let github_import = crate::parser::Import {
+            path: "github.com/user/repo".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(resolver.is_external_dependency(&github_import));
+
+        // 测试项目内部包（相对路径）
+        let internal_import = crate::parser::Import {
+            path: "./internal/utils".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(!resolver.is_external_dependency(&internal_import));
+
+        // 注意：没有项目模块路径时，非相对路径的包会被当作外部依赖处理
+        let relative_import = crate::parser::Import {
+            path: "myproject/internal".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(resolver.is_external_dependency(&relative_import));
+    }
+
+    #[test]
+    fn test_is_external_dependency_with_project_path() {
+        // 测试带有项目模块路径的解析器
+        let resolver =
+            DependencyResolver::new_with_project_path("github.com/M4n5ter/examplePkg".to_string());
+
+        // 测试项目内部包
+        let project_internal = crate::parser::Import {
+            path: "github.com/M4n5ter/examplePkg/internal/utils".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(!resolver.is_external_dependency(&project_internal));
+
+        let project_subpackage = crate::parser::Import {
+            path: "github.com/M4n5ter/examplePkg/api/v1".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(!resolver.is_external_dependency(&project_subpackage));
+
+        // 测试第三方库（即使有相似的前缀）
+        let third_party = crate::parser::Import {
+            path: "github.com/M4n5ter/otherPkg".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(resolver.is_external_dependency(&third_party));
+
+        let another_third_party = crate::parser::Import {
+            path: "github.com/other/repo".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(resolver.is_external_dependency(&another_third_party));
+
+        // 测试标准库
+        let std_import = crate::parser::Import {
+            path: "fmt".to_string(),
+            alias: None,
+            kind: crate::parser::ImportKind::Normal,
+        };
+        assert!(resolver.is_external_dependency(&std_import));
+    }
+
+    #[test]
+    fn test_extract_module_path_from_go_mod() {
+        let go_mod_content = r#"module github.com/M4n5ter/examplePkg
+
+go 1.21
+
+require (
+    github.com/stretchr/testify v1.8.4
 )
 "#;
 
@@ -1991,7 +4110,7 @@ go 1.21
         var mapping map[string]Handler
         "#;
 
-        let type_refs = resolver.extract_type_references_from_code(code);
+        let type_refs = resolver.extract_type_references(&BodyReferences::from_body(code));
 
         // 应该找到 User, Config, Handler 等类型
         assert!(!type_refs.is_empty());
@@ -2012,7 +4131,7 @@ go 1.21
         user.GetName()
         "#;
 
-        let func_calls = resolver.extract_function_calls_from_code(code);
+        let func_calls = resolver.extract_function_calls(&BodyReferences::from_body(code), &[]);
 
         // 应该找到函数调用
         assert!(!func_calls.is_empty());
@@ -2021,6 +4140,197 @@ go 1.21
         assert!(func_names.contains(&"Println") || func_names.contains(&"add"));
     }
 
+    #[test]
+    fn test_extract_type_references_from_cst() {
+        let resolver = DependencyResolver::new();
+
+        let code = r#"{
+    // SomeType 只出现在注释中
+    label := "Label{}"
+    if admin, ok := value.(Admin); ok {
+        _ = admin
+    }
+    switch v := value.(type) {
+    case *Guest, models.Member:
+        _ = v
+    }
+    cache := Cache[Key]{}
+    var count int
+    _ = label
+    _ = cache
+    _ = count
+}"#;
+
+        let type_refs = resolver.extract_type_references(&BodyReferences::from_body(code));
+        let reference = |name: &str, package: Option<&str>| TypeReference {
+            name: name.to_string(),
+            package: package.map(str::to_string),
+        };
+        assert_eq!(
+            type_refs,
+            vec![
+                reference("Admin", None),
+                reference("Guest", None),
+                reference("Member", Some("models")),
+                reference("Cache", None),
+                reference("Key", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_references_from_extracted_function() {
+        let source = r#"package app
+
+import "example.com/app/models"
+
+type Config struct{}
+
+func load() {
+    // Comment{} 只出现在注释中
+    config := Config{}
+    var store models.Store
+    label := "Label{}"
+    _, _, _ = config, store, label
+}
+"#;
+        let file_path = PathBuf::from("app/load.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, source.to_string())
+            .expect("Failed to analyze source");
+        let source_files = vec![source_file];
+
+        let resolver = DependencyResolver::new();
+        let mut load = resolver
+            .find_function_definition("load", &source_files)
+            .expect("load");
+        assert_eq!(
+            resolver.extract_type_references(&load.body_references),
+            vec![
+                TypeReference {
+                    name: "Config".to_string(),
+                    package: None,
+                },
+                TypeReference {
+                    name: "Store".to_string(),
+                    package: Some("models".to_string()),
+                },
+            ]
+        );
+
+        // 依赖分析使用提取时收集的类型引用，不再解析函数体文本
+        load.body.clear();
+        let dependencies =
+            resolver.extract_function_dependencies(&load, &resolver.build_index(&source_files));
+        assert!(
+            dependencies
+                .iter()
+                .any(|dep| dep.name == "Config" && dep.dependency_type == DependencyType::Type)
+        );
+    }
+
+    #[test]
+    fn test_extract_function_calls_ignores_non_calls() {
+        let resolver = DependencyResolver::new();
+
+        let code = r#"{
+    if (x > 0) {
+        log("if (not a call)")
+    }
+    for (i < n) {
+    }
+    n := int(x)
+    fmt.Println(n)
+    s.store.Save(n)
+}"#;
+
+        let func_calls =
+            resolver.extract_function_calls(&BodyReferences::from_body(code), &["fmt".to_string()]);
+        assert_eq!(
+            func_calls,
+            vec![
+                FunctionCall {
+                    name: "log".to_string(),
+                    receiver: None,
+                    package: None,
+                },
+                FunctionCall {
+                    name: "Println".to_string(),
+                    receiver: None,
+                    package: Some("fmt".to_string()),
+                },
+                FunctionCall {
+                    name: "Save".to_string(),
+                    receiver: Some("s.store".to_string()),
+                    package: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_function_calls_from_extracted_call_sites() {
+        let source = r#"package app
+
+import (
+    "fmt"
+    store "example.com/app/storage/v2"
+)
+
+func run() {
+    fmt.Println("start")
+    store.Open()
+    helper()
+    _ = int(3)
+}
+
+func helper() {}
+"#;
+        let file_path = PathBuf::from("app/run.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, source.to_string())
+            .expect("Failed to analyze source");
+        let package_names: Vec<String> = source_file
+            .language_specific
+            .imports()
+            .iter()
+            .map(|import| import.local_name().to_string())
+            .collect();
+        let source_files = vec![source_file];
+
+        let resolver = DependencyResolver::new();
+        let mut run = resolver
+            .find_function_definition("run", &source_files)
+            .expect("run");
+        let call = |name: &str, package: Option<&str>| FunctionCall {
+            name: name.to_string(),
+            receiver: None,
+            package: package.map(str::to_string),
+        };
+        assert_eq!(
+            resolver.extract_function_calls(&run.body_references, &package_names),
+            vec![
+                call("Println", Some("fmt")),
+                call("Open", Some("store")),
+                call("helper", None),
+            ]
+        );
+
+        // 依赖分析使用提取时收集的调用，不再解析函数体文本
+        run.body.clear();
+        let dependencies =
+            resolver.extract_function_dependencies(&run, &resolver.build_index(&source_files));
+        assert!(
+            dependencies
+                .iter()
+                .any(|dep| dep.name == "helper" && dep.dependency_type == DependencyType::Function)
+        );
+    }
+
     #[test]
     fn test_filter_internal_dependencies() {
         let resolver = DependencyResolver::new();
@@ -2127,13 +4437,90 @@ go 1.21
     }
 
     #[test]
-    fn test_parse_type_reference() {
-        let analyzer = TypeAnalyzer::new();
+    fn test_find_implementors() {
+        let source = include_str!("../tests/fixtures/storage.go");
 
-        // 测试简单类型
-        let simple_type = analyzer.parse_type_reference("User");
-        assert_eq!(simple_type.name, "User");
-        assert_eq!(simple_type.package, None);
+        let file_path = PathBuf::from("storage.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, source.to_string())
+            .expect("Failed to analyze source");
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Expected Go language info");
+        let storage = go_info
+            .find_type("Storage")
+            .expect("Storage should be parsed")
+            .clone();
+
+        let implementors =
+            TypeAnalyzer::new().find_implementors(&storage, std::slice::from_ref(&source_file));
+        let names: Vec<&str> = implementors.iter().map(|t| t.name.as_str()).collect();
+
+        // NoopStorage 缺少嵌入接口 Closer 提升的 Close 方法，
+        // CountingStorage 的 Snapshot 映射值类型不同，Logger 的 Save 签名不同
+        assert_eq!(names, vec!["MemoryStorage"]);
+    }
+
+    #[test]
+    fn test_embedded_type_dependencies_are_unique() {
+        let source = r#"package models
+
+type Base struct {
+    ID int
+}
+
+type Audit struct {
+    Base
+}
+
+type Owner struct {
+    *Base
+}
+
+type Record struct {
+    Audit
+    Owner
+    Primary Base
+}
+"#;
+
+        let file_path = PathBuf::from("models.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, source.to_string())
+            .expect("Failed to analyze source");
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Expected Go language info");
+        let record = go_info
+            .find_type("Record")
+            .expect("Record should be parsed")
+            .clone();
+
+        let dependencies = TypeAnalyzer::new()
+            .analyze_type_dependencies(&record, std::slice::from_ref(&source_file));
+        let mut names: Vec<&str> = dependencies.iter().map(|d| d.name.as_str()).collect();
+        names.sort_unstable();
+
+        // Base 经由字段、Audit 和 Owner 三条路径到达，只记录一次
+        assert_eq!(names, vec!["Audit", "Base", "Owner"]);
+    }
+
+    #[test]
+    fn test_parse_type_reference() {
+        let analyzer = TypeAnalyzer::new();
+
+        // 测试简单类型
+        let simple_type = analyzer.parse_type_reference("User");
+        assert_eq!(simple_type.name, "User");
+        assert_eq!(simple_type.package, None);
 
         // 测试指针类型
         let pointer_type = analyzer.parse_type_reference("*User");
@@ -2305,6 +4692,434 @@ func main() {}
             .expect("Should be GoLanguageInfo");
 
         assert_eq!(go_info.package_name(), "mypackage");
+        assert!(go_info.has_known_package());
+    }
+
+    #[test]
+    fn test_missing_package_clause_is_unknown() {
+        let go_source = r#"// 缺少 package 子句的片段
+
+func helper() int {
+    return 1
+}
+"#;
+
+        let file_path = PathBuf::from("fragment.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, go_source.to_string())
+            .expect("Failed to analyze source");
+
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Should be GoLanguageInfo");
+
+        assert_eq!(go_info.package_name(), crate::parser::UNKNOWN_PACKAGE_NAME);
+        assert_ne!(go_info.package_name(), "main");
+        assert!(!go_info.has_known_package());
+    }
+
+    #[test]
+    fn test_declaration_byte_ranges() {
+        let go_source = r#"package main
+
+type Config struct {
+    Name string
+}
+
+func Add(a, b int) int {
+    return a + b
+}
+
+const Single = 1
+
+const (
+    Low, High = 1, 9
+    Mid       = 5
+)
+
+var (
+    name  = "x"
+    count int
+)
+"#;
+
+        let file_path = PathBuf::from("ranges.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, go_source.to_string())
+            .expect("Failed to analyze source");
+
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Should be GoLanguageInfo");
+
+        let func = go_info.find_function("Add").expect("Add should be parsed");
+        assert_eq!(
+            &go_source[func.byte_start..func.byte_end],
+            "func Add(a, b int) int {\n    return a + b\n}"
+        );
+
+        let type_def = go_info
+            .find_type("Config")
+            .expect("Config should be parsed");
+        assert_eq!(
+            &go_source[type_def.byte_start..type_def.byte_end],
+            type_def.definition
+        );
+
+        // 分组声明的每个成员使用自己规范的范围，单独声明使用整个声明的范围
+        let spec_text = |name: &str| {
+            let (byte_start, byte_end) = source_file
+                .language_specific
+                .declarations()
+                .iter()
+                .find_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                    Some(GoDeclaration::Constant(c)) if c.name == name => {
+                        Some((c.byte_start, c.byte_end))
+                    }
+                    Some(GoDeclaration::Variable(v)) if v.name == name => {
+                        Some((v.byte_start, v.byte_end))
+                    }
+                    _ => None,
+                })
+                .unwrap();
+            &go_source[byte_start..byte_end]
+        };
+        assert_eq!(spec_text("Single"), "const Single = 1");
+        assert_eq!(spec_text("Low"), "Low, High = 1, 9");
+        assert_eq!(spec_text("High"), "Low, High = 1, 9");
+        assert_eq!(spec_text("Mid"), "Mid       = 5");
+        assert_eq!(spec_text("name"), "name  = \"x\"");
+        assert_eq!(spec_text("count"), "count int");
+    }
+
+    #[test]
+    fn test_function_info_uses_real_parameter_names() {
+        let go_source = r#"package main
+
+func Format(prefix, suffix string, count int, values ...string) string {
+    return prefix
+}
+"#;
+
+        let file_path = PathBuf::from("params.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, go_source.to_string())
+            .expect("Failed to analyze source");
+
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Should be GoLanguageInfo");
+
+        let func = go_info
+            .find_function("Format")
+            .expect("Format should be parsed");
+        let params: Vec<(&str, &str, bool)> = func
+            .parameters
+            .iter()
+            .map(|p| (p.name.as_str(), p.param_type.name.as_str(), p.is_variadic))
+            .collect();
+        assert_eq!(
+            params,
+            vec![
+                ("prefix", "string", false),
+                ("suffix", "string", false),
+                ("count", "int", false),
+                ("values", "string", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_generic_type_parameters() {
+        let go_source = r#"package main
+
+type Number interface {
+    ~int | ~float64
+}
+
+type Result[T any] struct {
+    Value T
+}
+
+func Keys[K comparable, V any](m map[K]V) []K {
+    return nil
+}
+
+func Sum[A, B Number](a A, b B) float64 {
+    return 0
+}
+"#;
+
+        let file_path = PathBuf::from("generics.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, go_source.to_string())
+            .expect("Failed to analyze source");
+
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Should be GoLanguageInfo");
+
+        let params_of = |params: &[crate::parser::GoTypeParam]| -> Vec<(String, String)> {
+            params
+                .iter()
+                .map(|p| (p.name.clone(), p.constraint.clone()))
+                .collect()
+        };
+        let pair = |name: &str, constraint: &str| (name.to_string(), constraint.to_string());
+
+        let result = go_info
+            .find_type("Result")
+            .expect("Result should be parsed");
+        assert_eq!(params_of(&result.type_parameters), vec![pair("T", "any")]);
+
+        let keys = go_info
+            .find_function("Keys")
+            .expect("Keys should be parsed");
+        assert_eq!(
+            params_of(&keys.type_parameters),
+            vec![pair("K", "comparable"), pair("V", "any")]
+        );
+
+        // 共享约束的类型参数按名称展开
+        let sum = go_info.find_function("Sum").expect("Sum should be parsed");
+        assert_eq!(
+            params_of(&sum.type_parameters),
+            vec![pair("A", "Number"), pair("B", "Number")]
+        );
+
+        // 非泛型声明没有类型参数
+        let number = go_info
+            .find_type("Number")
+            .expect("Number should be parsed");
+        assert!(number.type_parameters.is_empty());
+    }
+
+    #[test]
+    fn test_extract_iota_constant_block() {
+        use crate::parser::{GoConstantDefinition, GoDeclaration};
+
+        let source = r#"package main
+
+type ErrorCode int
+
+const (
+    ErrCodeUnknown ErrorCode = iota
+    ErrCodeInvalidInput
+    ErrCodeNotFound
+    ErrCodeUnauthorized
+    ErrCodeInternal
+)
+
+const (
+    _  = iota
+    KB = 1 << (10 * iota)
+    MB
+)
+
+const (
+    Host, Port = "localhost", 8080
+)
+"#;
+
+        let path = PathBuf::from("error_handling.go");
+        let mut analyzer = SourceAnalyzer::new_for_file(&path).unwrap();
+        let source_file = analyzer.analyze_source(&path, source.to_string()).unwrap();
+        let constants: Vec<GoConstantDefinition> = source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Constant(c)) => Some(c.clone()),
+                _ => None,
+            })
+            .collect();
+        let find = |name: &str| constants.iter().find(|c| c.name == name).unwrap();
+
+        let codes = [
+            "ErrCodeUnknown",
+            "ErrCodeInvalidInput",
+            "ErrCodeNotFound",
+            "ErrCodeUnauthorized",
+            "ErrCodeInternal",
+        ];
+        for (index, name) in codes.iter().enumerate() {
+            let constant = find(name);
+            assert_eq!(constant.value, "iota");
+            assert_eq!(constant.iota_index, Some(index));
+            assert_eq!(constant.const_type.as_ref().unwrap().name, "ErrorCode");
+        }
+
+        assert_eq!(find("KB").value, "1 << (10 * iota)");
+        assert_eq!(find("KB").iota_index, Some(1));
+        assert_eq!(find("MB").value, "1 << (10 * iota)");
+        assert_eq!(find("MB").iota_index, Some(2));
+
+        assert_eq!(find("Host").value, "\"localhost\"");
+        assert_eq!(find("Port").value, "8080");
+        assert_eq!(find("Port").iota_index, None);
+    }
+
+    #[test]
+    fn test_extract_doc_comments() {
+        use crate::parser::GoDeclaration;
+
+        let source = r#"package main
+
+// User 表示系统用户
+//
+// 多行文档注释需要完整保留
+type User struct {
+    Name string
+}
+
+// MaxUsers 是用户数量上限
+const MaxUsers = 100
+
+var (
+    // DefaultUser 是默认用户
+    DefaultUser = User{}
+    Anonymous   = User{}
+)
+
+// NewUser 创建用户
+/* 支持块注释 */
+func NewUser() *User {
+    return &User{}
+}
+
+func Undocumented() {}
+"#;
+
+        let path = PathBuf::from("user.go");
+        let mut analyzer = SourceAnalyzer::new_for_file(&path).unwrap();
+        let source_file = analyzer.analyze_source(&path, source.to_string()).unwrap();
+        let doc_of = |name: &str| {
+            source_file
+                .language_specific
+                .declarations()
+                .iter()
+                .find_map(
+                    |decl| match decl.as_any().downcast_ref::<GoDeclaration>()? {
+                        GoDeclaration::Function(f) if f.name == name => Some(f.doc_comment.clone()),
+                        GoDeclaration::Type(t) if t.name == name => Some(t.doc_comment.clone()),
+                        GoDeclaration::Constant(c) if c.name == name => Some(c.doc_comment.clone()),
+                        GoDeclaration::Variable(v) if v.name == name => Some(v.doc_comment.clone()),
+                        _ => None,
+                    },
+                )
+                .unwrap()
+        };
+
+        assert_eq!(
+            doc_of("User").as_deref(),
+            Some("// User 表示系统用户\n//\n// 多行文档注释需要完整保留")
+        );
+        assert_eq!(
+            doc_of("MaxUsers").as_deref(),
+            Some("// MaxUsers 是用户数量上限")
+        );
+        assert_eq!(
+            doc_of("DefaultUser").as_deref(),
+            Some("// DefaultUser 是默认用户")
+        );
+        assert_eq!(doc_of("Anonymous"), None);
+        assert_eq!(
+            doc_of("NewUser").as_deref(),
+            Some("// NewUser 创建用户\n/* 支持块注释 */")
+        );
+        assert_eq!(doc_of("Undocumented"), None);
+    }
+
+    #[test]
+    fn test_registered_parser_dispatch() {
+        use crate::parser::{GenericLanguageInfo, GoParser};
+        use tree_sitter::{Node, Tree};
+
+        /// 借用 Go 语法的假解析器，用于模拟下游注册的自定义语言
+        struct FooParser(GoParser);
+
+        impl LanguageParser for FooParser {
+            fn parse_source(&mut self, source: &str) -> Result<Tree> {
+                self.0.parse_source(source)
+            }
+
+            fn find_node_at_position<'a>(
+                &self,
+                tree: &'a Tree,
+                line: u32,
+                column: u32,
+            ) -> Option<Node<'a>> {
+                self.0.find_node_at_position(tree, line, column)
+            }
+
+            fn get_node_text<'a>(&self, node: Node, source: &'a str) -> &'a str {
+                self.0.get_node_text(node, source)
+            }
+
+            fn walk_tree_collect(&self, root: Node) -> Vec<String> {
+                self.0.walk_tree_collect(root)
+            }
+
+            fn language_name(&self) -> &'static str {
+                "Foo"
+            }
+
+            fn file_extensions(&self) -> &'static [&'static str] {
+                &["foo"]
+            }
+        }
+
+        let path = PathBuf::from("script.foo");
+        assert!(SourceAnalyzer::new_for_file(&path).is_err());
+
+        ParserFactory::register(
+            ".foo",
+            Box::new(|| Box::new(FooParser(GoParser::new().unwrap()))),
+        );
+
+        assert_eq!(
+            ParserFactory::detect_language(&path),
+            Some(SupportedLanguage::Custom("foo"))
+        );
+        let mut analyzer = SourceAnalyzer::new_for_file(&path).unwrap();
+        assert_eq!(analyzer.language(), SupportedLanguage::Custom("foo"));
+        assert_eq!(analyzer.parser.language_name(), "Foo");
+
+        // 自定义语言只提供语法树
+        let source_file = analyzer
+            .analyze_source(&path, "package foo\n".to_string())
+            .unwrap();
+        assert_eq!(source_file.syntax_tree.root_node().kind(), "source_file");
+        assert!(
+            source_file
+                .language_specific
+                .as_any()
+                .downcast_ref::<GenericLanguageInfo>()
+                .is_some()
+        );
+        assert!(source_file.language_specific.declarations().is_empty());
+
+        // 内置语言不受影响
+        assert_eq!(
+            ParserFactory::detect_language(Path::new("main.go")),
+            Some(SupportedLanguage::Go)
+        );
     }
 
     #[test]
@@ -2353,6 +5168,54 @@ import (
         }
     }
 
+    #[test]
+    fn test_extract_import_kinds() {
+        let go_source = r#"package main
+
+import (
+    "fmt"
+    f "fmt"
+    . "strings"
+    _ "github.com/lib/pq"
+)
+"#;
+
+        let temp_file = create_test_go_file(go_source);
+        let file_path = temp_file.path();
+
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_file(file_path)
+            .expect("Failed to analyze file");
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Should be GoLanguageInfo");
+
+        let forms: Vec<(&str, Option<&str>, crate::parser::ImportKind)> = go_info
+            .imports()
+            .iter()
+            .map(|i| (i.path.as_str(), i.alias.as_deref(), i.kind))
+            .collect();
+        assert_eq!(
+            forms,
+            vec![
+                ("fmt", None, crate::parser::ImportKind::Normal),
+                ("fmt", Some("f"), crate::parser::ImportKind::Normal),
+                ("strings", None, crate::parser::ImportKind::Dot),
+                ("github.com/lib/pq", None, crate::parser::ImportKind::Blank),
+            ]
+        );
+
+        // 点导入和空白导入不会被当作包限定符
+        let aliases = go_info.import_aliases();
+        assert_eq!(aliases.get("f"), Some(&"fmt"));
+        assert!(!aliases.contains_key("strings"));
+        assert!(!aliases.contains_key("pq"));
+    }
+
     #[test]
     fn test_extract_function_info() {
         let go_source = r#"package main
@@ -2463,6 +5326,541 @@ func main() {
         assert!(result.is_ok());
         assert!(result.unwrap().is_none(), "Line 100 should not exist");
     }
+
+    #[test]
+    fn test_find_renamed_functions_in_place() {
+        let old_source = r#"package main
+
+func computeTotal(items []int) int {
+    total := 0
+    for _, item := range items {
+        total += item
+    }
+    return total
+}
+
+func helper() int {
+    return 1
+}
+"#;
+
+        let new_source = r#"package main
+
+func sumItems(items []int) int {
+    total := 0
+    for _, item := range items {
+        total += item
+    }
+    return total
+}
+
+func helper() int {
+    return 2
+}
+"#;
+
+        let file_path = PathBuf::from("calc.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+
+        let old_file = analyzer
+            .analyze_source(&file_path, old_source.to_string())
+            .expect("Failed to analyze old source");
+        let new_file = analyzer
+            .analyze_source(&file_path, new_source.to_string())
+            .expect("Failed to analyze new source");
+
+        let renamed = analyzer.find_renamed_functions(&old_file, &new_file);
+        assert_eq!(renamed.len(), 1, "Should report exactly one rename");
+
+        match &renamed[0] {
+            ChangeTarget::Renamed {
+                old_name,
+                new_name,
+                function,
+            } => {
+                assert_eq!(old_name, "computeTotal");
+                assert_eq!(new_name, "sumItems");
+                assert_eq!(function.name, "sumItems");
+            }
+            other => panic!("Expected a rename target, got {other:?}"),
+        }
+        assert_eq!(renamed[0].name(), "sumItems");
+    }
+
+    #[test]
+    fn test_extract_struct_fields() {
+        let go_source = r#"package main
+
+type User struct {
+    Base
+    *audit.Trail
+    ID          int    `json:"id" db:"user_id"`
+    First, Last string `json:"-"`
+    Email       string
+}
+"#;
+
+        let file_path = PathBuf::from("user.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, go_source.to_string())
+            .expect("Failed to analyze source");
+
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Should be GoLanguageInfo");
+        let user = go_info.find_type("User").expect("User should be parsed");
+
+        let names: Vec<&str> = user.fields.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["Base", "Trail", "ID", "First", "Last", "Email"]);
+
+        // 嵌入字段
+        assert!(user.fields[0].embedded);
+        assert_eq!(user.fields[1].field_type.name, "audit.Trail");
+        assert!(user.fields[1].field_type.is_pointer);
+        assert!(user.fields[1].embedded);
+
+        // 标签
+        let id = &user.fields[2];
+        assert!(!id.embedded);
+        assert_eq!(id.field_type.name, "int");
+        assert_eq!(id.tag.as_deref(), Some(r#"json:"id" db:"user_id""#));
+        assert_eq!(id.tag_value("json").as_deref(), Some("id"));
+        assert_eq!(id.tag_value("db").as_deref(), Some("user_id"));
+        assert_eq!(id.tag_value("xml"), None);
+
+        // 分组字段共享类型和标签
+        assert_eq!(user.fields[4].tag_value("json").as_deref(), Some("-"));
+        assert_eq!(user.fields[5].tag, None);
+    }
+
+    #[test]
+    fn test_find_tag_changes() {
+        let old_source = r#"package main
+
+type User struct {
+    Name  string `json:"name" xml:"name"`
+    Email string `json:"email"`
+    Age   int    `json:"age"`
+}
+"#;
+
+        let new_source = r#"package main
+
+type User struct {
+    Name  string `json:"full_name" xml:"name"`
+    Email string `json:"email,omitempty"`
+    Age   int    `json:"age"`
+}
+"#;
+
+        let file_path = PathBuf::from("user.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let old_file = analyzer
+            .analyze_source(&file_path, old_source.to_string())
+            .expect("Failed to analyze old source");
+        let new_file = analyzer
+            .analyze_source(&file_path, new_source.to_string())
+            .expect("Failed to analyze new source");
+
+        let changes = analyzer.find_tag_changes(&old_file, &new_file);
+        assert_eq!(changes.len(), 2);
+
+        // json 键名变化会破坏序列化格式，未变化的 xml 标签不报告
+        let name_change = &changes[0];
+        assert_eq!(name_change.type_name, "User");
+        assert_eq!(name_change.field_name, "Name");
+        assert_eq!(name_change.tag_key, "json");
+        assert_eq!(name_change.old_value.as_deref(), Some("name"));
+        assert_eq!(name_change.new_value.as_deref(), Some("full_name"));
+        assert!(name_change.is_wire_breaking());
+
+        // 只增加 omitempty 选项不改变键名
+        let email_change = &changes[1];
+        assert_eq!(email_change.field_name, "Email");
+        assert!(!email_change.is_wire_breaking());
+    }
+
+    #[test]
+    fn test_classify_changes_for_semver() {
+        let old_source = r#"package main
+
+type Config struct {
+    Name string
+}
+
+func Load(path string) (*Config, error) { return nil, nil }
+
+func helper() {}
+"#;
+
+        let new_source = r#"package main
+
+type Config struct {
+    Name string
+    Port int
+}
+
+func Load(path string) (*Config, error) {
+    return &Config{Name: path}, nil
+}
+
+func Save(c *Config) error { return nil }
+"#;
+
+        let file_path = PathBuf::from("config.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let old_file = analyzer
+            .analyze_source(&file_path, old_source.to_string())
+            .expect("Failed to analyze old source");
+        let new_file = analyzer
+            .analyze_source(&file_path, new_source.to_string())
+            .expect("Failed to analyze new source");
+
+        let changes = analyzer.classify_changes(Some(&old_file), &new_file);
+        let change_of = |name: &str| {
+            changes
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.change.clone())
+        };
+        assert_eq!(change_of("function Load"), Some(CompatChange::Compatible));
+        assert_eq!(change_of("function Save"), Some(CompatChange::Additive));
+        assert_eq!(change_of("function helper"), Some(CompatChange::Internal));
+        assert_eq!(change_of("type Config"), Some(CompatChange::Additive));
+        assert_eq!(semver_impact(&changes), SemverBump::Minor);
+
+        // 反向比较时删除了导出函数和字段，需要升级主版本
+        let reverted = analyzer.classify_changes(Some(&new_file), &old_file);
+        assert_eq!(semver_impact(&reverted), SemverBump::Major);
+    }
+
+    #[test]
+    fn test_classify_changes_detects_visibility_flips() {
+        let old_source = r#"package main
+
+type Session struct{}
+
+const MaxUsers = 10
+
+var defaultTimeout = 30
+
+func GetUser(id int) string { return "" }
+"#;
+
+        let new_source = r#"package main
+
+type session struct{}
+
+const maxUsers = 10
+
+var DefaultTimeout = 30
+
+func getUser(id int) string { return "" }
+"#;
+
+        let file_path = PathBuf::from("user.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let old_file = analyzer
+            .analyze_source(&file_path, old_source.to_string())
+            .expect("Failed to analyze old source");
+        let new_file = analyzer
+            .analyze_source(&file_path, new_source.to_string())
+            .expect("Failed to analyze new source");
+
+        let changes = analyzer.classify_changes(Some(&old_file), &new_file);
+        let hidden = CompatChange::VisibilityChanged {
+            now_exported: false,
+        };
+        let exposed = CompatChange::VisibilityChanged { now_exported: true };
+
+        // 首字母大小写翻转不应被当作一次删除加一次新增
+        assert_eq!(changes.len(), 4);
+        let change_of = |name: &str| {
+            changes
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.change.clone())
+        };
+        assert_eq!(change_of("function getUser"), Some(hidden.clone()));
+        assert_eq!(change_of("type session"), Some(hidden.clone()));
+        assert_eq!(change_of("constant maxUsers"), Some(hidden));
+        assert_eq!(change_of("variable DefaultTimeout"), Some(exposed));
+        assert_eq!(semver_impact(&changes), SemverBump::Major);
+    }
+
+    #[test]
+    fn test_find_grown_functions() {
+        let old_source = r#"package main
+
+func process(items []int) int {
+    return len(items)
+}
+
+func helper() int {
+    return 1
+}
+"#;
+
+        let new_source = r#"package main
+
+func process(items []int) (total int) {
+    for _, item := range items {
+        total += item
+    }
+    return total
+}
+
+func helper() int {
+    return 2
+}
+"#;
+
+        let file_path = PathBuf::from("calc.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let old_file = analyzer
+            .analyze_source(&file_path, old_source.to_string())
+            .expect("Failed to analyze old source");
+        let new_file = analyzer
+            .analyze_source(&file_path, new_source.to_string())
+            .expect("Failed to analyze new source");
+
+        // process 从 3 行增长到 6 行，helper 行数不变
+        let grown = analyzer.find_grown_functions(&old_file, &new_file, 50.0);
+        assert_eq!(grown.len(), 1);
+        assert_eq!(grown[0].name, "process");
+        assert_eq!(grown[0].old_lines, 3);
+        assert_eq!(grown[0].new_lines, 6);
+        assert_eq!(grown[0].growth_ratio, 2.0);
+
+        // 阈值高于实际增长时不报告
+        assert!(
+            analyzer
+                .find_grown_functions(&old_file, &new_file, 100.0)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_grouped_type_declarations() {
+        let source = r#"package main
+
+type (
+    Point struct {
+        X int
+        Y int
+    }
+
+    Shape interface {
+        Area() float64
+    }
+)
+"#;
+
+        let file_path = PathBuf::from("shapes.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, source.to_string())
+            .expect("Failed to analyze source");
+
+        let types: Vec<_> = source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .filter_map(|decl| decl.as_any().downcast_ref::<crate::parser::GoDeclaration>())
+            .filter_map(|decl| match decl {
+                crate::parser::GoDeclaration::Type(type_def) => Some(type_def),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(types.len(), 2);
+
+        let point = types.iter().find(|t| t.name == "Point").expect("Point");
+        assert!(matches!(point.kind, crate::parser::GoTypeKind::Struct));
+        assert!(point.definition.starts_with("type Point struct"));
+        assert!(!point.definition.contains("Shape"));
+        assert_eq!((point.start_line, point.end_line), (3, 6));
+
+        let shape = types.iter().find(|t| t.name == "Shape").expect("Shape");
+        assert!(matches!(shape.kind, crate::parser::GoTypeKind::Interface));
+        assert!(shape.definition.starts_with("type Shape interface"));
+        assert!(!shape.definition.contains("Point"));
+        assert_eq!((shape.start_line, shape.end_line), (8, 10));
+    }
+
+    #[test]
+    fn test_methods_of_value_and_pointer_receivers() {
+        let source = r#"package main
+
+type User struct {
+    Name string
+}
+
+func (u User) DisplayName() string {
+    return u.Name
+}
+
+func (u *User) Rename(name string) {
+    u.Name = name
+}
+
+type Order struct{}
+
+func (o *Order) Total() int {
+    return 0
+}
+"#;
+
+        let file_path = PathBuf::from("user.go");
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(&file_path).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(&file_path, source.to_string())
+            .expect("Failed to analyze source");
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .expect("Expected Go language info");
+
+        let methods = go_info.methods_of("User");
+        let mut names: Vec<_> = methods.iter().map(|m| m.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["DisplayName", "Rename"]);
+
+        for method in &methods {
+            let receiver = method.receiver.as_ref().unwrap();
+            assert_eq!(receiver.name, "u");
+            assert_eq!(receiver.type_name, "User");
+            assert_eq!(receiver.is_pointer, method.name == "Rename");
+        }
+
+        // 带 `*` 的类型名同样可以查找
+        assert_eq!(go_info.methods_of("*User").len(), 2);
+        assert_eq!(go_info.methods_of("Order").len(), 1);
+    }
+
+    #[test]
+    fn test_resolve_function_prefers_build_constraints() {
+        let windows_source = r#"package platform
+
+func platformName() string {
+    return "windows"
+}
+"#;
+        let linux_source = r#"//go:build linux
+
+package platform
+
+func platformName() string {
+    return "linux"
+}
+"#;
+
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(Path::new("name.go")).expect("Failed to create analyzer");
+        let source_files = vec![
+            analyzer
+                .analyze_source(Path::new("name_windows.go"), windows_source.to_string())
+                .expect("Failed to analyze windows source"),
+            analyzer
+                .analyze_source(Path::new("name_linux.go"), linux_source.to_string())
+                .expect("Failed to analyze linux source"),
+        ];
+
+        // 不设置约束时取第一个找到的实现
+        let resolver = DependencyResolver::new();
+        let function = resolver
+            .find_function_definition("platformName", &source_files)
+            .unwrap();
+        assert_eq!(function.file_path, PathBuf::from("name_windows.go"));
+
+        // 针对 Linux 时选择 Linux 实现
+        let resolver = DependencyResolver::new()
+            .with_build_constraints(BuildConstraints::new("linux", "amd64"));
+        let function = resolver
+            .find_function_definition("platformName", &source_files)
+            .unwrap();
+        assert_eq!(function.file_path, PathBuf::from("name_linux.go"));
+        assert!(function.body.contains("\"linux\""));
+
+        // 没有满足约束的实现时退回到第一个找到的实现
+        let resolver = DependencyResolver::new()
+            .with_build_constraints(BuildConstraints::new("darwin", "arm64"));
+        let function = resolver
+            .find_function_definition("platformName", &source_files)
+            .unwrap();
+        assert_eq!(function.file_path, PathBuf::from("name_windows.go"));
+    }
+
+    #[test]
+    fn test_parse_build_constraints_filters_files() {
+        let sources = [
+            (
+                "poll_linux.go",
+                "//go:build linux && amd64\n\npackage poll\n",
+            ),
+            ("poll_bsd.go", "// +build darwin freebsd\n\npackage poll\n"),
+            (
+                "poll_other.go",
+                "//go:build !linux && !darwin\n\npackage poll\n",
+            ),
+            ("poll.go", "package poll\n"),
+        ];
+
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(Path::new("poll.go")).expect("Failed to create analyzer");
+        let source_files: Vec<SourceFile> = sources
+            .iter()
+            .map(|(path, source)| {
+                analyzer
+                    .analyze_source(Path::new(path), source.to_string())
+                    .expect("Failed to analyze source")
+            })
+            .collect();
+
+        // 约束表达式暴露在 SourceFile 上，旧式写法转换为等价表达式
+        assert_eq!(
+            source_files[0].build_constraint.as_deref(),
+            Some("linux && amd64")
+        );
+        assert_eq!(
+            source_files[1].build_constraint.as_deref(),
+            Some("darwin || freebsd")
+        );
+        assert_eq!(source_files[3].build_constraint, None);
+
+        let retained = |constraints: BuildConstraints| -> Vec<String> {
+            source_files
+                .iter()
+                .filter(|file| constraints.matches(file))
+                .map(|file| file.path.display().to_string())
+                .collect()
+        };
+
+        // 无约束的文件总是保留
+        assert_eq!(
+            retained(BuildConstraints::new("linux", "amd64")),
+            vec!["poll_linux.go", "poll.go"]
+        );
+        assert_eq!(
+            retained(BuildConstraints::new("darwin", "arm64")),
+            vec!["poll_bsd.go", "poll.go"]
+        );
+        assert_eq!(
+            retained(BuildConstraints::new("windows", "amd64")),
+            vec!["poll_other.go", "poll.go"]
+        );
+    }
 }
 
 #[cfg(test)]
@@ -2681,6 +6079,7 @@ require (
         let internal_import = crate::parser::Import {
             path: "github.com/M4n5ter/examplePkg/internal/utils".to_string(),
             alias: None,
+            kind: crate::parser::ImportKind::Normal,
         };
         assert!(!resolver.is_external_dependency(&internal_import));
 
@@ -2688,12 +6087,14 @@ require (
         let external_import = crate::parser::Import {
             path: "github.com/stretchr/testify".to_string(),
             alias: None,
+            kind: crate::parser::ImportKind::Normal,
         };
         assert!(resolver.is_external_dependency(&external_import));
 
         let other_external = crate::parser::Import {
             path: "github.com/other/library".to_string(),
             alias: None,
+            kind: crate::parser::ImportKind::Normal,
         };
         assert!(resolver.is_external_dependency(&other_external));
 
@@ -2701,6 +6102,7 @@ require (
         let std_import = crate::parser::Import {
             path: "fmt".to_string(),
             alias: None,
+            kind: crate::parser::ImportKind::Normal,
         };
         assert!(resolver.is_external_dependency(&std_import));
     }
@@ -2717,12 +6119,14 @@ require (
         let import = crate::parser::Import {
             path: "github.com/user/repo".to_string(),
             alias: None,
+            kind: crate::parser::ImportKind::Normal,
         };
         assert!(resolver.is_external_dependency(&import));
 
         let relative_import = crate::parser::Import {
             path: "./internal".to_string(),
             alias: None,
+            kind: crate::parser::ImportKind::Normal,
         };
         assert!(!resolver.is_external_dependency(&relative_import));
     }
@@ -0,0 +1,693 @@
+//! API 兼容性分析
+//!
+//! 比较同一声明在变更前后的版本，判断变更对导出 API 的影响。只有首字母大写的导出声明
+//! 属于包的公开 API，未导出声明的变更不影响调用方
+
+use super::TagChange;
+use crate::parser::{GoFunctionInfo, GoReceiverInfo, GoStructField, GoType, GoTypeDefinition};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// 声明变更对公开 API 的影响
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CompatChange {
+    /// 破坏兼容性，调用方可能需要修改代码；附带具体原因
+    Breaking(Vec<String>),
+    /// 新增公开 API，已有调用方不受影响
+    Additive,
+    /// 删除了导出声明，引用它的调用方无法再编译
+    Removed,
+    /// 公开 API 不变（例如只修改了函数体或参数名）
+    Compatible,
+    /// 变更前后都未导出，不影响公开 API
+    Internal,
+    /// 名称只有首字母大小写发生翻转，声明在导出和未导出之间切换
+    ///
+    /// 例如 `GetUser` 改为 `getUser`：看起来像普通重命名，实际上把声明从公开 API 中移除
+    VisibilityChanged { now_exported: bool },
+}
+
+/// 结构体在两个版本之间的字段级差异
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StructDelta {
+    /// 结构体类型名（取变更后的名称）
+    pub type_name: String,
+    /// 新增的字段
+    pub added: Vec<GoStructField>,
+    /// 删除的字段
+    pub removed: Vec<GoStructField>,
+    /// 类型发生变化的字段
+    pub retyped: Vec<FieldRetype>,
+    /// 序列化标签发生变化的字段
+    pub retagged: Vec<TagChange>,
+}
+
+/// 结构体字段的类型变化
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldRetype {
+    pub field_name: String,
+    pub old_type: GoType,
+    pub new_type: GoType,
+}
+
+impl StructDelta {
+    /// 字段是否完全没有变化
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.retyped.is_empty()
+            && self.retagged.is_empty()
+    }
+
+    /// 字段变化对公开 API 的影响
+    ///
+    /// 删除导出字段或修改其类型会破坏兼容性；新增导出字段是新增 API。
+    /// 未导出结构体的字段变化不影响公开 API
+    pub fn compat(&self) -> CompatChange {
+        if !is_exported(&self.type_name) {
+            return CompatChange::Internal;
+        }
+
+        let mut reasons: Vec<String> = self
+            .removed
+            .iter()
+            .filter(|field| is_exported(&field.name))
+            .map(|field| {
+                format!(
+                    "exported field {}.{} was removed",
+                    self.type_name, field.name
+                )
+            })
+            .collect();
+        reasons.extend(
+            self.retyped
+                .iter()
+                .filter(|retype| is_exported(&retype.field_name))
+                .map(|retype| {
+                    format!(
+                        "exported field {}.{} type changed",
+                        self.type_name, retype.field_name
+                    )
+                }),
+        );
+
+        if !reasons.is_empty() {
+            CompatChange::Breaking(reasons)
+        } else if self.added.iter().any(|field| is_exported(&field.name)) {
+            CompatChange::Additive
+        } else {
+            CompatChange::Compatible
+        }
+    }
+}
+
+/// 已分类的单个声明变更
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClassifiedChange {
+    /// 声明的可读名称，例如 `function Load` 或 `type Config`
+    pub name: String,
+    /// 声明所在的源文件
+    pub file_path: PathBuf,
+    pub change: CompatChange,
+}
+
+/// 建议的语义化版本升级级别，按严重程度从低到高排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SemverBump {
+    /// 只有内部实现变化
+    Patch,
+    /// 新增了公开 API
+    Minor,
+    /// 公开 API 发生了不兼容的变化
+    Major,
+}
+
+impl std::fmt::Display for SemverBump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SemverBump::Patch => write!(f, "patch"),
+            SemverBump::Minor => write!(f, "minor"),
+            SemverBump::Major => write!(f, "major"),
+        }
+    }
+}
+
+impl CompatChange {
+    /// 单个变更对应的版本升级级别
+    pub fn semver_bump(&self) -> SemverBump {
+        match self {
+            CompatChange::Breaking(_) | CompatChange::Removed => SemverBump::Major,
+            CompatChange::VisibilityChanged { now_exported } if !now_exported => SemverBump::Major,
+            CompatChange::Additive | CompatChange::VisibilityChanged { .. } => SemverBump::Minor,
+            CompatChange::Compatible | CompatChange::Internal => SemverBump::Patch,
+        }
+    }
+
+    /// 是否破坏兼容性；导出声明变为未导出同样属于破坏性变更
+    pub fn is_breaking(&self) -> bool {
+        matches!(
+            self,
+            CompatChange::Breaking(_)
+                | CompatChange::Removed
+                | CompatChange::VisibilityChanged {
+                    now_exported: false
+                }
+        )
+    }
+}
+
+/// 名称是否为导出标识符（首字母大写）
+pub fn is_exported(name: &str) -> bool {
+    name.chars().next().is_some_and(char::is_uppercase)
+}
+
+/// 两个名称是否只有首字母大小写不同且导出状态因此翻转，返回变更后是否导出
+///
+/// 例如 `GetUser` 与 `getUser` 返回 `Some(false)`；完全相同或其他重命名返回 None
+pub fn visibility_flip(old_name: &str, new_name: &str) -> Option<bool> {
+    let mut old_chars = old_name.chars();
+    let mut new_chars = new_name.chars();
+    let (old_first, new_first) = (old_chars.next()?, new_chars.next()?);
+
+    let flipped = is_exported(old_name) != is_exported(new_name)
+        && old_first.to_lowercase().eq(new_first.to_lowercase())
+        && old_chars.as_str() == new_chars.as_str();
+    flipped.then(|| is_exported(new_name))
+}
+
+/// 变更前后两组同类声明的配对结果
+pub(crate) struct DeclarationPairs<'a, T> {
+    /// 作用域和名称都相同的声明
+    pub matched: Vec<(&'a T, &'a T)>,
+    /// 作用域相同、名称只有首字母大小写翻转的声明
+    pub flipped: Vec<(&'a T, &'a T)>,
+    /// 只存在于变更前的声明
+    pub removed: Vec<&'a T>,
+    /// 只存在于变更后的声明
+    pub added: Vec<&'a T>,
+}
+
+/// 按 `key` 返回的（作用域，名称）配对变更前后的声明，作用域例如方法的接收者类型
+///
+/// 精确匹配优先；剩余声明中名称只翻转首字母大小写的视为同一声明的可见性变化
+pub(crate) fn pair_declarations<'a, T>(
+    old: &[&'a T],
+    new: &[&'a T],
+    key: impl Fn(&T) -> (Option<String>, String),
+) -> DeclarationPairs<'a, T> {
+    let mut unmatched_old: Vec<&'a T> = old
+        .iter()
+        .copied()
+        .filter(|&old_decl| !new.iter().any(|&new_decl| key(new_decl) == key(old_decl)))
+        .collect();
+    let mut pairs = DeclarationPairs {
+        matched: Vec::new(),
+        flipped: Vec::new(),
+        removed: Vec::new(),
+        added: Vec::new(),
+    };
+
+    for &new_decl in new {
+        let (scope, name) = key(new_decl);
+        if let Some(&old_decl) = old
+            .iter()
+            .find(|&&old_decl| key(old_decl) == (scope.clone(), name.clone()))
+        {
+            pairs.matched.push((old_decl, new_decl));
+            continue;
+        }
+
+        let flipped = unmatched_old.iter().position(|&old_decl| {
+            let (old_scope, old_name) = key(old_decl);
+            old_scope == scope && visibility_flip(&old_name, &name).is_some()
+        });
+        match flipped {
+            Some(index) => pairs.flipped.push((unmatched_old.remove(index), new_decl)),
+            None => pairs.added.push(new_decl),
+        }
+    }
+
+    pairs.removed = unmatched_old;
+    pairs
+}
+
+/// 判断函数或方法的变更对公开 API 的影响
+///
+/// 比较接收者、类型参数、参数类型和返回类型；参数名和命名返回值的名称不属于签名，
+/// 修改它们是兼容的。任何签名变化都视为破坏兼容性，因为即使调用点仍能编译，
+/// 把函数赋值给函数类型变量或实现接口的代码也会失效。只翻转名称首字母大小写的
+/// 变更报告为 [`CompatChange::VisibilityChanged`]
+pub fn classify_function_change(old: &GoFunctionInfo, new: &GoFunctionInfo) -> CompatChange {
+    let method_exported = |function: &GoFunctionInfo| {
+        is_exported(&function.name)
+            && function
+                .receiver
+                .as_ref()
+                .is_none_or(|receiver| is_exported(&receiver.type_name))
+    };
+
+    let same_receiver =
+        old.receiver.as_ref().map(receiver_text) == new.receiver.as_ref().map(receiver_text);
+    let flip = visibility_flip(&old.name, &new.name)
+        .filter(|_| same_receiver && method_exported(old) != method_exported(new));
+    if let Some(now_exported) = flip {
+        return CompatChange::VisibilityChanged { now_exported };
+    }
+
+    match (method_exported(old), method_exported(new)) {
+        (false, false) => return CompatChange::Internal,
+        (false, true) => return CompatChange::Additive,
+        (true, false) => {
+            return CompatChange::Breaking(vec![format!(
+                "exported {} is no longer exported",
+                describe(old)
+            )]);
+        }
+        (true, true) => {}
+    }
+
+    let mut reasons = Vec::new();
+    if old.name != new.name {
+        reasons.push(format!("{} was renamed to {}", describe(old), new.name));
+    }
+
+    match (&old.receiver, &new.receiver) {
+        (Some(old_receiver), Some(new_receiver))
+            if receiver_text(old_receiver) != receiver_text(new_receiver) =>
+        {
+            reasons.push(format!(
+                "receiver changed from {} to {}",
+                receiver_text(old_receiver),
+                receiver_text(new_receiver)
+            ));
+        }
+        (None, Some(_)) | (Some(_), None) => {
+            reasons.push("changed between function and method".to_string());
+        }
+        _ => {}
+    }
+
+    let type_params = |function: &GoFunctionInfo| -> Vec<String> {
+        function
+            .type_parameters
+            .iter()
+            .map(|param| param.constraint.trim().to_string())
+            .collect()
+    };
+    if type_params(old) != type_params(new) {
+        reasons.push("type parameters changed".to_string());
+    }
+
+    let parameters = |function: &GoFunctionInfo| -> Vec<String> {
+        function
+            .parameters
+            .iter()
+            .map(|param| signature_type(&param.param_type, param.is_variadic))
+            .collect()
+    };
+    compare_types(
+        "parameter",
+        &parameters(old),
+        &parameters(new),
+        &mut reasons,
+    );
+
+    let results = |function: &GoFunctionInfo| -> Vec<String> {
+        function
+            .return_types
+            .iter()
+            .map(|result| signature_type(result, false))
+            .collect()
+    };
+    compare_types("result", &results(old), &results(new), &mut reasons);
+
+    if reasons.is_empty() {
+        CompatChange::Compatible
+    } else {
+        CompatChange::Breaking(reasons)
+    }
+}
+
+/// 汇总所有声明变更，给出整体的版本升级建议
+///
+/// 遵循 Go 模块的语义化版本约定：任何破坏导出 API 的变更需要升级主版本，
+/// 新增导出 API 升级次版本，其余只升级修订号。取所有变更中最严重的级别
+pub fn semver_impact(changes: &[ClassifiedChange]) -> SemverBump {
+    changes
+        .iter()
+        .map(|classified| classified.change.semver_bump())
+        .max()
+        .unwrap_or(SemverBump::Patch)
+}
+
+/// 按字段名比较结构体的两个版本，列出新增、删除、改类型和改标签的字段
+///
+/// 嵌入字段以其类型名作为字段名参与匹配；标签只比较 `json`、`xml`、`yaml`
+/// 等影响序列化格式的键
+pub fn diff_struct(old: &GoTypeDefinition, new: &GoTypeDefinition) -> StructDelta {
+    let find = |fields: &[GoStructField], name: &str| -> Option<GoStructField> {
+        fields.iter().find(|field| field.name == name).cloned()
+    };
+
+    let mut delta = StructDelta {
+        type_name: new.name.clone(),
+        added: Vec::new(),
+        removed: old
+            .fields
+            .iter()
+            .filter(|field| find(&new.fields, &field.name).is_none())
+            .cloned()
+            .collect(),
+        retyped: Vec::new(),
+        retagged: Vec::new(),
+    };
+
+    for new_field in &new.fields {
+        let Some(old_field) = find(&old.fields, &new_field.name) else {
+            delta.added.push(new_field.clone());
+            continue;
+        };
+
+        if signature_type(&old_field.field_type, false)
+            != signature_type(&new_field.field_type, false)
+        {
+            delta.retyped.push(FieldRetype {
+                field_name: new_field.name.clone(),
+                old_type: old_field.field_type.clone(),
+                new_type: new_field.field_type.clone(),
+            });
+        }
+
+        for tag_key in TagChange::SERIALIZATION_KEYS {
+            let old_value = old_field.tag_value(tag_key);
+            let new_value = new_field.tag_value(tag_key);
+            if old_value != new_value {
+                delta.retagged.push(TagChange {
+                    type_name: new.name.clone(),
+                    field_name: new_field.name.clone(),
+                    tag_key: tag_key.to_string(),
+                    old_value,
+                    new_value,
+                });
+            }
+        }
+    }
+
+    delta
+}
+
+/// 比较两组签名类型，记录数量或逐个位置的差异
+fn compare_types(kind: &str, old: &[String], new: &[String], reasons: &mut Vec<String>) {
+    if old.len() != new.len() {
+        reasons.push(format!(
+            "{kind} count changed from {} to {}",
+            old.len(),
+            new.len()
+        ));
+        return;
+    }
+
+    for (index, (old_type, new_type)) in old.iter().zip(new).enumerate() {
+        if old_type != new_type {
+            reasons.push(format!("{kind} {} type changed", index + 1));
+        }
+    }
+}
+
+/// 签名中用于比较的类型表示，命名返回值的名称不参与比较
+fn signature_type(go_type: &GoType, is_variadic: bool) -> String {
+    let go_type = GoType {
+        result_name: None,
+        ..go_type.clone()
+    };
+    format!("{is_variadic}:{go_type:?}")
+}
+
+/// 接收者类型文本，例如 `*Server`
+fn receiver_text(receiver: &GoReceiverInfo) -> String {
+    let pointer = if receiver.is_pointer { "*" } else { "" };
+    format!("{pointer}{}", receiver.type_name)
+}
+
+/// 函数或方法的可读名称，例如 `function Handle` 或 `method (*Server).Handle`
+pub(crate) fn describe(function: &GoFunctionInfo) -> String {
+    match &function.receiver {
+        Some(receiver) => format!("method ({}).{}", receiver_text(receiver), function.name),
+        None => format!("function {}", function.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::SourceAnalyzer;
+    use crate::parser::{GoDeclaration, GoParameter, SupportedLanguage};
+    use std::path::Path;
+
+    /// 解析单个类型声明
+    fn parse_type(source: &str) -> GoTypeDefinition {
+        let source_file = SourceAnalyzer::new_for_language(SupportedLanguage::Go)
+            .unwrap()
+            .analyze_source(Path::new("p.go"), format!("package p\n\n{source}\n"))
+            .unwrap();
+        source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .find_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Type(t)) => Some(t.clone()),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    /// 解析单个函数声明
+    fn parse_function(source: &str) -> GoFunctionInfo {
+        let source_file = SourceAnalyzer::new_for_language(SupportedLanguage::Go)
+            .unwrap()
+            .analyze_source(Path::new("p.go"), format!("package p\n\n{source}\n"))
+            .unwrap();
+        source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .find_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Function(f)) | Some(GoDeclaration::Method(f)) => {
+                    Some(f.clone())
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn test_adding_parameter_is_breaking() {
+        let old = parse_function("func Load(path string) error { return nil }");
+        let new = parse_function("func Load(path string, strict bool) error { return nil }");
+
+        let change = classify_function_change(&old, &new);
+        assert_eq!(
+            change,
+            CompatChange::Breaking(vec!["parameter count changed from 1 to 2".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_adding_return_value_is_breaking() {
+        let old = parse_function("func (s *Server) Start() error { return nil }");
+        let new = parse_function("func (s *Server) Start() (int, error) { return 0, nil }");
+
+        let change = classify_function_change(&old, &new);
+        assert!(change.is_breaking());
+        assert_eq!(
+            change,
+            CompatChange::Breaking(vec!["result count changed from 1 to 2".to_string()])
+        );
+
+        // 指针接收者改为值接收者同样改变了方法集
+        let value_receiver = parse_function("func (s Server) Start() error { return nil }");
+        assert_eq!(
+            classify_function_change(&old, &value_receiver),
+            CompatChange::Breaking(vec!["receiver changed from *Server to Server".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_renaming_parameter_is_compatible() {
+        let old = parse_function("func Load(path string) (err error) { return nil }");
+        let new = parse_function("func Load(file string) (e error) {\n\treturn nil\n}");
+
+        assert_eq!(
+            classify_function_change(&old, &new),
+            CompatChange::Compatible
+        );
+
+        // 参数类型变化仍然是破坏性的
+        let mut retyped = new.clone();
+        retyped.parameters = vec![GoParameter {
+            name: "file".to_string(),
+            param_type: GoType::from_type_text("[]byte"),
+            is_variadic: false,
+        }];
+        assert_eq!(
+            classify_function_change(&old, &retyped),
+            CompatChange::Breaking(vec!["parameter 1 type changed".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_unexported_helper_is_internal() {
+        let old = parse_function("func load(path string) error { return nil }");
+        let new =
+            parse_function("func load(path string, strict bool) (int, error) { return 0, nil }");
+        assert_eq!(classify_function_change(&old, &new), CompatChange::Internal);
+
+        // 未导出类型上的导出方法也不属于公开 API
+        let old = parse_function("func (c *cache) Get(key string) string { return key }");
+        let new = parse_function("func (c *cache) Get(key int) string { return \"\" }");
+        assert_eq!(classify_function_change(&old, &new), CompatChange::Internal);
+
+        // 未导出的函数改名为导出函数是新增 API
+        let exported = parse_function("func Save(path string) error { return nil }");
+        let internal = parse_function("func load(path string) error { return nil }");
+        assert_eq!(
+            classify_function_change(&internal, &exported),
+            CompatChange::Additive
+        );
+    }
+
+    #[test]
+    fn test_lowercasing_exported_function_changes_visibility() {
+        let old = parse_function("func GetUser(id int) string { return \"\" }");
+        let new = parse_function("func getUser(id int) string { return \"\" }");
+
+        let change = classify_function_change(&old, &new);
+        assert_eq!(
+            change,
+            CompatChange::VisibilityChanged {
+                now_exported: false
+            }
+        );
+        assert!(change.is_breaking());
+        assert_eq!(change.semver_bump(), SemverBump::Major);
+
+        let change = classify_function_change(&new, &old);
+        assert_eq!(
+            change,
+            CompatChange::VisibilityChanged { now_exported: true }
+        );
+        assert!(!change.is_breaking());
+
+        assert_eq!(visibility_flip("GetUser", "getUser"), Some(false));
+        assert_eq!(visibility_flip("getUser", "GetUser"), Some(true));
+        assert_eq!(visibility_flip("GetUser", "GetUser"), None);
+        assert_eq!(visibility_flip("GetUser", "fetchUser"), None);
+    }
+
+    #[test]
+    fn test_struct_gains_field() {
+        let old = parse_type("type Config struct {\n\tName string\n}");
+        let new = parse_type("type Config struct {\n\tName string\n\tPort int\n\tdebug bool\n}");
+
+        let delta = diff_struct(&old, &new);
+        let added: Vec<_> = delta.added.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(added, vec!["Port", "debug"]);
+        assert!(delta.removed.is_empty());
+        assert!(delta.retyped.is_empty());
+        assert!(delta.retagged.is_empty());
+        assert_eq!(delta.compat(), CompatChange::Additive);
+    }
+
+    #[test]
+    fn test_struct_loses_field() {
+        let old = parse_type("type Config struct {\n\tName string\n\tPort int\n\tdebug bool\n}");
+        let new = parse_type("type Config struct {\n\tName []byte\n}");
+
+        let delta = diff_struct(&old, &new);
+        let removed: Vec<_> = delta.removed.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(removed, vec!["Port", "debug"]);
+        assert!(delta.added.is_empty());
+        assert_eq!(delta.retyped.len(), 1);
+        assert_eq!(delta.retyped[0].field_name, "Name");
+        assert_eq!(
+            delta.compat(),
+            CompatChange::Breaking(vec![
+                "exported field Config.Port was removed".to_string(),
+                "exported field Config.Name type changed".to_string(),
+            ])
+        );
+
+        // 只删除未导出字段不影响公开 API
+        let internal_only = parse_type("type Config struct {\n\tName string\n\tPort int\n}");
+        assert_eq!(
+            diff_struct(&old, &internal_only).compat(),
+            CompatChange::Compatible
+        );
+    }
+
+    #[test]
+    fn test_struct_changes_json_tag() {
+        let old = parse_type("type User struct {\n\tName string `json:\"name\" db:\"name\"`\n}");
+        let new = parse_type(
+            "type User struct {\n\tName string `json:\"full_name\" db:\"full_name\"`\n}",
+        );
+
+        let delta = diff_struct(&old, &new);
+        assert!(delta.added.is_empty());
+        assert!(delta.removed.is_empty());
+        assert!(delta.retyped.is_empty());
+        assert_eq!(
+            delta.retagged,
+            vec![TagChange {
+                type_name: "User".to_string(),
+                field_name: "Name".to_string(),
+                tag_key: "json".to_string(),
+                old_value: Some("name".to_string()),
+                new_value: Some("full_name".to_string()),
+            }]
+        );
+        assert_eq!(delta.compat(), CompatChange::Compatible);
+    }
+
+    #[test]
+    fn test_semver_impact_most_severe_wins() {
+        let classified = |name: &str, change: CompatChange| ClassifiedChange {
+            name: name.to_string(),
+            file_path: PathBuf::from("config.go"),
+            change,
+        };
+
+        assert_eq!(semver_impact(&[]), SemverBump::Patch);
+        assert_eq!(
+            semver_impact(&[
+                classified("function load", CompatChange::Internal),
+                classified("function Save", CompatChange::Compatible),
+            ]),
+            SemverBump::Patch
+        );
+
+        let additive = [
+            classified("function load", CompatChange::Internal),
+            classified("function Load", CompatChange::Additive),
+        ];
+        assert_eq!(semver_impact(&additive), SemverBump::Minor);
+        assert_eq!(semver_impact(&additive).to_string(), "minor");
+
+        // 新增和破坏性变更同时存在时取最严重的级别
+        let mixed = [
+            classified("function Load", CompatChange::Additive),
+            classified(
+                "type Config",
+                CompatChange::Breaking(vec!["exported field Config.Port was removed".to_string()]),
+            ),
+            classified("function load", CompatChange::Internal),
+        ];
+        assert_eq!(semver_impact(&mixed), SemverBump::Major);
+    }
+}
@@ -0,0 +1,339 @@
+//! 构建约束模块
+//!
+//! 根据目标平台（GOOS/GOARCH）和构建标签判断源文件是否参与构建，
+//! 支持文件名后缀（如 `foo_linux.go`、`foo_windows_amd64.go`）、`//go:build` 约束表达式
+//! 以及旧式的 `// +build` 约束行
+
+use crate::parser::SourceFile;
+use std::path::Path;
+
+/// Go 支持的操作系统列表（用于识别文件名后缀）
+const KNOWN_OS: &[&str] = &[
+    "aix",
+    "android",
+    "darwin",
+    "dragonfly",
+    "freebsd",
+    "hurd",
+    "illumos",
+    "ios",
+    "js",
+    "linux",
+    "nacl",
+    "netbsd",
+    "openbsd",
+    "plan9",
+    "solaris",
+    "wasip1",
+    "windows",
+    "zos",
+];
+
+/// Go 支持的体系结构列表（用于识别文件名后缀）
+const KNOWN_ARCH: &[&str] = &[
+    "386", "amd64", "amd64p32", "arm", "armbe", "arm64", "arm64be", "loong64", "mips", "mipsle",
+    "mips64", "mips64le", "ppc", "ppc64", "ppc64le", "riscv", "riscv64", "s390", "s390x", "sparc",
+    "sparc64", "wasm",
+];
+
+/// 满足 `unix` 构建标签的操作系统
+const UNIX_OS: &[&str] = &[
+    "aix",
+    "android",
+    "darwin",
+    "dragonfly",
+    "freebsd",
+    "hurd",
+    "illumos",
+    "ios",
+    "linux",
+    "netbsd",
+    "openbsd",
+    "solaris",
+];
+
+/// 构建约束
+///
+/// 描述当前分析所针对的目标平台和额外启用的构建标签
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildConstraints {
+    /// 目标操作系统（GOOS）
+    pub goos: String,
+    /// 目标体系结构（GOARCH）
+    pub goarch: String,
+    /// 额外启用的构建标签
+    pub tags: Vec<String>,
+}
+
+impl Default for BuildConstraints {
+    fn default() -> Self {
+        Self::host()
+    }
+}
+
+impl BuildConstraints {
+    /// 创建针对指定平台的构建约束
+    pub fn new(goos: impl Into<String>, goarch: impl Into<String>) -> Self {
+        Self {
+            goos: goos.into(),
+            goarch: goarch.into(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// 创建针对当前主机平台的构建约束
+    pub fn host() -> Self {
+        let goos = match std::env::consts::OS {
+            "macos" => "darwin",
+            os => os,
+        };
+        let goarch = match std::env::consts::ARCH {
+            "x86_64" => "amd64",
+            "x86" => "386",
+            "aarch64" => "arm64",
+            "powerpc64" => "ppc64",
+            arch => arch,
+        };
+        Self::new(goos, goarch)
+    }
+
+    /// 设置额外启用的构建标签
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 检查源文件是否满足构建约束
+    pub fn matches(&self, source_file: &SourceFile) -> bool {
+        self.matches_file_name(&source_file.path)
+            && parse_build_expression(&source_file.source_code)
+                .is_none_or(|expression| self.evaluate(&expression))
+    }
+
+    /// 检查文件名中的 `_GOOS`、`_GOARCH`、`_GOOS_GOARCH` 后缀是否匹配
+    pub fn matches_file_name(&self, path: &Path) -> bool {
+        let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            return true;
+        };
+        let stem = stem.strip_suffix("_test").unwrap_or(stem);
+
+        // 第一段是文件名本身，不参与平台匹配（`linux.go` 不受约束）
+        let parts: Vec<&str> = stem.split('_').skip(1).collect();
+        match parts.as_slice() {
+            [.., os, arch] if KNOWN_OS.contains(os) && KNOWN_ARCH.contains(arch) => {
+                *os == self.goos && *arch == self.goarch
+            }
+            [.., last] if KNOWN_OS.contains(last) => *last == self.goos,
+            [.., last] if KNOWN_ARCH.contains(last) => *last == self.goarch,
+            _ => true,
+        }
+    }
+
+    /// 计算 `//go:build` 约束表达式，无法解析的表达式视为满足
+    pub fn evaluate(&self, expression: &str) -> bool {
+        let tokens = tokenize(expression);
+        let mut parser = ExpressionParser {
+            tokens: &tokens,
+            position: 0,
+            constraints: self,
+        };
+        match parser.parse_or() {
+            Some(result) if parser.position == tokens.len() => result,
+            _ => true,
+        }
+    }
+
+    /// 检查单个构建标签是否启用
+    fn has_tag(&self, tag: &str) -> bool {
+        tag == self.goos
+            || tag == self.goarch
+            || (tag == "unix" && UNIX_OS.contains(&self.goos.as_str()))
+            // Go 版本标签（go1.N）总是视为满足
+            || tag.starts_with("go1.")
+            || self.tags.iter().any(|t| t == tag)
+    }
+}
+
+/// 提取 package 子句之前的构建约束表达式
+///
+/// 优先使用 `//go:build` 行；只有旧式 `// +build` 行时将其转换为等价的表达式：
+/// 行内空格分隔的选项为“或”，逗号分隔的项为“与”，多行之间为“与”
+pub fn parse_build_expression(source_code: &str) -> Option<String> {
+    let header: Vec<&str> = source_code
+        .lines()
+        .map(str::trim)
+        .take_while(|line| !line.starts_with("package "))
+        .collect();
+
+    if let Some(expression) = header
+        .iter()
+        .find_map(|line| line.strip_prefix("//go:build "))
+    {
+        return Some(expression.trim().to_string());
+    }
+
+    let lines: Vec<String> = header
+        .iter()
+        .filter_map(|line| line.strip_prefix("// +build "))
+        .map(|line| {
+            line.split_whitespace()
+                .map(|option| option.split(',').collect::<Vec<_>>().join(" && "))
+                .map(|option| {
+                    if option.contains("&&") {
+                        format!("({option})")
+                    } else {
+                        option
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" || ")
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+    match lines.len() {
+        0 => None,
+        1 => lines.into_iter().next(),
+        _ => Some(
+            lines
+                .iter()
+                .map(|line| format!("({line})"))
+                .collect::<Vec<_>>()
+                .join(" && "),
+        ),
+    }
+}
+
+/// 将约束表达式切分为词法单元
+fn tokenize(expression: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+
+    while !rest.is_empty() {
+        let length = if rest.starts_with("&&") || rest.starts_with("||") {
+            2
+        } else if rest.starts_with(['(', ')', '!']) {
+            1
+        } else {
+            rest.find(|c: char| c.is_whitespace() || "()!&|".contains(c))
+                .unwrap_or(rest.len())
+                .max(1)
+        };
+        tokens.push(&rest[..length]);
+        rest = rest[length..].trim_start();
+    }
+
+    tokens
+}
+
+/// 约束表达式的递归下降解析器
+///
+/// 语法：`or := and ("||" and)*`，`and := unary ("&&" unary)*`，
+/// `unary := "!" unary | "(" or ")" | tag`
+struct ExpressionParser<'a> {
+    tokens: &'a [&'a str],
+    position: usize,
+    constraints: &'a BuildConstraints,
+}
+
+impl ExpressionParser<'_> {
+    fn parse_or(&mut self) -> Option<bool> {
+        let mut result = self.parse_and()?;
+        while self.tokens.get(self.position) == Some(&"||") {
+            self.position += 1;
+            let rhs = self.parse_and()?;
+            result = result || rhs;
+        }
+        Some(result)
+    }
+
+    fn parse_and(&mut self) -> Option<bool> {
+        let mut result = self.parse_unary()?;
+        while self.tokens.get(self.position) == Some(&"&&") {
+            self.position += 1;
+            let rhs = self.parse_unary()?;
+            result = result && rhs;
+        }
+        Some(result)
+    }
+
+    fn parse_unary(&mut self) -> Option<bool> {
+        let token = *self.tokens.get(self.position)?;
+        self.position += 1;
+        match token {
+            "!" => self.parse_unary().map(|value| !value),
+            "(" => {
+                let value = self.parse_or()?;
+                if self.tokens.get(self.position) != Some(&")") {
+                    return None;
+                }
+                self.position += 1;
+                Some(value)
+            }
+            ")" | "&&" | "||" => None,
+            tag => Some(self.constraints.has_tag(tag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_file_name_suffixes() {
+        let linux = BuildConstraints::new("linux", "amd64");
+
+        assert!(linux.matches_file_name(&PathBuf::from("net_linux.go")));
+        assert!(!linux.matches_file_name(&PathBuf::from("net_windows.go")));
+        assert!(linux.matches_file_name(&PathBuf::from("net_linux_amd64.go")));
+        assert!(!linux.matches_file_name(&PathBuf::from("net_linux_arm64.go")));
+        assert!(!linux.matches_file_name(&PathBuf::from("net_arm64.go")));
+        assert!(!linux.matches_file_name(&PathBuf::from("net_windows_test.go")));
+        // 没有平台后缀或文件名本身就是平台名时不受约束
+        assert!(linux.matches_file_name(&PathBuf::from("net.go")));
+        assert!(linux.matches_file_name(&PathBuf::from("windows.go")));
+    }
+
+    #[test]
+    fn test_evaluate_build_expressions() {
+        let linux = BuildConstraints::new("linux", "amd64").with_tags(["integration"]);
+
+        assert!(linux.evaluate("linux"));
+        assert!(!linux.evaluate("windows"));
+        assert!(linux.evaluate("unix && !windows"));
+        assert!(linux.evaluate("(darwin || linux) && amd64"));
+        assert!(!linux.evaluate("!linux || arm64"));
+        assert!(linux.evaluate("integration && go1.21"));
+        assert!(!linux.evaluate("ignore"));
+        // 无法解析的表达式视为满足
+        assert!(linux.evaluate("linux &&"));
+    }
+
+    #[test]
+    fn test_parse_legacy_build_lines() {
+        assert_eq!(
+            parse_build_expression("// +build linux,amd64 darwin\n\npackage net\n").as_deref(),
+            Some("(linux && amd64) || darwin")
+        );
+        assert_eq!(
+            parse_build_expression("// +build linux darwin\n// +build !cgo\n\npackage net\n")
+                .as_deref(),
+            Some("(linux || darwin) && (!cgo)")
+        );
+        // 同时存在时以 `//go:build` 为准
+        assert_eq!(
+            parse_build_expression("//go:build linux\n// +build linux\n\npackage net\n").as_deref(),
+            Some("linux")
+        );
+        // package 子句之后的注释不是构建约束
+        assert_eq!(
+            parse_build_expression("package net\n\n//go:build linux\n"),
+            None
+        );
+    }
+}
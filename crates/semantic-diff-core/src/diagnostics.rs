@@ -0,0 +1,170 @@
+//! 诊断信息收集
+//!
+//! 分析过程中单个文件或变更目标的可恢复错误不会中断整次分析，而是记录为诊断信息，
+//! 与部分结果一同返回给调用方，以便嵌入本库的工具了解哪些内容被跳过
+
+use crate::error::SemanticDiffError;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 诊断信息的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DiagnosticKind {
+    /// 文件无法读取或解析
+    ParseFailure,
+    /// 解析超时
+    Timeout,
+    /// 依赖无法解析
+    UnresolvedDependency,
+    /// 其他可恢复的错误
+    Other,
+}
+
+impl DiagnosticKind {
+    /// 根据错误类型确定诊断种类
+    pub fn from_error(error: &SemanticDiffError) -> Self {
+        match error {
+            SemanticDiffError::IoError(_)
+            | SemanticDiffError::ParseError(_)
+            | SemanticDiffError::MalformedSyntax { .. }
+            | SemanticDiffError::TreeSitterError(_) => Self::ParseFailure,
+            SemanticDiffError::ParseTimeout(_) => Self::Timeout,
+            SemanticDiffError::DependencyError(_)
+            | SemanticDiffError::UnresolvedType { .. }
+            | SemanticDiffError::ModuleResolution { .. } => Self::UnresolvedDependency,
+            _ => Self::Other,
+        }
+    }
+
+    /// 诊断种类的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ParseFailure => "parse failure",
+            Self::Timeout => "timeout",
+            Self::UnresolvedDependency => "unresolved dependency",
+            Self::Other => "error",
+        }
+    }
+}
+
+/// 单条诊断信息
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Diagnostic {
+    /// 诊断种类
+    pub kind: DiagnosticKind,
+    /// 出错的文件
+    pub file_path: Option<PathBuf>,
+    /// 出错的变更目标名称
+    pub target: Option<String>,
+    /// 错误信息
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind.as_str())?;
+        if let Some(file_path) = &self.file_path {
+            write!(f, " in {}", file_path.display())?;
+        }
+        if let Some(target) = &self.target {
+            write!(f, " ({target})")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// 诊断信息收集器
+///
+/// 由调用方创建并传入分析流程，是否继续分析由 [`ErrorRecoveryStrategy`] 决定
+///
+/// [`ErrorRecoveryStrategy`]: crate::performance::ErrorRecoveryStrategy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Diagnostics {
+    entries: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// 创建空的收集器
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一条诊断信息
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.entries.push(diagnostic);
+    }
+
+    /// 将错误记录为诊断信息
+    pub fn record_error(
+        &mut self,
+        error: &SemanticDiffError,
+        file_path: Option<&Path>,
+        target: Option<&str>,
+    ) {
+        self.push(Diagnostic {
+            kind: DiagnosticKind::from_error(error),
+            file_path: file_path.map(Path::to_path_buf),
+            target: target.map(str::to_string),
+            message: error.to_string(),
+        });
+    }
+
+    /// 合并另一个收集器中的诊断信息
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.entries.extend(other.entries);
+    }
+
+    /// 所有诊断信息，按记录顺序排列
+    pub fn entries(&self) -> &[Diagnostic] {
+        &self.entries
+    }
+
+    /// 诊断信息条数
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// 是否没有任何诊断信息
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_error_classifies_kind() {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.record_error(
+            &SemanticDiffError::ParseTimeout("large.go".to_string()),
+            Some(Path::new("large.go")),
+            None,
+        );
+        diagnostics.record_error(
+            &SemanticDiffError::DependencyError("Config not found".to_string()),
+            Some(Path::new("service.go")),
+            Some("Handle"),
+        );
+
+        let kinds: Vec<_> = diagnostics.entries().iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                DiagnosticKind::Timeout,
+                DiagnosticKind::UnresolvedDependency
+            ]
+        );
+        assert_eq!(
+            diagnostics.entries()[1].to_string(),
+            "unresolved dependency in service.go (Handle): Dependency resolution failed: Config not found"
+        );
+    }
+}
@@ -0,0 +1,409 @@
+//! 变更目标检测模块
+//!
+//! 将已解析的源文件与文件变更结合，检测出需要分析的变更目标。
+//! 该步骤不依赖 Git 仓库，也不进行上下文提取，嵌入方可以在提取前检查或调整目标列表
+
+use crate::analyzer::SourceAnalyzer;
+use crate::error::Result;
+use crate::extractor::ChangeTarget;
+use crate::git::{ChangeType, DiffHunk, DiffLineType, FileChange};
+use crate::parser::SourceFile;
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tracing::debug;
+use tree_sitter::Node;
+
+/// 各类风险用法的匹配模式
+static RISK_PATTERNS: LazyLock<[(RiskKind, Regex); 3]> = LazyLock::new(|| {
+    [
+        (RiskKind::Panic, Regex::new(r"\bpanic\s*\(").unwrap()),
+        (RiskKind::Unsafe, Regex::new(r"\bunsafe\.").unwrap()),
+        (RiskKind::Reflection, Regex::new(r"\breflect\.").unwrap()),
+    ]
+});
+
+/// 语义差异分析的入口
+pub struct SemanticDiff;
+
+/// 审查风险信号的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RiskKind {
+    /// 新增的 `panic(...)` 调用
+    Panic,
+    /// 新增的 `unsafe.` 用法
+    Unsafe,
+    /// 新增的 `reflect.` 用法
+    Reflection,
+}
+
+impl RiskKind {
+    /// 识别代码行中的风险用法，调用方需要先去掉行中的注释
+    fn detect(code: &str) -> Vec<RiskKind> {
+        RISK_PATTERNS
+            .iter()
+            .filter(|(_, pattern)| pattern.is_match(code))
+            .map(|(kind, _)| *kind)
+            .collect()
+    }
+
+    /// 风险类别的简短描述
+    pub fn description(&self) -> &'static str {
+        match self {
+            RiskKind::Panic => "panic",
+            RiskKind::Unsafe => "unsafe",
+            RiskKind::Reflection => "reflection",
+        }
+    }
+}
+
+/// 变更引入的审查风险信号
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RiskSignal {
+    /// 风险类别
+    pub kind: RiskKind,
+    /// 所在文件
+    pub file_path: PathBuf,
+    /// 新版本文件中的行号（从 1 开始，与差异行号一致）
+    pub line: u32,
+    /// 新增行的内容
+    pub content: String,
+}
+
+impl SemanticDiff {
+    /// 根据文件变更检测源文件中的变更目标
+    ///
+    /// 源文件路径可以是文件变更路径本身，也可以是拼接了仓库根目录 `repo_root` 的路径，
+    /// 匹配规则见 [`FileChange::matches_path`]。删除的文件和二进制文件会被跳过
+    pub fn detect_targets(
+        source_files: &[SourceFile],
+        file_changes: &[FileChange],
+        repo_root: &Path,
+    ) -> Result<Vec<ChangeTarget>> {
+        let mut change_targets = Vec::new();
+
+        for source_file in source_files {
+            let file_change = file_changes
+                .iter()
+                .filter(|fc| !fc.is_binary && fc.change_type != ChangeType::Deleted)
+                .find(|fc| fc.matches_path(&source_file.path, repo_root));
+
+            let Some(file_change) = file_change else {
+                continue;
+            };
+
+            let analyzer = SourceAnalyzer::new_for_language(source_file.language)?;
+            for function in analyzer.find_changed_functions(source_file, &file_change.hunks)? {
+                debug!(
+                    "Found changed function: {} in {:?}",
+                    function.name, source_file.path
+                );
+                change_targets.push(ChangeTarget::Function(function));
+            }
+        }
+
+        Ok(change_targets)
+    }
+
+    /// 扫描变更目标范围内新增的行，报告其中引入的 panic、unsafe 和 reflect 用法
+    ///
+    /// 只检查差异中的新增行，并且只保留落在变更目标行范围内的行，
+    /// 已存在的用法不会被重复报告。`source_file` 是变更目标所在的新版本文件，
+    /// 其语法树中的注释节点不参与匹配
+    pub fn detect_risks(
+        target: &ChangeTarget,
+        source_file: &SourceFile,
+        hunks: &[DiffHunk],
+    ) -> Vec<RiskSignal> {
+        let (start_line, end_line, file_path) = match target {
+            ChangeTarget::Function(f) | ChangeTarget::Renamed { function: f, .. } => {
+                (f.start_line, f.end_line, &f.file_path)
+            }
+            ChangeTarget::Type(t) => (t.start_line, t.end_line, &t.file_path),
+            ChangeTarget::Variable(v) => (v.start_line, v.end_line, &v.file_path),
+            ChangeTarget::Constant(c) => (c.start_line, c.end_line, &c.file_path),
+            // 被删除的声明没有新增行，包目标没有单一的行范围
+            ChangeTarget::Removed { .. } | ChangeTarget::Package { .. } => return Vec::new(),
+        };
+        // 声明的行号从 0 开始，差异行号从 1 开始
+        let target_lines = start_line + 1..=end_line + 1;
+        let code = Self::strip_comments(source_file);
+        let code_lines: Vec<&str> = code.lines().collect();
+
+        let mut signals = Vec::new();
+        for diff_line in hunks.iter().flat_map(|hunk| &hunk.lines) {
+            if diff_line.line_type != DiffLineType::Added {
+                continue;
+            }
+            let Some(line) = diff_line
+                .new_line_number
+                .filter(|line| target_lines.contains(line))
+            else {
+                continue;
+            };
+
+            let content = diff_line.content.trim_start_matches('+').trim();
+            let code_line = code_lines
+                .get(line as usize - 1)
+                .copied()
+                .unwrap_or_default();
+            for kind in RiskKind::detect(code_line) {
+                debug!(
+                    "Found {} usage in {} at line {}",
+                    kind.description(),
+                    target.name(),
+                    line
+                );
+                signals.push(RiskSignal {
+                    kind,
+                    file_path: file_path.clone(),
+                    line,
+                    content: content.to_string(),
+                });
+            }
+        }
+
+        signals
+    }
+
+    /// 将源文件中的注释替换为空格，保留换行使行号不变
+    fn strip_comments(source_file: &SourceFile) -> String {
+        let source = &source_file.source_code;
+        let mut spans = Vec::new();
+        Self::collect_comment_spans(source_file.syntax_tree.root_node(), &mut spans);
+
+        let mut code = String::with_capacity(source.len());
+        let mut position = 0;
+        for span in spans {
+            code.push_str(&source[position..span.start]);
+            code.extend(
+                source[span.clone()]
+                    .chars()
+                    .map(|c| if c == '\n' { '\n' } else { ' ' }),
+            );
+            position = span.end;
+        }
+        code.push_str(&source[position..]);
+        code
+    }
+
+    /// 按出现顺序收集语法树中注释节点的字节范围
+    fn collect_comment_spans(node: Node, spans: &mut Vec<std::ops::Range<usize>>) {
+        if node.kind() == "comment" {
+            spans.push(node.byte_range());
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::collect_comment_spans(child, spans);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::{DiffHunk, DiffLine, DiffLineType};
+    use std::path::{Path, PathBuf};
+
+    fn create_file_change(file_path: &str, change_type: ChangeType, new_start: u32) -> FileChange {
+        FileChange {
+            file_path: PathBuf::from(file_path),
+            change_type,
+            hunks: vec![DiffHunk {
+                old_start: new_start,
+                old_lines: 1,
+                new_start,
+                new_lines: 1,
+                lines: vec![DiffLine {
+                    content: "    x := 2".to_string(),
+                    line_type: DiffLineType::Added,
+                    old_line_number: None,
+                    new_line_number: Some(new_start),
+                }],
+                context_lines: 0,
+            }],
+            is_binary: false,
+        }
+    }
+
+    #[test]
+    fn test_detect_targets() {
+        let source = r#"package main
+
+func alpha() int {
+    return 1
+}
+
+
+
+func beta() int {
+    x := 2
+    return x
+}
+"#;
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(Path::new("main.go")).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(Path::new("/repo/cmd/main.go"), source.to_string())
+            .expect("Failed to analyze source");
+        let source_files = vec![source_file];
+
+        // 只有 beta 所在的行发生了变更
+        let file_changes = vec![create_file_change("cmd/main.go", ChangeType::Modified, 10)];
+        let targets =
+            SemanticDiff::detect_targets(&source_files, &file_changes, Path::new("/repo")).unwrap();
+        let names: Vec<_> = targets.iter().map(|t| t.name()).collect();
+        assert_eq!(names, vec!["beta"]);
+        assert!(matches!(targets[0], ChangeTarget::Function(_)));
+
+        // 不匹配的文件和已删除的文件不产生变更目标
+        let file_changes = vec![
+            create_file_change("other.go", ChangeType::Modified, 10),
+            create_file_change("cmd/main.go", ChangeType::Deleted, 10),
+        ];
+        let targets =
+            SemanticDiff::detect_targets(&source_files, &file_changes, Path::new("/repo")).unwrap();
+        assert!(targets.is_empty());
+
+        // 路径按仓库相对路径完整比较，同名的 vendor 文件不会被当作变更文件
+        let vendored = analyzer
+            .analyze_source(Path::new("/repo/vendor/x/cmd/main.go"), source.to_string())
+            .expect("Failed to analyze source");
+        let file_changes = vec![create_file_change("cmd/main.go", ChangeType::Modified, 10)];
+        let targets =
+            SemanticDiff::detect_targets(&[vendored], &file_changes, Path::new("/repo")).unwrap();
+        assert!(targets.is_empty());
+    }
+
+    #[test]
+    fn test_detect_risks_in_added_lines() {
+        let source = r#"package main
+
+func load(path string) []byte {
+    data, err := read(path)
+    if err != nil {
+        panic(err)
+    }
+    return data
+}
+"#;
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(Path::new("main.go")).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(Path::new("main.go"), source.to_string())
+            .expect("Failed to analyze source");
+
+        let line = |content: &str, line_type: DiffLineType, new_line: Option<u32>| DiffLine {
+            content: content.to_string(),
+            line_type,
+            old_line_number: None,
+            new_line_number: new_line,
+        };
+        let hunks = vec![DiffHunk {
+            old_start: 4,
+            old_lines: 2,
+            new_start: 4,
+            new_lines: 5,
+            lines: vec![
+                line(
+                    "    data, err := read(path)",
+                    DiffLineType::Context,
+                    Some(4),
+                ),
+                line("+    if err != nil {", DiffLineType::Added, Some(5)),
+                line("+        panic(err)", DiffLineType::Added, Some(6)),
+                line("+    }", DiffLineType::Added, Some(7)),
+                // 删除的行和目标范围外的新增行不报告
+                line("-    reflect.TypeOf(data)", DiffLineType::Removed, None),
+                line("+var _ = unsafe.Sizeof(0)", DiffLineType::Added, Some(11)),
+            ],
+            context_lines: 1,
+        }];
+
+        let targets = SemanticDiff::detect_targets(
+            std::slice::from_ref(&source_file),
+            &[FileChange {
+                file_path: PathBuf::from("main.go"),
+                change_type: ChangeType::Modified,
+                hunks: hunks.clone(),
+                is_binary: false,
+            }],
+            Path::new("."),
+        )
+        .unwrap();
+        let load = targets.iter().find(|t| t.name() == "load").unwrap();
+
+        let risks = SemanticDiff::detect_risks(load, &source_file, &hunks);
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].kind, RiskKind::Panic);
+        assert_eq!(risks[0].line, 6);
+        assert_eq!(risks[0].content, "panic(err)");
+        assert_eq!(risks[0].file_path, PathBuf::from("main.go"));
+
+        assert_eq!(
+            RiskKind::detect("p := unsafe.Pointer(reflect.ValueOf(v).Pointer())"),
+            vec![RiskKind::Unsafe, RiskKind::Reflection]
+        );
+    }
+
+    #[test]
+    fn test_detect_risks_ignores_comments() {
+        let source = r#"package main
+
+func fetch() {
+    url := "http://example.com"; panic(url)
+    x := 1 // reflect.TypeOf(x)
+    /* unsafe.Pointer(nil) */
+}
+"#;
+        let mut analyzer =
+            SourceAnalyzer::new_for_file(Path::new("main.go")).expect("Failed to create analyzer");
+        let source_file = analyzer
+            .analyze_source(Path::new("main.go"), source.to_string())
+            .expect("Failed to analyze source");
+
+        let lines: Vec<DiffLine> = source
+            .lines()
+            .enumerate()
+            .skip(3)
+            .take(3)
+            .map(|(index, content)| DiffLine {
+                content: format!("+{content}"),
+                line_type: DiffLineType::Added,
+                old_line_number: None,
+                new_line_number: Some(index as u32 + 1),
+            })
+            .collect();
+        let hunks = vec![DiffHunk {
+            old_start: 4,
+            old_lines: 0,
+            new_start: 4,
+            new_lines: 3,
+            lines,
+            context_lines: 0,
+        }];
+        let targets = SemanticDiff::detect_targets(
+            std::slice::from_ref(&source_file),
+            &[FileChange {
+                file_path: PathBuf::from("main.go"),
+                change_type: ChangeType::Modified,
+                hunks: hunks.clone(),
+                is_binary: false,
+            }],
+            Path::new("."),
+        )
+        .unwrap();
+
+        // 字符串中的 `//` 不会截断代码，注释中的用法不报告
+        let risks = SemanticDiff::detect_risks(&targets[0], &source_file, &hunks);
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].kind, RiskKind::Panic);
+        assert_eq!(risks[0].line, 4);
+    }
+}
@@ -1,3 +1,4 @@
+use std::path::PathBuf;
 use thiserror::Error;
 
 /// semantic-diff 工具的错误类型定义
@@ -6,15 +7,49 @@ pub enum SemanticDiffError {
     #[error("Git repository error: {0}")]
     GitError(String),
 
+    /// 其他解析错误，没有更具体的变体时使用
     #[error("Go source parsing error: {0}")]
     ParseError(String),
 
+    /// 引用的类型在已分析的源文件中找不到定义
+    #[error("Unresolved type {name} referenced at {}:{line}", file.display())]
+    UnresolvedType {
+        /// 类型名称，包限定的引用包含限定符（如 `models.User`）
+        name: String,
+        /// 引用所在的文件
+        file: PathBuf,
+        /// 引用所在的行号（从 1 开始）
+        line: usize,
+    },
+
+    /// 源码中无法识别的语法结构
+    #[error("Malformed syntax in {} at byte {byte_offset}", file.display())]
+    MalformedSyntax {
+        /// 出错的文件
+        file: PathBuf,
+        /// 出错位置在文件中的字节偏移
+        byte_offset: usize,
+    },
+
+    /// Go 模块信息无法解析（如 go.mod 无法读取）
+    #[error("Module resolution failed: {detail}")]
+    ModuleResolution {
+        /// 失败原因
+        detail: String,
+        /// 底层错误
+        #[source]
+        source: Option<std::io::Error>,
+    },
+
     #[error("File I/O error: {0}")]
     IoError(#[from] std::io::Error),
 
     #[error("Invalid commit hash: {0}")]
     InvalidCommitHash(String),
 
+    #[error("Revision not found: {0}")]
+    RevisionNotFound(String),
+
     #[error("Unsupported file type: {0}")]
     UnsupportedFileType(String),
 
@@ -24,9 +59,74 @@ pub enum SemanticDiffError {
     #[error("Tree-sitter parsing failed: {0}")]
     TreeSitterError(String),
 
+    #[error("Parsing timed out: {0}")]
+    ParseTimeout(String),
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
+
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
 }
 
 /// 项目通用的 Result 类型别名
 pub type Result<T> = std::result::Result<T, SemanticDiffError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_structured_variants_display_and_source() {
+        let unresolved = SemanticDiffError::UnresolvedType {
+            name: "models.User".to_string(),
+            file: PathBuf::from("app/handler.go"),
+            line: 42,
+        };
+        assert_eq!(
+            unresolved.to_string(),
+            "Unresolved type models.User referenced at app/handler.go:42"
+        );
+        assert!(unresolved.source().is_none());
+
+        let malformed = SemanticDiffError::MalformedSyntax {
+            file: PathBuf::from("main.go"),
+            byte_offset: 128,
+        };
+        assert_eq!(
+            malformed.to_string(),
+            "Malformed syntax in main.go at byte 128"
+        );
+        assert!(malformed.source().is_none());
+
+        let module = SemanticDiffError::ModuleResolution {
+            detail: "failed to read go.mod".to_string(),
+            source: Some(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "permission denied",
+            )),
+        };
+        assert_eq!(
+            module.to_string(),
+            "Module resolution failed: failed to read go.mod"
+        );
+        assert_eq!(module.source().unwrap().to_string(), "permission denied");
+
+        let without_source = SemanticDiffError::ModuleResolution {
+            detail: "missing module directive".to_string(),
+            source: None,
+        };
+        assert!(without_source.source().is_none());
+
+        // 兼容的通用变体保持原有行为
+        let parse = SemanticDiffError::ParseError("unexpected token".to_string());
+        assert_eq!(
+            parse.to_string(),
+            "Go source parsing error: unexpected token"
+        );
+        assert!(parse.source().is_none());
+        let io = SemanticDiffError::from(std::io::Error::other("disk full"));
+        assert_eq!(io.source().unwrap().to_string(), "disk full");
+    }
+}
@@ -0,0 +1,189 @@
+//! 路径排除模块
+//!
+//! 使用 glob 模式按仓库相对路径排除文件，例如生成代码目录 `gen/` 或 `*.pb.go`。
+//! 模式语法与 `.gitignore` 一致：不含 `/` 的模式匹配任意层级的文件名，
+//! 含 `/` 的模式从仓库根目录开始匹配；`*` 和 `?` 不跨越目录，`**` 可以匹配任意层级
+
+use crate::error::{Result, SemanticDiffError};
+use gix::bstr::ByteSlice;
+use gix::glob::Pattern;
+use gix::glob::pattern::Case;
+use gix::glob::wildmatch::Mode;
+use std::path::Path;
+
+/// 一组排除模式
+#[derive(Debug, Clone, Default)]
+pub struct ExcludePatterns {
+    patterns: Vec<(String, Pattern)>,
+}
+
+impl ExcludePatterns {
+    /// 解析一组 glob 模式，空白模式会被忽略
+    ///
+    /// 含有未闭合的字符类（`[a-z`）或以转义符结尾的模式永远不会匹配任何路径，作为错误返回
+    pub fn new<I, S>(patterns: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .map(|pattern| pattern.as_ref().trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| {
+                validate_glob(&pattern)?;
+                let parsed = Pattern::from_bytes_without_negation(pattern.as_bytes())
+                    .ok_or_else(|| invalid_pattern(&pattern, "pattern is empty"))?;
+                Ok((pattern, parsed))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { patterns })
+    }
+
+    /// 是否没有任何模式
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// 原始的模式列表
+    pub fn patterns(&self) -> impl Iterator<Item = &str> {
+        self.patterns.iter().map(|(pattern, _)| pattern.as_str())
+    }
+
+    /// 返回第一个匹配仓库相对路径的模式
+    ///
+    /// 与 `.gitignore` 一致，匹配到的目录会连同其下所有文件一起排除，
+    /// 因此依次检查路径的每一级父目录和路径本身
+    pub fn matching_pattern(&self, relative_path: &Path) -> Option<&str> {
+        let components: Vec<String> = relative_path
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect();
+
+        let mut path = String::new();
+        for (index, component) in components.iter().enumerate() {
+            let basename_start = (!path.is_empty()).then(|| path.len() + 1);
+            if basename_start.is_some() {
+                path.push('/');
+            }
+            path.push_str(component);
+
+            let is_dir = index + 1 < components.len();
+            let matched = self.patterns.iter().find(|(_, pattern)| {
+                pattern.matches_repo_relative_path(
+                    path.as_bytes().as_bstr(),
+                    basename_start,
+                    Some(is_dir),
+                    Case::Sensitive,
+                    Mode::NO_MATCH_SLASH_LITERAL,
+                )
+            });
+            if let Some((pattern, _)) = matched {
+                return Some(pattern.as_str());
+            }
+        }
+
+        None
+    }
+
+    /// 仓库相对路径是否被任一模式排除
+    pub fn is_excluded(&self, relative_path: &Path) -> bool {
+        self.matching_pattern(relative_path).is_some()
+    }
+}
+
+/// 检查 gitignore 语法中会导致模式永远无法匹配的写法
+fn validate_glob(pattern: &str) -> Result<()> {
+    let bytes = pattern.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' if i + 1 == bytes.len() => {
+                return Err(invalid_pattern(pattern, "trailing escape character"));
+            }
+            b'\\' => i += 2,
+            b'[' => {
+                // `[!...]`/`[^...]` 取反，紧跟在开头的 `]` 是字面量
+                let mut end = i + 1;
+                if matches!(bytes.get(end), Some(b'!' | b'^')) {
+                    end += 1;
+                }
+                if bytes.get(end) == Some(&b']') {
+                    end += 1;
+                }
+                let close = bytes[end.min(bytes.len())..]
+                    .iter()
+                    .position(|&byte| byte == b']')
+                    .ok_or_else(|| invalid_pattern(pattern, "unclosed character class"))?;
+                i = end + close + 1;
+            }
+            _ => i += 1,
+        }
+    }
+    Ok(())
+}
+
+fn invalid_pattern(pattern: &str, reason: &str) -> SemanticDiffError {
+    SemanticDiffError::ConfigError(format!("Invalid exclude pattern '{pattern}': {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclude_generated_and_vendor_files() {
+        let excludes = ExcludePatterns::new(["*.pb.go", "vendor/**"]).unwrap();
+
+        assert!(excludes.is_excluded(Path::new("api/v1/service.pb.go")));
+        assert!(excludes.is_excluded(Path::new("service.pb.go")));
+        assert!(excludes.is_excluded(Path::new("vendor/github.com/pkg/errors/errors.go")));
+        assert_eq!(
+            excludes.matching_pattern(Path::new("vendor/modules.txt")),
+            Some("vendor/**")
+        );
+
+        // 普通文件不受影响；含 `/` 的模式从仓库根目录开始匹配
+        assert!(!excludes.is_excluded(Path::new("cmd/server/main.go")));
+        assert!(!excludes.is_excluded(Path::new("internal/vendor/patch.go")));
+        assert!(!excludes.is_excluded(Path::new("api/v1/service.go")));
+    }
+
+    #[test]
+    fn test_exclude_pattern_syntax() {
+        // 目录模式排除目录下的所有文件
+        let excludes = ExcludePatterns::new(["gen/"]).unwrap();
+        assert!(excludes.is_excluded(Path::new("gen/models.go")));
+        assert!(excludes.is_excluded(Path::new("internal/gen/models.go")));
+        assert!(!excludes.is_excluded(Path::new("generator.go")));
+
+        // `*` 不跨越目录，`**/` 匹配任意层级
+        let excludes = ExcludePatterns::new(["internal/*.go", "**/testdata/*.go"]).unwrap();
+        assert!(excludes.is_excluded(Path::new("internal/a.go")));
+        assert!(!excludes.is_excluded(Path::new("internal/sub/a.go")));
+        assert!(excludes.is_excluded(Path::new("testdata/a.go")));
+        assert!(excludes.is_excluded(Path::new("pkg/x/testdata/a.go")));
+
+        // 字符类和单字符通配
+        let excludes = ExcludePatterns::new(["zz_generated_[a-c]?.go", "mock_[!x].go"]).unwrap();
+        assert!(excludes.is_excluded(Path::new("zz_generated_b1.go")));
+        assert!(!excludes.is_excluded(Path::new("zz_generated_d1.go")));
+        assert!(excludes.is_excluded(Path::new("mock_a.go")));
+        assert!(!excludes.is_excluded(Path::new("mock_x.go")));
+
+        // 空白模式被忽略
+        assert!(ExcludePatterns::new(["", "  "]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_exclude_patterns() {
+        let err = ExcludePatterns::new(["*.go", "gen/[a-z"]).unwrap_err();
+        assert!(err.to_string().contains("gen/[a-z"));
+        assert!(ExcludePatterns::new(["mock\\"]).is_err());
+
+        // 开头的 `]` 属于字符类本身
+        let excludes = ExcludePatterns::new(["[]]x.go"]).unwrap();
+        assert!(excludes.is_excluded(Path::new("]x.go")));
+    }
+}
@@ -2,20 +2,47 @@
 //!
 //! 提供语义上下文提取和代码切片生成功能
 
-use crate::analyzer::{Dependency, DependencyResolver, DependencyType};
-use crate::error::Result;
+use crate::analyzer::{Dependency, DependencyResolver, DependencyType, SymbolIndex};
+use crate::constraints::BuildConstraints;
+use crate::diagnostics::Diagnostics;
+use crate::error::{Result, SemanticDiffError};
+use crate::exclude::ExcludePatterns;
+use crate::git::repo_relative_path;
 use crate::parser::common::LanguageSpecificInfo;
 use crate::parser::{
     GoConstantDefinition, GoFunctionInfo, GoTypeDefinition, GoVariableDefinition, Import,
-    SourceFile,
+    ReferenceKind, SourceFile,
+};
+use crate::performance::{
+    ErrorRecoveryStrategy, MemoryEfficientAstProcessor, ProgressCallback, ProgressEvent,
+    ScopedThreadPool,
 };
-use crate::performance::MemoryEfficientAstProcessor;
 use rayon::prelude::*;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Arc, LazyLock};
 use std::time::Instant;
 use tracing::{debug, info};
 
+/// Go 标识符
+static IDENTIFIER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap());
+
+/// 复合字面量 `pkg.Type{` 或调用 `pkg.Func(`，名称可以带包限定符
+static COMPOSITE_OR_CALL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"((?:[A-Za-z_][A-Za-z0-9_]*\.)*[A-Za-z_][A-Za-z0-9_]*)\s*([{(])").unwrap()
+});
+
+/// 类型约束中的导出类型名，可以带包限定符
+static CONSTRAINT_TYPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b((?:[a-z_][A-Za-z0-9_]*\.)?[A-Z][a-zA-Z0-9_]*)").unwrap());
+
 /// 语义上下文提取器
 ///
 /// 负责从源文件中提取函数的完整语义上下文，包括相关的类型定义、
@@ -24,10 +51,147 @@ pub struct SemanticContextExtractor {
     dependency_resolver: DependencyResolver,
     /// 最大递归深度，防止无限递归
     max_recursion_depth: usize,
+    /// 单个上下文涉及的最大文件数（None 表示不限制）
+    max_involved_files: Option<usize>,
+    /// 单个上下文收集的类型、依赖函数和常量总数上限（None 表示不限制）
+    max_dependencies: Option<usize>,
+    /// 类型依赖的遍历顺序
+    extraction_order: ExtractionOrder,
+    /// 批量处理前对变更目标排序的优先级比较器
+    batch_priority: Option<BatchPriority>,
+    /// 常量/变量变更时是否包含同一 `const (...)`/`var (...)` 分组中的其他成员
+    include_group_siblings: bool,
+    /// 是否检查变更的导出函数缺少对应测试
+    check_test_coverage: bool,
+    /// 使用正则扫描函数体的最大字节数，超过时改用基于 CST 的检测
+    max_regex_scan_bytes: usize,
+    /// 并发提取使用的线程池（None 表示使用全局 rayon 线程池）
+    thread_pool: Option<ScopedThreadPool>,
+    /// 进度回调
+    progress: Option<ProgressCallback>,
+    /// 分批提取的内存预算（字节）
+    memory_budget: Option<usize>,
+    /// 排除的文件模式及匹配时使用的仓库根目录，来自被排除文件的定义不会加入上下文
+    exclude_patterns: Option<(ExcludePatterns, PathBuf)>,
+}
+
+/// 变更目标优先级比较器，返回 `Ordering::Less` 的目标先被处理
+pub type BatchPriority = Box<dyn Fn(&ChangeTarget, &ChangeTarget) -> Ordering + Send + Sync>;
+
+/// 类型依赖的遍历顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExtractionOrder {
+    /// 深度优先：沿一条依赖链递归到底后再处理下一个直接依赖
+    #[default]
+    DepthFirst,
+    /// 广度优先：先收集同一层的全部直接依赖，再进入下一层
+    BreadthFirst,
+}
+
+/// 单个上下文提取过程中的涉及文件预算
+///
+/// 变更目标所在文件总是被接纳；文件数达到上限后，来自新文件的定义既不收集也不继续展开，
+/// 被拒绝的文件按首次遇到的顺序记录下来。匹配排除模式的文件不计入预算，也不记为被省略
+struct FileBudget<'a> {
+    max_files: Option<usize>,
+    allowed: HashSet<PathBuf>,
+    omitted: Vec<PathBuf>,
+    excluded: Option<(&'a ExcludePatterns, &'a Path)>,
+}
+
+impl<'a> FileBudget<'a> {
+    fn new(max_files: Option<usize>, target_file: &Path) -> Self {
+        Self {
+            max_files,
+            allowed: HashSet::from([target_file.to_path_buf()]),
+            omitted: Vec::new(),
+            excluded: None,
+        }
+    }
+
+    /// 不限制文件数的预算
+    fn unlimited() -> Self {
+        Self {
+            max_files: None,
+            allowed: HashSet::new(),
+            omitted: Vec::new(),
+            excluded: None,
+        }
+    }
+
+    /// 设置排除模式及匹配时使用的仓库根目录
+    fn excluding(mut self, excluded: Option<(&'a ExcludePatterns, &'a Path)>) -> Self {
+        self.excluded = excluded;
+        self
+    }
+
+    /// 文件是否被排除模式排除，已接纳的文件（如变更目标所在文件）不受影响
+    fn is_excluded(&self, file_path: &Path) -> bool {
+        self.excluded.is_some_and(|(patterns, repo_root)| {
+            !self.allowed.contains(file_path)
+                && patterns.is_excluded(&repo_relative_path(file_path, repo_root))
+        })
+    }
+
+    /// 判断来自 `file_path` 的定义能否加入上下文，必要时把文件计入预算
+    fn admit(&mut self, file_path: &Path) -> bool {
+        if self.is_excluded(file_path) {
+            return false;
+        }
+        let Some(max_files) = self.max_files else {
+            return true;
+        };
+        if self.allowed.contains(file_path) {
+            return true;
+        }
+        if self.allowed.len() < max_files {
+            self.allowed.insert(file_path.to_path_buf());
+            return true;
+        }
+        self.omit(file_path, max_files);
+        false
+    }
+
+    /// 判断来自 `file_path` 的定义能否连同它必需的文件一起加入上下文
+    ///
+    /// 只有全部新文件都能计入预算时才接纳，否则只把 `file_path` 记为被省略
+    fn admit_with(&mut self, file_path: &Path, required: &[&Path]) -> bool {
+        if self.is_excluded(file_path) {
+            return false;
+        }
+        let Some(max_files) = self.max_files else {
+            return true;
+        };
+        // 被排除的必需文件本来就不会加入上下文，不占用预算
+        let new_files: HashSet<&Path> = std::iter::once(file_path)
+            .chain(required.iter().copied())
+            .filter(|path| !self.allowed.contains(*path) && !self.is_excluded(path))
+            .collect();
+        if self.allowed.len() + new_files.len() <= max_files {
+            self.allowed
+                .extend(new_files.into_iter().map(Path::to_path_buf));
+            return true;
+        }
+        self.omit(file_path, max_files);
+        false
+    }
+
+    /// 记录被拒绝的文件，同一文件只记录一次
+    fn omit(&mut self, file_path: &Path, max_files: usize) {
+        if !self.omitted.iter().any(|omitted| omitted == file_path) {
+            debug!(
+                "达到涉及文件数上限 {}，不再展开文件 {:?}",
+                max_files, file_path
+            );
+            self.omitted.push(file_path.to_path_buf());
+        }
+    }
 }
 
 /// 变更类型枚举
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ChangeType {
     /// 函数变更
     Function,
@@ -43,6 +207,8 @@ pub enum ChangeType {
 
 /// 变更目标
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "target", rename_all = "snake_case"))]
 pub enum ChangeTarget {
     /// 函数变更
     Function(GoFunctionInfo),
@@ -52,6 +218,27 @@ pub enum ChangeTarget {
     Variable(GoVariableDefinition),
     /// 常量变更
     Constant(GoConstantDefinition),
+    /// 函数原地重命名（函数体不变，仅名称变化）
+    Renamed {
+        /// 重命名前的函数名
+        old_name: String,
+        /// 重命名后的函数名
+        new_name: String,
+        /// 重命名后的函数信息
+        function: GoFunctionInfo,
+    },
+    /// 被删除的声明（只存在于变更前的版本中）
+    Removed {
+        /// 变更前版本中的声明
+        declaration: Box<ChangeTarget>,
+    },
+    /// 整个包（包内所有导出声明）
+    Package {
+        /// 包名
+        name: String,
+        /// 包内的源文件
+        files: Vec<PathBuf>,
+    },
 }
 
 impl ChangeTarget {
@@ -62,6 +249,9 @@ impl ChangeTarget {
             ChangeTarget::Type(_) => ChangeType::Type,
             ChangeTarget::Variable(_) => ChangeType::Variable,
             ChangeTarget::Constant(_) => ChangeType::Constant,
+            ChangeTarget::Renamed { .. } => ChangeType::Function,
+            ChangeTarget::Removed { declaration } => declaration.change_type(),
+            ChangeTarget::Package { .. } => ChangeType::Package,
         }
     }
 
@@ -72,16 +262,75 @@ impl ChangeTarget {
             ChangeTarget::Type(t) => &t.name,
             ChangeTarget::Variable(v) => &v.name,
             ChangeTarget::Constant(c) => &c.name,
+            ChangeTarget::Renamed { new_name, .. } => new_name,
+            ChangeTarget::Removed { declaration } => declaration.name(),
+            ChangeTarget::Package { name, .. } => name,
         }
     }
 
     /// 获取文件路径
+    ///
+    /// 包目标返回其第一个源文件，没有源文件时返回空路径
     pub fn file_path(&self) -> &PathBuf {
+        static NO_FILE: PathBuf = PathBuf::new();
+
         match self {
             ChangeTarget::Function(f) => &f.file_path,
             ChangeTarget::Type(t) => &t.file_path,
             ChangeTarget::Variable(v) => &v.file_path,
             ChangeTarget::Constant(c) => &c.file_path,
+            ChangeTarget::Renamed { function, .. } => &function.file_path,
+            ChangeTarget::Removed { declaration } => declaration.file_path(),
+            ChangeTarget::Package { files, .. } => files.first().unwrap_or(&NO_FILE),
+        }
+    }
+
+    /// 获取声明的起止行号（从 0 开始的 CST 行号）
+    ///
+    /// 包目标跨越多个文件，没有单一的行范围，返回 `(0, 0)`
+    pub fn line_range(&self) -> (u32, u32) {
+        match self {
+            ChangeTarget::Function(f) => (f.start_line, f.end_line),
+            ChangeTarget::Type(t) => (t.start_line, t.end_line),
+            ChangeTarget::Variable(v) => (v.start_line, v.end_line),
+            ChangeTarget::Constant(c) => (c.start_line, c.end_line),
+            ChangeTarget::Renamed { function, .. } => (function.start_line, function.end_line),
+            ChangeTarget::Removed { declaration } => declaration.line_range(),
+            ChangeTarget::Package { .. } => (0, 0),
+        }
+    }
+
+    /// 获取变更目标的种类名称
+    ///
+    /// 取值为 `function`、`method`、`type`、`variable`、`constant`、`renamed`、`removed` 或 `package`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ChangeTarget::Function(f) if f.receiver.is_some() => "method",
+            ChangeTarget::Function(_) => "function",
+            ChangeTarget::Type(_) => "type",
+            ChangeTarget::Variable(_) => "variable",
+            ChangeTarget::Constant(_) => "constant",
+            ChangeTarget::Renamed { .. } => "renamed",
+            ChangeTarget::Removed { .. } => "removed",
+            ChangeTarget::Package { .. } => "package",
+        }
+    }
+
+    /// 检查变更目标是否为被删除的声明
+    pub fn is_removed(&self) -> bool {
+        matches!(self, ChangeTarget::Removed { .. })
+    }
+
+    /// 检查变更目标是否为导出符号（首字母大写）
+    pub fn is_exported(&self) -> bool {
+        self.name().chars().next().is_some_and(|c| c.is_uppercase())
+    }
+
+    /// 获取变更目标对应的函数信息（函数变更或函数重命名）
+    pub fn as_function(&self) -> Option<&GoFunctionInfo> {
+        match self {
+            ChangeTarget::Function(f) | ChangeTarget::Renamed { function: f, .. } => Some(f),
+            _ => None,
         }
     }
 }
@@ -90,6 +339,7 @@ impl ChangeTarget {
 ///
 /// 包含变更目标的完整语义上下文，使得代码片段可以独立理解和编译
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SemanticContext {
     /// 主要的变更目标
     pub change_target: ChangeTarget,
@@ -105,6 +355,33 @@ pub struct SemanticContext {
     pub imports: Vec<Import>,
     /// 跨模块依赖信息
     pub cross_module_dependencies: HashMap<String, Vec<String>>,
+    /// 因超过涉及文件数上限而未展开的文件
+    pub omitted_files: Vec<PathBuf>,
+    /// 变更的导出函数在源文件池中没有对应的 `Test<Name>` 测试函数
+    pub untested: bool,
+    /// 变更函数相对变更前的行数增长比例，仅在增长超过阈值时设置
+    pub growth_ratio: Option<f64>,
+    /// 变更中新增的 panic/unsafe/reflect 用法
+    pub risk_signals: Vec<crate::diff::RiskSignal>,
+    /// 上下文涉及的结构体字段序列化标签变化
+    pub tag_changes: Vec<crate::analyzer::TagChange>,
+    /// 已移入共享前言、不再在本上下文中重复输出的依赖名称
+    pub shared_dependencies: Vec<String>,
+    /// 因超过依赖总数上限而省略的类型、依赖函数和常量数量
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub omitted_dependencies: usize,
+}
+
+/// 语义上下文的序列化视图
+///
+/// 在上下文字段之外可选地附带依赖图；反序列化时依赖图字段会被忽略
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+pub(crate) struct SemanticContextExport<'a> {
+    #[serde(flatten)]
+    context: &'a SemanticContext,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dependency_graph: Option<DependencyGraph>,
 }
 
 impl SemanticContext {
@@ -118,9 +395,91 @@ impl SemanticContext {
             variables: Vec::new(),
             imports: Vec::new(),
             cross_module_dependencies: HashMap::new(),
+            omitted_files: Vec::new(),
+            untested: false,
+            growth_ratio: None,
+            risk_signals: Vec::new(),
+            tag_changes: Vec::new(),
+            shared_dependencies: Vec::new(),
+            omitted_dependencies: 0,
+        }
+    }
+
+    /// 变更目标是否为被删除的声明，渲染时应使用删除样式
+    pub fn is_removed(&self) -> bool {
+        self.change_target.is_removed()
+    }
+
+    /// 变更是否会破坏调用方（重命名或删除了导出符号，或改变了字段的序列化名称）
+    pub fn is_breaking(&self) -> bool {
+        let renamed_exported = match &self.change_target {
+            ChangeTarget::Renamed { old_name, .. } => {
+                old_name.chars().next().is_some_and(|c| c.is_uppercase())
+            }
+            ChangeTarget::Removed { declaration } => declaration.is_exported(),
+            _ => false,
+        };
+
+        renamed_exported || self.tag_changes.iter().any(|c| c.is_wire_breaking())
+    }
+
+    /// 变更的函数是否涉及并发（goroutine、channel、select 或 sync/atomic 包）
+    pub fn touches_concurrency(&self) -> bool {
+        let Some(function) = self.change_target.as_function() else {
+            return false;
+        };
+
+        function.body_references.touches_concurrency()
+    }
+
+    /// 将完整的语义上下文导出为 JSON
+    ///
+    /// `include_dependency_graph` 为 true 时额外附带 `dependency_graph` 字段，便于外部工具直接使用
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self, include_dependency_graph: bool) -> Result<String> {
+        serde_json::to_string_pretty(&self.export(include_dependency_graph))
+            .map_err(|e| SemanticDiffError::SerializationError(e.to_string()))
+    }
+
+    /// 构造用于序列化导出的视图，按需附带依赖图
+    #[cfg(feature = "serde")]
+    pub(crate) fn export(&self, include_dependency_graph: bool) -> SemanticContextExport<'_> {
+        SemanticContextExport {
+            context: self,
+            dependency_graph: include_dependency_graph.then(|| self.generate_dependency_graph()),
         }
     }
 
+    /// 从 `to_json` 导出的 JSON 重建语义上下文
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| SemanticDiffError::SerializationError(e.to_string()))
+    }
+
+    /// 将一次运行得到的多个语义上下文序列化为 MessagePack
+    ///
+    /// 与 JSON 导出共用同一套 serde 模型，但体积更小，适合进程间批量传输；
+    /// 依赖图的处理与 [`Self::to_json`] 一致
+    #[cfg(feature = "msgpack")]
+    pub fn contexts_to_msgpack(
+        contexts: &[SemanticContext],
+        include_dependency_graph: bool,
+    ) -> Result<Vec<u8>> {
+        let exports: Vec<_> = contexts
+            .iter()
+            .map(|context| context.export(include_dependency_graph))
+            .collect();
+        rmp_serde::to_vec_named(&exports)
+            .map_err(|e| SemanticDiffError::SerializationError(e.to_string()))
+    }
+
+    /// 从 `contexts_to_msgpack` 的输出重建语义上下文列表
+    #[cfg(feature = "msgpack")]
+    pub fn contexts_from_msgpack(bytes: &[u8]) -> Result<Vec<SemanticContext>> {
+        rmp_serde::from_slice(bytes)
+            .map_err(|e| SemanticDiffError::SerializationError(e.to_string()))
+    }
+
     /// 从函数创建语义上下文
     pub fn from_function(function: GoFunctionInfo) -> Self {
         Self::new(ChangeTarget::Function(function))
@@ -185,6 +544,48 @@ impl SemanticContext {
         }
     }
 
+    /// 把另一个上下文的变更目标和相关定义并入当前上下文，重复的定义只保留一份
+    pub fn merge(&mut self, other: SemanticContext) {
+        match other.change_target {
+            ChangeTarget::Function(f) | ChangeTarget::Renamed { function: f, .. } => {
+                self.add_function(f)
+            }
+            ChangeTarget::Type(t) => self.add_type(t),
+            ChangeTarget::Variable(v) => self.add_variable(v),
+            ChangeTarget::Constant(c) => self.add_constant(c),
+            ChangeTarget::Removed { .. } | ChangeTarget::Package { .. } => {}
+        }
+
+        for type_def in other.related_types {
+            self.add_type(type_def);
+        }
+        for function in other.dependent_functions {
+            self.add_function(function);
+        }
+        for constant in other.constants {
+            self.add_constant(constant);
+        }
+        for variable in other.variables {
+            self.add_variable(variable);
+        }
+        for import in other.imports {
+            self.add_import(import);
+        }
+        for (module, dependencies) in other.cross_module_dependencies {
+            let merged = self.cross_module_dependencies.entry(module).or_default();
+            for dependency in dependencies {
+                if !merged.contains(&dependency) {
+                    merged.push(dependency);
+                }
+            }
+        }
+        for file in other.omitted_files {
+            if !self.omitted_files.contains(&file) {
+                self.omitted_files.push(file);
+            }
+        }
+    }
+
     /// 获取所有相关的文件路径
     pub fn get_involved_files(&self) -> HashSet<PathBuf> {
         let mut files = HashSet::new();
@@ -210,6 +611,11 @@ impl SemanticContext {
         files
     }
 
+    /// 上下文是否因涉及文件数或依赖总数上限而被截断
+    pub fn is_truncated(&self) -> bool {
+        !self.omitted_files.is_empty() || self.omitted_dependencies > 0
+    }
+
     /// 检查上下文是否为空（除了变更目标）
     pub fn is_empty(&self) -> bool {
         self.related_types.is_empty()
@@ -220,12 +626,53 @@ impl SemanticContext {
             && self.cross_module_dependencies.is_empty()
     }
 
+    /// 估算上下文中源码文本占用的内存（字节）
+    pub fn estimated_size(&self) -> usize {
+        fn target_size(target: &ChangeTarget) -> usize {
+            match target {
+                ChangeTarget::Function(f) | ChangeTarget::Renamed { function: f, .. } => {
+                    f.name.len() + f.body.len()
+                }
+                ChangeTarget::Type(t) => t.name.len() + t.definition.len(),
+                ChangeTarget::Variable(v) => {
+                    v.name.len() + v.initial_value.as_ref().map_or(0, String::len)
+                }
+                ChangeTarget::Constant(c) => c.name.len() + c.value.len(),
+                ChangeTarget::Removed { declaration } => target_size(declaration),
+                ChangeTarget::Package { name, .. } => name.len(),
+            }
+        }
+
+        target_size(&self.change_target)
+            + self
+                .related_types
+                .iter()
+                .map(|t| t.name.len() + t.definition.len())
+                .sum::<usize>()
+            + self
+                .dependent_functions
+                .iter()
+                .map(|f| f.name.len() + f.body.len())
+                .sum::<usize>()
+            + self
+                .constants
+                .iter()
+                .map(|c| c.name.len() + c.value.len())
+                .sum::<usize>()
+            + self
+                .variables
+                .iter()
+                .map(|v| v.name.len() + v.initial_value.as_ref().map_or(0, String::len))
+                .sum::<usize>()
+            + self.imports.iter().map(|i| i.path.len()).sum::<usize>()
+    }
+
     /// 获取上下文的统计信息
     pub fn get_stats(&self) -> ContextStats {
         let mut functions_count = self.dependent_functions.len();
 
         // 如果变更目标是函数，也要计算在内
-        if matches!(self.change_target, ChangeTarget::Function(_)) {
+        if self.change_target.as_function().is_some() {
             functions_count += 1;
         }
 
@@ -259,7 +706,7 @@ impl SemanticContext {
         let mut functions_by_file = HashMap::new();
 
         // 包含变更目标（如果是函数）
-        if let ChangeTarget::Function(ref func) = self.change_target {
+        if let Some(func) = self.change_target.as_function() {
             functions_by_file
                 .entry(func.file_path.clone())
                 .or_insert_with(Vec::new)
@@ -454,6 +901,7 @@ impl SemanticContext {
 
 /// 语义上下文统计信息
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ContextStats {
     pub types_count: usize,
     pub functions_count: usize,
@@ -466,6 +914,7 @@ pub struct ContextStats {
 
 /// 依赖图节点类型
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DependencyNodeType {
     Function,
     Type,
@@ -477,6 +926,7 @@ pub enum DependencyNodeType {
 
 /// 依赖图节点
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DependencyNode {
     pub id: String,
     pub name: String,
@@ -487,6 +937,7 @@ pub struct DependencyNode {
 
 /// 依赖图边
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DependencyEdge {
     pub from: String,
     pub to: String,
@@ -495,6 +946,7 @@ pub struct DependencyEdge {
 
 /// 依赖边类型
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DependencyEdgeType {
     /// 函数调用
     FunctionCall,
@@ -512,6 +964,7 @@ pub enum DependencyEdgeType {
 
 /// 依赖图
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DependencyGraph {
     pub nodes: Vec<DependencyNode>,
     pub edges: Vec<DependencyEdge>,
@@ -576,8 +1029,57 @@ impl DependencyGraph {
         grouped
     }
 
+    /// 查找图中的循环依赖
+    ///
+    /// 使用 Tarjan 强连通分量算法，每个包含多个节点或存在自环的强连通分量作为一个循环返回，
+    /// 循环内的节点 ID 按添加到图中的顺序排列，因此同一个循环只会报告一次
+    pub fn find_cycles(&self) -> Vec<Vec<String>> {
+        let mut tarjan = Tarjan::default();
+        for node in &self.nodes {
+            if !tarjan.indices.contains_key(node.id.as_str()) {
+                tarjan.strong_connect(self, &node.id);
+            }
+        }
+
+        let position = |id: &str| self.nodes.iter().position(|node| node.id == id);
+        let mut cycles: Vec<Vec<String>> = tarjan
+            .components
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1
+                    || self
+                        .edges
+                        .iter()
+                        .any(|edge| edge.from == component[0] && edge.to == component[0])
+            })
+            .map(|mut component| {
+                component.sort_by_key(|id| position(id));
+                component.into_iter().map(str::to_string).collect()
+            })
+            .collect();
+        cycles.sort_by_key(|cycle| position(&cycle[0]));
+        cycles
+    }
+
+    /// 参与循环依赖的边，即两端位于同一个循环中的边
+    fn cycle_edges(&self) -> HashSet<(&str, &str)> {
+        let cycles = self.find_cycles();
+        self.edges
+            .iter()
+            .filter(|edge| {
+                cycles
+                    .iter()
+                    .any(|cycle| cycle.contains(&edge.from) && cycle.contains(&edge.to))
+            })
+            .map(|edge| (edge.from.as_str(), edge.to.as_str()))
+            .collect()
+    }
+
     /// 生成 DOT 格式的图表示（用于 Graphviz）
+    ///
+    /// 参与循环依赖的边以粗虚线绘制并标注 `cycle`
     pub fn to_dot(&self) -> String {
+        let cycle_edges = self.cycle_edges();
         let mut dot = String::new();
         dot.push_str("digraph DependencyGraph {\n");
         dot.push_str("    rankdir=TB;\n");
@@ -619,9 +1121,15 @@ impl DependencyGraph {
                 DependencyEdgeType::ModuleDependency => "purple",
             };
 
+            let cycle_attrs = if cycle_edges.contains(&(edge.from.as_str(), edge.to.as_str())) {
+                ", style=\"bold,dashed\", label=\"cycle\""
+            } else {
+                ""
+            };
+
             dot.push_str(&format!(
-                "    \"{}\" -> \"{}\" [color={}];\n",
-                edge.from, edge.to, color
+                "    \"{}\" -> \"{}\" [color={}{}];\n",
+                edge.from, edge.to, color, cycle_attrs
             ));
         }
 
@@ -629,7 +1137,88 @@ impl DependencyGraph {
         dot
     }
 
+    /// 生成 Mermaid 格式的图表示（可直接嵌入 Markdown 渲染）
+    ///
+    /// 节点 ID 可能包含 `:` 等 Mermaid 不接受的字符，因此按节点顺序使用 `n0`、`n1` 等生成的 ID，
+    /// 原始名称只出现在加引号的标签中。节点形状按节点类型区分，边标签按边类型区分，
+    /// 参与循环依赖的边以虚线绘制，变更目标节点使用 `changeTarget` 样式类
+    pub fn to_mermaid(&self) -> String {
+        let cycle_edges = self.cycle_edges();
+        let mermaid_ids: HashMap<&str, String> = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.id.as_str(), format!("n{index}")))
+            .collect();
+
+        let mut mermaid = String::new();
+        mermaid.push_str("graph TD\n");
+
+        for node in &self.nodes {
+            let label = node.name.replace('"', "#quot;");
+            let (open, close) = match node.node_type {
+                DependencyNodeType::Function => ("(", ")"),
+                DependencyNodeType::Type => ("[", "]"),
+                DependencyNodeType::Constant => ("{{", "}}"),
+                DependencyNodeType::Variable => ("[/", "/]"),
+                DependencyNodeType::Import => (">", "]"),
+                DependencyNodeType::Module => ("[(", ")]"),
+            };
+            mermaid.push_str(&format!(
+                "    {}{}\"{}\"{}\n",
+                mermaid_ids[node.id.as_str()],
+                open,
+                label,
+                close
+            ));
+        }
+
+        // 端点不在节点列表中的边无法引用生成的 ID，直接跳过
+        for edge in &self.edges {
+            let (Some(from), Some(to)) = (
+                mermaid_ids.get(edge.from.as_str()),
+                mermaid_ids.get(edge.to.as_str()),
+            ) else {
+                continue;
+            };
+            let label = match edge.edge_type {
+                DependencyEdgeType::FunctionCall => "calls",
+                DependencyEdgeType::TypeUsage => "uses",
+                DependencyEdgeType::ConstantReference => "const",
+                DependencyEdgeType::VariableReference => "var",
+                DependencyEdgeType::ImportDependency => "imports",
+                DependencyEdgeType::ModuleDependency => "module",
+            };
+            let arrow = if cycle_edges.contains(&(edge.from.as_str(), edge.to.as_str())) {
+                "-.->"
+            } else {
+                "-->"
+            };
+            mermaid.push_str(&format!("    {from} {arrow}|{label}| {to}\n"));
+        }
+
+        let change_targets: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter(|node| node.is_change_target)
+            .map(|node| mermaid_ids[node.id.as_str()].as_str())
+            .collect();
+        if !change_targets.is_empty() {
+            mermaid.push_str(
+                "    classDef changeTarget fill:#ffe082,stroke:#e65100,stroke-width:2px;\n",
+            );
+            mermaid.push_str(&format!(
+                "    class {} changeTarget;\n",
+                change_targets.join(",")
+            ));
+        }
+
+        mermaid
+    }
+
     /// 生成文本格式的依赖树
+    ///
+    /// 沿循环依赖的边回到已输出的节点时，输出一行 `↻` 标记说明循环，而不是静默跳过
     pub fn to_text_tree(&self) -> String {
         let mut result = String::new();
         let root_node = self.nodes.iter().find(|n| n.id == self.root_node);
@@ -639,7 +1228,13 @@ impl DependencyGraph {
             result.push_str("=".repeat(50).as_str());
             result.push('\n');
 
-            self.build_text_tree(&mut result, &self.root_node, 0, &mut HashSet::new());
+            self.build_text_tree(
+                &mut result,
+                &self.root_node,
+                0,
+                &mut HashSet::new(),
+                &self.cycle_edges(),
+            );
         }
 
         result
@@ -652,6 +1247,7 @@ impl DependencyGraph {
         node_id: &str,
         depth: usize,
         visited: &mut HashSet<String>,
+        cycle_edges: &HashSet<(&str, &str)>,
     ) {
         if visited.contains(node_id) {
             return;
@@ -683,8 +1279,58 @@ impl DependencyGraph {
             // 递归处理依赖
             let dependencies = self.get_direct_dependencies(node_id);
             for dep in dependencies {
-                self.build_text_tree(result, &dep.id, depth + 1, visited);
+                if visited.contains(&dep.id) && cycle_edges.contains(&(node_id, dep.id.as_str())) {
+                    result.push_str(&format!("{}  ↻ {} (cycle)\n", indent, dep.name));
+                    continue;
+                }
+                self.build_text_tree(result, &dep.id, depth + 1, visited, cycle_edges);
+            }
+        }
+    }
+}
+
+/// Tarjan 强连通分量算法的遍历状态
+#[derive(Default)]
+struct Tarjan<'a> {
+    next_index: usize,
+    indices: HashMap<&'a str, usize>,
+    low_links: HashMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: HashSet<&'a str>,
+    components: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Tarjan<'a> {
+    /// 从指定节点开始深度优先遍历，遍历结束时弹出以该节点为根的强连通分量
+    fn strong_connect(&mut self, graph: &'a DependencyGraph, node_id: &'a str) {
+        self.indices.insert(node_id, self.next_index);
+        self.low_links.insert(node_id, self.next_index);
+        self.next_index += 1;
+        self.stack.push(node_id);
+        self.on_stack.insert(node_id);
+
+        for edge in graph.edges.iter().filter(|edge| edge.from == node_id) {
+            let successor = edge.to.as_str();
+            if !self.indices.contains_key(successor) {
+                self.strong_connect(graph, successor);
+                let low_link = self.low_links[node_id].min(self.low_links[successor]);
+                self.low_links.insert(node_id, low_link);
+            } else if self.on_stack.contains(successor) {
+                let low_link = self.low_links[node_id].min(self.indices[successor]);
+                self.low_links.insert(node_id, low_link);
+            }
+        }
+
+        if self.low_links[node_id] == self.indices[node_id] {
+            let mut component = Vec::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(member);
+                component.push(member);
+                if member == node_id {
+                    break;
+                }
             }
+            self.components.push(component);
         }
     }
 }
@@ -701,6 +1347,17 @@ impl SemanticContextExtractor {
         Self {
             dependency_resolver: DependencyResolver::new(),
             max_recursion_depth: 10, // 默认最大递归深度
+            max_involved_files: None,
+            max_dependencies: None,
+            extraction_order: ExtractionOrder::default(),
+            batch_priority: None,
+            include_group_siblings: false,
+            check_test_coverage: false,
+            max_regex_scan_bytes: 64 * 1024, // 默认 64KB
+            thread_pool: None,
+            progress: None,
+            memory_budget: None,
+            exclude_patterns: None,
         }
     }
 
@@ -711,6 +1368,26 @@ impl SemanticContextExtractor {
         &self,
         change_targets: &[ChangeTarget],
         source_files: &[SourceFile],
+    ) -> Result<Vec<SemanticContext>> {
+        let index = self.dependency_resolver.build_index(source_files);
+        let completed = AtomicUsize::new(0);
+        self.extract_contexts_with_progress(
+            change_targets,
+            &index,
+            &completed,
+            change_targets.len(),
+        )
+    }
+
+    /// 并发提取语义上下文，每完成一个目标报告一次进度
+    ///
+    /// `completed` 在多次调用之间共享时，进度按 `total` 个目标连续计数
+    fn extract_contexts_with_progress(
+        &self,
+        change_targets: &[ChangeTarget],
+        index: &SymbolIndex,
+        completed: &AtomicUsize,
+        total: usize,
     ) -> Result<Vec<SemanticContext>> {
         info!(
             "开始并发提取 {} 个变更目标的语义上下文",
@@ -718,15 +1395,19 @@ impl SemanticContextExtractor {
         );
         let start_time = Instant::now();
 
-        let contexts: Result<Vec<_>> = change_targets
-            .par_iter()
-            .map(|target| {
-                debug!("提取变更目标的上下文: {}", target.name());
-                self.extract_context_for_target(target.clone(), source_files)
-            })
-            .collect();
-
-        let contexts = contexts?;
+        let extract = |target: &ChangeTarget| {
+            self.extract_reporting_progress(target, index, completed, total)
+        };
+        let contexts = match &self.thread_pool {
+            Some(pool) => pool
+                .map(change_targets, extract)?
+                .into_iter()
+                .collect::<Result<Vec<_>>>()?,
+            None => change_targets
+                .par_iter()
+                .map(extract)
+                .collect::<Result<Vec<_>>>()?,
+        };
         let duration = start_time.elapsed();
 
         info!(
@@ -738,6 +1419,99 @@ impl SemanticContextExtractor {
         Ok(contexts)
     }
 
+    /// 并发提取语义上下文，失败的目标记录为诊断信息
+    ///
+    /// 每个目标的错误交给 `recovery_strategy` 处理：可恢复的错误（如无法解析的依赖）
+    /// 记录到 `diagnostics` 后跳过该目标，不可恢复的错误中止提取并返回。
+    /// 成功的上下文按 `change_targets` 的顺序返回
+    pub fn extract_contexts_with_diagnostics(
+        &self,
+        change_targets: &[ChangeTarget],
+        source_files: &[SourceFile],
+        recovery_strategy: &ErrorRecoveryStrategy,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Vec<SemanticContext>> {
+        let index = self.dependency_resolver.build_index(source_files);
+        let completed = AtomicUsize::new(0);
+        let total = change_targets.len();
+        let extract = |target: &ChangeTarget| {
+            self.extract_reporting_progress(target, &index, &completed, total)
+        };
+        let results: Vec<Result<SemanticContext>> = match &self.thread_pool {
+            Some(pool) => pool.map(change_targets, extract)?,
+            None => change_targets.par_iter().map(extract).collect(),
+        };
+
+        let mut contexts = Vec::new();
+        for (target, result) in change_targets.iter().zip(results) {
+            match result {
+                Ok(context) => contexts.push(context),
+                Err(error) => recovery_strategy.recover(
+                    error,
+                    Some(target.file_path().as_path()),
+                    Some(target.name()),
+                    diagnostics,
+                )?,
+            }
+        }
+
+        Ok(contexts)
+    }
+
+    /// 流式提取语义上下文
+    ///
+    /// 每个变更目标的上下文一经提取就交给 `sink`，不在内存中收集全部结果，调用方可以边提取边输出。
+    /// 并发执行时 `sink` 在工作线程中被调用，调用顺序不保证与 `change_targets` 一致；
+    /// 通过 [`Self::with_threads`] 设置为 1 个线程时按输入顺序调用。
+    /// 某个目标提取失败时尽快停止并返回错误，在此之前提取的上下文已经交给了 `sink`
+    pub fn extract_contexts_streaming<F>(
+        &self,
+        change_targets: &[ChangeTarget],
+        source_files: &[SourceFile],
+        sink: F,
+    ) -> Result<()>
+    where
+        F: Fn(SemanticContext) + Send + Sync,
+    {
+        info!(
+            "开始流式提取 {} 个变更目标的语义上下文",
+            change_targets.len()
+        );
+
+        let index = self.dependency_resolver.build_index(source_files);
+        let completed = AtomicUsize::new(0);
+        let total = change_targets.len();
+        let emit = |target: &ChangeTarget| -> Result<()> {
+            sink(self.extract_reporting_progress(target, &index, &completed, total)?);
+            Ok(())
+        };
+
+        match &self.thread_pool {
+            Some(pool) if pool.is_sequential() => change_targets.iter().try_for_each(emit),
+            Some(pool) => pool.install(|| change_targets.par_iter().try_for_each(emit))?,
+            None => change_targets.par_iter().try_for_each(emit),
+        }
+    }
+
+    /// 提取单个变更目标的上下文，完成后报告一次进度
+    fn extract_reporting_progress(
+        &self,
+        target: &ChangeTarget,
+        index: &SymbolIndex,
+        completed: &AtomicUsize,
+        total: usize,
+    ) -> Result<SemanticContext> {
+        debug!("提取变更目标的上下文: {}", target.name());
+        let context = self.extract_target_context(target.clone(), index);
+        if let Some(progress) = &self.progress {
+            progress(ProgressEvent::ContextExtracted {
+                completed: completed.fetch_add(1, AtomicOrdering::Relaxed) + 1,
+                total,
+            });
+        }
+        context
+    }
+
     /// 批量提取语义上下文
     ///
     /// 将变更目标分批处理，避免内存使用过多
@@ -746,6 +1520,30 @@ impl SemanticContextExtractor {
         change_targets: &[ChangeTarget],
         source_files: &[SourceFile],
         batch_size: usize,
+    ) -> Result<Vec<SemanticContext>> {
+        let mut ast_processor = MemoryEfficientAstProcessor::new();
+        if let Some(budget) = self.memory_budget {
+            ast_processor = ast_processor.with_memory_budget(budget);
+        }
+        self.extract_contexts_in_batches_with(
+            change_targets,
+            source_files,
+            batch_size,
+            &ast_processor,
+        )
+    }
+
+    /// 使用指定的 AST 处理器批量提取语义上下文
+    ///
+    /// 处理器设置了内存预算时，先单独提取一个目标估算每个上下文的内存占用，
+    /// 之后每一批的大小按 [`MemoryEfficientAstProcessor::next_batch_size`] 缩小，
+    /// 使正在处理的一批上下文不超过预算；已经返回的上下文不计入估算
+    pub fn extract_contexts_in_batches_with(
+        &self,
+        change_targets: &[ChangeTarget],
+        source_files: &[SourceFile],
+        batch_size: usize,
+        ast_processor: &MemoryEfficientAstProcessor,
     ) -> Result<Vec<SemanticContext>> {
         info!(
             "开始批量提取 {} 个变更目标的语义上下文，批大小: {}",
@@ -753,26 +1551,62 @@ impl SemanticContextExtractor {
             batch_size
         );
 
+        let index = self.dependency_resolver.build_index(source_files);
         let mut all_contexts = Vec::new();
-        let ast_processor = MemoryEfficientAstProcessor::new();
+        let completed = AtomicUsize::new(0);
 
-        for (batch_index, batch) in change_targets.chunks(batch_size).enumerate() {
-            debug!(
-                "处理第 {} 批，包含 {} 个变更目标",
-                batch_index + 1,
-                batch.len()
-            );
+        // 按优先级排序（稳定排序，同优先级保持输入顺序）
+        let mut ordered_targets = change_targets.to_vec();
+        if let Some(priority) = &self.batch_priority {
+            ordered_targets.sort_by(|a, b| priority(a, b));
+        }
+
+        let batch_size = batch_size.max(1);
+        let mut remaining = ordered_targets.as_slice();
+        let mut bytes_per_context = None;
+        let mut batch_index = 0;
+        while !remaining.is_empty() {
+            let size = match (ast_processor.memory_budget(), bytes_per_context) {
+                (None, _) => batch_size,
+                (Some(_), None) => 1,
+                (Some(_), Some(bytes)) => ast_processor.next_batch_size(batch_size, bytes),
+            };
+            let (batch, rest) = remaining.split_at(size.min(remaining.len()));
+            remaining = rest;
+            batch_index += 1;
+
+            debug!("处理第 {} 批，包含 {} 个变更目标", batch_index, batch.len());
 
             // 检查内存使用情况
             if ast_processor.should_trigger_gc() {
                 debug!("内存使用过高，触发清理");
                 ast_processor.trigger_gc();
             }
+            if ast_processor.is_over_budget() {
+                ast_processor.drop_cached_trees();
+            }
+
+            let batch_contexts = self.extract_contexts_with_progress(
+                batch,
+                &index,
+                &completed,
+                change_targets.len(),
+            )?;
+
+            // 一批上下文在交给调用方之前占用的内存
+            let sizes: Vec<usize> = batch_contexts
+                .iter()
+                .map(SemanticContext::estimated_size)
+                .collect();
+            let batch_bytes = sizes.iter().sum();
+            ast_processor.track_memory(batch_bytes);
+            ast_processor.release_memory(batch_bytes);
+            let largest = sizes.iter().copied().max().unwrap_or(0);
+            bytes_per_context = Some(bytes_per_context.unwrap_or(0).max(largest));
 
-            let batch_contexts = self.extract_contexts_concurrent(batch, source_files)?;
             all_contexts.extend(batch_contexts);
 
-            debug!("第 {} 批处理完成", batch_index + 1);
+            debug!("第 {} 批处理完成", batch_index);
         }
 
         info!("批量上下文提取完成，总共处理 {} 个目标", all_contexts.len());
@@ -787,22 +1621,17 @@ impl SemanticContextExtractor {
         type_names: &[String],
         source_files: &[SourceFile],
     ) -> Result<Vec<GoTypeDefinition>> {
+        let index = self.dependency_resolver.build_index(source_files);
         let mut processed = HashSet::new();
         let mut result_types = Vec::new();
         let ast_processor = MemoryEfficientAstProcessor::new();
 
         // 使用并发处理初始类型列表
-        let initial_types: Result<Vec<_>> = type_names
+        let initial_types: Vec<GoTypeDefinition> = type_names
             .par_iter()
-            .filter_map(|type_name| {
-                self.dependency_resolver
-                    .find_type_definition(type_name, source_files)
-                    .map(Ok)
-            })
+            .filter_map(|type_name| index.find_type(type_name).cloned())
             .collect();
 
-        let initial_types = initial_types?;
-
         // 递归处理依赖类型
         for type_def in initial_types {
             if ast_processor.should_trigger_gc() {
@@ -811,9 +1640,10 @@ impl SemanticContextExtractor {
 
             self.extract_type_recursively(
                 &type_def.name,
-                source_files,
+                &index,
                 &mut result_types,
                 &mut processed,
+                &mut FileBudget::unlimited().excluding(self.excluded_files()),
                 0,
             )?;
         }
@@ -826,6 +1656,17 @@ impl SemanticContextExtractor {
         Self {
             dependency_resolver: DependencyResolver::new_with_project_path(project_module_path),
             max_recursion_depth: 10,
+            max_involved_files: None,
+            max_dependencies: None,
+            extraction_order: ExtractionOrder::default(),
+            batch_priority: None,
+            include_group_siblings: false,
+            check_test_coverage: false,
+            max_regex_scan_bytes: 64 * 1024,
+            thread_pool: None,
+            progress: None,
+            memory_budget: None,
+            exclude_patterns: None,
         }
     }
 
@@ -835,6 +1676,17 @@ impl SemanticContextExtractor {
         Ok(Self {
             dependency_resolver,
             max_recursion_depth: 10,
+            max_involved_files: None,
+            max_dependencies: None,
+            extraction_order: ExtractionOrder::default(),
+            batch_priority: None,
+            include_group_siblings: false,
+            check_test_coverage: false,
+            max_regex_scan_bytes: 64 * 1024,
+            thread_pool: None,
+            progress: None,
+            memory_budget: None,
+            exclude_patterns: None,
         })
     }
 
@@ -849,7 +1701,155 @@ impl SemanticContextExtractor {
         self.max_recursion_depth
     }
 
-    /// 提取变更目标的完整语义上下文
+    /// 设置单个上下文涉及的最大文件数
+    ///
+    /// 达到上限后不再引入新文件中的定义，被跳过的文件记录在 `omitted_files` 中
+    pub fn with_max_involved_files(mut self, max_files: usize) -> Self {
+        self.max_involved_files = Some(max_files);
+        self
+    }
+
+    /// 设置批量处理的优先级比较器
+    ///
+    /// `extract_contexts_in_batches` 会在分批前按比较器对变更目标做稳定排序，
+    /// 让重要的目标（例如导出的符号）最先被处理，结果也按该顺序返回
+    pub fn with_batch_priority<F>(mut self, comparator: F) -> Self
+    where
+        F: Fn(&ChangeTarget, &ChangeTarget) -> Ordering + Send + Sync + 'static,
+    {
+        self.batch_priority = Some(Box::new(comparator));
+        self
+    }
+
+    /// 获取单个上下文涉及的最大文件数
+    pub fn get_max_involved_files(&self) -> Option<usize> {
+        self.max_involved_files
+    }
+
+    /// 设置单个上下文收集的类型、依赖函数和常量总数上限
+    ///
+    /// 与递归深度不同，该上限同样约束字段类型很多的“宽”结构体。类型按提取顺序
+    /// （见 [`ExtractionOrder`]）接纳，其余名额依次分给依赖函数和常量；
+    /// 变更目标本身不占名额，始终保留
+    pub fn with_max_dependencies(mut self, max_dependencies: usize) -> Self {
+        self.max_dependencies = Some(max_dependencies);
+        self
+    }
+
+    /// 获取依赖总数上限
+    pub fn get_max_dependencies(&self) -> Option<usize> {
+        self.max_dependencies
+    }
+
+    /// 设置类型依赖的遍历顺序
+    ///
+    /// 广度优先会先收集完变更目标的所有直接依赖，与依赖总数上限搭配时，
+    /// 名额优先留给离变更目标最近的类型
+    pub fn with_extraction_order(mut self, order: ExtractionOrder) -> Self {
+        self.extraction_order = order;
+        self
+    }
+
+    /// 获取类型依赖的遍历顺序
+    pub fn get_extraction_order(&self) -> ExtractionOrder {
+        self.extraction_order
+    }
+
+    /// 设置是否包含常量/变量所在分组的其他成员
+    ///
+    /// 开启后，`const (...)` 分组中某个常量变更时，整个分组（例如完整的 iota 枚举）
+    /// 都会作为上下文输出
+    pub fn with_group_siblings(mut self, include: bool) -> Self {
+        self.include_group_siblings = include;
+        self
+    }
+
+    /// 是否包含常量/变量所在分组的其他成员
+    pub fn includes_group_siblings(&self) -> bool {
+        self.include_group_siblings
+    }
+
+    /// 设置是否检查变更的导出函数缺少对应测试
+    ///
+    /// 开启后会在源文件池中查找 `Test<Name>` 测试函数，找不到时将上下文标记为
+    /// `untested`。源文件池需要包含相应的 `_test.go` 文件
+    pub fn with_test_coverage_check(mut self, enabled: bool) -> Self {
+        self.check_test_coverage = enabled;
+        self
+    }
+
+    /// 设置构建约束
+    ///
+    /// 同名声明存在于多个平台相关文件中时，依赖解析优先选择满足约束的实现
+    pub fn with_build_constraints(mut self, constraints: BuildConstraints) -> Self {
+        self.dependency_resolver =
+            std::mem::take(&mut self.dependency_resolver).with_build_constraints(constraints);
+        self
+    }
+
+    /// 设置排除的文件模式
+    ///
+    /// 定义所在文件相对 `repo_root` 的路径匹配任一模式时，该定义不会加入上下文，
+    /// 也不会继续展开它的依赖；变更目标本身不受影响
+    pub fn with_exclude_patterns(
+        mut self,
+        patterns: ExcludePatterns,
+        repo_root: impl Into<PathBuf>,
+    ) -> Self {
+        self.exclude_patterns = (!patterns.is_empty()).then(|| (patterns, repo_root.into()));
+        self
+    }
+
+    /// 设置使用正则扫描函数体的最大字节数
+    ///
+    /// 超过该大小的函数体在判断是否使用类型、变量或常量时改用 CST 检测，
+    /// 避免对超大函数体反复编译和执行多个正则表达式
+    pub fn with_max_regex_scan_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_regex_scan_bytes = max_bytes;
+        self
+    }
+
+    /// 获取使用正则扫描函数体的最大字节数
+    pub fn get_max_regex_scan_bytes(&self) -> usize {
+        self.max_regex_scan_bytes
+    }
+
+    /// 设置并发提取使用的线程数
+    ///
+    /// 设置后 [`Self::extract_contexts_concurrent`] 在独立的线程池中执行，不占用全局
+    /// rayon 线程池；设置为 1 时按输入顺序逐个提取
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.thread_pool = Some(ScopedThreadPool::new(threads));
+        self
+    }
+
+    /// 获取并发提取使用的线程数（None 表示使用全局 rayon 线程池）
+    pub fn get_threads(&self) -> Option<usize> {
+        self.thread_pool.as_ref().map(ScopedThreadPool::threads)
+    }
+
+    /// 设置分批提取的内存预算（字节）
+    ///
+    /// [`Self::extract_contexts_in_batches`] 按估算的上下文大小缩小批大小，见
+    /// [`Self::extract_contexts_in_batches_with`]
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// 设置进度回调
+    ///
+    /// 并发或分批提取时每完成一个变更目标报告一次 [`ProgressEvent::ContextExtracted`]，
+    /// 回调在工作线程中调用
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(ProgressEvent) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// 提取变更目标的完整语义上下文
     ///
     /// 这是核心功能，根据变更目标类型提取所有相关上下文信息
     pub fn extract_context_for_target(
@@ -857,24 +1857,66 @@ impl SemanticContextExtractor {
         change_target: ChangeTarget,
         source_files: &[SourceFile],
     ) -> Result<SemanticContext> {
-        match change_target {
+        let index = self.dependency_resolver.build_index(source_files);
+        self.extract_target_context(change_target, &index)
+    }
+
+    /// 使用已建立的符号索引提取变更目标的语义上下文
+    fn extract_target_context(
+        &self,
+        change_target: ChangeTarget,
+        index: &SymbolIndex,
+    ) -> Result<SemanticContext> {
+        let source_files = index.source_files();
+        let mut context = match change_target {
             ChangeTarget::Function(function) => {
                 let target = ChangeTarget::Function(function.clone());
-                self.extract_function_context(&function, source_files, target)
+                self.extract_function_context(&function, index, target)
             }
             ChangeTarget::Type(type_def) => {
                 let target = ChangeTarget::Type(type_def.clone());
-                self.extract_type_context(&type_def, source_files, target)
+                self.extract_type_context(&type_def, index, target)
             }
             ChangeTarget::Variable(variable) => {
                 let target = ChangeTarget::Variable(variable.clone());
-                self.extract_variable_context(&variable, source_files, target)
+                self.extract_variable_context(&variable, index, target)
             }
             ChangeTarget::Constant(constant) => {
                 let target = ChangeTarget::Constant(constant.clone());
-                self.extract_constant_context(&constant, source_files, target)
+                self.extract_constant_context(&constant, index, target)
+            }
+            ChangeTarget::Renamed {
+                old_name,
+                new_name,
+                function,
+            } => {
+                let target = ChangeTarget::Renamed {
+                    old_name,
+                    new_name,
+                    function: function.clone(),
+                };
+                self.extract_function_context(&function, index, target)
             }
+            ChangeTarget::Removed { declaration } => {
+                // 按原始声明提取上下文，再把变更目标标记为删除
+                let mut context = self.extract_target_context(*declaration, index)?;
+                context.change_target = ChangeTarget::Removed {
+                    declaration: Box::new(context.change_target),
+                };
+                context.untested = false;
+                return Ok(context);
+            }
+            ChangeTarget::Package { name, files } => {
+                return self.extract_indexed_package_context(&name, &files, index);
+            }
+        }?;
+
+        self.apply_dependency_cap(&mut context);
+        if context.is_truncated() {
+            self.prune_unreferenced(&mut context);
         }
+        self.mark_untested(&mut context, source_files);
+        Ok(context)
     }
 
     /// 提取函数的完整语义上下文（保持向后兼容）
@@ -885,17 +1927,368 @@ impl SemanticContextExtractor {
         function: &GoFunctionInfo,
         source_files: &[SourceFile],
     ) -> Result<SemanticContext> {
+        let index = self.dependency_resolver.build_index(source_files);
         let change_target = ChangeTarget::Function(function.clone());
-        self.extract_function_context(function, source_files, change_target)
+        let mut context = self.extract_function_context(function, &index, change_target)?;
+        self.apply_dependency_cap(&mut context);
+        if context.is_truncated() {
+            self.prune_unreferenced(&mut context);
+        }
+        self.mark_untested(&mut context, source_files);
+        Ok(context)
+    }
+
+    /// 提取整个包的语义上下文
+    ///
+    /// 对包内每个导出声明分别提取上下文，再合并为一个以 [`ChangeTarget::Package`] 为目标的上下文：
+    /// 包内的导出声明本身和它们的依赖都并入相关定义，多个声明共享的类型只保留一份。
+    /// `files` 为空时按包名从 `source_files` 中查找包内文件，否则只使用列出的文件
+    pub fn extract_package_context(
+        &self,
+        name: &str,
+        files: &[PathBuf],
+        source_files: &[SourceFile],
+    ) -> Result<SemanticContext> {
+        let index = self.dependency_resolver.build_index(source_files);
+        self.extract_indexed_package_context(name, files, &index)
+    }
+
+    /// 使用已建立的符号索引提取整个包的语义上下文
+    fn extract_indexed_package_context(
+        &self,
+        name: &str,
+        files: &[PathBuf],
+        index: &SymbolIndex,
+    ) -> Result<SemanticContext> {
+        use crate::parser::GoDeclaration;
+
+        let source_files = index.source_files();
+        let package_files: Vec<&SourceFile> = source_files
+            .iter()
+            .filter(|file| {
+                if files.is_empty() {
+                    file.language_specific.package_name() == name
+                } else {
+                    files.contains(&file.path)
+                }
+            })
+            .collect();
+        if package_files.is_empty() {
+            return Err(SemanticDiffError::DependencyError(format!(
+                "No source files found for package {name}"
+            )));
+        }
+
+        let mut context = SemanticContext::new(ChangeTarget::Package {
+            name: name.to_string(),
+            files: package_files.iter().map(|file| file.path.clone()).collect(),
+        });
+
+        for source_file in package_files {
+            for declaration in source_file.language_specific.declarations() {
+                let target = match declaration.as_any().downcast_ref::<GoDeclaration>() {
+                    Some(GoDeclaration::Function(f)) | Some(GoDeclaration::Method(f)) => {
+                        ChangeTarget::Function(f.clone())
+                    }
+                    Some(GoDeclaration::Type(t)) => ChangeTarget::Type(t.clone()),
+                    Some(GoDeclaration::Variable(v)) => ChangeTarget::Variable(v.clone()),
+                    Some(GoDeclaration::Constant(c)) => ChangeTarget::Constant(c.clone()),
+                    None => continue,
+                };
+                if !target.is_exported() {
+                    continue;
+                }
+
+                let member = self.extract_target_context(target, index)?;
+                context.merge(member);
+            }
+        }
+        self.apply_dependency_cap(&mut context);
+
+        debug!(
+            "Extracted package context for {}: {:?}",
+            name,
+            context.get_stats()
+        );
+        Ok(context)
+    }
+
+    /// 为变更目标创建涉及文件预算
+    fn file_budget(&self, change_target: &ChangeTarget) -> FileBudget<'_> {
+        FileBudget::new(self.max_involved_files, change_target.file_path())
+            .excluding(self.excluded_files())
+    }
+
+    /// 排除模式及匹配时使用的仓库根目录
+    fn excluded_files(&self) -> Option<(&ExcludePatterns, &Path)> {
+        self.exclude_patterns
+            .as_ref()
+            .map(|(patterns, repo_root)| (patterns, repo_root.as_path()))
+    }
+
+    /// 按依赖总数上限裁剪上下文
+    ///
+    /// 从变更目标直接引用的类型开始广度优先遍历类型依赖，保证同一输入总是保留相同的类型；
+    /// 无法从变更目标到达的类型按收集顺序排在最后。剩余名额依次分给依赖函数和常量，
+    /// 被丢弃的数量记录在 `omitted_dependencies` 中
+    fn apply_dependency_cap(&self, context: &mut SemanticContext) {
+        let Some(max_dependencies) = self.max_dependencies else {
+            return;
+        };
+
+        // 按提取顺序从变更目标出发遍历已收集的类型，无法到达的类型排在最后
+        let mut pending: VecDeque<String> =
+            self.target_type_references(&context.change_target).into();
+        let mut visited = vec![false; context.related_types.len()];
+        let mut order = Vec::new();
+        while let Some(type_ref) = pending.pop_front() {
+            let name = type_ref.rsplit('.').next().unwrap_or(&type_ref);
+            let Some(index) = context.related_types.iter().position(|t| t.name == name) else {
+                continue;
+            };
+            if visited[index] {
+                continue;
+            }
+            visited[index] = true;
+            order.push(index);
+            let dependencies = self.extract_type_dependencies(&context.related_types[index]);
+            match self.extraction_order {
+                ExtractionOrder::BreadthFirst => pending.extend(dependencies),
+                ExtractionOrder::DepthFirst => {
+                    for dependency in dependencies.into_iter().rev() {
+                        pending.push_front(dependency);
+                    }
+                }
+            }
+        }
+        order.extend((0..visited.len()).filter(|&index| !visited[index]));
+
+        let mut admitted_types = vec![false; context.related_types.len()];
+        for &index in order.iter().take(max_dependencies) {
+            admitted_types[index] = true;
+        }
+        let mut remaining = max_dependencies.saturating_sub(order.len());
+
+        let total = context.related_types.len()
+            + context.dependent_functions.len()
+            + context.constants.len();
+        let mut admitted_types = admitted_types.into_iter();
+        context
+            .related_types
+            .retain(|_| admitted_types.next().unwrap_or(false));
+        let function_count = remaining.min(context.dependent_functions.len());
+        context.dependent_functions.truncate(function_count);
+        remaining -= function_count;
+        context.constants.truncate(remaining);
+
+        let kept = context.related_types.len()
+            + context.dependent_functions.len()
+            + context.constants.len();
+        context.omitted_dependencies = total - kept;
+        if context.omitted_dependencies > 0 {
+            debug!(
+                "上下文 {} 达到依赖总数上限 {}，省略 {} 个依赖",
+                context.change_target.name(),
+                max_dependencies,
+                context.omitted_dependencies
+            );
+        }
+    }
+
+    /// 清理上下文中不再被引用的类型、常量、变量和导入
+    ///
+    /// 以变更目标和依赖函数为根，沿标识符引用扩展可达集合直到不动点；
+    /// 引用者被截断后遗留下来的定义会被移除，使最终上下文保持自洽。
+    /// 只在上下文被上限截断后调用，未截断的上下文原样保留
+    fn prune_unreferenced(&self, context: &mut SemanticContext) {
+        let mut referenced = HashSet::new();
+        let mut kept_names = HashSet::new();
+        kept_names.insert(context.change_target.name().to_string());
+
+        // 变更目标所在的常量/变量分组，用于保留分组内的兄弟声明
+        let mut target_group = None;
+        match &context.change_target {
+            ChangeTarget::Function(function) | ChangeTarget::Renamed { function, .. } => {
+                self.collect_function_references(function, &mut referenced);
+            }
+            ChangeTarget::Type(type_def) => {
+                self.collect_identifiers(&type_def.definition, &mut referenced);
+            }
+            ChangeTarget::Variable(variable) => {
+                self.collect_variable_references(variable, &mut referenced);
+                target_group = Some((&variable.file_path, variable.start_line, variable.end_line));
+            }
+            ChangeTarget::Constant(constant) => {
+                self.collect_constant_references(constant, &mut referenced);
+                target_group = Some((&constant.file_path, constant.start_line, constant.end_line));
+            }
+            // 被删除的声明在提取时已按原始声明裁剪，包上下文由各声明的上下文合并而成
+            ChangeTarget::Removed { .. } | ChangeTarget::Package { .. } => return,
+        }
+        for function in &context.dependent_functions {
+            self.collect_function_references(function, &mut referenced);
+        }
+
+        let in_target_group = |file_path: &PathBuf, start_line: u32, end_line: u32| {
+            target_group == Some((file_path, start_line, end_line))
+        };
+
+        let mut kept_types = vec![false; context.related_types.len()];
+        let mut kept_constants = vec![false; context.constants.len()];
+        let mut kept_variables = vec![false; context.variables.len()];
+
+        loop {
+            let mut changed = false;
+
+            for (type_def, kept) in context.related_types.iter().zip(kept_types.iter_mut()) {
+                if !*kept && referenced.contains(&type_def.name) {
+                    *kept = true;
+                    changed = true;
+                    kept_names.insert(type_def.name.clone());
+                    self.collect_identifiers(&type_def.definition, &mut referenced);
+                }
+            }
+
+            // 常量和变量除了被引用外，声明为已保留类型的实例或与目标同组时也应保留
+            for (constant, kept) in context.constants.iter().zip(kept_constants.iter_mut()) {
+                if *kept {
+                    continue;
+                }
+                let of_kept_type = constant
+                    .const_type
+                    .as_ref()
+                    .is_some_and(|t| kept_names.iter().any(|name| self.type_matches(t, name)));
+                if referenced.contains(&constant.name)
+                    || of_kept_type
+                    || in_target_group(&constant.file_path, constant.start_line, constant.end_line)
+                {
+                    *kept = true;
+                    changed = true;
+                    kept_names.insert(constant.name.clone());
+                    self.collect_constant_references(constant, &mut referenced);
+                }
+            }
+
+            for (variable, kept) in context.variables.iter().zip(kept_variables.iter_mut()) {
+                if *kept {
+                    continue;
+                }
+                let of_kept_type = variable
+                    .var_type
+                    .as_ref()
+                    .is_some_and(|t| kept_names.iter().any(|name| self.type_matches(t, name)));
+                if referenced.contains(&variable.name)
+                    || of_kept_type
+                    || in_target_group(&variable.file_path, variable.start_line, variable.end_line)
+                {
+                    *kept = true;
+                    changed = true;
+                    kept_names.insert(variable.name.clone());
+                    self.collect_variable_references(variable, &mut referenced);
+                }
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let before = context.related_types.len()
+            + context.constants.len()
+            + context.variables.len()
+            + context.imports.len();
+
+        let mut kept_types = kept_types.into_iter();
+        context
+            .related_types
+            .retain(|_| kept_types.next().unwrap_or(false));
+        let mut kept_constants = kept_constants.into_iter();
+        context
+            .constants
+            .retain(|_| kept_constants.next().unwrap_or(false));
+        let mut kept_variables = kept_variables.into_iter();
+        context
+            .variables
+            .retain(|_| kept_variables.next().unwrap_or(false));
+
+        // 导入按包名（别名或路径最后一段）判断是否仍被引用，空白导入和点导入始终保留
+        context
+            .imports
+            .retain(|import| import.is_unqualified() || referenced.contains(import.local_name()));
+
+        let after = context.related_types.len()
+            + context.constants.len()
+            + context.variables.len()
+            + context.imports.len();
+        if after < before {
+            debug!(
+                "上下文 {} 清理了 {} 个不再被引用的定义",
+                context.change_target.name(),
+                before - after
+            );
+        }
+    }
+
+    /// 收集函数签名和函数体中出现的标识符
+    fn collect_function_references(
+        &self,
+        function: &GoFunctionInfo,
+        referenced: &mut HashSet<String>,
+    ) {
+        if let Some(receiver) = &function.receiver {
+            referenced.insert(receiver.type_name.clone());
+        }
+        for param in &function.parameters {
+            self.collect_identifiers(&param.param_type.name, referenced);
+        }
+        for return_type in &function.return_types {
+            self.collect_identifiers(&return_type.name, referenced);
+        }
+        for type_param in &function.type_parameters {
+            self.collect_identifiers(&type_param.constraint, referenced);
+        }
+        self.collect_identifiers(&function.body, referenced);
+    }
+
+    /// 收集常量类型和值中出现的标识符
+    fn collect_constant_references(
+        &self,
+        constant: &GoConstantDefinition,
+        referenced: &mut HashSet<String>,
+    ) {
+        if let Some(const_type) = &constant.const_type {
+            self.collect_identifiers(&const_type.name, referenced);
+        }
+        self.collect_identifiers(&constant.value, referenced);
+    }
+
+    /// 收集变量类型和初始值中出现的标识符
+    fn collect_variable_references(
+        &self,
+        variable: &GoVariableDefinition,
+        referenced: &mut HashSet<String>,
+    ) {
+        if let Some(var_type) = &variable.var_type {
+            self.collect_identifiers(&var_type.name, referenced);
+        }
+        if let Some(initial_value) = &variable.initial_value {
+            self.collect_identifiers(initial_value, referenced);
+        }
+    }
+
+    /// 提取代码片段中的所有标识符
+    fn collect_identifiers(&self, code: &str, referenced: &mut HashSet<String>) {
+        referenced.extend(IDENTIFIER.find_iter(code).map(|m| m.as_str().to_string()));
     }
 
     /// 提取函数变更的语义上下文
     fn extract_function_context(
         &self,
         function: &GoFunctionInfo,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
         change_target: ChangeTarget,
     ) -> Result<SemanticContext> {
+        let source_files = index.source_files();
         // 用于跟踪已处理的项目，避免重复和循环依赖
         let mut processed_types = HashSet::new();
         let mut processed_functions = HashSet::new();
@@ -906,19 +2299,21 @@ impl SemanticContextExtractor {
         let mut dependent_functions = Vec::new();
         let mut constants = Vec::new();
         let mut required_imports = HashSet::new();
+        let mut files = self.file_budget(&change_target);
 
         // 1. 首先提取函数签名中的类型依赖
         self.extract_function_signature_dependencies(
             function,
-            source_files,
+            index,
             &mut related_types,
             &mut processed_types,
+            &mut files,
         )?;
 
         // 2. 提取函数体中的直接依赖
         let direct_dependencies = self
             .dependency_resolver
-            .extract_function_dependencies(function, source_files);
+            .extract_function_dependencies(function, index);
         let internal_dependencies = self
             .dependency_resolver
             .filter_internal_dependencies(&direct_dependencies);
@@ -928,36 +2323,38 @@ impl SemanticContextExtractor {
             if dependency.dependency_type == DependencyType::Type {
                 self.extract_type_recursively(
                     &dependency.name,
-                    source_files,
+                    index,
                     &mut related_types,
                     &mut processed_types,
+                    &mut files,
                     0,
                 )?;
             }
         }
 
         // 4. 提取依赖函数
-        for dependency in &internal_dependencies {
-            if dependency.dependency_type == DependencyType::Function {
-                if let Some(func_info) = self
-                    .dependency_resolver
-                    .find_function_definition(&dependency.name, source_files)
-                {
-                    if !processed_functions.contains(&func_info.name) {
-                        processed_functions.insert(func_info.name.clone());
+        let dependent_definitions = internal_dependencies
+            .iter()
+            .filter(|dependency| dependency.dependency_type == DependencyType::Function)
+            .filter_map(|dependency| index.find_function(&dependency.name));
+        for func_info in dependent_definitions {
+            if !processed_functions.insert(func_info.name.clone()) {
+                continue;
+            }
+            if !self.admit_function(func_info, index, &mut files) {
+                continue;
+            }
 
-                        // 递归提取依赖函数签名中的类型
-                        self.extract_function_signature_dependencies(
-                            &func_info,
-                            source_files,
-                            &mut related_types,
-                            &mut processed_types,
-                        )?;
+            // 递归提取依赖函数签名中的类型
+            self.extract_function_signature_dependencies(
+                func_info,
+                index,
+                &mut related_types,
+                &mut processed_types,
+                &mut files,
+            )?;
 
-                        dependent_functions.push(func_info);
-                    }
-                }
-            }
+            dependent_functions.push(func_info.clone());
         }
 
         // 5. 提取常量定义
@@ -969,13 +2366,41 @@ impl SemanticContextExtractor {
                 {
                     if !processed_constants.contains(&const_def.name) {
                         processed_constants.insert(const_def.name.clone());
-                        constants.push(const_def);
+                        if files.admit(&const_def.file_path) {
+                            constants.push(const_def);
+                        }
                     }
                 }
             }
         }
 
-        // 6. 收集必需的导入声明
+        // 6. 提取函数读写的全局变量，以及变量类型和初始值中引用的类型和函数
+        let mut variables = Vec::new();
+        let mut processed_variables = HashSet::new();
+        let referenced_variables = internal_dependencies
+            .iter()
+            .filter(|dependency| dependency.dependency_type == DependencyType::Variable)
+            .filter_map(|dependency| self.find_variable_definition(&dependency.name, source_files))
+            .chain(self.find_variables_used_by_function(function, source_files));
+        for var_def in referenced_variables {
+            if !processed_variables.contains(&var_def.name) {
+                processed_variables.insert(var_def.name.clone());
+                if !files.admit(&var_def.file_path) {
+                    continue;
+                }
+                dependent_functions.extend(self.extract_variable_dependencies(
+                    &var_def,
+                    index,
+                    &mut related_types,
+                    &mut processed_types,
+                    &mut processed_functions,
+                    &mut files,
+                )?);
+                variables.push(var_def);
+            }
+        }
+
+        // 7. 收集必需的导入声明
         self.collect_required_imports(
             function,
             &related_types,
@@ -983,28 +2408,19 @@ impl SemanticContextExtractor {
             source_files,
             &mut required_imports,
         )?;
-
-        // 7. 提取变量定义
-        let mut variables = Vec::new();
-        let mut processed_variables = HashSet::new();
-        for dependency in &internal_dependencies {
-            if dependency.dependency_type == DependencyType::Variable {
-                if let Some(var_def) = self.find_variable_definition(&dependency.name, source_files)
-                {
-                    if !processed_variables.contains(&var_def.name) {
-                        processed_variables.insert(var_def.name.clone());
-                        variables.push(var_def);
-                    }
-                }
-            }
+        for variable in &variables {
+            self.collect_required_imports_for_variable(
+                variable,
+                &[],
+                &[],
+                source_files,
+                &mut required_imports,
+            )?;
         }
 
         // 8. 分析跨模块依赖
-        let cross_module_dependencies = self.analyze_cross_module_dependencies(
-            source_files,
-            &related_types,
-            &dependent_functions,
-        )?;
+        let cross_module_dependencies =
+            self.analyze_cross_module_dependencies(index, &related_types, &dependent_functions)?;
 
         Ok(SemanticContext {
             change_target,
@@ -1012,8 +2428,15 @@ impl SemanticContextExtractor {
             dependent_functions,
             constants,
             variables,
-            imports: required_imports.into_iter().collect(),
+            imports: Self::sorted_imports(required_imports),
             cross_module_dependencies,
+            omitted_files: files.omitted,
+            untested: false,
+            growth_ratio: None,
+            risk_signals: Vec::new(),
+            shared_dependencies: Vec::new(),
+            omitted_dependencies: 0,
+            tag_changes: Vec::new(),
         })
     }
 
@@ -1025,9 +2448,10 @@ impl SemanticContextExtractor {
         function: &GoFunctionInfo,
         source_files: &[SourceFile],
     ) -> Result<Vec<Dependency>> {
+        let index = self.dependency_resolver.build_index(source_files);
         let all_dependencies = self
             .dependency_resolver
-            .extract_function_dependencies(function, source_files);
+            .extract_function_dependencies(function, &index);
         let internal_dependencies = self
             .dependency_resolver
             .filter_internal_dependencies(&all_dependencies);
@@ -1038,9 +2462,10 @@ impl SemanticContextExtractor {
     fn extract_type_context(
         &self,
         type_def: &GoTypeDefinition,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
         change_target: ChangeTarget,
     ) -> Result<SemanticContext> {
+        let source_files = index.source_files();
         let mut processed_types = HashSet::new();
         let mut processed_functions = HashSet::new();
         let mut processed_constants = HashSet::new();
@@ -1051,17 +2476,33 @@ impl SemanticContextExtractor {
         let mut constants = Vec::new();
         let mut variables = Vec::new();
         let mut required_imports = HashSet::new();
+        let mut files = self.file_budget(&change_target);
 
         // 1. 递归提取类型依赖
-        let type_dependencies = self.extract_type_dependencies(type_def);
-        for dep_type in type_dependencies {
-            self.extract_type_recursively(
-                &dep_type,
-                source_files,
+        match self.extraction_order {
+            ExtractionOrder::DepthFirst => {
+                for dep_type in self.extract_type_dependencies(type_def) {
+                    if let Some(dep_def) =
+                        self.resolve_type_dependency(&dep_type, &type_def.file_path, index)
+                    {
+                        self.extract_resolved_type_recursively(
+                            dep_def,
+                            index,
+                            &mut related_types,
+                            &mut processed_types,
+                            &mut files,
+                            0,
+                        )?;
+                    }
+                }
+            }
+            ExtractionOrder::BreadthFirst => self.extract_type_dependencies_breadth_first(
+                type_def,
+                index,
                 &mut related_types,
                 &mut processed_types,
-                0,
-            )?;
+                &mut files,
+            ),
         }
 
         // 2. 查找使用此类型的函数
@@ -1069,7 +2510,9 @@ impl SemanticContextExtractor {
         for func in functions_using_type {
             if !processed_functions.contains(&func.name) {
                 processed_functions.insert(func.name.clone());
-                dependent_functions.push(func);
+                if files.admit(&func.file_path) {
+                    dependent_functions.push(func);
+                }
             }
         }
 
@@ -1078,15 +2521,32 @@ impl SemanticContextExtractor {
         for const_def in related_constants {
             if !processed_constants.contains(&const_def.name) {
                 processed_constants.insert(const_def.name.clone());
-                constants.push(const_def);
+                if files.admit(&const_def.file_path) {
+                    constants.push(const_def);
+                }
             }
         }
 
-        let related_variables = self.find_variables_of_type(&type_def.name, source_files);
+        // 数组长度常量（如 `[MaxSize]User`）决定了类型的内存布局，也属于依赖
+        for const_name in self.extract_array_length_constants(type_def) {
+            if processed_constants.contains(&const_name) {
+                continue;
+            }
+            if let Some(const_def) = self.find_constant_definition(&const_name, source_files) {
+                processed_constants.insert(const_name);
+                if files.admit(&const_def.file_path) {
+                    constants.push(const_def);
+                }
+            }
+        }
+
+        let related_variables = self.find_variables_of_type(&type_def.name, source_files);
         for var_def in related_variables {
             if !processed_variables.contains(&var_def.name) {
                 processed_variables.insert(var_def.name.clone());
-                variables.push(var_def);
+                if files.admit(&var_def.file_path) {
+                    variables.push(var_def);
+                }
             }
         }
 
@@ -1100,11 +2560,8 @@ impl SemanticContextExtractor {
         )?;
 
         // 5. 分析跨模块依赖
-        let cross_module_dependencies = self.analyze_cross_module_dependencies(
-            source_files,
-            &related_types,
-            &dependent_functions,
-        )?;
+        let cross_module_dependencies =
+            self.analyze_cross_module_dependencies(index, &related_types, &dependent_functions)?;
 
         Ok(SemanticContext {
             change_target,
@@ -1112,8 +2569,15 @@ impl SemanticContextExtractor {
             dependent_functions,
             constants,
             variables,
-            imports: required_imports.into_iter().collect(),
+            imports: Self::sorted_imports(required_imports),
             cross_module_dependencies,
+            omitted_files: files.omitted,
+            untested: false,
+            growth_ratio: None,
+            risk_signals: Vec::new(),
+            shared_dependencies: Vec::new(),
+            omitted_dependencies: 0,
+            tag_changes: Vec::new(),
         })
     }
 
@@ -1121,43 +2585,43 @@ impl SemanticContextExtractor {
     fn extract_variable_context(
         &self,
         variable: &GoVariableDefinition,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
         change_target: ChangeTarget,
     ) -> Result<SemanticContext> {
+        let source_files = index.source_files();
         let mut processed_types = HashSet::new();
         let mut processed_functions = HashSet::new();
 
         let mut related_types = Vec::new();
         let mut dependent_functions = Vec::new();
         let constants = Vec::new();
-        let variables = Vec::new();
+        let mut variables = Vec::new();
         let mut required_imports = HashSet::new();
+        let mut files = self.file_budget(&change_target);
 
-        // 1. 提取变量类型的依赖
-        if let Some(var_type) = &variable.var_type {
-            if !self.is_builtin_type(&var_type.name) {
-                if let Some(type_def) = self
-                    .dependency_resolver
-                    .find_type_definition(&var_type.name, source_files)
-                {
-                    self.extract_type_recursively(
-                        &type_def.name,
-                        source_files,
-                        &mut related_types,
-                        &mut processed_types,
-                        0,
-                    )?;
-                }
-            }
+        if self.include_group_siblings {
+            variables.extend(self.find_variable_group_siblings(variable, source_files));
         }
 
+        // 1. 提取变量类型和初始值的依赖
+        dependent_functions.extend(self.extract_variable_dependencies(
+            variable,
+            index,
+            &mut related_types,
+            &mut processed_types,
+            &mut processed_functions,
+            &mut files,
+        )?);
+
         // 2. 查找使用此变量的函数
         let functions_using_variable =
             self.find_functions_using_variable(&variable.name, source_files);
         for func in functions_using_variable {
             if !processed_functions.contains(&func.name) {
                 processed_functions.insert(func.name.clone());
-                dependent_functions.push(func);
+                if files.admit(&func.file_path) {
+                    dependent_functions.push(func);
+                }
             }
         }
 
@@ -1171,11 +2635,8 @@ impl SemanticContextExtractor {
         )?;
 
         // 4. 分析跨模块依赖
-        let cross_module_dependencies = self.analyze_cross_module_dependencies(
-            source_files,
-            &related_types,
-            &dependent_functions,
-        )?;
+        let cross_module_dependencies =
+            self.analyze_cross_module_dependencies(index, &related_types, &dependent_functions)?;
 
         Ok(SemanticContext {
             change_target,
@@ -1183,8 +2644,15 @@ impl SemanticContextExtractor {
             dependent_functions,
             constants,
             variables,
-            imports: required_imports.into_iter().collect(),
+            imports: Self::sorted_imports(required_imports),
             cross_module_dependencies,
+            omitted_files: files.omitted,
+            untested: false,
+            growth_ratio: None,
+            risk_signals: Vec::new(),
+            shared_dependencies: Vec::new(),
+            omitted_dependencies: 0,
+            tag_changes: Vec::new(),
         })
     }
 
@@ -1192,30 +2660,34 @@ impl SemanticContextExtractor {
     fn extract_constant_context(
         &self,
         constant: &GoConstantDefinition,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
         change_target: ChangeTarget,
     ) -> Result<SemanticContext> {
+        let source_files = index.source_files();
         let mut processed_types = HashSet::new();
         let mut processed_functions = HashSet::new();
 
         let mut related_types = Vec::new();
         let mut dependent_functions = Vec::new();
-        let constants = Vec::new();
+        let mut constants = Vec::new();
         let variables = Vec::new();
         let mut required_imports = HashSet::new();
+        let mut files = self.file_budget(&change_target);
+
+        if self.include_group_siblings {
+            constants.extend(self.find_constant_group_siblings(constant, source_files));
+        }
 
         // 1. 提取常量类型的依赖
         if let Some(const_type) = &constant.const_type {
             if !self.is_builtin_type(&const_type.name) {
-                if let Some(type_def) = self
-                    .dependency_resolver
-                    .find_type_definition(&const_type.name, source_files)
-                {
+                if let Some(type_def) = index.find_type(&const_type.name).cloned() {
                     self.extract_type_recursively(
                         &type_def.name,
-                        source_files,
+                        index,
                         &mut related_types,
                         &mut processed_types,
+                        &mut files,
                         0,
                     )?;
                 }
@@ -1223,12 +2695,13 @@ impl SemanticContextExtractor {
         }
 
         // 2. 查找使用此常量的函数
-        let functions_using_constant =
-            self.find_functions_using_constant(&constant.name, source_files);
+        let functions_using_constant = self.find_functions_using_constant(constant, source_files);
         for func in functions_using_constant {
             if !processed_functions.contains(&func.name) {
                 processed_functions.insert(func.name.clone());
-                dependent_functions.push(func);
+                if files.admit(&func.file_path) {
+                    dependent_functions.push(func);
+                }
             }
         }
 
@@ -1242,11 +2715,8 @@ impl SemanticContextExtractor {
         )?;
 
         // 4. 分析跨模块依赖
-        let cross_module_dependencies = self.analyze_cross_module_dependencies(
-            source_files,
-            &related_types,
-            &dependent_functions,
-        )?;
+        let cross_module_dependencies =
+            self.analyze_cross_module_dependencies(index, &related_types, &dependent_functions)?;
 
         Ok(SemanticContext {
             change_target,
@@ -1254,8 +2724,15 @@ impl SemanticContextExtractor {
             dependent_functions,
             constants,
             variables,
-            imports: required_imports.into_iter().collect(),
+            imports: Self::sorted_imports(required_imports),
             cross_module_dependencies,
+            omitted_files: files.omitted,
+            untested: false,
+            growth_ratio: None,
+            risk_signals: Vec::new(),
+            shared_dependencies: Vec::new(),
+            omitted_dependencies: 0,
+            tag_changes: Vec::new(),
         })
     }
 
@@ -1265,9 +2742,10 @@ impl SemanticContextExtractor {
     fn extract_type_recursively(
         &self,
         type_name: &str,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
         result_types: &mut Vec<GoTypeDefinition>,
         processed: &mut HashSet<String>,
+        files: &mut FileBudget,
         depth: usize,
     ) -> Result<()> {
         // 防止无限递归
@@ -1281,31 +2759,212 @@ impl SemanticContextExtractor {
         }
 
         // 查找类型定义
-        if let Some(type_def) = self
-            .dependency_resolver
-            .find_type_definition(type_name, source_files)
-        {
-            processed.insert(type_name.to_string());
+        if let Some(type_def) = index.find_type(type_name).cloned() {
+            self.extract_resolved_type_recursively(
+                type_def,
+                index,
+                result_types,
+                processed,
+                files,
+                depth,
+            )?;
+        }
 
-            // 提取类型定义中的依赖类型
-            let type_dependencies = self.extract_type_dependencies(&type_def);
+        Ok(())
+    }
 
-            // 递归处理依赖类型
-            for dep_type in type_dependencies {
-                self.extract_type_recursively(
-                    &dep_type,
-                    source_files,
+    /// 递归提取已解析的类型定义及其依赖类型
+    fn extract_resolved_type_recursively(
+        &self,
+        type_def: GoTypeDefinition,
+        index: &SymbolIndex,
+        result_types: &mut Vec<GoTypeDefinition>,
+        processed: &mut HashSet<String>,
+        files: &mut FileBudget,
+        depth: usize,
+    ) -> Result<()> {
+        if depth >= self.max_recursion_depth || processed.contains(&type_def.name) {
+            return Ok(());
+        }
+        processed.insert(type_def.name.clone());
+
+        // 来自超出文件数上限的新文件的类型不再收集，也不再展开它的依赖
+        if !files.admit(&type_def.file_path) {
+            return Ok(());
+        }
+
+        // 提取类型定义中的依赖类型
+        let type_dependencies = self.extract_type_dependencies(&type_def);
+
+        // 递归处理依赖类型
+        for dep_type in type_dependencies {
+            if let Some(dep_def) =
+                self.resolve_type_dependency(&dep_type, &type_def.file_path, index)
+            {
+                self.extract_resolved_type_recursively(
+                    dep_def,
+                    index,
                     result_types,
                     processed,
+                    files,
                     depth + 1,
                 )?;
             }
+        }
+
+        // 添加当前类型到结果中
+        result_types.push(type_def);
+
+        Ok(())
+    }
+
+    /// 按层提取类型定义的依赖类型
+    ///
+    /// 同一层的依赖全部收集完后才进入下一层，每个类型的深度即其与 `root` 的最短距离
+    fn extract_type_dependencies_breadth_first(
+        &self,
+        root: &GoTypeDefinition,
+        index: &SymbolIndex,
+        result_types: &mut Vec<GoTypeDefinition>,
+        processed: &mut HashSet<String>,
+        files: &mut FileBudget,
+    ) {
+        let mut queue: VecDeque<(String, PathBuf, usize)> = self
+            .extract_type_dependencies(root)
+            .into_iter()
+            .map(|dep_type| (dep_type, root.file_path.clone(), 0))
+            .collect();
+
+        while let Some((dep_type, owner_file, depth)) = queue.pop_front() {
+            let type_name = dep_type.rsplit('.').next().unwrap_or(&dep_type);
+            if depth >= self.max_recursion_depth || processed.contains(type_name) {
+                continue;
+            }
+
+            let Some(type_def) = self.resolve_type_dependency(&dep_type, &owner_file, index) else {
+                continue;
+            };
 
-            // 添加当前类型到结果中
+            processed.insert(type_def.name.clone());
+            if !files.admit(&type_def.file_path) {
+                continue;
+            }
+            queue.extend(
+                self.extract_type_dependencies(&type_def)
+                    .into_iter()
+                    .map(|next| (next, type_def.file_path.clone(), depth + 1)),
+            );
             result_types.push(type_def);
         }
+    }
 
-        Ok(())
+    /// 查找类型定义引用的单个依赖类型
+    ///
+    /// 包限定的依赖（如嵌入的 `models.Base`）借助 `owner_file` 的导入声明
+    /// 解析到对应包中的定义，避免按名称误匹配到其他包的同名类型
+    fn resolve_type_dependency(
+        &self,
+        dep_type: &str,
+        owner_file: &Path,
+        index: &SymbolIndex,
+    ) -> Option<GoTypeDefinition> {
+        let source_files = index.source_files();
+        match dep_type.split_once('.') {
+            Some((package, type_name)) => self.dependency_resolver.find_qualified_type_definition(
+                package,
+                type_name,
+                owner_file,
+                source_files,
+            ),
+            None => index.find_type(dep_type).cloned(),
+        }
+    }
+
+    /// 提取变量类型和初始值中引用的类型和函数
+    ///
+    /// 初始值中作为复合字面量类型（`&models.Config{...}`）或被调用（`NewClient(...)`）的
+    /// 标识符会被解析为项目内的类型或函数定义；类型加入 `related_types`，函数作为返回值
+    fn extract_variable_dependencies(
+        &self,
+        variable: &GoVariableDefinition,
+        index: &SymbolIndex,
+        related_types: &mut Vec<GoTypeDefinition>,
+        processed_types: &mut HashSet<String>,
+        processed_functions: &mut HashSet<String>,
+        files: &mut FileBudget,
+    ) -> Result<Vec<GoFunctionInfo>> {
+        let mut dependent_functions = Vec::new();
+        let mut type_names = Vec::new();
+        let mut function_names = Vec::new();
+
+        // 类型名和函数名都去掉包限定符（或方法调用的接收者），按名称查找定义
+        let unqualified = |name: &str| name.rsplit('.').next().unwrap_or(name).to_string();
+        if let Some(var_type) = &variable.var_type {
+            type_names.push(unqualified(&var_type.name));
+        }
+        for cap in variable
+            .initial_value
+            .iter()
+            .flat_map(|value| COMPOSITE_OR_CALL.captures_iter(value))
+        {
+            let name = unqualified(&cap[1]);
+            if &cap[2] == "{" {
+                type_names.push(name);
+            } else {
+                function_names.push(name);
+            }
+        }
+
+        for type_name in type_names {
+            if self.is_builtin_type(&type_name) {
+                continue;
+            }
+            self.extract_type_recursively(
+                &type_name,
+                index,
+                related_types,
+                processed_types,
+                files,
+                0,
+            )?;
+        }
+
+        for function_name in function_names {
+            if self.is_builtin_type(&function_name) || processed_functions.contains(&function_name)
+            {
+                continue;
+            }
+
+            // 类型转换（`Duration(5)`）同样以调用形式出现
+            if index.find_type(&function_name).is_some() {
+                self.extract_type_recursively(
+                    &function_name,
+                    index,
+                    related_types,
+                    processed_types,
+                    files,
+                    0,
+                )?;
+                continue;
+            }
+
+            if let Some(func_info) = index.find_function(&function_name).cloned() {
+                processed_functions.insert(func_info.name.clone());
+                if !self.admit_function(&func_info, index, files) {
+                    continue;
+                }
+                self.extract_function_signature_dependencies(
+                    &func_info,
+                    index,
+                    related_types,
+                    processed_types,
+                    files,
+                )?;
+                dependent_functions.push(func_info);
+            }
+        }
+
+        Ok(dependent_functions)
     }
 
     /// 从类型定义中提取依赖的类型名称
@@ -1366,6 +3025,32 @@ impl SemanticContextExtractor {
             }
         }
 
+        // 解析器识别出的映射和数组字段可以处理指针、包限定、嵌套映射和多维数组，
+        // 如 `map[string]*models.User`、`[MaxSize]User`
+        if let Ok(re) = regex::Regex::new(r"^(?:[a-z_][A-Za-z0-9_]*\.)?[A-Z][a-zA-Z0-9_]*$") {
+            for field in &type_def.fields {
+                let mut components = field.field_type.map_component_types();
+                if field.field_type.array_len.is_some() {
+                    components.push(&field.field_type);
+                }
+                for component in components {
+                    // 去掉多维数组的内层维度和指针标记
+                    let type_str = component
+                        .name
+                        .rsplit(']')
+                        .next()
+                        .unwrap_or_default()
+                        .trim_start_matches('*');
+                    if re.is_match(type_str)
+                        && type_str != type_def.name
+                        && !self.is_builtin_type(type_str)
+                    {
+                        dependencies.push(type_str.to_string());
+                    }
+                }
+            }
+        }
+
         // 4. 匹配接口方法中的参数和返回类型
         if let Ok(re) = regex::Regex::new(r"(\w+)\s*\([^)]*(\*?)([A-Z][a-zA-Z0-9_]*)[^)]*\)") {
             for cap in re.captures_iter(definition) {
@@ -1390,7 +3075,28 @@ impl SemanticContextExtractor {
             }
         }
 
-        // 6. 匹配类型别名: type NewType OldType
+        // 6. 匹配嵌入字段: TypeName、*TypeName 或包限定的 pkg.TypeName
+        if let Ok(re) = regex::Regex::new(
+            r"(?m)^\s*\*?((?:[a-z_][A-Za-z0-9_]*\.)?[A-Z][a-zA-Z0-9_]*)\s*(`[^`]*`)?\s*$",
+        ) {
+            for cap in re.captures_iter(definition) {
+                if let Some(embedded_type) = cap.get(1) {
+                    let type_str = embedded_type.as_str();
+                    if type_str != type_def.name && !self.is_builtin_type(type_str) {
+                        dependencies.push(type_str.to_string());
+                    }
+                }
+            }
+        }
+        // 解析器识别出的嵌入字段覆盖单行写法 `struct { User; Level int }`
+        for field in type_def.fields.iter().filter(|field| field.embedded) {
+            let type_str = field.field_type.name.trim_start_matches('*');
+            if type_str != type_def.name && !self.is_builtin_type(type_str) {
+                dependencies.push(type_str.to_string());
+            }
+        }
+
+        // 7. 匹配类型别名: type NewType OldType
         if let Ok(re) = regex::Regex::new(r"type\s+\w+\s+(\*?)([A-Z][a-zA-Z0-9_]*)") {
             for cap in re.captures_iter(definition) {
                 if let Some(base_type) = cap.get(2) {
@@ -1402,12 +3108,41 @@ impl SemanticContextExtractor {
             }
         }
 
+        // 8. 匹配泛型类型参数约束中的类型: [T Number] 或 [T ~int | Stringer]
+        for type_param in &type_def.type_parameters {
+            for cap in CONSTRAINT_TYPE.captures_iter(&type_param.constraint) {
+                let type_str = &cap[1];
+                if type_str != type_def.name && !self.is_builtin_type(type_str) {
+                    dependencies.push(type_str.to_string());
+                }
+            }
+        }
+
+        // 字段类型为类型参数（如 `Value T`）时不是真正的依赖
+        dependencies.retain(|dep| !type_def.type_parameters.iter().any(|p| p.name == *dep));
+
         // 去重并排序
         dependencies.sort();
         dependencies.dedup();
         dependencies
     }
 
+    /// 提取类型定义中作为数组长度使用的常量名，例如 `[MaxSize]User` 中的 `MaxSize`
+    fn extract_array_length_constants(&self, type_def: &GoTypeDefinition) -> Vec<String> {
+        let mut constants: Vec<String> = type_def
+            .fields
+            .iter()
+            .flat_map(|field| field.field_type.array_lengths())
+            .filter(|length| {
+                length.starts_with(|c: char| c.is_alphabetic() || c == '_')
+                    && length.chars().all(|c| c.is_alphanumeric() || c == '_')
+            })
+            .collect();
+        constants.sort();
+        constants.dedup();
+        constants
+    }
+
     /// 检查是否为 Go 内置类型
     fn is_builtin_type(&self, type_name: &str) -> bool {
         matches!(
@@ -1489,14 +3224,13 @@ impl SemanticContextExtractor {
         None
     }
 
-    /// 查找使用指定类型的函数
-    fn find_functions_using_type(
+    /// 查找函数中使用的全局变量定义
+    fn find_variables_used_by_function(
         &self,
-        type_name: &str,
+        function: &GoFunctionInfo,
         source_files: &[SourceFile],
-    ) -> Vec<GoFunctionInfo> {
-        let mut functions = Vec::new();
-
+    ) -> Vec<GoVariableDefinition> {
+        let mut variables = Vec::new();
         for source_file in source_files {
             if let Some(go_info) = source_file
                 .language_specific
@@ -1504,31 +3238,76 @@ impl SemanticContextExtractor {
                 .downcast_ref::<crate::parser::GoLanguageInfo>()
             {
                 for declaration in go_info.declarations() {
-                    if let Some(
-                        crate::parser::GoDeclaration::Function(func)
-                        | crate::parser::GoDeclaration::Method(func),
-                    ) = declaration
+                    if let Some(crate::parser::GoDeclaration::Variable(var_def)) = declaration
                         .as_any()
                         .downcast_ref::<crate::parser::GoDeclaration>()
                     {
-                        if self.function_uses_type(func, type_name) {
-                            functions.push(func.clone());
+                        if self.function_uses_variable(function, &var_def.name) {
+                            variables.push(var_def.clone());
                         }
                     }
                 }
             }
         }
+        variables
+    }
 
-        functions
+    /// 为缺少对应测试的导出函数设置 `untested` 标记
+    fn mark_untested(&self, context: &mut SemanticContext, source_files: &[SourceFile]) {
+        if !self.check_test_coverage || !context.change_target.is_exported() {
+            return;
+        }
+        if let Some(function) = context.change_target.as_function() {
+            context.untested = !self.has_test_for_function(function, source_files);
+        }
     }
 
-    /// 查找使用指定变量的函数
-    fn find_functions_using_variable(
+    /// 在源文件池中查找函数对应的测试
+    ///
+    /// 接受 `TestName`、`TestName_Case` 以及方法的 `TestRecv_Name` 命名
+    fn has_test_for_function(
         &self,
-        var_name: &str,
+        function: &GoFunctionInfo,
         source_files: &[SourceFile],
-    ) -> Vec<GoFunctionInfo> {
-        let mut functions = Vec::new();
+    ) -> bool {
+        let mut candidates = vec![format!("Test{}", function.name)];
+        if let Some(receiver) = &function.receiver {
+            candidates.push(format!("Test{}_{}", receiver.type_name, function.name));
+        }
+
+        let is_test_name = |name: &str| {
+            candidates.iter().any(|candidate| {
+                name.strip_prefix(candidate.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('_'))
+            })
+        };
+
+        source_files.iter().any(|source_file| {
+            source_file
+                .language_specific
+                .declarations()
+                .iter()
+                .any(|declaration| {
+                    matches!(
+                        declaration
+                            .as_any()
+                            .downcast_ref::<crate::parser::GoDeclaration>(),
+                        Some(crate::parser::GoDeclaration::Function(test_func))
+                            if test_func.receiver.is_none() && is_test_name(&test_func.name)
+                    )
+                })
+        })
+    }
+
+    /// 查找与指定常量处于同一 `const (...)` 分组的其他常量
+    ///
+    /// 同一分组中的常量共享声明块的行号范围，据此判断是否属于同一分组
+    fn find_constant_group_siblings(
+        &self,
+        constant: &GoConstantDefinition,
+        source_files: &[SourceFile],
+    ) -> Vec<GoConstantDefinition> {
+        let mut siblings = Vec::new();
 
         for source_file in source_files {
             if let Some(go_info) = source_file
@@ -1537,73 +3316,32 @@ impl SemanticContextExtractor {
                 .downcast_ref::<crate::parser::GoLanguageInfo>()
             {
                 for declaration in go_info.declarations() {
-                    if let Some(
-                        crate::parser::GoDeclaration::Function(func)
-                        | crate::parser::GoDeclaration::Method(func),
-                    ) = declaration
+                    if let Some(crate::parser::GoDeclaration::Constant(const_def)) = declaration
                         .as_any()
                         .downcast_ref::<crate::parser::GoDeclaration>()
                     {
-                        if self.function_uses_variable(func, var_name) {
-                            functions.push(func.clone());
+                        if const_def.name != constant.name
+                            && const_def.file_path == constant.file_path
+                            && const_def.start_line == constant.start_line
+                            && const_def.end_line == constant.end_line
+                        {
+                            siblings.push(const_def.clone());
                         }
                     }
                 }
             }
         }
 
-        functions
-    }
-
-    /// 检查函数是否使用指定变量
-    fn function_uses_variable(&self, function: &GoFunctionInfo, var_name: &str) -> bool {
-        let body = &function.body;
-
-        // 1. 直接使用变量名（作为独立标识符）
-        if let Ok(re) = regex::Regex::new(&format!(r"\b{}\b", regex::escape(var_name))) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 2. 赋值操作: GlobalConfig = ...
-        if let Ok(re) = regex::Regex::new(&format!(r"{}\s*=", regex::escape(var_name))) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 3. 取地址操作: &GlobalConfig
-        if let Ok(re) = regex::Regex::new(&format!(r"&{}\b", regex::escape(var_name))) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 4. 字段访问: GlobalConfig.Field
-        if let Ok(re) = regex::Regex::new(&format!(r"{}\.[\w]+", regex::escape(var_name))) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 5. 函数调用参数: func(GlobalConfig)
-        if let Ok(re) = regex::Regex::new(&format!(r"\({}\)", regex::escape(var_name))) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        false
+        siblings
     }
 
-    /// 查找使用指定常量的函数
-    fn find_functions_using_constant(
+    /// 查找与指定变量处于同一 `var (...)` 分组的其他变量
+    fn find_variable_group_siblings(
         &self,
-        const_name: &str,
+        variable: &GoVariableDefinition,
         source_files: &[SourceFile],
-    ) -> Vec<GoFunctionInfo> {
-        let mut functions = Vec::new();
+    ) -> Vec<GoVariableDefinition> {
+        let mut siblings = Vec::new();
 
         for source_file in source_files {
             if let Some(go_info) = source_file
@@ -1612,14 +3350,52 @@ impl SemanticContextExtractor {
                 .downcast_ref::<crate::parser::GoLanguageInfo>()
             {
                 for declaration in go_info.declarations() {
-                    if let Some(
-                        crate::parser::GoDeclaration::Function(func)
-                        | crate::parser::GoDeclaration::Method(func),
+                    if let Some(crate::parser::GoDeclaration::Variable(var_def)) = declaration
+                        .as_any()
+                        .downcast_ref::<crate::parser::GoDeclaration>()
+                    {
+                        if var_def.name != variable.name
+                            && var_def.file_path == variable.file_path
+                            && var_def.start_line == variable.start_line
+                            && var_def.end_line == variable.end_line
+                        {
+                            siblings.push(var_def.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        siblings
+    }
+
+    /// 查找使用指定类型的函数
+    ///
+    /// 嵌入了该类型的结构体会获得其提升的字段和方法，使用这些外层结构体的函数同样视为使用了该类型
+    fn find_functions_using_type(
+        &self,
+        type_name: &str,
+        source_files: &[SourceFile],
+    ) -> Vec<GoFunctionInfo> {
+        let mut functions = Vec::new();
+        let mut used_types = vec![type_name.to_string()];
+        used_types.extend(self.find_types_embedding(type_name, source_files));
+
+        for source_file in source_files {
+            if let Some(go_info) = source_file
+                .language_specific
+                .as_any()
+                .downcast_ref::<crate::parser::GoLanguageInfo>()
+            {
+                for declaration in go_info.declarations() {
+                    if let Some(
+                        crate::parser::GoDeclaration::Function(func)
+                        | crate::parser::GoDeclaration::Method(func),
                     ) = declaration
                         .as_any()
                         .downcast_ref::<crate::parser::GoDeclaration>()
                     {
-                        if self.function_uses_constant(func, const_name) {
+                        if used_types.iter().any(|t| self.function_uses_type(func, t)) {
                             functions.push(func.clone());
                         }
                     }
@@ -1630,32 +3406,203 @@ impl SemanticContextExtractor {
         functions
     }
 
-    /// 检查函数是否使用指定常量
-    fn function_uses_constant(&self, function: &GoFunctionInfo, const_name: &str) -> bool {
-        let body = &function.body;
+    /// 查找直接或间接（嵌入链）嵌入了指定类型的结构体名称
+    fn find_types_embedding(&self, type_name: &str, source_files: &[SourceFile]) -> Vec<String> {
+        let embeds = |type_def: &GoTypeDefinition, name: &str| {
+            type_def.fields.iter().any(|field| {
+                let embedded = field.field_type.name.trim_start_matches('*');
+                field.embedded && embedded.rsplit('.').next() == Some(name)
+            })
+        };
 
-        // 1. 直接使用常量名（作为独立标识符）
-        if let Ok(re) = regex::Regex::new(&format!(r"\b{}\b", regex::escape(const_name))) {
-            if re.is_match(body) {
-                return true;
+        let type_defs: Vec<&GoTypeDefinition> = source_files
+            .iter()
+            .filter_map(|source_file| {
+                source_file
+                    .language_specific
+                    .as_any()
+                    .downcast_ref::<crate::parser::GoLanguageInfo>()
+            })
+            .flat_map(|go_info| go_info.declarations())
+            .filter_map(|declaration| {
+                match declaration
+                    .as_any()
+                    .downcast_ref::<crate::parser::GoDeclaration>()
+                {
+                    Some(crate::parser::GoDeclaration::Type(type_def)) => Some(type_def),
+                    _ => None,
+                }
+            })
+            .collect();
+
+        let mut embedding = Vec::new();
+        let mut pending = vec![type_name.to_string()];
+        while let Some(current) = pending.pop() {
+            for type_def in &type_defs {
+                if embeds(type_def, &current)
+                    && type_def.name != type_name
+                    && !embedding.contains(&type_def.name)
+                {
+                    embedding.push(type_def.name.clone());
+                    pending.push(type_def.name.clone());
+                }
+            }
+        }
+
+        embedding
+    }
+
+    /// 查找使用指定变量的函数
+    fn find_functions_using_variable(
+        &self,
+        var_name: &str,
+        source_files: &[SourceFile],
+    ) -> Vec<GoFunctionInfo> {
+        let mut functions = Vec::new();
+
+        for source_file in source_files {
+            if let Some(go_info) = source_file
+                .language_specific
+                .as_any()
+                .downcast_ref::<crate::parser::GoLanguageInfo>()
+            {
+                for declaration in go_info.declarations() {
+                    if let Some(
+                        crate::parser::GoDeclaration::Function(func)
+                        | crate::parser::GoDeclaration::Method(func),
+                    ) = declaration
+                        .as_any()
+                        .downcast_ref::<crate::parser::GoDeclaration>()
+                    {
+                        if self.function_uses_variable(func, var_name) {
+                            functions.push(func.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        functions
+    }
+
+    /// 检查函数是否使用指定变量
+    ///
+    /// 基于 CST 并跟踪作用域，被局部声明或参数遮蔽的同名标识符不算作对包级变量的使用
+    fn function_uses_variable(&self, function: &GoFunctionInfo, var_name: &str) -> bool {
+        // 先做廉价的文本检查，函数体中出现该名称时才查询提取时收集的引用汇总
+        function.body.contains(var_name)
+            && !function.signature_declares(var_name)
+            && function.body_references.uses_unshadowed(var_name)
+    }
+
+    /// 查找使用指定常量的函数
+    ///
+    /// 与常量同包的函数通过未限定的名称引用常量；其他包的函数只有通过解析到常量所在包的
+    /// 限定符（包括别名导入，如 `m.DefaultHost`）引用时才算使用。无法确定常量所在包时，
+    /// 任何已导入包的限定符都视为可能的引用
+    fn find_functions_using_constant(
+        &self,
+        constant: &GoConstantDefinition,
+        source_files: &[SourceFile],
+    ) -> Vec<GoFunctionInfo> {
+        let mut functions = Vec::new();
+        let const_package = source_files
+            .iter()
+            .find(|file| file.path == constant.file_path)
+            .map(|file| file.language_specific.package_name());
+
+        for source_file in source_files {
+            if let Some(go_info) = source_file
+                .language_specific
+                .as_any()
+                .downcast_ref::<crate::parser::GoLanguageInfo>()
+            {
+                let same_package =
+                    const_package.is_none_or(|package| package == go_info.package_name());
+                let qualifiers: Vec<&str> = go_info
+                    .import_aliases()
+                    .into_keys()
+                    .filter(|qualifier| {
+                        const_package.is_none()
+                            || self
+                                .dependency_resolver
+                                .resolve_qualified(
+                                    qualifier,
+                                    &constant.name,
+                                    go_info.imports(),
+                                    source_files,
+                                )
+                                .is_some_and(|symbol| {
+                                    matches!(symbol.declaration,
+                                        crate::parser::GoDeclaration::Constant(ref c)
+                                            if c.file_path == constant.file_path)
+                                })
+                    })
+                    .collect();
+
+                for declaration in go_info.declarations() {
+                    if let Some(
+                        crate::parser::GoDeclaration::Function(func)
+                        | crate::parser::GoDeclaration::Method(func),
+                    ) = declaration
+                        .as_any()
+                        .downcast_ref::<crate::parser::GoDeclaration>()
+                    {
+                        let uses_constant = (same_package
+                            && self.function_uses_constant(func, &constant.name))
+                            || qualifiers.iter().any(|qualifier| {
+                                Self::function_uses_qualified(func, qualifier, &constant.name)
+                            });
+                        if uses_constant {
+                            functions.push(func.clone());
+                        }
+                    }
+                }
             }
         }
 
-        // 2. 包限定的常量使用: models.DefaultHost
-        if let Ok(re) = regex::Regex::new(&format!(r"\w+\.{}\b", regex::escape(const_name))) {
+        functions
+    }
+
+    /// 检查函数体中是否出现包限定的引用 `qualifier.name`
+    fn function_uses_qualified(function: &GoFunctionInfo, qualifier: &str, name: &str) -> bool {
+        regex::Regex::new(&format!(
+            r"\b{}\.{}\b",
+            regex::escape(qualifier),
+            regex::escape(name)
+        ))
+        .is_ok_and(|re| re.is_match(&function.body))
+    }
+
+    /// 检查函数是否使用指定常量
+    fn function_uses_constant(&self, function: &GoFunctionInfo, const_name: &str) -> bool {
+        // 超大函数体改用 CST 检测
+        if function.body.len() > self.max_regex_scan_bytes {
+            return function.body.contains(const_name)
+                && function
+                    .body_references
+                    .references(const_name, ReferenceKind::Constant);
+        }
+
+        let body = &function.body;
+
+        // 1. 直接使用常量名（作为独立标识符，`x.Name` 形式的字段或包限定引用除外，
+        //    包限定引用由 find_functions_using_constant 按导入解析）
+        if let Ok(re) = regex::Regex::new(&format!(r"(?:^|[^.\w]){}\b", regex::escape(const_name)))
+        {
             if re.is_match(body) {
                 return true;
             }
         }
 
-        // 3. 在赋值中使用
+        // 2. 在赋值中使用
         if let Ok(re) = regex::Regex::new(&format!(r"=\s*{}\b", regex::escape(const_name))) {
             if re.is_match(body) {
                 return true;
             }
         }
 
-        // 4. 在比较中使用
+        // 3. 在比较中使用
         if let Ok(re) = regex::Regex::new(&format!(r"[=!<>]=?\s*{}\b", regex::escape(const_name))) {
             if re.is_match(body) {
                 return true;
@@ -1684,6 +3631,20 @@ impl SemanticContextExtractor {
             return true;
         }
 
+        // 如果是通道类型，检查元素类型
+        if go_type.is_channel && go_type.name == type_name {
+            return true;
+        }
+
+        // 如果是映射类型，检查键和值类型
+        if go_type
+            .map_component_types()
+            .into_iter()
+            .any(|component| self.type_matches(component, type_name))
+        {
+            return true;
+        }
+
         // 处理复合类型，如 map[string]TypeName 中的 TypeName
         if go_type.name.contains(type_name) {
             // 使用正则表达式进行更精确的匹配
@@ -1763,7 +3724,7 @@ impl SemanticContextExtractor {
     fn function_uses_type(&self, function: &GoFunctionInfo, type_name: &str) -> bool {
         // 检查接收者类型
         if let Some(receiver) = &function.receiver {
-            if receiver.type_name == type_name {
+            if receiver.belongs_to(type_name) {
                 return true;
             }
         }
@@ -1782,69 +3743,32 @@ impl SemanticContextExtractor {
             }
         }
 
-        // 检查函数体中的类型使用（更智能的匹配）
-        let body = &function.body;
-
-        // 1. 匹配类型字面量: TypeName{...}
-        if let Ok(re) = regex::Regex::new(&format!(r"\b{}\s*\{{", regex::escape(type_name))) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 2. 匹配类型转换: TypeName(...)
-        if let Ok(re) = regex::Regex::new(&format!(r"\b{}\s*\(", regex::escape(type_name))) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 3. 匹配变量声明: var x TypeName 或 var x *TypeName
-        if let Ok(re) =
-            regex::Regex::new(&format!(r"var\s+\w+\s+\*?{}\b", regex::escape(type_name)))
-        {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 4. 匹配短变量声明中的类型断言: x := y.(TypeName)
-        if let Ok(re) = regex::Regex::new(&format!(r"\.\(\*?{}\)", regex::escape(type_name))) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 5. 匹配make调用: make([]TypeName, ...)
-        if let Ok(re) = regex::Regex::new(&format!(
-            r"make\s*\(\s*\[\]\*?{}\b",
-            regex::escape(type_name)
-        )) {
-            if re.is_match(body) {
-                return true;
-            }
-        }
-
-        // 6. 匹配new调用: new(TypeName)
-        if let Ok(re) =
-            regex::Regex::new(&format!(r"new\s*\(\s*\*?{}\s*\)", regex::escape(type_name)))
-        {
-            if re.is_match(body) {
-                return true;
-            }
+        // 检查泛型类型参数的约束
+        let in_constraint = function.type_parameters.iter().any(|type_param| {
+            IDENTIFIER
+                .find_iter(&type_param.constraint)
+                .any(|m| m.as_str() == type_name)
+        });
+        if in_constraint {
+            return true;
         }
 
-        // 7. 简单的包含检查作为后备
-        body.contains(type_name)
+        // 函数体中的类型使用以解析时收集的类型标识符为准，
+        // 字符串、注释和更长标识符中的同名文本不会被误判。
+        // 解析时无法确定其他文件中声明的类型，此处 `type_name` 已知是类型，对它的调用即为类型转换
+        let references = &function.body_references;
+        references.references(type_name, ReferenceKind::Type)
+            || references.references(type_name, ReferenceKind::Function)
     }
 
     /// 分析跨模块依赖
     fn analyze_cross_module_dependencies(
         &self,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
         types: &[GoTypeDefinition],
         functions: &[GoFunctionInfo],
     ) -> Result<HashMap<String, Vec<String>>> {
+        let source_files = index.source_files();
         let mut cross_module_deps = HashMap::new();
 
         // 按模块分组文件和创建类型到模块的映射
@@ -1858,6 +3782,11 @@ impl SemanticContextExtractor {
                 .as_any()
                 .downcast_ref::<crate::parser::GoLanguageInfo>()
             {
+                // 包名未知的文件无法归属到任何模块，不参与跨模块分组
+                if !go_info.has_known_package() {
+                    continue;
+                }
+
                 let package_name = go_info.package_name().to_string();
                 modules
                     .entry(package_name.clone())
@@ -1898,6 +3827,7 @@ impl SemanticContextExtractor {
                 if module_files.iter().any(|f| f.path == type_def.file_path) {
                     let type_deps = self.extract_type_dependencies(type_def);
                     for dep in type_deps {
+                        let dep = dep.rsplit('.').next().unwrap_or(&dep).to_string();
                         if let Some(dep_module) = type_to_module.get(&dep) {
                             if dep_module != module_name {
                                 dependencies.insert(format!("{dep_module}:{dep}"));
@@ -1934,7 +3864,7 @@ impl SemanticContextExtractor {
                     // 分析函数体中的跨模块调用
                     let func_deps = self
                         .dependency_resolver
-                        .extract_function_dependencies(function, source_files);
+                        .extract_function_dependencies(function, index);
                     for dep in func_deps {
                         match dep.dependency_type {
                             DependencyType::Type => {
@@ -1990,6 +3920,37 @@ impl SemanticContextExtractor {
         Ok(cross_module_deps)
     }
 
+    /// 将收集到的导入按 gofmt 的分组顺序排列，保证输出稳定
+    fn sorted_imports(required_imports: HashSet<Import>) -> Vec<Import> {
+        let mut imports: Vec<Import> = required_imports.into_iter().collect();
+        DependencyResolver::sort_imports(&mut imports);
+        imports
+    }
+
+    /// 收集目标所在文件中的空白导入和点导入
+    ///
+    /// 这两种导入无法通过包限定引用识别：空白导入只为初始化副作用而存在，
+    /// 点导入的标识符不经限定直接使用，因此无论是否找到引用都予以保留
+    fn collect_unqualified_imports(
+        &self,
+        file_path: &Path,
+        source_files: &[SourceFile],
+        required_imports: &mut HashSet<Import>,
+    ) {
+        let imports = source_files
+            .iter()
+            .filter(|source_file| source_file.path == file_path)
+            .filter_map(|source_file| {
+                source_file
+                    .language_specific
+                    .as_any()
+                    .downcast_ref::<crate::parser::GoLanguageInfo>()
+            })
+            .flat_map(|go_info| go_info.imports().iter())
+            .filter(|import| import.is_unqualified());
+        required_imports.extend(imports.cloned());
+    }
+
     /// 收集必需的导入声明
     ///
     /// 分析函数和类型定义，确定需要哪些导入声明
@@ -2001,6 +3962,8 @@ impl SemanticContextExtractor {
         source_files: &[SourceFile],
         required_imports: &mut HashSet<Import>,
     ) -> Result<()> {
+        self.collect_unqualified_imports(&function.file_path, source_files, required_imports);
+
         // 创建包名到导入的映射
         let mut package_imports = HashMap::new();
 
@@ -2010,18 +3973,12 @@ impl SemanticContextExtractor {
                 .as_any()
                 .downcast_ref::<crate::parser::GoLanguageInfo>()
             {
-                for import in go_info.imports() {
-                    let package_name = if let Some(alias) = &import.alias {
-                        alias.clone()
-                    } else {
-                        import
-                            .path
-                            .split('/')
-                            .next_back()
-                            .unwrap_or(&import.path)
-                            .to_string()
-                    };
-                    package_imports.insert(package_name, import.clone());
+                for import in go_info
+                    .imports()
+                    .iter()
+                    .filter(|import| !import.is_unqualified())
+                {
+                    package_imports.insert(import.local_name().to_string(), import.clone());
                 }
             }
         }
@@ -2083,6 +4040,8 @@ impl SemanticContextExtractor {
         source_files: &[SourceFile],
         required_imports: &mut HashSet<Import>,
     ) -> Result<()> {
+        self.collect_unqualified_imports(&type_def.file_path, source_files, required_imports);
+
         // 创建包名到导入的映射
         let mut package_imports = HashMap::new();
 
@@ -2092,18 +4051,12 @@ impl SemanticContextExtractor {
                 .as_any()
                 .downcast_ref::<crate::parser::GoLanguageInfo>()
             {
-                for import in go_info.imports() {
-                    let package_name = if let Some(alias) = &import.alias {
-                        alias.clone()
-                    } else {
-                        import
-                            .path
-                            .split('/')
-                            .next_back()
-                            .unwrap_or(&import.path)
-                            .to_string()
-                    };
-                    package_imports.insert(package_name, import.clone());
+                for import in go_info
+                    .imports()
+                    .iter()
+                    .filter(|import| !import.is_unqualified())
+                {
+                    package_imports.insert(import.local_name().to_string(), import.clone());
                 }
             }
         }
@@ -2145,6 +4098,8 @@ impl SemanticContextExtractor {
         source_files: &[SourceFile],
         required_imports: &mut HashSet<Import>,
     ) -> Result<()> {
+        self.collect_unqualified_imports(&variable.file_path, source_files, required_imports);
+
         // 创建包名到导入的映射
         let mut package_imports = HashMap::new();
 
@@ -2154,18 +4109,12 @@ impl SemanticContextExtractor {
                 .as_any()
                 .downcast_ref::<crate::parser::GoLanguageInfo>()
             {
-                for import in go_info.imports() {
-                    let package_name = if let Some(alias) = &import.alias {
-                        alias.clone()
-                    } else {
-                        import
-                            .path
-                            .split('/')
-                            .next_back()
-                            .unwrap_or(&import.path)
-                            .to_string()
-                    };
-                    package_imports.insert(package_name, import.clone());
+                for import in go_info
+                    .imports()
+                    .iter()
+                    .filter(|import| !import.is_unqualified())
+                {
+                    package_imports.insert(import.local_name().to_string(), import.clone());
                 }
             }
         }
@@ -2209,6 +4158,8 @@ impl SemanticContextExtractor {
         source_files: &[SourceFile],
         required_imports: &mut HashSet<Import>,
     ) -> Result<()> {
+        self.collect_unqualified_imports(&constant.file_path, source_files, required_imports);
+
         // 创建包名到导入的映射
         let mut package_imports = HashMap::new();
 
@@ -2218,18 +4169,12 @@ impl SemanticContextExtractor {
                 .as_any()
                 .downcast_ref::<crate::parser::GoLanguageInfo>()
             {
-                for import in go_info.imports() {
-                    let package_name = if let Some(alias) = &import.alias {
-                        alias.clone()
-                    } else {
-                        import
-                            .path
-                            .split('/')
-                            .next_back()
-                            .unwrap_or(&import.path)
-                            .to_string()
-                    };
-                    package_imports.insert(package_name, import.clone());
+                for import in go_info
+                    .imports()
+                    .iter()
+                    .filter(|import| !import.is_unqualified())
+                {
+                    package_imports.insert(import.local_name().to_string(), import.clone());
                 }
             }
         }
@@ -2271,12 +4216,13 @@ impl SemanticContextExtractor {
         source_files: &[SourceFile],
         max_depth: usize,
     ) -> Result<Vec<GoFunctionInfo>> {
+        let index = self.dependency_resolver.build_index(source_files);
         let mut result = Vec::new();
         let mut processed = HashSet::new();
 
         self.extract_dependent_functions_recursive_impl(
             function,
-            source_files,
+            &index,
             &mut result,
             &mut processed,
             0,
@@ -2290,7 +4236,7 @@ impl SemanticContextExtractor {
     fn extract_dependent_functions_recursive_impl(
         &self,
         function: &GoFunctionInfo,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
         result: &mut Vec<GoFunctionInfo>,
         processed: &mut HashSet<String>,
         depth: usize,
@@ -2302,7 +4248,7 @@ impl SemanticContextExtractor {
 
         let dependencies = self
             .dependency_resolver
-            .extract_function_dependencies(function, source_files);
+            .extract_function_dependencies(function, index);
         let internal_dependencies = self
             .dependency_resolver
             .filter_internal_dependencies(&dependencies);
@@ -2313,14 +4259,11 @@ impl SemanticContextExtractor {
             {
                 processed.insert(dependency.name.clone());
 
-                if let Some(func_info) = self
-                    .dependency_resolver
-                    .find_function_definition(&dependency.name, source_files)
-                {
+                if let Some(func_info) = index.find_function(&dependency.name).cloned() {
                     // 递归处理这个函数的依赖
                     self.extract_dependent_functions_recursive_impl(
                         &func_info,
-                        source_files,
+                        index,
                         result,
                         processed,
                         depth + 1,
@@ -2353,24 +4296,7 @@ impl SemanticContextExtractor {
         let mut missing_dependencies = Vec::new();
 
         // 检查变更目标的类型依赖是否都被包含
-        let target_deps = match &context.change_target {
-            ChangeTarget::Function(func) => self.extract_type_references_from_function(func),
-            ChangeTarget::Type(type_def) => self.extract_type_dependencies(type_def),
-            ChangeTarget::Variable(var) => {
-                if let Some(var_type) = &var.var_type {
-                    vec![var_type.name.clone()]
-                } else {
-                    vec![]
-                }
-            }
-            ChangeTarget::Constant(const_def) => {
-                if let Some(const_type) = &const_def.const_type {
-                    vec![const_type.name.clone()]
-                } else {
-                    vec![]
-                }
-            }
-        };
+        let target_deps = self.target_type_references(&context.change_target);
         for type_ref in target_deps {
             if !context.related_types.iter().any(|t| t.name == type_ref)
                 && !self.is_builtin_type(&type_ref)
@@ -2383,7 +4309,9 @@ impl SemanticContextExtractor {
         for type_def in &context.related_types {
             let type_deps = self.extract_type_dependencies(type_def);
             for dep_type in type_deps {
-                if !context.related_types.iter().any(|t| t.name == dep_type)
+                // 包限定的依赖按类型名比较
+                let dep_name = dep_type.rsplit('.').next().unwrap_or(&dep_type);
+                if !context.related_types.iter().any(|t| t.name == dep_name)
                     && !self.is_builtin_type(&dep_type)
                 {
                     missing_dependencies.push(format!(
@@ -2397,24 +4325,52 @@ impl SemanticContextExtractor {
         Ok(missing_dependencies)
     }
 
+    /// 变更目标直接引用的类型名
+    fn target_type_references(&self, change_target: &ChangeTarget) -> Vec<String> {
+        match change_target {
+            ChangeTarget::Function(func) | ChangeTarget::Renamed { function: func, .. } => {
+                self.extract_type_references_from_function(func)
+            }
+            ChangeTarget::Type(type_def) => self.extract_type_dependencies(type_def),
+            ChangeTarget::Variable(var) => {
+                if let Some(var_type) = &var.var_type {
+                    vec![var_type.name.clone()]
+                } else {
+                    vec![]
+                }
+            }
+            ChangeTarget::Constant(const_def) => {
+                if let Some(const_type) = &const_def.const_type {
+                    vec![const_type.name.clone()]
+                } else {
+                    vec![]
+                }
+            }
+            // 被删除的声明不要求其依赖仍然存在，包的依赖已并入相关类型
+            ChangeTarget::Removed { .. } | ChangeTarget::Package { .. } => Vec::new(),
+        }
+    }
+
     /// 提取函数签名中的类型依赖（递归）
     ///
     /// 这个方法专门处理函数签名中的类型依赖，包括参数类型、返回类型和接收者类型
     fn extract_function_signature_dependencies(
         &self,
         function: &GoFunctionInfo,
-        source_files: &[SourceFile],
+        index: &SymbolIndex,
         related_types: &mut Vec<GoTypeDefinition>,
         processed_types: &mut HashSet<String>,
+        files: &mut FileBudget,
     ) -> Result<()> {
         // 1. 提取接收者类型依赖
         if let Some(receiver) = &function.receiver {
             if !self.is_builtin_type(&receiver.type_name) {
                 self.extract_type_recursively(
                     &receiver.type_name,
-                    source_files,
+                    index,
                     related_types,
                     processed_types,
+                    files,
                     0,
                 )?;
             }
@@ -2426,9 +4382,10 @@ impl SemanticContextExtractor {
             if !self.is_builtin_type(type_name) {
                 self.extract_type_recursively(
                     type_name,
-                    source_files,
+                    index,
                     related_types,
                     processed_types,
+                    files,
                     0,
                 )?;
             }
@@ -2440,9 +4397,10 @@ impl SemanticContextExtractor {
             if !self.is_builtin_type(type_name) {
                 self.extract_type_recursively(
                     type_name,
-                    source_files,
+                    index,
                     related_types,
                     processed_types,
+                    files,
                     0,
                 )?;
             }
@@ -2451,6 +4409,25 @@ impl SemanticContextExtractor {
         Ok(())
     }
 
+    /// 判断依赖函数能否连同签名中的类型一起加入上下文
+    ///
+    /// 签名类型所在的文件与函数本身一起计入文件预算，放不下时整个函数被省略，
+    /// 上下文中不会出现缺少参数或返回类型定义的函数
+    fn admit_function(
+        &self,
+        function: &GoFunctionInfo,
+        index: &SymbolIndex,
+        files: &mut FileBudget,
+    ) -> bool {
+        let signature_files: Vec<&Path> = self
+            .extract_type_references_from_function(function)
+            .iter()
+            .filter_map(|type_name| index.find_type(type_name))
+            .map(|type_def| type_def.file_path.as_path())
+            .collect();
+        files.admit_with(&function.file_path, &signature_files)
+    }
+
     /// 从函数中提取类型引用
     fn extract_type_references_from_function(&self, function: &GoFunctionInfo) -> Vec<String> {
         let mut type_refs = Vec::new();
@@ -2476,10 +4453,10 @@ impl SemanticContextExtractor {
             }
         }
 
-        // 从函数体中提取（简化版本）
+        // 从函数体中提取
         let body_refs = self
             .dependency_resolver
-            .extract_type_references_from_code(&function.body);
+            .extract_type_references(&function.body_references);
         for type_ref in body_refs {
             if !self.is_builtin_type(&type_ref.name) {
                 type_refs.push(type_ref.name);
@@ -2497,7 +4474,10 @@ impl SemanticContextExtractor {
 mod tests {
     use super::*;
     use crate::parser::common::LanguageParser;
-    use crate::parser::{GoDeclaration, GoLanguageInfo, GoParameter, GoType, GoTypeKind};
+    use crate::parser::{
+        BodyReferences, GoDeclaration, GoLanguageInfo, GoParameter, GoType, GoTypeKind,
+        GoTypeParam, ImportKind,
+    };
     use pretty_assertions::assert_eq;
     use std::path::PathBuf;
 
@@ -2512,6 +4492,11 @@ mod tests {
             start_line: 1,
             end_line: 10,
             file_path: PathBuf::from("test.go"),
+            body_references: Arc::new(BodyReferences::from_body(body)),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         }
     }
 
@@ -2523,6 +4508,13 @@ mod tests {
             definition: definition.to_string(),
             file_path: PathBuf::from("test.go"),
             dependencies: vec![],
+            start_line: 0,
+            end_line: 0,
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            fields: Vec::new(),
+            doc_comment: None,
         }
     }
 
@@ -2547,6 +4539,7 @@ mod tests {
             syntax_tree,
             language: crate::parser::SupportedLanguage::Go,
             language_specific: Box::new(go_info),
+            build_constraint: None,
         }
     }
 
@@ -2592,6 +4585,7 @@ mod tests {
         let import = Import {
             path: "fmt".to_string(),
             alias: None,
+            kind: ImportKind::Normal,
         };
         context.add_import(import.clone());
         assert_eq!(context.imports.len(), 1);
@@ -2623,6 +4617,7 @@ mod tests {
         context.add_import(Import {
             path: "fmt".to_string(),
             alias: None,
+            kind: ImportKind::Normal,
         });
 
         let stats = context.get_stats();
@@ -2746,21 +4741,61 @@ mod tests {
     }
 
     #[test]
-    fn test_extract_type_references_from_function() {
-        // 测试从函数中提取类型引用
+    fn test_extract_generic_type_dependencies() {
         let extractor = SemanticContextExtractor::new();
 
-        let function = GoFunctionInfo {
-            name: "processUser".to_string(),
-            receiver: None,
-            parameters: vec![
+        let mut type_def = create_test_type(
+            "Pair",
+            r#"type Pair[K comparable, V Number] struct {
+    Key   K
+    Value V
+    Meta  Metadata
+}"#,
+        );
+        type_def.type_parameters = vec![
+            GoTypeParam {
+                name: "K".to_string(),
+                constraint: "comparable".to_string(),
+            },
+            GoTypeParam {
+                name: "V".to_string(),
+                constraint: "Number".to_string(),
+            },
+        ];
+
+        let dependencies = extractor.extract_type_dependencies(&type_def);
+
+        // 自定义约束接口是依赖，内置约束和类型参数本身不是
+        assert!(dependencies.contains(&"Number".to_string()));
+        assert!(dependencies.contains(&"Metadata".to_string()));
+        assert!(!dependencies.contains(&"comparable".to_string()));
+        assert!(!dependencies.contains(&"K".to_string()));
+        assert!(!dependencies.contains(&"V".to_string()));
+    }
+
+    #[test]
+    fn test_extract_type_references_from_function() {
+        // 测试从函数中提取类型引用
+        let extractor = SemanticContextExtractor::new();
+
+        let function = GoFunctionInfo {
+            name: "processUser".to_string(),
+            receiver: None,
+            parameters: vec![
                 GoParameter {
                     name: "user".to_string(),
                     param_type: GoType {
                         name: "User".to_string(),
                         is_pointer: false,
                         is_slice: false,
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
                     },
+                    is_variadic: false,
                 },
                 GoParameter {
                     name: "config".to_string(),
@@ -2768,7 +4803,14 @@ mod tests {
                         name: "Config".to_string(),
                         is_pointer: true,
                         is_slice: false,
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
                     },
+                    is_variadic: false,
                 },
             ],
             return_types: vec![
@@ -2776,17 +4818,36 @@ mod tests {
                     name: "Result".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
                 GoType {
                     name: "error".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
             ],
             body: "var order Order; return Result{}, nil".to_string(),
             start_line: 1,
             end_line: 5,
             file_path: PathBuf::from("test.go"),
+            body_references: Arc::new(BodyReferences::from_body(
+                "var order Order; return Result{}, nil",
+            )),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         };
 
         let type_refs = extractor.extract_type_references_from_function(&function);
@@ -2811,6 +4872,7 @@ mod tests {
             Import {
                 path: "fmt".to_string(),
                 alias: None,
+                kind: ImportKind::Normal,
             },
         );
         package_imports.insert(
@@ -2818,6 +4880,7 @@ mod tests {
             Import {
                 path: "encoding/json".to_string(),
                 alias: Some("json".to_string()),
+                kind: ImportKind::Normal,
             },
         );
 
@@ -2838,11 +4901,13 @@ mod tests {
         assert_eq!(required_imports.len(), 2);
         assert!(required_imports.contains(&Import {
             path: "fmt".to_string(),
-            alias: None
+            alias: None,
+            kind: ImportKind::Normal,
         }));
         assert!(required_imports.contains(&Import {
             path: "encoding/json".to_string(),
-            alias: Some("json".to_string())
+            alias: Some("json".to_string()),
+            kind: ImportKind::Normal,
         }));
     }
 
@@ -2860,13 +4925,25 @@ mod tests {
                     name: "User".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             }],
             return_types: vec![],
             body: "helper(); var config Config".to_string(),
             start_line: 1,
             end_line: 3,
             file_path: PathBuf::from("test.go"),
+            body_references: Arc::new(BodyReferences::from_body("helper(); var config Config")),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         };
 
         // 创建包含相关声明的源文件
@@ -2917,13 +4994,25 @@ mod tests {
                     name: "User".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             }],
             return_types: vec![],
             body: "return".to_string(),
             start_line: 1,
             end_line: 2,
             file_path: PathBuf::from("test.go"),
+            body_references: Arc::new(BodyReferences::from_body("return")),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         };
 
         let mut context = SemanticContext::from_function(main_function);
@@ -2958,17 +5047,37 @@ mod tests {
                     name: "User".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             }],
             return_types: vec![GoType {
                 name: "Result".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             }],
             body: "validateUser(user); return Result{}".to_string(),
             start_line: 1,
             end_line: 3,
             file_path: PathBuf::from("main.go"),
+            body_references: Arc::new(BodyReferences::from_body(
+                "validateUser(user); return Result{}",
+            )),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         };
 
         // 创建相关的类型和函数定义（使用简单的定义避免复杂的依赖）
@@ -3039,11 +5148,14 @@ mod tests {
         let mut processed = HashSet::new();
 
         // 这应该不会导致无限递归
+        let source_files = [source_file];
+        let index = extractor.dependency_resolver.build_index(&source_files);
         let result = extractor.extract_type_recursively(
             "TypeA",
-            &[source_file],
+            &index,
             &mut result_types,
             &mut processed,
+            &mut FileBudget::unlimited(),
             0,
         );
 
@@ -3052,6 +5164,1611 @@ mod tests {
         assert!(result_types.len() <= 3);
     }
 
+    #[test]
+    fn test_dependency_graph_find_cycles() {
+        // TypeA -> TypeB -> TypeC -> TypeA 的循环，外加不在循环中的 Config
+        let mut graph = DependencyGraph::new("type:TypeA".to_string());
+        for name in ["TypeA", "TypeB", "TypeC", "Config"] {
+            graph.add_node(DependencyNode {
+                id: format!("type:{name}"),
+                name: name.to_string(),
+                node_type: DependencyNodeType::Type,
+                file_path: Some(PathBuf::from("test.go")),
+                is_change_target: name == "TypeA",
+            });
+        }
+        for (from, to) in [
+            ("TypeA", "TypeB"),
+            ("TypeB", "TypeC"),
+            ("TypeC", "TypeA"),
+            ("TypeA", "Config"),
+        ] {
+            graph.add_edge(DependencyEdge {
+                from: format!("type:{from}"),
+                to: format!("type:{to}"),
+                edge_type: DependencyEdgeType::TypeUsage,
+            });
+        }
+
+        let cycles = graph.find_cycles();
+        assert_eq!(
+            cycles,
+            vec![vec![
+                "type:TypeA".to_string(),
+                "type:TypeB".to_string(),
+                "type:TypeC".to_string(),
+            ]]
+        );
+
+        // 文本树在回到 TypeA 的边上标注循环
+        let text = graph.to_text_tree();
+        assert_eq!(text.matches("(cycle)").count(), 1);
+        assert!(text.contains("↻ TypeA (cycle)"));
+
+        // DOT 输出只标注循环中的三条边
+        let dot = graph.to_dot();
+        assert_eq!(dot.matches("label=\"cycle\"").count(), 3);
+        assert!(dot.contains("\"type:TypeA\" -> \"type:Config\" [color=green];"));
+    }
+
+    #[test]
+    fn test_dependency_graph_to_mermaid() {
+        let mut context =
+            SemanticContext::from_function(create_test_function("Handle", "return nil"));
+        context.add_type(create_test_type("Server", "type Server struct {}"));
+        context.add_function(create_test_function("helper", "return 1"));
+        let graph = context.generate_dependency_graph();
+
+        let mermaid = graph.to_mermaid();
+        let lines: Vec<&str> = mermaid.lines().collect();
+        assert_eq!(lines[0], "graph TD");
+
+        // 每个节点和每条边各占一行，生成的 ID 不包含原始 ID 中的 `:`
+        let node_lines: Vec<&str> = lines
+            .iter()
+            .copied()
+            .filter(|line| line.trim_start().starts_with('n') && line.contains('"'))
+            .collect();
+        let edge_lines: Vec<&str> = lines
+            .iter()
+            .copied()
+            .filter(|line| line.contains("-->") || line.contains("-.->"))
+            .collect();
+        assert_eq!(node_lines.len(), graph.nodes.len());
+        assert_eq!(edge_lines.len(), graph.edges.len());
+        assert!(
+            node_lines
+                .iter()
+                .all(|line| !line.split('"').next().unwrap().contains(':'))
+        );
+
+        // 函数为圆角节点，类型为矩形节点，边带有类型标签
+        assert!(mermaid.contains("n0(\"Handle\")"));
+        assert!(mermaid.contains("[\"Server\"]"));
+        assert!(mermaid.contains("-->|uses|"));
+        assert!(mermaid.contains("-->|calls|"));
+
+        // 变更目标使用独立的样式类
+        assert!(mermaid.contains("classDef changeTarget"));
+        assert!(mermaid.contains("class n0 changeTarget;"));
+    }
+
+    #[test]
+    fn test_batch_priority_processes_exported_first() {
+        let source_file = create_test_source_file("test", vec![]);
+        let targets = vec![
+            ChangeTarget::Function(create_test_function("helper", "return nil")),
+            ChangeTarget::Function(create_test_function("Handle", "return nil")),
+            ChangeTarget::Function(create_test_function("parse", "return nil")),
+            ChangeTarget::Function(create_test_function("Serve", "return nil")),
+        ];
+
+        // 未设置优先级时保持输入顺序
+        let contexts = SemanticContextExtractor::new()
+            .extract_contexts_in_batches(&targets, std::slice::from_ref(&source_file), 1)
+            .unwrap();
+        let names: Vec<_> = contexts.iter().map(|c| c.change_target.name()).collect();
+        assert_eq!(names, vec!["helper", "Handle", "parse", "Serve"]);
+
+        // 导出的目标优先
+        let extractor = SemanticContextExtractor::new()
+            .with_batch_priority(|a, b| b.is_exported().cmp(&a.is_exported()));
+        let contexts = extractor
+            .extract_contexts_in_batches(&targets, &[source_file], 1)
+            .unwrap();
+        let names: Vec<_> = contexts.iter().map(|c| c.change_target.name()).collect();
+        assert_eq!(names, vec!["Handle", "Serve", "helper", "parse"]);
+    }
+
+    #[test]
+    fn test_progress_counts_contexts_across_batches() {
+        let source_file = create_test_source_file("test", vec![]);
+        let targets: Vec<_> = (0..5)
+            .map(|i| ChangeTarget::Function(create_test_function(&format!("f{i}"), "return nil")))
+            .collect();
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let collected = events.clone();
+        let extractor = SemanticContextExtractor::new()
+            .with_threads(2)
+            .on_progress(move |event| collected.lock().unwrap().push(event));
+        let contexts = extractor
+            .extract_contexts_in_batches(&targets, &[source_file], 2)
+            .unwrap();
+        assert_eq!(contexts.len(), targets.len());
+
+        // 分批处理时进度按全部目标连续计数
+        let mut completed: Vec<usize> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| match *event {
+                ProgressEvent::ContextExtracted { completed, total } => {
+                    assert_eq!(total, targets.len());
+                    completed
+                }
+                other => panic!("unexpected event: {other:?}"),
+            })
+            .collect();
+        completed.sort_unstable();
+        assert_eq!(completed, vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_streaming_matches_batch_extraction() {
+        let helper = create_test_function("helper", "return 1");
+        let source_file = create_test_source_file(
+            "test",
+            vec![
+                GoDeclaration::Function(helper.clone()),
+                GoDeclaration::Type(create_test_type("Config", "type Config struct {}")),
+            ],
+        );
+        let mut targets = vec![ChangeTarget::Function(helper)];
+        targets.extend((0..6).map(|i| {
+            ChangeTarget::Function(create_test_function(
+                &format!("f{i}"),
+                "var c Config\nreturn helper()",
+            ))
+        }));
+        let source_files = std::slice::from_ref(&source_file);
+
+        let summarize = |contexts: &[SemanticContext]| {
+            let mut summary: Vec<_> = contexts
+                .iter()
+                .map(|c| (c.change_target.name().to_string(), c.get_stats()))
+                .collect();
+            summary.sort_by(|a, b| a.0.cmp(&b.0));
+            summary
+        };
+        let expected = summarize(
+            &SemanticContextExtractor::new()
+                .extract_contexts_in_batches(&targets, source_files, 3)
+                .unwrap(),
+        );
+
+        for extractor in [
+            SemanticContextExtractor::new(),
+            SemanticContextExtractor::new().with_threads(1),
+        ] {
+            let streamed = std::sync::Mutex::new(Vec::new());
+            extractor
+                .extract_contexts_streaming(&targets, source_files, |context| {
+                    streamed.lock().unwrap().push(context)
+                })
+                .unwrap();
+            let streamed = streamed.into_inner().unwrap();
+
+            // 每个目标恰好出现一次，内容与批量提取一致
+            assert_eq!(streamed.len(), targets.len());
+            assert_eq!(summarize(&streamed), expected);
+        }
+    }
+
+    #[test]
+    fn test_memory_budget_shrinks_batches() {
+        let source_file = create_test_source_file("test", vec![]);
+        let targets: Vec<_> = (0..8)
+            .map(|i| {
+                ChangeTarget::Function(create_test_function(
+                    &format!("f{i}"),
+                    &"x := 1\n".repeat(20),
+                ))
+            })
+            .collect();
+        let source_files = std::slice::from_ref(&source_file);
+
+        let extractor = SemanticContextExtractor::new();
+        let context_size = extractor
+            .extract_context_for_target(targets[0].clone(), source_files)
+            .unwrap()
+            .estimated_size();
+        // 预算只够同时容纳 3 个上下文
+        let budget = context_size * 3 + context_size / 2;
+        let processor = MemoryEfficientAstProcessor::new()
+            .with_memory_monitoring(false)
+            .with_memory_budget(budget);
+
+        let contexts = extractor
+            .extract_contexts_in_batches_with(&targets, source_files, 8, &processor)
+            .unwrap();
+
+        assert_eq!(processor.current_batch_size(), Some(3));
+        assert!(processor.peak_tracked_memory() <= budget);
+        // 结果完整并保持顺序
+        let names: Vec<_> = contexts.iter().map(|c| c.change_target.name()).collect();
+        assert_eq!(names, vec!["f0", "f1", "f2", "f3", "f4", "f5", "f6", "f7"]);
+    }
+
+    #[test]
+    fn test_max_involved_files_cap() {
+        // 构造跨多个文件的类型依赖链：Type0 -> Type1 -> ... -> Type5
+        let mut declarations = Vec::new();
+        for i in 0..6 {
+            let definition = if i < 5 {
+                format!("type Type{i} struct {{\n    Next Type{}\n}}", i + 1)
+            } else {
+                format!("type Type{i} struct {{\n    Value string\n}}")
+            };
+            let mut type_def = create_test_type(&format!("Type{i}"), &definition);
+            type_def.file_path = PathBuf::from(format!("type{i}.go"));
+            declarations.push(GoDeclaration::Type(type_def));
+        }
+        let source_file = create_test_source_file("test", declarations);
+
+        let mut function = create_test_function("handle", "return nil");
+        function.file_path = PathBuf::from("main.go");
+        function.parameters = vec![GoParameter {
+            name: "t".to_string(),
+            param_type: GoType {
+                name: "Type0".to_string(),
+                is_pointer: false,
+                is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
+            },
+            is_variadic: false,
+        }];
+
+        let source_files = vec![source_file];
+
+        // 不设上限时整条链都会被展开
+        let unbounded = SemanticContextExtractor::new()
+            .extract_context(&function, &source_files)
+            .unwrap();
+        assert_eq!(unbounded.related_types.len(), 6);
+        assert!(unbounded.omitted_files.is_empty());
+
+        let extractor = SemanticContextExtractor::new().with_max_involved_files(3);
+        assert_eq!(extractor.get_max_involved_files(), Some(3));
+
+        let context = extractor.extract_context(&function, &source_files).unwrap();
+
+        // 变更目标所在文件 + 离它最近的两个类型文件
+        assert_eq!(context.get_involved_files().len(), 3);
+        let mut kept: Vec<_> = context
+            .related_types
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        kept.sort_unstable();
+        assert_eq!(kept, vec!["Type0", "Type1"]);
+
+        // 达到上限后不再沿 Type2 展开，只有被拒绝的文件会被记录，更深的文件不会被访问
+        assert_eq!(context.omitted_files, vec![PathBuf::from("type2.go")]);
+    }
+
+    #[test]
+    fn test_max_dependencies_cap() {
+        // 宽结构体：Wide 的十个字段各自使用不同的类型，Field0 还依赖更深一层的 Deep
+        let fields: String = (0..10).map(|i| format!("    F{i} Field{i}\n")).collect();
+        let wide = create_test_type("Wide", &format!("type Wide struct {{\n{fields}}}"));
+        let mut declarations = vec![
+            GoDeclaration::Type(create_test_type(
+                "Deep",
+                "type Deep struct {\n    Value string\n}",
+            )),
+            GoDeclaration::Type(wide.clone()),
+        ];
+        for i in 0..10 {
+            let definition = if i == 0 {
+                "type Field0 struct {\n    Inner Deep\n}".to_string()
+            } else {
+                format!("type Field{i} struct {{\n    Value string\n}}")
+            };
+            declarations.push(GoDeclaration::Type(create_test_type(
+                &format!("Field{i}"),
+                &definition,
+            )));
+        }
+        let source_files = vec![create_test_source_file("test", declarations)];
+
+        let unbounded = SemanticContextExtractor::new()
+            .extract_context_for_target(ChangeTarget::Type(wide.clone()), &source_files)
+            .unwrap();
+        assert_eq!(unbounded.related_types.len(), 11);
+        assert_eq!(unbounded.omitted_dependencies, 0);
+
+        let extractor = SemanticContextExtractor::new()
+            .with_max_dependencies(4)
+            .with_extraction_order(ExtractionOrder::BreadthFirst);
+        assert_eq!(extractor.get_max_dependencies(), Some(4));
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Type(wide.clone()), &source_files)
+            .unwrap();
+
+        // 变更目标本身不占名额；广度优先先接纳直接字段类型，更深的 Deep 被省略
+        assert_eq!(context.change_target.name(), "Wide");
+        let mut kept: Vec<_> = context
+            .related_types
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        kept.sort_unstable();
+        assert_eq!(kept, vec!["Field0", "Field1", "Field2", "Field3"]);
+        assert_eq!(context.omitted_dependencies, 7);
+
+        // 上限为 0 时只保留变更目标
+        let target_only = SemanticContextExtractor::new()
+            .with_max_dependencies(0)
+            .extract_context_for_target(ChangeTarget::Type(wide), &source_files)
+            .unwrap();
+        assert_eq!(target_only.change_target.name(), "Wide");
+        assert!(target_only.related_types.is_empty());
+        assert_eq!(target_only.omitted_dependencies, 11);
+    }
+
+    #[test]
+    fn test_extraction_order_on_diamond() {
+        // 菱形依赖：A→B, A→C, B→D, C→D
+        let a = create_test_type("A", "type A struct {\n    Left B\n    Right C\n}");
+        let source_files = vec![create_test_source_file(
+            "test",
+            vec![
+                GoDeclaration::Type(a.clone()),
+                GoDeclaration::Type(create_test_type("B", "type B struct {\n    Next D\n}")),
+                GoDeclaration::Type(create_test_type("C", "type C struct {\n    Next D\n}")),
+                GoDeclaration::Type(create_test_type(
+                    "D",
+                    "type D struct {\n    Value string\n}",
+                )),
+            ],
+        )];
+        let kept_types = |order: ExtractionOrder| {
+            let context = SemanticContextExtractor::new()
+                .with_max_dependencies(2)
+                .with_extraction_order(order)
+                .extract_context_for_target(ChangeTarget::Type(a.clone()), &source_files)
+                .unwrap();
+            assert_eq!(context.omitted_dependencies, 1);
+            let mut names: Vec<_> = context
+                .related_types
+                .iter()
+                .map(|t| t.name.clone())
+                .collect();
+            names.sort_unstable();
+            names
+        };
+
+        assert_eq!(
+            SemanticContextExtractor::new().get_extraction_order(),
+            ExtractionOrder::DepthFirst
+        );
+        // 广度优先先收集完第一层的 B 和 C
+        assert_eq!(kept_types(ExtractionOrder::BreadthFirst), vec!["B", "C"]);
+        // 深度优先沿 B 深入到 D，同样的名额容不下 C
+        assert_eq!(kept_types(ExtractionOrder::DepthFirst), vec!["B", "D"]);
+    }
+
+    #[test]
+    fn test_unqualified_imports_always_kept() {
+        let mut go_info = GoLanguageInfo::new("test".to_string());
+        for (path, alias, kind) in [
+            ("fmt", None, ImportKind::Normal),
+            ("strings", None, ImportKind::Dot),
+            ("github.com/lib/pq", None, ImportKind::Blank),
+            ("os", Some("sys"), ImportKind::Normal),
+        ] {
+            go_info.add_import(Import {
+                path: path.to_string(),
+                alias: alias.map(str::to_string),
+                kind,
+            });
+        }
+        let mut source_file = create_test_source_file("test", Vec::new());
+        source_file.language_specific = Box::new(go_info);
+        let source_files = vec![source_file];
+
+        // 函数体只限定引用了 fmt，点导入和空白导入没有任何包名引用
+        let function = create_test_function("handle", "fmt.Println(ToUpper(name))");
+        let context = SemanticContextExtractor::new()
+            .extract_context(&function, &source_files)
+            .unwrap();
+
+        let mut paths: Vec<&str> = context.imports.iter().map(|i| i.path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, vec!["fmt", "github.com/lib/pq", "strings"]);
+
+        // 清理未引用的定义时也不会移除它们
+        let mut pruned = context.clone();
+        SemanticContextExtractor::new().prune_unreferenced(&mut pruned);
+        assert!(pruned.imports.iter().any(|i| i.kind == ImportKind::Dot));
+        assert!(pruned.imports.iter().any(|i| i.kind == ImportKind::Blank));
+    }
+
+    #[test]
+    fn test_versioned_imports_kept() {
+        let mut go_info = GoLanguageInfo::new("test".to_string());
+        for path in ["github.com/foo/bar/v2", "gopkg.in/yaml.v3"] {
+            go_info.add_import(Import {
+                path: path.to_string(),
+                alias: None,
+                kind: ImportKind::Normal,
+            });
+        }
+        let mut helper = create_test_function("helper", "return nil");
+        helper.file_path = PathBuf::from("helper.go");
+        go_info.add_declaration(Box::new(GoDeclaration::Function(helper)));
+        let mut source_file = create_test_source_file("test", Vec::new());
+        source_file.language_specific = Box::new(go_info);
+        let source_files = vec![source_file];
+
+        // 包名分别是 bar 和 yaml，而不是导入路径的最后一段 v2 和 yaml.v3
+        let function = create_test_function("handle", "return bar.Do(yaml.Marshal(helper()))");
+        let expected = vec!["github.com/foo/bar/v2", "gopkg.in/yaml.v3"];
+
+        let context = SemanticContextExtractor::new()
+            .extract_context(&function, &source_files)
+            .unwrap();
+        let paths: Vec<&str> = context.imports.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, expected);
+
+        // 文件上限截断 helper 后会清理未引用的定义，两个导入仍被识别为已引用
+        let truncated = SemanticContextExtractor::new()
+            .with_max_involved_files(1)
+            .extract_context(&function, &source_files)
+            .unwrap();
+        assert!(truncated.is_truncated());
+        let paths: Vec<&str> = truncated.imports.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn test_exclude_patterns_skip_dependency_definitions() {
+        let mut config_type =
+            create_test_type("Config", "type Config struct {\n    Name string\n}");
+        config_type.file_path = PathBuf::from("/repo/gen/config.go");
+
+        let mut helper = create_test_function("helper", "return nil");
+        helper.file_path = PathBuf::from("/repo/helper.go");
+
+        let source_files = vec![create_test_source_file(
+            "test",
+            vec![
+                GoDeclaration::Type(config_type),
+                GoDeclaration::Function(helper),
+            ],
+        )];
+
+        let mut function = create_test_function("handle", "cfg := Config{}\nreturn helper()");
+        function.file_path = PathBuf::from("/repo/main.go");
+
+        let unfiltered = SemanticContextExtractor::new()
+            .extract_context(&function, &source_files)
+            .unwrap();
+        assert_eq!(unfiltered.related_types.len(), 1);
+
+        // gen/ 下的定义不加入上下文，也不记为因文件上限被省略
+        let excludes = ExcludePatterns::new(["gen/**"]).unwrap();
+        let context = SemanticContextExtractor::new()
+            .with_exclude_patterns(excludes, "/repo")
+            .extract_context(&function, &source_files)
+            .unwrap();
+        assert!(context.related_types.is_empty());
+        assert_eq!(context.dependent_functions.len(), 1);
+        assert!(context.omitted_files.is_empty());
+    }
+
+    #[test]
+    fn test_prune_types_of_truncated_functions() {
+        // helper 是 Config 的唯一使用者，且位于单独的文件中
+        let mut config_type =
+            create_test_type("Config", "type Config struct {\n    Name string\n}");
+        config_type.file_path = PathBuf::from("config.go");
+
+        let mut helper = create_test_function("helper", "return cfg.Name");
+        helper.file_path = PathBuf::from("helper.go");
+        helper.parameters = vec![GoParameter {
+            name: "cfg".to_string(),
+            param_type: GoType {
+                name: "Config".to_string(),
+                is_pointer: true,
+                is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
+            },
+            is_variadic: false,
+        }];
+
+        let source_files = vec![create_test_source_file(
+            "test",
+            vec![
+                GoDeclaration::Type(config_type),
+                GoDeclaration::Function(helper),
+            ],
+        )];
+
+        let mut function = create_test_function("handle", "return helper(nil)");
+        function.file_path = PathBuf::from("main.go");
+
+        // 不设上限时 Config 经由 helper 被引用，应当保留
+        let unbounded = SemanticContextExtractor::new()
+            .extract_context(&function, &source_files)
+            .unwrap();
+        assert_eq!(unbounded.dependent_functions.len(), 1);
+        assert_eq!(unbounded.related_types.len(), 1);
+
+        // 文件上限截断了 helper，Config 失去唯一的引用者后也应被清理
+        let context = SemanticContextExtractor::new()
+            .with_max_involved_files(2)
+            .extract_context(&function, &source_files)
+            .unwrap();
+        assert!(context.dependent_functions.is_empty());
+        assert!(context.related_types.is_empty());
+        assert!(context.omitted_files.contains(&PathBuf::from("helper.go")));
+    }
+
+    #[test]
+    fn test_large_body_falls_back_to_cst_detection() {
+        let extractor = SemanticContextExtractor::new();
+        let cst_extractor = SemanticContextExtractor::new().with_max_regex_scan_bytes(0);
+
+        // 小函数体上正则路径和 CST 路径的结果一致
+        let small = create_test_function(
+            "build",
+            "cfg := Config{Name: name}\nreturn process(cfg, MaxRetries, GlobalLimit)",
+        );
+        for type_name in ["Config", "Missing"] {
+            assert_eq!(
+                extractor.function_uses_type(&small, type_name),
+                cst_extractor.function_uses_type(&small, type_name)
+            );
+        }
+        assert!(extractor.function_uses_constant(&small, "MaxRetries"));
+        assert!(cst_extractor.function_uses_constant(&small, "MaxRetries"));
+        assert!(extractor.function_uses_variable(&small, "GlobalLimit"));
+        assert!(cst_extractor.function_uses_variable(&small, "GlobalLimit"));
+
+        // 超过阈值的函数体走 CST 检测
+        let mut body = String::new();
+        for i in 0..20_000 {
+            body.push_str(&format!("v{i} := compute({i})\n"));
+        }
+        body.push_str("cfg := Config{}\n_ = cfg\n_ = GlobalLimit\n");
+        let large = create_test_function("huge", &body);
+        assert!(large.body.len() > extractor.get_max_regex_scan_bytes());
+
+        let start = Instant::now();
+        assert!(extractor.function_uses_type(&large, "Config"));
+        assert!(!extractor.function_uses_type(&large, "Conf"));
+        assert!(extractor.function_uses_variable(&large, "GlobalLimit"));
+        assert!(!extractor.function_uses_variable(&large, "v19999"));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_large_body_fallback_uses_collected_references() {
+        let extractor = SemanticContextExtractor::new().with_max_regex_scan_bytes(0);
+        let function = create_test_function(
+            "render",
+            "page := Page{Size: maxSize}\nreturn Render(page, Limit(minSize))",
+        );
+
+        assert!(extractor.function_uses_type(&function, "Page"));
+        assert!(extractor.function_uses_type(&function, "Limit"));
+        assert!(!extractor.function_uses_type(&function, "Pages"));
+        assert!(extractor.function_uses_constant(&function, "maxSize"));
+        assert!(extractor.function_uses_constant(&function, "minSize"));
+        assert!(!extractor.function_uses_constant(&function, "midSize"));
+
+        // 超大函数体只查询已经收集的引用汇总，不会重新解析函数体
+        let mut without_references = function.clone();
+        without_references.body_references = Default::default();
+        assert!(!extractor.function_uses_type(&without_references, "Page"));
+        assert!(!extractor.function_uses_constant(&without_references, "maxSize"));
+    }
+
+    #[test]
+    fn test_function_uses_type_ignores_strings_and_comments() {
+        let extractor = SemanticContextExtractor::new();
+        let mut function = create_test_function(
+            "sum",
+            "// Config is loaded elsewhere\nlog.Print(\"Config missing\")\nreturn total",
+        );
+        function.type_parameters = vec![GoTypeParam {
+            name: "T".to_string(),
+            constraint: "Number | ~string".to_string(),
+        }];
+
+        assert!(!extractor.function_uses_type(&function, "Config"));
+        assert!(extractor.function_uses_type(&function, "Number"));
+        assert!(!extractor.function_uses_type(&function, "Num"));
+    }
+
+    #[test]
+    fn test_function_uses_variable_respects_local_shadows() {
+        let extractor = SemanticContextExtractor::new();
+
+        // 局部声明遮蔽了包级变量
+        let shadow = create_test_function("load", "config := loadConfig()\nreturn config.Port");
+        assert!(!extractor.function_uses_variable(&shadow, "config"));
+
+        let var_shadow = create_test_function("reset", "var config Config\nreturn config");
+        assert!(!extractor.function_uses_variable(&var_shadow, "config"));
+
+        // 声明之前的读取和声明右侧仍然引用包级变量
+        let self_init = create_test_function("copyConfig", "config := config\nreturn config");
+        assert!(extractor.function_uses_variable(&self_init, "config"));
+
+        // 内层作用域的遮蔽不影响外层的读取
+        let inner_shadow = create_test_function(
+            "inner",
+            "if ok {\n    config := Config{}\n    _ = config\n}\nreturn config.Port",
+        );
+        assert!(extractor.function_uses_variable(&inner_shadow, "config"));
+
+        // 真正读取包级变量
+        let global_read = create_test_function("port", "return config.Port");
+        assert!(extractor.function_uses_variable(&global_read, "config"));
+
+        // 取地址
+        let address_of = create_test_function("update", "return apply(&config)");
+        assert!(extractor.function_uses_variable(&address_of, "config"));
+
+        // 参数遮蔽了包级变量
+        let mut param_shadow = create_test_function("withConfig", "return config.Port");
+        param_shadow.parameters.push(GoParameter {
+            name: "config".to_string(),
+            param_type: GoType::from_type_text("*Config"),
+            is_variadic: false,
+        });
+        assert!(!extractor.function_uses_variable(&param_shadow, "config"));
+    }
+
+    #[test]
+    fn test_body_references_collected_at_extraction() {
+        let file_path = PathBuf::from("render.go");
+        let source = r#"package render
+
+func render() string {
+    page := Page{Size: maxSize}
+    return template.Render(page, config)
+}
+"#;
+        let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_file = analyzer
+            .analyze_source(&file_path, source.to_string())
+            .unwrap();
+        let function = source_file
+            .language_specific
+            .declarations()
+            .iter()
+            .find_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Function(f)) => Some(f.clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        let extractor = SemanticContextExtractor::new();
+        assert!(extractor.function_uses_variable(&function, "config"));
+        assert!(extractor.function_uses_variable(&function, "template"));
+        assert!(!extractor.function_uses_variable(&function, "page"));
+        // 函数体中没有出现的名称只做文本检查就能排除
+        assert!(!extractor.function_uses_variable(&function, "settings"));
+
+        // 查询只使用提取时收集的引用，不会重新解析函数体
+        let mut without_references = function.clone();
+        without_references.body_references = Default::default();
+        assert!(!extractor.function_uses_variable(&without_references, "config"));
+    }
+
+    #[test]
+    fn test_functions_using_qualified_constant() {
+        let files = [
+            (
+                "app/models/config.go",
+                r#"package models
+
+const DefaultHost = "localhost"
+
+func localDefault() string {
+    return DefaultHost
+}
+"#,
+            ),
+            (
+                "app/services/server.go",
+                r#"package services
+
+import m "example.com/app/models"
+
+func address() string {
+    return m.DefaultHost + ":80"
+}
+
+func fieldOnly(cfg Config) string {
+    return cfg.DefaultHost
+}
+
+func unqualified() string {
+    return DefaultHost
+}
+"#,
+            ),
+        ];
+        let source_files: Vec<SourceFile> = files
+            .iter()
+            .map(|(path, source)| {
+                let file_path = PathBuf::from(path);
+                let mut analyzer =
+                    crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+                analyzer
+                    .analyze_source(&file_path, source.to_string())
+                    .unwrap()
+            })
+            .collect();
+        let constant = source_files[0]
+            .language_specific
+            .declarations()
+            .iter()
+            .find_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Constant(c)) => Some(c.clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        let extractor = SemanticContextExtractor::new();
+        let mut names: Vec<String> = extractor
+            .find_functions_using_constant(&constant, &source_files)
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        names.sort();
+
+        // 同包的未限定引用和别名限定引用都算使用；字段访问和其他包的未限定同名标识符不算
+        assert_eq!(names, vec!["address", "localDefault"]);
+    }
+
+    #[test]
+    fn test_type_switch_targets_are_dependencies() {
+        let file_path = PathBuf::from("error_handling.go");
+        let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_file = analyzer
+            .analyze_source(
+                &file_path,
+                r#"package app
+
+type AppError struct {
+    Code int
+}
+
+type NotFoundError struct {
+    Resource string
+}
+
+type ValidationError struct {
+    Field string
+}
+
+type RetryableError interface {
+    Retry() bool
+}
+
+func Classify(err error) int {
+    if _, ok := err.(RetryableError); ok {
+        return 503
+    }
+    switch e := err.(type) {
+    case *AppError:
+        return e.Code
+    case NotFoundError:
+        return 404
+    case *ValidationError:
+        return 400
+    default:
+        return 500
+    }
+}
+"#
+                .to_string(),
+            )
+            .unwrap();
+        let source_files = vec![source_file];
+
+        let resolver = DependencyResolver::new();
+        let index = resolver.build_index(&source_files);
+        let classify = index.find_function("Classify").unwrap();
+
+        // 类型分支中的三个具体类型和类型断言中的接口都是依赖
+        let dependencies: Vec<String> = resolver
+            .extract_function_dependencies(classify, &index)
+            .into_iter()
+            .filter(|dependency| dependency.dependency_type == DependencyType::Type)
+            .map(|dependency| dependency.name)
+            .collect();
+        for type_name in [
+            "AppError",
+            "NotFoundError",
+            "ValidationError",
+            "RetryableError",
+        ] {
+            assert!(
+                dependencies.contains(&type_name.to_string()),
+                "{type_name} missing from {dependencies:?}"
+            );
+        }
+
+        let context = SemanticContextExtractor::new()
+            .extract_context(classify, &source_files)
+            .unwrap();
+        let mut types: Vec<&str> = context
+            .related_types
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        types.sort();
+        assert_eq!(
+            types,
+            vec![
+                "AppError",
+                "NotFoundError",
+                "RetryableError",
+                "ValidationError"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_promoted_method_pulled_into_context() {
+        let file_path = PathBuf::from("accounts.go");
+        let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_file = analyzer
+            .analyze_source(
+                &file_path,
+                r#"package accounts
+
+type Base struct {
+    ID int
+}
+
+func (b *Base) Touch() {
+    b.ID++
+}
+
+type User struct {
+    Base
+    Name string
+}
+
+func (u *User) Save() error {
+    return nil
+}
+
+type Admin struct {
+    *User
+    Level int
+}
+
+func Promote(admin *Admin) error {
+    admin.Level++
+    admin.Touch()
+    return admin.Save()
+}
+"#
+                .to_string(),
+            )
+            .unwrap();
+        let source_files = vec![source_file];
+
+        let promote = DependencyResolver::new()
+            .find_function_definition("Promote", &source_files)
+            .unwrap();
+        let context = SemanticContextExtractor::new()
+            .extract_context(&promote, &source_files)
+            .unwrap();
+
+        // Save 由嵌入的 User 提升，Touch 经 User 再由 Base 提升
+        let functions: Vec<&str> = context
+            .dependent_functions
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect();
+        assert!(functions.contains(&"Save"));
+        assert!(functions.contains(&"Touch"));
+        let save = context
+            .dependent_functions
+            .iter()
+            .find(|f| f.name == "Save")
+            .unwrap();
+        assert_eq!(save.receiver.as_ref().unwrap().type_name, "User");
+    }
+
+    #[test]
+    fn test_embedded_struct_fields_are_dependencies() {
+        let file_path = PathBuf::from("models.go");
+        let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_file = analyzer
+            .analyze_source(
+                &file_path,
+                r#"package main
+
+type User struct {
+    Name string
+}
+
+func (u *User) Greet() string {
+    return u.Name
+}
+
+type Admin struct { User; Level int }
+
+type Owner struct {
+    *User
+}
+
+func promote(a Admin) string {
+    return a.Greet()
+}
+"#
+                .to_string(),
+            )
+            .unwrap();
+
+        let find_type = |name: &str| {
+            source_file
+                .language_specific
+                .declarations()
+                .iter()
+                .find_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                    Some(GoDeclaration::Type(t)) if t.name == name => Some(t.clone()),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let admin = find_type("Admin");
+        let owner = find_type("Owner");
+
+        // 单行写法的嵌入字段和指针嵌入都被识别为依赖
+        assert_eq!(admin.dependencies, vec!["User".to_string()]);
+        assert_eq!(owner.dependencies, vec!["User".to_string()]);
+
+        let extractor = SemanticContextExtractor::new();
+        assert!(
+            extractor
+                .extract_type_dependencies(&admin)
+                .contains(&"User".to_string())
+        );
+
+        let source_files = vec![source_file];
+        for target in [admin, owner] {
+            let context = extractor
+                .extract_context_for_target(ChangeTarget::Type(target), &source_files)
+                .unwrap();
+            assert!(context.related_types.iter().any(|t| t.name == "User"));
+        }
+
+        // 通过 Admin 调用提升方法的函数也视为使用了 User
+        let users: Vec<String> = extractor
+            .find_functions_using_type("User", &source_files)
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        assert!(users.contains(&"promote".to_string()));
+        assert!(users.contains(&"Greet".to_string()));
+    }
+
+    #[test]
+    fn test_map_field_types_are_dependencies() {
+        let file_path = PathBuf::from("registry.go");
+        let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_file = analyzer
+            .analyze_source(
+                &file_path,
+                r#"package main
+
+type Registry struct {
+    users  map[int]*models.User
+    groups map[string]map[int]Group
+    byKey  map[Key]string
+}
+
+func lookup(groups map[string]map[int]Group) {
+}
+"#
+                .to_string(),
+            )
+            .unwrap();
+
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .unwrap();
+        let registry = go_info.find_type("Registry").unwrap().clone();
+
+        let extractor = SemanticContextExtractor::new();
+        let dependencies = extractor.extract_type_dependencies(&registry);
+        assert!(dependencies.contains(&"models.User".to_string()));
+        assert!(dependencies.contains(&"Group".to_string()));
+        assert!(dependencies.contains(&"Key".to_string()));
+
+        // 嵌套映射的值类型也能通过结构化信息匹配
+        let lookup = go_info.find_function("lookup").unwrap();
+        assert!(extractor.type_matches(&lookup.parameters[0].param_type, "Group"));
+        assert!(!extractor.type_matches(&lookup.parameters[0].param_type, "Grou"));
+    }
+
+    #[test]
+    fn test_array_length_constants_are_dependencies() {
+        let file_path = PathBuf::from("buffer.go");
+        let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_file = analyzer
+            .analyze_source(
+                &file_path,
+                r#"package main
+
+const MaxSize = 8
+
+type User struct {
+    Name string
+}
+
+type Buffer struct {
+    users  [MaxSize]User
+    digest [3]int
+    grid   [2][2]float64
+}
+"#
+                .to_string(),
+            )
+            .unwrap();
+
+        let go_info = source_file
+            .language_specific
+            .as_any()
+            .downcast_ref::<crate::parser::GoLanguageInfo>()
+            .unwrap();
+        let buffer = go_info.find_type("Buffer").unwrap().clone();
+
+        let extractor = SemanticContextExtractor::new();
+        assert_eq!(
+            extractor.extract_array_length_constants(&buffer),
+            vec!["MaxSize".to_string()]
+        );
+        assert!(
+            extractor
+                .extract_type_dependencies(&buffer)
+                .contains(&"User".to_string())
+        );
+
+        let source_files = vec![source_file];
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Type(buffer), &source_files)
+            .unwrap();
+        assert!(context.constants.iter().any(|c| c.name == "MaxSize"));
+        assert!(context.related_types.iter().any(|t| t.name == "User"));
+    }
+
+    #[test]
+    fn test_qualified_embedded_type_resolution() {
+        let parse = |path: &str, source: &str| {
+            let file_path = PathBuf::from(path);
+            let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+            analyzer
+                .analyze_source(&file_path, source.to_string())
+                .unwrap()
+        };
+
+        // 另一个包中的同名类型排在前面，按名称查找会匹配到它
+        let legacy = parse(
+            "legacy/base.go",
+            "package legacy\n\ntype Base struct {\n    Legacy string\n}\n",
+        );
+        let models = parse(
+            "models/base.go",
+            "package models\n\ntype Base struct {\n    ID int\n}\n",
+        );
+        let handler = parse(
+            "handler/user.go",
+            r#"package handler
+
+import "example.com/app/models"
+
+type User struct {
+    models.Base
+    Name string
+}
+"#,
+        );
+
+        let user = handler
+            .language_specific
+            .declarations()
+            .iter()
+            .find_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Type(t)) if t.name == "User" => Some(t.clone()),
+                _ => None,
+            })
+            .unwrap();
+
+        let extractor = SemanticContextExtractor::new();
+        assert!(
+            extractor
+                .extract_type_dependencies(&user)
+                .contains(&"models.Base".to_string())
+        );
+
+        let source_files = vec![legacy, models, handler];
+        let extractor =
+            SemanticContextExtractor::new_with_project_path("example.com/app".to_string());
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Type(user), &source_files)
+            .unwrap();
+
+        let base: Vec<_> = context
+            .related_types
+            .iter()
+            .filter(|t| t.name == "Base")
+            .collect();
+        assert_eq!(base.len(), 1);
+        assert_eq!(base[0].file_path, PathBuf::from("models/base.go"));
+        assert!(
+            context
+                .imports
+                .iter()
+                .any(|import| import.path == "example.com/app/models")
+        );
+    }
+
+    #[test]
+    fn test_constant_group_siblings() {
+        let source = r#"package errors
+
+type ErrorCode int
+
+const (
+    ErrOK ErrorCode = iota
+    ErrNotFound
+    ErrTimeout
+)
+
+const MaxRetries = 3
+"#;
+
+        let file_path = PathBuf::from("errors.go");
+        let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path).unwrap();
+        let source_file = analyzer
+            .analyze_source(&file_path, source.to_string())
+            .unwrap();
+
+        let find_constant = |name: &str| {
+            source_file
+                .language_specific
+                .declarations()
+                .iter()
+                .find_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                    Some(GoDeclaration::Constant(c)) if c.name == name => Some(c.clone()),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let changed = find_constant("ErrNotFound");
+        let standalone = find_constant("MaxRetries");
+        let source_files = std::slice::from_ref(&source_file);
+
+        // 默认只包含变更的常量本身
+        let extractor = SemanticContextExtractor::new();
+        assert!(!extractor.includes_group_siblings());
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Constant(changed.clone()), source_files)
+            .unwrap();
+        assert!(context.constants.is_empty());
+
+        // 开启后整个 ErrorCode 分组都进入上下文
+        let extractor = SemanticContextExtractor::new().with_group_siblings(true);
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Constant(changed), source_files)
+            .unwrap();
+        let mut names: Vec<_> = context.constants.iter().map(|c| c.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["ErrOK", "ErrTimeout"]);
+
+        // 单独声明的常量没有分组成员
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Constant(standalone), source_files)
+            .unwrap();
+        assert!(context.constants.is_empty());
+    }
+
+    #[test]
+    fn test_untested_exported_function() {
+        let process = create_test_function("Process", "return nil");
+        let validate = create_test_function("Validate", "return nil");
+        let helper = create_test_function("helper", "return nil");
+        let test_validate = create_test_function("TestValidate_EmptyInput", "return");
+
+        let mut test_file =
+            create_test_source_file("main", vec![GoDeclaration::Function(test_validate)]);
+        test_file.path = PathBuf::from("main_test.go");
+        let source_files = vec![
+            create_test_source_file(
+                "main",
+                vec![
+                    GoDeclaration::Function(process.clone()),
+                    GoDeclaration::Function(validate.clone()),
+                    GoDeclaration::Function(helper.clone()),
+                ],
+            ),
+            test_file,
+        ];
+
+        // 默认不做检查
+        let context = SemanticContextExtractor::new()
+            .extract_context_for_target(ChangeTarget::Function(process.clone()), &source_files)
+            .unwrap();
+        assert!(!context.untested);
+
+        let extractor = SemanticContextExtractor::new().with_test_coverage_check(true);
+
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Function(process), &source_files)
+            .unwrap();
+        assert!(context.untested, "Process has no TestProcess");
+
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Function(validate), &source_files)
+            .unwrap();
+        assert!(!context.untested, "TestValidate_EmptyInput covers Validate");
+
+        // 未导出的函数不参与检查
+        let context = extractor
+            .extract_context_for_target(ChangeTarget::Function(helper), &source_files)
+            .unwrap();
+        assert!(!context.untested);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_semantic_context_json_round_trip() {
+        let mut function = create_test_function("Handle", "return nil");
+        function.receiver = Some(crate::parser::GoReceiverInfo {
+            name: "s".to_string(),
+            type_name: "Server".to_string(),
+            is_pointer: true,
+        });
+
+        let mut context = SemanticContext::from_function(function);
+        context.add_type(create_test_type("Server", "type Server struct {}"));
+        context.add_function(create_test_function("helper", "return 1"));
+        context.add_constant(GoConstantDefinition {
+            name: "MaxConn".to_string(),
+            value: "10".to_string(),
+            const_type: None,
+            start_line: 3,
+            end_line: 3,
+            file_path: PathBuf::from("server.go"),
+            byte_start: 0,
+            byte_end: 0,
+            iota_index: None,
+            doc_comment: None,
+        });
+        context.add_variable(GoVariableDefinition {
+            name: "defaultServer".to_string(),
+            var_type: Some(GoType {
+                name: "Server".to_string(),
+                is_pointer: true,
+                is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
+            }),
+            initial_value: None,
+            start_line: 5,
+            end_line: 5,
+            file_path: PathBuf::from("server.go"),
+            byte_start: 0,
+            byte_end: 0,
+            doc_comment: None,
+        });
+        context.add_import(Import {
+            path: "net/http".to_string(),
+            alias: None,
+            kind: ImportKind::Normal,
+        });
+        context
+            .cross_module_dependencies
+            .insert("net/http".to_string(), vec!["Handler".to_string()]);
+        context.untested = true;
+
+        let json = context.to_json(true).unwrap();
+        assert!(json.contains("\"target\": \"function\""));
+        assert!(json.contains("\"dependency_graph\""));
+
+        let restored = SemanticContext::from_json(&json).unwrap();
+        assert_eq!(restored.change_target.name(), "Handle");
+        let restored_function = restored.change_target.as_function().unwrap();
+        assert_eq!(
+            restored_function
+                .receiver
+                .as_ref()
+                .map(|r| r.type_name.as_str()),
+            Some("Server")
+        );
+        assert_eq!(restored.related_types.len(), 1);
+        assert!(matches!(restored.related_types[0].kind, GoTypeKind::Struct));
+        assert_eq!(restored.dependent_functions[0].name, "helper");
+        assert_eq!(restored.constants[0].name, "MaxConn");
+        assert_eq!(
+            restored.variables[0]
+                .var_type
+                .as_ref()
+                .map(|t| t.is_pointer),
+            Some(true)
+        );
+        assert_eq!(restored.imports, context.imports);
+        assert_eq!(
+            restored.cross_module_dependencies,
+            context.cross_module_dependencies
+        );
+        assert!(restored.untested);
+        assert_eq!(restored.get_stats(), context.get_stats());
+
+        // 重命名目标同样可以往返
+        let renamed = SemanticContext::new(ChangeTarget::Renamed {
+            old_name: "computeTotal".to_string(),
+            new_name: "sumItems".to_string(),
+            function: create_test_function("sumItems", "return 0"),
+        });
+        let restored = SemanticContext::from_json(&renamed.to_json(false).unwrap()).unwrap();
+        assert!(matches!(
+            restored.change_target,
+            ChangeTarget::Renamed { ref old_name, .. } if old_name == "computeTotal"
+        ));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_to_json_dependency_graph_toggle() {
+        let mut context =
+            SemanticContext::from_function(create_test_function("Handle", "return nil"));
+        context.add_type(create_test_type("Server", "type Server struct {}"));
+
+        let with_graph: serde_json::Value =
+            serde_json::from_str(&context.to_json(true).unwrap()).unwrap();
+        assert!(with_graph["dependency_graph"]["nodes"].is_array());
+
+        let without_graph: serde_json::Value =
+            serde_json::from_str(&context.to_json(false).unwrap()).unwrap();
+        assert!(without_graph.get("dependency_graph").is_none());
+        assert_eq!(without_graph["related_types"][0]["name"], "Server");
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_contexts_to_msgpack_dependency_graph_toggle() {
+        let contexts = vec![SemanticContext::from_function(create_test_function(
+            "Handle",
+            "return nil",
+        ))];
+
+        let with_graph: Vec<serde_json::Value> =
+            rmp_serde::from_slice(&SemanticContext::contexts_to_msgpack(&contexts, true).unwrap())
+                .unwrap();
+        assert!(with_graph[0]["dependency_graph"]["nodes"].is_array());
+
+        let without_graph: Vec<serde_json::Value> =
+            rmp_serde::from_slice(&SemanticContext::contexts_to_msgpack(&contexts, false).unwrap())
+                .unwrap();
+        assert!(without_graph[0].get("dependency_graph").is_none());
+
+        // 附带的依赖图不影响反序列化
+        let bytes = SemanticContext::contexts_to_msgpack(&contexts, true).unwrap();
+        let restored = SemanticContext::contexts_from_msgpack(&bytes).unwrap();
+        assert_eq!(restored[0].change_target.name(), "Handle");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_dependency_graph_json_round_trip() {
+        let mut context =
+            SemanticContext::from_function(create_test_function("Handle", "return nil"));
+        context.add_type(create_test_type("Server", "type Server struct {}"));
+        context.add_function(create_test_function("helper", "return 1"));
+        let graph = context.generate_dependency_graph();
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let restored: DependencyGraph = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(restored.root_node, graph.root_node);
+        assert_eq!(restored.nodes, graph.nodes);
+        assert_eq!(restored.edges, graph.edges);
+        assert!(
+            restored
+                .nodes
+                .iter()
+                .any(|node| node.file_path == Some(PathBuf::from("test.go")))
+        );
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_semantic_contexts_msgpack_round_trip() {
+        let mut first =
+            SemanticContext::from_function(create_test_function("Handle", "return nil"));
+        first.add_type(create_test_type("Server", "type Server struct {}"));
+        first.add_import(Import {
+            path: "net/http".to_string(),
+            alias: Some("h".to_string()),
+            kind: ImportKind::Normal,
+        });
+        first.untested = true;
+
+        let mut second = SemanticContext::new(ChangeTarget::Renamed {
+            old_name: "computeTotal".to_string(),
+            new_name: "sumItems".to_string(),
+            function: create_test_function("sumItems", "return 0"),
+        });
+        second.add_function(create_test_function("helper", "return 1"));
+        second.add_constant(GoConstantDefinition {
+            name: "MaxConn".to_string(),
+            value: "10".to_string(),
+            const_type: None,
+            start_line: 3,
+            end_line: 3,
+            file_path: PathBuf::from("server.go"),
+            byte_start: 0,
+            byte_end: 0,
+            iota_index: None,
+            doc_comment: None,
+        });
+
+        let contexts = vec![first, second];
+        let bytes = SemanticContext::contexts_to_msgpack(&contexts, false).unwrap();
+        // 二进制格式应当比格式化的 JSON 更紧凑
+        let json_len: usize = contexts
+            .iter()
+            .map(|c| c.to_json(false).unwrap().len())
+            .sum();
+        assert!(bytes.len() < json_len);
+
+        let restored = SemanticContext::contexts_from_msgpack(&bytes).unwrap();
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored[0].change_target.name(), "Handle");
+        assert_eq!(restored[0].related_types[0].name, "Server");
+        assert_eq!(restored[0].imports, contexts[0].imports);
+        assert!(restored[0].untested);
+        assert!(matches!(
+            restored[1].change_target,
+            ChangeTarget::Renamed { ref old_name, .. } if old_name == "computeTotal"
+        ));
+        assert_eq!(restored[1].dependent_functions[0].name, "helper");
+        assert_eq!(restored[1].constants[0].name, "MaxConn");
+        for (original, restored) in contexts.iter().zip(&restored) {
+            assert_eq!(restored.get_stats(), original.get_stats());
+        }
+
+        // 损坏的输入返回序列化错误
+        assert!(matches!(
+            SemanticContext::contexts_from_msgpack(&bytes[..bytes.len() / 2]),
+            Err(SemanticDiffError::SerializationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_variable_initializer_dependencies() {
+        let extractor = SemanticContextExtractor::new();
+
+        let config_type = create_test_type(
+            "Config",
+            "type Config struct {\n    Name string\n    Limits Limits\n}",
+        );
+        let limits_type = create_test_type("Limits", "type Limits struct {\n    Max int\n}");
+        let global_config = GoVariableDefinition {
+            name: "GlobalConfig".to_string(),
+            var_type: None,
+            initial_value: Some(r#"&Config{Name: "default"}"#.to_string()),
+            start_line: 3,
+            end_line: 3,
+            file_path: PathBuf::from("test.go"),
+            byte_start: 0,
+            byte_end: 0,
+            doc_comment: None,
+        };
+        let function = create_test_function("Describe", "return GlobalConfig.Name");
+
+        let source_file = create_test_source_file(
+            "main",
+            vec![
+                GoDeclaration::Type(config_type),
+                GoDeclaration::Type(limits_type),
+                GoDeclaration::Variable(global_config),
+                GoDeclaration::Function(function.clone()),
+            ],
+        );
+
+        let context = extractor
+            .extract_context(&function, &[source_file])
+            .unwrap();
+
+        let variable_names: Vec<_> = context.variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(variable_names, vec!["GlobalConfig"]);
+
+        // Config 只通过 GlobalConfig 的初始值被引用，其字段类型 Limits 也会被递归提取
+        let type_names: HashSet<_> = context
+            .related_types
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        assert!(
+            type_names.contains("Config"),
+            "Config should come from the initializer"
+        );
+        assert!(type_names.contains("Limits"));
+    }
+
+    #[test]
+    fn test_variable_initializer_qualified_dependencies() {
+        let extractor = SemanticContextExtractor::new();
+
+        let limits_type = create_test_type("Limits", "type Limits struct {\n    Max int\n}");
+        let new_limits = create_test_function("NewLimits", "return l");
+        let fallback = GoVariableDefinition {
+            name: "Fallback".to_string(),
+            var_type: None,
+            initial_value: Some("settings.NewLimits(settings.Limits{Max: 1})".to_string()),
+            start_line: 3,
+            end_line: 3,
+            file_path: PathBuf::from("test.go"),
+            byte_start: 0,
+            byte_end: 0,
+            doc_comment: None,
+        };
+        let function = create_test_function("Describe", "return Fallback.Max");
+        let source_file = create_test_source_file(
+            "main",
+            vec![
+                GoDeclaration::Type(limits_type),
+                GoDeclaration::Function(new_limits),
+                GoDeclaration::Variable(fallback),
+                GoDeclaration::Function(function.clone()),
+            ],
+        );
+
+        let context = extractor
+            .extract_context(&function, &[source_file])
+            .unwrap();
+
+        // 带包限定的复合字面量和调用也要按未限定名解析
+        assert!(context.related_types.iter().any(|t| t.name == "Limits"));
+        assert!(
+            context
+                .dependent_functions
+                .iter()
+                .any(|f| f.name == "NewLimits")
+        );
+    }
+
     #[test]
     fn test_function_signature_dependencies() {
         // 测试函数签名依赖提取
@@ -3072,7 +6789,14 @@ mod tests {
                         name: "User".to_string(),
                         is_pointer: false,
                         is_slice: false,
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
                     },
+                    is_variadic: false,
                 },
                 GoParameter {
                     name: "configs".to_string(),
@@ -3080,7 +6804,14 @@ mod tests {
                         name: "Config".to_string(),
                         is_pointer: false,
                         is_slice: true,
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
                     },
+                    is_variadic: false,
                 },
             ],
             return_types: vec![
@@ -3088,17 +6819,34 @@ mod tests {
                     name: "Result".to_string(),
                     is_pointer: true,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
                 GoType {
                     name: "error".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
             ],
             body: "return &Result{}, nil".to_string(),
             start_line: 1,
             end_line: 3,
             file_path: PathBuf::from("service.go"),
+            body_references: Arc::new(BodyReferences::from_body("return &Result{}, nil")),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         };
 
         // 创建相关的类型定义
@@ -3151,6 +6899,12 @@ mod tests {
             name: "User".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         };
         assert!(extractor.type_matches(&simple_type, "User"));
         assert!(!extractor.type_matches(&simple_type, "Config"));
@@ -3160,6 +6914,12 @@ mod tests {
             name: "User".to_string(),
             is_pointer: true,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         };
         assert!(extractor.type_matches(&pointer_type, "User"));
 
@@ -3168,6 +6928,12 @@ mod tests {
             name: "User".to_string(),
             is_pointer: false,
             is_slice: true,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         };
         assert!(extractor.type_matches(&slice_type, "User"));
 
@@ -3176,6 +6942,12 @@ mod tests {
             name: "map[string]User".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         };
         assert!(extractor.type_matches(&map_type, "User"));
         assert!(!extractor.type_matches(&map_type, "Config"));
@@ -3,11 +3,33 @@
 //! 提供多种输出格式支持和语法高亮功能
 
 use crate::error::{Result, SemanticDiffError};
-use crate::generator::{CodeSlice, HighlightStyle, OutputFormat};
+use crate::extractor::{ChangeTarget, DependencyGraph, SemanticContext};
+#[cfg(feature = "serde")]
+use crate::generator::CodeSliceStats;
+use crate::generator::{CodeSlice, HighlightStyle, OutputFormat, TargetVersions};
+use crate::parser::{ParserFactory, SourceFile, SupportedLanguage};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+use tree_sitter::{Node, Tree};
+
+pub mod color;
+#[cfg(feature = "serde")]
+pub mod sarif;
+
+pub use color::ColorDepth;
+
+/// 复位所有颜色属性的 ANSI 转义序列
+const RESET: &str = "\x1b[0m";
+
+/// Markdown 输出中的一个代码块：来源文件和块内的 (行号, 行内容)
+type MarkdownLineGroup<'a> = (Option<&'a std::path::PathBuf>, Vec<(u32, &'a str)>);
 
 /// 输出格式化器配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FormatterConfig {
     /// 输出格式
     pub output_format: OutputFormat,
@@ -25,14 +47,27 @@ pub struct FormatterConfig {
     pub block_title_style: BlockTitleStyle,
     /// 自定义CSS样式（仅对HTML输出有效）
     pub custom_css: Option<String>,
-    /// 最大行宽
+    /// 最大行宽（纯文本输出中超出的行会在空白或运算符处软换行，0 表示不限制）
     pub max_line_width: Option<usize>,
     /// 缩进大小
     pub indent_size: usize,
+    /// 是否在来源文件切换处标注文件路径
+    pub annotate_source_files: bool,
+    /// 是否按依赖图将上下文渲染为嵌套树（切片需包含依赖图，否则按平铺方式渲染）
+    pub render_dependency_tree: bool,
+    /// 导出语义上下文（JSON/MessagePack）时是否附带依赖图，图可能很大，默认不附带
+    pub include_dependency_graph: bool,
+    /// 是否按左右两栏对照渲染变更目标的前后版本（仅对纯文本输出有效，切片需包含前后版本源码）
+    pub side_by_side: bool,
+    /// 折叠未变更的上下文：只保留每个高亮区域前后的 N 行，更长的未变更行段折叠为一个标记
+    pub collapse_context: Option<usize>,
+    /// 终端支持的色深，为 `ColorDepth::None` 时即使启用颜色也不输出 ANSI 转义序列
+    pub color_depth: ColorDepth,
 }
 
 /// 代码块标题样式
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum BlockTitleStyle {
     /// 简洁样式
     Minimal,
@@ -45,6 +80,8 @@ pub enum BlockTitleStyle {
 /// 输出渲染器
 pub struct OutputRenderer {
     config: FormatterConfig,
+    /// 按配置的色深生成的颜色主题
+    theme: ColorTheme,
 }
 
 /// 格式化结果
@@ -73,10 +110,78 @@ pub struct OutputMetadata {
     pub content_size: usize,
 }
 
+/// 代码切片的 JSON 文档
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct CodeSliceDocument<'a> {
+    header_comment: &'a str,
+    involved_files: &'a [std::path::PathBuf],
+    /// 按来源文件分组的连续行
+    files: Vec<FileLines<'a>>,
+    stats: CodeSliceStats,
+}
+
+/// 来自同一来源文件的一段连续行
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct FileLines<'a> {
+    /// 来源文件，头部注释和导入等没有来源信息的行为 None
+    file: Option<&'a Path>,
+    lines: Vec<DocumentEntry<'a>>,
+}
+
+/// JSON 文档中的一项：一行代码或一段被折叠的未变更行
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(untagged)]
+enum DocumentEntry<'a> {
+    Line(DocumentLine<'a>),
+    Collapsed {
+        /// 被折叠的未变更行数
+        collapsed_lines: usize,
+    },
+}
+
+/// JSON 文档中的一行代码
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct DocumentLine<'a> {
+    /// 切片中的行号（从 1 开始）
+    number: u32,
+    content: &'a str,
+    highlighted: bool,
+    /// 高亮行的变更类型：`added`、`removed` 或 `context`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    change_type: Option<&'static str>,
+}
+
+/// 折叠上下文时一行的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineDisplay {
+    /// 正常显示
+    Shown,
+    /// 折叠行段的第一行，携带被折叠的行数
+    Collapsed(usize),
+    /// 折叠行段中的其余行
+    Hidden,
+}
+
 /// 语法高亮器
 pub struct SyntaxHighlighter {
-    _language: String,
-    _style: HighlightStyle,
+    language: String,
+    style: HighlightStyle,
+    theme: ColorTheme,
+    enable_colors: bool,
+}
+
+/// 语法高亮的 token 类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenClass {
+    Keyword,
+    String,
+    Comment,
+    Number,
+    Type,
 }
 
 /// 颜色主题
@@ -86,6 +191,8 @@ pub struct ColorTheme {
     pub added_line: String,
     /// 删除行颜色
     pub removed_line: String,
+    /// 修改行颜色（上下文变更和左右对照中的修改行）
+    pub modified_line: String,
     /// 上下文行颜色
     pub context_line: String,
     /// 行号颜色
@@ -98,6 +205,8 @@ pub struct ColorTheme {
     pub string: String,
     /// 数字颜色
     pub number: String,
+    /// 类型名颜色
+    pub type_name: String,
 }
 
 impl Default for FormatterConfig {
@@ -113,6 +222,33 @@ impl Default for FormatterConfig {
             custom_css: None,
             max_line_width: Some(120),
             indent_size: 4,
+            annotate_source_files: false,
+            render_dependency_tree: false,
+            include_dependency_graph: false,
+            side_by_side: false,
+            collapse_context: None,
+            color_depth: ColorDepth::default(),
+        }
+    }
+}
+
+impl ColorTheme {
+    /// 按终端色深生成主题
+    ///
+    /// 主题颜色以 24 位颜色定义，色深不足时量化到 256 色或 16 色调色板中最接近的颜色，
+    /// `ColorDepth::None` 时所有颜色为空
+    pub fn for_depth(depth: ColorDepth) -> Self {
+        Self {
+            added_line: depth.foreground((0x3f, 0xb9, 0x50)),
+            removed_line: depth.foreground((0xf8, 0x51, 0x49)),
+            modified_line: depth.foreground((0xd2, 0x99, 0x22)),
+            context_line: depth.foreground((0xc9, 0xd1, 0xd9)),
+            line_number: depth.foreground((0x39, 0xc5, 0xcf)),
+            comment: depth.foreground((0x8b, 0x94, 0x9e)),
+            keyword: depth.foreground((0x58, 0xa6, 0xff)),
+            string: depth.foreground((0xe3, 0xb3, 0x41)),
+            number: depth.foreground((0xd2, 0xa8, 0xff)),
+            type_name: depth.foreground((0x56, 0xd4, 0xdd)),
         }
     }
 }
@@ -120,14 +256,16 @@ impl Default for FormatterConfig {
 impl Default for ColorTheme {
     fn default() -> Self {
         Self {
-            added_line: "\x1b[32m".to_string(),   // 绿色
-            removed_line: "\x1b[31m".to_string(), // 红色
-            context_line: "\x1b[37m".to_string(), // 白色
-            line_number: "\x1b[36m".to_string(),  // 青色
-            comment: "\x1b[90m".to_string(),      // 灰色
-            keyword: "\x1b[34m".to_string(),      // 蓝色
-            string: "\x1b[33m".to_string(),       // 黄色
-            number: "\x1b[35m".to_string(),       // 紫色
+            added_line: "\x1b[32m".to_string(),    // 绿色
+            removed_line: "\x1b[31m".to_string(),  // 红色
+            modified_line: "\x1b[33m".to_string(), // 黄色
+            context_line: "\x1b[37m".to_string(),  // 白色
+            line_number: "\x1b[36m".to_string(),   // 青色
+            comment: "\x1b[90m".to_string(),       // 灰色
+            keyword: "\x1b[34m".to_string(),       // 蓝色
+            string: "\x1b[33m".to_string(),        // 黄色
+            number: "\x1b[35m".to_string(),        // 紫色
+            type_name: "\x1b[96m".to_string(),     // 亮青色
         }
     }
 }
@@ -135,7 +273,8 @@ impl Default for ColorTheme {
 impl OutputRenderer {
     /// 创建新的输出渲染器
     pub fn new(config: FormatterConfig) -> Self {
-        Self { config }
+        let theme = ColorTheme::for_depth(config.color_depth);
+        Self { config, theme }
     }
 
     /// 使用默认配置创建渲染器
@@ -143,12 +282,132 @@ impl OutputRenderer {
         Self::new(FormatterConfig::default())
     }
 
+    /// 将语义上下文导出为 JSON 数组
+    ///
+    /// 是否在每个上下文中附带 `dependency_graph` 字段由 `include_dependency_graph` 决定
+    #[cfg(feature = "serde")]
+    pub fn render_contexts_json(&self, contexts: &[SemanticContext]) -> Result<String> {
+        let exports: Vec<_> = contexts
+            .iter()
+            .map(|context| context.export(self.config.include_dependency_graph))
+            .collect();
+        serde_json::to_string_pretty(&exports)
+            .map_err(|e| SemanticDiffError::SerializationError(e.to_string()))
+    }
+
+    /// 未启用 `serde` 特性时无法导出 JSON，返回配置错误
+    #[cfg(not(feature = "serde"))]
+    pub fn render_contexts_json(&self, _contexts: &[SemanticContext]) -> Result<String> {
+        Err(SemanticDiffError::ConfigError(
+            "JSON output requires the `serde` feature".to_string(),
+        ))
+    }
+
+    /// 将语义上下文导出为 MessagePack，依赖图的处理与 JSON 导出一致
+    #[cfg(feature = "msgpack")]
+    pub fn render_contexts_msgpack(&self, contexts: &[SemanticContext]) -> Result<Vec<u8>> {
+        SemanticContext::contexts_to_msgpack(contexts, self.config.include_dependency_graph)
+    }
+
+    /// 未启用 `msgpack` 特性时无法导出 MessagePack，返回配置错误
+    #[cfg(not(feature = "msgpack"))]
+    pub fn render_contexts_msgpack(&self, _contexts: &[SemanticContext]) -> Result<Vec<u8>> {
+        Err(SemanticDiffError::ConfigError(
+            "MessagePack output requires the `msgpack` feature".to_string(),
+        ))
+    }
+
+    /// 将语义上下文渲染为变更概览表格
+    ///
+    /// 每个变更目标一行，列出种类、名称、文件、行范围（从 1 开始）和 `ContextStats` 中的
+    /// 依赖统计；不生成代码切片，也不输出任何源码
+    pub fn render_contexts_summary(&self, contexts: &[SemanticContext]) -> String {
+        const HEADERS: [&str; 9] = [
+            "KIND", "NAME", "FILE", "LINES", "TYPES", "FUNCS", "CONSTS", "VARS", "FILES",
+        ];
+
+        let rows: Vec<[String; 9]> = contexts
+            .iter()
+            .map(|context| {
+                let target = &context.change_target;
+                let stats = context.get_stats();
+                let lines = match target {
+                    ChangeTarget::Package { .. } => "-".to_string(),
+                    _ => {
+                        let (start_line, end_line) = target.line_range();
+                        format!("{}-{}", start_line + 1, end_line + 1)
+                    }
+                };
+                [
+                    target.kind().to_string(),
+                    target.name().to_string(),
+                    target.file_path().display().to_string(),
+                    lines,
+                    stats.types_count.to_string(),
+                    stats.functions_count.to_string(),
+                    stats.constants_count.to_string(),
+                    stats.variables_count.to_string(),
+                    stats.files_count.to_string(),
+                ]
+            })
+            .collect();
+
+        let mut widths = HEADERS.map(str::len);
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let format_row = |cells: &[&str]| {
+            let line = cells
+                .iter()
+                .zip(widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            format!("{}\n", line.trim_end())
+        };
+
+        let mut output = format!("{} changed declarations\n\n", contexts.len());
+        output.push_str(&format_row(&HEADERS));
+        for row in &rows {
+            let cells: Vec<&str> = row.iter().map(String::as_str).collect();
+            output.push_str(&format_row(&cells));
+        }
+        output
+    }
+
     /// 渲染代码切片
     pub fn render(&self, code_slice: &CodeSlice) -> Result<FormattedOutput> {
         let content = match self.config.output_format {
             OutputFormat::PlainText => self.render_plain_text(code_slice)?,
             OutputFormat::Markdown => self.render_markdown(code_slice)?,
             OutputFormat::Html => self.render_html(code_slice)?,
+            // 生成器已经把内容构建为统一差异，原样输出
+            OutputFormat::UnifiedDiff => code_slice.content.clone(),
+            OutputFormat::Json => self.render_json(code_slice)?,
+            OutputFormat::MessagePack => {
+                return Err(SemanticDiffError::ConfigError(
+                    "MessagePack output is serialized from semantic contexts, \
+                     use OutputRenderer::render_contexts_msgpack instead"
+                        .to_string(),
+                ));
+            }
+            OutputFormat::Summary => {
+                return Err(SemanticDiffError::ConfigError(
+                    "Summary output is rendered from semantic contexts, \
+                     use OutputRenderer::render_contexts_summary instead"
+                        .to_string(),
+                ));
+            }
+            OutputFormat::Sarif => {
+                return Err(SemanticDiffError::ConfigError(
+                    "SARIF output covers all code slices in one document, \
+                     use formatter::sarif::to_sarif instead"
+                        .to_string(),
+                ));
+            }
         };
 
         let metadata = self.generate_metadata(code_slice, &content);
@@ -184,8 +443,15 @@ impl OutputRenderer {
         output.push_str(&code_slice.header_comment);
         output.push('\n');
 
-        // 使用内置的高亮方法处理内容
-        let highlighted_content = self.apply_highlighting_plain_text(code_slice)?;
+        // 使用内置的高亮方法处理内容，树形模式下按依赖图嵌套渲染，对照模式下左右两栏渲染变更目标
+        let highlighted_content = match (
+            self.side_by_side_versions(code_slice),
+            self.dependency_tree_of(code_slice),
+        ) {
+            (Some(versions), _) => self.render_side_by_side(&versions.old, &versions.new),
+            (None, Some(graph)) => self.render_dependency_tree_plain_text(code_slice, graph),
+            (None, None) => self.apply_highlighting_plain_text(code_slice)?,
+        };
         output.push_str(&highlighted_content);
 
         Ok(output)
@@ -224,13 +490,15 @@ impl OutputRenderer {
         );
         output.push_str("\n\n");
 
-        // 使用内置的高亮方法处理内容
-        let highlighted_content = self.apply_highlighting_markdown(code_slice)?;
-
-        // 将格式化内容包装在代码块中
-        output.push_str("```go\n");
-        output.push_str(&highlighted_content);
-        output.push_str("\n```\n");
+        // 高亮方法自行输出围栏代码块；树形模式下按依赖图嵌套渲染后整体包装在一个代码块中
+        match self.dependency_tree_of(code_slice) {
+            Some(graph) => {
+                output.push_str(&format!("```{}\n", Self::fence_language(code_slice, None)));
+                output.push_str(&self.render_dependency_tree_plain_text(code_slice, graph));
+                output.push_str("```\n");
+            }
+            None => output.push_str(&self.apply_highlighting_markdown(code_slice)?),
+        }
 
         Ok(output)
     }
@@ -294,8 +562,11 @@ impl OutputRenderer {
         let escaped_comment = html_escape(&code_slice.header_comment);
         output.push_str(&format!("            <div class=\"header-comment\">\n                <pre>{escaped_comment}</pre>\n            </div>\n"));
 
-        // 使用内置的高亮方法处理内容
-        let highlighted_content = self.apply_highlighting_html(code_slice)?;
+        // 使用内置的高亮方法处理内容，树形模式下按依赖图嵌套渲染
+        let highlighted_content = match self.dependency_tree_of(code_slice) {
+            Some(graph) => self.render_dependency_tree_html(code_slice, graph),
+            None => self.apply_highlighting_html(code_slice)?,
+        };
 
         output.push_str("            <div class=\"code-block\">\n");
         output.push_str(&highlighted_content);
@@ -309,6 +580,248 @@ impl OutputRenderer {
         Ok(output)
     }
 
+    /// 渲染为 JSON 格式
+    ///
+    /// 切片内容按行拆分并附带高亮标记，相邻且来源文件相同的行归为一组，末尾附带统计信息，
+    /// 便于编辑器等工具直接消费
+    #[cfg(feature = "serde")]
+    fn render_json(&self, code_slice: &CodeSlice) -> Result<String> {
+        let lines: Vec<&str> = code_slice.content.lines().collect();
+        let displays = self.line_displays(code_slice, lines.len());
+        let mut files: Vec<FileLines> = Vec::new();
+        for (index, content) in lines.into_iter().enumerate() {
+            let number = (index + 1) as u32;
+            let line = match displays[index] {
+                LineDisplay::Hidden => continue,
+                LineDisplay::Collapsed(collapsed_lines) => {
+                    DocumentEntry::Collapsed { collapsed_lines }
+                }
+                LineDisplay::Shown => {
+                    DocumentEntry::Line(self.document_line(code_slice, number, content))
+                }
+            };
+
+            let file = code_slice
+                .line_files
+                .get(&number)
+                .map(|file| file.as_path());
+            match files.last_mut() {
+                Some(group) if group.file == file => group.lines.push(line),
+                _ => files.push(FileLines {
+                    file,
+                    lines: vec![line],
+                }),
+            }
+        }
+
+        let document = CodeSliceDocument {
+            header_comment: &code_slice.header_comment,
+            involved_files: &code_slice.involved_files,
+            files,
+            stats: code_slice.get_stats(),
+        };
+        serde_json::to_string_pretty(&document)
+            .map_err(|e| SemanticDiffError::SerializationError(e.to_string()))
+    }
+
+    /// 未启用 `serde` 特性时无法导出 JSON，返回配置错误
+    #[cfg(not(feature = "serde"))]
+    fn render_json(&self, _code_slice: &CodeSlice) -> Result<String> {
+        Err(SemanticDiffError::ConfigError(
+            "JSON output requires the `serde` feature".to_string(),
+        ))
+    }
+
+    /// 构建 JSON 文档中的一行代码
+    #[cfg(feature = "serde")]
+    fn document_line<'a>(
+        &self,
+        code_slice: &CodeSlice,
+        number: u32,
+        content: &'a str,
+    ) -> DocumentLine<'a> {
+        let highlighted = code_slice.highlighted_lines.contains(&number);
+        let change_type = code_slice
+            .line_change_types
+            .get(&number)
+            .filter(|_| highlighted)
+            .map(|change_type| match change_type {
+                crate::git::DiffLineType::Added => "added",
+                crate::git::DiffLineType::Removed => "removed",
+                crate::git::DiffLineType::Context => "context",
+            });
+        DocumentLine {
+            number,
+            content,
+            highlighted,
+            change_type,
+        }
+    }
+
+    /// 按 `collapse_context` 计算切片中每一行的显示方式（下标为行号减一）
+    ///
+    /// 每个高亮行前后各保留 N 行，超过一行的连续未变更行段折叠为一个标记；
+    /// 未设置折叠或切片没有高亮行时所有行正常显示
+    fn line_displays(&self, code_slice: &CodeSlice, line_count: usize) -> Vec<LineDisplay> {
+        let mut displays = vec![LineDisplay::Shown; line_count];
+        let Some(context) = self.config.collapse_context else {
+            return displays;
+        };
+        if code_slice.highlighted_lines.is_empty() {
+            return displays;
+        }
+
+        let mut keep = vec![false; line_count];
+        for &line_number in &code_slice.highlighted_lines {
+            let index = line_number.saturating_sub(1) as usize;
+            if index >= line_count {
+                continue;
+            }
+            let end = (index + context).min(line_count - 1);
+            keep[index.saturating_sub(context)..=end].fill(true);
+        }
+
+        let mut index = 0;
+        while index < line_count {
+            if keep[index] {
+                index += 1;
+                continue;
+            }
+            let run_end = (index..line_count).find(|&i| keep[i]).unwrap_or(line_count);
+            // 只有一行时折叠标记并不比原行更短
+            if run_end - index > 1 {
+                displays[index] = LineDisplay::Collapsed(run_end - index);
+                displays[index + 1..run_end].fill(LineDisplay::Hidden);
+            }
+            index = run_end;
+        }
+
+        displays
+    }
+
+    /// 树形渲染模式下返回切片的依赖图
+    fn dependency_tree_of<'a>(&self, code_slice: &'a CodeSlice) -> Option<&'a DependencyGraph> {
+        if self.config.render_dependency_tree {
+            code_slice.dependency_graph.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// 按依赖图以嵌套树的形式渲染纯文本内容
+    ///
+    /// 变更目标位于根部，依赖沿图中的边嵌套在使用它的节点之下，每个节点下附带对应的代码
+    fn render_dependency_tree_plain_text(
+        &self,
+        code_slice: &CodeSlice,
+        graph: &DependencyGraph,
+    ) -> String {
+        let mut output = String::new();
+        self.build_plain_text_tree(
+            &mut output,
+            code_slice,
+            graph,
+            &graph.root_node,
+            0,
+            &mut HashSet::new(),
+        );
+        output
+    }
+
+    /// 递归构建纯文本依赖树
+    fn build_plain_text_tree(
+        &self,
+        output: &mut String,
+        code_slice: &CodeSlice,
+        graph: &DependencyGraph,
+        node_id: &str,
+        depth: usize,
+        visited: &mut HashSet<String>,
+    ) {
+        if !visited.insert(node_id.to_string()) {
+            return;
+        }
+        let Some(node) = graph.nodes.iter().find(|n| n.id == node_id) else {
+            return;
+        };
+
+        let indent = " ".repeat(depth * self.config.indent_size);
+        let marker = if node.is_change_target { "★ " } else { "" };
+        output.push_str(&format!(
+            "{indent}{marker}{} ({})\n",
+            node.name,
+            format!("{:?}", node.node_type).to_lowercase()
+        ));
+
+        if let Some(code) = code_slice.node_blocks.get(node_id) {
+            for line in code.lines() {
+                output.push_str(&format!("{indent}  | {line}\n"));
+            }
+        }
+
+        for dependency in graph.get_direct_dependencies(node_id) {
+            self.build_plain_text_tree(
+                output,
+                code_slice,
+                graph,
+                &dependency.id,
+                depth + 1,
+                visited,
+            );
+        }
+    }
+
+    /// 按依赖图以可折叠的嵌套 `<details>` 渲染 HTML 内容
+    fn render_dependency_tree_html(
+        &self,
+        code_slice: &CodeSlice,
+        graph: &DependencyGraph,
+    ) -> String {
+        let mut output = String::new();
+        self.build_html_tree(
+            &mut output,
+            code_slice,
+            graph,
+            &graph.root_node,
+            &mut HashSet::new(),
+        );
+        output
+    }
+
+    /// 递归构建 HTML 依赖树
+    fn build_html_tree(
+        &self,
+        output: &mut String,
+        code_slice: &CodeSlice,
+        graph: &DependencyGraph,
+        node_id: &str,
+        visited: &mut HashSet<String>,
+    ) {
+        if !visited.insert(node_id.to_string()) {
+            return;
+        }
+        let Some(node) = graph.nodes.iter().find(|n| n.id == node_id) else {
+            return;
+        };
+
+        let marker = if node.is_change_target { "★ " } else { "" };
+        output.push_str("<details open class=\"dependency-node\">\n");
+        output.push_str(&format!(
+            "<summary>{marker}{} ({})</summary>\n",
+            html_escape(&node.name),
+            format!("{:?}", node.node_type).to_lowercase()
+        ));
+
+        if let Some(code) = code_slice.node_blocks.get(node_id) {
+            output.push_str(&format!("<pre><code>{}</code></pre>\n", html_escape(code)));
+        }
+
+        for dependency in graph.get_direct_dependencies(node_id) {
+            self.build_html_tree(output, code_slice, graph, &dependency.id, visited);
+        }
+        output.push_str("</details>\n");
+    }
+
     /// 应用纯文本高亮
     fn apply_highlighting_plain_text(&self, code_slice: &CodeSlice) -> Result<String> {
         match self.config.highlight_style {
@@ -319,14 +832,50 @@ impl OutputRenderer {
     }
 
     /// 应用内联高亮（纯文本）
+    ///
+    /// 未变更的代码行按语法树着色，整个切片只解析一次；变更行保持变更类型的颜色
     fn apply_inline_highlighting_plain_text(&self, code_slice: &CodeSlice) -> Result<String> {
-        let lines: Vec<&str> = code_slice.content.lines().collect();
+        let content = code_slice.content.as_str();
+        let lines = line_ranges(content);
+        let displays = self.line_displays(code_slice, lines.len());
+        let highlighter = self.syntax_highlighter(code_slice);
+        let tree = highlighter.parse(content)?;
+        let code = |range: Range<usize>| match &tree {
+            Some(tree) => highlighter.highlight_tree(content, tree, range),
+            None => content[range].to_string(),
+        };
         let mut result = String::new();
+        let mut current_file = None;
+
+        for (index, range) in lines.iter().enumerate() {
+            let line = &content[range.clone()];
+            match displays[index] {
+                LineDisplay::Shown => {}
+                LineDisplay::Hidden => continue,
+                LineDisplay::Collapsed(count) => {
+                    let marker = collapsed_marker(count);
+                    if self.colors_enabled() {
+                        result.push_str(&format!("{}{marker}{RESET}\n", self.theme.comment));
+                    } else {
+                        result.push_str(&format!("{marker}\n"));
+                    }
+                    continue;
+                }
+            }
 
-        for (index, line) in lines.iter().enumerate() {
             let line_number = (index + 1) as u32;
             let is_highlighted = code_slice.highlighted_lines.contains(&line_number);
 
+            if let Some(annotation) =
+                self.source_file_annotation(code_slice, line_number, &mut current_file)
+            {
+                if self.colors_enabled() {
+                    result.push_str(&format!("{}{annotation}{RESET}\n", self.theme.comment));
+                } else {
+                    result.push_str(&format!("{annotation}\n"));
+                }
+            }
+
             if is_highlighted {
                 // 获取变更类型并显示相应的前缀
                 let change_prefix =
@@ -340,24 +889,24 @@ impl OutputRenderer {
                         ">"
                     };
 
-                if self.config.enable_colors {
-                    let color = match change_prefix {
-                        "+" => "\x1b[32m", // 绿色用于添加
-                        "-" => "\x1b[31m", // 红色用于删除
-                        _ => "\x1b[33m",   // 黄色用于其他变更
-                    };
-                    result.push_str(&format!("{color}{change_prefix} {line}\x1b[0m\n"));
+                if self.colors_enabled() {
+                    let color = self.change_color(change_prefix);
+                    let wrapped = self.wrap_line(&format!("{color}{change_prefix} "), line);
+                    result.push_str(&format!("{wrapped}{RESET}\n"));
                 } else {
-                    result.push_str(&format!("{change_prefix} {line}\n"));
-                }
-            } else if self.config.show_line_numbers {
-                if self.config.enable_colors {
-                    result.push_str(&format!("\x1b[36m{line_number:4}|\x1b[0m {line}\n"));
-                } else {
-                    result.push_str(&format!("{line_number:4}| {line}\n"));
+                    let wrapped = self.wrap_line(&format!("{change_prefix} "), line);
+                    result.push_str(&format!("{wrapped}\n"));
                 }
             } else {
-                result.push_str(&format!("{line}\n"));
+                let gutter = match (self.config.show_line_numbers, self.colors_enabled()) {
+                    (false, _) => String::new(),
+                    (true, true) => format!("{}{line_number:4}|{RESET} ", self.theme.line_number),
+                    (true, false) => format!("{line_number:4}| "),
+                };
+                let wrapped = self.wrap_line_with(&gutter, line, |piece| {
+                    code(range.start + piece.start..range.start + piece.end)
+                });
+                result.push_str(&format!("{wrapped}\n"));
             }
         }
 
@@ -371,7 +920,11 @@ impl OutputRenderer {
 
         // 添加完整内容
         result.push_str("=== Full Content ===\n");
-        result.push_str(&code_slice.content);
+        result.push_str(
+            &self
+                .syntax_highlighter(code_slice)
+                .highlight(&code_slice.content)?,
+        );
         result.push_str("\n\n");
 
         // 添加高亮部分
@@ -389,17 +942,15 @@ impl OutputRenderer {
                         "> "
                     };
 
-                if self.config.enable_colors {
-                    let color = match change_prefix.trim() {
-                        "+" => "\x1b[32m", // 绿色用于添加
-                        "-" => "\x1b[31m", // 红色用于删除
-                        _ => "\x1b[33m",   // 黄色用于其他变更
-                    };
-                    result.push_str(&format!(
-                        "{color}Line {line_number}: {change_prefix}{line}\x1b[0m\n"
-                    ));
+                if self.colors_enabled() {
+                    let color = self.change_color(change_prefix.trim());
+                    let wrapped = self
+                        .wrap_line(&format!("{color}Line {line_number}: {change_prefix}"), line);
+                    result.push_str(&format!("{wrapped}{RESET}\n"));
                 } else {
-                    result.push_str(&format!("Line {line_number}: {change_prefix}{line}\n"));
+                    let wrapped =
+                        self.wrap_line(&format!("Line {line_number}: {change_prefix}"), line);
+                    result.push_str(&format!("{wrapped}\n"));
                 }
             }
         }
@@ -407,49 +958,316 @@ impl OutputRenderer {
         Ok(result)
     }
 
+    /// 纯文本输出中为代码着色的语法高亮器，语言按切片涉及的文件检测
+    fn syntax_highlighter(&self, code_slice: &CodeSlice) -> SyntaxHighlighter {
+        SyntaxHighlighter::new(
+            Self::fence_language(code_slice, None).to_string(),
+            self.config.highlight_style.clone(),
+        )
+        .with_theme(self.theme.clone())
+        .with_colors(self.colors_enabled())
+    }
+
+    /// 变更行前缀对应的主题颜色：`+` 为添加，`-` 为删除，其余为修改
+    fn change_color(&self, change_prefix: &str) -> &str {
+        match change_prefix {
+            "+" => &self.theme.added_line,
+            "-" => &self.theme.removed_line,
+            _ => &self.theme.modified_line,
+        }
+    }
+
+    /// 是否输出 ANSI 颜色
+    fn colors_enabled(&self) -> bool {
+        self.config.enable_colors && self.config.color_depth != ColorDepth::None
+    }
+
+    /// 对照模式下获取切片中变更目标的前后版本
+    fn side_by_side_versions<'a>(&self, code_slice: &'a CodeSlice) -> Option<&'a TargetVersions> {
+        if !self.config.side_by_side {
+            return None;
+        }
+        code_slice.target_versions.as_ref()
+    }
+
+    /// 按左右两栏对照渲染变更前后的源码
+    ///
+    /// 按行级差异对齐两侧：未变更的行并排显示，同一差异块中的删除行和新增行
+    /// 逐行配对为修改行（标记 `|`），多出的部分对侧留空（删除标记 `<`，新增标记 `>`）。
+    /// 设置了最大行宽时每栏宽度为 `(max_line_width - 3) / 2`，否则取左栏最长行的宽度，
+    /// 超出栏宽的行会被截断
+    pub fn render_side_by_side(&self, old: &str, new: &str) -> String {
+        let old_lines: Vec<&str> = old.lines().collect();
+        let new_lines: Vec<&str> = new.lines().collect();
+
+        let column_width = match self.config.max_line_width {
+            Some(width) if width > 0 => width.saturating_sub(3) / 2,
+            _ => old_lines
+                .iter()
+                .map(|line| line.chars().count())
+                .max()
+                .unwrap_or(0),
+        };
+
+        let mut result = String::new();
+        for (old_line, new_line) in align_lines(&old_lines, &new_lines) {
+            let marker = match (old_line, new_line) {
+                (Some(old_line), Some(new_line)) if old_line == new_line => ' ',
+                (Some(_), Some(_)) => '|',
+                (Some(_), None) => '<',
+                (None, Some(_)) => '>',
+                (None, None) => continue,
+            };
+
+            let left: String = old_line.unwrap_or("").chars().take(column_width).collect();
+            let right: String = new_line.unwrap_or("").chars().take(column_width).collect();
+            let marker = if self.colors_enabled() && marker != ' ' {
+                let color = match marker {
+                    '>' => &self.theme.added_line,
+                    '<' => &self.theme.removed_line,
+                    _ => &self.theme.modified_line,
+                };
+                format!("{color}{marker}{RESET}")
+            } else {
+                marker.to_string()
+            };
+
+            let padding = column_width.saturating_sub(left.chars().count());
+            let row = format!("{left}{} {marker} {right}", " ".repeat(padding));
+            result.push_str(row.trim_end());
+            result.push('\n');
+        }
+
+        result
+    }
+
+    /// 在来源文件发生切换的行之前生成文件标注
+    ///
+    /// `current_file` 记录上一次标注的文件，没有来源信息的行（如头部注释、导入）不会触发标注
+    fn source_file_annotation<'a>(
+        &self,
+        code_slice: &'a CodeSlice,
+        line_number: u32,
+        current_file: &mut Option<&'a std::path::PathBuf>,
+    ) -> Option<String> {
+        if !self.config.annotate_source_files {
+            return None;
+        }
+
+        let file = code_slice.line_files.get(&line_number)?;
+        if *current_file == Some(file) {
+            return None;
+        }
+
+        *current_file = Some(file);
+        Some(format!("// File: {}", file.display()))
+    }
+
+    /// 按最大行宽对一行代码做软换行
+    ///
+    /// 只在空白或运算符处断行，不会拆开标识符；续行对齐到前缀之后，
+    /// 并在原有缩进基础上再缩进 `indent_size` 个空格。前缀中的 ANSI 转义序列不计入宽度
+    fn wrap_line(&self, prefix: &str, line: &str) -> String {
+        self.wrap_line_with(prefix, line, |piece| line[piece].to_string())
+    }
+
+    /// 按最大行宽对一行代码做软换行，每一段由 `render` 按其在行内的字节范围生成
+    fn wrap_line_with(
+        &self,
+        prefix: &str,
+        line: &str,
+        render: impl Fn(Range<usize>) -> String,
+    ) -> String {
+        let prefix_width = visible_width(prefix);
+        let leading = &line[..line.len() - line.trim_start().len()];
+        let continuation = format!(
+            "{}{leading}{}",
+            " ".repeat(prefix_width),
+            " ".repeat(self.config.indent_size)
+        );
+
+        let mut output = prefix.to_string();
+        for (index, piece) in self
+            .wrap_pieces(prefix_width, visible_width(&continuation), line)
+            .into_iter()
+            .enumerate()
+        {
+            if index > 0 {
+                output.push('\n');
+                output.push_str(&continuation);
+            }
+            output.push_str(&render(piece));
+        }
+        output
+    }
+
+    /// 计算软换行后每一段在行内的字节范围，断行处的空白不属于任何一段
+    fn wrap_pieces(
+        &self,
+        prefix_width: usize,
+        continuation_width: usize,
+        line: &str,
+    ) -> Vec<Range<usize>> {
+        let whole_line = 0..line.len();
+        let max_width = match self.config.max_line_width {
+            Some(width) if width > 0 => width,
+            _ => return vec![whole_line],
+        };
+        if prefix_width + visible_width(line) <= max_width {
+            return vec![whole_line];
+        }
+
+        let mut pieces = Vec::new();
+        let mut piece = 0..0;
+        let mut width = prefix_width;
+        // 当前段是否已有非空白内容，避免产生只有缩进的空行
+        let mut has_content = false;
+        let mut offset = 0;
+
+        for token in split_wrap_tokens(line) {
+            let token_start = offset;
+            offset += token.len();
+            let token_width = visible_width(token);
+            let is_space = token.chars().all(char::is_whitespace);
+
+            if has_content && width + token_width > max_width {
+                pieces.push(piece);
+                width = continuation_width;
+                has_content = false;
+                if is_space {
+                    piece = offset..offset;
+                    continue;
+                }
+                piece = token_start..token_start;
+            }
+
+            width += token_width;
+            if !is_space {
+                has_content = true;
+                piece.end = offset;
+            }
+        }
+
+        pieces.push(piece);
+        pieces
+    }
+
     /// 应用Markdown高亮
     fn apply_highlighting_markdown(&self, code_slice: &CodeSlice) -> Result<String> {
         match self.config.highlight_style {
-            HighlightStyle::None => Ok(format!("```go\n{}\n```\n", code_slice.content)),
+            HighlightStyle::None => Ok(format!(
+                "```{}\n{}\n```\n",
+                Self::fence_language(code_slice, None),
+                code_slice.content
+            )),
             HighlightStyle::Inline => self.apply_inline_highlighting_markdown(code_slice),
             HighlightStyle::Separate => self.apply_separate_highlighting_markdown(code_slice),
         }
     }
 
     /// 应用内联高亮（Markdown）
+    ///
+    /// 相邻且来源文件相同的行放在同一个围栏代码块中，每个来源文件的代码块前按
+    /// `block_title_style` 输出 `###` 标题。Markdown 无法为代码着色，高亮行使用
+    /// diff 风格的 `+`/`-` 前缀（上下文变更使用 `>`），其余行以空格对齐
     fn apply_inline_highlighting_markdown(&self, code_slice: &CodeSlice) -> Result<String> {
-        let lines: Vec<&str> = code_slice.content.lines().collect();
-        let mut result = String::new();
+        let mut groups: Vec<MarkdownLineGroup> = Vec::new();
+        // 块标题注释和空行没有来源信息，归入其后的代码所在的组；切片开头的头部注释和导入单独成组
+        let mut pending = Vec::new();
+        for (index, line) in code_slice.content.lines().enumerate() {
+            let line_number = (index + 1) as u32;
+            let Some(file) = code_slice.line_files.get(&line_number) else {
+                pending.push((line_number, line));
+                continue;
+            };
+            if groups.is_empty() && !pending.is_empty() {
+                groups.push((None, std::mem::take(&mut pending)));
+            }
+            match groups.last_mut() {
+                Some((Some(group_file), lines)) if *group_file == file => {
+                    lines.append(&mut pending);
+                    lines.push((line_number, line));
+                }
+                _ => {
+                    pending.push((line_number, line));
+                    groups.push((Some(file), std::mem::take(&mut pending)));
+                }
+            }
+        }
+        match groups.last_mut() {
+            Some((_, lines)) => lines.append(&mut pending),
+            None => groups.push((None, pending)),
+        }
 
-        result.push_str("```go\n");
+        let displays = self.line_displays(code_slice, code_slice.content.lines().count());
+        let mut result = String::new();
+        for (file, lines) in groups {
+            if let Some(title) = file.and_then(|file| self.markdown_block_title(file, lines.len()))
+            {
+                result.push_str(&format!("{title}\n\n"));
+            }
 
-        for (index, line) in lines.iter().enumerate() {
-            let line_number = (index + 1) as u32;
-            let is_highlighted = code_slice.highlighted_lines.contains(&line_number);
+            result.push_str(&format!(
+                "```{}\n",
+                Self::fence_language(code_slice, file.map(|file| file.as_path()))
+            ));
+            if let Some(file) = file.filter(|_| self.config.annotate_source_files) {
+                result.push_str(&format!("// File: {}\n", file.display()));
+            }
+            for (line_number, line) in lines {
+                match displays[(line_number - 1) as usize] {
+                    LineDisplay::Shown => {}
+                    LineDisplay::Hidden => continue,
+                    LineDisplay::Collapsed(count) => {
+                        result.push_str(&format!("  {}\n", collapsed_marker(count)));
+                        continue;
+                    }
+                }
 
-            if is_highlighted {
-                // 在Markdown中，我们使用注释来标记高亮行，显示变更类型
-                let change_prefix =
-                    if let Some(change_type) = code_slice.line_change_types.get(&line_number) {
-                        match change_type {
-                            crate::git::DiffLineType::Added => "// +++ ",
-                            crate::git::DiffLineType::Removed => "// --- ",
-                            crate::git::DiffLineType::Context => "// >>> ",
-                        }
-                    } else {
-                        "// >>> "
-                    };
-                result.push_str(&format!("{change_prefix}{line}\n"));
-            } else {
-                result.push_str(&format!("{line}\n"));
+                let marker = if code_slice.highlighted_lines.contains(&line_number) {
+                    match code_slice.line_change_types.get(&line_number) {
+                        Some(crate::git::DiffLineType::Added) => "+ ",
+                        Some(crate::git::DiffLineType::Removed) => "- ",
+                        Some(crate::git::DiffLineType::Context) | None => "> ",
+                    }
+                } else {
+                    "  "
+                };
+                result.push_str(&format!("{marker}{line}\n"));
             }
+            result.push_str("```\n\n");
         }
 
-        result.push_str("```\n");
-
         Ok(result)
     }
 
+    /// 来源文件代码块的 Markdown 标题，`BlockTitleStyle::None` 时不输出标题
+    fn markdown_block_title(&self, file: &Path, line_count: usize) -> Option<String> {
+        match self.config.block_title_style {
+            BlockTitleStyle::Detailed => {
+                Some(format!("### `{}` ({line_count} lines)", file.display()))
+            }
+            BlockTitleStyle::Minimal => Some(format!(
+                "### {}",
+                file.file_name()
+                    .unwrap_or(file.as_os_str())
+                    .to_string_lossy()
+            )),
+            BlockTitleStyle::None => None,
+        }
+    }
+
+    /// 围栏代码块的语言标记
+    ///
+    /// 优先按代码块的来源文件检测语言，没有来源信息时使用切片涉及的第一个文件，都无法识别时默认为 Go
+    fn fence_language(code_slice: &CodeSlice, file: Option<&Path>) -> &'static str {
+        file.into_iter()
+            .chain(code_slice.involved_files.iter().map(|file| file.as_path()))
+            .find_map(crate::parser::ParserFactory::detect_language)
+            .unwrap_or(crate::parser::SupportedLanguage::Go)
+            .markdown_tag()
+    }
+
     /// 应用分离式高亮（Markdown）
     fn apply_separate_highlighting_markdown(&self, code_slice: &CodeSlice) -> Result<String> {
         let lines: Vec<&str> = code_slice.content.lines().collect();
@@ -457,7 +1275,7 @@ impl OutputRenderer {
 
         // 添加完整代码部分
         result.push_str("### Full Code\n\n");
-        result.push_str("```go\n");
+        result.push_str(&format!("```{}\n", Self::fence_language(code_slice, None)));
         result.push_str(&code_slice.content);
         result.push_str("\n```\n\n");
 
@@ -490,11 +1308,34 @@ impl OutputRenderer {
         let mut result = String::new();
 
         result.push_str("<pre><code class=\"language-go\">\n");
+        let displays = self.line_displays(code_slice, lines.len());
+        let mut current_file = None;
 
         for (index, line) in lines.iter().enumerate() {
+            match displays[index] {
+                LineDisplay::Shown => {}
+                LineDisplay::Hidden => continue,
+                LineDisplay::Collapsed(count) => {
+                    result.push_str(&format!(
+                        "<span class=\"collapsed-lines\">{}</span>\n",
+                        collapsed_marker(count)
+                    ));
+                    continue;
+                }
+            }
+
             let line_number = (index + 1) as u32;
             let is_highlighted = code_slice.highlighted_lines.contains(&line_number);
 
+            if let Some(annotation) =
+                self.source_file_annotation(code_slice, line_number, &mut current_file)
+            {
+                result.push_str(&format!(
+                    "<span class=\"file-annotation\">{}</span>\n",
+                    html_escape(&annotation)
+                ));
+            }
+
             if self.config.show_line_numbers {
                 result.push_str(&format!(
                     "<span class=\"line-number\">{line_number:4}</span>"
@@ -668,6 +1509,18 @@ impl OutputRenderer {
         .normal-line {
             padding-left: 8px;
         }
+        .file-annotation {
+            color: #6c757d;
+            font-style: italic;
+        }
+        .collapsed-lines {
+            display: block;
+            color: #6c757d;
+            background-color: #f1f3f5;
+        }
+        .dependency-node {
+            margin-left: 1.5em;
+        }
         ul {
             list-style-type: none;
             padding-left: 0;
@@ -694,16 +1547,153 @@ impl SyntaxHighlighter {
     /// 创建新的语法高亮器
     pub fn new(language: String, style: HighlightStyle) -> Self {
         Self {
-            _language: language,
-            _style: style,
+            language,
+            style,
+            theme: ColorTheme::default(),
+            enable_colors: true,
         }
     }
 
+    /// 设置颜色主题
+    pub fn with_theme(mut self, theme: ColorTheme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// 设置是否输出 ANSI 颜色
+    pub fn with_colors(mut self, enable_colors: bool) -> Self {
+        self.enable_colors = enable_colors;
+        self
+    }
+
     /// 应用语法高亮
+    ///
+    /// 将内容解析为 Go 语法树后按 token 着色；禁用颜色、高亮样式为 `None`
+    /// 或语言不是 Go 时原样返回内容
     pub fn highlight(&self, content: &str) -> Result<String> {
-        // 这里可以集成第三方语法高亮库，如 syntect
-        // 目前先返回基本的格式化
-        Ok(content.to_string())
+        Ok(match self.parse(content)? {
+            Some(tree) => self.highlight_tree(content, &tree, 0..content.len()),
+            None => content.to_string(),
+        })
+    }
+
+    /// 解析需要着色的内容
+    ///
+    /// 禁用颜色、高亮样式为 `None` 或语言不是 Go 时不需要语法树，返回 None
+    fn parse(&self, content: &str) -> Result<Option<Tree>> {
+        if !self.is_active() || !self.language.eq_ignore_ascii_case("go") {
+            return Ok(None);
+        }
+
+        let tree = ParserFactory::create_parser(SupportedLanguage::Go)?.parse_source(content)?;
+        Ok(Some(tree))
+    }
+
+    /// 对源文件中的一段行范围应用语法高亮
+    ///
+    /// 行号为从 0 开始的 CST 行号（包含两端），直接遍历 `source_file.syntax_tree`，不会重新解析；
+    /// 禁用颜色或高亮样式为 `None` 时原样返回该范围的源码
+    pub fn highlight_source_range(
+        &self,
+        source_file: &SourceFile,
+        start_line: u32,
+        end_line: u32,
+    ) -> String {
+        let source = &source_file.source_code;
+        let range = line_byte_range(source, start_line, end_line);
+        if !self.is_active() {
+            return source[range].to_string();
+        }
+
+        self.highlight_tree(source, &source_file.syntax_tree, range)
+    }
+
+    /// 是否需要输出颜色
+    fn is_active(&self) -> bool {
+        self.enable_colors && self.style != HighlightStyle::None
+    }
+
+    /// 按语法树为源码的指定字节范围着色
+    ///
+    /// 跨行的 token（原始字符串、块注释）按行分别着色，保证每一行的颜色都在行尾复位
+    fn highlight_tree(&self, source: &str, tree: &Tree, range: Range<usize>) -> String {
+        let mut spans = Vec::new();
+        Self::collect_token_spans(tree.root_node(), &range, &mut spans);
+
+        let mut output = String::with_capacity(range.len());
+        let mut position = range.start;
+        for (span, class) in spans {
+            let start = span.start.max(position);
+            let end = span.end.min(range.end);
+            if start >= end {
+                continue;
+            }
+
+            output.push_str(&source[position..start]);
+            let color = self.token_color(class);
+            for (index, piece) in source[start..end].split('\n').enumerate() {
+                if index > 0 {
+                    output.push('\n');
+                }
+                if !piece.is_empty() {
+                    output.push_str(&format!("{color}{piece}{RESET}"));
+                }
+            }
+            position = end;
+        }
+        output.push_str(&source[position..range.end]);
+
+        output
+    }
+
+    /// 按源码顺序收集与字节范围相交的 token 及其类别
+    fn collect_token_spans(
+        node: Node,
+        range: &Range<usize>,
+        spans: &mut Vec<(Range<usize>, TokenClass)>,
+    ) {
+        if node.end_byte() <= range.start || node.start_byte() >= range.end {
+            return;
+        }
+
+        let class = match node.kind() {
+            "comment" => Some(TokenClass::Comment),
+            "interpreted_string_literal" | "raw_string_literal" | "rune_literal" => {
+                Some(TokenClass::String)
+            }
+            "int_literal" | "float_literal" | "imaginary_literal" => Some(TokenClass::Number),
+            "type_identifier" => Some(TokenClass::Type),
+            "nil" | "true" | "false" | "iota" => Some(TokenClass::Keyword),
+            // 关键字是由小写字母组成的匿名节点
+            kind if !node.is_named()
+                && !kind.is_empty()
+                && kind.chars().all(|c| c.is_ascii_lowercase()) =>
+            {
+                Some(TokenClass::Keyword)
+            }
+            _ => None,
+        };
+
+        if let Some(class) = class {
+            spans.push((node.start_byte()..node.end_byte(), class));
+            return;
+        }
+
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::collect_token_spans(child, range, spans);
+        }
+    }
+
+    /// token 类别对应的主题颜色
+    fn token_color(&self, class: TokenClass) -> &str {
+        match class {
+            TokenClass::Keyword => &self.theme.keyword,
+            TokenClass::String => &self.theme.string,
+            TokenClass::Comment => &self.theme.comment,
+            TokenClass::Number => &self.theme.number,
+            TokenClass::Type => &self.theme.type_name,
+        }
     }
 }
 
@@ -725,6 +1715,143 @@ impl FormattedOutput {
     }
 }
 
+/// 按行级差异对齐变更前后的行
+///
+/// 返回的每一行是左右两侧的配对，未变更的行两侧相同；每个差异块内的删除行与新增行按顺序配对，
+/// 多出的行对侧为 None。差异由 imara-diff 计算，内存占用与行数成线性关系
+fn align_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Option<&'a str>, Option<&'a str>)> {
+    use imara_diff::{Algorithm, Diff, InternedInput};
+
+    let mut input = InternedInput::default();
+    input.update_before(old.iter().copied());
+    input.update_after(new.iter().copied());
+    let diff = Diff::compute(Algorithm::Histogram, &input);
+
+    let mut rows = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for hunk in diff.hunks() {
+        // 差异块之前的未变更行两侧一一对应
+        while i < hunk.before.start as usize {
+            rows.push((Some(old[i]), Some(new[j])));
+            i += 1;
+            j += 1;
+        }
+
+        let removed = &old[hunk.before.start as usize..hunk.before.end as usize];
+        let added = &new[hunk.after.start as usize..hunk.after.end as usize];
+        for row in 0..removed.len().max(added.len()) {
+            rows.push((removed.get(row).copied(), added.get(row).copied()));
+        }
+        i = hunk.before.end as usize;
+        j = hunk.after.end as usize;
+    }
+    while i < old.len() && j < new.len() {
+        rows.push((Some(old[i]), Some(new[j])));
+        i += 1;
+        j += 1;
+    }
+
+    rows
+}
+
+/// 计算从 0 开始的起止行（包含两端）在源码中的字节范围，不包含结束行的换行符
+fn line_byte_range(source: &str, start_line: u32, end_line: u32) -> Range<usize> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let line_end = |line: usize| {
+        line_starts
+            .get(line + 1)
+            .map_or(source.len(), |next_start| next_start - 1)
+    };
+
+    let start = line_starts
+        .get(start_line as usize)
+        .copied()
+        .unwrap_or(source.len());
+    let end = line_end(end_line as usize).max(start);
+    start..end
+}
+
+/// 按 [`str::lines`] 的规则切分内容，返回每一行在内容中的字节范围（不含行尾换行符）
+fn line_ranges(content: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for piece in content.split_inclusive('\n') {
+        let line = piece
+            .strip_suffix('\n')
+            .map_or(piece, |line| line.strip_suffix('\r').unwrap_or(line));
+        ranges.push(start..start + line.len());
+        start += piece.len();
+    }
+    ranges
+}
+
+/// 折叠行段的标记文本
+fn collapsed_marker(count: usize) -> String {
+    format!("… {count} unchanged lines …")
+}
+
+/// 计算文本的显示宽度，忽略 ANSI 转义序列
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // 跳过 CSI 序列直到结束字符
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// 将一行代码切分为可换行的片段
+///
+/// 标识符（字母、数字、下划线）、连续空白和连续的运算符/标点各自作为一个整体，
+/// 换行只会发生在片段之间
+fn split_wrap_tokens(line: &str) -> Vec<&str> {
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    enum CharClass {
+        Word,
+        Space,
+        Symbol,
+    }
+
+    let classify = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            CharClass::Word
+        } else if c.is_whitespace() {
+            CharClass::Space
+        } else {
+            CharClass::Symbol
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut previous = None;
+
+    for (index, c) in line.char_indices() {
+        let class = classify(c);
+        if previous.is_some_and(|previous| previous != class) {
+            tokens.push(&line[start..index]);
+            start = index;
+        }
+        previous = Some(class);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+
+    tokens
+}
+
 /// HTML转义函数
 fn html_escape(text: &str) -> String {
     text.replace('&', "&amp;")
@@ -0,0 +1,204 @@
+//! 终端色深检测与颜色量化
+//!
+//! 根据 `NO_COLOR`、`COLORTERM` 和 `TERM` 环境变量判断终端支持的色深，
+//! 并将 24 位颜色量化为 256 色或 16 色调色板中最接近的颜色
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// 终端支持的色深
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ColorDepth {
+    /// 不输出颜色
+    None,
+    /// 标准 16 色
+    #[default]
+    Ansi16,
+    /// xterm 256 色
+    Ansi256,
+    /// 24 位真彩色
+    TrueColor,
+}
+
+/// xterm 标准 16 色调色板的 RGB 值
+const ANSI16_PALETTE: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// 256 色调色板中 6x6x6 颜色立方体每个分量的取值
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorDepth {
+    /// 从当前进程的环境变量检测色深
+    pub fn detect() -> Self {
+        Self::from_env_vars(
+            std::env::var("NO_COLOR").ok().as_deref(),
+            std::env::var("COLORTERM").ok().as_deref(),
+            std::env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    /// 根据给定的环境变量值判断色深
+    ///
+    /// 非空的 `NO_COLOR` 优先禁用颜色；`COLORTERM` 为 `truecolor`/`24bit` 时使用真彩色；
+    /// 否则按 `TERM` 判断：`dumb` 不输出颜色，`*-direct` 为真彩色，`*256color*` 为 256 色，
+    /// 其余情况（包括未设置）按 16 色处理
+    pub fn from_env_vars(
+        no_color: Option<&str>,
+        colorterm: Option<&str>,
+        term: Option<&str>,
+    ) -> Self {
+        if no_color.is_some_and(|value| !value.is_empty()) {
+            return ColorDepth::None;
+        }
+
+        if let Some(colorterm) = colorterm {
+            let colorterm = colorterm.to_ascii_lowercase();
+            if colorterm == "truecolor" || colorterm == "24bit" {
+                return ColorDepth::TrueColor;
+            }
+        }
+
+        match term.map(str::to_ascii_lowercase) {
+            Some(term) if term == "dumb" => ColorDepth::None,
+            Some(term) if term.ends_with("-direct") => ColorDepth::TrueColor,
+            Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+
+    /// 生成前景色的 ANSI 转义序列，色深不足时量化到最接近的调色板颜色
+    ///
+    /// `ColorDepth::None` 时返回空字符串
+    pub fn foreground(&self, (r, g, b): (u8, u8, u8)) -> String {
+        match self {
+            ColorDepth::None => String::new(),
+            ColorDepth::Ansi16 => match quantize_ansi16((r, g, b)) {
+                index @ 0..8 => format!("\x1b[{}m", 30 + index),
+                index => format!("\x1b[{}m", 90 + index - 8),
+            },
+            ColorDepth::Ansi256 => format!("\x1b[38;5;{}m", quantize_ansi256((r, g, b))),
+            ColorDepth::TrueColor => format!("\x1b[38;2;{r};{g};{b}m"),
+        }
+    }
+}
+
+/// 将 24 位颜色量化为 256 色调色板的索引
+///
+/// 分别在 6x6x6 颜色立方体（16-231）和灰阶（232-255）中取最接近的颜色，再选择两者中距离更近的一个
+pub fn quantize_ansi256((r, g, b): (u8, u8, u8)) -> u8 {
+    let cube_index = |value: u8| match value {
+        0..48 => 0,
+        48..115 => 1,
+        _ => (value - 35) / 40,
+    };
+    let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+    let cube = (
+        CUBE_LEVELS[ri as usize],
+        CUBE_LEVELS[gi as usize],
+        CUBE_LEVELS[bi as usize],
+    );
+
+    let average = (r as u32 + g as u32 + b as u32) / 3;
+    let gray_index = (average.saturating_sub(3) / 10).min(23) as u8;
+    let gray_level = 8 + 10 * gray_index;
+
+    if distance((r, g, b), (gray_level, gray_level, gray_level)) < distance((r, g, b), cube) {
+        232 + gray_index
+    } else {
+        16 + 36 * ri + 6 * gi + bi
+    }
+}
+
+/// 将 24 位颜色量化为 16 色调色板的索引（0-7 为标准色，8-15 为高亮色）
+pub fn quantize_ansi16(rgb: (u8, u8, u8)) -> u8 {
+    ANSI16_PALETTE
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, color)| distance(rgb, **color))
+        .map_or(0, |(index, _)| index as u8)
+}
+
+/// 两个颜色之间的欧氏距离的平方
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let channel = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    channel(a.0, b.0) + channel(a.1, b.1) + channel(a.2, b.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_color_depth_from_env_vars() {
+        // NO_COLOR 优先于其他变量
+        assert_eq!(
+            ColorDepth::from_env_vars(Some("1"), Some("truecolor"), Some("xterm-256color")),
+            ColorDepth::None
+        );
+        // 空的 NO_COLOR 视为未设置
+        assert_eq!(
+            ColorDepth::from_env_vars(Some(""), Some("truecolor"), None),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env_vars(None, Some("24bit"), Some("xterm")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env_vars(None, None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::from_env_vars(None, None, Some("xterm-direct")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env_vars(None, None, Some("dumb")),
+            ColorDepth::None
+        );
+        assert_eq!(
+            ColorDepth::from_env_vars(None, None, Some("xterm")),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(
+            ColorDepth::from_env_vars(None, None, None),
+            ColorDepth::Ansi16
+        );
+    }
+
+    #[test]
+    fn test_quantize_ansi256() {
+        assert_eq!(quantize_ansi256((255, 0, 0)), 196);
+        assert_eq!(quantize_ansi256((0, 0, 0)), 16);
+        assert_eq!(quantize_ansi256((95, 135, 175)), 67);
+        // 中性灰落在灰阶区间
+        assert_eq!(quantize_ansi256((128, 128, 128)), 244);
+    }
+
+    #[test]
+    fn test_foreground_by_depth() {
+        let rgb = (255, 0, 0);
+        assert_eq!(ColorDepth::TrueColor.foreground(rgb), "\x1b[38;2;255;0;0m");
+        assert_eq!(ColorDepth::Ansi256.foreground(rgb), "\x1b[38;5;196m");
+        assert_eq!(ColorDepth::Ansi16.foreground(rgb), "\x1b[91m");
+        assert_eq!(ColorDepth::Ansi16.foreground((0, 200, 0)), "\x1b[32m");
+        assert_eq!(ColorDepth::None.foreground(rgb), "");
+    }
+}
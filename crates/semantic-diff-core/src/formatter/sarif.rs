@@ -0,0 +1,219 @@
+//! SARIF 输出
+//!
+//! 将代码切片对应的变更目标导出为最小化的 SARIF 2.1.0 文档，
+//! 供 GitHub 代码扫描等 CI 工具在 PR 上标注受变更影响的声明
+
+use crate::generator::CodeSlice;
+use crate::git::repo_relative_path;
+use serde::Serialize;
+use std::path::Path;
+
+/// SARIF 2.1.0 的 JSON Schema 地址
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+/// 所有结果共用的规则 ID
+const CHANGED_TARGET_RULE: &str = "changed-target";
+/// 结果路径相对的基准目录，代码扫描工具将其解析为检出的源码根目录
+const SRCROOT: &str = "%SRCROOT%";
+
+#[derive(Serialize)]
+struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Serialize)]
+struct Run {
+    tool: Tool,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: &'static str,
+    short_description: Message,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: &'static str,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    region: Region,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactLocation {
+    uri: String,
+    uri_base_id: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: u32,
+    end_line: u32,
+}
+
+/// 将代码切片导出为 SARIF 2.1.0 文档
+///
+/// 每个带有变更目标的切片生成一个 `note` 级别的结果，位置指向变更目标所在文件及其起止行；
+/// 文件路径转换为相对于 `repo_root` 的 URI 并以 `%SRCROOT%` 为基准，
+/// 共享依赖前言等没有变更目标的切片会被跳过
+pub fn to_sarif(slices: &[CodeSlice], repo_root: &Path) -> String {
+    let results = slices
+        .iter()
+        .filter_map(|slice| slice.change_target.as_ref())
+        .map(|target| {
+            // CST 行号从 0 开始，SARIF 的行号从 1 开始
+            let (start_line, end_line) = target.line_range();
+            SarifResult {
+                rule_id: CHANGED_TARGET_RULE,
+                level: "note",
+                message: Message {
+                    text: format!("Changed {}: {}", target.kind(), target.name()),
+                },
+                locations: vec![Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: ArtifactLocation {
+                            uri: repo_relative_path(target.file_path(), repo_root)
+                                .to_string_lossy()
+                                .replace('\\', "/"),
+                            uri_base_id: SRCROOT,
+                        },
+                        region: Region {
+                            start_line: start_line + 1,
+                            end_line: end_line + 1,
+                        },
+                    },
+                }],
+            }
+        })
+        .collect();
+
+    let log = SarifLog {
+        schema: SARIF_SCHEMA,
+        version: "2.1.0",
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: "semantic-diff",
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: env!("CARGO_PKG_REPOSITORY"),
+                    rules: vec![Rule {
+                        id: CHANGED_TARGET_RULE,
+                        short_description: Message {
+                            text: "Declaration changed by the analyzed commit".to_string(),
+                        },
+                    }],
+                },
+            },
+            results,
+        }],
+    };
+
+    // 文档只包含字符串和整数字段，序列化不会失败
+    serde_json::to_string_pretty(&log).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractor::{ChangeTarget, SemanticContext};
+    use crate::generator::CodeSliceGenerator;
+    use crate::parser::GoFunctionInfo;
+    use std::path::PathBuf;
+
+    fn create_test_function(name: &str, start_line: u32, end_line: u32) -> GoFunctionInfo {
+        GoFunctionInfo {
+            name: name.to_string(),
+            receiver: None,
+            parameters: Vec::new(),
+            return_types: Vec::new(),
+            type_parameters: Vec::new(),
+            body: "{\n    return\n}".to_string(),
+            start_line,
+            end_line,
+            byte_start: 0,
+            byte_end: 0,
+            doc_comment: None,
+            file_path: PathBuf::from("/work/repo/pkg/handler.go"),
+            body_references: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_to_sarif_required_fields() {
+        let generator = CodeSliceGenerator::new();
+        let mut slices: Vec<CodeSlice> = [
+            create_test_function("Handle", 9, 11),
+            create_test_function("helper", 20, 22),
+        ]
+        .into_iter()
+        .map(|function| {
+            let context = SemanticContext::new(ChangeTarget::Function(function));
+            generator.generate_slice(&context, &[]).unwrap()
+        })
+        .collect();
+        // 没有变更目标的切片不产生结果
+        let mut preamble = slices[0].clone();
+        preamble.change_target = None;
+        slices.push(preamble);
+
+        let sarif = to_sarif(&slices, Path::new("/work/repo"));
+        let value: serde_json::Value = serde_json::from_str(&sarif).unwrap();
+
+        assert_eq!(value["version"], "2.1.0");
+        assert!(value["$schema"].is_string());
+        let run = &value["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "semantic-diff");
+        assert_eq!(run["tool"]["driver"]["version"], env!("CARGO_PKG_VERSION"));
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "changed-target");
+        assert_eq!(results[0]["message"]["text"], "Changed function: Handle");
+
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "pkg/handler.go");
+        assert_eq!(location["artifactLocation"]["uriBaseId"], "%SRCROOT%");
+        assert_eq!(location["region"]["startLine"], 10);
+        assert_eq!(location["region"]["endLine"], 12);
+    }
+}
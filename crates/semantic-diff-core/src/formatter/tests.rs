@@ -4,12 +4,13 @@ use super::*;
 use crate::generator::CodeSlice;
 use crate::parser::{GoFunctionInfo, GoParameter, GoType};
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use tempfile::tempdir;
 
-/// 创建测试用的代码切片
-fn create_test_code_slice() -> CodeSlice {
-    let function = GoFunctionInfo {
+/// 创建测试用的函数信息
+fn create_test_function() -> GoFunctionInfo {
+    GoFunctionInfo {
         name: "TestFunction".to_string(),
         receiver: None,
         parameters: vec![GoParameter {
@@ -18,18 +19,41 @@ fn create_test_code_slice() -> CodeSlice {
                 name: "string".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
+            is_variadic: false,
         }],
         return_types: vec![GoType {
             name: "error".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "func TestFunction(param1 string) error {\n    return nil\n}".to_string(),
         start_line: 10,
         end_line: 12,
         file_path: PathBuf::from("test.go"),
-    };
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
+    }
+}
+
+/// 创建测试用的代码切片
+fn create_test_code_slice() -> CodeSlice {
+    let function = create_test_function();
 
     CodeSlice {
         header_comment: "// Test code slice\n// Generated for testing".to_string(),
@@ -42,8 +66,12 @@ fn create_test_code_slice() -> CodeSlice {
         line_mapping: [(11, 5)].iter().cloned().collect(),
         line_change_types: [(11, crate::git::DiffLineType::Context)].iter().cloned().collect(),
         involved_files: vec![PathBuf::from("test.go")],
+        line_files: HashMap::new(),
+        node_blocks: HashMap::new(),
         content: "// Test code slice\n// Generated for testing\n\nimport \"fmt\"\n\ntype TestStruct struct {\n    Field string\n}\n\nconst TestConst = \"test\"\n\nvar TestVar string\n\nfunc TestFunction(param1 string) error {\n    return nil\n}".to_string(),
         dependency_graph: None,
+        change_target: None,
+        target_versions: None,
     }
 }
 
@@ -59,6 +87,7 @@ fn test_formatter_config_default() {
     assert!(config.enable_colors);
     assert_eq!(config.block_title_style, BlockTitleStyle::Detailed);
     assert_eq!(config.indent_size, 4);
+    assert!(!config.include_dependency_graph);
 }
 
 #[test]
@@ -300,12 +329,94 @@ fn test_formatted_output_properties() {
 fn test_syntax_highlighter() {
     let highlighter = SyntaxHighlighter::new("go".to_string(), HighlightStyle::Inline);
 
-    let code = "func main() {\n    fmt.Println(\"Hello, World!\")\n}";
-    let result = highlighter.highlight(code);
+    let code = "func main() {\n    fmt.Println(\"Hello, World!\")\n    return\n}";
+    let result = highlighter.highlight(code).unwrap();
+
+    assert!(result.starts_with("\x1b[34mfunc\x1b[0m main() {"));
+    assert!(result.contains("    \x1b[34mreturn\x1b[0m\n"));
+    assert!(result.contains("fmt.Println(\x1b[33m\"Hello, World!\"\x1b[0m)"));
+
+    // 禁用颜色或不使用高亮时原样返回
+    let plain = SyntaxHighlighter::new("go".to_string(), HighlightStyle::Inline)
+        .with_colors(false)
+        .highlight(code)
+        .unwrap();
+    assert_eq!(plain, code);
+    let plain = SyntaxHighlighter::new("go".to_string(), HighlightStyle::None)
+        .highlight(code)
+        .unwrap();
+    assert_eq!(plain, code);
+}
 
-    assert!(result.is_ok());
-    // 目前的实现只是返回原始内容
-    assert_eq!(result.unwrap(), code);
+#[test]
+fn test_syntax_highlighter_source_range() {
+    let file_path = PathBuf::from("user.go");
+    let source_file = crate::analyzer::SourceAnalyzer::new_for_file(&file_path)
+        .unwrap()
+        .analyze_source(
+            &file_path,
+            "package main\n\n// User 表示用户\ntype User struct {\n    Age int\n}\n".to_string(),
+        )
+        .unwrap();
+
+    let highlighter = SyntaxHighlighter::new("go".to_string(), HighlightStyle::Inline);
+    let highlighted = highlighter.highlight_source_range(&source_file, 2, 4);
+    assert_eq!(
+        highlighted,
+        "\x1b[90m// User 表示用户\x1b[0m\n\
+         \x1b[34mtype\x1b[0m \x1b[96mUser\x1b[0m \x1b[34mstruct\x1b[0m {\n    \
+         Age \x1b[96mint\x1b[0m"
+    );
+
+    let plain = highlighter
+        .with_colors(false)
+        .highlight_source_range(&source_file, 2, 4);
+    assert_eq!(plain, "// User 表示用户\ntype User struct {\n    Age int");
+}
+
+#[test]
+fn test_render_plain_text_highlights_unchanged_code() {
+    let mut code_slice = create_test_code_slice();
+    code_slice.content =
+        "func Greet() string {\n    // 问候语\n    return \"hi\"\n}\nfunc Count() int {\n    return 42\n}"
+            .to_string();
+    code_slice.highlighted_lines = vec![6];
+    code_slice.line_change_types = [(6, crate::git::DiffLineType::Added)].into_iter().collect();
+
+    let config = FormatterConfig {
+        show_statistics: false,
+        show_file_paths: false,
+        ..Default::default()
+    };
+    let output = OutputRenderer::new(config.clone())
+        .render(&code_slice)
+        .unwrap()
+        .content;
+
+    // 未变更的行按 token 着色
+    let theme = ColorTheme::for_depth(config.color_depth);
+    assert!(output.contains(&format!(
+        "{}func\x1b[0m Greet() {}string\x1b[0m {{",
+        theme.keyword, theme.type_name
+    )));
+    assert!(output.contains(&format!("{}// 问候语\x1b[0m", theme.comment)));
+    assert!(output.contains(&format!(
+        "{}return\x1b[0m {}\"hi\"\x1b[0m",
+        theme.keyword, theme.string
+    )));
+    // 变更行整行保持变更类型的颜色，不插入 token 颜色
+    assert!(output.contains(&format!("{}+     return 42\x1b[0m\n", theme.added_line)));
+
+    // 禁用颜色时输出原样的代码
+    let plain = OutputRenderer::new(FormatterConfig {
+        enable_colors: false,
+        ..config
+    })
+    .render(&code_slice)
+    .unwrap()
+    .content;
+    assert!(plain.contains("   3|     return \"hi\"\n"));
+    assert!(!plain.contains('\x1b'));
 }
 
 #[test]
@@ -393,6 +504,151 @@ fn test_max_line_width() {
     assert!(result.is_ok());
 }
 
+#[test]
+fn test_max_line_width_wraps_at_token_boundaries() {
+    let long_line = "    total := calculateDiscountedTotal(orderItems, customerProfile.DiscountRate) + shippingCost";
+    let mut code_slice = create_test_code_slice();
+    code_slice.content = format!("func Checkout() int {{\n{long_line}\n    return total\n}}");
+    code_slice.highlighted_lines = vec![2];
+    code_slice.line_change_types = [(2, crate::git::DiffLineType::Added)].into_iter().collect();
+
+    let config = FormatterConfig {
+        show_statistics: false,
+        show_file_paths: false,
+        enable_colors: true,
+        max_line_width: Some(40),
+        ..Default::default()
+    };
+    let renderer = OutputRenderer::new(config);
+    let output = renderer.render(&code_slice).unwrap().content;
+
+    let lines: Vec<&str> = output.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.contains("total :="))
+        .expect("wrapped line should be rendered");
+    // 未变更的行按语法着色，`return` 关键字带有颜色
+    let end = lines
+        .iter()
+        .position(|line| line.ends_with("return\x1b[0m total"))
+        .unwrap();
+    let wrapped = &lines[start..end];
+    assert!(
+        wrapped.len() > 1,
+        "long line should be wrapped: {wrapped:?}"
+    );
+
+    for line in wrapped {
+        // ANSI 转义序列不计入宽度
+        assert!(visible_width(line) <= 40, "line too wide: {line:?}");
+    }
+    for line in &wrapped[1..] {
+        // 续行对齐到 "+ " 前缀之后，并在原缩进上额外缩进
+        assert!(
+            line.starts_with("          "),
+            "missing continuation indent: {line:?}"
+        );
+    }
+
+    // 标识符不会被拆开
+    let joined = wrapped.join(" ");
+    for identifier in [
+        "calculateDiscountedTotal",
+        "orderItems",
+        "customerProfile",
+        "DiscountRate",
+        "shippingCost",
+    ] {
+        assert!(
+            joined.contains(identifier),
+            "identifier split: {identifier}"
+        );
+    }
+}
+
+#[test]
+fn test_annotate_source_files_at_transitions() {
+    use crate::extractor::SemanticContext;
+    use crate::generator::CodeSliceGenerator;
+    use crate::parser::{GoTypeDefinition, GoTypeKind};
+
+    let function = create_test_function();
+    let mut context = SemanticContext::from_function(function);
+    context.add_type(GoTypeDefinition {
+        name: "TestStruct".to_string(),
+        kind: GoTypeKind::Struct,
+        definition: "type TestStruct struct {\n    Field string\n}".to_string(),
+        start_line: 0,
+        end_line: 2,
+        file_path: PathBuf::from("types.go"),
+        dependencies: vec![],
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
+    });
+
+    let code_slice = CodeSliceGenerator::new()
+        .generate_slice(&context, &[])
+        .unwrap();
+    // 每一行代码都记录了来源文件
+    assert!(
+        code_slice
+            .line_files
+            .values()
+            .any(|f| f.ends_with("types.go"))
+    );
+    assert!(
+        code_slice
+            .line_files
+            .values()
+            .any(|f| f.ends_with("test.go"))
+    );
+
+    let config = FormatterConfig {
+        show_statistics: false,
+        show_file_paths: false,
+        show_line_numbers: false,
+        enable_colors: false,
+        annotate_source_files: true,
+        ..Default::default()
+    };
+    let output = OutputRenderer::new(config)
+        .render(&code_slice)
+        .unwrap()
+        .content;
+    let lines: Vec<&str> = output.lines().collect();
+
+    let types_annotation = lines
+        .iter()
+        .position(|line| *line == "// File: types.go")
+        .expect("types.go annotation");
+    assert_eq!(lines[types_annotation + 1], "type TestStruct struct {");
+
+    // 切换到另一个文件时再次标注
+    let test_annotation = lines
+        .iter()
+        .position(|line| *line == "// File: test.go")
+        .expect("test.go annotation");
+    assert!(test_annotation > types_annotation);
+    assert!(lines[test_annotation + 1].starts_with("func TestFunction("));
+    assert_eq!(
+        lines
+            .iter()
+            .filter(|line| line.starts_with("// File: "))
+            .count(),
+        2
+    );
+
+    // 默认不标注
+    let output = OutputRenderer::with_default_config()
+        .render(&code_slice)
+        .unwrap()
+        .content;
+    assert!(!output.contains("// File: "));
+}
+
 #[test]
 fn test_empty_code_slice() {
     let empty_slice = CodeSlice {
@@ -406,8 +662,12 @@ fn test_empty_code_slice() {
         line_mapping: std::collections::HashMap::new(),
         line_change_types: std::collections::HashMap::new(),
         involved_files: vec![],
+        line_files: HashMap::new(),
+        node_blocks: HashMap::new(),
         content: String::new(),
         dependency_graph: None,
+        change_target: None,
+        target_versions: None,
     };
 
     let renderer = OutputRenderer::with_default_config();
@@ -431,7 +691,7 @@ fn test_markdown_highlighting_styles() {
     };
     let renderer = OutputRenderer::new(config);
     let result = renderer.render(&code_slice).unwrap();
-    assert!(result.content.contains("// >>>"));
+    assert!(result.content.contains("\n> "));
 
     // 分离式高亮
     let config = FormatterConfig {
@@ -564,11 +824,11 @@ fn test_apply_highlighting_markdown_inline() {
         "Should start with Go code block"
     );
     assert!(
-        highlighted.contains("// +++ line 2"),
-        "Should highlight line 2 with +++ comment"
+        highlighted.contains("\n+ line 2\n"),
+        "Should highlight line 2 with a diff-style + marker"
     );
     assert!(
-        highlighted.contains("line 1\n"),
+        highlighted.contains("\n  line 1\n"),
         "Should contain unchanged line 1"
     );
     assert!(
@@ -664,3 +924,524 @@ fn test_highlighting_with_colors_disabled() {
         "Should contain - prefix for removed line"
     );
 }
+
+#[test]
+fn test_render_dependency_tree_nests_types_under_users() {
+    use crate::extractor::SemanticContext;
+    use crate::generator::{CodeSliceGenerator, GeneratorConfig};
+    use crate::parser::{GoTypeDefinition, GoTypeKind};
+
+    let function = create_test_function();
+    let mut context = SemanticContext::from_function(function);
+    context.add_type(GoTypeDefinition {
+        name: "TestStruct".to_string(),
+        kind: GoTypeKind::Struct,
+        definition: "type TestStruct struct {\n    Field string\n}".to_string(),
+        start_line: 0,
+        end_line: 2,
+        file_path: PathBuf::from("types.go"),
+        dependencies: vec![],
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
+    });
+
+    let generator = CodeSliceGenerator::with_config(GeneratorConfig {
+        include_dependency_graph: true,
+        ..Default::default()
+    });
+    let code_slice = generator.generate_slice(&context, &[]).unwrap();
+
+    let config = FormatterConfig {
+        show_statistics: false,
+        show_file_paths: false,
+        enable_colors: false,
+        render_dependency_tree: true,
+        ..Default::default()
+    };
+    let output = OutputRenderer::new(config)
+        .render(&code_slice)
+        .unwrap()
+        .content;
+    let lines: Vec<&str> = output.lines().collect();
+
+    // 变更目标位于根部，并附带自身代码
+    let root = lines
+        .iter()
+        .position(|line| *line == "★ TestFunction (function)")
+        .expect("root node");
+    assert!(lines[root + 1].starts_with("  | func TestFunction("));
+
+    // 被使用的类型嵌套在函数之下
+    let type_node = lines
+        .iter()
+        .position(|line| *line == "    TestStruct (type)")
+        .expect("nested type node");
+    assert!(type_node > root);
+    assert_eq!(lines[type_node + 1], "      | type TestStruct struct {");
+
+    // HTML 模式下使用可折叠的嵌套节点
+    let html_config = FormatterConfig {
+        output_format: OutputFormat::Html,
+        render_dependency_tree: true,
+        ..Default::default()
+    };
+    let html = OutputRenderer::new(html_config)
+        .render(&code_slice)
+        .unwrap()
+        .content;
+    let root_summary = html
+        .find("<summary>★ TestFunction (function)</summary>")
+        .unwrap();
+    let type_summary = html.find("<summary>TestStruct (type)</summary>").unwrap();
+    assert!(type_summary > root_summary);
+    assert!(html[root_summary..type_summary].contains("<details open"));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_render_contexts_json_dependency_graph_toggle() {
+    use crate::extractor::SemanticContext;
+
+    let mut context = SemanticContext::from_function(create_test_function());
+    context.add_type(crate::parser::GoTypeDefinition {
+        name: "TestStruct".to_string(),
+        kind: crate::parser::GoTypeKind::Struct,
+        definition: "type TestStruct struct {}".to_string(),
+        start_line: 0,
+        end_line: 0,
+        file_path: PathBuf::from("types.go"),
+        dependencies: vec![],
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
+    });
+    let contexts = vec![context];
+
+    // 默认不附带依赖图
+    let json = OutputRenderer::with_default_config()
+        .render_contexts_json(&contexts)
+        .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(value[0]["change_target"]["name"], "TestFunction");
+    assert!(value[0].get("dependency_graph").is_none());
+
+    let config = FormatterConfig {
+        include_dependency_graph: true,
+        ..Default::default()
+    };
+    let json = OutputRenderer::new(config)
+        .render_contexts_json(&contexts)
+        .unwrap();
+    let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let graph = &value[0]["dependency_graph"];
+    assert_eq!(graph["root_node"], "function:TestFunction");
+    let node_ids: Vec<_> = graph["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["id"].as_str().unwrap())
+        .collect();
+    assert!(node_ids.contains(&"type:TestStruct"));
+
+    // 附带依赖图的输出仍可还原为语义上下文
+    let restored: Vec<SemanticContext> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored[0].change_target.name(), "TestFunction");
+}
+
+#[test]
+fn test_render_contexts_summary() {
+    use crate::extractor::{ChangeTarget, SemanticContext};
+    use crate::parser::{GoTypeDefinition, GoTypeKind};
+
+    let test_struct = GoTypeDefinition {
+        name: "TestStruct".to_string(),
+        kind: GoTypeKind::Struct,
+        definition: "type TestStruct struct {\n    Field string\n}".to_string(),
+        start_line: 0,
+        end_line: 2,
+        file_path: PathBuf::from("types.go"),
+        dependencies: vec![],
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
+    };
+    let mut function_context = SemanticContext::from_function(create_test_function());
+    function_context.add_type(test_struct.clone());
+    let type_context = SemanticContext::new(ChangeTarget::Type(test_struct));
+
+    let summary = OutputRenderer::with_default_config()
+        .render_contexts_summary(&[function_context, type_context]);
+    let lines: Vec<&str> = summary.lines().collect();
+
+    assert_eq!(lines[0], "2 changed declarations");
+    assert_eq!(
+        lines[2].split_whitespace().collect::<Vec<_>>(),
+        vec![
+            "KIND", "NAME", "FILE", "LINES", "TYPES", "FUNCS", "CONSTS", "VARS", "FILES"
+        ]
+    );
+    // 函数目标：行号从 1 开始，统计包含依赖类型、目标函数本身和两个涉及的文件
+    assert_eq!(
+        lines[3].split_whitespace().collect::<Vec<_>>(),
+        vec![
+            "function",
+            "TestFunction",
+            "test.go",
+            "11-13",
+            "1",
+            "1",
+            "0",
+            "0",
+            "2"
+        ]
+    );
+    assert_eq!(
+        lines[4].split_whitespace().collect::<Vec<_>>(),
+        vec![
+            "type",
+            "TestStruct",
+            "types.go",
+            "1-3",
+            "0",
+            "0",
+            "0",
+            "0",
+            "1"
+        ]
+    );
+    assert_eq!(lines.len(), 5);
+
+    // 概览不包含任何源码
+    assert!(!summary.contains("return nil"));
+    assert!(!summary.contains("Field string"));
+
+    // 代码切片渲染不支持概览格式
+    let renderer = OutputRenderer::new(FormatterConfig {
+        output_format: OutputFormat::Summary,
+        ..Default::default()
+    });
+    assert!(renderer.render(&create_test_code_slice()).is_err());
+}
+
+#[test]
+fn test_message_pack_is_not_rendered_from_code_slices() {
+    let renderer = OutputRenderer::new(FormatterConfig {
+        output_format: OutputFormat::MessagePack,
+        ..Default::default()
+    });
+    assert!(renderer.render(&create_test_code_slice()).is_err());
+
+    // 未启用 msgpack 特性时序列化返回配置错误而不是输出其他格式
+    #[cfg(not(feature = "msgpack"))]
+    assert!(matches!(
+        renderer.render_contexts_msgpack(&[]),
+        Err(SemanticDiffError::ConfigError(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_render_json_code_slice() {
+    let config = FormatterConfig {
+        output_format: OutputFormat::Json,
+        ..Default::default()
+    };
+    let renderer = OutputRenderer::new(config);
+    let mut code_slice = create_test_code_slice();
+    code_slice.highlighted_lines = vec![14, 15];
+    code_slice.line_change_types = [
+        (14, crate::git::DiffLineType::Context),
+        (15, crate::git::DiffLineType::Added),
+    ]
+    .into_iter()
+    .collect();
+    code_slice.line_files = (14..=16)
+        .map(|line| (line, PathBuf::from("test.go")))
+        .collect();
+
+    let formatted = renderer.render(&code_slice).unwrap();
+    assert_eq!(formatted.format, OutputFormat::Json);
+    let value: serde_json::Value = serde_json::from_str(&formatted.content).unwrap();
+
+    // 没有来源信息的行与来自 test.go 的行分为两组
+    let files = value["files"].as_array().unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files[0]["file"].is_null());
+    assert_eq!(files[1]["file"], "test.go");
+
+    let lines: Vec<&serde_json::Value> = files
+        .iter()
+        .flat_map(|group| group["lines"].as_array().unwrap())
+        .collect();
+    assert_eq!(lines.len(), code_slice.content.lines().count());
+
+    let highlighted: Vec<u64> = lines
+        .iter()
+        .filter(|line| line["highlighted"] == true)
+        .map(|line| line["number"].as_u64().unwrap())
+        .collect();
+    let stats = code_slice.get_stats();
+    assert_eq!(highlighted, vec![14, 15]);
+    assert_eq!(highlighted.len(), stats.highlighted_lines);
+    assert_eq!(value["stats"]["highlighted_lines"], stats.highlighted_lines);
+    assert_eq!(lines[14]["change_type"], "added");
+    assert!(lines[0].get("change_type").is_none());
+}
+
+#[test]
+fn test_render_markdown_fenced_blocks_per_file() {
+    let mut code_slice = create_test_code_slice();
+    code_slice.involved_files = vec![PathBuf::from("pkg/types.go"), PathBuf::from("test.go")];
+    code_slice.line_files = (6..=8)
+        .map(|line| (line, PathBuf::from("pkg/types.go")))
+        .chain((14..=16).map(|line| (line, PathBuf::from("test.go"))))
+        .collect();
+    code_slice.highlighted_lines = vec![15];
+    code_slice.line_change_types = [(15, crate::git::DiffLineType::Removed)]
+        .into_iter()
+        .collect();
+
+    let render = |block_title_style| {
+        let config = FormatterConfig {
+            output_format: OutputFormat::Markdown,
+            block_title_style,
+            ..Default::default()
+        };
+        OutputRenderer::new(config)
+            .render(&code_slice)
+            .unwrap()
+            .content
+    };
+
+    // 语言标记来自来源文件检测到的语言
+    let fence = format!("```{}", crate::parser::SupportedLanguage::Go.markdown_tag());
+    let content = render(BlockTitleStyle::Detailed);
+    assert_eq!(content.matches(&fence).count(), 3);
+    assert!(content.contains("### `pkg/types.go` (3 lines)"));
+    assert!(content.contains("### `test.go`"));
+    assert!(content.contains("\n-     return nil\n"));
+    assert!(content.contains("\n  type TestStruct struct {\n"));
+
+    let content = render(BlockTitleStyle::Minimal);
+    assert!(content.contains("### types.go\n"));
+
+    let content = render(BlockTitleStyle::None);
+    assert!(!content.contains("### `"));
+    assert_eq!(content.matches(&fence).count(), 3);
+}
+
+#[test]
+fn test_render_side_by_side_alignment() {
+    let renderer = OutputRenderer::new(FormatterConfig {
+        enable_colors: false,
+        max_line_width: None,
+        ..Default::default()
+    });
+    let old = "func Add(a, b int) int {\n    return a - b\n}";
+    let new = "// Add 返回两数之和\nfunc Add(a, b int) int {\n    return a + b\n}";
+
+    let rendered = renderer.render_side_by_side(old, new);
+    let rows: Vec<&str> = rendered.lines().collect();
+    assert_eq!(
+        rows,
+        vec![
+            // 插入的行左侧留空
+            format!("{} > // Add 返回两数之和", " ".repeat(24)),
+            "func Add(a, b int) int {   func Add(a, b int) int {".to_string(),
+            // 修改的行并排对齐
+            format!("    return a - b{} |     return a + b", " ".repeat(8)),
+            format!("}}{}   }}", " ".repeat(23)),
+        ]
+    );
+
+    // 删除的行右侧留空，设置最大行宽时按栏宽截断
+    let renderer = OutputRenderer::new(FormatterConfig {
+        enable_colors: false,
+        max_line_width: Some(23),
+        ..Default::default()
+    });
+    let rendered = renderer.render_side_by_side("keep\nremoved line here", "keep");
+    assert_eq!(rendered, "keep         keep\nremoved li <\n");
+}
+
+#[test]
+fn test_align_lines_pairs_within_hunks() {
+    let old = ["a", "b", "c", "d", "e"];
+    let new = ["a", "B", "c", "d", "E", "f"];
+
+    // 每个差异块内的删除行和新增行按顺序配对，未变更的行两侧对齐
+    assert_eq!(
+        align_lines(&old, &new),
+        vec![
+            (Some("a"), Some("a")),
+            (Some("b"), Some("B")),
+            (Some("c"), Some("c")),
+            (Some("d"), Some("d")),
+            (Some("e"), Some("E")),
+            (None, Some("f")),
+        ]
+    );
+}
+
+#[test]
+fn test_render_plain_text_side_by_side() {
+    let mut code_slice = create_test_code_slice();
+    code_slice.target_versions = Some(TargetVersions {
+        old: "func TestFunction() {\n    return nil\n}".to_string(),
+        new: "func TestFunction() {\n    return err\n}".to_string(),
+    });
+
+    let config = FormatterConfig {
+        enable_colors: false,
+        max_line_width: None,
+        side_by_side: true,
+        ..Default::default()
+    };
+    let content = OutputRenderer::new(config.clone())
+        .render(&code_slice)
+        .unwrap()
+        .content;
+    assert!(content.contains("    return nil        |     return err\n"));
+
+    // 未开启对照模式时按原有方式渲染
+    let content = OutputRenderer::new(FormatterConfig {
+        side_by_side: false,
+        ..config
+    })
+    .render(&code_slice)
+    .unwrap()
+    .content;
+    assert!(!content.contains(" | "));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_collapse_context_around_single_change() {
+    let mut code_slice = create_test_code_slice();
+    code_slice.content = std::iter::once("func Big() {".to_string())
+        .chain((2..100).map(|i| format!("    x{i} := {i}")))
+        .chain(std::iter::once("}".to_string()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    code_slice.highlighted_lines = vec![50];
+    code_slice.line_change_types = [(50, crate::git::DiffLineType::Added)].into();
+
+    let config = FormatterConfig {
+        show_statistics: false,
+        show_file_paths: false,
+        show_line_numbers: false,
+        enable_colors: false,
+        collapse_context: Some(3),
+        ..Default::default()
+    };
+    let content = OutputRenderer::new(config.clone())
+        .render(&code_slice)
+        .unwrap()
+        .content;
+
+    // 头部注释 2 行 + 前后两个折叠标记 + 变更行及其前后各 3 行
+    assert_eq!(content.lines().count(), 2 + 2 + 7);
+    assert!(content.contains("… 46 unchanged lines …\n    x47 := 47\n"));
+    assert!(content.contains("+     x50 := 50\n"));
+    assert!(content.contains("    x53 := 53\n… 47 unchanged lines …\n"));
+    assert!(!content.contains("x46 := 46"));
+
+    // JSON 输出不包含折叠标记，使用结构化字段记录折叠行数
+    let json = OutputRenderer::new(FormatterConfig {
+        output_format: OutputFormat::Json,
+        ..config
+    })
+    .render(&code_slice)
+    .unwrap()
+    .content;
+    assert!(!json.contains("unchanged lines"));
+    let document: serde_json::Value = serde_json::from_str(&json).unwrap();
+    let entries = document["files"][0]["lines"].as_array().unwrap();
+    assert_eq!(entries.len(), 2 + 7);
+    assert_eq!(entries[0]["collapsed_lines"], 46);
+    assert_eq!(entries[1]["number"], 47);
+    assert_eq!(entries[8]["collapsed_lines"], 47);
+}
+
+#[test]
+fn test_color_depth_controls_output() {
+    let theme = ColorTheme::for_depth(ColorDepth::Ansi256);
+    assert_eq!(theme.keyword, "\x1b[38;5;75m");
+    let theme = ColorTheme::for_depth(ColorDepth::TrueColor);
+    assert_eq!(theme.keyword, "\x1b[38;2;88;166;255m");
+    assert!(ColorTheme::for_depth(ColorDepth::None).keyword.is_empty());
+
+    // 终端不支持颜色时即使启用颜色也不输出转义序列
+    let renderer = OutputRenderer::new(FormatterConfig {
+        enable_colors: true,
+        color_depth: ColorDepth::None,
+        ..Default::default()
+    });
+    let content = renderer.render(&create_test_code_slice()).unwrap().content;
+    assert!(!content.contains('\x1b'));
+}
+
+#[test]
+fn test_render_plain_text_uses_theme_for_color_depth() {
+    let mut code_slice = create_test_code_slice();
+    code_slice.content = "func Remove() {\n    delete(items, key)\n}".to_string();
+    code_slice.highlighted_lines = vec![2];
+    code_slice.line_change_types = [(2, crate::git::DiffLineType::Removed)]
+        .into_iter()
+        .collect();
+
+    let cases = [
+        (ColorDepth::Ansi16, "\x1b[91m", "\x1b[94m", "\x1b[36m"),
+        (
+            ColorDepth::Ansi256,
+            "\x1b[38;5;203m",
+            "\x1b[38;5;75m",
+            "\x1b[38;5;80m",
+        ),
+        (
+            ColorDepth::TrueColor,
+            "\x1b[38;2;248;81;73m",
+            "\x1b[38;2;88;166;255m",
+            "\x1b[38;2;57;197;207m",
+        ),
+    ];
+    for (depth, removed, keyword, line_number) in cases {
+        let theme = ColorTheme::for_depth(depth);
+        assert_eq!(theme.removed_line, removed);
+        assert_eq!(theme.keyword, keyword);
+        assert_eq!(theme.line_number, line_number);
+
+        let renderer = OutputRenderer::new(FormatterConfig {
+            show_statistics: false,
+            show_file_paths: false,
+            color_depth: depth,
+            ..Default::default()
+        });
+        let output = renderer.render(&code_slice).unwrap().content;
+
+        // 变更行、行号和语法着色都使用该色深下的主题颜色
+        assert!(
+            output.contains(&format!("{removed}-     delete(items, key)\x1b[0m")),
+            "{depth:?}: {output:?}"
+        );
+        assert!(
+            output.contains(&format!(
+                "{line_number}   1|\x1b[0m {keyword}func\x1b[0m Remove()"
+            )),
+            "{depth:?}: {output:?}"
+        );
+        // 其他色深的转义序列不会出现
+        for (other_depth, _, other_keyword, _) in cases {
+            if other_depth != depth {
+                assert!(!output.contains(other_keyword), "{depth:?}: {output:?}");
+            }
+        }
+    }
+}
@@ -2,12 +2,22 @@
 //!
 //! 提供代码切片生成和格式化功能
 
-use crate::error::Result;
+use crate::analyzer::DependencyResolver;
+use crate::error::{Result, SemanticDiffError};
 use crate::extractor::{ChangeTarget, SemanticContext};
 use crate::git::{DiffHunk, DiffLineType};
-use crate::parser::{GoFunctionInfo, GoTypeDefinition, Import};
+use crate::parser::{
+    ChannelDir, GoFunctionInfo, GoType, GoTypeDefinition, Import, ParserFactory, SupportedLanguage,
+    go::IOTA,
+};
+use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::PathBuf;
+use tree_sitter::Node;
+
+pub mod changelog;
+pub mod stats;
 
 /// 代码切片生成器
 pub struct CodeSliceGenerator {
@@ -39,6 +49,69 @@ pub struct GeneratorConfig {
     pub output_format: OutputFormat,
     /// 高亮样式
     pub highlight_style: HighlightStyle,
+    /// 按变更种类和分析信号注入到头部注释中的审查清单
+    pub review_checklist: ReviewChecklist,
+    /// 仓库根目录，设置后统一差异头部中的路径相对于它，使输出可以直接交给 `git apply`
+    pub repo_root: Option<PathBuf>,
+}
+
+/// 审查清单配置
+///
+/// 每个变更目标会收集与其种类及分析信号匹配的全部条目，去重后按配置顺序渲染
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ReviewChecklist {
+    /// 按变更目标种类（见 [`ChangeTarget::kind`]）注入的条目
+    pub by_kind: HashMap<String, Vec<String>>,
+    /// 破坏性变更时注入的条目
+    pub breaking: Vec<String>,
+    /// 导出函数缺少测试时注入的条目
+    pub untested: Vec<String>,
+    /// 变更涉及并发时注入的条目
+    pub concurrency: Vec<String>,
+}
+
+impl ReviewChecklist {
+    /// 从 JSON 配置解析审查清单，缺省的字段视为空
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| SemanticDiffError::ConfigError(format!("Invalid review checklist: {e}")))
+    }
+
+    /// 是否没有配置任何条目
+    pub fn is_empty(&self) -> bool {
+        self.by_kind.values().all(Vec::is_empty)
+            && self.breaking.is_empty()
+            && self.untested.is_empty()
+            && self.concurrency.is_empty()
+    }
+
+    /// 收集适用于指定上下文的条目
+    pub fn items_for(&self, context: &SemanticContext) -> Vec<&str> {
+        let kind_items = self
+            .by_kind
+            .get(context.change_target.kind())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let signal_items = [
+            (context.is_breaking(), &self.breaking),
+            (context.untested, &self.untested),
+            (context.touches_concurrency(), &self.concurrency),
+        ]
+        .into_iter()
+        .filter(|(matched, _)| *matched)
+        .flat_map(|(_, items)| items.iter());
+
+        let mut seen = HashSet::new();
+        kind_items
+            .iter()
+            .chain(signal_items)
+            .map(String::as_str)
+            .filter(|item| seen.insert(*item))
+            .collect()
+    }
 }
 
 /// 代码切片
@@ -64,22 +137,77 @@ pub struct CodeSlice {
     pub line_change_types: HashMap<u32, DiffLineType>,
     /// 涉及的文件路径
     pub involved_files: Vec<PathBuf>,
+    /// 切片中的行号到来源文件的映射
+    pub line_files: HashMap<u32, PathBuf>,
+    /// 依赖图节点 id 到对应代码块内容的映射
+    pub node_blocks: HashMap<String, String>,
     /// 生成的完整代码内容
     pub content: String,
     /// 依赖图
     pub dependency_graph: Option<crate::extractor::DependencyGraph>,
+    /// 切片对应的变更目标，共享依赖前言等不对应单个变更目标的切片为 None
+    pub change_target: Option<ChangeTarget>,
+    /// 变更目标在变更前后两个版本中的源码，设置后可按左右两栏对照渲染
+    pub target_versions: Option<TargetVersions>,
+}
+
+/// 变更目标在变更前后两个版本中的源码
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetVersions {
+    /// 变更前版本中的源码（取自变更前的 blob），新增的声明为空
+    pub old: String,
+    /// 变更后版本中的源码，删除的声明为空
+    pub new: String,
 }
 
 /// 输出格式
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OutputFormat {
     PlainText,
     Markdown,
     Html,
+    /// 统一差异格式，只包含变更目标范围内的差异行
+    UnifiedDiff,
+    /// JSON 格式
+    ///
+    /// 命令行直接序列化完整的语义上下文；`OutputRenderer::render` 则把代码切片渲染为
+    /// 带逐行高亮信息和统计信息的结构化文档
+    Json,
+    /// 紧凑的 MessagePack 二进制格式，直接序列化语义上下文而不渲染代码切片
+    ///
+    /// 序列化需要启用 `msgpack` 特性，未启用时返回配置错误
+    MessagePack,
+    /// 变更概览表格，每个变更目标一行，只包含种类、位置和依赖统计，不生成代码切片
+    Summary,
+    /// SARIF 2.1.0 文档，所有代码切片的变更目标汇总为一个文档
+    ///
+    /// 由 `formatter::sarif::to_sarif` 一次性生成，`OutputRenderer::render` 不支持逐切片渲染
+    Sarif,
+}
+
+impl OutputFormat {
+    /// 是否直接由语义上下文生成输出，而不是渲染代码切片
+    pub fn serializes_contexts(&self) -> bool {
+        match self {
+            OutputFormat::PlainText
+            | OutputFormat::Markdown
+            | OutputFormat::Html
+            | OutputFormat::UnifiedDiff
+            | OutputFormat::Sarif => false,
+            OutputFormat::Json | OutputFormat::MessagePack | OutputFormat::Summary => true,
+        }
+    }
+
+    /// 是否为二进制格式，二进制格式不能作为文本格式化或渲染
+    pub fn is_binary(&self) -> bool {
+        matches!(self, OutputFormat::MessagePack)
+    }
 }
 
 /// 高亮样式
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum HighlightStyle {
     None,
     Inline,
@@ -101,10 +229,14 @@ struct CodeBlock {
     title: String,
     lines: Vec<CodeLine>,
     block_type: BlockType,
+    /// 代码块来源文件（导入块没有单一来源）
+    source_file: Option<PathBuf>,
+    /// 代码块对应的依赖图节点 id（导入块不对应单个节点）
+    node_id: Option<String>,
 }
 
 /// 代码块类型
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum BlockType {
     Import,
     Type,
@@ -124,6 +256,8 @@ impl Default for GeneratorConfig {
             max_lines: None,
             output_format: OutputFormat::PlainText,
             highlight_style: HighlightStyle::Inline,
+            review_checklist: ReviewChecklist::default(),
+            repo_root: None,
         }
     }
 }
@@ -212,19 +346,295 @@ impl CodeSliceGenerator {
         };
 
         // 10. 生成最终的代码切片（包含 diff 信息）
-        let mut code_slice = self.build_code_slice_with_diff(
-            header_comment,
-            code_blocks,
-            involved_files.into_iter().collect(),
-            changes,
-        )?;
+        // 文件列表排序，保证相同输入的输出稳定
+        let mut involved_files: Vec<PathBuf> = involved_files.into_iter().collect();
+        involved_files.sort();
+        let mut code_slice =
+            self.build_code_slice_with_diff(header_comment, code_blocks, involved_files, changes)?;
 
-        // 添加依赖图
+        // 添加依赖图和变更目标
         code_slice.dependency_graph = dependency_graph;
+        code_slice.change_target = Some(context.change_target.clone());
+
+        // 统一差异模式下只输出变更目标范围内的差异
+        if self.config.output_format == OutputFormat::UnifiedDiff {
+            code_slice.content = self.unified_diff(&context.change_target, changes);
+        }
 
         Ok(code_slice)
     }
 
+    /// 生成限定在变更目标范围内的统一差异
+    ///
+    /// 只保留落在变更目标行范围内的差异行（删除的声明按旧文件行号判断，其余按新文件行号判断，
+    /// 删除行以其后一行的新行号为准），每个仍包含变更的差异块输出一个 `@@` 头。
+    /// 行数为 1 时省略行数，行数为 0 时起始行为插入位置的前一行，与 `git diff` 一致。
+    /// 配置了仓库根目录时头部路径相对于仓库根目录
+    fn unified_diff(&self, target: &ChangeTarget, hunks: &[DiffHunk]) -> String {
+        // 声明的行号是从 0 开始的 CST 行号，差异行号从 1 开始
+        let (start_line, end_line) = target.line_range();
+        let scope = (start_line + 1)..=(end_line + 1);
+        let path = match &self.config.repo_root {
+            Some(repo_root) => crate::git::repo_relative_path(target.file_path(), repo_root),
+            None => target.file_path().clone(),
+        };
+        let path = path.to_string_lossy().replace('\\', "/");
+
+        let mut output = format!("--- a/{path}\n+++ b/{path}\n");
+        for hunk in hunks {
+            let mut old_line = hunk.old_start;
+            let mut new_line = hunk.new_start;
+            let mut scoped_lines = Vec::new();
+            for line in &hunk.lines {
+                old_line = line.old_line_number.unwrap_or(old_line);
+                new_line = line.new_line_number.unwrap_or(new_line);
+                let position = if target.is_removed() {
+                    old_line
+                } else {
+                    new_line
+                };
+                if scope.contains(&position) {
+                    scoped_lines.push((line, old_line, new_line));
+                }
+                match line.line_type {
+                    DiffLineType::Context => {
+                        old_line += 1;
+                        new_line += 1;
+                    }
+                    DiffLineType::Removed => old_line += 1,
+                    DiffLineType::Added => new_line += 1,
+                }
+            }
+
+            if scoped_lines
+                .iter()
+                .all(|(line, _, _)| line.line_type == DiffLineType::Context)
+            {
+                continue;
+            }
+
+            let old_count = scoped_lines
+                .iter()
+                .filter(|(line, _, _)| line.line_type != DiffLineType::Added)
+                .count() as u32;
+            let new_count = scoped_lines
+                .iter()
+                .filter(|(line, _, _)| line.line_type != DiffLineType::Removed)
+                .count() as u32;
+            let (_, old_start, new_start) = scoped_lines[0];
+            output.push_str(&format!(
+                "@@ -{} +{} @@\n",
+                Self::unified_range(old_start, old_count),
+                Self::unified_range(new_start, new_count)
+            ));
+
+            for (line, _, _) in scoped_lines {
+                let prefix = match line.line_type {
+                    DiffLineType::Context => ' ',
+                    DiffLineType::Removed => '-',
+                    DiffLineType::Added => '+',
+                };
+                output.push(prefix);
+                output.push_str(&line.content);
+                output.push('\n');
+            }
+        }
+
+        output
+    }
+
+    /// 统一差异 `@@` 头中的行范围
+    fn unified_range(start: u32, count: u32) -> String {
+        match count {
+            0 => format!("{},0", start.saturating_sub(1)),
+            1 => start.to_string(),
+            _ => format!("{start},{count}"),
+        }
+    }
+
+    /// 并行生成多个代码切片
+    ///
+    /// 使用 rayon 按上下文并行生成，`changes_for` 返回每个上下文对应的差异块，
+    /// 结果顺序与输入顺序一致
+    pub fn generate_slices_parallel<F>(
+        &self,
+        contexts: &[SemanticContext],
+        changes_for: F,
+    ) -> Vec<Result<CodeSlice>>
+    where
+        F: Fn(&SemanticContext) -> Vec<DiffHunk> + Sync,
+    {
+        contexts
+            .par_iter()
+            .map(|context| self.generate_slice(context, &changes_for(context)))
+            .collect()
+    }
+
+    /// 只为变更目标本身生成代码切片（无上下文模式）
+    ///
+    /// 完全跳过依赖解析，切片中只包含变更目标的完整定义，适合大型提交的快速浏览。
+    /// `changes_for` 与 [`Self::generate_slices_parallel`] 相同，结果顺序与输入顺序一致
+    pub fn generate_targets_only<F>(
+        &self,
+        targets: &[ChangeTarget],
+        changes_for: F,
+    ) -> Vec<Result<CodeSlice>>
+    where
+        F: Fn(&SemanticContext) -> Vec<DiffHunk> + Sync,
+    {
+        let contexts: Vec<SemanticContext> =
+            targets.iter().cloned().map(SemanticContext::new).collect();
+        self.generate_slices_parallel(&contexts, changes_for)
+    }
+
+    /// 将多个上下文共享的依赖提取为单独的前言切片
+    ///
+    /// 在至少 `min_shared` 个上下文中出现的类型、常量、变量和依赖函数（按名称和来源文件判断）
+    /// 会从这些上下文中移除并记录到 `shared_dependencies`，统一在返回的前言切片中输出一次。
+    /// 没有满足条件的共享依赖时返回 None
+    pub fn hoist_shared_dependencies(
+        &self,
+        contexts: &mut [SemanticContext],
+        min_shared: usize,
+    ) -> Result<Option<CodeSlice>> {
+        // 至少要被两个上下文共享才有意义
+        let min_shared = min_shared.max(2);
+
+        let mut counts: HashMap<(BlockType, String, PathBuf), usize> = HashMap::new();
+        for context in contexts.iter() {
+            let keys: HashSet<_> = Self::dependency_keys(context).collect();
+            for key in keys {
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+        let shared: HashSet<_> = counts
+            .into_iter()
+            .filter(|(_, count)| *count >= min_shared)
+            .map(|(key, _)| key)
+            .collect();
+        if shared.is_empty() {
+            return Ok(None);
+        }
+
+        // 每个共享依赖只生成一次代码块，顺序与切片内的块顺序一致
+        let mut blocks: Vec<(BlockType, String, CodeBlock)> = Vec::new();
+        let mut involved_files = HashSet::new();
+        for context in contexts.iter_mut() {
+            let is_shared = |block_type: BlockType, name: &str, path: &PathBuf| {
+                shared.contains(&(block_type, name.to_string(), path.clone()))
+            };
+            let mut hoisted = Vec::new();
+            let mut hoist =
+                |block_type: BlockType, name: &str, path: &PathBuf, block: CodeBlock| {
+                    if !blocks.iter().any(|(t, n, b)| {
+                        *t == block_type && n == name && b.source_file.as_ref() == Some(path)
+                    }) {
+                        involved_files.insert(path.clone());
+                        blocks.push((block_type, name.to_string(), block));
+                    }
+                    hoisted.push(name.to_string());
+                };
+
+            for type_def in &context.related_types {
+                if is_shared(BlockType::Type, &type_def.name, &type_def.file_path) {
+                    hoist(
+                        BlockType::Type,
+                        &type_def.name,
+                        &type_def.file_path,
+                        self.generate_type_block(type_def),
+                    );
+                }
+            }
+            for constant in &context.constants {
+                if is_shared(BlockType::Constant, &constant.name, &constant.file_path) {
+                    hoist(
+                        BlockType::Constant,
+                        &constant.name,
+                        &constant.file_path,
+                        self.generate_constant_block(constant),
+                    );
+                }
+            }
+            for variable in &context.variables {
+                if is_shared(BlockType::Variable, &variable.name, &variable.file_path) {
+                    hoist(
+                        BlockType::Variable,
+                        &variable.name,
+                        &variable.file_path,
+                        self.generate_variable_block(variable),
+                    );
+                }
+            }
+            for function in &context.dependent_functions {
+                if is_shared(BlockType::Function, &function.name, &function.file_path) {
+                    hoist(
+                        BlockType::Function,
+                        &function.name,
+                        &function.file_path,
+                        self.generate_function_block(function),
+                    );
+                }
+            }
+
+            context
+                .related_types
+                .retain(|t| !is_shared(BlockType::Type, &t.name, &t.file_path));
+            context
+                .constants
+                .retain(|c| !is_shared(BlockType::Constant, &c.name, &c.file_path));
+            context
+                .variables
+                .retain(|v| !is_shared(BlockType::Variable, &v.name, &v.file_path));
+            context
+                .dependent_functions
+                .retain(|f| !is_shared(BlockType::Function, &f.name, &f.file_path));
+            context.shared_dependencies.extend(hoisted);
+        }
+
+        blocks.sort_by_key(|(block_type, _, _)| match block_type {
+            BlockType::Import => 0,
+            BlockType::Type => 1,
+            BlockType::Constant => 2,
+            BlockType::Variable => 3,
+            BlockType::Function => 4,
+        });
+
+        let header_comment = format!(
+            "// Shared definitions\n// Generated by semantic-diff\n// \n// {} definitions used by at least {} slices, emitted once here\n",
+            blocks.len(),
+            min_shared
+        );
+        let mut involved_files: Vec<PathBuf> = involved_files.into_iter().collect();
+        involved_files.sort();
+        let code_blocks = blocks.into_iter().map(|(_, _, block)| block).collect();
+
+        self.build_code_slice(header_comment, code_blocks, involved_files)
+            .map(Some)
+    }
+
+    /// 列出上下文中可被共享的依赖（代码块类型、名称、来源文件）
+    fn dependency_keys(
+        context: &SemanticContext,
+    ) -> impl Iterator<Item = (BlockType, String, PathBuf)> + '_ {
+        let types = context
+            .related_types
+            .iter()
+            .map(|t| (BlockType::Type, t.name.clone(), t.file_path.clone()));
+        let constants = context
+            .constants
+            .iter()
+            .map(|c| (BlockType::Constant, c.name.clone(), c.file_path.clone()));
+        let variables = context
+            .variables
+            .iter()
+            .map(|v| (BlockType::Variable, v.name.clone(), v.file_path.clone()));
+        let functions = context
+            .dependent_functions
+            .iter()
+            .map(|f| (BlockType::Function, f.name.clone(), f.file_path.clone()));
+        types.chain(constants).chain(variables).chain(functions)
+    }
+
     /// 高亮变更
     pub fn highlight_changes(&self, slice: &mut CodeSlice, changes: &[DiffHunk]) -> Result<()> {
         // 清空现有的高亮信息
@@ -260,9 +670,18 @@ impl CodeSliceGenerator {
             ChangeTarget::Type(t) => format!("Type: {}", t.name),
             ChangeTarget::Variable(v) => format!("Variable: {}", v.name),
             ChangeTarget::Constant(c) => format!("Constant: {}", c.name),
+            ChangeTarget::Renamed {
+                old_name, new_name, ..
+            } => format!("Renamed function: {old_name} -> {new_name}"),
+            ChangeTarget::Removed { declaration } => {
+                format!("Removed {}: {}", declaration.kind(), declaration.name())
+            }
+            ChangeTarget::Package { name, files } => {
+                format!("Package: {name} ({} files)", files.len())
+            }
         };
 
-        format!(
+        let mut header = format!(
             "// Semantic Context for {}\n// Generated by semantic-diff\n// \n// Context includes:\n//   - {} types\n//   - {} functions\n//   - {} constants\n//   - {} variables\n//   - {} imports\n//   - {} files\n",
             change_type,
             stats.types_count,
@@ -271,70 +690,165 @@ impl CodeSliceGenerator {
             stats.variables_count,
             stats.imports_count,
             stats.files_count
-        )
+        );
+
+        if context.untested {
+            header.push_str(&format!(
+                "// \n// Untested: no Test{} found\n",
+                context.change_target.name()
+            ));
+        }
+
+        if let Some(growth_ratio) = context.growth_ratio {
+            header.push_str(&format!(
+                "// \n// Large growth: function is {growth_ratio:.2}x its previous size\n"
+            ));
+        }
+
+        if !context.shared_dependencies.is_empty() {
+            header.push_str(&format!(
+                "// \n// Shared definitions (see preamble): {}\n",
+                context.shared_dependencies.join(", ")
+            ));
+        }
+
+        if !context.risk_signals.is_empty() {
+            header.push_str("// \n// Review risks introduced by this change:\n");
+            for signal in &context.risk_signals {
+                header.push_str(&format!(
+                    "//   - {} at {}:{}: {}\n",
+                    signal.kind.description(),
+                    signal.file_path.display(),
+                    signal.line,
+                    signal.content
+                ));
+            }
+        }
+
+        if !context.tag_changes.is_empty() {
+            header.push_str("// \n// Serialization tag changes:\n");
+            for change in &context.tag_changes {
+                let breaking = if change.is_wire_breaking() {
+                    " (wire-breaking)"
+                } else {
+                    ""
+                };
+                header.push_str(&format!(
+                    "//   - {}.{} {}: {} -> {}{}\n",
+                    change.type_name,
+                    change.field_name,
+                    change.tag_key,
+                    change.old_value.as_deref().unwrap_or("<none>"),
+                    change.new_value.as_deref().unwrap_or("<none>"),
+                    breaking
+                ));
+            }
+        }
+
+        let checklist = self.config.review_checklist.items_for(context);
+        if !checklist.is_empty() {
+            header.push_str("// \n// Review checklist:\n");
+            for item in checklist {
+                header.push_str(&format!("//   [ ] {item}\n"));
+            }
+        }
+
+        header
     }
 
     /// 生成导入声明块
+    ///
+    /// 与 gofmt 一致：单个导入使用单行声明，多个导入合并为分组导入块，
+    /// 标准库与其他包之间以空行分隔，组内按路径字母序排列
     fn generate_import_block(&self, imports: &[Import]) -> CodeBlock {
-        let mut lines = Vec::new();
-        let mut line_number = 1u32;
-
-        // 按路径排序导入
         let mut sorted_imports = imports.to_vec();
-        sorted_imports.sort_by(|a, b| a.path.cmp(&b.path));
+        DependencyResolver::sort_imports(&mut sorted_imports);
 
-        for import in sorted_imports {
-            let import_line = if let Some(alias) = &import.alias {
-                format!("import {} \"{}\"", alias, import.path)
-            } else {
-                format!("import \"{}\"", import.path)
-            };
+        let mut contents = Vec::new();
+        if let [import] = sorted_imports.as_slice() {
+            contents.push(import.to_go_declaration());
+        } else {
+            contents.push("import (".to_string());
+            let mut previous_group = None;
+            for import in &sorted_imports {
+                let group = DependencyResolver::is_standard_library(&import.path);
+                if previous_group.is_some_and(|previous| previous != group) {
+                    contents.push(String::new());
+                }
+                previous_group = Some(group);
+                contents.push(format!("\t{}", import.to_go_spec()));
+            }
+            contents.push(")".to_string());
+        }
 
-            lines.push(CodeLine {
-                content: import_line,
+        let lines = contents
+            .into_iter()
+            .zip(1u32..)
+            .map(|(content, line_number)| CodeLine {
+                content,
                 line_number,
                 is_highlighted: false,
                 change_type: None,
-            });
-            line_number += 1;
-        }
+            })
+            .collect();
 
         CodeBlock {
             title: "Imports".to_string(),
             lines,
             block_type: BlockType::Import,
+            source_file: None,
+            node_id: None,
         }
     }
 
     /// 生成类型定义块
     fn generate_type_block(&self, type_def: &GoTypeDefinition) -> CodeBlock {
-        let lines = self.split_into_lines(&type_def.definition, 1);
+        let lines =
+            self.split_with_doc_comment(type_def.doc_comment.as_deref(), &type_def.definition, 1);
 
         CodeBlock {
             title: format!("Type: {}", type_def.name),
             lines,
             block_type: BlockType::Type,
+            source_file: Some(type_def.file_path.clone()),
+            node_id: Some(format!("type:{}", type_def.name)),
         }
     }
 
     /// 生成常量定义块
     fn generate_constant_block(&self, constant: &crate::parser::GoConstantDefinition) -> CodeBlock {
+        // 单独声明的常量中 iota 总是 0，需要替换为它在原常量块中的值才能保持语义
+        let value = match constant.iota_index {
+            Some(index) if index > 0 => IOTA
+                .replace_all(&constant.value, index.to_string())
+                .into_owned(),
+            _ => constant.value.clone(),
+        };
+
         // 根据常量信息构建定义字符串
         let definition = if let Some(const_type) = &constant.const_type {
             format!(
                 "const {} {} = {}",
-                constant.name, const_type.name, constant.value
+                constant.name,
+                Self::format_go_type(const_type),
+                value
             )
         } else {
-            format!("const {} = {}", constant.name, constant.value)
+            format!("const {} = {}", constant.name, value)
         };
 
-        let lines = self.split_into_lines(&definition, constant.start_line);
+        let lines = self.split_with_doc_comment(
+            constant.doc_comment.as_deref(),
+            &definition,
+            constant.start_line,
+        );
 
         CodeBlock {
             title: format!("Constant: {}", constant.name),
             lines,
             block_type: BlockType::Constant,
+            source_file: Some(constant.file_path.clone()),
+            node_id: Some(format!("constant:{}", constant.name)),
         }
     }
 
@@ -345,11 +859,13 @@ impl CodeSliceGenerator {
             (Some(var_type), Some(initial_value)) => {
                 format!(
                     "var {} {} = {}",
-                    variable.name, var_type.name, initial_value
+                    variable.name,
+                    Self::format_go_type(var_type),
+                    initial_value
                 )
             }
             (Some(var_type), None) => {
-                format!("var {} {}", variable.name, var_type.name)
+                format!("var {} {}", variable.name, Self::format_go_type(var_type))
             }
             (None, Some(initial_value)) => {
                 format!("var {} = {}", variable.name, initial_value)
@@ -359,12 +875,18 @@ impl CodeSliceGenerator {
             }
         };
 
-        let lines = self.split_into_lines(&definition, variable.start_line);
+        let lines = self.split_with_doc_comment(
+            variable.doc_comment.as_deref(),
+            &definition,
+            variable.start_line,
+        );
 
         CodeBlock {
             title: format!("Variable: {}", variable.name),
             lines,
             block_type: BlockType::Variable,
+            source_file: Some(variable.file_path.clone()),
+            node_id: Some(format!("variable:{}", variable.name)),
         }
     }
 
@@ -375,6 +897,31 @@ impl CodeSliceGenerator {
             ChangeTarget::Type(type_def) => self.generate_type_block(type_def),
             ChangeTarget::Variable(var) => self.generate_variable_block(var),
             ChangeTarget::Constant(const_def) => self.generate_constant_block(const_def),
+            ChangeTarget::Renamed {
+                old_name, function, ..
+            } => {
+                let mut block = self.generate_function_block(function);
+                block.title = format!("Function: {old_name} -> {}", function.name);
+                block
+            }
+            ChangeTarget::Removed { declaration } => {
+                // 被删除的声明整体按删除行渲染
+                let mut block = self.generate_change_target_block(declaration);
+                block.title = format!("Removed {}", block.title);
+                for line in &mut block.lines {
+                    line.is_highlighted = true;
+                    line.change_type = Some(DiffLineType::Removed);
+                }
+                block
+            }
+            // 包内的声明已作为相关定义输出，这里只输出包声明，与导入同属文件头
+            ChangeTarget::Package { name, files } => CodeBlock {
+                title: format!("Package: {name}"),
+                lines: self.split_into_lines(&format!("package {name}"), 0),
+                block_type: BlockType::Import,
+                source_file: files.first().cloned(),
+                node_id: Some(format!("package:{name}")),
+            },
         }
     }
 
@@ -382,12 +929,18 @@ impl CodeSliceGenerator {
     fn generate_function_block(&self, function: &GoFunctionInfo) -> CodeBlock {
         // 构建完整的函数定义，包括签名和函数体
         let full_function_definition = self.build_complete_function_definition(function);
-        let lines = self.split_into_lines(&full_function_definition, function.start_line);
+        let lines = self.split_with_doc_comment(
+            function.doc_comment.as_deref(),
+            &full_function_definition,
+            function.start_line,
+        );
 
         CodeBlock {
             title: format!("Function: {}", function.name),
             lines,
             block_type: BlockType::Function,
+            source_file: Some(function.file_path.clone()),
+            node_id: Some(format!("function:{}", function.name)),
         }
     }
 
@@ -447,8 +1000,18 @@ impl CodeSliceGenerator {
             signature.push_str(") ");
         }
 
-        // 3. 添加函数名
+        // 3. 添加函数名和泛型类型参数
         signature.push_str(&function.name);
+        if !function.type_parameters.is_empty() {
+            let type_params: Vec<String> = function
+                .type_parameters
+                .iter()
+                .map(|param| format!("{} {}", param.name, param.constraint))
+                .collect();
+            signature.push('[');
+            signature.push_str(&type_params.join(", "));
+            signature.push(']');
+        }
 
         // 4. 添加参数列表
         signature.push('(');
@@ -456,44 +1019,41 @@ impl CodeSliceGenerator {
             if i > 0 {
                 signature.push_str(", ");
             }
-            signature.push_str(&param.name);
-            signature.push(' ');
-            if param.param_type.is_pointer {
-                signature.push('*');
+            // 未命名参数（如 `func(int)`）只输出类型
+            if !param.name.is_empty() {
+                signature.push_str(&param.name);
+                signature.push(' ');
             }
-            if param.param_type.is_slice {
-                signature.push_str("[]");
+            if param.is_variadic {
+                signature.push_str("...");
             }
-            signature.push_str(&param.param_type.name);
+            signature.push_str(&Self::format_go_type(&param.param_type));
         }
         signature.push(')');
 
-        // 5. 添加返回类型
+        // 5. 添加返回类型（多个返回值或命名返回值需要括号）
         if !function.return_types.is_empty() {
+            let parenthesized = function.return_types.len() > 1
+                || function
+                    .return_types
+                    .iter()
+                    .any(|r| r.result_name.is_some());
+
             signature.push(' ');
-            if function.return_types.len() == 1 {
-                let return_type = &function.return_types[0];
-                if return_type.is_pointer {
-                    signature.push('*');
-                }
-                if return_type.is_slice {
-                    signature.push_str("[]");
-                }
-                signature.push_str(&return_type.name);
-            } else {
+            if parenthesized {
                 signature.push('(');
-                for (i, return_type) in function.return_types.iter().enumerate() {
-                    if i > 0 {
-                        signature.push_str(", ");
-                    }
-                    if return_type.is_pointer {
-                        signature.push('*');
-                    }
-                    if return_type.is_slice {
-                        signature.push_str("[]");
-                    }
-                    signature.push_str(&return_type.name);
+            }
+            for (i, return_type) in function.return_types.iter().enumerate() {
+                if i > 0 {
+                    signature.push_str(", ");
+                }
+                if let Some(result_name) = &return_type.result_name {
+                    signature.push_str(result_name);
+                    signature.push(' ');
                 }
+                signature.push_str(&Self::format_go_type(return_type));
+            }
+            if parenthesized {
                 signature.push(')');
             }
         }
@@ -501,6 +1061,116 @@ impl CodeSliceGenerator {
         signature
     }
 
+    /// 将类型信息还原为 Go 类型文本，通道类型会带上方向箭头，数组带上长度，映射类型按键值重建
+    fn format_go_type(go_type: &GoType) -> String {
+        let mut text = String::new();
+        if go_type.is_channel {
+            text.push_str(match go_type.channel_direction {
+                Some(ChannelDir::SendOnly) => "chan<- ",
+                Some(ChannelDir::ReceiveOnly) => "<-chan ",
+                _ => "chan ",
+            });
+        }
+        if let Some(length) = &go_type.array_len {
+            text.push_str(&format!("[{length}]"));
+        }
+        if go_type.is_pointer {
+            text.push('*');
+        }
+        if go_type.is_slice {
+            text.push_str("[]");
+        }
+        match (&go_type.key_type, &go_type.value_type) {
+            (Some(key), Some(value)) => text.push_str(&format!(
+                "map[{}]{}",
+                Self::format_go_type(key),
+                Self::format_go_type(value)
+            )),
+            _ => text.push_str(&go_type.name),
+        }
+        text
+    }
+
+    /// 将声明分割为代码行
+    ///
+    /// 启用 `include_comments` 时在声明前保留其文档注释；关闭时不输出文档注释，
+    /// 并移除声明内部的行注释和块注释
+    fn split_with_doc_comment(
+        &self,
+        doc_comment: Option<&str>,
+        content: &str,
+        start_line: u32,
+    ) -> Vec<CodeLine> {
+        if !self.config.include_comments {
+            return self.split_without_comments(content, start_line);
+        }
+
+        match doc_comment {
+            Some(doc_comment) => {
+                let doc_lines = doc_comment.lines().count() as u32;
+                self.split_into_lines(
+                    &format!("{doc_comment}\n{content}"),
+                    start_line.saturating_sub(doc_lines),
+                )
+            }
+            None => self.split_into_lines(content, start_line),
+        }
+    }
+
+    /// 移除注释后将文本分割为代码行
+    ///
+    /// 注释由 CST 中的 `comment` 节点确定，字符串字面量中形似注释的内容不受影响。
+    /// 只包含注释的行被整行省略，其余行保留原始行号；解析失败时原样分割
+    fn split_without_comments(&self, content: &str, start_line: u32) -> Vec<CodeLine> {
+        let Ok(mut parser) = ParserFactory::create_parser(SupportedLanguage::Go) else {
+            return self.split_into_lines(content, start_line);
+        };
+        let Ok(tree) = parser.parse_source(content) else {
+            return self.split_into_lines(content, start_line);
+        };
+
+        let mut spans = Vec::new();
+        Self::collect_comment_spans(tree.root_node(), &mut spans);
+        if spans.is_empty() {
+            return self.split_into_lines(content, start_line);
+        }
+
+        // 删除注释内容但保留其中的换行，使剩余代码的行号不变
+        let mut stripped = String::with_capacity(content.len());
+        let mut position = 0;
+        for span in spans {
+            stripped.push_str(&content[position..span.start]);
+            stripped.extend(content[span.clone()].chars().filter(|&c| c == '\n'));
+            position = span.end;
+        }
+        stripped.push_str(&content[position..]);
+
+        content
+            .lines()
+            .zip(stripped.lines())
+            .enumerate()
+            .filter(|(_, (original, line))| !line.trim().is_empty() || original.trim().is_empty())
+            .map(|(index, (_, line))| CodeLine {
+                content: line.trim_end().to_string(),
+                line_number: start_line + index as u32,
+                is_highlighted: false,
+                change_type: None,
+            })
+            .collect()
+    }
+
+    /// 收集语法树中所有注释节点的字节范围，按出现顺序排列
+    fn collect_comment_spans(node: Node, spans: &mut Vec<Range<usize>>) {
+        if node.kind() == "comment" {
+            spans.push(node.byte_range());
+            return;
+        }
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            Self::collect_comment_spans(child, spans);
+        }
+    }
+
     /// 将文本分割为代码行
     fn split_into_lines(&self, content: &str, start_line: u32) -> Vec<CodeLine> {
         content
@@ -638,6 +1308,8 @@ impl CodeSliceGenerator {
         let mut highlighted_lines = Vec::new();
         let mut line_mapping = HashMap::new();
         let mut line_change_types = HashMap::new();
+        let mut line_files = HashMap::new();
+        let mut node_blocks = HashMap::new();
         let mut current_line = 1u32;
 
         // 添加头部注释
@@ -659,6 +1331,9 @@ impl CodeSliceGenerator {
 
                 // 记录行映射
                 line_mapping.insert(line.line_number, current_line);
+                if let Some(source_file) = &block.source_file {
+                    line_files.insert(current_line, source_file.clone());
+                }
 
                 // 记录高亮行和变更类型
                 if line.is_highlighted {
@@ -672,6 +1347,9 @@ impl CodeSliceGenerator {
             }
 
             content_parts.push(block_content.clone());
+            if let Some(node_id) = &block.node_id {
+                node_blocks.insert(node_id.clone(), block_content.clone());
+            }
 
             // 按类型分类存储
             match block.block_type {
@@ -698,8 +1376,12 @@ impl CodeSliceGenerator {
             line_mapping,
             line_change_types,
             involved_files,
+            line_files,
+            node_blocks,
             content,
             dependency_graph: None, // 将在 generate_slice 中设置
+            change_target: None,    // 将在 generate_slice 中设置
+            target_versions: None,
         };
 
         Ok(code_slice)
@@ -722,6 +1404,8 @@ impl CodeSliceGenerator {
         let mut highlighted_lines = Vec::new();
         let mut line_mapping = HashMap::new();
         let mut line_change_types = HashMap::new();
+        let mut line_files = HashMap::new();
+        let mut node_blocks = HashMap::new();
         let mut current_line = 1u32;
 
         // 添加头部注释
@@ -745,6 +1429,9 @@ impl CodeSliceGenerator {
 
                 // 记录行映射
                 line_mapping.insert(line.line_number, current_line);
+                if let Some(source_file) = &block.source_file {
+                    line_files.insert(current_line, source_file.clone());
+                }
 
                 // 记录高亮行和变更类型
                 if line.is_highlighted {
@@ -758,6 +1445,9 @@ impl CodeSliceGenerator {
             }
 
             content_parts.push(block_content.clone());
+            if let Some(node_id) = &block.node_id {
+                node_blocks.insert(node_id.clone(), block_content.clone());
+            }
 
             // 按类型分类存储
             match block.block_type {
@@ -784,8 +1474,12 @@ impl CodeSliceGenerator {
             line_mapping,
             line_change_types,
             involved_files,
+            line_files,
+            node_blocks,
             content,
             dependency_graph: None, // 将在 generate_slice 中设置
+            change_target: None,    // 将在 generate_slice 中设置
+            target_versions: None,
         };
 
         Ok(code_slice)
@@ -1032,6 +1726,14 @@ impl CodeFormatter {
             OutputFormat::PlainText => Ok(self.format_plain_text(content)),
             OutputFormat::Markdown => Ok(self.format_markdown(content)),
             OutputFormat::Html => Ok(self.format_html(content)),
+            OutputFormat::UnifiedDiff
+            | OutputFormat::Json
+            | OutputFormat::Summary
+            | OutputFormat::Sarif => Ok(self.format_plain_text(content)),
+            OutputFormat::MessagePack => Err(SemanticDiffError::ConfigError(format!(
+                "{:?} is a binary output format and cannot be formatted as text",
+                self.output_format
+            ))),
         }
     }
 
@@ -1108,6 +1810,7 @@ impl CodeSlice {
 
 /// 代码切片统计信息
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CodeSliceStats {
     pub total_lines: usize,
     pub highlighted_lines: usize,
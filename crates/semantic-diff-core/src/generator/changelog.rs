@@ -0,0 +1,118 @@
+//! 变更日志生成
+//!
+//! 根据声明的兼容性分类结果生成 Markdown 格式的发布说明草稿
+
+use crate::analyzer::{ClassifiedChange, CompatChange};
+
+/// 变更日志的分组标题，按输出顺序排列
+const SECTIONS: [&str; 4] = ["Breaking", "Added", "Changed", "Removed"];
+
+/// 生成 Markdown 变更日志
+///
+/// 破坏性修改（包括导出声明变为未导出）归入 Breaking 并附带原因，新增的导出声明归入 Added，
+/// 兼容的修改归入 Changed，删除的导出声明归入 Removed。内部变更不出现在日志中，
+/// 没有条目的分组会被省略
+pub fn render(changes: &[ClassifiedChange]) -> String {
+    let mut sections: [Vec<String>; 4] = Default::default();
+
+    for classified in changes {
+        let entry = format!(
+            "- `{}` ({})",
+            classified.name,
+            classified.file_path.display()
+        );
+        let (section, entry) = match &classified.change {
+            CompatChange::Breaking(reasons) => (0, format!("{entry}: {}", reasons.join("; "))),
+            CompatChange::VisibilityChanged {
+                now_exported: false,
+            } => (0, format!("{entry}: no longer exported")),
+            CompatChange::Additive => (1, entry),
+            CompatChange::VisibilityChanged { now_exported: true } => {
+                (1, format!("{entry}: now exported"))
+            }
+            CompatChange::Compatible => (2, entry),
+            CompatChange::Removed => (3, entry),
+            CompatChange::Internal => continue,
+        };
+        sections[section].push(entry);
+    }
+
+    let mut output = String::from("# Changelog\n");
+    for (title, entries) in SECTIONS.iter().zip(&sections) {
+        if entries.is_empty() {
+            continue;
+        }
+        output.push_str(&format!("\n## {title}\n\n"));
+        for entry in entries {
+            output.push_str(entry);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn classified(name: &str, file: &str, change: CompatChange) -> ClassifiedChange {
+        ClassifiedChange {
+            name: name.to_string(),
+            file_path: PathBuf::from(file),
+            change,
+        }
+    }
+
+    #[test]
+    fn test_render_groups_changes_by_heading() {
+        let changes = vec![
+            classified("function Save", "store.go", CompatChange::Additive),
+            classified(
+                "function Load",
+                "store.go",
+                CompatChange::Breaking(vec!["parameter count changed from 1 to 2".to_string()]),
+            ),
+            classified("type Config", "config.go", CompatChange::Compatible),
+            classified("function Legacy", "legacy.go", CompatChange::Removed),
+            classified("function helper", "store.go", CompatChange::Internal),
+            classified(
+                "function getUser",
+                "user.go",
+                CompatChange::VisibilityChanged {
+                    now_exported: false,
+                },
+            ),
+        ];
+
+        let changelog = render(&changes);
+        assert_eq!(
+            changelog,
+            "# Changelog\n\
+             \n## Breaking\n\n\
+             - `function Load` (store.go): parameter count changed from 1 to 2\n\
+             - `function getUser` (user.go): no longer exported\n\
+             \n## Added\n\n\
+             - `function Save` (store.go)\n\
+             \n## Changed\n\n\
+             - `type Config` (config.go)\n\
+             \n## Removed\n\n\
+             - `function Legacy` (legacy.go)\n"
+        );
+        assert!(!changelog.contains("helper"));
+    }
+
+    #[test]
+    fn test_render_omits_empty_sections() {
+        let changes = vec![
+            classified("function Save", "store.go", CompatChange::Additive),
+            classified("function helper", "store.go", CompatChange::Internal),
+        ];
+
+        let changelog = render(&changes);
+        assert!(changelog.contains("## Added"));
+        assert!(!changelog.contains("## Breaking"));
+        assert!(!changelog.contains("## Changed"));
+        assert!(!changelog.contains("## Removed"));
+    }
+}
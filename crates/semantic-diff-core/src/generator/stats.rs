@@ -0,0 +1,184 @@
+//! 统计报告生成
+//!
+//! 汇总一次分析生成的代码切片和语义上下文，输出供仪表盘等工具读取的 JSON 报告，
+//! 与面向阅读的主输出相互独立
+
+use super::{CodeSlice, CodeSliceStats};
+#[cfg(feature = "serde")]
+use crate::error::{Result, SemanticDiffError};
+use crate::extractor::{ContextStats, SemanticContext};
+#[cfg(feature = "serde")]
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// 单个变更目标的统计
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct TargetStats {
+    /// 变更目标名称
+    pub name: String,
+    /// 变更目标种类（见 `ChangeTarget::kind`）
+    pub kind: String,
+    /// 变更目标所在文件
+    pub file_path: PathBuf,
+    /// 渲染出的代码切片统计
+    pub slice: CodeSliceStats,
+    /// 语义上下文统计，未提取上下文（如 `--no-context`）时为空
+    pub context: Option<ContextStats>,
+}
+
+/// 整次分析的统计报告
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct StatsReport {
+    /// 变更目标总数
+    pub total_targets: usize,
+    /// 按种类统计的变更目标数
+    pub targets_by_kind: BTreeMap<String, usize>,
+    /// 所有代码切片（包括共享依赖前言）的总行数
+    pub total_lines: usize,
+    /// 所有代码切片的高亮行总数
+    pub highlighted_lines: usize,
+    /// 所有代码切片涉及的不同文件数
+    pub files_touched: usize,
+    /// 每个变更目标的统计，顺序与代码切片一致
+    pub targets: Vec<TargetStats>,
+}
+
+impl StatsReport {
+    /// 根据代码切片和对应的语义上下文构建报告
+    ///
+    /// 没有变更目标的切片（共享依赖前言）计入行数和文件数，但不计入目标；
+    /// 上下文按变更目标的名称、文件和行范围与切片对应
+    pub fn new(code_slices: &[CodeSlice], contexts: &[SemanticContext]) -> Self {
+        let mut targets_by_kind = BTreeMap::new();
+        let mut files_touched = BTreeSet::new();
+        let mut targets = Vec::new();
+        let mut total_lines = 0;
+        let mut highlighted_lines = 0;
+
+        for slice in code_slices {
+            let slice_stats = slice.get_stats();
+            total_lines += slice_stats.total_lines;
+            highlighted_lines += slice_stats.highlighted_lines;
+            files_touched.extend(slice.involved_files.iter().cloned());
+
+            let Some(target) = &slice.change_target else {
+                continue;
+            };
+            *targets_by_kind
+                .entry(target.kind().to_string())
+                .or_insert(0) += 1;
+
+            let context = contexts
+                .iter()
+                .find(|context| {
+                    let candidate = &context.change_target;
+                    candidate.name() == target.name()
+                        && candidate.file_path() == target.file_path()
+                        && candidate.line_range() == target.line_range()
+                })
+                .map(SemanticContext::get_stats);
+            targets.push(TargetStats {
+                name: target.name().to_string(),
+                kind: target.kind().to_string(),
+                file_path: target.file_path().clone(),
+                slice: slice_stats,
+                context,
+            });
+        }
+
+        Self {
+            total_targets: targets.len(),
+            targets_by_kind,
+            total_lines,
+            highlighted_lines,
+            files_touched: files_touched.len(),
+            targets,
+        }
+    }
+
+    /// 序列化为格式化的 JSON
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| SemanticDiffError::SerializationError(e.to_string()))
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::generator::CodeSliceGenerator;
+    use crate::parser::{GoFunctionInfo, GoTypeDefinition, GoTypeKind};
+
+    fn function(name: &str, file: &str) -> GoFunctionInfo {
+        GoFunctionInfo {
+            name: name.to_string(),
+            receiver: None,
+            parameters: Vec::new(),
+            return_types: Vec::new(),
+            body: format!("func {name}() {{\n    println(\"{name}\")\n}}"),
+            start_line: 0,
+            end_line: 2,
+            file_path: PathBuf::from(file),
+            body_references: Default::default(),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
+        }
+    }
+
+    #[test]
+    fn test_stats_report_aggregates_slices() {
+        let mut save = SemanticContext::from_function(function("Save", "store.go"));
+        save.add_type(GoTypeDefinition {
+            name: "Config".to_string(),
+            kind: GoTypeKind::Struct,
+            definition: "type Config struct {\n    Name string\n}".to_string(),
+            start_line: 0,
+            end_line: 2,
+            file_path: PathBuf::from("config.go"),
+            dependencies: Vec::new(),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            fields: Vec::new(),
+            doc_comment: None,
+        });
+        let load = SemanticContext::from_function(function("Load", "store.go"));
+        let contexts = vec![save, load];
+
+        let generator = CodeSliceGenerator::new();
+        let slices: Vec<CodeSlice> = contexts
+            .iter()
+            .map(|context| generator.generate_slice(context, &[]).unwrap())
+            .collect();
+        let report = StatsReport::new(&slices, &contexts);
+
+        assert_eq!(report.total_targets, 2);
+        assert_eq!(report.targets_by_kind.get("function"), Some(&2));
+        assert_eq!(
+            report.total_lines,
+            slices
+                .iter()
+                .map(|s| s.content.lines().count())
+                .sum::<usize>()
+        );
+        assert_eq!(report.highlighted_lines, 0);
+        assert_eq!(report.files_touched, 2);
+        assert_eq!(report.targets[0].name, "Save");
+        assert_eq!(report.targets[0].context.as_ref().unwrap().types_count, 1);
+        assert_eq!(report.targets[1].context.as_ref().unwrap().types_count, 0);
+
+        // 没有对应上下文的目标只报告切片统计
+        let target_only = StatsReport::new(&slices, &[]);
+        assert!(target_only.targets.iter().all(|t| t.context.is_none()));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["total_targets"], 2);
+        assert_eq!(json["targets"][1]["kind"], "function");
+    }
+}
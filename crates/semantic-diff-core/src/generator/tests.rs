@@ -3,7 +3,9 @@
 use super::*;
 use crate::extractor::SemanticContext;
 use crate::git::{DiffHunk, DiffLine, DiffLineType};
-use crate::parser::{GoFunctionInfo, GoParameter, GoType, GoTypeDefinition, GoTypeKind, Import};
+use crate::parser::{
+    GoFunctionInfo, GoParameter, GoType, GoTypeDefinition, GoTypeKind, Import, ImportKind,
+};
 use std::path::PathBuf;
 
 /// 创建测试用的函数信息
@@ -17,17 +19,35 @@ fn create_test_function() -> GoFunctionInfo {
                 name: "string".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
+            is_variadic: false,
         }],
         return_types: vec![GoType {
             name: "error".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "func TestFunction(param1 string) error {\n    return nil\n}".to_string(),
         start_line: 10,
         end_line: 12,
         file_path: PathBuf::from("test.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     }
 }
 
@@ -39,6 +59,13 @@ fn create_test_type() -> GoTypeDefinition {
         definition: "type TestStruct struct {\n    Field1 string\n    Field2 int\n}".to_string(),
         file_path: PathBuf::from("types.go"),
         dependencies: vec!["string".to_string(), "int".to_string()],
+        start_line: 0,
+        end_line: 0,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
     }
 }
 
@@ -54,6 +81,7 @@ fn create_test_context() -> SemanticContext {
     context.add_import(Import {
         path: "fmt".to_string(),
         alias: None,
+        kind: ImportKind::Normal,
     });
 
     context
@@ -106,6 +134,8 @@ fn test_code_slice_generator_with_config() {
         max_lines: Some(100),
         output_format: OutputFormat::Markdown,
         highlight_style: HighlightStyle::Separate,
+        review_checklist: ReviewChecklist::default(),
+        repo_root: None,
     };
 
     let generator = CodeSliceGenerator::with_config(config.clone());
@@ -150,6 +180,33 @@ fn test_generate_slice_basic() {
     assert!(slice.content.contains("fmt"), "Should contain import");
 }
 
+#[test]
+fn test_import_block_follows_gofmt_grouping() {
+    let mut context = SemanticContext::from_function(create_test_function());
+    for path in ["github.com/google/uuid", "time", "fmt"] {
+        context.add_import(Import {
+            path: path.to_string(),
+            alias: None,
+            kind: ImportKind::Normal,
+        });
+    }
+
+    let slice = CodeSliceGenerator::new()
+        .generate_slice(&context, &[])
+        .unwrap();
+    assert_eq!(
+        slice.imports,
+        vec!["import (\n\t\"fmt\"\n\t\"time\"\n\n\t\"github.com/google/uuid\"\n)\n"]
+    );
+    assert!(slice.content.contains(&slice.imports[0]));
+
+    // 单个导入保持单行声明
+    let slice = CodeSliceGenerator::new()
+        .generate_slice(&create_test_context(), &[])
+        .unwrap();
+    assert_eq!(slice.imports, vec!["import \"fmt\"\n"]);
+}
+
 #[test]
 fn test_generate_slice_without_imports() {
     let config = GeneratorConfig {
@@ -195,6 +252,93 @@ fn test_generate_slice_without_types() {
     );
 }
 
+#[test]
+fn test_generate_slices_parallel_matches_sequential() {
+    let generator = CodeSliceGenerator::new();
+
+    let contexts: Vec<SemanticContext> = (0..8)
+        .map(|i| {
+            let mut function = create_test_function();
+            function.name = format!("TestFunction{i}");
+            function.file_path = PathBuf::from(format!("file{i}.go"));
+            let mut context = SemanticContext::from_function(function);
+            context.add_type(create_test_type());
+            context.add_import(Import {
+                path: "fmt".to_string(),
+                alias: None,
+                kind: ImportKind::Normal,
+            });
+            context
+        })
+        .collect();
+
+    let changes_for = |context: &SemanticContext| {
+        if context.change_target.name().ends_with(['0', '2', '4']) {
+            vec![create_test_diff_hunk()]
+        } else {
+            Vec::new()
+        }
+    };
+
+    let sequential: Vec<CodeSlice> = contexts
+        .iter()
+        .map(|context| {
+            generator
+                .generate_slice(context, &changes_for(context))
+                .unwrap()
+        })
+        .collect();
+    let parallel: Vec<CodeSlice> = generator
+        .generate_slices_parallel(&contexts, changes_for)
+        .into_iter()
+        .map(|slice| slice.unwrap())
+        .collect();
+
+    assert_eq!(parallel.len(), sequential.len());
+    for (par, seq) in parallel.iter().zip(&sequential) {
+        assert_eq!(par.header_comment, seq.header_comment);
+        assert_eq!(par.content, seq.content);
+        assert_eq!(par.involved_files, seq.involved_files);
+        assert_eq!(par.highlighted_lines, seq.highlighted_lines);
+    }
+}
+
+#[test]
+fn test_generate_targets_only() {
+    let generator = CodeSliceGenerator::new();
+
+    // 参数类型 TestStruct 在完整模式下会被解析为相关类型
+    let mut function = create_test_function();
+    function.parameters[0].param_type.name = "TestStruct".to_string();
+    let targets = vec![
+        ChangeTarget::Function(function.clone()),
+        ChangeTarget::Type(create_test_type()),
+    ];
+
+    // 不传入任何源文件，生成器拿到的上下文中只有变更目标本身
+    let results = generator.generate_targets_only(&targets, |context| {
+        assert!(context.related_types.is_empty());
+        assert!(context.dependent_functions.is_empty());
+        assert!(context.imports.is_empty());
+        vec![create_test_diff_hunk()]
+    });
+    let slices: Vec<CodeSlice> = results.into_iter().map(|slice| slice.unwrap()).collect();
+    assert_eq!(slices.len(), 2);
+
+    let function_slice = &slices[0];
+    assert!(function_slice.content.contains("return nil"));
+    assert!(!function_slice.content.contains("Field1 string"));
+    assert!(function_slice.type_definitions.is_empty());
+    assert!(function_slice.imports.is_empty());
+    assert_eq!(function_slice.function_definitions.len(), 1);
+    assert_eq!(function_slice.involved_files, vec![function.file_path]);
+
+    let type_slice = &slices[1];
+    assert!(type_slice.content.contains("Field1 string"));
+    assert!(type_slice.function_definitions.is_empty());
+    assert_eq!(type_slice.involved_files, vec![PathBuf::from("types.go")]);
+}
+
 #[test]
 fn test_highlight_changes() {
     let generator = CodeSliceGenerator::new();
@@ -285,6 +429,17 @@ fn test_code_formatter_html_escaping() {
     assert!(formatted.contains("&quot;"), "Should escape \" character");
 }
 
+#[test]
+fn test_code_formatter_rejects_binary_formats() {
+    let formatter = CodeFormatter::new(OutputFormat::MessagePack);
+
+    let result = formatter.format_content("package main");
+    assert!(
+        matches!(result, Err(SemanticDiffError::ConfigError(_))),
+        "Binary formats should not be formatted as text"
+    );
+}
+
 // 注意：高亮功能测试已移至 formatter/tests.rs，因为高亮现在由 OutputRenderer 处理
 
 #[test]
@@ -473,17 +628,35 @@ fn test_build_function_signature() {
                 name: "string".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
+            is_variadic: false,
         }],
         return_types: vec![GoType {
             name: "error".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "return nil".to_string(),
         start_line: 1,
         end_line: 1,
         file_path: PathBuf::from("test.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let signature = generator.build_function_signature(&simple_function);
@@ -504,7 +677,14 @@ fn test_build_function_signature() {
                     name: "User".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
             GoParameter {
                 name: "param2".to_string(),
@@ -512,7 +692,14 @@ fn test_build_function_signature() {
                     name: "Config".to_string(),
                     is_pointer: true,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
         ],
         return_types: vec![
@@ -520,17 +707,34 @@ fn test_build_function_signature() {
                 name: "Result".to_string(),
                 is_pointer: true,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
             GoType {
                 name: "error".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
         ],
         body: "return &Result{}, nil".to_string(),
         start_line: 1,
         end_line: 1,
         file_path: PathBuf::from("service.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let method_signature = generator.build_function_signature(&method_function);
@@ -549,23 +753,72 @@ fn test_build_function_signature() {
                 name: "string".to_string(),
                 is_pointer: false,
                 is_slice: true,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
+            is_variadic: false,
         }],
         return_types: vec![GoType {
             name: "int".to_string(),
             is_pointer: false,
             is_slice: true,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "return make([]int, len(items))".to_string(),
         start_line: 1,
         end_line: 1,
         file_path: PathBuf::from("test.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let slice_signature = generator.build_function_signature(&slice_function);
     assert_eq!(slice_signature, "func sliceFunc(items []string) []int");
 }
 
+#[test]
+fn test_build_function_signature_variadic_round_trip() {
+    let generator = CodeSliceGenerator::new();
+    let source = r#"package main
+
+func Printf(format string, args ...interface{}) {
+}
+"#;
+
+    let file_path = PathBuf::from("printf.go");
+    let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path)
+        .expect("Failed to create analyzer");
+    let source_file = analyzer
+        .analyze_source(&file_path, source.to_string())
+        .expect("Failed to analyze source");
+    let go_info = source_file
+        .language_specific
+        .as_any()
+        .downcast_ref::<crate::parser::GoLanguageInfo>()
+        .expect("Should be GoLanguageInfo");
+    let function = go_info
+        .find_function("Printf")
+        .expect("Printf should be parsed");
+
+    assert!(function.parameters[1].is_variadic);
+    assert_eq!(
+        generator.build_function_signature(function),
+        "func Printf(format string, args ...interface{})"
+    );
+}
+
 #[test]
 fn test_build_complete_function_definition() {
     let generator = CodeSliceGenerator::new();
@@ -583,17 +836,35 @@ fn test_build_complete_function_definition() {
                 name: "string".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
+            is_variadic: false,
         }],
         return_types: vec![GoType {
             name: "error".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "if data == \"\" {\n    return errors.New(\"empty data\")\n}\nreturn nil".to_string(),
         start_line: 10,
         end_line: 14,
         file_path: PathBuf::from("service.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let complete_definition = generator.build_complete_function_definition(&function);
@@ -622,17 +893,35 @@ fn test_generate_function_block_with_signature() {
                 name: "Config".to_string(),
                 is_pointer: true,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
+            is_variadic: false,
         }],
         return_types: vec![GoType {
             name: "Service".to_string(),
             is_pointer: true,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "return &Service{\n    config: config,\n}".to_string(),
         start_line: 5,
         end_line: 8,
         file_path: PathBuf::from("service.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let block = generator.generate_function_block(&function);
@@ -672,18 +961,36 @@ fn test_function_body_with_existing_braces() {
                 name: "string".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
+            is_variadic: false,
         }],
         return_types: vec![GoType {
             name: "error".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         // 注意：这里的函数体已经包含了完整的大括号
         body: "{\n    if data == \"\" {\n        return errors.New(\"empty data\")\n    }\n    return nil\n}".to_string(),
         start_line: 10,
         end_line: 15,
         file_path: PathBuf::from("test.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let complete_definition = generator.build_complete_function_definition(&function_with_braces);
@@ -724,12 +1031,23 @@ fn test_function_body_without_braces() {
             name: "string".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         // 函数体只包含内容，没有大括号
         body: "return \"hello world\"".to_string(),
         start_line: 5,
         end_line: 5,
         file_path: PathBuf::from("test.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let complete_definition =
@@ -765,6 +1083,11 @@ fn test_empty_function_body() {
         start_line: 1,
         end_line: 1,
         file_path: PathBuf::from("test.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let complete_definition = generator.build_complete_function_definition(&empty_function);
@@ -794,7 +1117,14 @@ fn test_function_with_complex_body() {
                     name: "Context".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
             GoParameter {
                 name: "req".to_string(),
@@ -802,7 +1132,14 @@ fn test_function_with_complex_body() {
                     name: "Request".to_string(),
                     is_pointer: true,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
         ],
         return_types: vec![
@@ -810,17 +1147,34 @@ fn test_function_with_complex_body() {
                 name: "Response".to_string(),
                 is_pointer: true,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
             GoType {
                 name: "error".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
         ],
         body: "if req == nil {\n    return nil, errors.New(\"nil request\")\n}\n\nswitch req.Type {\ncase \"A\":\n    return s.handleA(ctx, req)\ncase \"B\":\n    return s.handleB(ctx, req)\ndefault:\n    return nil, errors.New(\"unknown type\")\n}".to_string(),
         start_line: 10,
         end_line: 20,
         file_path: PathBuf::from("service.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let complete_definition = generator.build_complete_function_definition(&complex_function);
@@ -846,3 +1200,459 @@ fn test_function_with_complex_body() {
         "Braces should be balanced"
     );
 }
+
+#[test]
+fn test_hoist_shared_dependencies_into_preamble() {
+    let config_type = GoTypeDefinition {
+        name: "Config".to_string(),
+        kind: GoTypeKind::Struct,
+        definition: "type Config struct {\n    Name string\n}".to_string(),
+        file_path: PathBuf::from("config.go"),
+        dependencies: vec![],
+        start_line: 0,
+        end_line: 2,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
+    };
+
+    let mut first = SemanticContext::from_function(create_test_function());
+    first.add_type(config_type.clone());
+    first.add_type(create_test_type());
+
+    let mut second_function = create_test_function();
+    second_function.name = "OtherFunction".to_string();
+    let mut second = SemanticContext::from_function(second_function);
+    second.add_type(config_type);
+
+    let generator = CodeSliceGenerator::new();
+    let mut contexts = vec![first, second];
+    let preamble = generator
+        .hoist_shared_dependencies(&mut contexts, 2)
+        .unwrap()
+        .expect("Config is shared by both slices");
+
+    // Config 只在前言中输出一次，只被一个切片使用的 TestStruct 保留在原处
+    assert_eq!(preamble.content.matches("type Config struct").count(), 1);
+    assert!(!preamble.content.contains("TestStruct"));
+    assert_eq!(preamble.involved_files, vec![PathBuf::from("config.go")]);
+
+    for context in &contexts {
+        assert!(context.related_types.iter().all(|t| t.name != "Config"));
+        assert_eq!(context.shared_dependencies, vec!["Config".to_string()]);
+
+        let slice = generator.generate_slice(context, &[]).unwrap();
+        assert!(!slice.content.contains("type Config struct"));
+        assert!(
+            slice
+                .header_comment
+                .contains("Shared definitions (see preamble): Config")
+        );
+    }
+    assert_eq!(contexts[0].related_types[0].name, "TestStruct");
+
+    // 阈值高于共享次数时不生成前言
+    let mut contexts = vec![create_test_context(), create_test_context()];
+    assert!(
+        generator
+            .hoist_shared_dependencies(&mut contexts, 3)
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(contexts[0].related_types.len(), 1);
+}
+
+#[test]
+fn test_build_function_signature_named_results() {
+    let generator = CodeSliceGenerator::new();
+    let source = r#"package main
+
+func Bare() (int, error) {
+}
+
+func Single() error {
+}
+
+func SingleNamed() (n int) {
+}
+
+func FullyNamed() (n int, err error) {
+}
+"#;
+
+    let file_path = PathBuf::from("results.go");
+    let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path)
+        .expect("Failed to create analyzer");
+    let source_file = analyzer
+        .analyze_source(&file_path, source.to_string())
+        .expect("Failed to analyze source");
+    let go_info = source_file
+        .language_specific
+        .as_any()
+        .downcast_ref::<crate::parser::GoLanguageInfo>()
+        .expect("Should be GoLanguageInfo");
+    let signature_of = |name: &str| {
+        let function = go_info
+            .find_function(name)
+            .expect("function should be parsed");
+        generator.build_function_signature(function)
+    };
+
+    assert_eq!(signature_of("Bare"), "func Bare() (int, error)");
+    assert_eq!(signature_of("Single"), "func Single() error");
+    assert_eq!(signature_of("SingleNamed"), "func SingleNamed() (n int)");
+    assert_eq!(
+        signature_of("FullyNamed"),
+        "func FullyNamed() (n int, err error)"
+    );
+
+    let fully_named = go_info.find_function("FullyNamed").unwrap();
+    assert_eq!(
+        fully_named
+            .return_types
+            .iter()
+            .map(|r| r.result_name.as_deref())
+            .collect::<Vec<_>>(),
+        vec![Some("n"), Some("err")]
+    );
+}
+
+#[test]
+fn test_build_function_signature_channel_round_trip() {
+    let generator = CodeSliceGenerator::new();
+    let source = r#"package main
+
+func Pipe(in <-chan Task, out chan<- *Result, done chan struct{}) chan error {
+}
+"#;
+
+    let file_path = PathBuf::from("channels.go");
+    let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path)
+        .expect("Failed to create analyzer");
+    let source_file = analyzer
+        .analyze_source(&file_path, source.to_string())
+        .expect("Failed to analyze source");
+    let go_info = source_file
+        .language_specific
+        .as_any()
+        .downcast_ref::<crate::parser::GoLanguageInfo>()
+        .expect("Should be GoLanguageInfo");
+    let function = go_info
+        .find_function("Pipe")
+        .expect("function should be parsed");
+
+    assert!(function.parameters.iter().all(|p| p.param_type.is_channel));
+    assert_eq!(
+        generator.build_function_signature(function),
+        "func Pipe(in <-chan Task, out chan<- *Result, done chan struct{}) chan error"
+    );
+}
+
+#[test]
+fn test_build_function_signature_map_round_trip() {
+    let generator = CodeSliceGenerator::new();
+    let source = r#"package main
+
+func Index(users map[int]*models.User, groups map[string]map[int]User) map[string][]User {
+}
+"#;
+
+    let file_path = PathBuf::from("maps.go");
+    let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path)
+        .expect("Failed to create analyzer");
+    let source_file = analyzer
+        .analyze_source(&file_path, source.to_string())
+        .expect("Failed to analyze source");
+    let go_info = source_file
+        .language_specific
+        .as_any()
+        .downcast_ref::<crate::parser::GoLanguageInfo>()
+        .expect("Should be GoLanguageInfo");
+    let function = go_info
+        .find_function("Index")
+        .expect("function should be parsed");
+
+    assert_eq!(
+        generator.build_function_signature(function),
+        "func Index(users map[int]*models.User, groups map[string]map[int]User) map[string][]User"
+    );
+}
+
+#[test]
+fn test_build_function_signature_array_round_trip() {
+    let generator = CodeSliceGenerator::new();
+    let source = r#"package main
+
+func Hash(key [16]byte, data []byte, grid [2][2]float64, cfg *Config) [MaxSize]*User {
+}
+"#;
+
+    let file_path = PathBuf::from("arrays.go");
+    let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path)
+        .expect("Failed to create analyzer");
+    let source_file = analyzer
+        .analyze_source(&file_path, source.to_string())
+        .expect("Failed to analyze source");
+    let go_info = source_file
+        .language_specific
+        .as_any()
+        .downcast_ref::<crate::parser::GoLanguageInfo>()
+        .expect("Should be GoLanguageInfo");
+    let function = go_info
+        .find_function("Hash")
+        .expect("function should be parsed");
+
+    assert_eq!(
+        generator.build_function_signature(function),
+        "func Hash(key [16]byte, data []byte, grid [2][2]float64, cfg *Config) [MaxSize]*User"
+    );
+}
+
+#[test]
+fn test_build_function_signature_renders_from_type_flags() {
+    let generator = CodeSliceGenerator::new();
+    let source = r#"package main
+
+func Load(items []*Item, buf *[]byte, opts ...*Option, out chan<- []string) (*Result, []error) {
+}
+"#;
+
+    let file_path = PathBuf::from("load.go");
+    let mut analyzer = crate::analyzer::SourceAnalyzer::new_for_file(&file_path)
+        .expect("Failed to create analyzer");
+    let source_file = analyzer
+        .analyze_source(&file_path, source.to_string())
+        .expect("Failed to analyze source");
+    let go_info = source_file
+        .language_specific
+        .as_any()
+        .downcast_ref::<crate::parser::GoLanguageInfo>()
+        .expect("Should be GoLanguageInfo");
+    let function = go_info
+        .find_function("Load")
+        .expect("function should be parsed");
+
+    // 类型名不包含已记录在标志中的前缀
+    let names: Vec<&str> = function
+        .parameters
+        .iter()
+        .map(|param| param.param_type.name.as_str())
+        .collect();
+    assert_eq!(names, vec!["*Item", "byte", "Option", "string"]);
+    assert_eq!(function.return_types[0].name, "Result");
+    assert_eq!(function.return_types[1].name, "error");
+
+    assert_eq!(
+        generator.build_function_signature(function),
+        "func Load(items []*Item, buf *[]byte, opts ...*Option, out chan<- []string) (*Result, []error)"
+    );
+}
+
+#[test]
+fn test_build_function_signature_type_parameters() {
+    let generator = CodeSliceGenerator::new();
+    let mut function = create_test_function();
+    function.name = "Keys".to_string();
+    function.type_parameters = vec![
+        crate::parser::GoTypeParam {
+            name: "K".to_string(),
+            constraint: "comparable".to_string(),
+        },
+        crate::parser::GoTypeParam {
+            name: "V".to_string(),
+            constraint: "Number".to_string(),
+        },
+    ];
+
+    assert_eq!(
+        generator.build_function_signature(&function),
+        "func Keys[K comparable, V Number](param1 string) error"
+    );
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_review_checklist_for_breaking_change() {
+    let checklist = ReviewChecklist::from_json(
+        r#"{
+            "by_kind": { "renamed": ["Are all call sites updated?"] },
+            "breaking": ["Is the breaking change noted in the changelog?", "Are all call sites updated?"],
+            "concurrency": ["Is shared state protected?"]
+        }"#,
+    )
+    .unwrap();
+    assert!(checklist.untested.is_empty());
+
+    let generator = CodeSliceGenerator::with_config(GeneratorConfig {
+        review_checklist: checklist,
+        ..Default::default()
+    });
+
+    let context = SemanticContext::new(ChangeTarget::Renamed {
+        old_name: "OldFunction".to_string(),
+        new_name: "TestFunction".to_string(),
+        function: create_test_function(),
+    });
+    assert!(context.is_breaking());
+
+    let slice = generator.generate_slice(&context, &[]).unwrap();
+    assert!(slice.header_comment.contains("// Review checklist:"));
+    assert!(
+        slice
+            .header_comment
+            .contains("//   [ ] Is the breaking change noted in the changelog?")
+    );
+    // 种类与信号重复的条目只渲染一次，未触发的信号不渲染
+    assert_eq!(
+        slice
+            .header_comment
+            .matches("Are all call sites updated?")
+            .count(),
+        1
+    );
+    assert!(!slice.header_comment.contains("Is shared state protected?"));
+
+    // 非破坏性变更不会注入破坏性变更条目
+    let plain = SemanticContext::from_function(create_test_function());
+    let slice = generator.generate_slice(&plain, &[]).unwrap();
+    assert!(!slice.header_comment.contains("Review checklist"));
+}
+
+#[test]
+fn test_doc_comment_follows_include_comments() {
+    let mut function = create_test_function();
+    function.doc_comment = Some("// TestFunction 处理请求\n// 返回处理错误".to_string());
+    let context = SemanticContext::from_function(function);
+
+    let generator = CodeSliceGenerator::new();
+    let slice = generator.generate_slice(&context, &[]).unwrap();
+    assert!(
+        slice
+            .content
+            .contains("// TestFunction 处理请求\n// 返回处理错误\nfunc TestFunction(")
+    );
+
+    let generator = CodeSliceGenerator::with_config(GeneratorConfig {
+        include_comments: false,
+        ..Default::default()
+    });
+    let slice = generator.generate_slice(&context, &[]).unwrap();
+    assert!(!slice.content.contains("TestFunction 处理请求"));
+}
+
+#[test]
+fn test_comments_stripped_when_disabled() {
+    let mut function = create_test_function();
+    function.doc_comment = Some("// TestFunction 处理请求".to_string());
+    function.start_line = 10;
+    function.body = "{\n    // 校验参数\n    url := \"http://example.com\" // 默认地址\n    /* 临时\n       禁用 */\n    return check(url /* inline */)\n}".to_string();
+    let context = SemanticContext::from_function(function);
+
+    let generator = CodeSliceGenerator::new();
+    let slice = generator.generate_slice(&context, &[]).unwrap();
+    for comment in [
+        "// TestFunction 处理请求",
+        "// 校验参数",
+        "// 默认地址",
+        "/* 临时",
+        "/* inline */",
+    ] {
+        assert!(slice.content.contains(comment), "missing {comment}");
+    }
+
+    let generator = CodeSliceGenerator::with_config(GeneratorConfig {
+        include_comments: false,
+        ..Default::default()
+    });
+    let slice = generator.generate_slice(&context, &[]).unwrap();
+    for comment in [
+        "处理请求",
+        "校验参数",
+        "默认地址",
+        "临时",
+        "禁用",
+        "inline",
+        "/*",
+    ] {
+        assert!(!slice.content.contains(comment), "unexpected {comment}");
+    }
+    // 字符串字面量中的 `//` 不是注释，只包含注释的行被整行省略
+    assert!(
+        slice
+            .content
+            .contains("{\n    url := \"http://example.com\"\n    return check(url )\n}")
+    );
+}
+
+#[test]
+fn test_unified_diff_scoped_to_change_target() {
+    let mut function = create_test_function();
+    function.name = "Add".to_string();
+    function.start_line = 2;
+    function.end_line = 4;
+    function.file_path = PathBuf::from("math.go");
+    let context = SemanticContext::from_function(function);
+
+    let line = |content: &str, line_type, old, new| DiffLine {
+        content: content.to_string(),
+        line_type,
+        old_line_number: old,
+        new_line_number: new,
+    };
+    let hunks = vec![
+        DiffHunk {
+            old_start: 2,
+            old_lines: 5,
+            new_start: 2,
+            new_lines: 5,
+            lines: vec![
+                line("", DiffLineType::Context, Some(2), Some(2)),
+                line(
+                    "func Add(a, b int) int {",
+                    DiffLineType::Context,
+                    Some(3),
+                    Some(3),
+                ),
+                line("    return a - b", DiffLineType::Removed, Some(4), None),
+                line("    return a + b", DiffLineType::Added, None, Some(4)),
+                line("}", DiffLineType::Context, Some(5), Some(5)),
+                line("", DiffLineType::Context, Some(6), Some(6)),
+            ],
+            context_lines: 3,
+        },
+        // 目标范围之外的变更不会出现在输出中
+        DiffHunk {
+            old_start: 20,
+            old_lines: 0,
+            new_start: 21,
+            new_lines: 1,
+            lines: vec![line("var x = 1", DiffLineType::Added, None, Some(21))],
+            context_lines: 3,
+        },
+    ];
+
+    let generator = CodeSliceGenerator::with_config(GeneratorConfig {
+        output_format: OutputFormat::UnifiedDiff,
+        ..Default::default()
+    });
+    let slice = generator.generate_slice(&context, &hunks).unwrap();
+    assert_eq!(
+        slice.content,
+        "--- a/math.go\n\
+         +++ b/math.go\n\
+         @@ -3,3 +3,3 @@\n \
+         func Add(a, b int) int {\n\
+         -    return a - b\n\
+         +    return a + b\n \
+         }\n"
+    );
+}
+
+#[test]
+fn test_unified_range() {
+    assert_eq!(CodeSliceGenerator::unified_range(4, 0), "3,0");
+    assert_eq!(CodeSliceGenerator::unified_range(5, 1), "5");
+    assert_eq!(CodeSliceGenerator::unified_range(5, 3), "5,3");
+}
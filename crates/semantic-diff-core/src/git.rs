@@ -4,13 +4,21 @@
 
 use crate::error::{Result, SemanticDiffError};
 use gix::{ObjectId, ThreadSafeRepository};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 
 /// Git 差异解析器
 pub struct GitDiffParser {
     repo: ThreadSafeRepository,
+    /// 差异块中变更前后保留的上下文行数
+    context_lines: u32,
 }
 
+/// 默认的上下文行数，与 `git diff` 一致
+const DEFAULT_CONTEXT_LINES: u32 = 3;
+
 /// 文件变更信息
 #[derive(Debug, Clone)]
 pub struct FileChange {
@@ -20,6 +28,41 @@ pub struct FileChange {
     pub is_binary: bool,
 }
 
+impl FileChange {
+    /// 判断源文件路径是否指向这次变更的文件
+    ///
+    /// 源文件路径可以是仓库相对路径，也可以是拼接了仓库根目录的路径；去掉根目录后按路径组件完整比较，
+    /// 因此 `pkg/a.go` 的变更不会匹配 `vendor/x/pkg/a.go`
+    pub fn matches_path(&self, path: &Path, repo_root: &Path) -> bool {
+        repo_relative_path(path, repo_root) == self.file_path
+    }
+}
+
+/// 提交范围中的一个分析单元
+///
+/// 启用 autosquash 时，fixup 提交不再单独出现，而是折叠进其目标提交
+#[derive(Debug, Clone)]
+pub struct RangeCommit {
+    /// 目标提交的完整哈希
+    pub commit_hash: String,
+    /// 提交标题（提交消息首行）
+    pub summary: String,
+    /// 折叠进该提交的 fixup 提交哈希，按提交先后排列
+    pub folded_commits: Vec<String>,
+    /// 折叠后的有效文件变更
+    pub changes: Vec<FileChange>,
+}
+
+/// 比较两个提交时旧版本的选取方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RangeBase {
+    /// 以两个提交的 merge-base 作为旧版本（等同于 `git diff from...to`）
+    #[default]
+    MergeBase,
+    /// 直接比较两个提交的树（等同于 `git diff from to`）
+    TwoDot,
+}
+
 /// 变更类型枚举
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ChangeType {
@@ -69,7 +112,23 @@ impl GitDiffParser {
             ))
         })?;
 
-        Ok(Self { repo })
+        Ok(Self {
+            repo,
+            context_lines: DEFAULT_CONTEXT_LINES,
+        })
+    }
+
+    /// 设置差异块的上下文行数（对应 `git diff -U<n>`）
+    ///
+    /// 更多的上下文可以让相邻的修改合并为一个差异块，并包含定位外层函数所需的闭合括号
+    pub fn with_context_lines(mut self, context_lines: u32) -> Self {
+        self.context_lines = context_lines;
+        self
+    }
+
+    /// 差异块的上下文行数
+    pub fn context_lines(&self) -> u32 {
+        self.context_lines
     }
 
     /// 解析指定提交的差异
@@ -80,16 +139,537 @@ impl GitDiffParser {
         // 获取仓库实例
         let repo = self.repo.to_thread_local();
 
-        // 获取提交对象
+        // 计算提交与其父提交之间的差异
+        let (parent_tree, current_tree) = self.commit_trees(commit_id, &repo)?;
+        self.get_commit_diff(parent_tree, current_tree, &repo)
+    }
+
+    /// 解析合并提交相对指定父提交的差异
+    ///
+    /// `parent_index` 从 0 开始，0 即第一父提交，与 [`Self::parse_commit`] 的结果相同；
+    /// 1 通常是被合并进来的分支，差异中只包含该分支之外引入的变更。
+    /// 索引超出父提交数量时返回 `RevisionNotFound`
+    pub fn parse_merge_commit(
+        &self,
+        commit_hash: &str,
+        parent_index: usize,
+    ) -> Result<Vec<FileChange>> {
+        let commit_id = self.parse_commit_hash(commit_hash)?;
+        let repo = self.repo.to_thread_local();
+
+        let (parent_tree, current_tree) =
+            self.commit_trees_with_parent(commit_id, parent_index, &repo)?;
+        self.get_commit_diff(parent_tree, current_tree, &repo)
+    }
+
+    /// 解析工作区相对 HEAD 的差异
+    ///
+    /// 包含已暂存和未暂存的全部修改，新版本直接读取工作区中的文件；
+    /// 未被忽略规则排除的未跟踪文件作为 `ChangeType::Added` 出现，全部内容都是新增行。
+    /// 跟踪文件的状态信息与索引一致时直接使用索引中的对象 ID，否则计算工作区内容的对象 ID，
+    /// 只有与 HEAD 不同的文件才会读取内容
+    pub fn parse_working_tree(&self) -> Result<Vec<FileChange>> {
+        let repo = self.repo.to_thread_local();
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| {
+                SemanticDiffError::GitError("Repository has no working tree".to_string())
+            })?
+            .to_path_buf();
+        let index = repo
+            .index_or_empty()
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to open index: {e}")))?;
+        let head_ids = self.head_blob_ids(&repo)?;
+
+        let mut old_files = BTreeMap::new();
+        let mut new_files = BTreeMap::new();
+        let mut indexed = HashSet::new();
+        for entry in index.entries() {
+            if !Self::is_file_entry(entry) {
+                continue;
+            }
+            let file_path = PathBuf::from(entry.path(&index).to_string());
+            indexed.insert(file_path.clone());
+            let head_id = head_ids.get(&file_path).copied();
+
+            // 工作区中已删除的跟踪文件不进入新版本，稍后表现为删除
+            let worktree_path = workdir.join(&file_path);
+            let Ok(metadata) = gix::index::fs::Metadata::from_path_no_follow(&worktree_path) else {
+                if let Some(head_id) = head_id {
+                    old_files.insert(file_path, Self::read_blob(&repo, head_id)?);
+                }
+                continue;
+            };
+
+            let is_stat_clean = Self::is_stat_clean(entry, &metadata, &index);
+            if is_stat_clean && Some(entry.id) == head_id {
+                continue;
+            }
+            let data = fs::read(&worktree_path)?;
+            if !is_stat_clean && Some(Self::blob_id(&repo, &data)?) == head_id {
+                continue;
+            }
+            if let Some(head_id) = head_id {
+                old_files.insert(file_path.clone(), Self::read_blob(&repo, head_id)?);
+            }
+            new_files.insert(file_path, data);
+        }
+        Self::collect_unindexed_head_files(&repo, &head_ids, &indexed, &mut old_files)?;
+        self.collect_untracked_files(&repo, &index, &workdir, &mut new_files)?;
+
+        self.diff_file_contents(old_files, new_files)
+    }
+
+    /// 解析暂存区相对 HEAD 的差异
+    ///
+    /// 只包含已暂存的修改，新版本读取索引中记录的 blob；
+    /// 索引条目的对象 ID 与 HEAD 树中相同的文件不读取内容
+    pub fn parse_staged(&self) -> Result<Vec<FileChange>> {
+        let repo = self.repo.to_thread_local();
+        let index = repo
+            .index_or_empty()
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to open index: {e}")))?;
+        let head_ids = self.head_blob_ids(&repo)?;
+
+        let mut old_files = BTreeMap::new();
+        let mut new_files = BTreeMap::new();
+        let mut indexed = HashSet::new();
+        for entry in index.entries() {
+            if !Self::is_file_entry(entry) {
+                continue;
+            }
+            let file_path = PathBuf::from(entry.path(&index).to_string());
+            indexed.insert(file_path.clone());
+            let head_id = head_ids.get(&file_path).copied();
+            if Some(entry.id) == head_id {
+                continue;
+            }
+            if let Some(head_id) = head_id {
+                old_files.insert(file_path.clone(), Self::read_blob(&repo, head_id)?);
+            }
+            new_files.insert(file_path, Self::read_blob(&repo, entry.id)?);
+        }
+        Self::collect_unindexed_head_files(&repo, &head_ids, &indexed, &mut old_files)?;
+
+        self.diff_file_contents(old_files, new_files)
+    }
+
+    /// 索引条目是否为普通文件（跳过符号链接和子模块）
+    fn is_file_entry(entry: &gix::index::Entry) -> bool {
+        use gix::index::entry::Mode;
+
+        entry.mode == Mode::FILE || entry.mode == Mode::FILE_EXECUTABLE
+    }
+
+    /// 工作区文件的状态信息是否与索引条目一致
+    ///
+    /// 修改时间不早于索引写入时间的条目处于竞争窗口中，状态一致也不能说明内容未变
+    fn is_stat_clean(
+        entry: &gix::index::Entry,
+        metadata: &gix::index::fs::Metadata,
+        index: &gix::index::State,
+    ) -> bool {
+        let options = gix::index::entry::stat::Options::default();
+        gix::index::entry::Stat::from_fs(metadata).is_ok_and(|stat| {
+            entry.stat.matches(&stat, options) && !entry.stat.is_racy(index.timestamp(), options)
+        })
+    }
+
+    /// 计算内容作为 blob 时的对象 ID
+    fn blob_id(repo: &gix::Repository, data: &[u8]) -> Result<ObjectId> {
+        gix::objs::compute_hash(repo.object_hash(), gix::objs::Kind::Blob, data)
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to hash file: {e}")))
+    }
+
+    /// 读取 HEAD 树中所有文件的对象 ID，尚无提交时为空
+    fn head_blob_ids(&self, repo: &gix::Repository) -> Result<BTreeMap<PathBuf, ObjectId>> {
+        let tree_id = repo
+            .head_tree_id_or_empty()
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to resolve HEAD tree: {e}")))?
+            .detach();
+        let tree = repo
+            .find_object(tree_id)
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to find HEAD tree: {e}")))?
+            .into_tree();
+        let entries =
+            tree.traverse().breadthfirst.files().map_err(|e| {
+                SemanticDiffError::GitError(format!("Failed to traverse tree: {e}"))
+            })?;
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| entry.mode.is_blob())
+            .map(|entry| (PathBuf::from(entry.filepath.to_string()), entry.oid))
+            .collect())
+    }
+
+    /// 读取 HEAD 中存在、但索引中没有对应普通文件条目的文件内容，作为旧版本
+    fn collect_unindexed_head_files(
+        repo: &gix::Repository,
+        head_ids: &BTreeMap<PathBuf, ObjectId>,
+        indexed: &HashSet<PathBuf>,
+        files: &mut BTreeMap<PathBuf, Vec<u8>>,
+    ) -> Result<()> {
+        for (file_path, head_id) in head_ids {
+            if !indexed.contains(file_path) {
+                files.insert(file_path.clone(), Self::read_blob(repo, *head_id)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// 收集工作区中未跟踪且未被忽略的文件
+    ///
+    /// 忽略规则包括 `.gitignore`、`$GIT_DIR/info/exclude` 和全局排除文件，被忽略的目录不会继续遍历
+    fn collect_untracked_files(
+        &self,
+        repo: &gix::Repository,
+        index: &gix::index::State,
+        workdir: &Path,
+        files: &mut BTreeMap<PathBuf, Vec<u8>>,
+    ) -> Result<()> {
+        let tracked: HashSet<PathBuf> = index
+            .entries()
+            .iter()
+            .map(|entry| PathBuf::from(entry.path(index).to_string()))
+            .collect();
+        let mut excludes = repo
+            .excludes(
+                index,
+                None,
+                gix::worktree::stack::state::ignore::Source::WorktreeThenIdMappingIfNotSkipped,
+            )
+            .map_err(|e| {
+                SemanticDiffError::GitError(format!("Failed to load ignore rules: {e}"))
+            })?;
+
+        let mut pending = vec![PathBuf::new()];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(workdir.join(&dir))? {
+                let entry = entry?;
+                let relative = dir.join(entry.file_name());
+                let file_type = entry.file_type()?;
+
+                if file_type.is_dir() {
+                    if relative == Path::new(".git") {
+                        continue;
+                    }
+                    let is_excluded = excludes
+                        .at_path(&relative, Some(gix::index::entry::Mode::DIR))?
+                        .is_excluded();
+                    if !is_excluded {
+                        pending.push(relative);
+                    }
+                } else if file_type.is_file() && !tracked.contains(&relative) {
+                    let is_excluded = excludes.at_path(&relative, None)?.is_excluded();
+                    if !is_excluded {
+                        let data = fs::read(workdir.join(&relative))?;
+                        files.insert(relative, data);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 比较两组按路径索引的文件内容，生成文件变更列表（按路径排序）
+    fn diff_file_contents(
+        &self,
+        old_files: BTreeMap<PathBuf, Vec<u8>>,
+        mut new_files: BTreeMap<PathBuf, Vec<u8>>,
+    ) -> Result<Vec<FileChange>> {
+        let mut changes = Vec::new();
+        for (file_path, old_data) in old_files {
+            match new_files.remove(&file_path) {
+                None => changes.push(self.content_change(
+                    file_path,
+                    ChangeType::Deleted,
+                    &old_data,
+                    &[],
+                )?),
+                Some(new_data) if new_data != old_data => changes.push(self.content_change(
+                    file_path,
+                    ChangeType::Modified,
+                    &old_data,
+                    &new_data,
+                )?),
+                Some(_) => {}
+            }
+        }
+        for (file_path, new_data) in new_files {
+            changes.push(self.content_change(file_path, ChangeType::Added, &[], &new_data)?);
+        }
+
+        changes.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+        Ok(changes)
+    }
+
+    /// 根据变更前后的文件内容构建文件变更
+    fn content_change(
+        &self,
+        file_path: PathBuf,
+        change_type: ChangeType,
+        old_data: &[u8],
+        new_data: &[u8],
+    ) -> Result<FileChange> {
+        let hunks = self.compute_line_diff(
+            &String::from_utf8_lossy(old_data),
+            &String::from_utf8_lossy(new_data),
+        )?;
+
+        Ok(FileChange {
+            file_path,
+            change_type,
+            hunks,
+            is_binary: is_binary_data(old_data) || is_binary_data(new_data),
+        })
+    }
+
+    /// 读取 blob 的内容
+    fn read_blob(repo: &gix::Repository, blob_id: ObjectId) -> Result<Vec<u8>> {
+        let blob = repo
+            .find_object(blob_id)
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to find blob: {e}")))?
+            .into_blob();
+        Ok(blob.data.clone())
+    }
+
+    /// 解析两个提交之间的累积差异
+    ///
+    /// 以 `from` 与 `to` 的 merge-base 作为旧版本，见 [`Self::parse_commit_range_with`]
+    pub fn parse_commit_range(&self, from: &str, to: &str) -> Result<Vec<FileChange>> {
+        self.parse_commit_range_with(from, to, RangeBase::MergeBase)
+    }
+
+    /// 按指定的基准解析两个提交之间的累积差异
+    ///
+    /// `RangeBase::MergeBase` 时，`from` 是 `to` 的祖先则 merge-base 就是 `from` 本身；
+    /// 两者分叉时只包含 `to` 一侧引入的变更，不会把 `from` 上独有的提交反向计入。
+    /// `RangeBase::TwoDot` 直接比较两个提交的树
+    pub fn parse_commit_range_with(
+        &self,
+        from: &str,
+        to: &str,
+        base: RangeBase,
+    ) -> Result<Vec<FileChange>> {
+        let from_id = self.parse_commit_hash(from)?;
+        let to_id = self.parse_commit_hash(to)?;
+        self.range_changes(from_id, to_id, base, from, to)
+    }
+
+    /// 解析 `head` 分支相对 `base` 分支引入的变更
+    ///
+    /// 两者都按引用名解析（分支、标签或远程分支，例如 `main`、`v1.0`、`origin/main`），
+    /// 不接受提交哈希；以两者的 merge-base 作为旧版本，等同于 `git diff base...head`。
+    /// 任一引用不存在时返回 `RevisionNotFound`
+    pub fn parse_branches(&self, base: &str, head: &str) -> Result<Vec<FileChange>> {
+        let base_id = self.resolve_reference(base)?;
+        let head_id = self.resolve_reference(head)?;
+        self.range_changes(base_id, head_id, RangeBase::MergeBase, base, head)
+    }
+
+    /// 将引用名解析为其指向的提交
+    fn resolve_reference(&self, name: &str) -> Result<ObjectId> {
+        let repo = self.repo.to_thread_local();
+        let mut reference = repo.find_reference(name).map_err(|e| {
+            SemanticDiffError::RevisionNotFound(format!(
+                "'{name}' does not name a branch or tag in this repository: {e}"
+            ))
+        })?;
+        let commit = reference.peel_to_commit().map_err(|e| {
+            SemanticDiffError::RevisionNotFound(format!("'{name}' does not point to a commit: {e}"))
+        })?;
+
+        Ok(commit.id)
+    }
+
+    /// 计算两个已解析提交之间的累积差异，`from`/`to` 仅用于错误信息
+    fn range_changes(
+        &self,
+        from_id: ObjectId,
+        to_id: ObjectId,
+        base: RangeBase,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<FileChange>> {
+        let repo = self.repo.to_thread_local();
+
+        let base_id = match base {
+            RangeBase::MergeBase => repo
+                .merge_base(from_id, to_id)
+                .map_err(|e| {
+                    SemanticDiffError::RevisionNotFound(format!(
+                        "Failed to find merge base of '{from}' and '{to}': {e}"
+                    ))
+                })?
+                .detach(),
+            RangeBase::TwoDot => from_id,
+        };
+
+        let (_, base_tree) = self.commit_trees(base_id, &repo)?;
+        let (_, to_tree) = self.commit_trees(to_id, &repo)?;
+        self.get_commit_diff(Some(base_tree), to_tree, &repo)
+    }
+
+    /// 解析提交范围 `<base>..<head>` 中的每个提交
+    ///
+    /// 沿第一父提交从 head 回溯到 base（不含 base），按提交先后顺序返回。
+    /// `autosquash` 为 true 时，`fixup!`/`squash!`/`amend!` 提交会折叠进其目标提交，
+    /// 目标的变更为从目标父提交到最后一个折叠提交之间、该组提交所涉及文件的有效差异
+    pub fn parse_range(&self, range: &str, autosquash: bool) -> Result<Vec<RangeCommit>> {
+        let (base, head) = range.split_once("..").ok_or_else(|| {
+            SemanticDiffError::InvalidCommitHash(format!(
+                "Invalid commit range '{range}', expected <base>..<head>"
+            ))
+        })?;
+        let base_id = self.parse_commit_hash(base)?;
+        let head_id = self.parse_commit_hash(head)?;
+        let repo = self.repo.to_thread_local();
+
+        // 沿第一父提交回溯，收集范围内的提交及其标题
+        let mut commits = Vec::new();
+        let mut current = Some(head_id);
+        while let Some(commit_id) = current {
+            if commit_id == base_id {
+                break;
+            }
+            let commit = repo
+                .find_object(commit_id)
+                .map_err(|e| {
+                    SemanticDiffError::GitError(format!("Failed to find commit {commit_id}: {e}"))
+                })?
+                .into_commit();
+            let summary = String::from_utf8_lossy(commit.message_raw_sloppy())
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            current = commit.parent_ids().next().map(|id| id.detach());
+            commits.push((commit_id, summary));
+        }
+        if current.is_none() {
+            return Err(SemanticDiffError::RevisionNotFound(format!(
+                "'{base}' is not an ancestor of '{head}'"
+            )));
+        }
+        commits.reverse();
+
+        // 分组：每组为目标提交下标及折叠进来的提交下标
+        let mut groups: Vec<(usize, Vec<usize>)> = Vec::new();
+        for (index, (_, summary)) in commits.iter().enumerate() {
+            let target_group = if autosquash {
+                self.fixup_target_subject(summary).and_then(|subject| {
+                    groups.iter().rposition(|(target, _)| {
+                        let (target_id, target_summary) = &commits[*target];
+                        target_summary == subject
+                            || (subject.len() >= 4 && target_id.to_string().starts_with(subject))
+                    })
+                })
+            } else {
+                None
+            };
+
+            match target_group {
+                Some(group) => groups[group].1.push(index),
+                None => groups.push((index, Vec::new())),
+            }
+        }
+
+        let mut result = Vec::with_capacity(groups.len());
+        for (target, folded) in groups {
+            let (target_id, summary) = &commits[target];
+            let (parent_tree, target_tree) = self.commit_trees(*target_id, &repo)?;
+
+            let changes = match folded.last() {
+                None => self.get_commit_diff(parent_tree, target_tree, &repo)?,
+                Some(&last) => {
+                    // 只保留组内提交实际涉及的文件，避免混入中间无关提交的变更
+                    let mut touched_files = HashSet::new();
+                    for &index in std::iter::once(&target).chain(folded.iter()) {
+                        let (old_tree, new_tree) = self.commit_trees(commits[index].0, &repo)?;
+                        for change in self.get_commit_diff(old_tree, new_tree, &repo)? {
+                            if let ChangeType::Renamed { old_path }
+                            | ChangeType::Copied { old_path } = &change.change_type
+                            {
+                                touched_files.insert(old_path.clone());
+                            }
+                            touched_files.insert(change.file_path);
+                        }
+                    }
+
+                    let (_, last_tree) = self.commit_trees(commits[last].0, &repo)?;
+                    let mut changes = self.get_commit_diff(parent_tree, last_tree, &repo)?;
+                    changes.retain(|change| touched_files.contains(&change.file_path));
+                    changes
+                }
+            };
+
+            result.push(RangeCommit {
+                commit_hash: target_id.to_string(),
+                summary: summary.clone(),
+                folded_commits: folded
+                    .iter()
+                    .map(|&index| commits[index].0.to_string())
+                    .collect(),
+                changes,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// 解析 fixup 类提交标题所指向的目标标题
+    ///
+    /// 与 `git rebase --autosquash` 一致，支持嵌套的前缀（如 `fixup! fixup! ...`）
+    fn fixup_target_subject<'a>(&self, summary: &'a str) -> Option<&'a str> {
+        let mut subject = summary;
+        let mut is_fixup = false;
+        while let Some(rest) = ["fixup! ", "squash! ", "amend! "]
+            .iter()
+            .find_map(|prefix| subject.strip_prefix(prefix))
+        {
+            subject = rest.trim_start();
+            is_fixup = true;
+        }
+        is_fixup.then_some(subject)
+    }
+
+    /// 获取提交父提交的树（初始提交为 None）和提交自身的树
+    fn commit_trees(
+        &self,
+        commit_id: ObjectId,
+        repo: &gix::Repository,
+    ) -> Result<(Option<ObjectId>, ObjectId)> {
+        self.commit_trees_with_parent(commit_id, 0, repo)
+    }
+
+    /// 获取提交的第 `parent_index` 个父提交的树和提交自身的树
+    ///
+    /// 没有父提交的初始提交只接受索引 0，此时父提交的树为 None
+    fn commit_trees_with_parent(
+        &self,
+        commit_id: ObjectId,
+        parent_index: usize,
+        repo: &gix::Repository,
+    ) -> Result<(Option<ObjectId>, ObjectId)> {
         let commit = repo
             .find_object(commit_id)
             .map_err(|e| {
-                SemanticDiffError::GitError(format!("Failed to find commit {commit_hash}: {e}"))
+                SemanticDiffError::GitError(format!("Failed to find commit {commit_id}: {e}"))
             })?
             .into_commit();
 
+        let parent_count = commit.parent_ids().count();
+        if parent_index > 0 && parent_index >= parent_count {
+            return Err(SemanticDiffError::RevisionNotFound(format!(
+                "Commit {commit_id} has {parent_count} parent(s), parent index {parent_index} is out of range"
+            )));
+        }
+
         // 获取父提交（如果存在）
-        let parent_tree = if let Some(parent_id) = commit.parent_ids().next() {
+        let parent_tree = if let Some(parent_id) = commit.parent_ids().nth(parent_index) {
             Some(
                 repo.find_object(parent_id)
                     .map_err(|e| {
@@ -99,7 +679,8 @@ impl GitDiffParser {
                     .tree_id()
                     .map_err(|e| {
                         SemanticDiffError::GitError(format!("Failed to get parent tree: {e}"))
-                    })?,
+                    })?
+                    .detach(),
             )
         } else {
             None
@@ -108,10 +689,10 @@ impl GitDiffParser {
         // 获取当前提交的树
         let current_tree = commit
             .tree_id()
-            .map_err(|e| SemanticDiffError::GitError(format!("Failed to get commit tree: {e}")))?;
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to get commit tree: {e}")))?
+            .detach();
 
-        // 计算差异
-        self.get_commit_diff(parent_tree.map(|id| id.into()), current_tree.into(), &repo)
+        Ok((parent_tree, current_tree))
     }
 
     /// 获取变更的文件列表
@@ -120,7 +701,75 @@ impl GitDiffParser {
         Ok(changes.into_iter().map(|change| change.file_path).collect())
     }
 
-    /// 解析提交哈希字符串为 ObjectId
+    /// 获取文件在指定提交的父提交中的内容
+    ///
+    /// 用于比较变更前后的声明；初始提交或父提交中不存在该文件时返回 None
+    pub fn get_parent_file_content(
+        &self,
+        commit_hash: &str,
+        file_path: &Path,
+    ) -> Result<Option<String>> {
+        let commit_id = self.parse_commit_hash(commit_hash)?;
+        let repo = self.repo.to_thread_local();
+
+        let commit = repo
+            .find_object(commit_id)
+            .map_err(|e| {
+                SemanticDiffError::GitError(format!("Failed to find commit {commit_hash}: {e}"))
+            })?
+            .into_commit();
+
+        let Some(parent_id) = commit.parent_ids().next() else {
+            return Ok(None);
+        };
+
+        let parent_tree = repo
+            .find_object(parent_id)
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to find parent commit: {e}")))?
+            .into_commit()
+            .tree()
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to get parent tree: {e}")))?;
+
+        let Some(entry) = parent_tree.lookup_entry_by_path(file_path).map_err(|e| {
+            SemanticDiffError::GitError(format!(
+                "Failed to look up {} in parent tree: {e}",
+                file_path.display()
+            ))
+        })?
+        else {
+            return Ok(None);
+        };
+
+        let blob = repo
+            .find_object(entry.object_id())
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to find blob: {e}")))?
+            .into_blob();
+
+        Ok(Some(String::from_utf8_lossy(&blob.data).into_owned()))
+    }
+
+    /// 获取文件变更在变更前的内容
+    ///
+    /// 修改和删除的文件读取原路径，重命名和复制的文件读取来源路径；
+    /// 新增的文件在父提交中没有对应的旧版本，返回 None
+    pub fn get_old_file_content(
+        &self,
+        commit_hash: &str,
+        file_change: &FileChange,
+    ) -> Result<Option<String>> {
+        let old_path = match &file_change.change_type {
+            ChangeType::Modified | ChangeType::Deleted => &file_change.file_path,
+            ChangeType::Renamed { old_path } | ChangeType::Copied { old_path } => old_path,
+            ChangeType::Added => return Ok(None),
+        };
+
+        self.get_parent_file_content(commit_hash, old_path)
+    }
+
+    /// 将修订字符串解析为提交的 ObjectId
+    ///
+    /// 支持完整哈希、缩写哈希（至少 4 位十六进制）以及引用名（如 `HEAD`、分支名、标签名），
+    /// 解析结果会被剥离到对应的提交对象
     fn parse_commit_hash(&self, commit_hash: &str) -> Result<ObjectId> {
         // 验证提交哈希格式
         if commit_hash.is_empty() {
@@ -129,30 +778,36 @@ impl GitDiffParser {
             ));
         }
 
-        // 支持短哈希和完整哈希
+        // 纯十六进制输入按哈希处理，长度需在缩写下限与完整哈希之间
         let hash_len = commit_hash.len();
-        if !(4..=40).contains(&hash_len) {
+        if commit_hash.chars().all(|c| c.is_ascii_hexdigit()) && !(4..=40).contains(&hash_len) {
             return Err(SemanticDiffError::InvalidCommitHash(format!(
                 "Invalid commit hash length: {hash_len}"
             )));
         }
 
-        // 验证哈希字符是否为十六进制
-        if !commit_hash.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(SemanticDiffError::InvalidCommitHash(format!(
-                "Invalid commit hash format: {commit_hash}"
-            )));
-        }
-
-        // 解析为 ObjectId
+        // 通过 rev-parse 解析缩写哈希和引用名
         let repo = self.repo.to_thread_local();
-        repo.rev_parse_single(commit_hash)
-            .map_err(|e| {
-                SemanticDiffError::InvalidCommitHash(format!(
-                    "Failed to resolve commit hash {commit_hash}: {e}"
-                ))
-            })
-            .map(|obj| obj.into())
+        let id = repo.rev_parse_single(commit_hash).map_err(|e| {
+            SemanticDiffError::RevisionNotFound(format!(
+                "'{commit_hash}' does not name a commit in this repository \
+                 (expected a commit hash of at least 4 hex characters or a ref name): {e}"
+            ))
+        })?;
+
+        // 标签等对象需要剥离到提交
+        let not_a_commit = |e: &dyn std::fmt::Display| {
+            SemanticDiffError::RevisionNotFound(format!(
+                "'{commit_hash}' does not point to a commit: {e}"
+            ))
+        };
+        let commit = id
+            .object()
+            .map_err(|e| not_a_commit(&e))?
+            .peel_to_kind(gix::object::Kind::Commit)
+            .map_err(|e| not_a_commit(&e))?;
+
+        Ok(commit.id)
     }
 
     /// 获取两个树之间的差异
@@ -176,7 +831,11 @@ impl GitDiffParser {
                 .map_err(|e| SemanticDiffError::GitError(format!("Failed to find new tree: {e}")))?
                 .into_tree();
 
-            // 使用 gix 的 changes 方法来计算差异
+            // 使用 gix 的 changes 方法来计算差异，除了默认的重命名检测外还检测复制
+            let rewrites = gix::diff::Rewrites {
+                copies: Some(gix::diff::rewrites::Copies::default()),
+                ..Default::default()
+            };
             old_tree_obj
                 .changes()
                 .map_err(|e| {
@@ -184,6 +843,9 @@ impl GitDiffParser {
                         "Failed to create tree changes iterator: {e}"
                     ))
                 })?
+                .options(|options| {
+                    options.track_rewrites(Some(rewrites));
+                })
                 .for_each_to_obtain_tree(&new_tree_obj, |change| {
                     match self.process_object_tree_change(change, repo) {
                         Ok(file_change) => {
@@ -383,13 +1045,7 @@ impl GitDiffParser {
             .map_err(|e| SemanticDiffError::GitError(format!("Failed to find blob: {e}")))?
             .into_blob();
 
-        let data = &blob.data;
-
-        // 简单的二进制文件检测：检查前 8192 字节中是否包含 null 字节
-        let check_size = std::cmp::min(data.len(), 8192);
-        let is_binary = data[..check_size].contains(&0);
-
-        Ok(is_binary)
+        Ok(is_binary_data(&blob.data))
     }
 
     /// 为新增文件生成差异块
@@ -426,7 +1082,7 @@ impl GitDiffParser {
             new_start: 1,
             new_lines: lines.len() as u32,
             lines: diff_lines,
-            context_lines: 3,
+            context_lines: self.context_lines,
         }])
     }
 
@@ -464,7 +1120,7 @@ impl GitDiffParser {
             new_start: 0,
             new_lines: 0,
             lines: diff_lines,
-            context_lines: 3,
+            context_lines: self.context_lines,
         }])
     }
 
@@ -489,54 +1145,111 @@ impl GitDiffParser {
             .into_blob();
         let new_content = String::from_utf8_lossy(&new_blob.data);
 
-        // 使用简单的行级差异计算
-        self.compute_simple_line_diff(&old_content, &new_content)
+        self.compute_line_diff(&old_content, &new_content)
     }
 
-    /// 计算简单的行级差异（不使用复杂的 diff 算法）
-    fn compute_simple_line_diff(
-        &self,
-        old_content: &str,
-        new_content: &str,
-    ) -> Result<Vec<DiffHunk>> {
-        let old_lines: Vec<&str> = old_content.lines().collect();
-        let new_lines: Vec<&str> = new_content.lines().collect();
+    /// 计算行级差异并按上下文行数切分为差异块
+    ///
+    /// 变更之间未修改的行不超过两倍上下文行数时合并为同一个差异块，与 `git diff` 的规则一致
+    fn compute_line_diff(&self, old_content: &str, new_content: &str) -> Result<Vec<DiffHunk>> {
+        use imara_diff::{Algorithm, Diff, InternedInput};
 
-        // 简单实现：如果内容不同，就标记为全部删除和全部添加
         if old_content == new_content {
             return Ok(vec![]);
         }
 
-        let mut diff_lines = Vec::new();
+        let old_lines: Vec<&str> = old_content.lines().collect();
+        let new_lines: Vec<&str> = new_content.lines().collect();
 
-        // 添加删除的行
-        for (i, line) in old_lines.iter().enumerate() {
-            diff_lines.push(DiffLine {
-                content: line.to_string(),
-                line_type: DiffLineType::Removed,
-                old_line_number: Some(i as u32 + 1),
-                new_line_number: None,
-            });
+        // 两侧都按不含换行符的行切分，末行缺少换行符不会被当作变更
+        let mut input = InternedInput::default();
+        input.update_before(old_lines.iter().copied());
+        input.update_after(new_lines.iter().copied());
+        let changes: Vec<(Range<u32>, Range<u32>)> = Diff::compute(Algorithm::Histogram, &input)
+            .hunks()
+            .map(|hunk| (hunk.before, hunk.after))
+            .collect();
+
+        // 按间隔把相邻的变更分组，每组生成一个差异块
+        let context = self.context_lines;
+        let mut groups: Vec<Vec<(Range<u32>, Range<u32>)>> = Vec::new();
+        for change in changes {
+            match groups.last_mut() {
+                Some(group)
+                    if change.0.start - group.last().map_or(0, |(before, _)| before.end)
+                        <= 2 * context =>
+                {
+                    group.push(change)
+                }
+                _ => groups.push(vec![change]),
+            }
         }
 
-        // 添加新增的行
-        for (i, line) in new_lines.iter().enumerate() {
-            diff_lines.push(DiffLine {
-                content: line.to_string(),
-                line_type: DiffLineType::Added,
-                old_line_number: None,
-                new_line_number: Some(i as u32 + 1),
+        let mut hunks = Vec::new();
+        for group in groups {
+            let (first_before, first_after) = &group[0];
+            let (last_before, last_after) = &group[group.len() - 1];
+            let old_start = first_before.start.saturating_sub(context);
+            let new_start = first_after.start - (first_before.start - old_start);
+            let old_end = (last_before.end + context).min(old_lines.len() as u32);
+            let new_end = last_after.end + (old_end - last_before.end);
+
+            let mut lines = Vec::new();
+            let (mut old_line, mut new_line) = (old_start, new_start);
+            for (before, after) in &group {
+                while old_line < before.start {
+                    lines.push(DiffLine {
+                        content: old_lines[old_line as usize].to_string(),
+                        line_type: DiffLineType::Context,
+                        old_line_number: Some(old_line + 1),
+                        new_line_number: Some(new_line + 1),
+                    });
+                    old_line += 1;
+                    new_line += 1;
+                }
+                for line in before.clone() {
+                    lines.push(DiffLine {
+                        content: old_lines[line as usize].to_string(),
+                        line_type: DiffLineType::Removed,
+                        old_line_number: Some(line + 1),
+                        new_line_number: None,
+                    });
+                }
+                for line in after.clone() {
+                    lines.push(DiffLine {
+                        content: new_lines[line as usize].to_string(),
+                        line_type: DiffLineType::Added,
+                        old_line_number: None,
+                        new_line_number: Some(line + 1),
+                    });
+                }
+                old_line = before.end;
+                new_line = after.end;
+            }
+            while old_line < old_end {
+                lines.push(DiffLine {
+                    content: old_lines[old_line as usize].to_string(),
+                    line_type: DiffLineType::Context,
+                    old_line_number: Some(old_line + 1),
+                    new_line_number: Some(new_line + 1),
+                });
+                old_line += 1;
+                new_line += 1;
+            }
+
+            // 与统一差异格式一致：行数为 0 时起始行号指向变更位置之前的一行
+            let hunk_start = |start: u32, end: u32| if end > start { start + 1 } else { start };
+            hunks.push(DiffHunk {
+                old_start: hunk_start(old_start, old_end),
+                old_lines: old_end - old_start,
+                new_start: hunk_start(new_start, new_end),
+                new_lines: new_end - new_start,
+                lines,
+                context_lines: context,
             });
         }
 
-        Ok(vec![DiffHunk {
-            old_start: if old_lines.is_empty() { 0 } else { 1 },
-            old_lines: old_lines.len() as u32,
-            new_start: if new_lines.is_empty() { 0 } else { 1 },
-            new_lines: new_lines.len() as u32,
-            lines: diff_lines,
-            context_lines: 3,
-        }])
+        Ok(hunks)
     }
 
     /// 检测文件重命名（简化实现）
@@ -612,9 +1325,25 @@ impl GitDiffParser {
         diff_ratio < 0.2
     }
 }
+/// 将路径转换为相对仓库根目录的路径
+///
+/// 按路径组件比较，`./main.go` 与 `.` 这样的相对写法同样可以去掉前缀；
+/// 路径不在仓库根目录之下时原样返回
+pub fn repo_relative_path(path: &Path, repo_root: &Path) -> PathBuf {
+    path.strip_prefix(repo_root)
+        .map_or_else(|_| path.to_path_buf(), Path::to_path_buf)
+}
+
+/// 简单的二进制内容检测：检查前 8192 字节中是否包含 null 字节
+fn is_binary_data(data: &[u8]) -> bool {
+    let check_size = std::cmp::min(data.len(), 8192);
+    data[..check_size].contains(&0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::run_git;
     use std::path::PathBuf;
     use tempfile::TempDir;
 
@@ -772,6 +1501,21 @@ mod tests {
         Ok(commit_hash)
     }
 
+    /// 以指定提交消息提交文件，返回提交哈希
+    fn commit_file_with_message(
+        repo_path: &PathBuf,
+        file_name: &str,
+        content: &str,
+        message: &str,
+    ) -> Result<String> {
+        std::fs::write(repo_path.join(file_name), content).map_err(SemanticDiffError::IoError)?;
+        run_git(repo_path, &["add", file_name]);
+        run_git(repo_path, &["commit", "-m", message]);
+        Ok(run_git(repo_path, &["rev-parse", "HEAD"])
+            .trim()
+            .to_string())
+    }
+
     #[test]
     fn test_git_diff_parser_new() {
         let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
@@ -880,6 +1624,261 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve_abbreviated_hash_and_ref_name() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+        let commit_hash = create_test_commit(&repo_path, "main.go", "package main\n")
+            .expect("Failed to create test commit");
+
+        let parser = GitDiffParser::new(repo_path).expect("Failed to create parser");
+        let full_id = parser
+            .parse_commit_hash(&commit_hash)
+            .expect("Full hash should resolve");
+        assert_eq!(full_id.to_string(), commit_hash);
+
+        // 7 位缩写哈希应解析为完整提交
+        let short_id = parser
+            .parse_commit_hash(&commit_hash[..7])
+            .expect("Abbreviated hash should resolve");
+        assert_eq!(short_id, full_id);
+
+        // 引用名同样可以解析
+        let head_id = parser
+            .parse_commit_hash("HEAD")
+            .expect("HEAD should resolve");
+        assert_eq!(head_id, full_id);
+        assert!(parser.parse_commit(&commit_hash[..7]).is_ok());
+
+        // 无法解析的修订应返回 RevisionNotFound，并给出提示
+        let err = parser
+            .parse_commit_hash("no-such-branch")
+            .expect_err("Unknown revision should fail");
+        assert!(matches!(err, SemanticDiffError::RevisionNotFound(_)));
+        assert!(err.to_string().contains("no-such-branch"));
+    }
+
+    #[test]
+    fn test_parse_range_autosquash_folds_fixup_commits() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+        let base = create_test_commit(&repo_path, "README.md", "# test\n")
+            .expect("Failed to create base commit");
+        let handler = commit_file_with_message(
+            &repo_path,
+            "handler.go",
+            "package main\n\nfunc Handle() {}\n",
+            "Add handler",
+        )
+        .expect("Failed to commit handler");
+        let util = commit_file_with_message(
+            &repo_path,
+            "util.go",
+            "package main\n\nfunc helper() {}\n",
+            "Add util",
+        )
+        .expect("Failed to commit util");
+        let fixup = commit_file_with_message(
+            &repo_path,
+            "handler.go",
+            "package main\n\nfunc Handle() {\n\thelper()\n}\n",
+            "fixup! Add handler",
+        )
+        .expect("Failed to commit fixup");
+
+        let parser = GitDiffParser::new(repo_path).expect("Failed to create parser");
+        let range = format!("{base}..HEAD");
+
+        // 不折叠时每个提交单独出现
+        let commits = parser.parse_range(&range, false).unwrap();
+        assert_eq!(commits.len(), 3);
+        assert_eq!(commits[2].commit_hash, fixup);
+
+        // 折叠后 fixup 提交并入其目标提交
+        let commits = parser.parse_range(&range, true).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].commit_hash, handler);
+        assert_eq!(commits[0].folded_commits, vec![fixup]);
+        assert_eq!(commits[1].commit_hash, util);
+        assert!(commits[1].folded_commits.is_empty());
+
+        // 目标提交的变更反映折叠后的最终内容，且不混入 util.go
+        let changes = &commits[0].changes;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].file_path, PathBuf::from("handler.go"));
+        assert!(matches!(changes[0].change_type, ChangeType::Added));
+        let added: Vec<_> = changes[0]
+            .hunks
+            .iter()
+            .flat_map(|hunk| &hunk.lines)
+            .filter(|line| line.line_type == DiffLineType::Added)
+            .map(|line| line.content.trim())
+            .collect();
+        assert!(added.contains(&"helper()"));
+    }
+
+    #[test]
+    fn test_parse_commit_range_cumulative_hunks() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+        let first = create_test_commit(&repo_path, "main.go", "package main\n\nfunc A() {}\n")
+            .expect("Failed to create first commit");
+        let second = modify_file_and_commit(
+            &repo_path,
+            "main.go",
+            "package main\n\nfunc A() {}\n\nfunc B() {}\n",
+        )
+        .expect("Failed to create second commit");
+        let third = modify_file_and_commit(
+            &repo_path,
+            "main.go",
+            "package main\n\nfunc A() {}\n\nfunc B() {}\n\nfunc C() {}\n",
+        )
+        .expect("Failed to create third commit");
+
+        let parser = GitDiffParser::new(repo_path.clone()).expect("Failed to create parser");
+        let lines_of = |changes: &[FileChange], line_type: DiffLineType| -> Vec<String> {
+            changes
+                .iter()
+                .flat_map(|change| &change.hunks)
+                .flat_map(|hunk| &hunk.lines)
+                .filter(|line| line.line_type == line_type)
+                .map(|line| line.content.trim().to_string())
+                .collect()
+        };
+
+        // 祖先到后代的范围包含中间所有提交的累积变更
+        let changes = parser.parse_commit_range(&first, &third).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].file_path, PathBuf::from("main.go"));
+        assert_eq!(changes[0].change_type, ChangeType::Modified);
+        let added = lines_of(&changes, DiffLineType::Added);
+        assert!(added.contains(&"func B() {}".to_string()));
+        assert!(added.contains(&"func C() {}".to_string()));
+        assert!(!lines_of(&changes, DiffLineType::Removed).contains(&"func B() {}".to_string()));
+
+        // 从中间提交开始时旧版本已经包含 B
+        let changes = parser.parse_commit_range(&second, &third).unwrap();
+        assert!(lines_of(&changes, DiffLineType::Added).contains(&"func C() {}".to_string()));
+        let old_side: Vec<String> = [DiffLineType::Removed, DiffLineType::Context]
+            .into_iter()
+            .flat_map(|line_type| lines_of(&changes, line_type))
+            .collect();
+        assert!(old_side.contains(&"func B() {}".to_string()));
+        assert!(!old_side.contains(&"func C() {}".to_string()));
+
+        // 从第一个提交分叉出的分支
+        run_git(
+            &repo_path,
+            &["checkout", "-q", "-b", "feature", first.as_str()],
+        );
+        let feature = commit_file_with_message(
+            &repo_path,
+            "feature.go",
+            "package main\n\nfunc Feature() {}\n",
+            "Add feature",
+        )
+        .expect("Failed to commit feature");
+
+        // 默认以 merge-base 为基准，只包含分支自身的变更
+        let changes = parser.parse_commit_range(&third, &feature).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].file_path, PathBuf::from("feature.go"));
+        assert_eq!(changes[0].change_type, ChangeType::Added);
+
+        // 两点比较直接对比两棵树，主线上的函数表现为被删除
+        let changes = parser
+            .parse_commit_range_with(&third, &feature, RangeBase::TwoDot)
+            .unwrap();
+        assert_eq!(changes.len(), 2);
+        let main_change = changes
+            .iter()
+            .find(|change| change.file_path == Path::new("main.go"))
+            .expect("main.go should differ");
+        assert!(
+            main_change
+                .hunks
+                .iter()
+                .flat_map(|hunk| &hunk.lines)
+                .any(|line| line.line_type == DiffLineType::Removed
+                    && line.content.contains("func C()"))
+        );
+    }
+
+    #[test]
+    fn test_parse_branches() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+
+        create_test_commit(&repo_path, "base.go", "package main\n")
+            .expect("Failed to create base commit");
+        run_git(&repo_path, &["branch", "-M", "main"]);
+        run_git(&repo_path, &["tag", "v1.0"]);
+
+        // feature 分支上新增文件，main 分支上另有独立的提交
+        run_git(&repo_path, &["checkout", "-q", "-b", "feature"]);
+        create_test_commit(&repo_path, "feature.go", "package main\n\nfunc F() {}\n")
+            .expect("Failed to commit on feature");
+        run_git(&repo_path, &["checkout", "-q", "main"]);
+        create_test_commit(&repo_path, "main_only.go", "package main\n")
+            .expect("Failed to commit on main");
+
+        let parser = GitDiffParser::new(repo_path.clone()).expect("Failed to create parser");
+
+        // 三点语义：只包含 feature 分支引入的变更
+        let changes = parser.parse_branches("main", "feature").unwrap();
+        let paths: Vec<_> = changes.iter().map(|c| c.file_path.clone()).collect();
+        assert_eq!(paths, vec![PathBuf::from("feature.go")]);
+        assert_eq!(changes[0].change_type, ChangeType::Added);
+
+        // 标签同样可以作为基准
+        let changes = parser.parse_branches("v1.0", "main").unwrap();
+        let paths: Vec<_> = changes.iter().map(|c| c.file_path.clone()).collect();
+        assert_eq!(paths, vec![PathBuf::from("main_only.go")]);
+
+        // 不存在的引用给出明确的错误
+        let error = parser.parse_branches("main", "no-such-branch").unwrap_err();
+        assert!(
+            matches!(&error, SemanticDiffError::RevisionNotFound(msg) if msg.contains("no-such-branch")),
+            "unexpected error: {error}"
+        );
+    }
+
+    #[test]
+    fn test_parse_merge_commit_parent_selection() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+
+        create_test_commit(&repo_path, "base.go", "package main\n")
+            .expect("Failed to create base commit");
+        run_git(&repo_path, &["branch", "-M", "main"]);
+        run_git(&repo_path, &["checkout", "-q", "-b", "feature"]);
+        create_test_commit(&repo_path, "feature.go", "package main\n\nfunc F() {}\n")
+            .expect("Failed to commit on feature");
+        run_git(&repo_path, &["checkout", "-q", "main"]);
+        create_test_commit(&repo_path, "main.go", "package main\n\nfunc M() {}\n")
+            .expect("Failed to commit on main");
+        run_git(
+            &repo_path,
+            &["merge", "-q", "--no-ff", "--no-edit", "feature"],
+        );
+
+        let parser = GitDiffParser::new(repo_path.clone()).expect("Failed to create parser");
+        let paths = |changes: Vec<FileChange>| -> Vec<PathBuf> {
+            changes.into_iter().map(|change| change.file_path).collect()
+        };
+
+        // 相对第一父提交（main）只看到合并进来的 feature.go，默认行为保持不变
+        let first_parent = paths(parser.parse_merge_commit("HEAD", 0).unwrap());
+        assert_eq!(first_parent, vec![PathBuf::from("feature.go")]);
+        assert_eq!(paths(parser.parse_commit("HEAD").unwrap()), first_parent);
+
+        // 相对第二父提交（feature）看到的是 main 上的 main.go
+        let second_parent = paths(parser.parse_merge_commit("HEAD", 1).unwrap());
+        assert_eq!(second_parent, vec![PathBuf::from("main.go")]);
+
+        let error = parser.parse_merge_commit("HEAD", 2).unwrap_err();
+        assert!(
+            matches!(&error, SemanticDiffError::RevisionNotFound(msg) if msg.contains("out of range")),
+            "unexpected error: {error}"
+        );
+    }
+
     #[test]
     fn test_detailed_line_diff() {
         let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
@@ -924,6 +1923,182 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_line_diff_without_trailing_newline() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+        let parser = GitDiffParser::new(repo_path)
+            .expect("Failed to create parser")
+            .with_context_lines(0);
+
+        // 旧版本末行没有换行符，追加函数只应产生新增的行
+        let hunks = parser
+            .compute_line_diff(
+                "package main\n\nfunc A() {}",
+                "package main\n\nfunc A() {}\n\nfunc B() {}\n",
+            )
+            .unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].new_start, hunks[0].new_lines), (4, 2));
+        assert!(
+            hunks[0]
+                .lines
+                .iter()
+                .all(|line| line.line_type == DiffLineType::Added)
+        );
+    }
+
+    #[test]
+    fn test_configurable_context_lines() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+        let old_lines: Vec<String> = (1..=30).map(|i| format!("// line {i}")).collect();
+        create_test_commit(&repo_path, "lines.go", &(old_lines.join("\n") + "\n"))
+            .expect("Failed to create initial commit");
+
+        // 修改第 10 行和第 17 行，两处之间有 6 行未修改
+        let mut new_lines = old_lines.clone();
+        new_lines[9] = "// changed 10".to_string();
+        new_lines[16] = "// changed 17".to_string();
+        let commit_hash =
+            modify_file_and_commit(&repo_path, "lines.go", &(new_lines.join("\n") + "\n"))
+                .expect("Failed to create modified commit");
+
+        let parser = GitDiffParser::new(repo_path.clone()).expect("Failed to create parser");
+        assert_eq!(parser.context_lines(), 3);
+
+        // 没有上下文时每处修改各自成为一个差异块，只包含删除和新增的行
+        let parser = parser.with_context_lines(0);
+        let hunks = &parser.parse_commit(&commit_hash).unwrap()[0].hunks;
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            (hunks[0].old_start, hunks[0].old_lines, hunks[0].lines.len()),
+            (10, 1, 2)
+        );
+        assert_eq!(
+            (hunks[1].new_start, hunks[1].new_lines, hunks[1].lines.len()),
+            (17, 1, 2)
+        );
+        assert!(
+            hunks
+                .iter()
+                .flat_map(|hunk| &hunk.lines)
+                .all(|line| line.line_type != DiffLineType::Context)
+        );
+
+        // 5 行上下文让两处修改合并为一个差异块，覆盖第 5 行到第 22 行
+        let parser = parser.with_context_lines(5);
+        let hunks = &parser.parse_commit(&commit_hash).unwrap()[0].hunks;
+        assert_eq!(hunks.len(), 1);
+        let hunk = &hunks[0];
+        assert_eq!(hunk.context_lines, 5);
+        assert_eq!((hunk.new_start, hunk.new_lines), (5, 18));
+        assert_eq!(hunk.lines.len(), 20);
+        assert_eq!(hunk.lines[0].content, "// line 5");
+        assert_eq!(hunk.lines[0].line_type, DiffLineType::Context);
+        assert_eq!(hunk.lines[19].content, "// line 22");
+    }
+
+    #[test]
+    fn test_parse_working_tree_and_staged() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+        create_test_commit(&repo_path, "main.go", "package main\n\nfunc A() {}\n")
+            .expect("Failed to commit main.go");
+        create_test_commit(&repo_path, ".gitignore", "*.log\n")
+            .expect("Failed to commit .gitignore");
+
+        // 未暂存的修改、未跟踪的新文件和被忽略的文件
+        std::fs::write(
+            repo_path.join("main.go"),
+            "package main\n\nfunc A() {\n\tprintln()\n}\n",
+        )
+        .unwrap();
+        std::fs::create_dir(repo_path.join("pkg")).unwrap();
+        std::fs::write(
+            repo_path.join("pkg/new.go"),
+            "package pkg\n\nfunc New() {}\n",
+        )
+        .unwrap();
+        std::fs::write(repo_path.join("debug.log"), "ignored\n").unwrap();
+
+        let parser = GitDiffParser::new(repo_path.clone()).expect("Failed to create parser");
+        let changes = parser.parse_working_tree().unwrap();
+        let summary: Vec<(PathBuf, ChangeType)> = changes
+            .iter()
+            .map(|change| (change.file_path.clone(), change.change_type.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (PathBuf::from("main.go"), ChangeType::Modified),
+                (PathBuf::from("pkg/new.go"), ChangeType::Added),
+            ]
+        );
+
+        // 未跟踪文件的全部内容都是新增行
+        let new_file = &changes[1];
+        let lines: Vec<_> = new_file.hunks.iter().flat_map(|hunk| &hunk.lines).collect();
+        assert_eq!(lines.len(), 3);
+        assert!(
+            lines
+                .iter()
+                .all(|line| line.line_type == DiffLineType::Added)
+        );
+        assert_eq!(lines[2].content, "func New() {}");
+
+        // 没有暂存任何修改
+        assert!(parser.parse_staged().unwrap().is_empty());
+
+        // 暂存新文件后只出现在暂存区差异中，未暂存的修改不计入
+        run_git(&repo_path, &["add", "pkg/new.go"]);
+        let staged = parser.parse_staged().unwrap();
+        assert_eq!(staged.len(), 1);
+        assert_eq!(staged[0].file_path, PathBuf::from("pkg/new.go"));
+        assert_eq!(staged[0].change_type, ChangeType::Added);
+    }
+
+    #[test]
+    fn test_working_tree_compares_object_ids_with_head() {
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+        let original = "package main\n\nfunc A() {}\n";
+        create_test_commit(&repo_path, "main.go", original).expect("Failed to commit main.go");
+        create_test_commit(&repo_path, "util.go", "package main\n\nfunc B() {}\n")
+            .expect("Failed to commit util.go");
+
+        // 只改变状态信息、内容不变的文件不是变更
+        std::fs::write(repo_path.join("util.go"), "package main\n\nfunc B() {}\n").unwrap();
+
+        // 暂存修改后在工作区还原：暂存区与 HEAD 不同，工作区与 HEAD 相同
+        std::fs::write(
+            repo_path.join("main.go"),
+            "package main\n\nfunc A() { B() }\n",
+        )
+        .unwrap();
+        run_git(&repo_path, &["add", "main.go"]);
+        std::fs::write(repo_path.join("main.go"), original).unwrap();
+
+        let parser = GitDiffParser::new(repo_path.clone()).expect("Failed to create parser");
+        assert!(parser.parse_working_tree().unwrap().is_empty());
+
+        let staged = parser.parse_staged().unwrap();
+        assert_eq!(staged.len(), 1);
+        assert_eq!(staged[0].file_path, PathBuf::from("main.go"));
+        assert_eq!(staged[0].change_type, ChangeType::Modified);
+
+        // 从索引中移除的文件在两种差异中都表现为删除
+        run_git(&repo_path, &["rm", "--cached", "-q", "util.go"]);
+        std::fs::remove_file(repo_path.join("util.go")).unwrap();
+        let changes = parser.parse_working_tree().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].file_path, PathBuf::from("util.go"));
+        assert_eq!(changes[0].change_type, ChangeType::Deleted);
+        assert!(
+            parser
+                .parse_staged()
+                .unwrap()
+                .iter()
+                .any(|change| change.change_type == ChangeType::Deleted)
+        );
+    }
+
     #[test]
     fn test_binary_file_detection() {
         let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
@@ -1074,6 +2249,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_renamed_file_reports_only_edited_functions() {
+        use crate::analyzer::SourceAnalyzer;
+        use crate::parser::SupportedLanguage;
+
+        let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
+        let functions = [
+            "func Add(a, b int) int {\n\treturn a + b\n}\n",
+            "func Sub(a, b int) int {\n\treturn a - b\n}\n",
+            "func Mul(a, b int) int {\n\treturn a * b\n}\n",
+            "func Div(a, b int) int {\n\treturn a / b\n}\n",
+        ];
+        let content = format!("package calc\n\n{}", functions.join("\n"));
+        create_test_commit(&repo_path, "calc.go", &content).expect("Failed to commit calc.go");
+
+        // 重命名文件并修改其中一个函数
+        run_git(&repo_path, &["mv", "calc.go", "arith.go"]);
+        let edited = content.replace("return a - b", "return b - a");
+        std::fs::write(repo_path.join("arith.go"), &edited).unwrap();
+        let commit_hash =
+            create_test_commit(&repo_path, "arith.go", &edited).expect("Failed to commit rename");
+
+        let parser = GitDiffParser::new(repo_path.clone()).expect("Failed to create parser");
+        let changes = parser.parse_commit(&commit_hash).unwrap();
+        assert_eq!(
+            changes.len(),
+            1,
+            "rename should not be reported as delete + add"
+        );
+        let file_change = &changes[0];
+        assert_eq!(file_change.file_path, PathBuf::from("arith.go"));
+        assert_eq!(
+            file_change.change_type,
+            ChangeType::Renamed {
+                old_path: PathBuf::from("calc.go")
+            }
+        );
+
+        // 与重命名前的版本比较，只有修改过的函数是变更目标
+        let old_content = parser
+            .get_old_file_content(&commit_hash, file_change)
+            .unwrap()
+            .expect("renamed file should have a previous version");
+        let mut analyzer = SourceAnalyzer::new_for_language(SupportedLanguage::Go).unwrap();
+        let old_file = analyzer
+            .analyze_source(&repo_path.join("calc.go"), old_content)
+            .unwrap();
+        let new_file = analyzer
+            .analyze_source(&repo_path.join("arith.go"), edited)
+            .unwrap();
+
+        let modified: Vec<String> = analyzer
+            .find_modified_functions(&old_file, &new_file)
+            .into_iter()
+            .map(|function| function.name)
+            .collect();
+        assert_eq!(modified, vec!["Sub".to_string()]);
+    }
+
     #[test]
     fn test_performance_with_large_commit() {
         let (_temp_dir, repo_path) = create_test_repo().expect("Failed to create test repo");
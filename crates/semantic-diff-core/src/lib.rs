@@ -4,7 +4,11 @@
 //! 提供语义级别的代码差异分析功能。
 
 pub mod analyzer;
+pub mod constraints;
+pub mod diagnostics;
+pub mod diff;
 pub mod error;
+pub mod exclude;
 pub mod extractor;
 pub mod formatter;
 pub mod generator;
@@ -12,24 +16,39 @@ pub mod git;
 pub mod parser;
 pub mod performance;
 
+#[cfg(test)]
+mod test_support;
+
 // 重新导出主要的公共 API
-pub use analyzer::{DependencyResolver, TypeAnalyzer};
+pub use analyzer::{
+    ClassifiedChange, CompatChange, DependencyResolver, FunctionGrowth, QualifiedSymbol,
+    SemverBump, StructDelta, SymbolIndex, TagChange, TypeAnalyzer,
+};
+pub use constraints::BuildConstraints;
+pub use diagnostics::{Diagnostic, DiagnosticKind, Diagnostics};
+pub use diff::{RiskKind, RiskSignal, SemanticDiff};
 pub use error::{Result, SemanticDiffError};
-pub use extractor::{SemanticContext, SemanticContextExtractor};
+pub use exclude::ExcludePatterns;
+pub use extractor::{ExtractionOrder, SemanticContext, SemanticContextExtractor};
 pub use formatter::{
-    BlockTitleStyle, ColorTheme, FormattedOutput, FormatterConfig, OutputMetadata, OutputRenderer,
-    SyntaxHighlighter,
+    BlockTitleStyle, ColorDepth, ColorTheme, FormattedOutput, FormatterConfig, OutputMetadata,
+    OutputRenderer, SyntaxHighlighter,
+};
+pub use generator::{
+    CodeSlice, CodeSliceGenerator, HighlightStyle, OutputFormat, ReviewChecklist, TargetVersions,
 };
-pub use generator::{CodeSlice, CodeSliceGenerator, HighlightStyle, OutputFormat};
-pub use git::{ChangeType, DiffHunk, FileChange, GitDiffParser};
+pub use git::{ChangeType, DiffHunk, FileChange, GitDiffParser, RangeBase, RangeCommit};
 // 导出多语言解析器架构
 pub use parser::{
-    Declaration, GoConstantDefinition, GoDeclaration, GoFunctionInfo, GoLanguageInfo, GoParameter,
-    GoParser, GoReceiverInfo, GoType, GoTypeDefinition, GoTypeKind, GoVariableDefinition, Import,
-    LanguageParser, LanguageSpecificInfo, ParserFactory, SourceFile, SupportedLanguage,
+    ChannelDir, Declaration, GenericLanguageInfo, GoConstantDefinition, GoDeclaration,
+    GoFunctionInfo, GoLanguageInfo, GoParameter, GoParser, GoReceiverInfo, GoStructField, GoType,
+    GoTypeDefinition, GoTypeKind, GoTypeParam, GoVariableDefinition, Import, ImportKind,
+    LanguageParser, LanguageSpecificInfo, ParserConstructor, ParserFactory, ReferenceKind,
+    SourceFile, SupportedLanguage, UNKNOWN_PACKAGE_NAME,
 };
 // 导出性能优化组件
 pub use performance::{
     CacheStats, ConcurrentFileProcessor, ErrorRecoveryStrategy, MemoryEfficientAstProcessor,
-    ParseResult, ParserCache, PerformanceMonitor, PerformanceStats,
+    ParseResult, ParserCache, PerformanceMonitor, PerformanceStats, ProgressCallback,
+    ProgressEvent, ScopedThreadPool,
 };
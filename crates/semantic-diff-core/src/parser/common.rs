@@ -3,24 +3,67 @@
 //! 定义多语言解析器的通用接口和共享数据结构
 
 use crate::error::{Result, SemanticDiffError};
+use crate::git::DiffHunk;
+use crate::parser::go::GoType;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use tree_sitter::{Node, Tree};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+use tree_sitter::{InputEdit, Node, Point, Tree};
 
 /// 支持的编程语言枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SupportedLanguage {
     Go,
+    /// 通过 `ParserFactory::register` 注册的自定义语言，值为注册时的文件扩展名
+    Custom(&'static str),
     // 未来支持的语言
     // Rust,
     // TypeScript,
     // Python,
 }
 
+impl SupportedLanguage {
+    /// Markdown 围栏代码块使用的语言标记
+    ///
+    /// 自定义语言使用注册时的文件扩展名
+    pub fn markdown_tag(&self) -> &'static str {
+        match *self {
+            SupportedLanguage::Go => "go",
+            SupportedLanguage::Custom(extension) => extension,
+        }
+    }
+}
+
 /// 通用语言解析器接口
 pub trait LanguageParser: Send + Sync {
     /// 解析源码为语法树
     fn parse_source(&mut self, source: &str) -> Result<Tree>;
 
+    /// 基于旧版本的语法树增量解析新版本源码
+    ///
+    /// `edits` 必须描述从 `old_src` 到 `new_src` 的行级差异（见 [`input_edits`]），
+    /// 未变化部分的节点会被复用。默认实现直接完整解析 `new_src`
+    fn parse_incremental(
+        &mut self,
+        old_tree: &Tree,
+        old_src: &str,
+        new_src: &str,
+        edits: &[DiffHunk],
+    ) -> Result<Tree> {
+        let _ = (old_tree, old_src, edits);
+        self.parse_source(new_src)
+    }
+
+    /// 设置单次解析的超时时间，`None` 表示不限制
+    ///
+    /// 解析超时时返回 [`SemanticDiffError::ParseTimeout`]。默认实现忽略该设置
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        let _ = timeout;
+    }
+
     /// 根据行列位置查找节点
     fn find_node_at_position<'a>(&self, tree: &'a Tree, line: u32, column: u32)
     -> Option<Node<'a>>;
@@ -38,20 +81,130 @@ pub trait LanguageParser: Send + Sync {
     fn file_extensions(&self) -> &'static [&'static str];
 }
 
+/// 将行级差异块转换为 tree-sitter 的编辑操作
+///
+/// 每个差异块对应一次整行替换：旧版本中的 `old_lines` 行被新版本中的 `new_lines` 行取代。
+/// 编辑按差异块顺序排列，位置以应用了之前所有编辑后的文本为准，可以依次传给 `Tree::edit`
+pub fn input_edits(old_src: &str, new_src: &str, hunks: &[DiffHunk]) -> Vec<InputEdit> {
+    let old_offsets = line_start_offsets(old_src);
+    let new_offsets = line_start_offsets(new_src);
+    // 行号从 1 开始；行数为 0 时起始行号指向变更位置之前的一行
+    let first_line = |start: u32, lines: u32| {
+        if lines > 0 {
+            start.saturating_sub(1) as usize
+        } else {
+            start as usize
+        }
+    };
+
+    hunks
+        .iter()
+        .map(|hunk| {
+            let old_row = first_line(hunk.old_start, hunk.old_lines);
+            let new_row = first_line(hunk.new_start, hunk.new_lines);
+            let old_len =
+                old_offsets.offset(old_row + hunk.old_lines as usize) - old_offsets.offset(old_row);
+            let new_len =
+                new_offsets.offset(new_row + hunk.new_lines as usize) - new_offsets.offset(new_row);
+            let start_byte = new_offsets.offset(new_row);
+
+            InputEdit {
+                start_byte,
+                old_end_byte: start_byte + old_len,
+                new_end_byte: start_byte + new_len,
+                start_position: Point::new(new_row, 0),
+                old_end_position: Point::new(new_row + hunk.old_lines as usize, 0),
+                new_end_position: Point::new(new_row + hunk.new_lines as usize, 0),
+            }
+        })
+        .collect()
+}
+
+/// 源码中每一行起始位置的字节偏移
+struct LineOffsets {
+    starts: Vec<usize>,
+    len: usize,
+}
+
+impl LineOffsets {
+    /// 第 `row` 行（从 0 开始）的起始偏移，超出末行时为源码长度
+    fn offset(&self, row: usize) -> usize {
+        self.starts.get(row).copied().unwrap_or(self.len)
+    }
+}
+
+/// 计算每一行的起始字节偏移
+fn line_start_offsets(source: &str) -> LineOffsets {
+    let starts = std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+        .filter(|&offset| offset < source.len())
+        .collect();
+
+    LineOffsets {
+        starts,
+        len: source.len(),
+    }
+}
+
+/// 自定义解析器的构造函数
+pub type ParserConstructor = Box<dyn Fn() -> Box<dyn LanguageParser> + Send + Sync>;
+
+/// 已注册的自定义解析器，按文件扩展名索引
+type ParserRegistry = HashMap<&'static str, Arc<ParserConstructor>>;
+
 /// 解析器工厂
 pub struct ParserFactory;
 
 impl ParserFactory {
+    /// 全局的自定义解析器注册表
+    fn registry() -> &'static RwLock<ParserRegistry> {
+        static REGISTRY: OnceLock<RwLock<ParserRegistry>> = OnceLock::new();
+        REGISTRY.get_or_init(|| RwLock::new(HashMap::new()))
+    }
+
+    /// 为文件扩展名注册自定义解析器
+    ///
+    /// 扩展名可以带或不带前导 `.`；注册的解析器优先于内置语言，重复注册会覆盖之前的构造函数。
+    /// 注册通常在程序启动时进行，扩展名会在进程生命周期内一直保留
+    pub fn register(extension: &str, factory: ParserConstructor) {
+        let extension = extension.trim_start_matches('.');
+        let mut registry = Self::registry()
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let key = match registry.get_key_value(extension) {
+            Some((key, _)) => *key,
+            None => Box::leak(extension.to_string().into_boxed_str()),
+        };
+        registry.insert(key, Arc::new(factory));
+    }
+
+    /// 查找已注册的自定义解析器
+    fn registered(extension: &str) -> Option<(&'static str, Arc<ParserConstructor>)> {
+        let registry = Self::registry()
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        registry
+            .get_key_value(extension)
+            .map(|(key, factory)| (*key, Arc::clone(factory)))
+    }
+
     /// 根据语言类型创建解析器
     pub fn create_parser(language: SupportedLanguage) -> Result<Box<dyn LanguageParser>> {
         match language {
             SupportedLanguage::Go => Ok(Box::new(super::go::GoParser::new()?)),
+            SupportedLanguage::Custom(extension) => Self::registered(extension)
+                .map(|(_, factory)| factory())
+                .ok_or_else(|| SemanticDiffError::UnsupportedFileType(extension.to_string())),
         }
     }
 
-    /// 根据文件路径检测语言类型
+    /// 根据文件路径检测语言类型，已注册的自定义解析器优先于内置语言
     pub fn detect_language(file_path: &Path) -> Option<SupportedLanguage> {
-        match file_path.extension()?.to_str()? {
+        let extension = file_path.extension()?.to_str()?;
+        if let Some((extension, _)) = Self::registered(extension) {
+            return Some(SupportedLanguage::Custom(extension));
+        }
+        match extension {
             "go" => Some(SupportedLanguage::Go),
             _ => None,
         }
@@ -68,9 +221,89 @@ impl ParserFactory {
 
 /// 通用导入声明
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Import {
     pub path: String,
     pub alias: Option<String>,
+    /// 导入形式，点导入和空白导入没有别名
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: ImportKind,
+}
+
+/// 导入形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ImportKind {
+    /// 普通导入（可带别名），通过包名限定引用
+    #[default]
+    Normal,
+    /// 点导入（`. "fmt"`），导出的标识符不经限定直接引用
+    Dot,
+    /// 空白导入（`_ "driver"`），只为初始化副作用而导入
+    Blank,
+}
+
+impl Import {
+    /// 导入在引用文件中使用的名称：有别名时为别名，否则为 [`Self::package_name`]；
+    /// 点导入和空白导入分别为 `.` 和 `_`，不会与任何包限定符匹配
+    pub fn local_name(&self) -> &str {
+        match self.kind {
+            ImportKind::Dot => ".",
+            ImportKind::Blank => "_",
+            ImportKind::Normal => self.alias.as_deref().unwrap_or_else(|| self.package_name()),
+        }
+    }
+
+    /// 按 Go 的惯例从导入路径推断被导入包的包名
+    ///
+    /// 取路径最后一段，跳过主版本后缀段（`github.com/foo/bar/v2` 为 `bar`），
+    /// 去掉 gopkg.in 风格的 `.vN` 后缀（`gopkg.in/yaml.v3` 为 `yaml`）以及
+    /// `go-` 前缀和 `-go`、`.go` 后缀（`github.com/mattn/go-sqlite3` 为 `sqlite3`）。
+    /// 包声明与路径不符的包只能通过别名导入准确识别
+    pub fn package_name(&self) -> &str {
+        let is_major_version = |segment: &str| {
+            segment.strip_prefix('v').is_some_and(|digits| {
+                !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+            })
+        };
+
+        let mut segments = self.path.rsplit('/');
+        let mut name = segments.next().unwrap_or(&self.path);
+        if is_major_version(name) {
+            name = segments.next().unwrap_or(name);
+        }
+        if let Some((base, version)) = name.rsplit_once('.') {
+            if is_major_version(version) {
+                name = base;
+            }
+        }
+        let name = name.strip_prefix("go-").unwrap_or(name);
+        name.strip_suffix("-go")
+            .or_else(|| name.strip_suffix(".go"))
+            .unwrap_or(name)
+    }
+
+    /// 是否需要保留在上下文中，即使没有找到包限定的引用
+    ///
+    /// 空白导入的初始化副作用和点导入的非限定引用都无法通过包名识别
+    pub fn is_unqualified(&self) -> bool {
+        self.kind != ImportKind::Normal
+    }
+
+    /// 生成导入说明（分组导入块中的一行，不含 `import` 关键字）
+    pub fn to_go_spec(&self) -> String {
+        match (self.kind, &self.alias) {
+            (ImportKind::Dot, _) => format!(". \"{}\"", self.path),
+            (ImportKind::Blank, _) => format!("_ \"{}\"", self.path),
+            (ImportKind::Normal, Some(alias)) => format!("{alias} \"{}\"", self.path),
+            (ImportKind::Normal, None) => format!("\"{}\"", self.path),
+        }
+    }
+
+    /// 生成对应的 Go 导入声明
+    pub fn to_go_declaration(&self) -> String {
+        format!("import {}", self.to_go_spec())
+    }
 }
 
 /// 通用声明类型 - 使用 trait object 来支持不同语言的声明
@@ -115,6 +348,40 @@ pub trait LanguageSpecificInfo: Send + Sync + std::fmt::Debug {
     fn declarations(&self) -> &[Box<dyn Declaration>];
 }
 
+/// 自定义语言的语言特定信息
+///
+/// 自定义解析器只提供语法树，不提取包名、导入和声明
+#[derive(Debug, Clone)]
+pub struct GenericLanguageInfo {
+    pub language: SupportedLanguage,
+}
+
+impl LanguageSpecificInfo for GenericLanguageInfo {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn LanguageSpecificInfo> {
+        Box::new(self.clone())
+    }
+
+    fn language(&self) -> SupportedLanguage {
+        self.language
+    }
+
+    fn package_name(&self) -> &str {
+        ""
+    }
+
+    fn imports(&self) -> &[Import] {
+        &[]
+    }
+
+    fn declarations(&self) -> &[Box<dyn Declaration>] {
+        &[]
+    }
+}
+
 /// 函数签名信息
 #[derive(Debug, Clone)]
 pub struct FunctionSignature {
@@ -124,6 +391,21 @@ pub struct FunctionSignature {
     pub receiver: Option<String>,
 }
 
+/// 参数签名信息
+///
+/// 分组参数 `a, b int` 会展开为多个共享同一类型的参数；
+/// 未命名参数（如接口方法签名 `func(int) error`）的名称为空字符串
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParameterSignature {
+    pub name: String,
+    /// 参数类型文本，可变参数不包含 `...` 前缀
+    pub param_type: String,
+    /// 从类型节点构造的结构化类型
+    pub go_type: GoType,
+    /// 是否为可变参数 `args ...T`
+    pub is_variadic: bool,
+}
+
 /// 函数信息
 #[derive(Debug, Clone)]
 pub struct FunctionInfo {
@@ -261,6 +543,117 @@ impl CstNavigator {
         })
     }
 
+    /// 提取函数（或方法）参数列表中的每个参数名及其类型
+    ///
+    /// 方法的接收者不计入参数；可变参数 `args ...string` 的类型记为 `string`，
+    /// 并通过 [`ParameterSignature::is_variadic`] 标记
+    pub fn get_function_parameters(
+        &self,
+        func_node: Node,
+        source: &str,
+    ) -> Vec<ParameterSignature> {
+        match func_node.child_by_field_name("parameters") {
+            Some(param_list) => self.extract_parameter_signatures(param_list, source),
+            None => Vec::new(),
+        }
+    }
+
+    /// 提取函数（或方法）的返回值列表
+    ///
+    /// 命名返回值 `(n int, err error)` 保留名称；未命名的返回值（包括单个返回类型）名称为空字符串
+    pub fn get_function_results(&self, func_node: Node, source: &str) -> Vec<ParameterSignature> {
+        let Some(result) = func_node.child_by_field_name("result") else {
+            return Vec::new();
+        };
+
+        if result.kind() == "parameter_list" {
+            self.extract_parameter_signatures(result, source)
+        } else {
+            vec![ParameterSignature {
+                name: String::new(),
+                param_type: source[result.byte_range()].to_string(),
+                go_type: GoType::from_type_node(result, source),
+                is_variadic: false,
+            }]
+        }
+    }
+
+    /// 展开参数列表节点中的每个参数，分组参数会拆分为多个共享类型的参数
+    fn extract_parameter_signatures(
+        &self,
+        param_list: Node,
+        source: &str,
+    ) -> Vec<ParameterSignature> {
+        let mut parameters = Vec::new();
+        let mut cursor = param_list.walk();
+        for child in param_list.named_children(&mut cursor) {
+            let is_variadic = match child.kind() {
+                "parameter_declaration" => false,
+                "variadic_parameter_declaration" => true,
+                _ => continue,
+            };
+            let Some(type_node) = child.child_by_field_name("type") else {
+                continue;
+            };
+            let param_type = source[type_node.byte_range()].to_string();
+            let go_type = GoType::from_type_node(type_node, source);
+
+            let mut name_cursor = child.walk();
+            let names: Vec<String> = child
+                .children_by_field_name("name", &mut name_cursor)
+                .map(|name_node| source[name_node.byte_range()].to_string())
+                .collect();
+
+            if names.is_empty() {
+                parameters.push(ParameterSignature {
+                    name: String::new(),
+                    param_type,
+                    go_type,
+                    is_variadic,
+                });
+            } else {
+                parameters.extend(names.into_iter().map(|name| ParameterSignature {
+                    name,
+                    param_type: param_type.clone(),
+                    go_type: go_type.clone(),
+                    is_variadic,
+                }));
+            }
+        }
+
+        parameters
+    }
+
+    /// 获取紧邻声明上方的文档注释
+    ///
+    /// 收集与节点之间没有空行的连续 `comment` 兄弟节点，多行注释按原文以换行拼接；
+    /// 位于上一行代码行尾的注释不属于该声明
+    pub fn get_doc_comment(&self, node: Node, source: &str) -> Option<String> {
+        let mut comments = Vec::new();
+        let mut next_row = node.start_position().row;
+        let mut current = node.prev_named_sibling();
+        while let Some(comment) = current {
+            if comment.kind() != "comment" || comment.end_position().row + 1 != next_row {
+                break;
+            }
+            let trailing = comment
+                .prev_named_sibling()
+                .is_some_and(|prev| prev.end_position().row == comment.start_position().row);
+            if trailing {
+                break;
+            }
+            comments.push(&source[comment.byte_range()]);
+            next_row = comment.start_position().row;
+            current = comment.prev_named_sibling();
+        }
+
+        if comments.is_empty() {
+            return None;
+        }
+        comments.reverse();
+        Some(comments.join("\n"))
+    }
+
     /// 提取节点中的类型引用
     pub fn extract_type_references(&self, node: Node, source: &str) -> Vec<String> {
         let mut type_refs = Vec::new();
@@ -417,6 +810,8 @@ pub struct SourceFile {
     pub language: SupportedLanguage,
     /// 语言特定的信息通过 trait object 处理
     pub language_specific: Box<dyn LanguageSpecificInfo>,
+    /// 文件头部的构建约束表达式（`//go:build` 或转换后的 `// +build`）
+    pub build_constraint: Option<String>,
 }
 
 impl Clone for SourceFile {
@@ -427,6 +822,7 @@ impl Clone for SourceFile {
             syntax_tree: self.syntax_tree.clone(),
             language: self.language,
             language_specific: self.language_specific.clone_box(),
+            build_constraint: self.build_constraint.clone(),
         }
     }
 }
@@ -489,6 +885,7 @@ mod tests {
         let import = Import {
             path: "fmt".to_string(),
             alias: Some("f".to_string()),
+            kind: ImportKind::Normal,
         };
         assert_eq!(import.path, "fmt");
         assert_eq!(import.alias, Some("f".to_string()));
@@ -496,11 +893,51 @@ mod tests {
         let import_no_alias = Import {
             path: "os".to_string(),
             alias: None,
+            kind: ImportKind::Normal,
         };
         assert_eq!(import_no_alias.path, "os");
         assert_eq!(import_no_alias.alias, None);
     }
 
+    #[test]
+    fn test_import_kind_declarations() {
+        let import = |path: &str, alias: Option<&str>, kind| Import {
+            path: path.to_string(),
+            alias: alias.map(str::to_string),
+            kind,
+        };
+
+        let normal = import("net/http", None, ImportKind::Normal);
+        assert_eq!(normal.local_name(), "http");
+        assert_eq!(normal.to_go_declaration(), "import \"net/http\"");
+
+        let aliased = import("fmt", Some("f"), ImportKind::Normal);
+        assert_eq!(aliased.local_name(), "f");
+        assert_eq!(aliased.to_go_declaration(), "import f \"fmt\"");
+        assert!(!aliased.is_unqualified());
+
+        let dot = import("strings", None, ImportKind::Dot);
+        assert_eq!(dot.local_name(), ".");
+        assert_eq!(dot.to_go_declaration(), "import . \"strings\"");
+        assert!(dot.is_unqualified());
+
+        // 主版本后缀、gopkg.in 版本后缀和 go- 前缀都不属于包名
+        let versioned = import("github.com/foo/bar/v2", None, ImportKind::Normal);
+        assert_eq!(versioned.local_name(), "bar");
+        let gopkg = import("gopkg.in/yaml.v3", None, ImportKind::Normal);
+        assert_eq!(gopkg.local_name(), "yaml");
+        let prefixed = import("github.com/mattn/go-sqlite3", None, ImportKind::Normal);
+        assert_eq!(prefixed.local_name(), "sqlite3");
+        let aliased_versioned = import("github.com/foo/bar/v2", Some("b"), ImportKind::Normal);
+        assert_eq!(aliased_versioned.local_name(), "b");
+        assert_eq!(aliased_versioned.package_name(), "bar");
+
+        let blank = import("github.com/lib/pq", None, ImportKind::Blank);
+        assert_eq!(blank.local_name(), "_");
+        assert_eq!(blank.to_go_declaration(), "import _ \"github.com/lib/pq\"");
+        assert!(blank.is_unqualified());
+    }
+
     #[test]
     fn test_function_signature_creation() {
         // 测试函数签名结构
@@ -789,6 +1226,88 @@ func (s *Server) Start(port int) error {
         assert_eq!(method_sig.receiver.unwrap(), "(s *Server)");
     }
 
+    #[test]
+    fn test_get_function_parameters() {
+        let source = r#"
+package main
+
+func grouped(a, b int, name string) {}
+
+func variadic(format string, args ...interface{}) {}
+
+func (s *Server) Handle(w http.ResponseWriter, r *http.Request) {}
+
+func anonymous(func(int) error) {}
+"#;
+
+        let (_parser, tree) = create_test_tree(source);
+        let navigator = CstNavigator::new();
+        let functions = navigator.find_function_declarations(tree.root_node());
+        let methods = navigator.find_method_declarations(tree.root_node());
+
+        let describe = |params: Vec<ParameterSignature>| -> Vec<(String, String)> {
+            params.into_iter().map(|p| (p.name, p.param_type)).collect()
+        };
+        let pair = |name: &str, ty: &str| (name.to_string(), ty.to_string());
+
+        // 分组参数共享同一类型
+        assert_eq!(
+            describe(navigator.get_function_parameters(functions[0], source)),
+            vec![pair("a", "int"), pair("b", "int"), pair("name", "string")]
+        );
+
+        // 可变参数：类型不含 `...`，通过标记区分
+        let variadic = navigator.get_function_parameters(functions[1], source);
+        assert_eq!(
+            describe(variadic.clone()),
+            vec![pair("format", "string"), pair("args", "interface{}")]
+        );
+        assert!(!variadic[0].is_variadic);
+        assert!(variadic[1].is_variadic);
+
+        // 方法的接收者不计入参数
+        assert_eq!(
+            describe(navigator.get_function_parameters(methods[0], source)),
+            vec![pair("w", "http.ResponseWriter"), pair("r", "*http.Request")]
+        );
+
+        // 未命名参数使用空名称
+        assert_eq!(
+            describe(navigator.get_function_parameters(functions[2], source)),
+            vec![pair("", "func(int) error")]
+        );
+    }
+
+    #[test]
+    fn test_get_doc_comment() {
+        let source = r#"
+package main
+
+// Greet 返回问候语
+//
+// 多行注释需要完整保留
+func Greet() {}
+
+// 与函数之间隔了空行，不是文档注释
+
+func Detached() {}
+
+var x = 1 // 行尾注释
+func Trailing() {}
+"#;
+
+        let (_parser, tree) = create_test_tree(source);
+        let navigator = CstNavigator::new();
+        let functions = navigator.find_function_declarations(tree.root_node());
+
+        assert_eq!(
+            navigator.get_doc_comment(functions[0], source).as_deref(),
+            Some("// Greet 返回问候语\n//\n// 多行注释需要完整保留")
+        );
+        assert_eq!(navigator.get_doc_comment(functions[1], source), None);
+        assert_eq!(navigator.get_doc_comment(functions[2], source), None);
+    }
+
     #[test]
     fn test_extract_type_references() {
         let source = r#"
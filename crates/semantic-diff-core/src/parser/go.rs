@@ -2,59 +2,895 @@
 //!
 //! 基于 Tree-sitter 的 Go 语言源码解析器
 
-use super::common::{Declaration, Import, LanguageParser, LanguageSpecificInfo, SupportedLanguage};
+use super::common::{
+    Declaration, Import, ImportKind, LanguageParser, LanguageSpecificInfo, SupportedLanguage,
+    input_edits,
+};
 use crate::error::{Result, SemanticDiffError};
+use crate::git::DiffHunk;
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
-use tree_sitter::{Node, Parser, Point, Tree};
+use std::sync::{Arc, LazyLock};
+use std::time::{Duration, Instant};
+use tree_sitter::{Node, ParseOptions, ParseState, Parser, Point, Tree};
 
 /// Go 语言函数信息
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GoFunctionInfo {
     pub name: String,
     pub receiver: Option<GoReceiverInfo>,
     pub parameters: Vec<GoParameter>,
     pub return_types: Vec<GoType>,
+    /// 泛型类型参数，例如 `func Map[T any, U any](...)` 中的 `T` 和 `U`
+    pub type_parameters: Vec<GoTypeParam>,
     pub body: String,
     pub start_line: u32,
     pub end_line: u32,
+    /// 声明在源文件中的起始字节偏移
+    pub byte_start: usize,
+    /// 声明在源文件中的结束字节偏移（不含）
+    pub byte_end: usize,
+    /// 紧邻声明上方的文档注释原文（保留 `//` 前缀，多行以换行拼接）
+    pub doc_comment: Option<String>,
     pub file_path: PathBuf,
+    /// 函数体中的标识符引用，提取函数信息时从语法树中收集一次
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub body_references: Arc<BodyReferences>,
+}
+
+/// 符号引用种类
+///
+/// 用于 [`GoFunctionInfo::references`] 指定要查找的引用类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceKind {
+    /// 类型引用（复合字面量、变量声明、类型转换等）
+    Type,
+    /// 变量引用
+    Variable,
+    /// 常量引用
+    Constant,
+    /// 函数调用
+    Function,
+}
+
+/// 函数体中标识符引用的汇总
+///
+/// 提取函数信息时遍历一次函数体的语法树，按引用种类记录出现过的标识符，
+/// 之后的每次符号查询都只是集合查找
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BodyReferences {
+    /// 类型标识符，以及被调用者解析为类型的调用目标（类型转换 `User(x)` 在语法上是一次调用）
+    types: HashSet<String>,
+    /// 出现过的所有标识符
+    identifiers: HashSet<String>,
+    /// 作为调用目标的标识符
+    calls: HashSet<String>,
+    /// 至少有一次出现没有被函数体内的局部声明遮蔽的标识符
+    unshadowed: HashSet<String>,
+    /// 按源码顺序记录的调用（去重）
+    call_sites: Vec<CallSite>,
+    /// 按源码顺序记录的类型引用（去重），`pkg.Type` 形式记录包限定符
+    type_references: Vec<(Option<String>, String)>,
+    /// 是否出现了 goroutine、channel、select 或 sync/atomic 包的使用
+    #[cfg_attr(feature = "serde", serde(default))]
+    concurrency: bool,
+}
+
+/// 函数体中的一次调用
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CallSite {
+    /// 被调用的函数或方法名
+    pub name: String,
+    /// `x.Method()` 形式中 `x` 的源码文本，直接调用 `name()` 时为 None
+    pub operand: Option<String>,
+}
+
+impl CallSite {
+    /// 从 `call_expression` 节点读取调用目标，调用目标不是标识符或选择器表达式时返回 None
+    fn from_call_expression(node: Node, source: &str) -> Option<Self> {
+        let function = node.child_by_field_name("function")?;
+        match function.kind() {
+            "identifier" => Some(Self {
+                name: source[function.byte_range()].to_string(),
+                operand: None,
+            }),
+            "selector_expression" => {
+                let operand = function.child_by_field_name("operand")?;
+                let field = function.child_by_field_name("field")?;
+                Some(Self {
+                    name: source[field.byte_range()].to_string(),
+                    operand: Some(source[operand.byte_range()].to_string()),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl BodyReferences {
+    /// 从函数或方法声明节点的函数体收集引用
+    pub fn from_function_node(func_node: Node, source: &str) -> Self {
+        let mut references = Self::default();
+        if let Some(body) = func_node.child_by_field_name("body") {
+            references.collect_references(body, source);
+            references.collect_unshadowed(body, source, &mut vec![HashSet::new()]);
+            references.resolve_conversions(func_node, source);
+        }
+        references
+    }
+
+    /// 解析单独的函数体源码并收集引用
+    ///
+    /// 用于不是从源文件中提取的函数信息（例如手工构造的函数），
+    /// 函数体为空或无法解析时返回空的汇总
+    pub fn from_body(body: &str) -> Self {
+        let body = body.trim();
+        if body.is_empty() {
+            return Self::default();
+        }
+
+        // 函数体单独无法解析，包装成一个完整的源文件
+        let source = if body.starts_with('{') {
+            format!("package p\nfunc _() {body}\n")
+        } else {
+            format!("package p\nfunc _() {{\n{body}\n}}\n")
+        };
+
+        let Ok(mut parser) = GoParser::new() else {
+            return Self::default();
+        };
+        let Ok(tree) = parser.parse_source(&source) else {
+            return Self::default();
+        };
+
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        let func_node = root
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "function_declaration");
+        func_node
+            .map(|func_node| Self::from_function_node(func_node, &source))
+            .unwrap_or_default()
+    }
+
+    /// 检查函数体是否引用了指定符号
+    pub fn references(&self, symbol: &str, kind: ReferenceKind) -> bool {
+        match kind {
+            ReferenceKind::Type => self.types.contains(symbol),
+            ReferenceKind::Variable | ReferenceKind::Constant => self.identifiers.contains(symbol),
+            ReferenceKind::Function => self.calls.contains(symbol),
+        }
+    }
+
+    /// 检查函数体中是否有未被局部声明遮蔽的 `name`（不考虑函数签名中的声明）
+    pub fn uses_unshadowed(&self, name: &str) -> bool {
+        self.unshadowed.contains(name)
+    }
+
+    /// 函数体中的调用，按源码中首次出现的顺序排列
+    pub fn call_sites(&self) -> &[CallSite] {
+        &self.call_sites
+    }
+
+    /// 函数体中引用的类型，按源码中首次出现的顺序返回包限定符和类型名
+    ///
+    /// 泛型实例化 `Foo[Bar]{}` 中的类型和类型实参、类型断言与类型分支中的类型都包含在内，
+    /// 字符串和注释中的文本不会被误认为类型
+    pub fn type_references(&self) -> impl Iterator<Item = (Option<&str>, &str)> {
+        self.type_references
+            .iter()
+            .map(|(package, name)| (package.as_deref(), name.as_str()))
+    }
+
+    /// 函数体是否涉及并发（goroutine、channel、select 或 sync/atomic 包）
+    ///
+    /// 只根据语法节点判断，注释和字符串中的 `go`、`chan` 等文本不会计入
+    pub fn touches_concurrency(&self) -> bool {
+        self.concurrency
+    }
+
+    /// 递归记录节点及其子节点中的标识符引用
+    fn collect_references(&mut self, node: Node, source: &str) {
+        let text = || source[node.byte_range()].to_string();
+        let is_call_target = || {
+            node.parent().is_some_and(|parent| {
+                parent.kind() == "call_expression"
+                    && parent
+                        .child_by_field_name("function")
+                        .is_some_and(|function| function.id() == node.id())
+            })
+        };
+
+        let mut add_type_reference = |package: Option<String>, name: String| {
+            let type_reference = (package, name);
+            if !self.type_references.contains(&type_reference) {
+                self.type_references.push(type_reference);
+            }
+        };
+
+        match node.kind() {
+            "qualified_type" => {
+                let package = node.child_by_field_name("package");
+                let name = node.child_by_field_name("name");
+                if let (Some(package), Some(name)) = (package, name) {
+                    add_type_reference(
+                        Some(source[package.byte_range()].to_string()),
+                        source[name.byte_range()].to_string(),
+                    );
+                }
+            }
+            "type_identifier" => {
+                // 限定类型的名称已经随包限定符记录
+                let is_qualified = node
+                    .parent()
+                    .is_some_and(|parent| parent.kind() == "qualified_type");
+                if !is_qualified {
+                    add_type_reference(None, text());
+                }
+                self.types.insert(text());
+            }
+            "identifier" => {
+                self.identifiers.insert(text());
+                if is_call_target() {
+                    self.calls.insert(text());
+                }
+            }
+            "call_expression" => {
+                let call = CallSite::from_call_expression(node, source)
+                    .filter(|call| !self.call_sites.contains(call));
+                if let Some(call) = call {
+                    self.call_sites.push(call);
+                }
+            }
+            _ => {}
+        }
+        self.concurrency |= Self::is_concurrency_node(node, source);
+
+        // 匿名节点（关键字和标点）下不会再有标识符，只遍历具名子节点
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.collect_references(child, source);
+        }
+    }
+
+    /// 把被调用者解析为类型的调用记为类型引用
+    ///
+    /// `User(x)` 在语法上与函数调用 `f(x)` 相同，只有被调用者是预声明类型、
+    /// 函数体内声明的类型或同一文件中声明的类型时才是类型转换
+    fn resolve_conversions(&mut self, func_node: Node, source: &str) {
+        // 函数体内声明的类型已经作为类型标识符记录
+        let unresolved: Vec<String> = self
+            .calls
+            .iter()
+            .filter(|name| !self.types.contains(*name))
+            .cloned()
+            .collect();
+        if unresolved.is_empty() {
+            return;
+        }
+
+        let mut file_types = HashSet::new();
+        let mut root = func_node;
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+        let mut cursor = root.walk();
+        for declaration in root.named_children(&mut cursor) {
+            if declaration.kind() != "type_declaration" {
+                continue;
+            }
+            let mut spec_cursor = declaration.walk();
+            for spec in declaration.named_children(&mut spec_cursor) {
+                if let Some(name) = spec.child_by_field_name("name") {
+                    file_types.insert(&source[name.byte_range()]);
+                }
+            }
+        }
+
+        for name in unresolved {
+            if Self::is_predeclared_type(&name) || file_types.contains(name.as_str()) {
+                self.types.insert(name);
+            }
+        }
+    }
+
+    /// 判断名称是否为 Go 的预声明类型
+    fn is_predeclared_type(name: &str) -> bool {
+        matches!(
+            name,
+            "any"
+                | "bool"
+                | "byte"
+                | "comparable"
+                | "complex64"
+                | "complex128"
+                | "error"
+                | "float32"
+                | "float64"
+                | "int"
+                | "int8"
+                | "int16"
+                | "int32"
+                | "int64"
+                | "rune"
+                | "string"
+                | "uint"
+                | "uint8"
+                | "uint16"
+                | "uint32"
+                | "uint64"
+                | "uintptr"
+        )
+    }
+
+    /// 判断具名节点本身是否表示一次并发操作
+    fn is_concurrency_node(node: Node, source: &str) -> bool {
+        let is_concurrency_package = |field: &str| {
+            node.child_by_field_name(field)
+                .is_some_and(|package| matches!(&source[package.byte_range()], "sync" | "atomic"))
+        };
+
+        match node.kind() {
+            "go_statement" | "channel_type" | "send_statement" | "select_statement" => true,
+            "unary_expression" => node
+                .child_by_field_name("operator")
+                .is_some_and(|operator| operator.kind() == "<-"),
+            "qualified_type" => is_concurrency_package("package"),
+            "selector_expression" => is_concurrency_package("operand"),
+            _ => false,
+        }
+    }
+
+    /// 按源码顺序遍历节点，`scopes` 记录每一层作用域中已经声明的局部名称
+    ///
+    /// 声明语句的右侧在声明生效前求值，因此 `config := config` 仍会读取外层的 `config`
+    fn collect_unshadowed(&mut self, node: Node, source: &str, scopes: &mut Vec<HashSet<String>>) {
+        let walk_field = |this: &mut Self, field: &str, scopes: &mut Vec<HashSet<String>>| {
+            if let Some(child) = node.child_by_field_name(field) {
+                this.collect_unshadowed(child, source, scopes);
+            }
+        };
+        let declare = |child: Option<Node>, scopes: &mut Vec<HashSet<String>>| {
+            if let Some(child) = child {
+                Self::declared_names(child, source, scopes.last_mut().unwrap());
+            }
+        };
+
+        match node.kind() {
+            "identifier" => {
+                let name = &source[node.byte_range()];
+                if !scopes.iter().any(|scope| scope.contains(name)) {
+                    self.unshadowed.insert(name.to_string());
+                }
+            }
+            "short_var_declaration" => {
+                walk_field(self, "right", scopes);
+                declare(node.child_by_field_name("left"), scopes);
+            }
+            "var_spec" | "const_spec" => {
+                walk_field(self, "value", scopes);
+                let mut cursor = node.walk();
+                for child in node.children_by_field_name("name", &mut cursor) {
+                    declare(Some(child), scopes);
+                }
+            }
+            "range_clause" => {
+                let mut cursor = node.walk();
+                let is_declaration = node.children(&mut cursor).any(|c| c.kind() == ":=");
+                if !is_declaration {
+                    walk_field(self, "left", scopes);
+                    walk_field(self, "right", scopes);
+                } else {
+                    walk_field(self, "right", scopes);
+                    declare(node.child_by_field_name("left"), scopes);
+                }
+            }
+            "func_literal" => {
+                scopes.push(HashSet::new());
+                declare(node.child_by_field_name("parameters"), scopes);
+                declare(node.child_by_field_name("result"), scopes);
+                walk_field(self, "body", scopes);
+                scopes.pop();
+            }
+            "type_switch_statement" => {
+                scopes.push(HashSet::new());
+                walk_field(self, "initializer", scopes);
+                walk_field(self, "value", scopes);
+                // 类型分支的别名只在各个分支内生效，在求值 `value` 之后声明
+                declare(node.child_by_field_name("alias"), scopes);
+                let mut cursor = node.walk();
+                let cases: Vec<Node> = node
+                    .named_children(&mut cursor)
+                    .filter(|child| matches!(child.kind(), "type_case" | "default_case"))
+                    .collect();
+                for case in cases {
+                    self.collect_unshadowed(case, source, scopes);
+                }
+                scopes.pop();
+            }
+            kind => {
+                let creates_scope = matches!(
+                    kind,
+                    "block"
+                        | "if_statement"
+                        | "for_statement"
+                        | "expression_switch_statement"
+                        | "select_statement"
+                        | "expression_case"
+                        | "default_case"
+                        | "type_case"
+                        | "communication_case"
+                );
+                if creates_scope {
+                    scopes.push(HashSet::new());
+                }
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    self.collect_unshadowed(child, source, scopes);
+                }
+                if creates_scope {
+                    scopes.pop();
+                }
+            }
+        }
+    }
+
+    /// 收集声明列表（标识符列表或参数列表）声明的名称
+    fn declared_names(node: Node, source: &str, names: &mut HashSet<String>) {
+        match node.kind() {
+            "identifier" => {
+                names.insert(source[node.byte_range()].to_string());
+            }
+            "parameter_declaration" | "variadic_parameter_declaration" => {
+                let mut cursor = node.walk();
+                for child in node.children_by_field_name("name", &mut cursor) {
+                    names.insert(source[child.byte_range()].to_string());
+                }
+            }
+            _ => {
+                let mut cursor = node.walk();
+                for child in node.named_children(&mut cursor) {
+                    Self::declared_names(child, source, names);
+                }
+            }
+        }
+    }
+}
+
+impl GoFunctionInfo {
+    /// 检查函数体是否引用了指定符号
+    ///
+    /// 基于 CST 而不是文本匹配：只有语法树中完全相同的标识符才算引用，
+    /// 因此 `User{}` 会被视为引用了 `User`，但不会被视为引用了 `Userland`。
+    /// 调用 `User(x)` 只有在 `User` 是预声明类型或同一文件中声明的类型时才算类型引用。
+    /// 函数体中不包含该名称时直接返回，否则在提取时收集的引用汇总中查找
+    pub fn references(&self, symbol: &str, kind: ReferenceKind) -> bool {
+        if symbol.is_empty() || !self.body.contains(symbol) {
+            return false;
+        }
+        self.body_references.references(symbol, kind)
+    }
+
+    /// 检查函数体是否引用了包级变量 `name`
+    ///
+    /// 与 [`GoFunctionInfo::references`] 不同，这里会跟踪作用域：被参数、接收者、命名返回值
+    /// 或局部声明（`:=`、`var`、`range`、类型分支别名）遮蔽的同名标识符不算引用。
+    /// 声明语句的右侧在声明生效前求值，因此 `config := config` 仍会读取包级变量
+    pub fn references_package_variable(&self, name: &str) -> bool {
+        if name.is_empty() || !self.body.contains(name) || self.signature_declares(name) {
+            return false;
+        }
+        self.body_references.uses_unshadowed(name)
+    }
+
+    /// 接收者、参数或命名返回值是否声明了 `name`，这些声明在整个函数体内遮蔽同名的包级符号
+    pub fn signature_declares(&self, name: &str) -> bool {
+        self.receiver.as_ref().is_some_and(|r| r.name == name)
+            || self.parameters.iter().any(|p| p.name == name)
+            || self
+                .return_types
+                .iter()
+                .any(|r| r.result_name.as_deref() == Some(name))
+    }
 }
 
 /// Go 语言接收者信息（方法的接收者）
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GoReceiverInfo {
     pub name: String,
     pub type_name: String,
     pub is_pointer: bool,
 }
 
+impl GoReceiverInfo {
+    /// 从签名中的接收者文本解析接收者信息
+    ///
+    /// 支持 `(u User)`、`(u *User)`、`(*User)` 以及泛型接收者 `(s *Stack[T])`，
+    /// `type_name` 总是不带 `*` 和类型参数的基础类型名
+    pub fn from_signature(receiver: &str) -> Self {
+        let receiver = receiver
+            .trim()
+            .trim_start_matches('(')
+            .trim_end_matches(')');
+        // 接收者名只能是标识符，避免把 `Map[K, V]` 中的空格误当作分隔
+        let (name, type_str) = match receiver.trim().split_once(char::is_whitespace) {
+            Some((name, type_str)) if name.chars().all(|c| c.is_alphanumeric() || c == '_') => {
+                (name, type_str.trim())
+            }
+            _ => ("", receiver.trim()),
+        };
+
+        Self {
+            name: name.to_string(),
+            type_name: Self::base_type_name(type_str).to_string(),
+            is_pointer: type_str.starts_with('*'),
+        }
+    }
+
+    /// 接收者是否属于指定类型（值接收者和指针接收者都视为属于该类型）
+    pub fn belongs_to(&self, type_name: &str) -> bool {
+        Self::base_type_name(&self.type_name) == Self::base_type_name(type_name)
+    }
+
+    /// 去掉指针标记和类型参数后的类型名
+    fn base_type_name(type_str: &str) -> &str {
+        let type_str = type_str.trim().trim_start_matches('*').trim();
+        type_str.split('[').next().unwrap_or(type_str).trim()
+    }
+}
+
 /// Go 语言参数信息
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GoParameter {
     pub name: String,
     pub param_type: GoType,
+    /// 是否为可变参数 `name ...T`（`param_type` 为元素类型 `T`）
+    pub is_variadic: bool,
 }
 
-/// Go 语言类型信息
+/// Go 语言结构体字段
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GoStructField {
+    /// 字段名，嵌入字段为其类型名（去掉指针和包名）
+    pub name: String,
+    pub field_type: GoType,
+    /// 字段标签内容（不含反引号），例如 `json:"name,omitempty"`
+    pub tag: Option<String>,
+    /// 是否为嵌入（匿名）字段
+    pub embedded: bool,
+}
+
+impl GoStructField {
+    /// 读取标签中指定键的值，例如从 `json:"name,omitempty" xml:"n"` 中读取 `json` 得到 `name,omitempty`
+    pub fn tag_value(&self, key: &str) -> Option<String> {
+        let mut rest = self.tag.as_deref()?.trim();
+        while !rest.is_empty() {
+            let (name, after) = rest.split_once(':')?;
+            let after = after.strip_prefix('"')?;
+            let end = after.find('"')?;
+            if name.trim() == key {
+                return Some(after[..end].to_string());
+            }
+            rest = after[end + 1..].trim_start();
+        }
+        None
+    }
+}
+
+/// Go 语言泛型类型参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GoTypeParam {
+    pub name: String,
+    /// 类型约束原文，例如 `any`、`comparable` 或 `~int | ~float64`
+    pub constraint: String,
+}
+
+/// Go 语言类型信息
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GoType {
+    /// 元素类型名，不包含已记录在 `is_pointer`/`is_slice` 等字段中的外层结构；映射类型为映射原文
     pub name: String,
+    /// 外层是否为指针 `*T`
     pub is_pointer: bool,
+    /// 外层（指针之内）是否为切片 `[]T`
     pub is_slice: bool,
+    /// 命名返回值的名称，例如 `(n int, err error)` 中的 `n`；仅用于返回类型
+    pub result_name: Option<String>,
+    /// 是否为通道类型，此时 `name`/`is_pointer`/`is_slice` 描述的是元素类型
+    pub is_channel: bool,
+    /// 通道方向，仅当 `is_channel` 为真时有值
+    pub channel_direction: Option<ChannelDir>,
+    /// 映射类型 `map[K]V` 的键类型
+    pub key_type: Option<Box<GoType>>,
+    /// 映射类型 `map[K]V` 的值类型
+    pub value_type: Option<Box<GoType>>,
+    /// 固定长度数组 `[N]T` 的长度表达式，可以是字面量或常量名；多维数组的内层维度保留在 `name` 中
+    pub array_len: Option<String>,
+}
+
+/// Go 通道方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ChannelDir {
+    /// `chan T`
+    Bidirectional,
+    /// `chan<- T`
+    SendOnly,
+    /// `<-chan T`
+    ReceiveOnly,
+}
+
+impl GoType {
+    /// 从 CST 类型节点构造类型信息
+    ///
+    /// 外层结构按通道、数组、指针、切片的顺序记录到对应字段，`name` 只保留元素类型名；
+    /// 无法再用这些字段表达的内层结构（如 `[]*T` 中的 `*T`、多维数组的内层维度）保留原文。
+    /// 映射类型记录键值类型，`name` 为映射类型原文
+    pub fn from_type_node(node: Node, source: &str) -> Self {
+        let text = |node: Node| source[node.byte_range()].to_string();
+        match node.kind() {
+            "parenthesized_type" => match node.named_child(0) {
+                Some(inner) => Self::from_type_node(inner, source),
+                None => Self::named(text(node)),
+            },
+            "channel_type" => Self::from_channel_node(node, source),
+            "array_type" => {
+                let array_len = node.child_by_field_name("length").map(text);
+                let Some(element) = node.child_by_field_name("element") else {
+                    return Self::named(text(node));
+                };
+                match Self::from_type_node(element, source) {
+                    element_type
+                        if element_type.array_len.is_none() && !element_type.is_channel =>
+                    {
+                        Self {
+                            array_len,
+                            ..element_type
+                        }
+                    }
+                    // 多维数组只记录最外层长度，内层维度（以及通道元素）保留在元素类型原文中
+                    _ => Self {
+                        array_len,
+                        ..Self::named(text(element))
+                    },
+                }
+            }
+            "pointer_type" => {
+                let Some(element) = node.named_child(0) else {
+                    return Self::named(text(node));
+                };
+                match Self::from_type_node(element, source) {
+                    // `*[]T` 的切片可以继续记录在标志中
+                    element_type if element_type.is_plain() || element_type.is_plain_slice() => {
+                        Self {
+                            is_pointer: true,
+                            ..element_type
+                        }
+                    }
+                    _ => Self {
+                        is_pointer: true,
+                        ..Self::named(text(element))
+                    },
+                }
+            }
+            "slice_type" => {
+                let Some(element) = node.child_by_field_name("element") else {
+                    return Self::named(text(node));
+                };
+                match Self::from_type_node(element, source) {
+                    element_type if element_type.is_plain() => Self {
+                        is_slice: true,
+                        ..element_type
+                    },
+                    _ => Self {
+                        is_slice: true,
+                        ..Self::named(text(element))
+                    },
+                }
+            }
+            "map_type" => {
+                let component = |field: &str| {
+                    node.child_by_field_name(field)
+                        .map(|child| Box::new(Self::from_type_node(child, source)))
+                };
+                Self {
+                    key_type: component("key"),
+                    value_type: component("value"),
+                    ..Self::named(text(node))
+                }
+            }
+            _ => Self::named(text(node)),
+        }
+    }
+
+    /// 从类型文本构造类型信息
+    ///
+    /// 供没有 CST 节点的调用方使用：把文本包装成变量声明解析出类型节点后交给
+    /// [`Self::from_type_node`]，无法解析时整段文本作为类型名
+    pub fn from_type_text(text: &str) -> Self {
+        let text = text.trim();
+        // 类型文本单独无法解析，包装成一个变量声明
+        let source = format!("package p\nvar _ {text}\n");
+        let tree = GoParser::new()
+            .ok()
+            .and_then(|mut parser| parser.parse_source(&source).ok());
+        let type_node = tree
+            .as_ref()
+            .and_then(|tree| Self::find_var_type_node(tree.root_node()))
+            .filter(|node| &source[node.byte_range()] == text);
+
+        match type_node {
+            Some(node) => Self::from_type_node(node, &source),
+            None => Self::named(text.to_string()),
+        }
+    }
+
+    /// 签名中用于比较的类型表示
+    ///
+    /// 类型结构（通道方向、数组长度、映射键值等）都参与比较，命名返回值的名称不参与
+    pub fn without_result_name(&self) -> Self {
+        Self {
+            result_name: None,
+            ..self.clone()
+        }
+    }
+
+    /// 只有类型名、没有任何结构标志的类型
+    fn named(name: String) -> Self {
+        Self {
+            name,
+            is_pointer: false,
+            is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
+        }
+    }
+
+    /// 是否为不带任何外层结构的普通类型
+    fn is_plain(&self) -> bool {
+        !self.is_pointer
+            && !self.is_slice
+            && !self.is_channel
+            && self.array_len.is_none()
+            && self.key_type.is_none()
+    }
+
+    /// 是否为普通类型的切片 `[]T`
+    fn is_plain_slice(&self) -> bool {
+        self.is_slice
+            && Self {
+                is_slice: false,
+                ..self.clone()
+            }
+            .is_plain()
+    }
+
+    /// 类型中出现的所有数组长度表达式，包括多维数组的内层维度和映射键值中的数组
+    pub fn array_lengths(&self) -> Vec<String> {
+        let mut lengths: Vec<String> = self.array_len.iter().cloned().collect();
+        let mut rest = self.name.as_str();
+        while let Some(inner) = rest.strip_prefix('[') {
+            match inner.split_once(']') {
+                Some((length, element)) if !length.is_empty() => {
+                    lengths.push(length.trim().to_string());
+                    rest = element;
+                }
+                _ => break,
+            }
+        }
+        for component in self.map_component_types() {
+            lengths.extend(component.array_lengths());
+        }
+        lengths
+    }
+
+    /// 映射类型中出现的所有非映射类型（递归展开嵌套映射的键和值）
+    pub fn map_component_types(&self) -> Vec<&GoType> {
+        match (&self.key_type, &self.value_type) {
+            (Some(key), Some(value)) => {
+                let mut components = Vec::new();
+                for component in [key, value] {
+                    if component.key_type.is_some() {
+                        components.extend(component.map_component_types());
+                    } else {
+                        components.push(component.as_ref());
+                    }
+                }
+                components
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// 从 `channel_type` 节点构造类型信息，元素类型的结构记录在同一个 `GoType` 中
+    fn from_channel_node(node: Node, source: &str) -> Self {
+        // `<-chan T` 以箭头开头；`chan<- T` 的箭头位于 `chan` 之后
+        let mut cursor = node.walk();
+        let tokens: Vec<&str> = node
+            .children(&mut cursor)
+            .map(|child| child.kind())
+            .take_while(|kind| *kind == "<-" || *kind == "chan")
+            .collect();
+        let direction = match tokens.as_slice() {
+            ["<-", ..] => ChannelDir::ReceiveOnly,
+            ["chan", "<-", ..] => ChannelDir::SendOnly,
+            _ => ChannelDir::Bidirectional,
+        };
+
+        let Some(element) = node.child_by_field_name("value") else {
+            return Self {
+                is_channel: true,
+                channel_direction: Some(direction),
+                ..Self::named(String::new())
+            };
+        };
+        match Self::from_type_node(element, source) {
+            element_type if !element_type.is_channel => Self {
+                is_channel: true,
+                channel_direction: Some(direction),
+                ..element_type
+            },
+            // 通道的通道无法用一层 GoType 表达，元素保留原文
+            _ => Self {
+                is_channel: true,
+                channel_direction: Some(direction),
+                ..Self::named(source[element.byte_range()].to_string())
+            },
+        }
+    }
+
+    /// 查找 `var _ T` 声明中的类型节点
+    fn find_var_type_node(node: Node) -> Option<Node> {
+        if node.kind() == "var_spec" {
+            return node.child_by_field_name("type");
+        }
+        let mut cursor = node.walk();
+        node.named_children(&mut cursor)
+            .find_map(Self::find_var_type_node)
+    }
 }
 
 /// Go 语言类型定义
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GoTypeDefinition {
     pub name: String,
     pub kind: GoTypeKind,
     pub definition: String,
+    /// 泛型类型参数，例如 `type Result[T any] struct` 中的 `T`
+    pub type_parameters: Vec<GoTypeParam>,
+    /// 结构体字段（仅结构体类型）
+    pub fields: Vec<GoStructField>,
+    pub start_line: u32,
+    pub end_line: u32,
+    /// 声明在源文件中的起始字节偏移
+    pub byte_start: usize,
+    /// 声明在源文件中的结束字节偏移（不含）
+    pub byte_end: usize,
+    /// 紧邻声明上方的文档注释原文（保留 `//` 前缀，多行以换行拼接）
+    pub doc_comment: Option<String>,
     pub file_path: PathBuf,
     pub dependencies: Vec<String>,
 }
 
 /// Go 语言类型种类
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GoTypeKind {
     Struct,
     Interface,
@@ -63,30 +899,51 @@ pub enum GoTypeKind {
     Constant,
 }
 
+/// 常量表达式中的 `iota` 标识符
+pub(crate) static IOTA: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\biota\b").unwrap());
+
 /// Go 语言常量定义
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GoConstantDefinition {
     pub name: String,
+    /// 常量的有效表达式；常量块中省略的表达式会继承上一个规范，例如 `iota`
     pub value: String,
     pub const_type: Option<GoType>,
+    /// 表达式引用 `iota` 时，该常量在常量块中的 iota 值
+    pub iota_index: Option<usize>,
     pub start_line: u32,
     pub end_line: u32,
+    /// 声明在源文件中的起始字节偏移
+    pub byte_start: usize,
+    /// 声明在源文件中的结束字节偏移（不含）
+    pub byte_end: usize,
+    /// 紧邻声明上方的文档注释原文（保留 `//` 前缀，多行以换行拼接）
+    pub doc_comment: Option<String>,
     pub file_path: PathBuf,
 }
 
 /// Go 语言变量定义
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GoVariableDefinition {
     pub name: String,
     pub var_type: Option<GoType>,
     pub initial_value: Option<String>,
     pub start_line: u32,
     pub end_line: u32,
+    /// 声明在源文件中的起始字节偏移
+    pub byte_start: usize,
+    /// 声明在源文件中的结束字节偏移（不含）
+    pub byte_end: usize,
+    /// 紧邻声明上方的文档注释原文（保留 `//` 前缀，多行以换行拼接）
+    pub doc_comment: Option<String>,
     pub file_path: PathBuf,
 }
 
 /// Go 语言声明枚举
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GoDeclaration {
     Function(GoFunctionInfo),
     Method(GoFunctionInfo),
@@ -120,11 +977,7 @@ impl Declaration for GoDeclaration {
         match self {
             GoDeclaration::Function(f) => f.start_line,
             GoDeclaration::Method(m) => m.start_line,
-            GoDeclaration::Type(_t) => {
-                // 对于类型定义，我们需要从定义中解析行号，这里先返回 0
-                // 在实际实现中应该从 AST 中获取
-                0
-            }
+            GoDeclaration::Type(t) => t.start_line,
             GoDeclaration::Constant(c) => c.start_line,
             GoDeclaration::Variable(v) => v.start_line,
         }
@@ -134,11 +987,7 @@ impl Declaration for GoDeclaration {
         match self {
             GoDeclaration::Function(f) => f.end_line,
             GoDeclaration::Method(m) => m.end_line,
-            GoDeclaration::Type(_) => {
-                // 对于类型定义，我们需要从定义中解析行号，这里先返回 0
-                // 在实际实现中应该从 AST 中获取
-                0
-            }
+            GoDeclaration::Type(t) => t.end_line,
             GoDeclaration::Constant(c) => c.end_line,
             GoDeclaration::Variable(v) => v.end_line,
         }
@@ -163,6 +1012,11 @@ impl Declaration for GoDeclaration {
     }
 }
 
+/// 源文件缺少 package 子句时使用的包名
+///
+/// 不是合法的 Go 标识符，因此不会与真实的包名冲突
+pub const UNKNOWN_PACKAGE_NAME: &str = "<unknown>";
+
 /// Go 语言特定信息
 #[derive(Debug)]
 pub struct GoLanguageInfo {
@@ -191,6 +1045,11 @@ impl GoLanguageInfo {
         }
     }
 
+    /// 源文件是否声明了包名
+    pub fn has_known_package(&self) -> bool {
+        self.package_name != UNKNOWN_PACKAGE_NAME
+    }
+
     /// 添加导入声明
     pub fn add_import(&mut self, import: Import) {
         self.imports.push(import);
@@ -219,6 +1078,34 @@ impl GoLanguageInfo {
         })
     }
 
+    /// 查找指定类型的所有方法
+    ///
+    /// 值接收者（`func (u User)`）和指针接收者（`func (u *User)`）的方法都会被返回
+    pub fn methods_of(&self, type_name: &str) -> Vec<&GoFunctionInfo> {
+        self.declarations
+            .iter()
+            .filter_map(|decl| match decl.as_any().downcast_ref::<GoDeclaration>() {
+                Some(GoDeclaration::Function(func)) | Some(GoDeclaration::Method(func)) => func
+                    .receiver
+                    .as_ref()
+                    .filter(|receiver| receiver.belongs_to(type_name))
+                    .map(|_| func),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 文件的导入别名表：引用时使用的包名到导入路径的映射
+    ///
+    /// 未设置别名的导入使用导入路径的最后一段；空白导入（`_`）和点导入（`.`）不引入限定符，不会出现在表中
+    pub fn import_aliases(&self) -> HashMap<&str, &str> {
+        self.imports
+            .iter()
+            .filter(|import| import.kind == ImportKind::Normal)
+            .map(|import| (import.local_name(), import.path.as_str()))
+            .collect()
+    }
+
     /// 查找指定名称的类型定义
     pub fn find_type(&self, name: &str) -> Option<&GoTypeDefinition> {
         self.declarations.iter().find_map(|decl| {
@@ -266,6 +1153,8 @@ impl LanguageSpecificInfo for GoLanguageInfo {
 /// Go 语言解析器
 pub struct GoParser {
     parser: Parser,
+    /// 单次解析的超时时间
+    timeout: Option<Duration>,
 }
 
 impl GoParser {
@@ -278,7 +1167,44 @@ impl GoParser {
             SemanticDiffError::TreeSitterError(format!("Failed to set Go language: {e}"))
         })?;
 
-        Ok(Self { parser })
+        Ok(Self {
+            parser,
+            timeout: None,
+        })
+    }
+
+    /// 解析源码，设置了超时时间时超时后取消解析
+    fn parse_with_timeout(
+        &mut self,
+        source: &str,
+        old_tree: Option<&Tree>,
+    ) -> Option<Result<Tree>> {
+        let Some(timeout) = self.timeout else {
+            return self.parser.parse(source, old_tree).map(Ok);
+        };
+
+        let deadline = Instant::now() + timeout;
+        let mut timed_out = false;
+        let mut progress = |_: &ParseState| {
+            timed_out = Instant::now() >= deadline;
+            timed_out
+        };
+        let bytes = source.as_bytes();
+        let tree = self.parser.parse_with_options(
+            &mut |offset, _| &bytes[offset.min(bytes.len())..],
+            old_tree,
+            Some(ParseOptions::new().progress_callback(&mut progress)),
+        );
+
+        if timed_out {
+            // 被取消的解析会在下次调用时继续，需要先重置解析器
+            self.parser.reset();
+            return Some(Err(SemanticDiffError::ParseTimeout(format!(
+                "Go source ({} bytes) was not parsed within {timeout:?}",
+                source.len()
+            ))));
+        }
+        tree.map(Ok)
     }
 
     /// 递归查找包含指定位置的最小节点
@@ -320,11 +1246,39 @@ impl GoParser {
 impl LanguageParser for GoParser {
     /// 解析源码为语法树
     fn parse_source(&mut self, source: &str) -> Result<Tree> {
-        self.parser.parse(source, None).ok_or_else(|| {
-            SemanticDiffError::ParseError("Failed to parse Go source code".to_string())
+        self.parse_with_timeout(source, None).unwrap_or_else(|| {
+            Err(SemanticDiffError::ParseError(
+                "Failed to parse Go source code".to_string(),
+            ))
         })
     }
 
+    /// 基于旧版本的语法树增量解析新版本源码
+    fn parse_incremental(
+        &mut self,
+        old_tree: &Tree,
+        old_src: &str,
+        new_src: &str,
+        edits: &[DiffHunk],
+    ) -> Result<Tree> {
+        let mut tree = old_tree.clone();
+        for edit in input_edits(old_src, new_src, edits) {
+            tree.edit(&edit);
+        }
+
+        self.parse_with_timeout(new_src, Some(&tree))
+            .unwrap_or_else(|| {
+                Err(SemanticDiffError::ParseError(
+                    "Failed to incrementally parse Go source code".to_string(),
+                ))
+            })
+    }
+
+    /// 设置单次解析的超时时间
+    fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
     /// 根据行列位置查找节点
     fn find_node_at_position<'a>(
         &self,
@@ -445,6 +1399,84 @@ func multiply(x, y int) int {
         );
     }
 
+    /// 测试增量解析与完整解析结果一致
+    #[test]
+    fn test_parse_incremental_matches_full_reparse() {
+        use crate::git::DiffHunk;
+
+        let old_src = "package main\n\nfunc A() int {\n\treturn 1\n}\n\nfunc B() int {\n\treturn 2\n}\n\nfunc C() int {\n\treturn 3\n}\n";
+        // 修改 B 的函数体：第 8 行被两行取代
+        let new_src = "package main\n\nfunc A() int {\n\treturn 1\n}\n\nfunc B() int {\n\tx := 2\n\treturn x\n}\n\nfunc C() int {\n\treturn 3\n}\n";
+        let hunks = vec![DiffHunk {
+            old_start: 8,
+            old_lines: 1,
+            new_start: 8,
+            new_lines: 2,
+            lines: Vec::new(),
+            context_lines: 0,
+        }];
+
+        let mut parser = GoParser::new().unwrap();
+        let old_tree = parser.parse_source(old_src).unwrap();
+        let incremental = parser
+            .parse_incremental(&old_tree, old_src, new_src, &hunks)
+            .unwrap();
+        let full = parser.parse_source(new_src).unwrap();
+
+        assert!(!incremental.root_node().has_error());
+        assert_eq!(
+            incremental.root_node().to_sexp(),
+            full.root_node().to_sexp()
+        );
+        assert_eq!(
+            incremental.root_node().byte_range(),
+            full.root_node().byte_range()
+        );
+
+        // 编辑只覆盖被修改的行，重新解析影响的范围小于整个文件
+        let edits = input_edits(old_src, new_src, &hunks);
+        assert_eq!(edits.len(), 1);
+        assert_eq!(
+            &new_src[edits[0].start_byte..edits[0].new_end_byte],
+            "\tx := 2\n\treturn x\n"
+        );
+
+        let mut edited = old_tree.clone();
+        for edit in &edits {
+            edited.edit(edit);
+        }
+        let changed_bytes: usize = edited
+            .changed_ranges(&incremental)
+            .map(|range| range.end_byte - range.start_byte)
+            .sum();
+        assert!(changed_bytes < new_src.len());
+    }
+
+    /// 测试解析超时后返回超时错误，并且解析器可以继续使用
+    #[test]
+    fn test_parse_timeout() {
+        let mut source = String::from("package main\n");
+        for i in 0..20_000 {
+            source.push_str(&format!(
+                "\nfunc F{i}(a, b int) int {{\n\treturn a*{i} + b\n}}\n"
+            ));
+        }
+
+        let mut parser = GoParser::new().unwrap();
+        parser.set_timeout(Some(Duration::from_nanos(1)));
+        let result = parser.parse_source(&source);
+        assert!(
+            matches!(result, Err(SemanticDiffError::ParseTimeout(_))),
+            "expected a timeout, got {result:?}"
+        );
+
+        // 取消超时后重新完整解析
+        parser.set_timeout(None);
+        let tree = parser.parse_source(&source).unwrap();
+        assert!(!tree.root_node().has_error());
+        assert_eq!(tree.root_node().end_byte(), source.len());
+    }
+
     /// 测试根据位置查找节点
     #[test]
     fn test_find_node_at_position() {
@@ -938,10 +1970,12 @@ mod go_data_tests {
         let import1 = Import {
             path: "fmt".to_string(),
             alias: None,
+            kind: ImportKind::Normal,
         };
         let import2 = Import {
             path: "github.com/example/pkg".to_string(),
             alias: Some("pkg".to_string()),
+            kind: ImportKind::Normal,
         };
 
         go_info.add_import(import1.clone());
@@ -968,6 +2002,11 @@ mod go_data_tests {
             start_line: 1,
             end_line: 3,
             file_path: PathBuf::from("test.go"),
+            body_references: Default::default(),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         };
 
         let type_def = GoTypeDefinition {
@@ -976,6 +2015,13 @@ mod go_data_tests {
             definition: "type TestStruct struct {}".to_string(),
             file_path: PathBuf::from("test.go"),
             dependencies: vec![],
+            start_line: 0,
+            end_line: 0,
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            fields: Vec::new(),
+            doc_comment: None,
         };
 
         go_info.add_go_declaration(GoDeclaration::Function(func_info));
@@ -1023,6 +2069,12 @@ mod go_data_tests {
             name: "string".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         };
         assert_eq!(go_type.name, "string");
         assert!(!go_type.is_pointer);
@@ -1031,6 +2083,7 @@ mod go_data_tests {
         let parameter = GoParameter {
             name: "msg".to_string(),
             param_type: go_type,
+            is_variadic: false,
         };
         assert_eq!(parameter.name, "msg");
         assert_eq!(parameter.param_type.name, "string");
@@ -1051,10 +2104,20 @@ mod go_data_tests {
                 name: "int".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             }),
             start_line: 1,
             end_line: 1,
             file_path: PathBuf::from("constants.go"),
+            byte_start: 0,
+            byte_end: 0,
+            iota_index: None,
+            doc_comment: None,
         };
         assert_eq!(constant.name, "MaxSize");
         assert_eq!(constant.value, "100");
@@ -1066,17 +2129,51 @@ mod go_data_tests {
                 name: "int".to_string(),
                 is_pointer: false,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             }),
             initial_value: Some("0".to_string()),
             start_line: 2,
             end_line: 2,
             file_path: PathBuf::from("variables.go"),
+            byte_start: 0,
+            byte_end: 0,
+            doc_comment: None,
         };
         assert_eq!(variable.name, "counter");
         assert_eq!(variable.initial_value, Some("0".to_string()));
         assert_eq!(variable.start_line, 2);
     }
 
+    #[test]
+    fn test_receiver_from_signature() {
+        let value = GoReceiverInfo::from_signature("(u User)");
+        assert_eq!(value.name, "u");
+        assert_eq!(value.type_name, "User");
+        assert!(!value.is_pointer);
+
+        let pointer = GoReceiverInfo::from_signature("(u *User)");
+        assert_eq!(pointer.type_name, "User");
+        assert!(pointer.is_pointer);
+
+        let anonymous = GoReceiverInfo::from_signature("(*User)");
+        assert_eq!(anonymous.name, "");
+        assert_eq!(anonymous.type_name, "User");
+
+        let generic = GoReceiverInfo::from_signature("(s *Stack[T])");
+        assert_eq!(generic.type_name, "Stack");
+        let generic = GoReceiverInfo::from_signature("(m Map[K, V])");
+        assert_eq!(generic.name, "m");
+        assert_eq!(generic.type_name, "Map");
+
+        assert!(value.belongs_to("User") && pointer.belongs_to("*User"));
+        assert!(!pointer.belongs_to("UserStore"));
+    }
+
     #[test]
     fn test_go_type_kind_enum() {
         // 测试 GoTypeKind 枚举
@@ -1125,6 +2222,11 @@ mod go_data_tests {
             start_line: 1,
             end_line: 1,
             file_path: PathBuf::from("test.go"),
+            body_references: Default::default(),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         };
 
         let type_def = GoTypeDefinition {
@@ -1133,6 +2235,13 @@ mod go_data_tests {
             definition: "".to_string(),
             file_path: PathBuf::from("test.go"),
             dependencies: vec![],
+            start_line: 0,
+            end_line: 0,
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            fields: Vec::new(),
+            doc_comment: None,
         };
 
         let const_def = GoConstantDefinition {
@@ -1142,6 +2251,10 @@ mod go_data_tests {
             start_line: 1,
             end_line: 1,
             file_path: PathBuf::from("test.go"),
+            byte_start: 0,
+            byte_end: 0,
+            iota_index: None,
+            doc_comment: None,
         };
 
         let var_def = GoVariableDefinition {
@@ -1151,6 +2264,9 @@ mod go_data_tests {
             start_line: 1,
             end_line: 1,
             file_path: PathBuf::from("test.go"),
+            byte_start: 0,
+            byte_end: 0,
+            doc_comment: None,
         };
 
         let func_decl = GoDeclaration::Function(func_info);
@@ -1192,6 +2308,11 @@ mod go_data_tests {
             start_line: 10,
             end_line: 15,
             file_path: PathBuf::from("test.go"),
+            body_references: Default::default(),
+            byte_start: 0,
+            byte_end: 0,
+            type_parameters: Vec::new(),
+            doc_comment: None,
         };
 
         let func_decl = GoDeclaration::Function(func_info);
@@ -1214,4 +2335,240 @@ mod go_data_tests {
             panic!("Expected Function declaration");
         }
     }
+
+    #[test]
+    fn test_function_references_symbol() {
+        let body = r#"{
+    u := User{}
+    count := maxUsers
+    save(u, count)
+    return u
+}"#;
+        let source = format!("package p\n\nfunc createUser() User {body}\n");
+        let mut parser = GoParser::new().unwrap();
+        let tree = parser.parse_source(&source).unwrap();
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        let func_node = root
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "function_declaration")
+            .unwrap();
+
+        let func_info = GoFunctionInfo {
+            name: "createUser".to_string(),
+            receiver: None,
+            parameters: vec![],
+            return_types: vec![],
+            body: body.to_string(),
+            start_line: 3,
+            end_line: 8,
+            file_path: PathBuf::from("user.go"),
+            body_references: Arc::new(BodyReferences::from_function_node(func_node, &source)),
+            byte_start: func_node.start_byte(),
+            byte_end: func_node.end_byte(),
+            type_parameters: Vec::new(),
+            doc_comment: None,
+        };
+
+        // 类型引用必须完整匹配标识符
+        assert!(func_info.references("User", ReferenceKind::Type));
+        assert!(!func_info.references("Userland", ReferenceKind::Type));
+        assert!(!func_info.references("Use", ReferenceKind::Type));
+
+        assert!(func_info.references("maxUsers", ReferenceKind::Constant));
+        assert!(func_info.references("maxUsers", ReferenceKind::Variable));
+        assert!(!func_info.references("maxUser", ReferenceKind::Variable));
+
+        assert!(func_info.references("save", ReferenceKind::Function));
+        assert!(!func_info.references("count", ReferenceKind::Function));
+
+        // 普通函数调用不是类型引用
+        assert!(!func_info.references("save", ReferenceKind::Type));
+    }
+
+    #[test]
+    fn test_body_references_conversions() {
+        let source = r#"package p
+
+type Celsius float64
+
+func convert(x float64) {
+    c := Celsius(x)
+    n := int(c)
+    report(n)
+}
+"#;
+        let mut parser = GoParser::new().unwrap();
+        let tree = parser.parse_source(source).unwrap();
+        let root = tree.root_node();
+        let mut cursor = root.walk();
+        let func_node = root
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "function_declaration")
+            .unwrap();
+        let references = BodyReferences::from_function_node(func_node, source);
+
+        // 被调用者是同一文件中声明的类型或预声明类型时才是类型转换
+        assert!(references.references("Celsius", ReferenceKind::Type));
+        assert!(references.references("int", ReferenceKind::Type));
+        assert!(!references.references("report", ReferenceKind::Type));
+        assert!(references.references("report", ReferenceKind::Function));
+    }
+
+    #[test]
+    fn test_body_references_touches_concurrency() {
+        for body in [
+            "go worker(jobs)",
+            "done := make(chan struct{})",
+            "results <- value",
+            "value := <-results",
+            "select {\ndefault:\n}",
+            "var mu sync.Mutex",
+            "atomic.AddInt64(&count, 1)",
+        ] {
+            assert!(
+                BodyReferences::from_body(body).touches_concurrency(),
+                "{body} should touch concurrency"
+            );
+        }
+
+        // 注释、字符串和普通标识符中的关键字文本不算并发操作
+        for body in [
+            "// go to the channel, select one\nreturn nil",
+            r#"log.Print("go chan <- select sync.Mutex")"#,
+            "gopher := channel - selected",
+        ] {
+            assert!(
+                !BodyReferences::from_body(body).touches_concurrency(),
+                "{body} should not touch concurrency"
+            );
+        }
+    }
+
+    /// 测试通道类型的方向和元素类型解析
+    #[test]
+    fn test_go_type_from_channel_text() {
+        let bidirectional = GoType::from_type_text("chan int");
+        assert!(bidirectional.is_channel);
+        assert_eq!(
+            bidirectional.channel_direction,
+            Some(ChannelDir::Bidirectional)
+        );
+        assert_eq!(bidirectional.name, "int");
+
+        let receive_only = GoType::from_type_text("<-chan Task");
+        assert!(receive_only.is_channel);
+        assert_eq!(
+            receive_only.channel_direction,
+            Some(ChannelDir::ReceiveOnly)
+        );
+        assert_eq!(receive_only.name, "Task");
+
+        let send_only = GoType::from_type_text("chan<- Result");
+        assert!(send_only.is_channel);
+        assert_eq!(send_only.channel_direction, Some(ChannelDir::SendOnly));
+        assert_eq!(send_only.name, "Result");
+
+        let pointer_element = GoType::from_type_text("chan *Result");
+        assert!(pointer_element.is_channel);
+        assert!(pointer_element.is_pointer);
+        assert_eq!(pointer_element.name, "Result");
+
+        // 名称中包含 chan 的普通类型不是通道
+        let plain = GoType::from_type_text("*Channel");
+        assert!(!plain.is_channel);
+        assert_eq!(plain.channel_direction, None);
+        assert!(plain.is_pointer);
+        assert_eq!(plain.name, "Channel");
+    }
+
+    /// 测试映射类型的键值解析
+    #[test]
+    fn test_go_type_from_map_text() {
+        let pointer_value = GoType::from_type_text("map[int]*models.User");
+        assert_eq!(pointer_value.name, "map[int]*models.User");
+        let key = pointer_value.key_type.as_deref().unwrap();
+        let value = pointer_value.value_type.as_deref().unwrap();
+        assert_eq!(key.name, "int");
+        assert!(value.is_pointer);
+        assert_eq!(value.name, "models.User");
+
+        let nested = GoType::from_type_text("map[string]map[int]User");
+        let inner = nested.value_type.as_deref().unwrap();
+        assert_eq!(inner.key_type.as_deref().unwrap().name, "int");
+        assert_eq!(inner.value_type.as_deref().unwrap().name, "User");
+        assert_eq!(
+            nested
+                .map_component_types()
+                .iter()
+                .map(|t| t.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["string", "int", "User"]
+        );
+
+        // 映射的切片不是映射
+        let slice = GoType::from_type_text("[]map[string]int");
+        assert!(slice.key_type.is_none());
+        assert!(slice.is_slice);
+    }
+
+    /// 测试固定长度数组与切片的区分
+    #[test]
+    fn test_go_type_from_array_text() {
+        let literal = GoType::from_type_text("[3]int");
+        assert_eq!(literal.array_len.as_deref(), Some("3"));
+        assert!(!literal.is_slice);
+        assert_eq!(literal.name, "int");
+
+        let constant = GoType::from_type_text("[MaxSize]User");
+        assert_eq!(constant.array_len.as_deref(), Some("MaxSize"));
+        assert_eq!(constant.name, "User");
+
+        let matrix = GoType::from_type_text("[2][2]float64");
+        assert_eq!(matrix.array_len.as_deref(), Some("2"));
+        assert_eq!(matrix.name, "[2]float64");
+        assert_eq!(matrix.array_lengths(), vec!["2", "2"]);
+
+        let slice = GoType::from_type_text("[]byte");
+        assert!(slice.is_slice);
+        assert!(slice.array_len.is_none());
+    }
+
+    /// 测试从参数的类型节点构造结构化类型
+    #[test]
+    fn test_go_type_from_type_node() {
+        let source = "package main\n\nfunc Handle(a *models.User, b []*Item, c *[]Item, d <-chan *Task, e [N]Point, f List[int], g (Config)) {}\n";
+        let mut parser = GoParser::new().unwrap();
+        let tree = parser.parse_source(source).unwrap();
+        let func_node = tree.root_node().named_child(1).unwrap();
+        let types: Vec<GoType> = crate::parser::common::CstNavigator::new()
+            .get_function_parameters(func_node, source)
+            .into_iter()
+            .map(|param| param.go_type)
+            .collect();
+
+        let qualified = &types[0];
+        assert!(qualified.is_pointer && !qualified.is_slice);
+        assert_eq!(qualified.name, "models.User");
+
+        // 切片元素中的指针无法用外层标志表达，保留在名称中
+        let pointer_slice = &types[1];
+        assert!(pointer_slice.is_slice && !pointer_slice.is_pointer);
+        assert_eq!(pointer_slice.name, "*Item");
+
+        let slice_pointer = &types[2];
+        assert!(slice_pointer.is_pointer && slice_pointer.is_slice);
+        assert_eq!(slice_pointer.name, "Item");
+
+        let channel = &types[3];
+        assert_eq!(channel.channel_direction, Some(ChannelDir::ReceiveOnly));
+        assert!(channel.is_pointer);
+        assert_eq!(channel.name, "Task");
+
+        assert_eq!(types[4].array_len.as_deref(), Some("N"));
+        assert_eq!(types[4].name, "Point");
+        assert_eq!(types[5].name, "List[int]");
+        assert_eq!(types[6].name, "Config");
+        assert_eq!(GoType::from_type_text("*models.User"), types[0]);
+    }
 }
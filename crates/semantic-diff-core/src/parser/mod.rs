@@ -7,10 +7,11 @@ pub mod go;
 
 // 重新导出核心类型
 pub use common::{
-    Declaration, Import, LanguageParser, LanguageSpecificInfo, ParserFactory, SourceFile,
-    SupportedLanguage,
+    Declaration, GenericLanguageInfo, Import, ImportKind, LanguageParser, LanguageSpecificInfo,
+    ParserConstructor, ParserFactory, SourceFile, SupportedLanguage, input_edits,
 };
 pub use go::{
-    GoConstantDefinition, GoDeclaration, GoFunctionInfo, GoLanguageInfo, GoParameter, GoParser,
-    GoReceiverInfo, GoType, GoTypeDefinition, GoTypeKind, GoVariableDefinition,
+    BodyReferences, CallSite, ChannelDir, GoConstantDefinition, GoDeclaration, GoFunctionInfo,
+    GoLanguageInfo, GoParameter, GoParser, GoReceiverInfo, GoStructField, GoType, GoTypeDefinition,
+    GoTypeKind, GoTypeParam, GoVariableDefinition, ReferenceKind, UNKNOWN_PACKAGE_NAME,
 };
@@ -2,12 +2,22 @@
 //!
 //! 提供内存高效的 AST 处理、解析器缓存、并发处理等性能优化功能
 
+use crate::diagnostics::Diagnostics;
 use crate::error::{Result, SemanticDiffError};
-use crate::parser::{LanguageParser, ParserFactory, SourceFile, SupportedLanguage};
+use crate::git::DiffHunk;
+use crate::parser::{
+    BodyReferences, GoDeclaration, GoLanguageInfo, Import, LanguageParser, ParserFactory,
+    SourceFile, SupportedLanguage,
+};
+use clru::CLruCache;
 use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 use std::time::{Duration, Instant};
 use tracing::{debug, info, warn};
 
@@ -17,17 +27,149 @@ type CachedParser = Arc<Mutex<Box<dyn LanguageParser>>>;
 /// 类型别名：解析器缓存映射类型
 type ParserCacheMap = HashMap<SupportedLanguage, CachedParser>;
 
+/// 类型别名：按最近使用顺序排列的解析结果缓存，队首为最近使用的条目
+type ParseResultMap = CLruCache<PathBuf, ParseEntry>;
+
+/// 估算语法树内存占用时每个源码字节对应的字节数（经验值）
+const ESTIMATED_TREE_BYTES_PER_SOURCE_BYTE: usize = 8;
+
+/// 进度回调，可能在工作线程中被并发调用
+pub type ProgressCallback = Arc<dyn Fn(ProgressEvent) + Send + Sync>;
+
+/// 长时间运行的任务报告的进度事件
+///
+/// `completed` 是已完成的数量（包括失败的），并发执行时事件到达的顺序不一定与
+/// `completed` 的大小顺序一致，但每个值只会出现一次
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// 一个文件处理完成
+    FileParsed { completed: usize, total: usize },
+    /// 一个变更目标的语义上下文提取完成
+    ContextExtracted { completed: usize, total: usize },
+}
+
+/// 缓存中的一个解析结果
+#[derive(Debug)]
+struct ParseEntry {
+    parse: CachedParse,
+    /// 本次运行中解析出的语法树，不会持久化；从磁盘加载的条目在第一次命中时补上
+    tree: Option<tree_sitter::Tree>,
+    /// 是否从磁盘加载
+    from_disk: bool,
+    /// 最近一次使用的序号，越大越新；命中时只在读锁下更新
+    last_used: AtomicU64,
+    /// 条目移到队首时的使用序号，`last_used` 比它大说明之后被命中过
+    promoted_at: u64,
+}
+
+impl ParseEntry {
+    fn new(
+        parse: CachedParse,
+        tree: Option<tree_sitter::Tree>,
+        from_disk: bool,
+        tick: u64,
+    ) -> Self {
+        Self {
+            parse,
+            tree,
+            from_disk,
+            last_used: AtomicU64::new(tick),
+            promoted_at: tick,
+        }
+    }
+
+    fn last_used(&self) -> u64 {
+        self.last_used.load(Ordering::Relaxed)
+    }
+}
+
+/// 持久化解析结果的文件名
+#[cfg(feature = "serde")]
+const PARSE_CACHE_FILE: &str = "parse-cache.json";
+
 /// 解析器缓存
 ///
-/// 缓存已创建的解析器实例，避免重复创建的开销
+/// 缓存已创建的解析器实例，避免重复创建的开销；
+/// 同时按文件路径缓存解析结果，可以持久化到磁盘供后续运行复用
 pub struct ParserCache {
     /// 缓存的解析器实例
     /// 注意：由于 tree-sitter 解析器不是 Clone，我们使用工厂函数
     cache: Arc<RwLock<ParserCacheMap>>,
+    /// 按文件路径缓存的解析结果
+    parse_results: Arc<RwLock<ParseResultMap>>,
+    /// 持久化目录，未设置时解析结果只保存在内存中
+    #[cfg(feature = "serde")]
+    cache_dir: Option<PathBuf>,
+    /// 递增的使用序号
+    use_counter: Arc<AtomicU64>,
     /// 缓存统计信息
     stats: Arc<Mutex<CacheStats>>,
 }
 
+/// 缓存的文件解析结果
+///
+/// 保存从语法树中提取的包名、导入和声明，内容哈希不一致时视为失效
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CachedParse {
+    /// 文件内容的哈希（与 Git blob 对象 ID 相同）
+    pub content_hash: String,
+    /// 包名
+    pub package_name: String,
+    /// 导入声明
+    pub imports: Vec<Import>,
+    /// 顶层声明
+    pub declarations: Vec<GoDeclaration>,
+    /// 按声明顺序保存的函数和方法的函数体引用（函数信息序列化时不包含这些引用）
+    pub body_references: Vec<BodyReferences>,
+}
+
+impl CachedParse {
+    /// 从 Go 语言特定信息构建缓存条目
+    pub fn from_language_info(content_hash: String, info: &GoLanguageInfo) -> Self {
+        let declarations: Vec<GoDeclaration> = info
+            .declarations
+            .iter()
+            .filter_map(|decl| decl.as_any().downcast_ref::<GoDeclaration>().cloned())
+            .collect();
+        let body_references = declarations
+            .iter()
+            .filter_map(|decl| match decl {
+                GoDeclaration::Function(func) | GoDeclaration::Method(func) => {
+                    Some(BodyReferences::clone(&func.body_references))
+                }
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            content_hash,
+            package_name: info.package_name.clone(),
+            imports: info.imports.clone(),
+            declarations,
+            body_references,
+        }
+    }
+
+    /// 转换为 Go 语言特定信息
+    pub fn to_language_info(&self) -> GoLanguageInfo {
+        let mut info = GoLanguageInfo::new(self.package_name.clone());
+        for import in &self.imports {
+            info.add_import(import.clone());
+        }
+        let mut body_references = self.body_references.iter();
+        for declaration in &self.declarations {
+            let mut declaration = declaration.clone();
+            if let GoDeclaration::Function(func) | GoDeclaration::Method(func) = &mut declaration {
+                func.body_references =
+                    Arc::new(body_references.next().cloned().unwrap_or_default());
+            }
+            info.add_go_declaration(declaration);
+        }
+        info
+    }
+}
+
 /// 缓存统计信息
 #[derive(Debug, Clone, Default)]
 pub struct CacheStats {
@@ -37,6 +179,16 @@ pub struct CacheStats {
     pub misses: u64,
     /// 缓存创建次数
     pub creates: u64,
+    /// 由本次运行中产生的解析结果命中的次数
+    pub memory_hits: u64,
+    /// 由磁盘加载的解析结果命中的次数
+    pub disk_hits: u64,
+    /// 需要重新解析文件的次数
+    pub parse_misses: u64,
+    /// 因内容哈希不一致而失效的解析结果数量
+    pub invalidations: u64,
+    /// 因超出容量而被淘汰的解析结果数量
+    pub evictions: u64,
 }
 
 impl CacheStats {
@@ -61,20 +213,46 @@ pub struct MemoryEfficientAstProcessor {
     memory_threshold: usize,
     /// 是否启用内存监控
     memory_monitoring: bool,
+    /// 每个文件上一次解析的源码和语法树，用于增量解析
+    previous_trees: Mutex<HashMap<PathBuf, (String, tree_sitter::Tree)>>,
+    /// 内存预算（字节），超过时释放缓存的语法树并缩小批大小
+    memory_budget: Option<usize>,
+    /// 当前估算的内存占用（字节）
+    tracked_memory: AtomicUsize,
+    /// 估算内存占用的峰值（字节）
+    peak_tracked_memory: AtomicUsize,
+    /// 按内存预算调整后的批大小，0 表示尚未调整
+    batch_size: AtomicUsize,
 }
 
 /// 并发文件处理器
 ///
 /// 使用 rayon 实现高效的并发文件处理
 pub struct ConcurrentFileProcessor {
-    /// 线程池大小
-    thread_pool_size: usize,
+    /// 处理文件使用的线程池
+    thread_pool: ScopedThreadPool,
     /// 批处理大小
     batch_size: usize,
     /// 解析器缓存
     parser_cache: Arc<ParserCache>,
     /// AST 处理器
     ast_processor: MemoryEfficientAstProcessor,
+    /// 单个文件的解析超时时间
+    parse_timeout: Option<Duration>,
+    /// 进度回调
+    progress: Option<ProgressCallback>,
+}
+
+/// 按需创建的局部 rayon 线程池
+///
+/// 并行任务在这个线程池中执行，不占用全局线程池；线程数为 1 时不创建线程池，
+/// 任务在调用线程上按顺序执行，便于调试时得到确定的执行顺序
+#[derive(Debug)]
+pub struct ScopedThreadPool {
+    /// 线程数
+    threads: usize,
+    /// 首次使用时创建的线程池
+    pool: OnceLock<rayon::ThreadPool>,
 }
 
 /// 性能监控器
@@ -89,6 +267,10 @@ pub struct PerformanceMonitor {
     total_processing_time: Arc<Mutex<Duration>>,
     /// 错误计数
     error_count: Arc<Mutex<u64>>,
+    /// 需要处理的文件总数
+    total_files: usize,
+    /// 进度回调
+    progress: Option<ProgressCallback>,
 }
 
 /// 解析结果
@@ -117,6 +299,10 @@ pub struct PerformanceStats {
     pub avg_file_processing_time: Duration,
     /// 内存使用峰值（如果启用监控）
     pub peak_memory_usage: Option<usize>,
+    /// 处理结束时估算的内存占用（字节）
+    pub estimated_memory_usage: usize,
+    /// 处理过程中估算内存占用的峰值（字节）
+    pub peak_estimated_memory_usage: usize,
     /// 缓存统计
     pub cache_stats: CacheStats,
 }
@@ -132,10 +318,288 @@ impl ParserCache {
     pub fn new() -> Self {
         Self {
             cache: Arc::new(RwLock::new(HashMap::new())),
+            parse_results: Arc::new(RwLock::new(CLruCache::new(NonZeroUsize::MAX))),
+            #[cfg(feature = "serde")]
+            cache_dir: None,
+            use_counter: Arc::new(AtomicU64::new(0)),
             stats: Arc::new(Mutex::new(CacheStats::default())),
         }
     }
 
+    /// 限制缓存的解析结果数量，超出时按最近最少使用（LRU）淘汰
+    ///
+    /// 容量至少为 1；已缓存的条目超出新容量时立即淘汰
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        {
+            let mut parse_results = self.parse_results.write().unwrap();
+            self.evict_until(&mut parse_results, capacity.get());
+            parse_results.resize(capacity);
+        }
+        self
+    }
+
+    /// 从缓存目录加载持久化的解析结果
+    ///
+    /// 目录或缓存文件不存在时返回空缓存；缓存文件损坏时丢弃其内容并重新开始。
+    /// 之后调用 [`Self::persist_to_disk`] 会写回同一目录
+    #[cfg(feature = "serde")]
+    pub fn load_from_disk<P: AsRef<Path>>(cache_dir: P) -> Result<Self> {
+        let cache_dir = cache_dir.as_ref().to_path_buf();
+        let cache_file = cache_dir.join(PARSE_CACHE_FILE);
+
+        // 缓存文件按最近使用顺序保存，最近使用的条目在前
+        let entries: Vec<(PathBuf, CachedParse)> = match std::fs::read_to_string(&cache_file) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("解析缓存文件损坏，已忽略: {:?}, 错误: {}", cache_file, e);
+                Vec::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(SemanticDiffError::IoError(e)),
+        };
+        debug!("从 {:?} 加载了 {} 个解析结果", cache_file, entries.len());
+
+        let cache = Self {
+            cache_dir: Some(cache_dir),
+            ..Self::new()
+        };
+        {
+            // 从最久未使用的条目开始放入，恢复上次运行结束时的使用顺序
+            let mut parse_results = cache.parse_results.write().unwrap();
+            for (path, parse) in entries.into_iter().rev() {
+                parse_results.put(path, ParseEntry::new(parse, None, true, cache.next_use()));
+            }
+        }
+        Ok(cache)
+    }
+
+    /// 将解析结果写入缓存目录，目录不存在时自动创建
+    ///
+    /// 写入前丢弃文件已删除或内容已变化的条目；先写入临时文件再重命名，
+    /// 中断的写入不会留下不完整的缓存文件。
+    /// 没有通过 [`Self::load_from_disk`] 指定缓存目录时返回配置错误
+    #[cfg(feature = "serde")]
+    pub fn persist_to_disk(&self) -> Result<()> {
+        let cache_dir = self.cache_dir.as_ref().ok_or_else(|| {
+            SemanticDiffError::ConfigError("Parser cache has no cache directory".to_string())
+        })?;
+
+        let pruned = self.prune_unreferenced();
+        if pruned > 0 {
+            debug!("丢弃了 {} 个不再被引用的解析结果", pruned);
+        }
+
+        let parse_results = self.parse_results.read().unwrap();
+        let mut entries: Vec<(&PathBuf, &ParseEntry)> = parse_results.iter().collect();
+        entries.sort_by_key(|(_, entry)| std::cmp::Reverse(entry.last_used()));
+        let entries: Vec<(&PathBuf, &CachedParse)> = entries
+            .into_iter()
+            .map(|(path, entry)| (path, &entry.parse))
+            .collect();
+        let content = serde_json::to_string(&entries).map_err(|e| {
+            SemanticDiffError::SerializationError(format!("Failed to serialize parse cache: {e}"))
+        })?;
+
+        std::fs::create_dir_all(cache_dir)?;
+        let temp_file = cache_dir.join(format!("{PARSE_CACHE_FILE}.{}.tmp", std::process::id()));
+        std::fs::write(&temp_file, content)?;
+        if let Err(e) = std::fs::rename(&temp_file, cache_dir.join(PARSE_CACHE_FILE)) {
+            let _ = std::fs::remove_file(&temp_file);
+            return Err(SemanticDiffError::IoError(e));
+        }
+        debug!("已持久化 {} 个解析结果到 {:?}", entries.len(), cache_dir);
+
+        Ok(())
+    }
+
+    /// 获取文件的解析结果，内容未变化时直接复用缓存
+    ///
+    /// 缓存条目的内容哈希与 `source_code` 不一致时条目失效并重新解析。
+    /// 返回的是解析结果的副本，之后的淘汰不会影响正在使用它的调用方
+    pub fn get_or_parse(&self, file_path: &Path, source_code: &str) -> Result<CachedParse> {
+        let language = ParserFactory::detect_language(file_path).ok_or_else(|| {
+            SemanticDiffError::UnsupportedFileType(file_path.to_string_lossy().to_string())
+        })?;
+
+        let (parse, _) = self.lookup_or_parse(
+            file_path,
+            source_code,
+            false,
+            || {
+                let parser = self.get_or_create_parser(language)?;
+                let mut parser = parser.lock().unwrap();
+                parser.parse_source(source_code)
+            },
+            |syntax_tree, content_hash| {
+                let info = crate::analyzer::SourceAnalyzer::new_for_language(language)?
+                    .extract_go_specific_info(syntax_tree, source_code, file_path)?;
+                Ok(CachedParse::from_language_info(content_hash, &info))
+            },
+        )?;
+        Ok(parse)
+    }
+
+    /// 获取文件的解析结果和语法树，内容未变化时都直接复用缓存
+    ///
+    /// 未命中时先由 `parse_tree` 解析语法树，再由 `extract` 根据语法树和内容哈希生成解析结果；
+    /// 从磁盘加载的条目没有语法树，第一次命中时只调用 `parse_tree`，之后的命中不再解析
+    pub fn get_or_parse_with<T, F>(
+        &self,
+        file_path: &Path,
+        source_code: &str,
+        parse_tree: T,
+        extract: F,
+    ) -> Result<(CachedParse, tree_sitter::Tree)>
+    where
+        T: FnOnce() -> Result<tree_sitter::Tree>,
+        F: FnOnce(&tree_sitter::Tree, String) -> Result<CachedParse>,
+    {
+        let (parse, tree) =
+            self.lookup_or_parse(file_path, source_code, true, parse_tree, extract)?;
+        Ok((parse, tree.expect("需要语法树时总会解析或复用语法树")))
+    }
+
+    /// 查找或生成解析结果，`need_tree` 为 false 时磁盘命中不补解析语法树
+    fn lookup_or_parse<T, F>(
+        &self,
+        file_path: &Path,
+        source_code: &str,
+        need_tree: bool,
+        parse_tree: T,
+        extract: F,
+    ) -> Result<(CachedParse, Option<tree_sitter::Tree>)>
+    where
+        T: FnOnce() -> Result<tree_sitter::Tree>,
+        F: FnOnce(&tree_sitter::Tree, String) -> Result<CachedParse>,
+    {
+        let content_hash = Self::content_hash(source_code.as_bytes())?;
+
+        let cached = {
+            // 命中时只记录使用序号，条目在淘汰时才移动位置，读取方之间不会互相阻塞
+            let parse_results = self.parse_results.read().unwrap();
+            match parse_results.peek(file_path) {
+                Some(entry) if entry.parse.content_hash == content_hash => {
+                    let mut stats = self.stats.lock().unwrap();
+                    if entry.from_disk {
+                        stats.disk_hits += 1;
+                    } else {
+                        stats.memory_hits += 1;
+                    }
+                    entry.last_used.store(self.next_use(), Ordering::Relaxed);
+                    debug!("Parse cache hit for file: {:?}", file_path);
+                    match &entry.tree {
+                        Some(tree) => return Ok((entry.parse.clone(), Some(tree.clone()))),
+                        None if !need_tree => return Ok((entry.parse.clone(), None)),
+                        None => Some(entry.parse.clone()),
+                    }
+                }
+                Some(_) => {
+                    self.stats.lock().unwrap().invalidations += 1;
+                    debug!("Parse cache entry invalidated for file: {:?}", file_path);
+                    None
+                }
+                None => None,
+            }
+        };
+
+        if let Some(parse) = cached {
+            // 从磁盘加载的条目只缺语法树，解析后补到条目上
+            let tree = parse_tree()?;
+            let mut parse_results = self.parse_results.write().unwrap();
+            if let Some(entry) = parse_results
+                .peek_mut(file_path)
+                .filter(|entry| entry.parse.content_hash == parse.content_hash)
+            {
+                entry.tree = Some(tree.clone());
+            }
+            return Ok((parse, Some(tree)));
+        }
+
+        self.stats.lock().unwrap().parse_misses += 1;
+        let tree = parse_tree()?;
+        let parse = extract(&tree, content_hash)?;
+
+        let mut parse_results = self.parse_results.write().unwrap();
+        // 先为新条目腾出位置，刚写入的结果不会被淘汰
+        if !parse_results.contains(file_path) {
+            let limit = parse_results.capacity() - 1;
+            self.evict_until(&mut parse_results, limit);
+        }
+        parse_results.put(
+            file_path.to_path_buf(),
+            ParseEntry::new(parse.clone(), Some(tree.clone()), false, self.next_use()),
+        );
+
+        Ok((parse, Some(tree)))
+    }
+
+    /// 丢弃文件已不存在或内容已变化的解析结果，返回丢弃的数量
+    ///
+    /// 这些条目的 blob 已不再被工作区引用，之后的运行不可能再命中
+    #[cfg(feature = "serde")]
+    fn prune_unreferenced(&self) -> usize {
+        let mut parse_results = self.parse_results.write().unwrap();
+        let stale: Vec<PathBuf> = parse_results
+            .iter()
+            .filter(|(path, entry)| {
+                std::fs::read(path)
+                    .ok()
+                    .and_then(|content| Self::content_hash(&content).ok())
+                    .is_none_or(|hash| hash != entry.parse.content_hash)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &stale {
+            parse_results.pop(path);
+        }
+        stale.len()
+    }
+
+    /// 是否缓存了文件的解析结果（不影响 LRU 顺序）
+    pub fn contains_parse(&self, file_path: &Path) -> bool {
+        self.parse_results.read().unwrap().contains(file_path)
+    }
+
+    /// 下一个使用序号
+    fn next_use(&self) -> u64 {
+        self.use_counter.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// 从队尾淘汰条目直到数量不超过 `limit`
+    ///
+    /// 队尾条目在移到队首之后被命中过时先移回队首，再继续检查新的队尾
+    fn evict_until(&self, parse_results: &mut ParseResultMap, limit: usize) {
+        while parse_results.len() > limit {
+            let Some((path, entry)) = parse_results.back_mut() else {
+                break;
+            };
+            let last_used = entry.last_used();
+            if last_used > entry.promoted_at {
+                entry.promoted_at = last_used;
+                let path = path.clone();
+                parse_results.get(&path);
+                continue;
+            }
+
+            if let Some((path, _)) = parse_results.pop_back() {
+                self.stats.lock().unwrap().evictions += 1;
+                debug!("Parse cache evicted file: {:?}", path);
+            }
+        }
+    }
+
+    /// 计算文件内容的哈希，与 Git 为相同内容计算的 blob ID 一致
+    fn content_hash(content: &[u8]) -> Result<String> {
+        gix::objs::compute_hash(gix::hash::Kind::Sha1, gix::objs::Kind::Blob, content)
+            .map(|id| id.to_string())
+            .map_err(|e| SemanticDiffError::GitError(format!("Failed to hash file content: {e}")))
+    }
+
+    /// 获取缓存的解析结果数量
+    pub fn parse_result_count(&self) -> usize {
+        self.parse_results.read().unwrap().len()
+    }
+
     /// 获取或创建解析器
     pub fn get_or_create_parser(&self, language: SupportedLanguage) -> Result<CachedParser> {
         // 首先尝试从缓存中获取
@@ -181,6 +645,7 @@ impl ParserCache {
     pub fn clear(&self) {
         let mut cache = self.cache.write().unwrap();
         cache.clear();
+        self.parse_results.write().unwrap().clear();
         debug!("Parser cache cleared");
     }
 
@@ -204,6 +669,11 @@ impl MemoryEfficientAstProcessor {
             max_concurrent_files: num_cpus::get() * 2,
             memory_threshold: 512 * 1024 * 1024, // 512MB
             memory_monitoring: true,
+            previous_trees: Mutex::new(HashMap::new()),
+            memory_budget: None,
+            tracked_memory: AtomicUsize::new(0),
+            peak_tracked_memory: AtomicUsize::new(0),
+            batch_size: AtomicUsize::new(0),
         }
     }
 
@@ -225,6 +695,148 @@ impl MemoryEfficientAstProcessor {
         self
     }
 
+    /// 设置内存预算（字节）
+    ///
+    /// 与 [`Self::with_memory_threshold`] 检查的进程内存不同，预算约束的是处理器估算的内存：
+    /// 缓存的语法树和通过 [`Self::track_memory`] 记录的工作数据。超过预算时释放缓存的语法树，
+    /// 分批处理时按 [`Self::next_batch_size`] 缩小批大小
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self
+    }
+
+    /// 获取内存预算
+    pub fn memory_budget(&self) -> Option<usize> {
+        self.memory_budget
+    }
+
+    /// 记录新占用的内存
+    pub fn track_memory(&self, bytes: usize) {
+        let current = self.tracked_memory.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.peak_tracked_memory
+            .fetch_max(current, Ordering::Relaxed);
+    }
+
+    /// 记录释放的内存
+    pub fn release_memory(&self, bytes: usize) {
+        let _ = self
+            .tracked_memory
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
+                Some(current.saturating_sub(bytes))
+            });
+    }
+
+    /// 当前估算的内存占用
+    pub fn tracked_memory(&self) -> usize {
+        self.tracked_memory.load(Ordering::Relaxed)
+    }
+
+    /// 估算内存占用的峰值
+    pub fn peak_tracked_memory(&self) -> usize {
+        self.peak_tracked_memory.load(Ordering::Relaxed)
+    }
+
+    /// 估算的内存占用是否超过预算
+    pub fn is_over_budget(&self) -> bool {
+        self.memory_budget
+            .is_some_and(|budget| self.tracked_memory() > budget)
+    }
+
+    /// 释放所有缓存的语法树，返回释放的文件数量
+    pub fn drop_cached_trees(&self) -> usize {
+        let dropped: Vec<_> = self.previous_trees.lock().unwrap().drain().collect();
+        for (source, _) in dropped.iter().map(|(_, entry)| entry) {
+            self.release_memory(estimated_tree_bytes(source));
+        }
+        if !dropped.is_empty() {
+            debug!("释放 {} 个缓存的语法树", dropped.len());
+        }
+        dropped.len()
+    }
+
+    /// 根据内存预算计算下一批的大小
+    ///
+    /// `bytes_per_item` 是处理一个元素预计占用的内存。批大小只会缩小，不会超过 `requested`，
+    /// 并且至少为 1；未设置预算时返回 `requested`
+    pub fn next_batch_size(&self, requested: usize, bytes_per_item: usize) -> usize {
+        let Some(budget) = self.memory_budget else {
+            return requested;
+        };
+
+        let available = budget.saturating_sub(self.tracked_memory());
+        let fits = available / bytes_per_item.max(1);
+        let current = match self.batch_size.load(Ordering::Relaxed) {
+            0 => requested,
+            size => size.min(requested),
+        };
+        let size = current.min(fits).max(1);
+        if size < current {
+            debug!("内存预算不足，批大小从 {} 缩小到 {}", current, size);
+        }
+        self.batch_size.store(size, Ordering::Relaxed);
+        size
+    }
+
+    /// 按内存预算调整后的批大小（尚未调整时为 None）
+    pub fn current_batch_size(&self) -> Option<usize> {
+        match self.batch_size.load(Ordering::Relaxed) {
+            0 => None,
+            size => Some(size),
+        }
+    }
+
+    /// 解析文件源码，缓存中有该文件上一版本的语法树时增量解析
+    ///
+    /// 新旧版本之间的差异取首尾相同行之间的部分，作为一次整行替换传给
+    /// [`LanguageParser::parse_incremental`]；解析结果会替换缓存中的旧版本
+    pub fn parse_with_previous_tree(
+        &self,
+        parser: &mut dyn LanguageParser,
+        file_path: &Path,
+        source_code: &str,
+    ) -> Result<tree_sitter::Tree> {
+        let previous = self.previous_trees.lock().unwrap().remove(file_path);
+        if let Some((old_src, _)) = &previous {
+            self.release_memory(estimated_tree_bytes(old_src));
+        }
+        let tree = match previous {
+            Some((old_src, old_tree)) if old_src == source_code => old_tree,
+            Some((old_src, old_tree)) => {
+                let hunk = changed_line_range(&old_src, source_code);
+                debug!(
+                    "增量解析文件: {:?}, 旧版本第 {} 行起 {} 行被替换为 {} 行",
+                    file_path, hunk.old_start, hunk.old_lines, hunk.new_lines
+                );
+                parser.parse_incremental(&old_tree, &old_src, source_code, &[hunk])?
+            }
+            None => parser.parse_source(source_code)?,
+        };
+
+        // 超过内存预算时先释放其他文件的语法树，仍然放不下时不缓存
+        let tree_bytes = estimated_tree_bytes(source_code);
+        let fits = |processor: &Self| {
+            processor
+                .memory_budget
+                .is_none_or(|budget| processor.tracked_memory() + tree_bytes <= budget)
+        };
+        if !fits(self) {
+            self.drop_cached_trees();
+        }
+        if fits(self) {
+            self.previous_trees.lock().unwrap().insert(
+                file_path.to_path_buf(),
+                (source_code.to_string(), tree.clone()),
+            );
+            self.track_memory(tree_bytes);
+        }
+        Ok(tree)
+    }
+
+    /// 缓存了上一版本语法树的文件数量
+    pub fn cached_tree_count(&self) -> usize {
+        self.previous_trees.lock().unwrap().len()
+    }
+
     /// 检查内存使用情况
     pub fn check_memory_usage(&self) -> Option<usize> {
         if !self.memory_monitoring {
@@ -276,27 +888,109 @@ impl MemoryEfficientAstProcessor {
     pub fn trigger_gc(&self) {
         if self.should_trigger_gc() {
             debug!("Triggering garbage collection due to high memory usage");
+            // 缓存的旧版本语法树可以随时重建
+            self.drop_cached_trees();
             // 在 Rust 中，我们不能直接触发 GC，但可以释放一些缓存
             // 这里可以清理一些缓存或者建议系统进行内存整理
         }
     }
 }
 
+impl ScopedThreadPool {
+    /// 创建指定线程数的线程池，线程数至少为 1
+    pub fn new(threads: usize) -> Self {
+        Self {
+            threads: threads.max(1),
+            pool: OnceLock::new(),
+        }
+    }
+
+    /// 线程数
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// 是否在调用线程上顺序执行
+    pub fn is_sequential(&self) -> bool {
+        self.threads == 1
+    }
+
+    /// 在线程池中执行 `op`，其中的 rayon 并行迭代器使用这个线程池
+    ///
+    /// 顺序执行时直接在调用线程上运行，此时 `op` 中的并行迭代器仍会使用全局线程池，
+    /// 需要顺序执行的调用方应先检查 [`ScopedThreadPool::is_sequential`]
+    pub fn install<R, F>(&self, op: F) -> Result<R>
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        if self.is_sequential() {
+            return Ok(op());
+        }
+
+        if let Some(pool) = self.pool.get() {
+            return Ok(pool.install(op));
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads)
+            .build()
+            .map_err(|e| {
+                SemanticDiffError::ParseError(format!("Failed to create thread pool: {e}"))
+            })?;
+        Ok(self.pool.get_or_init(|| pool).install(op))
+    }
+
+    /// 对每个元素执行 `f`，结果按输入顺序返回
+    pub fn map<T, R, F>(&self, items: &[T], f: F) -> Result<Vec<R>>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync + Send,
+    {
+        if self.is_sequential() {
+            return Ok(items.iter().map(f).collect());
+        }
+
+        self.install(|| items.par_iter().map(f).collect())
+    }
+}
+
+impl Clone for ScopedThreadPool {
+    fn clone(&self) -> Self {
+        Self::new(self.threads)
+    }
+}
+
+impl Default for ScopedThreadPool {
+    fn default() -> Self {
+        Self::new(num_cpus::get())
+    }
+}
+
 impl ConcurrentFileProcessor {
     /// 创建新的并发文件处理器
     pub fn new() -> Self {
-        let thread_pool_size = num_cpus::get();
         Self {
-            thread_pool_size,
+            thread_pool: ScopedThreadPool::default(),
             batch_size: 10,
             parser_cache: Arc::new(ParserCache::new()),
             ast_processor: MemoryEfficientAstProcessor::new(),
+            parse_timeout: None,
+            progress: None,
         }
     }
 
     /// 设置线程池大小
-    pub fn with_thread_pool_size(mut self, size: usize) -> Self {
-        self.thread_pool_size = size;
+    pub fn with_thread_pool_size(self, size: usize) -> Self {
+        self.with_threads(size)
+    }
+
+    /// 设置处理文件使用的线程数
+    ///
+    /// 文件在独立的线程池中处理，不占用全局 rayon 线程池；设置为 1 时在调用线程上顺序处理
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.thread_pool = ScopedThreadPool::new(threads);
         self
     }
 
@@ -318,31 +1012,48 @@ impl ConcurrentFileProcessor {
         self
     }
 
+    /// 设置单个文件的解析超时时间
+    ///
+    /// 超时的文件记录在 [`ParseResult::failed`] 中，其余文件继续处理
+    pub fn with_parse_timeout(mut self, timeout: Duration) -> Self {
+        self.parse_timeout = Some(timeout);
+        self
+    }
+
+    /// 设置进度回调，每处理完一个文件报告一次 [`ProgressEvent::FileParsed`]
+    ///
+    /// 回调在处理文件的工作线程中调用
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(ProgressEvent) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(callback));
+        self
+    }
+
     /// 并发处理多个文件
     pub fn process_files_concurrent(&self, file_paths: &[PathBuf]) -> Result<ParseResult> {
-        let monitor = PerformanceMonitor::new();
+        let mut monitor = PerformanceMonitor::new().with_total_files(file_paths.len());
+        if let Some(progress) = &self.progress {
+            monitor = monitor.with_progress_callback(progress.clone());
+        }
 
         info!("开始并发处理 {} 个文件", file_paths.len());
 
-        // 配置 rayon 线程池
-        let pool = rayon::ThreadPoolBuilder::new()
-            .num_threads(self.thread_pool_size)
-            .build()
-            .map_err(|e| {
-                SemanticDiffError::ParseError(format!("Failed to create thread pool: {e}"))
-            })?;
+        let process =
+            |file_path: &PathBuf| self.process_single_file_with_recovery(file_path, &monitor);
 
-        // 使用线程池处理文件
-        let results: Vec<_> = pool.install(|| {
-            file_paths
-                .par_chunks(self.batch_size)
-                .flat_map(|chunk| {
-                    chunk.par_iter().map(|file_path| {
-                        self.process_single_file_with_recovery(file_path, &monitor)
-                    })
-                })
-                .collect()
-        });
+        // 使用线程池处理文件，单线程时顺序处理
+        let results: Vec<_> = if self.thread_pool.is_sequential() {
+            file_paths.iter().map(process).collect()
+        } else {
+            self.thread_pool.install(|| {
+                file_paths
+                    .par_chunks(self.batch_size)
+                    .flat_map(|chunk| chunk.par_iter().map(process))
+                    .collect()
+            })?
+        };
 
         // 分离成功和失败的结果
         let mut successful = Vec::new();
@@ -355,7 +1066,9 @@ impl ConcurrentFileProcessor {
             }
         }
 
-        let performance_stats = monitor.get_stats(self.parser_cache.get_stats());
+        let mut performance_stats = monitor.get_stats(self.parser_cache.get_stats());
+        performance_stats.estimated_memory_usage = self.ast_processor.tracked_memory();
+        performance_stats.peak_estimated_memory_usage = self.ast_processor.peak_tracked_memory();
 
         info!(
             "文件处理完成: 成功 {}, 失败 {}, 总耗时 {:?}",
@@ -383,6 +1096,9 @@ impl ConcurrentFileProcessor {
         if self.ast_processor.should_trigger_gc() {
             self.ast_processor.trigger_gc();
         }
+        if self.ast_processor.is_over_budget() {
+            self.ast_processor.drop_cached_trees();
+        }
 
         let result = self.process_single_file(file_path);
 
@@ -423,18 +1139,45 @@ impl ConcurrentFileProcessor {
             ))
         })?;
 
-        // 解析文件
-        let syntax_tree = {
+        // 解析文件，同一文件再次处理时基于上一版本的语法树增量解析
+        let parse_tree = || {
             let mut parser = parser_arc.lock().unwrap();
-            parser.parse_source(&source_code)?
+            // 解析器在多个处理器之间共享，每次解析前设置本处理器的超时时间
+            parser.set_timeout(self.parse_timeout);
+            self.ast_processor
+                .parse_with_previous_tree(&mut **parser, file_path, &source_code)
         };
 
-        // 提取语言特定信息
-        let language_specific =
-            self.extract_language_specific_info(&syntax_tree, &source_code, file_path, language)?;
+        // 提取语言特定信息，内容未变化时复用解析缓存中的结果和语法树
+        let (syntax_tree, language_specific): (_, Box<dyn crate::parser::LanguageSpecificInfo>) =
+            match language {
+                SupportedLanguage::Go => {
+                    let (parse, syntax_tree) = self.parser_cache.get_or_parse_with(
+                        file_path,
+                        &source_code,
+                        parse_tree,
+                        |syntax_tree, content_hash| {
+                            let info = self.extract_go_specific_info(
+                                syntax_tree,
+                                &source_code,
+                                file_path,
+                            )?;
+                            Ok(CachedParse::from_language_info(content_hash, &info))
+                        },
+                    )?;
+                    (syntax_tree, Box::new(parse.to_language_info()))
+                } // 未来可以在这里添加其他语言的支持
+                SupportedLanguage::Custom(_) => (
+                    parse_tree()?,
+                    Box::new(crate::parser::GenericLanguageInfo { language }),
+                ),
+            };
 
         Ok(SourceFile {
             path: file_path.to_path_buf(),
+            build_constraint: crate::analyzer::SourceAnalyzer::parse_build_constraints(
+                &source_code,
+            ),
             source_code,
             syntax_tree,
             language,
@@ -442,35 +1185,22 @@ impl ConcurrentFileProcessor {
         })
     }
 
-    /// 提取语言特定信息
-    fn extract_language_specific_info(
-        &self,
-        syntax_tree: &tree_sitter::Tree,
-        source_code: &str,
-        file_path: &Path,
-        language: SupportedLanguage,
-    ) -> Result<Box<dyn crate::parser::LanguageSpecificInfo>> {
-        match language {
-            SupportedLanguage::Go => {
-                self.extract_go_specific_info(syntax_tree, source_code, file_path)
-            } // 未来可以在这里添加其他语言的支持
-        }
-    }
-
     /// 提取 Go 语言特定信息
     fn extract_go_specific_info(
         &self,
         syntax_tree: &tree_sitter::Tree,
         source_code: &str,
         file_path: &Path,
-    ) -> Result<Box<dyn crate::parser::LanguageSpecificInfo>> {
-        use crate::parser::{GoLanguageInfo, common::CstNavigator};
+    ) -> Result<GoLanguageInfo> {
+        use crate::parser::{UNKNOWN_PACKAGE_NAME, common::CstNavigator};
 
         let navigator = CstNavigator::new();
         let root = syntax_tree.root_node();
 
-        // 提取包名
-        let package_name = self.extract_package_name(root, source_code, &navigator);
+        // 提取包名，缺少 package 子句时不猜测包名
+        let package_name = self
+            .extract_package_name(root, source_code, &navigator)
+            .unwrap_or_else(|| UNKNOWN_PACKAGE_NAME.to_string());
 
         // 提取导入
         let imports = self.extract_imports(root, source_code, &navigator);
@@ -479,20 +1209,20 @@ impl ConcurrentFileProcessor {
         let declarations =
             self.extract_go_declarations(root, source_code, file_path, &navigator)?;
 
-        Ok(Box::new(GoLanguageInfo {
+        Ok(GoLanguageInfo {
             package_name,
             imports,
             declarations,
-        }))
+        })
     }
 
-    /// 提取 Go 包名
+    /// 提取 Go 包名，没有 package 子句时返回 None
     fn extract_package_name(
         &self,
         root: tree_sitter::Node,
         source_code: &str,
         _navigator: &crate::parser::common::CstNavigator,
-    ) -> String {
+    ) -> Option<String> {
         let mut cursor = root.walk();
         for child in root.children(&mut cursor) {
             if child.kind() == "package_clause" {
@@ -500,12 +1230,12 @@ impl ConcurrentFileProcessor {
                 let mut pkg_cursor = child.walk();
                 for pkg_child in child.children(&mut pkg_cursor) {
                     if pkg_child.kind() == "package_identifier" {
-                        return source_code[pkg_child.byte_range()].to_string();
+                        return Some(source_code[pkg_child.byte_range()].to_string());
                     }
                 }
             }
         }
-        "main".to_string() // 默认包名
+        None
     }
 
     /// 提取导入声明
@@ -555,6 +1285,7 @@ impl ConcurrentFileProcessor {
     ) -> Option<crate::parser::Import> {
         let mut path = String::new();
         let mut alias = None;
+        let mut kind = crate::parser::ImportKind::Normal;
 
         let mut cursor = import_spec.walk();
         for child in import_spec.children(&mut cursor) {
@@ -566,12 +1297,14 @@ impl ConcurrentFileProcessor {
                 "package_identifier" => {
                     alias = Some(source_code[child.byte_range()].to_string());
                 }
+                "dot" => kind = crate::parser::ImportKind::Dot,
+                "blank_identifier" => kind = crate::parser::ImportKind::Blank,
                 _ => {}
             }
         }
 
         if !path.is_empty() {
-            Some(crate::parser::Import { path, alias })
+            Some(crate::parser::Import { path, alias, kind })
         } else {
             None
         }
@@ -601,7 +1334,12 @@ impl ConcurrentFileProcessor {
         // 提取类型声明
         let type_nodes = navigator.find_type_declarations(root);
         for type_node in type_nodes {
-            if let Ok(type_def) = self.extract_type_definition(type_node, source_code, file_path) {
+            let type_defs = crate::analyzer::SourceAnalyzer::extract_type_definitions(
+                type_node,
+                source_code,
+                file_path,
+            );
+            for type_def in type_defs {
                 declarations.push(Box::new(crate::parser::GoDeclaration::Type(type_def))
                     as Box<dyn crate::parser::common::Declaration>);
             }
@@ -623,8 +1361,9 @@ impl ConcurrentFileProcessor {
         // 获取函数签名
         let signature = navigator
             .get_function_signature(func_node, source_code)
-            .ok_or_else(|| {
-                SemanticDiffError::ParseError("Failed to extract function signature".to_string())
+            .ok_or_else(|| SemanticDiffError::MalformedSyntax {
+                file: file_path.to_path_buf(),
+                byte_offset: func_node.start_byte(),
             })?;
 
         // 获取函数体
@@ -637,43 +1376,31 @@ impl ConcurrentFileProcessor {
         // 获取行号范围
         let (start_line, end_line) = navigator.get_node_line_range(func_node);
 
-        // 转换参数
-        let parameters: Vec<GoParameter> = signature
-            .parameters
-            .iter()
-            .enumerate()
-            .map(|(i, param_str)| GoParameter {
-                name: format!("param{i}"),
-                param_type: GoType {
-                    name: param_str.clone(),
-                    is_pointer: param_str.contains('*'),
-                    is_slice: param_str.contains("[]"),
-                },
+        // 转换参数（按 CST 展开分组参数，保留真实参数名）
+        let parameters: Vec<GoParameter> = navigator
+            .get_function_parameters(func_node, source_code)
+            .into_iter()
+            .map(|param| GoParameter {
+                param_type: param.go_type,
+                name: param.name,
+                is_variadic: param.is_variadic,
             })
             .collect();
 
-        // 转换返回类型
-        let return_types: Vec<GoType> = signature
-            .return_types
-            .iter()
-            .map(|ret_str| GoType {
-                name: ret_str.clone(),
-                is_pointer: ret_str.contains('*'),
-                is_slice: ret_str.contains("[]"),
+        // 转换返回类型（保留命名返回值的名称）
+        let return_types: Vec<GoType> = navigator
+            .get_function_results(func_node, source_code)
+            .into_iter()
+            .map(|result| GoType {
+                result_name: Some(result.name).filter(|name| !name.is_empty()),
+                ..result.go_type
             })
             .collect();
 
         // 转换接收者信息
-        let receiver = signature.receiver.map(|recv_str| {
-            let is_pointer = recv_str.contains('*');
-            let type_name = recv_str.replace(['*', '(', ')'], "").trim().to_string();
-
-            GoReceiverInfo {
-                name: "self".to_string(),
-                type_name,
-                is_pointer,
-            }
-        });
+        let receiver = signature
+            .receiver
+            .map(|recv_str| GoReceiverInfo::from_signature(&recv_str));
 
         Ok(crate::parser::GoFunctionInfo {
             name: signature.name.clone(),
@@ -683,43 +1410,15 @@ impl ConcurrentFileProcessor {
             body,
             start_line,
             end_line,
+            byte_start: func_node.start_byte(),
+            byte_end: func_node.end_byte(),
+            doc_comment: navigator.get_doc_comment(func_node, source_code),
             file_path: file_path.to_path_buf(),
-        })
-    }
-
-    /// 提取类型定义
-    fn extract_type_definition(
-        &self,
-        type_node: tree_sitter::Node,
-        source_code: &str,
-        file_path: &Path,
-    ) -> Result<crate::parser::GoTypeDefinition> {
-        use crate::parser::{GoTypeDefinition, GoTypeKind};
-
-        let definition = source_code[type_node.byte_range()].to_string();
-
-        // 简化的类型名称提取
-        let name = if let Some(name_node) = type_node.child_by_field_name("name") {
-            source_code[name_node.byte_range()].to_string()
-        } else {
-            "UnknownType".to_string()
-        };
-
-        // 简化的类型种类判断
-        let kind = if definition.contains("struct") {
-            GoTypeKind::Struct
-        } else if definition.contains("interface") {
-            GoTypeKind::Interface
-        } else {
-            GoTypeKind::Alias
-        };
-
-        Ok(GoTypeDefinition {
-            name,
-            kind,
-            definition,
-            file_path: file_path.to_path_buf(),
-            dependencies: Vec::new(), // 这里可以进一步分析依赖
+            body_references: Arc::new(BodyReferences::from_function_node(func_node, source_code)),
+            type_parameters: crate::analyzer::SourceAnalyzer::extract_type_parameters(
+                func_node,
+                source_code,
+            ),
         })
     }
 }
@@ -738,16 +1437,42 @@ impl PerformanceMonitor {
             files_processed: Arc::new(Mutex::new(0)),
             total_processing_time: Arc::new(Mutex::new(Duration::ZERO)),
             error_count: Arc::new(Mutex::new(0)),
+            total_files: 0,
+            progress: None,
         }
     }
 
+    /// 设置需要处理的文件总数，用于进度事件
+    pub fn with_total_files(mut self, total: usize) -> Self {
+        self.total_files = total;
+        self
+    }
+
+    /// 设置进度回调，每记录一个处理完成的文件报告一次 [`ProgressEvent::FileParsed`]
+    pub fn with_progress_callback(mut self, callback: ProgressCallback) -> Self {
+        self.progress = Some(callback);
+        self
+    }
+
     /// 记录文件处理完成
     pub fn record_file_processed(&self, processing_time: Duration) {
-        let mut files = self.files_processed.lock().unwrap();
-        *files += 1;
+        let completed = {
+            let mut files = self.files_processed.lock().unwrap();
+            *files += 1;
+            *files as usize
+        };
 
         let mut total_time = self.total_processing_time.lock().unwrap();
         *total_time += processing_time;
+        drop(total_time);
+
+        // 在锁外调用回调，避免回调阻塞其他工作线程
+        if let Some(progress) = &self.progress {
+            progress(ProgressEvent::FileParsed {
+                completed,
+                total: self.total_files.max(completed),
+            });
+        }
     }
 
     /// 记录错误
@@ -776,11 +1501,50 @@ impl PerformanceMonitor {
             failed_files: error_count,
             avg_file_processing_time,
             peak_memory_usage: None, // 可以在实际实现中添加内存监控
+            estimated_memory_usage: 0,
+            peak_estimated_memory_usage: 0,
             cache_stats,
         }
     }
 }
 
+/// 估算缓存一个文件的源码和语法树占用的内存
+fn estimated_tree_bytes(source: &str) -> usize {
+    source.len() * (1 + ESTIMATED_TREE_BYTES_PER_SOURCE_BYTE)
+}
+
+/// 计算两个版本之间首尾相同行以外的变化范围，作为单个整行替换的差异块
+fn changed_line_range(old_src: &str, new_src: &str) -> DiffHunk {
+    let old_lines: Vec<&str> = old_src.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new_src.split_inclusive('\n').collect();
+
+    let prefix = old_lines
+        .iter()
+        .zip(&new_lines)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old_lines[prefix..]
+        .iter()
+        .rev()
+        .zip(new_lines[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let old_count = (old_lines.len() - prefix - suffix) as u32;
+    let new_count = (new_lines.len() - prefix - suffix) as u32;
+    // 行数为 0 时起始行号指向变更位置之前的一行
+    let start = |count: u32| prefix as u32 + u32::from(count > 0);
+
+    DiffHunk {
+        old_start: start(old_count),
+        old_lines: old_count,
+        new_start: start(new_count),
+        new_lines: new_count,
+        lines: Vec::new(),
+        context_lines: 0,
+    }
+}
+
 impl Default for PerformanceMonitor {
     fn default() -> Self {
         Self::new()
@@ -841,6 +1605,8 @@ impl ErrorRecoveryStrategy {
         for attempt in 0..=self.max_retries {
             match operation() {
                 Ok(result) => return Ok(result),
+                // 超时的文件重试仍会超时
+                Err(error @ SemanticDiffError::ParseTimeout(_)) => return Err(error),
                 Err(error) => {
                     last_error = Some(error);
 
@@ -861,9 +1627,33 @@ impl ErrorRecoveryStrategy {
             SemanticDiffError::IoError(_) => true,
             SemanticDiffError::ParseError(_) => self.skip_corrupted_files,
             SemanticDiffError::TreeSitterError(_) => self.skip_corrupted_files,
+            SemanticDiffError::MalformedSyntax { .. } => self.skip_corrupted_files,
+            SemanticDiffError::ParseTimeout(_) => self.skip_corrupted_files,
+            SemanticDiffError::DependencyError(_) => true,
+            SemanticDiffError::UnresolvedType { .. } => true,
             _ => false,
         }
     }
+
+    /// 处理单个文件或变更目标的错误，决定继续还是中止
+    ///
+    /// 可恢复的错误记录到 `diagnostics` 后返回 `Ok`，调用方跳过该项继续分析；
+    /// 不可恢复的错误原样返回，调用方应中止整次分析
+    pub fn recover(
+        &self,
+        error: SemanticDiffError,
+        file_path: Option<&Path>,
+        target: Option<&str>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<()> {
+        if !self.is_recoverable_error(&error) {
+            return Err(error);
+        }
+
+        warn!("已跳过 {:?} {:?}: {}", file_path, target, error);
+        diagnostics.record_error(&error, file_path, target);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -887,6 +1677,253 @@ mod tests {
         assert_eq!(stats.creates, 1);
     }
 
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_parser_cache_persists_parse_results() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let file_path = &temp_dir.path().join("service.go");
+        let source = "package pkg\n\nimport \"fmt\"\n\nfunc Hello() {\n\tfmt.Println()\n}\n";
+        // 持久化时会丢弃工作区中已不存在的文件
+        std::fs::write(file_path, source).unwrap();
+
+        // 首次解析并持久化
+        let cache = ParserCache::load_from_disk(&cache_dir).unwrap();
+        let parse = cache.get_or_parse(file_path, source).unwrap();
+        assert_eq!(parse.package_name, "pkg");
+        assert_eq!(cache.get_stats().parse_misses, 1);
+        cache.get_or_parse(file_path, source).unwrap();
+        assert_eq!(cache.get_stats().memory_hits, 1);
+        cache.persist_to_disk().unwrap();
+        drop(cache);
+
+        // 重新加载后直接命中，不需要重新解析
+        let cache = ParserCache::load_from_disk(&cache_dir).unwrap();
+        assert_eq!(cache.parse_result_count(), 1);
+        let reloaded = cache.get_or_parse(file_path, source).unwrap();
+        let stats = cache.get_stats();
+        assert_eq!(stats.disk_hits, 1);
+        assert_eq!(stats.parse_misses, 0);
+        assert_eq!(stats.creates, 0);
+        assert_eq!(reloaded.content_hash, parse.content_hash);
+        // 函数体引用不随函数信息序列化，随缓存条目一起恢复
+        let hello = reloaded
+            .to_language_info()
+            .find_function("Hello")
+            .cloned()
+            .unwrap();
+        assert!(hello.references("fmt", crate::parser::ReferenceKind::Variable));
+
+        // 内容变化时缓存条目失效
+        let changed = source.replace("Hello", "Goodbye");
+        let reparsed = cache.get_or_parse(file_path, &changed).unwrap();
+        let stats = cache.get_stats();
+        assert_eq!(stats.invalidations, 1);
+        assert_eq!(stats.parse_misses, 1);
+        assert!(
+            reparsed
+                .to_language_info()
+                .find_function("Goodbye")
+                .is_some()
+        );
+
+        // 没有缓存目录时无法持久化
+        assert!(ParserCache::new().persist_to_disk().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_processor_reuses_persisted_parse_results() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let file_path = temp_dir.path().join("service.go");
+        std::fs::write(&file_path, "package pkg\n\nfunc Hello() {}\n").unwrap();
+        let file_paths = vec![file_path.clone()];
+
+        // 第一次运行解析文件并持久化结果
+        let cache = Arc::new(ParserCache::load_from_disk(&cache_dir).unwrap());
+        let processor = ConcurrentFileProcessor::new().with_parser_cache(cache.clone());
+        let first = processor.process_files_concurrent(&file_paths).unwrap();
+        assert_eq!(first.successful.len(), 1);
+        assert_eq!(cache.get_stats().parse_misses, 1);
+        cache.persist_to_disk().unwrap();
+
+        // 第二次运行从磁盘缓存获取声明
+        let cache = Arc::new(ParserCache::load_from_disk(&cache_dir).unwrap());
+        let processor = ConcurrentFileProcessor::new().with_parser_cache(cache.clone());
+        let second = processor.process_files_concurrent(&file_paths).unwrap();
+        let stats = cache.get_stats();
+        assert_eq!(stats.disk_hits, 1);
+        assert_eq!(stats.parse_misses, 0);
+        assert!(
+            second.successful[0]
+                .language_specific
+                .as_any()
+                .downcast_ref::<GoLanguageInfo>()
+                .unwrap()
+                .find_function("Hello")
+                .is_some()
+        );
+
+        // 单文件分析器同样使用磁盘缓存
+        let cache = ParserCache::load_from_disk(&cache_dir).unwrap();
+        let source_file = crate::analyzer::SourceAnalyzer::new_for_file(&file_path)
+            .unwrap()
+            .analyze_file_with_cache(&file_path, &cache)
+            .unwrap();
+        assert_eq!(cache.get_stats().disk_hits, 1);
+        assert_eq!(source_file.language_specific.package_name(), "pkg");
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_parser_cache_reuses_syntax_trees() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let file_path = temp_dir.path().join("service.go");
+        let source = "package pkg\n\nfunc Hello() {}\n";
+        std::fs::write(&file_path, source).unwrap();
+
+        let tree_parses = AtomicUsize::new(0);
+        let extractions = AtomicUsize::new(0);
+        let lookup = |cache: &ParserCache| {
+            cache
+                .get_or_parse_with(
+                    &file_path,
+                    source,
+                    || {
+                        tree_parses.fetch_add(1, Ordering::Relaxed);
+                        ParserFactory::create_parser(SupportedLanguage::Go)?.parse_source(source)
+                    },
+                    |syntax_tree, content_hash| {
+                        extractions.fetch_add(1, Ordering::Relaxed);
+                        let info = crate::analyzer::SourceAnalyzer::new_for_language(
+                            SupportedLanguage::Go,
+                        )?
+                        .extract_go_specific_info(
+                            syntax_tree,
+                            source,
+                            &file_path,
+                        )?;
+                        Ok(CachedParse::from_language_info(content_hash, &info))
+                    },
+                )
+                .unwrap()
+        };
+
+        // 内存命中同时复用语法树
+        let cache = ParserCache::load_from_disk(&cache_dir).unwrap();
+        lookup(&cache);
+        let (_, tree) = lookup(&cache);
+        assert_eq!(tree.root_node().kind(), "source_file");
+        assert_eq!(tree_parses.load(Ordering::Relaxed), 1);
+        assert_eq!(extractions.load(Ordering::Relaxed), 1);
+        cache.persist_to_disk().unwrap();
+
+        // 磁盘命中只在第一次补解析语法树，不再提取声明
+        let cache = ParserCache::load_from_disk(&cache_dir).unwrap();
+        lookup(&cache);
+        lookup(&cache);
+        assert_eq!(cache.get_stats().disk_hits, 2);
+        assert_eq!(tree_parses.load(Ordering::Relaxed), 2);
+        assert_eq!(extractions.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_persist_prunes_unreferenced_entries() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let source = |name: &str| format!("package pkg\n\nfunc {name}() {{}}\n");
+        let path = |name: &str| temp_dir.path().join(format!("{name}.go"));
+
+        let cache = ParserCache::load_from_disk(&cache_dir).unwrap();
+        for name in ["kept", "deleted", "changed"] {
+            std::fs::write(path(name), source(name)).unwrap();
+            cache.get_or_parse(&path(name), &source(name)).unwrap();
+        }
+        std::fs::remove_file(path("deleted")).unwrap();
+        std::fs::write(path("changed"), source("renamed")).unwrap();
+        cache.persist_to_disk().unwrap();
+
+        // 只保留内容与工作区一致的条目
+        assert_eq!(cache.parse_result_count(), 1);
+        let cache = ParserCache::load_from_disk(&cache_dir).unwrap();
+        assert_eq!(cache.parse_result_count(), 1);
+        assert!(cache.contains_parse(&path("kept")));
+
+        // 写入通过临时文件完成，不会留下临时文件
+        let files: Vec<_> = std::fs::read_dir(&cache_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, vec![std::ffi::OsString::from(PARSE_CACHE_FILE)]);
+    }
+
+    #[test]
+    fn test_parser_cache_lru_eviction() {
+        let cache = ParserCache::new().with_capacity(3);
+        let source = |name: &str| format!("package pkg\n\nfunc {name}() {{}}\n");
+        let path = |name: &str| PathBuf::from(format!("pkg/{name}.go"));
+
+        for name in ["a", "b", "c"] {
+            cache.get_or_parse(&path(name), &source(name)).unwrap();
+        }
+
+        // 访问 a 和 b，使 c 成为最久未使用的条目
+        cache.get_or_parse(&path("a"), &source("a")).unwrap();
+        cache.get_or_parse(&path("b"), &source("b")).unwrap();
+
+        cache.get_or_parse(&path("d"), &source("d")).unwrap();
+        cache.get_or_parse(&path("e"), &source("e")).unwrap();
+
+        assert_eq!(cache.parse_result_count(), 3);
+        assert!(!cache.contains_parse(&path("c")));
+        assert!(cache.contains_parse(&path("b")));
+        assert!(cache.contains_parse(&path("e")));
+        // d 在 a 之后加入，因此 a 先于 d 被淘汰
+        assert!(!cache.contains_parse(&path("a")));
+        assert!(cache.contains_parse(&path("d")));
+
+        let stats = cache.get_stats();
+        assert_eq!(stats.evictions, 2);
+        assert_eq!(stats.memory_hits, 2);
+
+        // 被淘汰的条目再次访问时需要重新解析
+        cache.get_or_parse(&path("c"), &source("c")).unwrap();
+        assert_eq!(cache.get_stats().parse_misses, 6);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_parser_cache_keeps_lru_order_across_runs() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = temp_dir.path().join("cache");
+        let source = |name: &str| format!("package pkg\n\nfunc {name}() {{}}\n");
+        let path = |name: &str| temp_dir.path().join(format!("{name}.go"));
+
+        let cache = ParserCache::load_from_disk(&cache_dir).unwrap();
+        for name in ["a", "b", "c"] {
+            std::fs::write(path(name), source(name)).unwrap();
+            cache.get_or_parse(&path(name), &source(name)).unwrap();
+        }
+        cache.get_or_parse(&path("a"), &source("a")).unwrap();
+        cache.persist_to_disk().unwrap();
+        drop(cache);
+
+        // 重新加载的条目保留上次运行的使用顺序，b 是最久未使用的条目
+        let cache = ParserCache::load_from_disk(&cache_dir)
+            .unwrap()
+            .with_capacity(2);
+        assert_eq!(cache.get_stats().evictions, 1);
+        assert!(!cache.contains_parse(&path("b")));
+
+        cache.get_or_parse(&path("d"), &source("d")).unwrap();
+        assert!(!cache.contains_parse(&path("c")));
+        assert!(cache.contains_parse(&path("a")));
+        assert!(cache.contains_parse(&path("d")));
+    }
+
     #[test]
     fn test_memory_efficient_ast_processor() {
         let processor = MemoryEfficientAstProcessor::new()
@@ -900,16 +1937,225 @@ mod tests {
         assert!(!processor.memory_monitoring);
     }
 
+    #[test]
+    fn test_ast_processor_reuses_previous_tree() {
+        let processor = MemoryEfficientAstProcessor::new().with_memory_monitoring(false);
+        let mut parser = ParserFactory::create_parser(SupportedLanguage::Go).unwrap();
+        let path = Path::new("main.go");
+
+        let old_src = "package main\n\nfunc A() {}\n\nfunc B() {}\n";
+        processor
+            .parse_with_previous_tree(parser.as_mut(), path, old_src)
+            .unwrap();
+        assert_eq!(processor.cached_tree_count(), 1);
+
+        // 第二次解析基于缓存的语法树增量进行，结果与完整解析一致
+        let new_src = "package main\n\nfunc A() {\n\tB()\n}\n\nfunc B() {}\n";
+        let incremental = processor
+            .parse_with_previous_tree(parser.as_mut(), path, new_src)
+            .unwrap();
+        let full = parser.parse_source(new_src).unwrap();
+        assert_eq!(
+            incremental.root_node().to_sexp(),
+            full.root_node().to_sexp()
+        );
+
+        let hunk = changed_line_range(old_src, new_src);
+        assert_eq!(
+            (
+                hunk.old_start,
+                hunk.old_lines,
+                hunk.new_start,
+                hunk.new_lines
+            ),
+            (3, 1, 3, 3)
+        );
+    }
+
+    #[test]
+    fn test_memory_budget_drops_cached_trees() {
+        let source = "package main\n\nfunc A() {}\n";
+        let tree_bytes = estimated_tree_bytes(source);
+        // 预算只够缓存一个文件的语法树
+        let processor = MemoryEfficientAstProcessor::new()
+            .with_memory_monitoring(false)
+            .with_memory_budget(tree_bytes * 3 / 2);
+        let mut parser = ParserFactory::create_parser(SupportedLanguage::Go).unwrap();
+
+        processor
+            .parse_with_previous_tree(parser.as_mut(), Path::new("a.go"), source)
+            .unwrap();
+        processor
+            .parse_with_previous_tree(parser.as_mut(), Path::new("b.go"), source)
+            .unwrap();
+        assert_eq!(processor.cached_tree_count(), 1);
+        assert_eq!(processor.tracked_memory(), tree_bytes);
+        assert!(processor.peak_tracked_memory() <= tree_bytes * 3 / 2);
+
+        // 批大小按剩余预算缩小，且只缩小不放大
+        assert_eq!(processor.next_batch_size(10, tree_bytes / 4), 2);
+        assert_eq!(processor.next_batch_size(10, 1), 2);
+        assert_eq!(processor.current_batch_size(), Some(2));
+
+        assert_eq!(processor.drop_cached_trees(), 1);
+        assert_eq!(processor.tracked_memory(), 0);
+    }
+
     #[test]
     fn test_concurrent_file_processor() {
         let processor = ConcurrentFileProcessor::new()
             .with_thread_pool_size(2)
             .with_batch_size(5);
 
-        assert_eq!(processor.thread_pool_size, 2);
+        assert_eq!(processor.thread_pool.threads(), 2);
         assert_eq!(processor.batch_size, 5);
     }
 
+    #[test]
+    fn test_thread_count_does_not_change_results() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_paths: Vec<PathBuf> = (0..8)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("file{i}.go"));
+                std::fs::write(
+                    &path,
+                    format!("package p{i}\n\nfunc F{i}() int {{\n\treturn {i}\n}}\n"),
+                )
+                .unwrap();
+                path
+            })
+            .collect();
+
+        let summarize = |threads: usize| {
+            let result = ConcurrentFileProcessor::new()
+                .with_threads(threads)
+                .with_batch_size(3)
+                .process_files_concurrent(&file_paths)
+                .unwrap();
+            assert!(result.failed.is_empty());
+            result
+                .successful
+                .iter()
+                .map(|file| {
+                    (
+                        file.path.clone(),
+                        file.language_specific.package_name().to_string(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let sequential = summarize(1);
+        assert_eq!(sequential.len(), file_paths.len());
+        assert_eq!(summarize(2), sequential);
+        assert_eq!(summarize(4), sequential);
+    }
+
+    #[test]
+    fn test_single_thread_pool_runs_on_caller() {
+        let pool = ScopedThreadPool::new(1);
+        let caller = std::thread::current().id();
+        let items: Vec<usize> = (0..16).collect();
+
+        let threads = pool
+            .map(&items, |_| {
+                (std::thread::current().id(), rayon::current_thread_index())
+            })
+            .unwrap();
+        assert!(
+            threads
+                .iter()
+                .all(|&(id, index)| id == caller && index.is_none())
+        );
+        // 顺序执行时不会创建线程池
+        assert!(pool.pool.get().is_none());
+
+        // 多线程时任务在局部线程池中执行，结果保持输入顺序
+        let pool = ScopedThreadPool::new(2);
+        let doubled = pool.map(&items, |&i| i * 2).unwrap();
+        assert_eq!(doubled, items.iter().map(|i| i * 2).collect::<Vec<_>>());
+        let num_threads = pool.install(rayon::current_num_threads).unwrap();
+        assert_eq!(num_threads, 2);
+        assert_eq!(ScopedThreadPool::new(0).threads(), 1);
+    }
+
+    #[test]
+    fn test_parse_timeout_is_recoverable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let small = temp_dir.path().join("small.go");
+        std::fs::write(&small, "package main\n\nfunc main() {}\n").unwrap();
+
+        let mut large_source = String::from("package main\n");
+        for i in 0..20_000 {
+            large_source.push_str(&format!("\nfunc F{i}() int {{\n\treturn {i}\n}}\n"));
+        }
+        let large = temp_dir.path().join("large.go");
+        std::fs::write(&large, &large_source).unwrap();
+
+        let result = ConcurrentFileProcessor::new()
+            .with_threads(1)
+            .with_parse_timeout(Duration::from_nanos(1))
+            .process_files_concurrent(&[large.clone(), small.clone()])
+            .unwrap();
+
+        // 超时的大文件被跳过，小文件在超时检查之前就已解析完成
+        assert_eq!(result.failed.len(), 1);
+        let (path, error) = &result.failed[0];
+        assert_eq!(path, &large);
+        assert!(matches!(error, SemanticDiffError::ParseTimeout(_)));
+        assert!(ErrorRecoveryStrategy::new().is_recoverable_error(error));
+        assert_eq!(result.successful.len(), 1);
+        assert_eq!(result.successful[0].path, small);
+
+        // 超时错误不会被重试
+        let strategy = ErrorRecoveryStrategy::new().with_retry_delay(Duration::ZERO);
+        let mut attempts = 0;
+        let retried: Result<()> = strategy.execute_with_retry(|| {
+            attempts += 1;
+            Err(SemanticDiffError::ParseTimeout("large.go".to_string()))
+        });
+        assert!(retried.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn test_progress_events_cover_all_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut file_paths: Vec<PathBuf> = (0..6)
+            .map(|i| {
+                let path = temp_dir.path().join(format!("file{i}.go"));
+                std::fs::write(&path, format!("package p{i}\n")).unwrap();
+                path
+            })
+            .collect();
+        // 失败的文件同样计入进度
+        file_paths.push(temp_dir.path().join("missing.go"));
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let collected = events.clone();
+        let result = ConcurrentFileProcessor::new()
+            .with_threads(3)
+            .on_progress(move |event| collected.lock().unwrap().push(event))
+            .process_files_concurrent(&file_paths)
+            .unwrap();
+        assert_eq!(result.failed.len(), 1);
+
+        let mut completed: Vec<usize> = events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| match *event {
+                ProgressEvent::FileParsed { completed, total } => {
+                    assert_eq!(total, file_paths.len());
+                    completed
+                }
+                other => panic!("unexpected event: {other:?}"),
+            })
+            .collect();
+        completed.sort_unstable();
+        assert_eq!(completed, (1..=file_paths.len()).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_error_recovery_strategy() {
         let strategy = ErrorRecoveryStrategy::new()
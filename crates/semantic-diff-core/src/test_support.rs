@@ -0,0 +1,22 @@
+//! 单元测试共用的辅助函数
+
+use std::path::Path;
+use std::process::Command;
+
+/// 在测试仓库中运行 git 命令，返回标准输出
+///
+/// 命令执行失败时带着 git 的错误输出直接 panic
+pub(crate) fn run_git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .expect("Failed to run git");
+    assert!(
+        output.status.success(),
+        "git {:?} failed: {}",
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
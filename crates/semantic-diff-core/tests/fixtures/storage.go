@@ -0,0 +1,74 @@
+package storage
+
+type (
+	Closer interface {
+		Close() error
+	}
+
+	Storage interface {
+		Closer
+		Save(key, value string) error
+		Load(key string) (string, error)
+		Snapshot() map[string]string
+	}
+)
+
+type MemoryStorage struct {
+	data map[string]string
+}
+
+func (m *MemoryStorage) Save(key, value string) error {
+	m.data[key] = value
+	return nil
+}
+
+func (m *MemoryStorage) Load(key string) (string, error) {
+	return m.data[key], nil
+}
+
+func (m *MemoryStorage) Snapshot() map[string]string {
+	return m.data
+}
+
+func (m MemoryStorage) Close() error {
+	return nil
+}
+
+type CountingStorage struct {
+	counts map[string]int
+}
+
+func (c *CountingStorage) Save(key, value string) error {
+	c.counts[key]++
+	return nil
+}
+
+func (c *CountingStorage) Load(key string) (string, error) {
+	return "", nil
+}
+
+func (c *CountingStorage) Snapshot() map[string]int {
+	return c.counts
+}
+
+func (c *CountingStorage) Close() error {
+	return nil
+}
+
+type NoopStorage struct{}
+
+func (n *NoopStorage) Save(key, value string) error {
+	return nil
+}
+
+func (n *NoopStorage) Load(key string) (string, error) {
+	return "", nil
+}
+
+func (n *NoopStorage) Snapshot() map[string]string {
+	return nil
+}
+
+type Logger struct{}
+
+func (l *Logger) Save(message string) {}
@@ -6,7 +6,8 @@ use semantic_diff_core::extractor::{ChangeTarget, SemanticContextExtractor};
 use semantic_diff_core::parser::common::LanguageParser;
 use semantic_diff_core::parser::{
     GoConstantDefinition, GoDeclaration, GoFunctionInfo, GoLanguageInfo, GoParameter, GoType,
-    GoTypeDefinition, GoTypeKind, GoVariableDefinition, Import, SourceFile, SupportedLanguage,
+    GoTypeDefinition, GoTypeKind, GoVariableDefinition, Import, ImportKind, SourceFile,
+    SupportedLanguage,
 };
 use std::path::PathBuf;
 
@@ -45,6 +46,7 @@ func (u *User) GetName() string {
     go_info.add_import(Import {
         path: "fmt".to_string(),
         alias: None,
+        kind: ImportKind::Normal,
     });
 
     // 添加类型定义
@@ -54,6 +56,13 @@ func (u *User) GetName() string {
         definition: "type User struct { Name string; Age int }".to_string(),
         file_path: PathBuf::from("main.go"),
         dependencies: vec![],
+        start_line: 0,
+        end_line: 0,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
     };
     go_info.add_go_declaration(GoDeclaration::Type(user_type));
 
@@ -65,10 +74,20 @@ func (u *User) GetName() string {
             name: "string".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }),
         start_line: 9,
         end_line: 9,
         file_path: PathBuf::from("main.go"),
+        byte_start: 0,
+        byte_end: 0,
+        iota_index: None,
+        doc_comment: None,
     };
     go_info.add_go_declaration(GoDeclaration::Constant(default_name_const));
 
@@ -79,11 +98,20 @@ func (u *User) GetName() string {
             name: "User".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }),
         initial_value: None,
         start_line: 11,
         end_line: 11,
         file_path: PathBuf::from("main.go"),
+        byte_start: 0,
+        byte_end: 0,
+        doc_comment: None,
     };
     go_info.add_go_declaration(GoDeclaration::Variable(global_user_var));
 
@@ -98,7 +126,14 @@ func (u *User) GetName() string {
                     name: "string".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
             GoParameter {
                 name: "age".to_string(),
@@ -106,18 +141,36 @@ func (u *User) GetName() string {
                     name: "int".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
         ],
         return_types: vec![GoType {
             name: "User".to_string(),
             is_pointer: true,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "return &User{Name: name, Age: age}".to_string(),
         start_line: 13,
         end_line: 15,
         file_path: PathBuf::from("main.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
     go_info.add_go_declaration(GoDeclaration::Function(create_user_func));
 
@@ -134,11 +187,22 @@ func (u *User) GetName() string {
             name: "string".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "return u.Name".to_string(),
         start_line: 17,
         end_line: 19,
         file_path: PathBuf::from("main.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
     go_info.add_go_declaration(GoDeclaration::Method(get_name_method));
 
@@ -148,6 +212,7 @@ func (u *User) GetName() string {
         syntax_tree,
         language: SupportedLanguage::Go,
         language_specific: Box::new(go_info),
+        build_constraint: None,
     }
 }
 
@@ -167,7 +232,14 @@ fn test_basic_function_context_extraction() {
                     name: "string".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
             GoParameter {
                 name: "age".to_string(),
@@ -175,18 +247,36 @@ fn test_basic_function_context_extraction() {
                     name: "int".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
         ],
         return_types: vec![GoType {
             name: "User".to_string(),
             is_pointer: true,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: "return &User{Name: name, Age: age}".to_string(),
         start_line: 13,
         end_line: 15,
         file_path: PathBuf::from("main.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let context = extractor
@@ -225,6 +315,13 @@ fn test_basic_type_context_extraction() {
         definition: "type User struct { Name string; Age int }".to_string(),
         file_path: PathBuf::from("main.go"),
         dependencies: vec![],
+        start_line: 0,
+        end_line: 0,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
     };
 
     let change_target = ChangeTarget::Type(user_type);
@@ -260,11 +357,20 @@ fn test_basic_variable_context_extraction() {
             name: "User".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }),
         initial_value: None,
         start_line: 11,
         end_line: 11,
         file_path: PathBuf::from("main.go"),
+        byte_start: 0,
+        byte_end: 0,
+        doc_comment: None,
     };
 
     let change_target = ChangeTarget::Variable(global_user_var);
@@ -297,10 +403,20 @@ fn test_basic_constant_context_extraction() {
             name: "string".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }),
         start_line: 9,
         end_line: 9,
         file_path: PathBuf::from("main.go"),
+        byte_start: 0,
+        byte_end: 0,
+        iota_index: None,
+        doc_comment: None,
     };
 
     let change_target = ChangeTarget::Constant(default_name_const);
@@ -334,6 +450,11 @@ fn test_context_api_methods() {
         start_line: 1,
         end_line: 3,
         file_path: PathBuf::from("main.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let mut context = extractor
@@ -351,6 +472,13 @@ fn test_context_api_methods() {
         definition: "type TestType struct {}".to_string(),
         file_path: PathBuf::from("test.go"),
         dependencies: vec![],
+        start_line: 0,
+        end_line: 0,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
     };
     context.add_type(user_type);
 
@@ -363,6 +491,11 @@ fn test_context_api_methods() {
         start_line: 1,
         end_line: 1,
         file_path: PathBuf::from("helper.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
     context.add_function(helper_func);
 
@@ -373,6 +506,10 @@ fn test_context_api_methods() {
         start_line: 1,
         end_line: 1,
         file_path: PathBuf::from("const.go"),
+        byte_start: 0,
+        byte_end: 0,
+        iota_index: None,
+        doc_comment: None,
     };
     context.add_constant(test_const);
 
@@ -383,12 +520,16 @@ fn test_context_api_methods() {
         start_line: 1,
         end_line: 1,
         file_path: PathBuf::from("var.go"),
+        byte_start: 0,
+        byte_end: 0,
+        doc_comment: None,
     };
     context.add_variable(test_var);
 
     let import = Import {
         path: "testing".to_string(),
         alias: None,
+        kind: ImportKind::Normal,
     };
     context.add_import(import);
 
@@ -466,6 +607,11 @@ fn test_change_target_api() {
         start_line: 1,
         end_line: 1,
         file_path: PathBuf::from("test.go"),
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
     };
 
     let func_target = ChangeTarget::Function(func);
@@ -482,6 +628,13 @@ fn test_change_target_api() {
         definition: "".to_string(),
         file_path: PathBuf::from("type.go"),
         dependencies: vec![],
+        start_line: 0,
+        end_line: 0,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
     };
 
     let type_target = ChangeTarget::Type(type_def);
@@ -264,6 +264,7 @@ fn test_go_module_dependency_resolution_simple_module() {
 
     let resolver = DependencyResolver::from_project_root(project_root)
         .expect("Failed to create resolver from project root");
+    let index = resolver.build_index(&source_files);
 
     // 测试类型解析
     let user_type_ref = semantic_diff_core::analyzer::TypeReference {
@@ -271,7 +272,7 @@ fn test_go_module_dependency_resolution_simple_module() {
         package: Some("models".to_string()),
     };
 
-    let resolved_user = resolver.resolve_type(&user_type_ref, &source_files);
+    let resolved_user = resolver.resolve_type(&user_type_ref, &index);
     assert!(resolved_user.is_some(), "应该能够解析User类型");
 
     let user_def = resolved_user.unwrap();
@@ -285,7 +286,7 @@ fn test_go_module_dependency_resolution_simple_module() {
         package: Some("models".to_string()),
     };
 
-    let resolved_func = resolver.resolve_function(&new_user_func, &source_files);
+    let resolved_func = resolver.resolve_function(&new_user_func, &index);
     assert!(resolved_func.is_some(), "应该能够解析NewUser函数");
 
     let func_def = resolved_func.unwrap();
@@ -298,7 +299,7 @@ fn test_go_module_dependency_resolution_simple_module() {
         package: None,
     };
 
-    let resolved_method = resolver.resolve_function(&get_full_name_method, &source_files);
+    let resolved_method = resolver.resolve_function(&get_full_name_method, &index);
     assert!(resolved_method.is_some(), "应该能够解析GetFullName方法");
 
     // 测试类型分析器
@@ -320,6 +321,7 @@ fn test_go_module_dependency_resolution_simple_module() {
     let fmt_import = semantic_diff_core::parser::Import {
         path: "fmt".to_string(),
         alias: None,
+        kind: semantic_diff_core::parser::ImportKind::Normal,
     };
     assert!(
         resolver.is_external_dependency(&fmt_import),
@@ -329,6 +331,7 @@ fn test_go_module_dependency_resolution_simple_module() {
     let internal_import = semantic_diff_core::parser::Import {
         path: "examplePkg/internal/models".to_string(),
         alias: None,
+        kind: semantic_diff_core::parser::ImportKind::Normal,
     };
     assert!(
         !resolver.is_external_dependency(&internal_import),
@@ -438,6 +441,7 @@ func FormatUserName(user *models.User) string {
     let github_models_import = semantic_diff_core::parser::Import {
         path: "github.com/M4n5ter/examplePkg/internal/models".to_string(),
         alias: None,
+        kind: semantic_diff_core::parser::ImportKind::Normal,
     };
 
     // GitHub模块的内部包不应该被认为是外部依赖
@@ -450,6 +454,7 @@ func FormatUserName(user *models.User) string {
     let third_party_import = semantic_diff_core::parser::Import {
         path: "github.com/stretchr/testify".to_string(),
         alias: None,
+        kind: semantic_diff_core::parser::ImportKind::Normal,
     };
     assert!(
         resolver.is_external_dependency(&third_party_import),
@@ -462,7 +467,8 @@ func FormatUserName(user *models.User) string {
         package: Some("models".to_string()),
     };
 
-    let resolved_user = resolver.resolve_type(&user_type_ref, &source_files);
+    let index = resolver.build_index(&source_files);
+    let resolved_user = resolver.resolve_type(&user_type_ref, &index);
     assert!(resolved_user.is_some(), "应该能够解析User类型");
 }
 
@@ -505,7 +511,8 @@ fn test_cross_package_dependency_analysis() {
     let func_info = process_user_func.unwrap();
 
     // 分析函数的依赖关系
-    let func_dependencies = resolver.extract_function_dependencies(&func_info, &source_files);
+    let func_dependencies =
+        resolver.extract_function_dependencies(&func_info, &resolver.build_index(&source_files));
     assert!(!func_dependencies.is_empty(), "ProcessUser函数应该有依赖");
 
     // 过滤内部依赖
@@ -557,6 +564,7 @@ fn test_module_path_detection() {
         let import = semantic_diff_core::parser::Import {
             path: import_path.to_string(),
             alias: None,
+            kind: semantic_diff_core::parser::ImportKind::Normal,
         };
 
         let is_external = resolver.is_external_dependency(&import);
@@ -875,7 +883,8 @@ func ProcessUser(user UserEntity, repo Repository[UserEntity]) error {
     assert!(process_user_func.is_some(), "应该找到ProcessUser函数");
 
     let func_info = process_user_func.unwrap();
-    let func_dependencies = resolver.extract_function_dependencies(&func_info, &source_files);
+    let func_dependencies =
+        resolver.extract_function_dependencies(&func_info, &resolver.build_index(&source_files));
 
     println!("ProcessUser function dependencies: {func_dependencies:?}");
     let func_dep_names: Vec<&str> = func_dependencies.iter().map(|d| d.name.as_str()).collect();
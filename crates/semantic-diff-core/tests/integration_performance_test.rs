@@ -3,7 +3,7 @@
 //! 测试性能优化组件的集成功能
 
 use semantic_diff_core::{
-    LanguageSpecificInfo, SupportedLanguage,
+    DiagnosticKind, Diagnostics, LanguageSpecificInfo, SupportedLanguage,
     analyzer::SourceAnalyzer,
     extractor::{ChangeTarget, SemanticContextExtractor},
     performance::{
@@ -200,6 +200,42 @@ fn test_source_analyzer_with_error_recovery() {
     assert_eq!(source_files.len(), 5);
 }
 
+#[test]
+fn test_unparseable_file_reported_in_diagnostics() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut files = create_test_go_files(&temp_dir, 3);
+
+    // 非 UTF-8 内容的文件无法读取为源码
+    let bad_file = temp_dir.path().join("corrupt.go");
+    std::fs::write(&bad_file, b"package main\n\xff\xfe\xfd func").unwrap();
+    files.insert(1, bad_file.clone());
+
+    let strategy = ErrorRecoveryStrategy::new().with_retry_delay(Duration::ZERO);
+    let mut diagnostics = Diagnostics::new();
+    let source_files =
+        SourceAnalyzer::analyze_files_with_diagnostics(&files, &strategy, &mut diagnostics)
+            .unwrap();
+
+    // 其他文件照常分析，并保持输入顺序
+    let analyzed: Vec<&PathBuf> = source_files.iter().map(|f| &f.path).collect();
+    assert_eq!(analyzed, vec![&files[0], &files[2], &files[3]]);
+
+    assert_eq!(diagnostics.len(), 1);
+    let diagnostic = &diagnostics.entries()[0];
+    assert_eq!(diagnostic.kind, DiagnosticKind::ParseFailure);
+    assert_eq!(diagnostic.file_path.as_ref(), Some(&bad_file));
+    assert!(diagnostic.message.contains("corrupt.go"));
+
+    // 不可恢复的错误中止整次分析
+    let unsupported = temp_dir.path().join("notes.txt");
+    std::fs::write(&unsupported, "not go").unwrap();
+    files.push(unsupported);
+    let mut diagnostics = Diagnostics::new();
+    let result =
+        SourceAnalyzer::analyze_files_with_diagnostics(&files, &strategy, &mut diagnostics);
+    assert!(result.is_err());
+}
+
 #[test]
 fn test_semantic_context_extractor_concurrent() {
     let temp_dir = TempDir::new().unwrap();
@@ -2,17 +2,24 @@
 //!
 //! 测试复杂场景，包括跨模块依赖、全局变量、接口、结构体等
 
-use semantic_diff_core::extractor::{ChangeTarget, SemanticContextExtractor};
+use semantic_diff_core::extractor::{ChangeTarget, ChangeType, SemanticContextExtractor};
 use semantic_diff_core::parser::common::LanguageParser;
 use semantic_diff_core::parser::{
-    GoConstantDefinition, GoDeclaration, GoFunctionInfo, GoLanguageInfo, GoParameter,
-    GoReceiverInfo, GoType, GoTypeDefinition, GoTypeKind, GoVariableDefinition, Import, SourceFile,
-    SupportedLanguage,
+    BodyReferences, GoConstantDefinition, GoDeclaration, GoFunctionInfo, GoLanguageInfo,
+    GoParameter, GoReceiverInfo, GoType, GoTypeDefinition, GoTypeKind, GoVariableDefinition,
+    Import, ImportKind, SourceFile, SupportedLanguage,
 };
 use std::fs;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tempfile::TempDir;
 
+/// 为手工构造的函数收集函数体引用（从源文件提取的函数在提取时已经收集）
+fn with_body_references(mut function: GoFunctionInfo) -> GoFunctionInfo {
+    function.body_references = Arc::new(BodyReferences::from_body(&function.body));
+    function
+}
+
 /// 创建测试用的临时Go项目
 fn create_test_go_project() -> (TempDir, Vec<SourceFile>) {
     let temp_dir = TempDir::new().expect("Failed to create temp directory");
@@ -305,30 +312,35 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 go_info.add_import(Import {
                     path: "fmt".to_string(),
                     alias: None,
+                    kind: ImportKind::Normal,
                 });
             }
             if content.contains("time") {
                 go_info.add_import(Import {
                     path: "time".to_string(),
                     alias: None,
+                    kind: ImportKind::Normal,
                 });
             }
             if content.contains("errors") {
                 go_info.add_import(Import {
                     path: "errors".to_string(),
                     alias: None,
+                    kind: ImportKind::Normal,
                 });
             }
             if content.contains("models") {
                 go_info.add_import(Import {
                     path: "github.com/test/semantic-diff-test/models".to_string(),
                     alias: None,
+                    kind: ImportKind::Normal,
                 });
             }
             if content.contains("services") {
                 go_info.add_import(Import {
                     path: "github.com/test/semantic-diff-test/services".to_string(),
                     alias: None,
+                    kind: ImportKind::Normal,
                 });
             }
         }
@@ -349,6 +361,13 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 .to_string(),
                 file_path: file_path.clone(),
                 dependencies: vec!["Profile".to_string()],
+                start_line: 0,
+                end_line: 0,
+                byte_start: 0,
+                byte_end: 0,
+                type_parameters: Vec::new(),
+                fields: Vec::new(),
+                doc_comment: None,
             })));
 
             // 添加 Profile 类型
@@ -363,6 +382,13 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 .to_string(),
                 file_path: file_path.clone(),
                 dependencies: vec!["Address".to_string()],
+                start_line: 0,
+                end_line: 0,
+                byte_start: 0,
+                byte_end: 0,
+                type_parameters: Vec::new(),
+                fields: Vec::new(),
+                doc_comment: None,
             })));
 
             // 添加 Address 类型
@@ -377,53 +403,87 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 .to_string(),
                 file_path: file_path.clone(),
                 dependencies: vec![],
+                start_line: 0,
+                end_line: 0,
+                byte_start: 0,
+                byte_end: 0,
+                type_parameters: Vec::new(),
+                fields: Vec::new(),
+                doc_comment: None,
             })));
 
             // 添加方法
-            go_info.add_declaration(Box::new(GoDeclaration::Method(GoFunctionInfo {
-                name: "GetDisplayName".to_string(),
-                receiver: Some(GoReceiverInfo {
-                    name: "u".to_string(),
-                    type_name: "User".to_string(),
-                    is_pointer: true,
-                }),
-                parameters: vec![],
-                return_types: vec![GoType {
-                    name: "string".to_string(),
-                    is_pointer: false,
-                    is_slice: false,
-                }],
-                body: r#"if u.Profile != nil && u.Profile.Bio != "" {
+            go_info.add_declaration(Box::new(GoDeclaration::Method(with_body_references(
+                GoFunctionInfo {
+                    name: "GetDisplayName".to_string(),
+                    receiver: Some(GoReceiverInfo {
+                        name: "u".to_string(),
+                        type_name: "User".to_string(),
+                        is_pointer: true,
+                    }),
+                    parameters: vec![],
+                    return_types: vec![GoType {
+                        name: "string".to_string(),
+                        is_pointer: false,
+                        is_slice: false,
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
+                    }],
+                    body: r#"if u.Profile != nil && u.Profile.Bio != "" {
         return u.Name + " (" + u.Profile.Bio + ")"
     }
     return u.Name"#
-                    .to_string(),
-                start_line: 25,
-                end_line: 30,
-                file_path: file_path.clone(),
-            })));
-
-            go_info.add_declaration(Box::new(GoDeclaration::Method(GoFunctionInfo {
-                name: "UpdateProfile".to_string(),
-                receiver: Some(GoReceiverInfo {
-                    name: "u".to_string(),
-                    type_name: "User".to_string(),
-                    is_pointer: true,
-                }),
-                parameters: vec![GoParameter {
-                    name: "profile".to_string(),
-                    param_type: GoType {
-                        name: "Profile".to_string(),
+                        .to_string(),
+                    start_line: 25,
+                    end_line: 30,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
+
+            go_info.add_declaration(Box::new(GoDeclaration::Method(with_body_references(
+                GoFunctionInfo {
+                    name: "UpdateProfile".to_string(),
+                    receiver: Some(GoReceiverInfo {
+                        name: "u".to_string(),
+                        type_name: "User".to_string(),
                         is_pointer: true,
-                        is_slice: false,
-                    },
-                }],
-                return_types: vec![],
-                body: "u.Profile = profile".to_string(),
-                start_line: 32,
-                end_line: 34,
-                file_path: file_path.clone(),
-            })));
+                    }),
+                    parameters: vec![GoParameter {
+                        name: "profile".to_string(),
+                        param_type: GoType {
+                            name: "Profile".to_string(),
+                            is_pointer: true,
+                            is_slice: false,
+                            result_name: None,
+                            is_channel: false,
+                            channel_direction: None,
+                            key_type: None,
+                            value_type: None,
+                            array_len: None,
+                        },
+                        is_variadic: false,
+                    }],
+                    return_types: vec![],
+                    body: "u.Profile = profile".to_string(),
+                    start_line: 32,
+                    end_line: 34,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
         }
 
         if filename == "models/config.go" {
@@ -434,11 +494,21 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                     name: "string".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 }),
                 value: "\"localhost\"".to_string(),
                 start_line: 5,
                 end_line: 5,
                 file_path: file_path.clone(),
+                byte_start: 0,
+                byte_end: 0,
+                iota_index: None,
+                doc_comment: None,
             })));
 
             go_info.add_declaration(Box::new(GoDeclaration::Constant(GoConstantDefinition {
@@ -447,11 +517,21 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                     name: "int".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 }),
                 value: "8080".to_string(),
                 start_line: 6,
                 end_line: 6,
                 file_path: file_path.clone(),
+                byte_start: 0,
+                byte_end: 0,
+                iota_index: None,
+                doc_comment: None,
             })));
 
             // 添加变量
@@ -461,11 +541,20 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                     name: "int".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 }),
                 initial_value: Some("30".to_string()),
                 start_line: 10,
                 end_line: 10,
                 file_path: file_path.clone(),
+                byte_start: 0,
+                byte_end: 0,
+                doc_comment: None,
             })));
 
             // 添加 Config 类型
@@ -481,6 +570,13 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 .to_string(),
                 file_path: file_path.clone(),
                 dependencies: vec!["DatabaseConfig".to_string(), "FeatureFlags".to_string()],
+                start_line: 0,
+                end_line: 0,
+                byte_start: 0,
+                byte_end: 0,
+                type_parameters: Vec::new(),
+                fields: Vec::new(),
+                doc_comment: None,
             })));
 
             // 添加 DatabaseConfig 类型
@@ -498,6 +594,13 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 .to_string(),
                 file_path: file_path.clone(),
                 dependencies: vec![],
+                start_line: 0,
+                end_line: 0,
+                byte_start: 0,
+                byte_end: 0,
+                type_parameters: Vec::new(),
+                fields: Vec::new(),
+                doc_comment: None,
             })));
 
             // 添加 FeatureFlags 类型
@@ -512,19 +615,33 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 .to_string(),
                 file_path: file_path.clone(),
                 dependencies: vec![],
+                start_line: 0,
+                end_line: 0,
+                byte_start: 0,
+                byte_end: 0,
+                type_parameters: Vec::new(),
+                fields: Vec::new(),
+                doc_comment: None,
             })));
 
             // 添加 NewDefaultConfig 函数
-            go_info.add_declaration(Box::new(GoDeclaration::Function(GoFunctionInfo {
-                name: "NewDefaultConfig".to_string(),
-                receiver: None,
-                parameters: vec![],
-                return_types: vec![GoType {
-                    name: "Config".to_string(),
-                    is_pointer: true,
-                    is_slice: false,
-                }],
-                body: r#"return &Config{
+            go_info.add_declaration(Box::new(GoDeclaration::Function(with_body_references(
+                GoFunctionInfo {
+                    name: "NewDefaultConfig".to_string(),
+                    receiver: None,
+                    parameters: vec![],
+                    return_types: vec![GoType {
+                        name: "Config".to_string(),
+                        is_pointer: true,
+                        is_slice: false,
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
+                    }],
+                    body: r#"return &Config{
         Host: DefaultHost,
         Port: DefaultPort,
         Database: DatabaseConfig{
@@ -538,11 +655,17 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
             EnableTracing: DebugMode,
         },
     }"#
-                .to_string(),
-                start_line: 40,
-                end_line: 55,
-                file_path: file_path.clone(),
-            })));
+                    .to_string(),
+                    start_line: 40,
+                    end_line: 55,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
         }
 
         if filename == "services/user_service.go" {
@@ -558,6 +681,13 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 .to_string(),
                 file_path: file_path.clone(),
                 dependencies: vec!["Config".to_string(), "User".to_string()],
+                start_line: 0,
+                end_line: 0,
+                byte_start: 0,
+                byte_end: 0,
+                type_parameters: Vec::new(),
+                fields: Vec::new(),
+                doc_comment: None,
             })));
 
             // 添加 UserRepository 接口
@@ -572,68 +702,116 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                 .to_string(),
                 file_path: file_path.clone(),
                 dependencies: vec!["User".to_string()],
+                start_line: 0,
+                end_line: 0,
+                byte_start: 0,
+                byte_end: 0,
+                type_parameters: Vec::new(),
+                fields: Vec::new(),
+                doc_comment: None,
             })));
 
             // 添加 NewUserService 函数
-            go_info.add_declaration(Box::new(GoDeclaration::Function(GoFunctionInfo {
-                name: "NewUserService".to_string(),
-                receiver: None,
-                parameters: vec![GoParameter {
-                    name: "config".to_string(),
-                    param_type: GoType {
-                        name: "Config".to_string(),
+            go_info.add_declaration(Box::new(GoDeclaration::Function(with_body_references(
+                GoFunctionInfo {
+                    name: "NewUserService".to_string(),
+                    receiver: None,
+                    parameters: vec![GoParameter {
+                        name: "config".to_string(),
+                        param_type: GoType {
+                            name: "Config".to_string(),
+                            is_pointer: true,
+                            is_slice: false,
+                            result_name: None,
+                            is_channel: false,
+                            channel_direction: None,
+                            key_type: None,
+                            value_type: None,
+                            array_len: None,
+                        },
+                        is_variadic: false,
+                    }],
+                    return_types: vec![GoType {
+                        name: "UserService".to_string(),
                         is_pointer: true,
                         is_slice: false,
-                    },
-                }],
-                return_types: vec![GoType {
-                    name: "UserService".to_string(),
-                    is_pointer: true,
-                    is_slice: false,
-                }],
-                body: r#"return &UserService{
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
+                    }],
+                    body: r#"return &UserService{
         config: config,
         users:  make(map[int]*models.User),
         nextID: 1,
     }"#
-                .to_string(),
-                start_line: 20,
-                end_line: 26,
-                file_path: file_path.clone(),
-            })));
+                    .to_string(),
+                    start_line: 20,
+                    end_line: 26,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
 
             // 添加 CreateUser 方法
-            go_info.add_declaration(Box::new(GoDeclaration::Method(GoFunctionInfo {
-                name: "CreateUser".to_string(),
-                receiver: Some(GoReceiverInfo {
-                    name: "s".to_string(),
-                    type_name: "UserService".to_string(),
-                    is_pointer: true,
-                }),
-                parameters: vec![
-                    GoParameter {
-                        name: "name".to_string(),
-                        param_type: GoType {
-                            name: "string".to_string(),
-                            is_pointer: false,
-                            is_slice: false,
+            go_info.add_declaration(Box::new(GoDeclaration::Method(with_body_references(
+                GoFunctionInfo {
+                    name: "CreateUser".to_string(),
+                    receiver: Some(GoReceiverInfo {
+                        name: "s".to_string(),
+                        type_name: "UserService".to_string(),
+                        is_pointer: true,
+                    }),
+                    parameters: vec![
+                        GoParameter {
+                            name: "name".to_string(),
+                            param_type: GoType {
+                                name: "string".to_string(),
+                                is_pointer: false,
+                                is_slice: false,
+                                result_name: None,
+                                is_channel: false,
+                                channel_direction: None,
+                                key_type: None,
+                                value_type: None,
+                                array_len: None,
+                            },
+                            is_variadic: false,
                         },
-                    },
-                    GoParameter {
-                        name: "age".to_string(),
-                        param_type: GoType {
-                            name: "int".to_string(),
-                            is_pointer: false,
-                            is_slice: false,
+                        GoParameter {
+                            name: "age".to_string(),
+                            param_type: GoType {
+                                name: "int".to_string(),
+                                is_pointer: false,
+                                is_slice: false,
+                                result_name: None,
+                                is_channel: false,
+                                channel_direction: None,
+                                key_type: None,
+                                value_type: None,
+                                array_len: None,
+                            },
+                            is_variadic: false,
                         },
-                    },
-                ],
-                return_types: vec![GoType {
-                    name: "User".to_string(),
-                    is_pointer: true,
-                    is_slice: false,
-                }],
-                body: r#"user := &models.User{
+                    ],
+                    return_types: vec![GoType {
+                        name: "User".to_string(),
+                        is_pointer: true,
+                        is_slice: false,
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
+                    }],
+                    body: r#"user := &models.User{
         ID:      s.nextID,
         Name:    name,
         Age:     age,
@@ -648,76 +826,123 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
     }
     
     return user"#
-                    .to_string(),
-                start_line: 28,
-                end_line: 45,
-                file_path: file_path.clone(),
-            })));
+                        .to_string(),
+                    start_line: 28,
+                    end_line: 45,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
 
             // 添加 UpdateUserProfile 方法
-            go_info.add_declaration(Box::new(GoDeclaration::Method(GoFunctionInfo {
-                name: "UpdateUserProfile".to_string(),
-                receiver: Some(GoReceiverInfo {
-                    name: "s".to_string(),
-                    type_name: "UserService".to_string(),
-                    is_pointer: true,
-                }),
-                parameters: vec![
-                    GoParameter {
-                        name: "userID".to_string(),
-                        param_type: GoType {
-                            name: "int".to_string(),
-                            is_pointer: false,
-                            is_slice: false,
+            go_info.add_declaration(Box::new(GoDeclaration::Method(with_body_references(
+                GoFunctionInfo {
+                    name: "UpdateUserProfile".to_string(),
+                    receiver: Some(GoReceiverInfo {
+                        name: "s".to_string(),
+                        type_name: "UserService".to_string(),
+                        is_pointer: true,
+                    }),
+                    parameters: vec![
+                        GoParameter {
+                            name: "userID".to_string(),
+                            param_type: GoType {
+                                name: "int".to_string(),
+                                is_pointer: false,
+                                is_slice: false,
+                                result_name: None,
+                                is_channel: false,
+                                channel_direction: None,
+                                key_type: None,
+                                value_type: None,
+                                array_len: None,
+                            },
+                            is_variadic: false,
                         },
-                    },
-                    GoParameter {
-                        name: "profile".to_string(),
-                        param_type: GoType {
-                            name: "Profile".to_string(),
-                            is_pointer: true,
-                            is_slice: false,
+                        GoParameter {
+                            name: "profile".to_string(),
+                            param_type: GoType {
+                                name: "Profile".to_string(),
+                                is_pointer: true,
+                                is_slice: false,
+                                result_name: None,
+                                is_channel: false,
+                                channel_direction: None,
+                                key_type: None,
+                                value_type: None,
+                                array_len: None,
+                            },
+                            is_variadic: false,
                         },
-                    },
-                ],
-                return_types: vec![GoType {
-                    name: "error".to_string(),
-                    is_pointer: false,
-                    is_slice: false,
-                }],
-                body: r#"user, err := s.GetUser(userID)
+                    ],
+                    return_types: vec![GoType {
+                        name: "error".to_string(),
+                        is_pointer: false,
+                        is_slice: false,
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
+                    }],
+                    body: r#"user, err := s.GetUser(userID)
     if err != nil {
         return err
     }
     
     user.UpdateProfile(profile)
     return nil"#
-                    .to_string(),
-                start_line: 55,
-                end_line: 63,
-                file_path: file_path.clone(),
-            })));
+                        .to_string(),
+                    start_line: 55,
+                    end_line: 63,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
         }
 
         if filename == "services/config_service.go" {
             // 添加 ValidateConfig 函数
-            go_info.add_declaration(Box::new(GoDeclaration::Function(GoFunctionInfo {
-                name: "ValidateConfig".to_string(),
-                receiver: None,
-                parameters: vec![GoParameter {
-                    name: "config".to_string(),
-                    param_type: GoType {
-                        name: "Config".to_string(),
-                        is_pointer: true,
+            go_info.add_declaration(Box::new(GoDeclaration::Function(with_body_references(
+                GoFunctionInfo {
+                    name: "ValidateConfig".to_string(),
+                    receiver: None,
+                    parameters: vec![GoParameter {
+                        name: "config".to_string(),
+                        param_type: GoType {
+                            name: "Config".to_string(),
+                            is_pointer: true,
+                            is_slice: false,
+                            result_name: None,
+                            is_channel: false,
+                            channel_direction: None,
+                            key_type: None,
+                            value_type: None,
+                            array_len: None,
+                        },
+                        is_variadic: false,
+                    }],
+                    return_types: vec![GoType {
+                        name: "error".to_string(),
+                        is_pointer: false,
                         is_slice: false,
-                    },
-                }],
-                return_types: vec![GoType {
-                    name: "error".to_string(),
-                    is_pointer: false,
-                    is_slice: false,
-                }],
-                body: r#"if config == nil {
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
+                    }],
+                    body: r#"if config == nil {
         return errors.New("config cannot be nil")
     }
     
@@ -730,11 +955,17 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
     }
     
     return validateDatabaseConfig(&config.Database)"#
-                    .to_string(),
-                start_line: 8,
-                end_line: 21,
-                file_path: file_path.clone(),
-            })));
+                        .to_string(),
+                    start_line: 8,
+                    end_line: 21,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
         }
 
         if filename == "main.go" {
@@ -745,20 +976,30 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
                     name: "Config".to_string(),
                     is_pointer: true,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 }),
                 initial_value: None,
                 start_line: 8,
                 end_line: 8,
                 file_path: file_path.clone(),
+                byte_start: 0,
+                byte_end: 0,
+                doc_comment: None,
             })));
 
             // 添加 main 函数
-            go_info.add_declaration(Box::new(GoDeclaration::Function(GoFunctionInfo {
-                name: "main".to_string(),
-                receiver: None,
-                parameters: vec![],
-                return_types: vec![],
-                body: r#"GlobalConfig = &models.Config{
+            go_info.add_declaration(Box::new(GoDeclaration::Function(with_body_references(
+                GoFunctionInfo {
+                    name: "main".to_string(),
+                    receiver: None,
+                    parameters: vec![],
+                    return_types: vec![],
+                    body: r#"GlobalConfig = &models.Config{
         Host: "localhost",
         Port: 8080,
     }
@@ -766,36 +1007,62 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
     userService := services.NewUserService(GlobalConfig)
     user := userService.CreateUser("Alice", 25)
     fmt.Printf("Created user: %+v\n", user)"#
-                    .to_string(),
-                start_line: 10,
-                end_line: 18,
-                file_path: file_path.clone(),
-            })));
+                        .to_string(),
+                    start_line: 10,
+                    end_line: 18,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
 
             // 添加 InitializeSystem 函数
-            go_info.add_declaration(Box::new(GoDeclaration::Function(GoFunctionInfo {
-                name: "InitializeSystem".to_string(),
-                receiver: None,
-                parameters: vec![GoParameter {
-                    name: "config".to_string(),
-                    param_type: GoType {
-                        name: "Config".to_string(),
-                        is_pointer: true,
+            go_info.add_declaration(Box::new(GoDeclaration::Function(with_body_references(
+                GoFunctionInfo {
+                    name: "InitializeSystem".to_string(),
+                    receiver: None,
+                    parameters: vec![GoParameter {
+                        name: "config".to_string(),
+                        param_type: GoType {
+                            name: "Config".to_string(),
+                            is_pointer: true,
+                            is_slice: false,
+                            result_name: None,
+                            is_channel: false,
+                            channel_direction: None,
+                            key_type: None,
+                            value_type: None,
+                            array_len: None,
+                        },
+                        is_variadic: false,
+                    }],
+                    return_types: vec![GoType {
+                        name: "error".to_string(),
+                        is_pointer: false,
                         is_slice: false,
-                    },
-                }],
-                return_types: vec![GoType {
-                    name: "error".to_string(),
-                    is_pointer: false,
-                    is_slice: false,
-                }],
-                body: r#"GlobalConfig = config
+                        result_name: None,
+                        is_channel: false,
+                        channel_direction: None,
+                        key_type: None,
+                        value_type: None,
+                        array_len: None,
+                    }],
+                    body: r#"GlobalConfig = config
     return services.ValidateConfig(config)"#
-                    .to_string(),
-                start_line: 20,
-                end_line: 23,
-                file_path: file_path.clone(),
-            })));
+                        .to_string(),
+                    start_line: 20,
+                    end_line: 23,
+                    file_path: file_path.clone(),
+                    body_references: Default::default(),
+                    byte_start: 0,
+                    byte_end: 0,
+                    type_parameters: Vec::new(),
+                    doc_comment: None,
+                },
+            ))));
         }
 
         let source_file = SourceFile {
@@ -804,6 +1071,7 @@ func LoadConfigFromFile(filename string) (*models.Config, error) {
             syntax_tree,
             language: SupportedLanguage::Go,
             language_specific: Box::new(go_info),
+            build_constraint: None,
         };
 
         source_files.push(source_file);
@@ -819,7 +1087,7 @@ fn test_function_context_extraction_complex() {
         .expect("Failed to create extractor");
 
     // 测试提取 CreateUser 函数的上下文
-    let create_user_func = GoFunctionInfo {
+    let create_user_func = with_body_references(GoFunctionInfo {
         name: "CreateUser".to_string(),
         receiver: Some(semantic_diff_core::parser::GoReceiverInfo {
             name: "s".to_string(),
@@ -833,7 +1101,14 @@ fn test_function_context_extraction_complex() {
                     name: "string".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
             GoParameter {
                 name: "age".to_string(),
@@ -841,13 +1116,26 @@ fn test_function_context_extraction_complex() {
                     name: "int".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
         ],
         return_types: vec![GoType {
             name: "User".to_string(),
             is_pointer: true,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: r#"user := &models.User{
         ID:      s.nextID,
@@ -868,7 +1156,12 @@ fn test_function_context_extraction_complex() {
         start_line: 25,
         end_line: 40,
         file_path: PathBuf::from("services/user_service.go"),
-    };
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
+    });
 
     let context = extractor
         .extract_context(&create_user_func, &source_files)
@@ -915,6 +1208,13 @@ fn test_type_context_extraction() {
         .to_string(),
         file_path: PathBuf::from("models/user.go"),
         dependencies: vec!["Profile".to_string()],
+        start_line: 0,
+        end_line: 0,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
     };
 
     let change_target = ChangeTarget::Type(user_type);
@@ -944,6 +1244,55 @@ fn test_type_context_extraction() {
     println!("Functions using User type: {function_names:?}");
 }
 
+#[test]
+fn test_package_context_extraction() {
+    let (_temp_dir, source_files) = create_test_go_project();
+    let extractor = SemanticContextExtractor::from_project_root(_temp_dir.path())
+        .expect("Failed to create extractor");
+
+    let change_target = ChangeTarget::Package {
+        name: "models".to_string(),
+        files: Vec::new(),
+    };
+    let context = extractor
+        .extract_context_for_target(change_target, &source_files)
+        .expect("Failed to extract package context");
+
+    assert_eq!(context.change_target.change_type(), ChangeType::Package);
+    match &context.change_target {
+        ChangeTarget::Package { name, files } => {
+            assert_eq!(name, "models");
+            assert_eq!(files.len(), 2, "models/user.go and models/config.go");
+        }
+        other => panic!("Expected package target, got {other:?}"),
+    }
+
+    // 包内的导出类型和它们依赖的类型都只出现一次
+    for type_name in [
+        "User",
+        "Profile",
+        "Address",
+        "Config",
+        "DatabaseConfig",
+        "FeatureFlags",
+    ] {
+        let count = context
+            .related_types
+            .iter()
+            .filter(|t| t.name == type_name)
+            .count();
+        assert_eq!(count, 1, "{type_name} should appear exactly once");
+    }
+
+    // 其他包的声明不属于包上下文
+    assert!(
+        !context
+            .related_types
+            .iter()
+            .any(|t| t.name == "UserService")
+    );
+}
+
 #[test]
 fn test_global_variable_context_extraction() {
     let (_temp_dir, source_files) = create_test_go_project();
@@ -957,11 +1306,20 @@ fn test_global_variable_context_extraction() {
             name: "Config".to_string(),
             is_pointer: true,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }),
         initial_value: None,
         start_line: 8,
         end_line: 8,
         file_path: PathBuf::from("main.go"),
+        byte_start: 0,
+        byte_end: 0,
+        doc_comment: None,
     };
 
     let change_target = ChangeTarget::Variable(global_config_var);
@@ -1003,10 +1361,20 @@ fn test_constant_context_extraction() {
             name: "string".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }),
         start_line: 4,
         end_line: 4,
         file_path: PathBuf::from("models/config.go"),
+        byte_start: 0,
+        byte_end: 0,
+        iota_index: None,
+        doc_comment: None,
     };
 
     let change_target = ChangeTarget::Constant(default_host_const);
@@ -1039,7 +1407,7 @@ fn test_cross_module_dependency_analysis() {
         .expect("Failed to create extractor");
 
     // 测试 main 包中的函数，它依赖多个模块
-    let main_func = GoFunctionInfo {
+    let main_func = with_body_references(GoFunctionInfo {
         name: "main".to_string(),
         receiver: None,
         parameters: vec![],
@@ -1056,7 +1424,12 @@ fn test_cross_module_dependency_analysis() {
         start_line: 10,
         end_line: 18,
         file_path: PathBuf::from("main.go"),
-    };
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
+    });
 
     let context = extractor
         .extract_context(&main_func, &source_files)
@@ -1107,6 +1480,13 @@ fn test_interface_dependency_extraction() {
         .to_string(),
         file_path: PathBuf::from("services/user_service.go"),
         dependencies: vec!["User".to_string()],
+        start_line: 0,
+        end_line: 0,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
     };
 
     let change_target = ChangeTarget::Type(user_repository_interface);
@@ -1148,6 +1528,13 @@ fn test_recursive_type_dependency_extraction() {
         .to_string(),
         file_path: PathBuf::from("models/user.go"),
         dependencies: vec!["Address".to_string()],
+        start_line: 0,
+        end_line: 0,
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        fields: Vec::new(),
+        doc_comment: None,
     };
 
     let change_target = ChangeTarget::Type(profile_type);
@@ -1176,7 +1563,7 @@ fn test_complex_function_with_multiple_dependencies() {
         .expect("Failed to create extractor");
 
     // 测试复杂函数 UpdateUserProfile，它涉及多个类型和函数调用
-    let update_profile_func = GoFunctionInfo {
+    let update_profile_func = with_body_references(GoFunctionInfo {
         name: "UpdateUserProfile".to_string(),
         receiver: Some(semantic_diff_core::parser::GoReceiverInfo {
             name: "s".to_string(),
@@ -1190,7 +1577,14 @@ fn test_complex_function_with_multiple_dependencies() {
                     name: "int".to_string(),
                     is_pointer: false,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
             GoParameter {
                 name: "profile".to_string(),
@@ -1198,13 +1592,26 @@ fn test_complex_function_with_multiple_dependencies() {
                     name: "Profile".to_string(),
                     is_pointer: true,
                     is_slice: false,
+                    result_name: None,
+                    is_channel: false,
+                    channel_direction: None,
+                    key_type: None,
+                    value_type: None,
+                    array_len: None,
                 },
+                is_variadic: false,
             },
         ],
         return_types: vec![GoType {
             name: "error".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: r#"user, err := s.GetUser(userID)
     if err != nil {
@@ -1217,7 +1624,12 @@ fn test_complex_function_with_multiple_dependencies() {
         start_line: 55,
         end_line: 63,
         file_path: PathBuf::from("services/user_service.go"),
-    };
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
+    });
 
     let context = extractor
         .extract_context(&update_profile_func, &source_files)
@@ -1256,7 +1668,7 @@ fn test_context_validation_and_completeness() {
         .expect("Failed to create extractor");
 
     // 测试一个简单函数的上下文完整性
-    let validate_config_func = GoFunctionInfo {
+    let validate_config_func = with_body_references(GoFunctionInfo {
         name: "ValidateConfig".to_string(),
         receiver: None,
         parameters: vec![GoParameter {
@@ -1265,12 +1677,25 @@ fn test_context_validation_and_completeness() {
                 name: "Config".to_string(),
                 is_pointer: true,
                 is_slice: false,
+                result_name: None,
+                is_channel: false,
+                channel_direction: None,
+                key_type: None,
+                value_type: None,
+                array_len: None,
             },
+            is_variadic: false,
         }],
         return_types: vec![GoType {
             name: "error".to_string(),
             is_pointer: false,
             is_slice: false,
+            result_name: None,
+            is_channel: false,
+            channel_direction: None,
+            key_type: None,
+            value_type: None,
+            array_len: None,
         }],
         body: r#"if config == nil {
         return errors.New("config cannot be nil")
@@ -1289,7 +1714,12 @@ fn test_context_validation_and_completeness() {
         start_line: 8,
         end_line: 21,
         file_path: PathBuf::from("services/config_service.go"),
-    };
+        body_references: Default::default(),
+        byte_start: 0,
+        byte_end: 0,
+        type_parameters: Vec::new(),
+        doc_comment: None,
+    });
 
     let context = extractor
         .extract_context(&validate_config_func, &source_files)